@@ -0,0 +1,78 @@
+# httm message catalog -- German (de)
+
+## Diagnostics
+
+diagnostic-error = Fehler
+diagnostic-warning = WARNUNG
+diagnostic-notice = HINWEIS
+
+## Interactive views
+
+mode-browse = Durchsuchen
+mode-select = Auswählen
+mode-restore = Wiederherstellen
+mode-prune = Bereinigen
+session-aborted = httm-Sitzung (Auswählen/Wiederherstellen/Bereinigen) wurde abgebrochen.  Beende.
+session-failed = httm-Sitzung (Auswählen/Wiederherstellen/Bereinigen) ist fehlgeschlagen.
+no-selection = Die erste ausgewählte Übereinstimmung konnte nicht ermittelt werden.
+
+## Consent
+
+consent-yes = JA
+consent-no = NEIN
+consent-prompt = Fortfahren? ({ $yes }/{ $no })
+
+## Restore
+
+label-source = Quelle
+label-target = Ziel
+label-live = aktuell
+label-ancestor = Vorfahr
+label-snapshot = Snapshot
+recovery-summary = httm Wiederherstellungsbericht
+restore-intro = httm wird eine Kopie aus dem Snapshot erstellen:
+restore-consent = Bevor httm eine Wiederherstellung durchführt, benötigt es Ihre Zustimmung.
+restore-done = httm hat aus dem Snapshot kopiert:
+restore-success = Wiederherstellung erfolgreich abgeschlossen.
+restore-declined = Benutzer hat die Wiederherstellung abgelehnt: { $path }
+restore-failed = httm-Wiederherstellung ist aus folgendem Grund fehlgeschlagen: { $reason }.
+restore-rollback = Versuche, auf den vorsorglich erstellten Snapshot zurückzusetzen.
+rollback-succeeded = Zurücksetzen erfolgreich.
+restore-queued = httm hat eine Kopie aus dem Snapshot eingereiht:
+restore-queued-job = Die Wiederherstellung läuft im Hintergrund als Auftrag { $id }.  Mit "httm --jobs" können Sie den Fortschritt prüfen, mit "httm --cancel-job={ $id }" den Auftrag abbrechen.
+live-version-changed = httm wird { $path } nicht überschreiben, da die aktuelle Datei seit der Auswahl der Snapshot-Version verändert wurde. Beende. Um die aktuelle Datei dennoch zu überschreiben, geben Sie OVERWRITE_CHANGED an.
+
+## Merge
+
+merge-intro = httm wird eine Drei-Wege-Zusammenführung durchführen und das Ergebnis in die aktuelle Datei schreiben:
+merge-consent = Bevor httm eine Zusammenführung durchführt, benötigt es Ihre Zustimmung.
+merge-done = httm hat aus dem Snapshot zusammengeführt:
+merge-clean = Zusammenführung erfolgreich abgeschlossen.
+merge-conflicts = Zusammenführung mit Konflikten abgeschlossen. Bitte lösen Sie die Konfliktmarkierungen in der aktuellen Datei auf.
+merge-declined = Benutzer hat die Zusammenführung abgelehnt: { $path }
+
+## Recover
+
+recover-pick-dir = Verzeichnis für die Suche nach gelöschten Dateien wählen: { $dir }
+recover-search-here = [ DIESES VERZEICHNIS DURCHSUCHEN ]
+recover-none = In { $dir } wurden keine gelöschten Dateien gefunden.
+recover-deleted-after = gelöscht nach { $after }
+recover-deleted-between = gelöscht zwischen { $after } und { $before }
+recover-pick-dest = Wohin soll httm die ausgewählten gelöschten Dateien wiederherstellen:
+recover-original-dir = URSPRÜNGLICHES VERZEICHNIS
+recover-custom-dir = ANDERES VERZEICHNIS
+recover-custom-prompt = Bitte geben Sie ein Zielverzeichnis ein (leer lassen, um zurückzugehen):
+recover-intro = httm wird { $count } gelöschte Datei/en aus dem Snapshot wiederherstellen:
+recover-consent = Bevor httm diese Dateien wiederherstellt, benötigt es Ihre Zustimmung.
+recover-declined = Benutzer hat die Wiederherstellung gelöschter Dateien abgelehnt: { $dir }
+recover-done = httm hat { $count } gelöschte Datei/en nach { $dir } wiederhergestellt.
+
+## Prune
+
+prune-intro = Der Benutzer hat angefordert, die Snapshots der folgenden Datei/en zu bereinigen:
+prune-snapshots = httm wird die folgenden Snapshots löschen:
+prune-consent = Bevor httm diese Snapshots löscht, benötigt es Ihre Zustimmung.
+prune-done = httm hat die Snapshots der folgenden Datei/en bereinigt:
+prune-done-snapshots = Durch Löschen der folgenden Snapshots:
+prune-success = Bereinigung erfolgreich abgeschlossen.
+prune-declined = Benutzer hat die Bereinigung abgelehnt.  Es wurden keine Dateien bereinigt.
@@ -0,0 +1,81 @@
+# httm message catalog -- English (en)
+#
+# This is the reference catalog.  Any message missing from another catalog falls back to the
+# message of the same id here.  Please keep the ids of every catalog in sync with this file.
+
+## Diagnostics
+
+diagnostic-error = Error
+diagnostic-warning = WARN
+diagnostic-notice = NOTICE
+
+## Interactive views
+
+mode-browse = Browse Mode
+mode-select = Select Mode
+mode-restore = Restore Mode
+mode-prune = Prune Mode
+session-aborted = httm select/restore/prune session was aborted.  Quitting.
+session-failed = httm select/restore/prune session failed.
+no-selection = Could not obtain the first match selected.
+
+## Consent
+
+consent-yes = YES
+consent-no = NO
+consent-prompt = Continue? ({ $yes }/{ $no })
+
+## Restore
+
+label-source = source
+label-target = target
+label-live = live
+label-ancestor = ancestor
+label-snapshot = snapshot
+recovery-summary = httm recovery summary
+restore-intro = httm will perform a copy from snapshot:
+restore-consent = Before httm performs a restore, it would like your consent.
+restore-done = httm copied from snapshot:
+restore-success = Restore completed successfully.
+restore-declined = User declined restore of: { $path }
+restore-failed = httm restore failed for the following reason: { $reason }.
+restore-rollback = Attempting roll back to precautionary pre-execution snapshot.
+rollback-succeeded = Rollback succeeded.
+restore-queued = httm queued a copy from snapshot:
+restore-queued-job = The restore will run in the background as job { $id }.  Use "httm --jobs" to check its progress, or "httm --cancel-job={ $id }" to cancel it.
+live-version-changed = httm will not overwrite { $path }, as the live file has been modified since the snapshot version was selected. Quitting. To overwrite the live file anyway, specify OVERWRITE_CHANGED.
+
+## Merge
+
+merge-intro = httm will perform a three-way merge, and write the result to the live file:
+merge-consent = Before httm performs a merge, it would like your consent.
+merge-done = httm merged from snapshot:
+merge-clean = Merge completed successfully.
+merge-conflicts = Merge completed with conflicts. Please resolve the conflict markers within the live file.
+merge-declined = User declined merge of: { $path }
+
+## Recover
+
+recover-pick-dir = Browsing for a directory to search for deleted files: { $dir }
+recover-search-here = [ SEARCH THIS DIRECTORY ]
+recover-none = No deleted files were found within { $dir }.
+recover-deleted-after = deleted after { $after }
+recover-deleted-between = deleted between { $after } and { $before }
+recover-pick-dest = Where would httm restore the deleted files selected:
+recover-original-dir = ORIGINAL DIRECTORY
+recover-custom-dir = CUSTOM DIRECTORY
+recover-custom-prompt = Please enter a custom restore directory (leave empty to go back):
+recover-intro = httm will restore { $count } deleted file/s from snapshot:
+recover-consent = Before httm restores these files, it would like your consent.
+recover-declined = User declined recovery of deleted files to: { $dir }
+recover-done = httm restored { $count } deleted file/s to { $dir }.
+
+## Prune
+
+prune-intro = User has requested snapshots related to the following file/s be pruned:
+prune-snapshots = httm will destroy the following snapshot/s:
+prune-consent = Before httm destroys these snapshot/s, it would like your consent.
+prune-done = httm pruned snapshots related to the following file/s:
+prune-done-snapshots = By destroying the following snapshot/s:
+prune-success = Prune completed successfully.
+prune-declined = User declined prune.  No files were pruned.
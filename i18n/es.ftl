@@ -0,0 +1,78 @@
+# httm message catalog -- Spanish (es)
+
+## Diagnostics
+
+diagnostic-error = Error
+diagnostic-warning = AVISO
+diagnostic-notice = NOTA
+
+## Interactive views
+
+mode-browse = Modo Explorar
+mode-select = Modo Seleccionar
+mode-restore = Modo Restaurar
+mode-prune = Modo Depurar
+session-aborted = La sesión de selección/restauración/depuración de httm fue cancelada.  Saliendo.
+session-failed = La sesión de selección/restauración/depuración de httm falló.
+no-selection = No se pudo obtener la primera coincidencia seleccionada.
+
+## Consent
+
+consent-yes = SÍ
+consent-no = NO
+consent-prompt = ¿Continuar? ({ $yes }/{ $no })
+
+## Restore
+
+label-source = origen
+label-target = destino
+label-live = actual
+label-ancestor = ancestro
+label-snapshot = instantánea
+recovery-summary = resumen de recuperación de httm
+restore-intro = httm realizará una copia desde la instantánea:
+restore-consent = Antes de realizar una restauración, httm necesita su consentimiento.
+restore-done = httm copió desde la instantánea:
+restore-success = Restauración completada con éxito.
+restore-declined = El usuario rechazó la restauración de: { $path }
+restore-failed = La restauración de httm falló por el siguiente motivo: { $reason }.
+restore-rollback = Intentando revertir a la instantánea preventiva previa a la ejecución.
+rollback-succeeded = Reversión completada con éxito.
+restore-queued = httm puso en cola una copia desde la instantánea:
+restore-queued-job = La restauración se ejecutará en segundo plano como la tarea { $id }.  Use "httm --jobs" para consultar su progreso, o "httm --cancel-job={ $id }" para cancelarla.
+live-version-changed = httm no sobrescribirá { $path }, ya que el archivo actual ha sido modificado desde que se seleccionó la versión de la instantánea. Saliendo. Para sobrescribir el archivo actual de todos modos, especifique OVERWRITE_CHANGED.
+
+## Merge
+
+merge-intro = httm realizará una fusión a tres bandas y escribirá el resultado en el archivo actual:
+merge-consent = Antes de realizar una fusión, httm necesita su consentimiento.
+merge-done = httm fusionó desde la instantánea:
+merge-clean = Fusión completada con éxito.
+merge-conflicts = Fusión completada con conflictos. Por favor, resuelva los marcadores de conflicto en el archivo actual.
+merge-declined = El usuario rechazó la fusión de: { $path }
+
+## Recover
+
+recover-pick-dir = Elija un directorio donde buscar archivos eliminados: { $dir }
+recover-search-here = [ BUSCAR EN ESTE DIRECTORIO ]
+recover-none = No se encontraron archivos eliminados en { $dir }.
+recover-deleted-after = eliminado después de { $after }
+recover-deleted-between = eliminado entre { $after } y { $before }
+recover-pick-dest = Dónde debe httm restaurar los archivos eliminados seleccionados:
+recover-original-dir = DIRECTORIO ORIGINAL
+recover-custom-dir = OTRO DIRECTORIO
+recover-custom-prompt = Introduzca un directorio de restauración (déjelo vacío para volver):
+recover-intro = httm restaurará { $count } archivo(s) eliminado(s) desde la instantánea:
+recover-consent = Antes de restaurar estos archivos, httm necesita su consentimiento.
+recover-declined = El usuario rechazó la recuperación de archivos eliminados en: { $dir }
+recover-done = httm restauró { $count } archivo(s) eliminado(s) en { $dir }.
+
+## Prune
+
+prune-intro = El usuario ha solicitado depurar las instantáneas relacionadas con los siguientes archivos:
+prune-snapshots = httm destruirá las siguientes instantáneas:
+prune-consent = Antes de destruir estas instantáneas, httm necesita su consentimiento.
+prune-done = httm depuró las instantáneas relacionadas con los siguientes archivos:
+prune-done-snapshots = Destruyendo las siguientes instantáneas:
+prune-success = Depuración completada con éxito.
+prune-declined = El usuario rechazó la depuración.  No se depuró ningún archivo.
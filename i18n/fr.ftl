@@ -0,0 +1,78 @@
+# httm message catalog -- French (fr)
+
+## Diagnostics
+
+diagnostic-error = Erreur
+diagnostic-warning = AVERTISSEMENT
+diagnostic-notice = REMARQUE
+
+## Interactive views
+
+mode-browse = Mode Parcourir
+mode-select = Mode Sélection
+mode-restore = Mode Restauration
+mode-prune = Mode Élagage
+session-aborted = La session de sélection/restauration/élagage httm a été interrompue.  Fin.
+session-failed = La session de sélection/restauration/élagage httm a échoué.
+no-selection = Impossible d'obtenir la première correspondance sélectionnée.
+
+## Consent
+
+consent-yes = OUI
+consent-no = NON
+consent-prompt = Continuer ? ({ $yes }/{ $no })
+
+## Restore
+
+label-source = source
+label-target = cible
+label-live = actuel
+label-ancestor = ancêtre
+label-snapshot = instantané
+recovery-summary = bilan de récupération httm
+restore-intro = httm va effectuer une copie depuis l'instantané :
+restore-consent = Avant d'effectuer une restauration, httm souhaite obtenir votre consentement.
+restore-done = httm a copié depuis l'instantané :
+restore-success = Restauration terminée avec succès.
+restore-declined = L'utilisateur a refusé la restauration de : { $path }
+restore-failed = La restauration httm a échoué pour la raison suivante : { $reason }.
+restore-rollback = Tentative de retour à l'instantané de précaution pris avant l'exécution.
+rollback-succeeded = Retour arrière réussi.
+restore-queued = httm a mis en file d'attente une copie depuis l'instantané :
+restore-queued-job = La restauration s'exécutera en arrière-plan en tant que tâche { $id }.  Utilisez "httm --jobs" pour suivre sa progression, ou "httm --cancel-job={ $id }" pour l'annuler.
+live-version-changed = httm n'écrasera pas { $path }, car le fichier actuel a été modifié depuis la sélection de la version de l'instantané. Fin. Pour écraser le fichier actuel malgré tout, spécifiez OVERWRITE_CHANGED.
+
+## Merge
+
+merge-intro = httm va effectuer une fusion à trois voies, et écrire le résultat dans le fichier actuel :
+merge-consent = Avant d'effectuer une fusion, httm souhaite obtenir votre consentement.
+merge-done = httm a fusionné depuis l'instantané :
+merge-clean = Fusion terminée avec succès.
+merge-conflicts = Fusion terminée avec des conflits. Veuillez résoudre les marqueurs de conflit dans le fichier actuel.
+merge-declined = L'utilisateur a refusé la fusion de : { $path }
+
+## Recover
+
+recover-pick-dir = Choisissez un répertoire où rechercher des fichiers supprimés : { $dir }
+recover-search-here = [ RECHERCHER DANS CE RÉPERTOIRE ]
+recover-none = Aucun fichier supprimé n'a été trouvé dans { $dir }.
+recover-deleted-after = supprimé après { $after }
+recover-deleted-between = supprimé entre { $after } et { $before }
+recover-pick-dest = Où httm doit-il restaurer les fichiers supprimés sélectionnés :
+recover-original-dir = RÉPERTOIRE D'ORIGINE
+recover-custom-dir = AUTRE RÉPERTOIRE
+recover-custom-prompt = Veuillez saisir un répertoire de restauration (laisser vide pour revenir) :
+recover-intro = httm va restaurer { $count } fichier(s) supprimé(s) depuis l'instantané :
+recover-consent = Avant de restaurer ces fichiers, httm souhaite obtenir votre consentement.
+recover-declined = L'utilisateur a refusé la restauration des fichiers supprimés vers : { $dir }
+recover-done = httm a restauré { $count } fichier(s) supprimé(s) dans { $dir }.
+
+## Prune
+
+prune-intro = L'utilisateur a demandé la purge des instantanés liés au(x) fichier(s) suivant(s) :
+prune-snapshots = httm va détruire le(s) instantané(s) suivant(s) :
+prune-consent = Avant de détruire ces instantanés, httm souhaite obtenir votre consentement.
+prune-done = httm a purgé les instantanés liés au(x) fichier(s) suivant(s) :
+prune-done-snapshots = En détruisant le(s) instantané(s) suivant(s) :
+prune-success = Purge terminée avec succès.
+prune-declined = L'utilisateur a refusé la purge.  Aucun fichier n'a été purgé.
@@ -0,0 +1,111 @@
+#!/usr/bin/env bash
+
+#       ___           ___           ___           ___
+#      /\__\         /\  \         /\  \         /\__\
+#     /:/  /         \:\  \        \:\  \       /::|  |
+#    /:/__/           \:\  \        \:\  \     /:|:|  |
+#   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+#  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+#  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+#       \::/  /    /:/  /        /:/  /            /:/  /
+#       /:/  /     \/__/         \/__/            /:/  /
+#      /:/  /                                    /:/  /
+#      \/__/                                     \/__/
+#
+# Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+#
+# For the full copyright and license information, please view the LICENSE file
+# that was distributed with this source code.
+
+## Note: this is a developer tool, and is not installed with httm ##
+
+set -euf -o pipefail
+#set -x
+
+function print_usage {
+	local bench="\e[31mbench-versions\e[0m"
+
+	printf "\
+$bench times a version search of the same paths by two httm binaries, and fails if their results differ.
+
+Version searches are most unbalanced when one path has thousands of snapshots and the rest only a few,
+so give at least one path with many snapshots, and several with few, like:
+
+	bench-versions -b ~/httm-baseline -- /var/log/syslog ~/notes.txt ~/todo.txt
+
+USAGE:
+	bench-versions [OPTIONS] -- [path1 path2...]
+
+OPTIONS:
+	-b BASELINE:	httm binary to compare against (default: httm in your path)
+	-c CANDIDATE:	httm binary to benchmark (default: ./target/release/httm)
+	-n RUNS:	number of timed runs of each binary (default: 5)
+	-h:		print this help
+
+" 1>&2
+	exit 1
+}
+
+function print_err_exit {
+	printf "%s\n" "ERROR: $*" 1>&2
+	exit 1
+}
+
+# prints the median and the minimum wall time, in milliseconds, of RUNS searches
+function time_runs {
+	local httm_bin="$1"
+	local runs="$2"
+	shift 2
+
+	local -a times=()
+
+	for ((i = 0; i < runs; i++)); do
+		local start
+		start="$(date +%s%N)"
+		"$httm_bin" --raw --uniqueness=all -- "$@" >/dev/null
+		local end
+		end="$(date +%s%N)"
+		times+=("$(((end - start) / 1000000))")
+	done
+
+	local -a sorted=()
+	mapfile -t sorted < <(printf "%s\n" "${times[@]}" | sort -n)
+
+	printf "median: %sms, min: %sms\n" "${sorted[$((runs / 2))]}" "${sorted[0]}"
+}
+
+function bench_versions {
+	local baseline
+	baseline="$(command -v httm || true)"
+	local candidate="./target/release/httm"
+	local runs=5
+
+	while getopts "b:c:n:h" opt; do
+		case "$opt" in
+		b) baseline="$OPTARG" ;;
+		c) candidate="$OPTARG" ;;
+		n) runs="$OPTARG" ;;
+		*) print_usage ;;
+		esac
+	done
+
+	shift $((OPTIND - 1))
+	[[ "${1:-}" != "--" ]] || shift
+
+	[[ $# -gt 0 ]] || print_usage
+	[[ -x "$baseline" ]] || print_err_exit "Baseline httm binary is not executable: \"$baseline\""
+	[[ -x "$candidate" ]] || print_err_exit "Candidate httm binary is not executable: \"$candidate\""
+	[[ "$runs" -gt 0 ]] || print_err_exit "RUNS must be greater than zero"
+
+	# the regression check: both must find exactly the same versions
+	diff <("$baseline" --raw --uniqueness=all -- "$@" | sort) <("$candidate" --raw --uniqueness=all -- "$@" | sort) >/dev/null ||
+		print_err_exit "The versions found by \"$candidate\" differ from those found by \"$baseline\""
+
+	# warm the page cache, so neither binary is favored by running second
+	"$baseline" --raw --uniqueness=all -- "$@" >/dev/null
+
+	printf "baseline:  %s\n" "$(time_runs "$baseline" "$runs" "$@")"
+	printf "candidate: %s\n" "$(time_runs "$candidate" "$runs" "$@")"
+}
+
+bench_versions "$@"
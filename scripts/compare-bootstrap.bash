@@ -0,0 +1,121 @@
+#!/usr/bin/env bash
+
+#       ___           ___           ___           ___
+#      /\__\         /\  \         /\  \         /\__\
+#     /:/  /         \:\  \        \:\  \       /::|  |
+#    /:/__/           \:\  \        \:\  \     /:|:|  |
+#   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+#  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+#  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+#       \::/  /    /:/  /        /:/  /            /:/  /
+#       /:/  /     \/__/         \/__/            /:/  /
+#      /:/  /                                    /:/  /
+#      \/__/                                     \/__/
+#
+# Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+#
+# For the full copyright and license information, please view the LICENSE file
+# that was distributed with this source code.
+
+set -euf -o pipefail
+#set -x
+
+print_err_exit() {
+	print_err "$@"
+	pause
+	exit 1
+}
+
+print_err() {
+	printf "%s\n" "ERROR: $*" 1>&2
+}
+
+pause() {
+	printf "%s" "Press enter to return to httm..."
+	read -r _ </dev/tty || true
+}
+
+prep_exec() {
+	[[ -n "$(
+		command -v cut
+		exit 0
+	)" ]] || print_err_exit "'cut' is required to compare versions.  Please check that 'cut' is in your path."
+}
+
+selected_file() {
+	local raw_input="$1"
+	local snap_file=""
+
+	[[ -n $raw_input ]] || print_err_exit "Selection is empty."
+
+	snap_file="$(echo ${raw_input} | cut -d'"' -f2)"
+
+	[[ -n "$snap_file" ]] || print_err_exit "Snap file path is empty."
+	[[ -e "$snap_file" ]] || [[ -L "$snap_file" ]] || print_err_exit "Selection does not refer to a valid file, link or directory."
+
+	printf "%s" "$snap_file"
+}
+
+pin_base() {
+	local snap_file=""
+
+	snap_file="$(selected_file "$1")"
+
+	printf "%s" "$snap_file" >"{base_file}"
+}
+
+compare_with_base() {
+	local base_file=""
+	local compare_file=""
+	local width=""
+
+	[[ -s "{base_file}" ]] || print_err_exit "No base version has been pinned.  Pin a base version with alt-p, and then compare another version with alt-c."
+
+	base_file="$(cat "{base_file}")"
+	compare_file="$(selected_file "$1")"
+
+	# a user's diff tool is trusted to present its own view
+	if [[ -n "${DIFF_TOOL:-}" ]]; then
+		${DIFF_TOOL} "$base_file" "$compare_file" || true
+		return 0
+	fi
+
+	[[ -n "$(
+		command -v diff
+		exit 0
+	)" ]] || print_err_exit "'diff' is required to compare versions, when DIFF_TOOL is not set.  Please check that 'diff' is in your path."
+
+	width="$(tput cols 2>/dev/null || printf "%s" "160")"
+
+	if [[ -n "${PAGER:-}" ]]; then
+		side_by_side "$base_file" "$compare_file" "$width" | ${PAGER} || true
+	elif [[ -n "$(
+		command -v less
+		exit 0
+	)" ]]; then
+		side_by_side "$base_file" "$compare_file" "$width" | less -R || true
+	else
+		side_by_side "$base_file" "$compare_file" "$width"
+		pause
+	fi
+}
+
+side_by_side() {
+	printf "%s\n" "BASE:    $1"
+	printf "%s\n" "COMPARE: $2"
+	printf "%s\n" "──────────────────────────────────────────────────────────────────────────────"
+	# each version is transcoded to UTF-8 first, so versions in another text encoding, like UTF-16, are comparable
+	diff --side-by-side --width="$3" -- <({transcode}"$1") <({transcode}"$2") || true
+}
+
+bootstrap_compare() {
+	prep_exec
+
+	case "${1:-}" in
+	pin) pin_base "${2:-}" ;;
+	compare) compare_with_base "${2:-}" ;;
+	*) print_err_exit "Unknown compare action: ${1:-}" ;;
+	esac
+}
+
+bootstrap_compare "$@"
@@ -0,0 +1,39 @@
+#       ___           ___           ___           ___
+#      /\__\         /\  \         /\  \         /\__\
+#     /:/  /         \:\  \        \:\  \       /::|  |
+#    /:/__/           \:\  \        \:\  \     /:|:|  |
+#   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+#  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+#  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+#       \::/  /    /:/  /        /:/  /            /:/  /
+#       /:/  /     \/__/         \/__/            /:/  /
+#      /:/  /                                    /:/  /
+#      \/__/                                     \/__/
+#
+# Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+#
+# For the full copyright and license information, please view the LICENSE file
+# that was distributed with this source code.
+
+# bash key bindings for httm, source from your ~/.bashrc, like so:
+#
+# source /path/to/httm-key-bindings.bash
+
+# ALT-v - replace the path under the cursor with a snapshot version of that path
+__httm_version_widget() {
+  local lhs="${READLINE_LINE:0:$READLINE_POINT}"
+  local rhs="${READLINE_LINE:$READLINE_POINT}"
+  local word="${lhs##* }"
+  local input_path="${word/#\~/$HOME}"
+  local selected
+
+  selected="$(command httm --widget=bash -- "${input_path:-$PWD}" < /dev/tty)"
+
+  [[ -n "$selected" ]] || return 0
+
+  lhs="${lhs%"$word"}${selected}"
+  READLINE_LINE="${lhs}${rhs}"
+  READLINE_POINT=${#lhs}
+}
+
+bind -x '"\ev": __httm_version_widget'
@@ -50,11 +50,55 @@ prep_exec() {
 	)" ]] || print_err_exit "'cut' is required to execute 'httm --preview'.  Please check that 'cut' is in your path."
 }
 
+snapshot_name() {
+	local path="$1"
+
+	case "$path" in
+	*/.zfs/snapshot/*)
+		path="${path#*/.zfs/snapshot/}"
+		printf "%s" "${path%%/*}"
+		;;
+	*/.snapshots/*/snapshot/*)
+		path="${path#*/.snapshots/}"
+		printf "%s" "${path%%/*}"
+		;;
+	esac
+
+	return 0
+}
+
+derive_live_path() {
+	local path="$1"
+	local name="$2"
+
+	[[ -n "$name" ]] || return 0
+
+	case "$path" in
+	*/.zfs/snapshot/*) printf "%s" "${path/\/.zfs\/snapshot\/"$name"/}" ;;
+	*/.snapshots/*/snapshot/*) printf "%s" "${path/\/.snapshots\/"$name"\/snapshot/}" ;;
+	esac
+
+	return 0
+}
+
+# GNU stat first, then BSD stat
+file_mtime() {
+	stat -c '%y' "$1" 2>/dev/null || stat -f '%Sm' "$1" 2>/dev/null || printf "%s" "unknown"
+}
+
+file_size() {
+	stat -c '%s' "$1" 2>/dev/null || stat -f '%z' "$1" 2>/dev/null || printf "%s" "unknown"
+}
+
 bootstrap_preview() {
 	prep_exec
 
 	local raw_input=""
 	local snap_file=""
+	local snap_name=""
+	local live_path=""
+	local mtime=""
+	local size=""
 
 	raw_input={}
 
@@ -70,6 +114,12 @@ bootstrap_preview() {
 
 	[[ -f "$snap_file" ]] || [[ -d "$snap_file" ]] || [[ -L "$snap_file" ]] || print_warn_exit "Selection does not refer to a valid file, link or directory."
 
+	# version metadata available to preview command templates
+	snap_name="$(snapshot_name "$snap_file")"
+	live_path="$(derive_live_path "$snap_file" "$snap_name")"
+	mtime="$(file_mtime "$snap_file")"
+	size="$(file_size "$snap_file")"
+
 	exec 0<&-
 	{command} 2>&1
 }
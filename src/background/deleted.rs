@@ -67,12 +67,23 @@ impl SpawnDeletedThread {
         }
 
         // combined entries will be sent or printed, but we need the vec_dirs to recurse
-        let (vec_dirs, vec_files): (Vec<BasicDirEntryInfo>, Vec<BasicDirEntryInfo>) =
+        let (vec_dirs, mut vec_files): (Vec<BasicDirEntryInfo>, Vec<BasicDirEntryInfo>) =
             vec_deleted.into_iter().partition(|entry| {
                 // no need to traverse symlinks in deleted search
                 SharedRecursive::is_entry_dir(entry)
             });
 
+        // only files are filtered by size, dirs must still be traversed
+        if let Some(size_filter) = &GLOBAL_CONFIG.opt_size_filter {
+            vec_files.retain(|entry| {
+                entry
+                    .path
+                    .symlink_metadata()
+                    .map(|md| size_filter.is_match(md.len()))
+                    .unwrap_or(false)
+            });
+        }
+
         SharedRecursive::combine_and_send_entries(
             vec_files,
             &vec_dirs,
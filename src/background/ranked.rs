@@ -0,0 +1,116 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::config::generate::{DeletedRanking, DeletedSort, ExecMode};
+use crate::data::paths::{BasicDirEntryInfo, PathData, PHANTOM_DATE};
+use crate::display_versions::wrapper::VersionsDisplayWrapper;
+use crate::library::diagnostics::Diagnostic;
+use crate::library::results::HttmResult;
+use crate::library::utility::print_output_buf;
+use crate::{VersionsMap, GLOBAL_CONFIG};
+use once_cell::sync::Lazy;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+// when the user requests deleted files be sorted or limited, each is collected here, rather than printed as found
+static RANKED_DELETED: Lazy<Mutex<Vec<RankedDeleted>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+pub struct RankedDeleted {
+    pseudo_live: BasicDirEntryInfo,
+    modify_time: SystemTime,
+    size: u64,
+}
+
+impl RankedDeleted {
+    // the sort keys are read from the last snapshot version of each deleted file,
+    // as the "path that once was" has no metadata of its own
+    pub fn collect(entries: Vec<BasicDirEntryInfo>, pseudo_live_dir: &Path) {
+        let ranked = entries.into_iter().map(|basic_info| {
+            let opt_metadata = basic_info.path.symlink_metadata().ok();
+
+            RankedDeleted {
+                modify_time: opt_metadata
+                    .as_ref()
+                    .and_then(|md| md.modified().ok())
+                    .unwrap_or(PHANTOM_DATE),
+                size: opt_metadata.as_ref().map(|md| md.len()).unwrap_or_default(),
+                pseudo_live: BasicDirEntryInfo {
+                    path: pseudo_live_dir.join(basic_info.filename()),
+                    file_type: basic_info.file_type,
+                },
+            }
+        });
+
+        if let Ok(mut locked) = RANKED_DELETED.lock() {
+            locked.extend(ranked);
+        }
+
+        if let ExecMode::NonInteractiveRecursive(progress_bar) = &GLOBAL_CONFIG.exec_mode {
+            progress_bar.tick();
+        }
+    }
+
+    pub fn print(ranking: &DeletedRanking) -> HttmResult<()> {
+        let mut ranked: Vec<RankedDeleted> = match RANKED_DELETED.lock() {
+            Ok(mut locked) => std::mem::take(&mut *locked),
+            Err(_) => Vec::new(),
+        };
+
+        if ranked.is_empty() {
+            Diagnostic::notice(
+                "no_deleted_files",
+                "httm could not find any deleted files at this directory level.",
+            )
+            .print();
+
+            return Ok(());
+        }
+
+        match ranking.sort {
+            DeletedSort::ModifyTime => ranked.sort_by(|a, b| {
+                b.modify_time
+                    .cmp(&a.modify_time)
+                    .then_with(|| a.pseudo_live.path.cmp(&b.pseudo_live.path))
+            }),
+            DeletedSort::Size => ranked.sort_by(|a, b| {
+                b.size
+                    .cmp(&a.size)
+                    .then_with(|| a.pseudo_live.path.cmp(&b.pseudo_live.path))
+            }),
+            DeletedSort::Path => ranked.sort_by(|a, b| a.pseudo_live.path.cmp(&b.pseudo_live.path)),
+        }
+
+        if let Some(limit) = ranking.opt_limit {
+            ranked.truncate(limit);
+        }
+
+        // a versions map is always sorted by path, so each deleted file is formatted on its own to keep our order
+        let output_buf = ranked
+            .into_iter()
+            .map(|ranked| {
+                let pseudo_live_set = [PathData::from(ranked.pseudo_live)];
+
+                let versions_map = VersionsMap::new(&GLOBAL_CONFIG, &pseudo_live_set)?;
+
+                Ok(VersionsDisplayWrapper::from(&GLOBAL_CONFIG, versions_map).to_string())
+            })
+            .collect::<HttmResult<String>>()?;
+
+        print_output_buf(&output_buf)
+    }
+}
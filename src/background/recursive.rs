@@ -16,10 +16,12 @@
 // that was distributed with this source code.
 
 use crate::background::deleted::SpawnDeletedThread;
+use crate::background::ranked::RankedDeleted;
 use crate::config::generate::{DeletedMode, ExecMode};
 use crate::data::paths::{BasicDirEntryInfo, PathData};
 use crate::data::selection::SelectionCandidate;
 use crate::display_versions::wrapper::VersionsDisplayWrapper;
+use crate::library::diagnostics::Diagnostic;
 use crate::library::results::{HttmError, HttmResult};
 use crate::library::utility::{
     is_channel_closed, path_is_filter_dir, print_output_buf, HttmIsDir, Never,
@@ -133,6 +135,18 @@ impl RecursiveMainLoop {
         let mut queue: Vec<BasicDirEntryInfo> =
             Self::enter_directory(requested_dir, opt_deleted_scope, skim_tx, hangup_rx)?;
 
+        // a non-recursive search would otherwise stop at the boundary of the requested dir's dataset
+        if GLOBAL_CONFIG.opt_include_child_datasets && !GLOBAL_CONFIG.opt_recursive {
+            GLOBAL_CONFIG
+                .dataset_collection
+                .child_datasets(requested_dir)
+                .into_iter()
+                .for_each(|child_dataset| {
+                    let _ =
+                        Self::enter_directory(child_dataset, opt_deleted_scope, skim_tx, hangup_rx);
+                });
+        }
+
         if GLOBAL_CONFIG.opt_recursive {
             // condition kills iter when user has made a selection
             // pop_back makes this a LIFO queue which is supposedly better for caches
@@ -214,7 +228,12 @@ impl SharedRecursive {
                 }
             }
             PathProvenance::IsPhantom => {
-                // deleted - phantom
+                // deleted - phantom, collected to be sorted and printed at the end of the search, if requested
+                if GLOBAL_CONFIG.opt_deleted_ranking.is_some() {
+                    RankedDeleted::collect(combined, requested_dir);
+                    return Ok(());
+                }
+
                 Self::pseudo_live_versions(combined, requested_dir)
             }
         };
@@ -245,6 +264,10 @@ impl SharedRecursive {
                 if GLOBAL_CONFIG.opt_one_filesystem {
                     match entry.path.metadata() {
                         Ok(path_md) if *OPT_REQUESTED_DIR_DEV == path_md.dev() => {}
+                        // child datasets are distinct filesystems, but may be searched when requested
+                        Ok(_path_md)
+                            if GLOBAL_CONFIG.opt_include_child_datasets
+                                && Self::is_child_dataset_entry(entry) => {}
                         _ => {
                             // if we can't read the metadata for a path,
                             // we probably shouldn't show it either
@@ -266,6 +289,17 @@ impl SharedRecursive {
         Ok((vec_dirs, vec_files))
     }
 
+    fn is_child_dataset_entry(entry: &BasicDirEntryInfo) -> bool {
+        GLOBAL_CONFIG
+            .opt_requested_dir
+            .as_ref()
+            .is_some_and(|requested_dir| {
+                GLOBAL_CONFIG
+                    .dataset_collection
+                    .is_within_child_dataset(requested_dir, &entry.path)
+            })
+    }
+
     pub fn is_entry_dir(entry: &BasicDirEntryInfo) -> bool {
         // must do is_dir() look up on DirEntry file_type() as look up on Path will traverse links!
         if GLOBAL_CONFIG.opt_no_traverse {
@@ -341,10 +375,12 @@ impl SharedRecursive {
                     if GLOBAL_CONFIG.opt_recursive {
                         progress_bar.tick();
                     } else {
-                        eprintln!(
-              "NOTICE: httm could not find any deleted files at this directory level.  \
-                        Perhaps try specifying a deleted mode in combination with \"--recursive\"."
-            )
+                        Diagnostic::notice(
+                            "no_deleted_files",
+                            "httm could not find any deleted files at this directory level.  \
+                            Perhaps try specifying a deleted mode in combination with \"--recursive\".",
+                        )
+                        .print()
                     }
                 } else {
                     NonInteractiveRecursiveWrapper::print(entries)?;
@@ -391,6 +427,11 @@ impl NonInteractiveRecursiveWrapper {
         match &GLOBAL_CONFIG.opt_requested_dir {
             Some(requested_dir) => {
                 RecursiveSearch::exec(requested_dir, dummy_skim_tx, hangup_rx);
+
+                // all deleted threads have completed once the search returns
+                if let Some(ranking) = &GLOBAL_CONFIG.opt_deleted_ranking {
+                    RankedDeleted::print(ranking)?;
+                }
             }
             None => {
                 return Err(HttmError::new(
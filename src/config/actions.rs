@@ -0,0 +1,308 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use clap::{Arg, Command};
+
+// shown in the help of every action
+const COMMON_OPTIONS: &[&str] = &[
+    "INPUT_FILES",
+    "ALT_REPLICATED",
+    "ALT_STORE",
+    "MAP_ALIASES",
+    "REMOTE_DIR",
+    "LOCAL_DIR",
+    "ARCHIVE_DIR",
+    "EXCLUDE_DATASET",
+    "INCLUDE_ONLY_DATASET",
+    "INCLUDE_CHILD_DATASETS",
+    "INCLUDE_SNAP_MOUNTS",
+    "SNAP_CLASS",
+    "MOUNT_BACKEND",
+    "PRIVILEGED_HELPER",
+    "NETWORK_CONCURRENCY",
+    "JSON_ERRORS",
+    "DEBUG",
+];
+
+// an action is a subcommand which stands for an option, like "httm restore" for "httm --restore",
+// and which takes all the same options, so the rest of httm need never know which was given
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    List,
+    Restore,
+    Browse,
+    Snap,
+    Prune,
+    Mounts,
+    Stat,
+    PathAt,
+}
+
+impl Action {
+    const ALL: [Action; 8] = [
+        Action::List,
+        Action::Restore,
+        Action::Browse,
+        Action::Snap,
+        Action::Prune,
+        Action::Mounts,
+        Action::Stat,
+        Action::PathAt,
+    ];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|action| action.name() == name || action.aliases().contains(&name))
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::List => "list",
+            Action::Restore => "restore",
+            Action::Browse => "browse",
+            Action::Snap => "snap",
+            Action::Prune => "prune",
+            Action::Mounts => "mounts",
+            Action::Stat => "stat",
+            Action::PathAt => "path-at",
+        }
+    }
+
+    fn aliases(&self) -> &'static [&'static str] {
+        match self {
+            Action::List => &["ls"],
+            Action::Snap => &["snapshot"],
+            Action::Restore
+            | Action::Browse
+            | Action::Prune
+            | Action::Mounts
+            | Action::Stat
+            | Action::PathAt => &[],
+        }
+    }
+
+    fn about(&self) -> &'static str {
+        match self {
+            Action::List => "display the unique versions of the input file/s, and their sizes and dates.",
+            Action::Restore => "interactive browse and search a directory, then select a snapshot version to restore.",
+            Action::Browse => "interactive browse and search a directory to display unique file versions.",
+            Action::Snap => "snapshot the most immediate mount of the input file/s.",
+            Action::Prune => "prune all snapshot/s which contain the input file/s on that file's most immediate mount.",
+            Action::Mounts => "display the mount point/s of the dataset/s which contain the input file/s.",
+            Action::Stat => "display the full metadata of the live file and of each unique snapshot version of the input file/s.",
+            Action::PathAt => "print only the path of the input file/s upon the snapshot named, or upon the most recent snapshot taken at or before the date given.",
+        }
+    }
+
+    // the id of the option for which the action stands
+    fn id(&self) -> Option<&'static str> {
+        match self {
+            Action::List => None,
+            Action::Restore => Some("RESTORE"),
+            Action::Browse => Some("BROWSE"),
+            Action::Snap => Some("SNAPSHOT"),
+            Action::Prune => Some("PRUNE"),
+            Action::Mounts => Some("FILE_MOUNT"),
+            Action::Stat => Some("STAT"),
+            Action::PathAt => Some("PATH_AT"),
+        }
+    }
+
+    // the name by which the value of the action's option may be given, like "httm restore --mode=guard",
+    // and the option's own name
+    fn value_option(&self) -> Option<(&'static str, &'static str)> {
+        match self {
+            Action::Restore => Some(("mode", "restore")),
+            Action::Snap => Some(("suffix", "snap")),
+            Action::Mounts => Some(("show", "file-mount")),
+            Action::PathAt => Some(("at", "path-at")),
+            Action::List | Action::Browse | Action::Prune | Action::Stat => None,
+        }
+    }
+
+    // the action's own option, given on behalf of the user, just after the action, so the option is present
+    // for the purposes of conflicts and requirements, as a default value would not be, though the user may
+    // still give another value, like "--mode=guard", which, given later, overrides it
+    pub fn implied_arg(&self) -> Option<&'static str> {
+        match self {
+            Action::Restore => Some("--mode"),
+            Action::Browse => Some("--browse"),
+            Action::Snap => Some("--suffix"),
+            Action::Prune => Some("--prune"),
+            Action::Mounts => Some("--show"),
+            Action::Stat => Some("--stat"),
+            // a snapshot or date has no default, and so must be given by the user
+            Action::List | Action::PathAt => None,
+        }
+    }
+
+    fn options(&self) -> &'static [&'static str] {
+        match self {
+            Action::List => &[
+                "UNIQUENESS",
+                "EXACT",
+                "DELETED",
+                "RECURSIVE",
+                "LAST_SNAP",
+                "NUM_VERSIONS",
+                "RAW",
+                "ZEROS",
+                "NOT_SO_PRETTY",
+                "JSON",
+                "TABLE",
+                "TREE",
+                "OMIT_DITTO",
+                "WITH_FIELDS",
+                "NO_LIVE",
+                "NO_SNAP",
+                "NO_FILTER",
+                "FILTER_HIDDEN",
+                "ONE_FILESYSTEM",
+                "NO_TRAVERSE",
+                "MIN_SIZE",
+                "MAX_SIZE",
+                "FILE_TYPE",
+                "CHANGED_ATTR",
+                "LIMIT",
+                "SORT_DELETED",
+                "PRESERVE_ORDER",
+                "PROBE_ORDER",
+                "SNAP_CREATION",
+                "UTC",
+                "SI",
+                "LOCALE",
+                "DATE_FORMAT",
+                "VERSION_IDS",
+                "HISTOGRAM",
+                "CACHE_METADATA",
+                "INDEX_DIR",
+                "OUTPUT",
+                "TEE",
+            ],
+            Action::Restore => &[
+                "RESTORE",
+                "DELETED",
+                "RECURSIVE",
+                "UNIQUENESS",
+                "PREVIEW",
+                "PREVIEW_POSITION",
+                "PREVIEW_SIZE",
+                "PREVIEW_WRAP",
+                "NO_FILTER",
+                "FILTER_HIDDEN",
+                "ONE_FILESYSTEM",
+                "OVERWRITE_CHANGED",
+                "RESTORE_MTIME",
+                "OWNER_MAP",
+                "RESTORE_SPECIAL",
+                "SKIP_SPACE_CHECK",
+                "IN_PLACE",
+                "NO_CLONES",
+                "BWLIMIT",
+                "BACKGROUND",
+                "SESSION",
+            ],
+            Action::Browse => &[
+                "BROWSE",
+                "SELECT",
+                "DELETED",
+                "RECURSIVE",
+                "UNIQUENESS",
+                "PREVIEW",
+                "PREVIEW_POSITION",
+                "PREVIEW_SIZE",
+                "PREVIEW_WRAP",
+                "NO_FILTER",
+                "FILTER_HIDDEN",
+                "ONE_FILESYSTEM",
+                "SESSION",
+            ],
+            Action::Snap => &["SNAPSHOT"],
+            Action::Prune => &["PRUNE", "LIST_SNAPS", "SELECT", "UNIQUENESS"],
+            Action::Mounts => &["FILE_MOUNT", "RAW", "ZEROS", "NOT_SO_PRETTY", "JSON"],
+            Action::Stat => &[
+                "STAT",
+                "UNIQUENESS",
+                "NO_LIVE",
+                "NO_SNAP",
+                "RAW",
+                "ZEROS",
+                "NOT_SO_PRETTY",
+                "JSON",
+                "UTC",
+                "SI",
+                "DATE_FORMAT",
+                "OUTPUT",
+                "TEE",
+            ],
+            Action::PathAt => &["PATH_AT", "ZEROS", "UTC", "SNAP_CREATION", "OUTPUT", "TEE"],
+        }
+    }
+
+    // each subcommand takes the options of httm itself, given as the root command, but only shows
+    // those most relevant to the action in its help
+    pub fn subcommands(root: &Command) -> Vec<Command> {
+        Self::ALL
+            .iter()
+            .map(|action| action.subcommand(root))
+            .collect()
+    }
+
+    fn subcommand(&self, root: &Command) -> Command {
+        let relevant: Vec<&str> = COMMON_OPTIONS
+            .iter()
+            .chain(self.options())
+            .copied()
+            .collect();
+
+        let args = root.get_arguments().map(|arg| {
+            if Some(arg.get_id().as_str()) == self.id() {
+                return self.action_arg(arg.clone());
+            }
+
+            if relevant.contains(&arg.get_id().as_str()) {
+                arg.clone()
+            } else {
+                arg.clone().hide(true)
+            }
+        });
+
+        Command::new(self.name())
+            .aliases(self.aliases())
+            .about(self.about())
+            .after_help(
+                "Every option of httm is accepted after an action, but only those most relevant to the action are shown here. \
+                See \"httm --help\" for all options.",
+            )
+            .args_override_self(true)
+            .args(args)
+    }
+
+    // the action's own option may be given by its own name, like "--mode=guard",
+    // or by the name of the option, like "--restore=guard"
+    fn action_arg(&self, arg: Arg) -> Arg {
+        match self.value_option() {
+            Some((value_long, long)) => arg
+                .long(value_long)
+                .alias(long)
+                .required(self.implied_arg().is_none()),
+            // an action which stands for a flag only ever sets that flag
+            None => arg.hide(true),
+        }
+    }
+}
@@ -0,0 +1,583 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+use crate::config::actions::Action;
+use crate::config::generate::command;
+use crate::library::diagnostics::Diagnostic;
+use crate::library::jobs::RestoreJob;
+use clap::Command;
+use std::collections::HashSet;
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+// a config file is simply a list of default arguments, one per line, like:
+//
+// # sizes in powers of 1000
+// --si
+// --date-format=[year]-[month]-[day] [hour]:[minute]
+//
+// which are inserted before the arguments given on the command line, so the command line always wins.
+//
+// arguments which follow a "[name]" line belong to the profile of that name, and are only used when
+// that profile is selected, via "--profile=name" or HTTM_PROFILE, and then override the same options
+// given outside of any profile:
+//
+// --uniqueness=contents
+//
+// [quick]
+// --uniqueness=metadata
+// --omit-ditto
+pub struct ConfigFile;
+
+// a config file's value for an option is a policy, and not itself a request for that mode, so it is
+// only used as the value of that option, when the option is given without a value on the command line
+const POLICY_OPTIONS: [&str; 1] = ["LAST_SNAP"];
+
+// one option in the config file, with any value given upon the following lines
+struct ConfigArg<'a> {
+    lines: Vec<&'a str>,
+    opt_id: Option<String>,
+}
+
+// an environment variable takes precedence over the same option in the config file,
+// so the config file's values for that option are dropped when its environment variable is set
+const ENV_OVERRIDES: [(&[&str], &[&str]); 3] = [
+    (&["HTTM_MAP_ALIASES"], &["--map-aliases", "--aliases"]),
+    (
+        &["HTTM_REMOTE_DIR", "HTTM_SNAP_POINT"],
+        &["--remote-dir", "--remote", "--snap-point"],
+    ),
+    (&["HTTM_LOCAL_DIR"], &["--local-dir", "--local"]),
+];
+
+impl ConfigFile {
+    pub fn args() -> Vec<OsString> {
+        let mut args_os = std::env::args_os();
+
+        let mut res: Vec<OsString> = args_os.next().into_iter().collect();
+
+        let mut user_args: Vec<OsString> = args_os.collect();
+
+        RestoreJob::worker_args(&mut user_args);
+
+        let command = command();
+
+        // an action may only be the first argument given on the command line, so the default arguments
+        // are inserted after it, and are parsed as arguments of the action
+        let opt_action = user_args
+            .first()
+            .and_then(|arg| arg.to_str())
+            .and_then(Action::from_name);
+
+        let command = match opt_action {
+            Some(action) => {
+                res.push(user_args.remove(0));
+
+                // the action's own option is given as though by the user, so it overrides the config file
+                if let Some(implied_arg) = action.implied_arg() {
+                    user_args.insert(0, OsString::from(implied_arg));
+                }
+
+                command
+                    .find_subcommand(action.name())
+                    .cloned()
+                    .unwrap_or(command)
+            }
+            None => command,
+        };
+
+        let contents = Self::contents().unwrap_or_default();
+
+        res.extend(Self::merge(&command, &contents, user_args));
+
+        res
+    }
+
+    // the default arguments of the config file's contents, followed by the arguments given on the command line
+    fn merge(command: &Command, contents: &str, mut user_args: Vec<OsString>) -> Vec<OsString> {
+        let config_args = Self::config_args(command, contents, &user_args);
+        let user_ids = Self::user_ids(command, &user_args);
+
+        let (policies, defaults): (Vec<ConfigArg>, Vec<ConfigArg>) =
+            config_args.into_iter().partition(|config_arg| {
+                config_arg
+                    .opt_id
+                    .as_deref()
+                    .is_some_and(|id| POLICY_OPTIONS.contains(&id))
+            });
+
+        policies
+            .iter()
+            .for_each(|policy| Self::apply_policy(command, policy, &mut user_args));
+
+        defaults
+            .into_iter()
+            .filter(|config_arg| !Self::is_overridden(command, config_arg, &user_ids))
+            .flat_map(|config_arg| config_arg.lines)
+            .map(OsString::from)
+            .chain(user_args)
+            .collect()
+    }
+
+    fn config_args<'a>(
+        command: &Command,
+        contents: &'a str,
+        user_args: &[OsString],
+    ) -> Vec<ConfigArg<'a>> {
+        let opt_profile = Self::profile(user_args);
+
+        let mut global: Vec<ConfigArg> = Vec::new();
+        let mut profile: Vec<ConfigArg> = Vec::new();
+        let mut opt_section: Option<&str> = None;
+        let mut is_profile_found = false;
+
+        let mut opt_last_line: Option<&str> = None;
+
+        Self::lines(contents).into_iter().for_each(|line| {
+            let is_value = opt_last_line.is_some_and(|last| Self::awaits_value(command, last));
+            opt_last_line = Some(line);
+
+            if let Some(section) = line
+                .strip_prefix('[')
+                .and_then(|rest| rest.strip_suffix(']'))
+                .filter(|_| !is_value)
+            {
+                opt_section = Some(section.trim());
+                is_profile_found |= opt_profile.as_deref() == opt_section;
+                return;
+            }
+
+            let section_args = match opt_section {
+                None => &mut global,
+                Some(section) if opt_profile.as_deref() == Some(section) => &mut profile,
+                Some(_) => return,
+            };
+
+            // a line which is not itself an option is the value of the option upon the line before
+            match section_args.last_mut() {
+                Some(config_arg) if !line.starts_with('-') => config_arg.lines.push(line),
+                _ => section_args.push(ConfigArg {
+                    lines: vec![line],
+                    opt_id: Self::id_of(command, line),
+                }),
+            }
+        });
+
+        if let Some(profile_name) = opt_profile.as_deref() {
+            if !is_profile_found {
+                Diagnostic::warn(
+                    "profile_not_found",
+                    format!(
+                        "The config file contains no profile named {:?}.",
+                        profile_name
+                    ),
+                )
+                .print();
+            }
+        }
+
+        // a profile's option overrides the same option given outside of any profile
+        let profile_ids: HashSet<&str> = profile
+            .iter()
+            .filter_map(|config_arg| config_arg.opt_id.as_deref())
+            .collect();
+
+        let mut res: Vec<ConfigArg> = global
+            .into_iter()
+            .filter(|config_arg| {
+                !config_arg
+                    .opt_id
+                    .as_deref()
+                    .is_some_and(|id| profile_ids.contains(id))
+            })
+            .collect();
+
+        res.extend(profile);
+
+        res
+    }
+
+    // an option given without an '=' takes its value from the next line, which may look like a section
+    fn awaits_value(command: &Command, last_line: &str) -> bool {
+        if last_line.contains('=') {
+            return false;
+        }
+
+        let Some(id) = Self::id_of(command, last_line) else {
+            return false;
+        };
+
+        command
+            .get_arguments()
+            .find(|cmd_arg| cmd_arg.get_id() == id.as_str())
+            .is_some_and(|cmd_arg| {
+                cmd_arg.get_action().takes_values()
+                    && !cmd_arg.is_require_equals_set()
+                    && cmd_arg
+                        .get_num_args()
+                        .is_none_or(|range| range.min_values() > 0)
+            })
+    }
+
+    fn profile(user_args: &[OsString]) -> Option<String> {
+        let mut iter = user_args.iter().map(|arg| arg.to_string_lossy());
+
+        while let Some(arg) = iter.next() {
+            if arg == "--" {
+                break;
+            }
+
+            if let Some(value) = arg.strip_prefix("--profile=") {
+                return Some(value.to_string());
+            }
+
+            if arg == "--profile" {
+                return iter.next().map(|value| value.to_string());
+            }
+        }
+
+        std::env::var("HTTM_PROFILE")
+            .ok()
+            .filter(|value| !value.is_empty())
+    }
+
+    // the ids of the options given on the command line, whether by long name, alias or short flag
+    fn user_ids(command: &Command, user_args: &[OsString]) -> HashSet<String> {
+        let mut res = HashSet::new();
+
+        for arg in user_args.iter().map(|arg| arg.to_string_lossy()) {
+            if arg == "--" {
+                break;
+            }
+
+            if arg.starts_with("--") {
+                res.extend(Self::id_of(command, &arg));
+                continue;
+            }
+
+            if let Some(shorts) = arg.strip_prefix('-') {
+                res.extend(shorts.chars().filter_map(|short| {
+                    command
+                        .get_arguments()
+                        .find(|cmd_arg| {
+                            cmd_arg
+                                .get_short_and_visible_aliases()
+                                .is_some_and(|all| all.contains(&short))
+                                || cmd_arg
+                                    .get_all_short_aliases()
+                                    .is_some_and(|all| all.contains(&short))
+                        })
+                        .map(|cmd_arg| cmd_arg.get_id().to_string())
+                }));
+            }
+        }
+
+        res
+    }
+
+    fn id_of(command: &Command, line: &str) -> Option<String> {
+        let name = line.split('=').next()?;
+
+        if let Some(long) = name.strip_prefix("--") {
+            return command
+                .get_arguments()
+                .find(|cmd_arg| {
+                    cmd_arg.get_long() == Some(long)
+                        || cmd_arg
+                            .get_all_aliases()
+                            .is_some_and(|all| all.contains(&long))
+                })
+                .map(|cmd_arg| cmd_arg.get_id().to_string());
+        }
+
+        let mut shorts = name.strip_prefix('-')?.chars();
+
+        match (shorts.next(), shorts.next()) {
+            (Some(short), None) => command
+                .get_arguments()
+                .find(|cmd_arg| {
+                    cmd_arg.get_short() == Some(short)
+                        || cmd_arg
+                            .get_all_short_aliases()
+                            .is_some_and(|all| all.contains(&short))
+                })
+                .map(|cmd_arg| cmd_arg.get_id().to_string()),
+            _ => None,
+        }
+    }
+
+    // the command line wins, so a default is dropped when the same option, or one which conflicts with it,
+    // is given on the command line
+    fn is_overridden(
+        command: &Command,
+        config_arg: &ConfigArg,
+        user_ids: &HashSet<String>,
+    ) -> bool {
+        let Some(id) = config_arg.opt_id.as_deref() else {
+            return false;
+        };
+
+        if user_ids.contains(id) {
+            return true;
+        }
+
+        let Some(cmd_arg) = command
+            .get_arguments()
+            .find(|cmd_arg| cmd_arg.get_id() == id)
+        else {
+            return false;
+        };
+
+        command
+            .get_arg_conflicts_with(cmd_arg)
+            .iter()
+            .any(|conflict| user_ids.contains(conflict.get_id().as_str()))
+            || command
+                .get_arguments()
+                .filter(|other| user_ids.contains(other.get_id().as_str()))
+                .any(|other| {
+                    command
+                        .get_arg_conflicts_with(other)
+                        .iter()
+                        .any(|conflict| conflict.get_id() == id)
+                })
+    }
+
+    fn apply_policy(command: &Command, policy: &ConfigArg, user_args: &mut [OsString]) {
+        let Some(id) = policy.opt_id.as_deref() else {
+            return;
+        };
+
+        let value = match policy.lines.as_slice() {
+            [option] => match option.split_once('=') {
+                Some((_name, value)) => value,
+                None => return,
+            },
+            [_option, value, ..] => value,
+            [] => return,
+        };
+
+        let Some(cmd_arg) = command
+            .get_arguments()
+            .find(|cmd_arg| cmd_arg.get_id() == id)
+        else {
+            return;
+        };
+
+        let Some(long) = cmd_arg.get_long() else {
+            return;
+        };
+
+        let names: Vec<String> = cmd_arg
+            .get_short()
+            .map(|short| format!("-{short}"))
+            .into_iter()
+            .chain(std::iter::once(format!("--{long}")))
+            .chain(
+                cmd_arg
+                    .get_all_aliases()
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|alias| format!("--{alias}")),
+            )
+            .collect();
+
+        user_args
+            .iter_mut()
+            .take_while(|arg| arg.as_os_str() != "--")
+            .filter(|arg| names.iter().any(|name| arg.as_os_str() == name.as_str()))
+            .for_each(|arg| *arg = OsString::from(format!("--{long}={value}")));
+    }
+
+    fn contents() -> Option<String> {
+        let (path, is_user_specified) = match std::env::var_os("HTTM_CONFIG_FILE") {
+            Some(value) if value.is_empty() => return None,
+            Some(value) => (PathBuf::from(value), true),
+            None => (Self::default_path()?, false),
+        };
+
+        match std::fs::read_to_string(&path) {
+            Ok(contents) => Some(contents),
+            Err(err) => {
+                // a config file at the default location is optional, but a specified file is not
+                if is_user_specified || err.kind() != std::io::ErrorKind::NotFound {
+                    Diagnostic::warn(
+                        "config_file_unreadable",
+                        format!(
+                            "Could not read the config file at {:?}, and its arguments will be ignored: {}",
+                            path, err
+                        ),
+                    )
+                    .with_path(&path)
+                    .print();
+                }
+
+                None
+            }
+        }
+    }
+
+    fn lines(contents: &str) -> Vec<&str> {
+        let lines: Vec<&str> = contents
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .collect();
+
+        let overridden: Vec<&str> = ENV_OVERRIDES
+            .iter()
+            .filter(|(keys, _options)| {
+                keys.iter()
+                    .any(|key| std::env::var_os(key).is_some_and(|value| !value.is_empty()))
+            })
+            .flat_map(|(_keys, options)| options.iter().copied())
+            .collect();
+
+        let mut res = Vec::with_capacity(lines.len());
+        let mut iter = lines.into_iter();
+
+        while let Some(line) = iter.next() {
+            match overridden
+                .iter()
+                .find(|option| Self::is_option(line, option))
+            {
+                // an option given without an '=' takes its value from the next line
+                Some(option) if line == *option => {
+                    iter.next();
+                }
+                Some(_) => {}
+                None => res.push(line),
+            }
+        }
+
+        res
+    }
+
+    fn is_option(line: &str, option: &str) -> bool {
+        line.strip_prefix(option)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('='))
+    }
+
+    fn default_path() -> Option<PathBuf> {
+        let config_dir = match std::env::var_os("XDG_CONFIG_HOME") {
+            Some(value) if !value.is_empty() => PathBuf::from(value),
+            _ => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+        };
+
+        Some(config_dir.join("httm").join("config"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn merged(contents: &str, user_args: &[&str]) -> Vec<String> {
+        let user_args: Vec<OsString> = user_args.iter().map(OsString::from).collect();
+
+        ConfigFile::merge(&command(), contents, user_args)
+            .into_iter()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn default_precedes_command_line() {
+        assert_eq!(merged("--si\n", &["file"]), ["--si", "file"]);
+    }
+
+    #[test]
+    fn command_line_overrides_default() {
+        assert_eq!(
+            merged(
+                "--uniqueness=contents\n",
+                &["--uniqueness=metadata", "file"]
+            ),
+            ["--uniqueness=metadata", "file"]
+        );
+    }
+
+    #[test]
+    fn conflicting_command_line_option_drops_default() {
+        assert_eq!(
+            merged("--json\n", &["--histogram", "file"]),
+            ["--histogram", "file"]
+        );
+    }
+
+    #[test]
+    fn value_upon_the_next_line() {
+        assert_eq!(
+            merged("--uniqueness\ncontents\n", &["file"]),
+            ["--uniqueness", "contents", "file"]
+        );
+    }
+
+    #[test]
+    fn profile_overrides_global_default() {
+        let contents = "--uniqueness=contents\n--si\n[quick]\n--uniqueness=metadata\n";
+
+        assert_eq!(
+            merged(contents, &["--profile=quick", "file"]),
+            ["--si", "--uniqueness=metadata", "--profile=quick", "file"]
+        );
+        assert_eq!(
+            merged(contents, &["file"]),
+            ["--uniqueness=contents", "--si", "file"]
+        );
+    }
+
+    #[test]
+    fn environment_overrides_default_but_not_command_line() {
+        let contents = "--local-dir=/config\n--si\n";
+
+        assert_eq!(
+            merged(contents, &["file"]),
+            ["--local-dir=/config", "--si", "file"]
+        );
+
+        std::env::set_var("HTTM_LOCAL_DIR", "/environment");
+        let with_env = merged(contents, &["file"]);
+        let with_both = merged(contents, &["--local-dir=/command-line", "file"]);
+        std::env::remove_var("HTTM_LOCAL_DIR");
+
+        assert_eq!(with_env, ["--si", "file"]);
+        assert_eq!(with_both, ["--si", "--local-dir=/command-line", "file"]);
+    }
+
+    #[test]
+    fn policy_is_only_a_value() {
+        let contents = "--last-snap=no-ditto\n";
+
+        assert_eq!(
+            merged(contents, &["--last-snap", "file"]),
+            ["--last-snap=no-ditto", "file"]
+        );
+        assert_eq!(merged(contents, &["file"]), ["file"]);
+        assert_eq!(
+            merged(contents, &["--last-snap=any", "file"]),
+            ["--last-snap=any", "file"]
+        );
+    }
+
+    #[test]
+    fn policy_after_the_end_of_options_is_a_path() {
+        assert_eq!(
+            merged("--last-snap=no-ditto\n", &["--", "--last-snap"]),
+            ["--", "--last-snap"]
+        );
+    }
+}
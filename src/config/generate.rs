@@ -15,22 +15,46 @@
 // For the full copyright and license information, please view the LICENSE file
 // that was distributed with this source code.
 
+use crate::config::actions::Action;
+use crate::config::config_file::ConfigFile;
 use crate::config::install_hot_keys::install_hot_keys;
 use crate::data::filesystem_info::FilesystemInfo;
 use crate::data::paths::PathDeconstruction;
 use crate::data::paths::{PathData, ZfsSnapPathGuard};
+use crate::interactive::preview::PreviewWindow;
+use crate::library::audit_log::AuditLog;
+use crate::library::compare_command::CompareCommand;
+use crate::library::content_range::ContentRange;
+use crate::library::diagnostics::Diagnostic;
+use crate::library::hooks::Hooks;
+use crate::library::owner_map::OwnerMap;
+use crate::library::privileged::{PrivilegedHelper, DEFAULT_PRIVILEGED_HELPER};
+use crate::library::redact::{RedactField, Redaction};
+use crate::library::rename_template::RenameTemplate;
 use crate::library::results::{HttmError, HttmResult};
-use crate::library::utility::{pwd, HttmIsDir};
+use crate::library::size_filter::SizeFilter;
+use crate::library::snap_limits::SnapLimits;
+use crate::library::stash::Stash;
+use crate::library::text_encoding::TextEncoding;
+use crate::library::network_limit::NetworkLimit;
+use crate::library::throttle::Throttle;
+use crate::library::utility::{pwd, DateFormat, HttmIsDir, NumberFormat, NumberSeparators};
 use crate::lookup::file_mounts::MountDisplay;
-use crate::parse::mounts::FilesystemType;
+use crate::parse::encrypted::LockedDatasets;
+use crate::parse::mount_backends::MountBackend;
+use crate::parse::mounts::{DatasetFilter, FilesystemType, IncludeSnapMounts};
+use crate::parse::unc_paths::MapOfUncShares;
 use crate::ROOT_DIRECTORY;
 use clap::parser::ValuesRef;
-use clap::{crate_name, crate_version, Arg, ArgAction, ArgMatches};
+use clap::{crate_name, crate_version, Arg, ArgAction, ArgMatches, Command};
 use indicatif::ProgressBar;
 use rayon::prelude::*;
+use std::collections::HashSet;
+use std::ffi::OsString;
 use std::io::Read;
 use std::ops::Index;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 use time::UtcOffset;
 
 #[derive(Debug, Clone)]
@@ -40,10 +64,44 @@ pub enum ExecMode {
     BasicDisplay,
     SnapFileMount(String),
     Prune(Option<ListSnapsFilters>),
+    Materialize(PathBuf),
+    DirDiff(String),
+    PathAt(String),
     MountsForFiles(MountDisplay),
     SnapsForFiles(Option<ListSnapsFilters>),
     NumVersions(NumVersionsMode),
+    Histogram(HistogramBucket),
+    ListDatasets,
+    CheckAliases,
+    ProbeSnapshots,
+    Jobs(JobAction),
+    ProtectionReport,
+    Reconstruct,
     RollForward(String),
+    Top,
+    Transcode(PathBuf),
+    PrintNote(PathBuf),
+    VersionIds(Vec<String>, VersionAction),
+    CompareRemote(String),
+    Recover,
+    ParseMounts(PathBuf),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VersionAction {
+    Path,
+    Cat(Option<ContentRange>),
+    Diff,
+    Restore,
+    Splice(ContentRange),
+    Note(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JobAction {
+    List,
+    Cancel(String),
+    Run(String),
 }
 
 #[derive(Debug, Clone)]
@@ -52,6 +110,16 @@ pub enum BulkExclusion {
     NoSnap,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RawField {
+    ModifyTime,
+    Size,
+    SnapName,
+    FileType,
+    VersionId,
+    Note,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum InteractiveMode {
     Browse,
@@ -70,6 +138,8 @@ pub enum SelectMode {
     Path,
     Contents,
     Preview,
+    Json,
+    Widget(ShellDialect),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -77,6 +147,41 @@ pub enum RestoreMode {
     CopyOnly,
     CopyAndPreserve,
     Overwrite(RestoreSnapGuard),
+    PickDestination,
+    Merge,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProbeOrder {
+    Newest,
+    Oldest,
+    Name,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RestoreMtime {
+    Source,
+    Now,
+    PreserveLive,
+}
+
+impl RestoreMtime {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RestoreMtime::Source => "source",
+            RestoreMtime::Now => "now",
+            RestoreMtime::PreserveLive => "preserve-live",
+        }
+    }
+
+    pub fn from_str(value: &str) -> Option<Self> {
+        match value {
+            "source" => Some(RestoreMtime::Source),
+            "now" => Some(RestoreMtime::Now),
+            "preserve-live" => Some(RestoreMtime::PreserveLive),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -94,11 +199,65 @@ pub enum DeletedMode {
     Only,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeletedSort {
+    ModifyTime,
+    Size,
+    Path,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeletedRanking {
+    pub sort: DeletedSort,
+    pub opt_limit: Option<usize>,
+}
+
 #[derive(Debug, Clone)]
 pub enum ListSnapsOfType {
     All,
     UniqueMetadata,
     UniqueContents,
+    UniqueKey(DedupKey),
+    UniqueCommand(CompareCommand),
+}
+
+// a composite of those attributes which, when all are equal, make two versions the "same" version
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DedupKey {
+    pub mtime: bool,
+    pub size: bool,
+    pub ctime: bool,
+    pub hash: bool,
+    pub perms: bool,
+}
+
+impl DedupKey {
+    fn new(values: &str) -> HttmResult<Self> {
+        let mut dedup_key = DedupKey::default();
+
+        values
+            .split(',')
+            .map(str::trim)
+            .try_for_each(|value| {
+                match value {
+                    "mtime" => dedup_key.mtime = true,
+                    "size" => dedup_key.size = true,
+                    "ctime" => dedup_key.ctime = true,
+                    "hash" | "contents" => dedup_key.hash = true,
+                    "perms" | "mode" => dedup_key.perms = true,
+                    _ => {
+                        let msg = format!(
+                            "Uniqueness key value is not recognized: {value:?}.  Valid values are: mtime, size, ctime, hash, and perms."
+                        );
+                        return Err(HttmError::new(&msg));
+                    }
+                }
+
+                Ok(())
+            })?;
+
+        Ok(dedup_key)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -127,6 +286,42 @@ pub enum NumVersionsMode {
     Multiple,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangedAttr {
+    Owner,
+    Mode,
+    Xattr,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChecksumAlgorithm {
+    Sha256,
+    Blake3,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShellDialect {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RsyncListRoot {
+    // a directory, like "/", or the root of one snapshot, like "/home/.zfs/snapshot/snap_1"
+    Dir(PathBuf),
+    // the name of a snapshot, whose root is the snapshot mount of that name
+    SnapName(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HistogramBucket {
+    Auto,
+    Day,
+    Week,
+    Month,
+}
+
 const NATIVE_SNAP_SUFFIXES: [&str; 4] = [
     "ounceSnapFileMount",
     "httmSnapFileMount",
@@ -135,16 +330,49 @@ const NATIVE_SNAP_SUFFIXES: [&str; 4] = [
 ];
 
 fn parse_args() -> ArgMatches {
+    let mut matches = command().get_matches_from(ConfigFile::args());
+
+    // an action takes the same options as httm itself, so its matches stand in for the whole
+    match matches.remove_subcommand() {
+        Some((_name, action_matches)) => action_matches,
+        None => matches,
+    }
+}
+
+// every action is a subcommand, which must be the first argument, see Action
+pub fn command() -> Command {
+    let root = root_command();
+    let subcommands = Action::subcommands(&root);
+
+    root.subcommands(subcommands)
+        .args_conflicts_with_subcommands(true)
+        .disable_help_subcommand(true)
+}
+
+fn root_command() -> Command {
     clap::command!(crate_name!())
         .about("httm prints the size, date and corresponding locations of available unique versions of files residing on snapshots. \
         May also be used interactively to select and restore from such versions, and even to snapshot datasets which contain certain files.")
         .version(crate_version!())
+        .after_help("Default arguments may be specified, one per line, in a config file, located at \"$XDG_CONFIG_HOME/httm/config\" \
+        (or \"~/.config/httm/config\"), or at the path specified by the HTTM_CONFIG_FILE environment variable. \
+        Arguments given on the command line override those specified in the config file, as do arguments which conflict with them. \
+        Default arguments may be grouped into profiles (see --profile). A config file's value for --last-snap is a policy, \
+        used only as the value of --last-snap, when --last-snap is given on the command line without a value. \
+        Where an option may also be set via an environment variable (like HTTM_MAP_ALIASES or HTTM_REMOTE_DIR), a value given on the command line overrides the environment variable, \
+        which, in turn, overrides the config file.\n\n\
+        The first argument may also name an action, which stands for the option of the same name, like \"httm restore --mode=guard ~/file.txt\" for \"httm --restore=guard ~/file.txt\". \
+        To name a file of the same name as an action, give its path, like \"./restore\", or give it after \"--\". See \"httm <ACTION> --help\" for the options most relevant to each action.")
+        .args_override_self(true)
         .arg(
             Arg::new("INPUT_FILES")
                 .help("in any non-interactive mode, put requested paths here. If you include no paths as arguments, \
                 then httm will pause waiting for input on stdin. In any interactive mode, \
                 this is the directory search path. If no directory is specified, \
-                httm will use the current working directory.")
+                httm will use the current working directory. \
+                Windows paths, like \"\\\\server\\share\\dir\\file\", are mapped onto the local mount of their SMB share, where one exists. \
+                Windows drive letter paths, like \"Z:\\dir\\file\", are mapped by the environment variable HTTM_DRIVE_MAP, \
+                a comma separated list of drive letters, each mapped to an SMB share or a local directory, like \"Z:=\\\\nas\\media,H:=/home/alice\".")
                 .value_parser(clap::value_parser!(PathBuf))
                 .num_args(0..)
                 .display_order(1)
@@ -164,14 +392,23 @@ fn parse_args() -> ArgMatches {
             Arg::new("SELECT")
                 .short('s')
                 .long("select")
-                .value_parser(["path", "contents", "preview"])
+                .value_parser(["path", "contents", "preview", "json"])
                 .num_args(0..=1)
                 .default_missing_value("path")
                 .require_equals(true)
                 .help("interactive browse and search a specified directory to display unique file versions. \
                 Continue to another dialog to select a snapshot version to dump to stdout. This argument optionally takes a value. \
                 Default behavior/value is to simply print the path name, but, if the path is a file, the user can print the file's contents by giving the value \"contents\", \
-                or print the PREVIEW output by giving the value \"preview\".")
+                or print the PREVIEW output by giving the value \"preview\". \
+                Giving the value \"json\" prints, instead, the versions selected as a JSON array, each with its snapshot path and metadata, its live path and metadata, \
+                its snapshot name, its version ID (see VERSION_ID), and its note, if any (see NOTE), so that scripts may act upon versions chosen interactively. \
+                Specify OUTPUT to write the same as a manifest file. \
+                While selecting, pin a version as the base with alt-p, and compare any other version to the base, side by side, with alt-c. \
+                Press alt-r to restore a copy of the highlighted version alongside its live file, named with the snapshot name as a suffix (like \"file.txt.snap_name\"), without any prompt. \
+                Versions are compared via 'diff' and a pager, or, if the DIFF_TOOL environment variable is set, via that command. \
+                Press alt-a to display all versions, alt-m to display only versions unique by metadata, or alt-u to display only versions unique by contents, \
+                and the versions are looked up again, without restarting httm, whatever the UNIQUENESS given. \
+                Each version is listed alongside its snapshot name, its modify date (like \"2024-05-01 12:00\") and its size in bytes, so typing \"daily 05-01\" will find the versions upon \"daily\" snapshots modified on May 1st.")
                 .conflicts_with("RESTORE")
                 .display_order(3)
                 .action(ArgAction::Append)
@@ -180,7 +417,7 @@ fn parse_args() -> ArgMatches {
             Arg::new("RESTORE")
                 .short('r')
                 .long("restore")
-                .value_parser(["copy", "copy-and-preserve", "overwrite", "yolo", "guard"])
+                .value_parser(["copy", "copy-and-preserve", "overwrite", "yolo", "guard", "pick", "merge"])
                 .num_args(0..=1)
                 .default_missing_value("copy")
                 .require_equals(true)
@@ -190,6 +427,14 @@ fn parse_args() -> ArgMatches {
                 Overwrite mode will attempt to preserve attributes, like the permissions/mode, timestamps, xattrs and ownership of the selected snapshot file version (this is and will likely remain a UNIX only feature). \
                 In order to preserve such attributes in \"copy\" mode, specify the \"copy-and-preserve\" value. User may also specify \"guard\". \
                 Guard mode has the same semantics as \"overwrite\" but will attempt to take a precautionary snapshot before any overwrite action occurs. \
+                User may also specify \"pick\", in which case, after a snapshot version is selected, httm will display a second dialog to pick the restore destination: \
+                the original location, the original directory with a timestamped name, a directory chosen by browsing, or a custom path. \
+                User may also specify \"merge\", in which case httm will perform a three-way merge of the live file, the common ancestor snapshot version (the most recent snapshot version no newer than the live file), \
+                and the snapshot version selected, writing the result, with any conflict markers, to the live file via 'diff3', or, if the MERGE_TOOL environment variable is set, \
+                launching that merge tool with a temporary copy of the live file, the ancestor, and the selected version as arguments, in which case the merge tool should write its result to that copy. \
+                Either way, the live file is replaced with the result as with any other restore. \
+                Before any live file is overwritten, httm warns if another process has the file open, and holds an advisory lock (flock) upon the file while it is overwritten, \
+                refusing to overwrite a file already locked by another process. \
                 Note: Guard mode is a ZFS only option. User may also set via the HTTM_RESTORE_MODE environment variable.")
                 .conflicts_with("SELECT")
                 .display_order(4)
@@ -224,6 +469,8 @@ fn parse_args() -> ArgMatches {
                 .short('a')
                 .long("alt-replicated")
                 .help("automatically discover locally replicated datasets and list their snapshots as well. \
+                A dataset is considered a replica when its name ends with the name of the original dataset (for example, \"rpool/home\" replicated to \"tank/rpool/home\"), \
+                or, for ZFS, when it shares a snapshot with the original dataset, as when it is the destination of a \"zfs send\" and \"zfs receive\", whatever its name or pool. \
                 NOTE: Be certain such replicated datasets are mounted before use. \
                 httm will silently ignore unmounted datasets in the interactive modes.")
                 .conflicts_with_all(&["REMOTE_DIR", "LOCAL_DIR"])
@@ -237,6 +484,8 @@ fn parse_args() -> ArgMatches {
                 .help("user may specify a command to preview snapshots while in a snapshot selection view. This argument optionally takes a value specifying the command to be executed. \
                 The default value/command, if no command value specified, is a 'bowie' formatted 'diff'. \
                 User defined commands must specify the snapshot file name \"{snap_file}\" and the live file name \"{live_file}\" within their shell command. \
+                User defined commands may also reference the following version metadata: the snapshot file name as \"{path}\", the snapshot name as \"{snap_name}\", \
+                the version's modify time as \"{mtime}\", the version's size as \"{size}\", and the live file name as \"{live_path}\" (for example: \"delta {live_path} {path}\"). \
                 NOTE: 'bash' is required to bootstrap any preview script, even if user defined preview commands or script is written in a different language.")
                 .value_parser(clap::value_parser!(String))
                 .num_args(0..=1)
@@ -248,7 +497,6 @@ fn parse_args() -> ArgMatches {
         .arg(
             Arg::new("UNIQUENESS")
                 .long("uniqueness")
-                .value_parser(["all", "no-filter", "metadata", "contents"])
                 .num_args(0..=1)
                 .visible_aliases(&["unique"])
                 .default_missing_value("contents")
@@ -258,7 +506,12 @@ fn parse_args() -> ArgMatches {
                 or a user can simply update the modify time via 'touch'. If only this flag is specified, the \"contents\" option compares the actual file contents of file versions, if their sizes match, \
                 and overrides the default \"metadata\" behavior. The \"contents\" option can be expensive, as the file versions need to be read back and compared, and should probably only be used for smaller files. \
                 Given how expensive this operation can be, for larger files or files with many versions, \"contents\" option is not shown in Interactive browse mode, \
-                but after a selection is made, can be utilized in Select or Restore modes. The \"all\" or \"no-filter\" option dumps all snapshot versions, and no attempt is made to determine if the file versions are distinct.")
+                but after a selection is made, can be utilized in Select or Restore modes. The \"all\" or \"no-filter\" option dumps all snapshot versions, and no attempt is made to determine if the file versions are distinct. \
+                Instead of these fixed modes, the user may also specify a composite key, of any of \"mtime\", \"size\", \"ctime\", \"hash\", and \"perms\", \
+                where versions are only considered the same, when each attribute of the key is the same (for example: \"--uniqueness=key=mtime,size,perms\"). \
+                Finally, the user may specify an external program, which is given the paths of two versions, and should exit 0, when they are the same, or 1, when they differ, \
+                like 'cmp' or 'diff' (for example: \"--uniqueness='cmd:cmp -s'\"), which makes possible a domain specific comparison, say, of JSON or SQLite files which differ byte-wise, but not logically. \
+                Here, each version is compared to the version just before it, and, as ever, versions with the same size and modify time are the same, without asking the program.")
                 .display_order(9)
                 .action(ArgAction::Append)
         )
@@ -348,7 +601,8 @@ fn parse_args() -> ArgMatches {
                 This argument optionally takes a value to display other information about the path. Possible values are: \
                 \"mount\" or \"target\" or \"directory\", return the directory upon which the underlying dataset or device of the mount, \
                 \"source\" or \"device\" or \"dataset\", return the underlying dataset/device of the mount, and, \
-                \"relative-path\" or \"relative\", return the path relative to the underlying dataset/device of the mount.")
+                \"relative-path\" or \"relative\", return the path relative to the underlying dataset/device of the mount. \
+                A read-only ZFS dataset (readonly=on), as is usual of a received replica, is labeled \"(read-only)\" in the formatted output, and httm will not restore to it.")
                 .conflicts_with_all(&["BROWSE", "SELECT", "RESTORE"])
                 .display_order(14)
                 .action(ArgAction::Append)
@@ -493,7 +747,9 @@ fn parse_args() -> ArgMatches {
                 This option is useful if you wish to view snapshot versions from within the local directory you back up to a remote network share. \
                 This option requires a value. Such a value is delimited by a colon, ':', and is specified in the form <LOCAL_DIR>:<REMOTE_DIR> \
                 (eg. --map-aliases /Users/<User Name>:/Volumes/Home). Multiple maps may be specified delimited by a comma, ','. \
-                You may also set via the environment variable HTTM_MAP_ALIASES.")
+                Aliases may be nested (eg. \"/data\" and \"/data/projects\" may each map to a different mount point), in which case the alias with the longest local directory \
+                containing a path claims that path.  Where the same local directory is specified more than once, the alias specified last takes precedence. \
+                You may also set via the environment variable HTTM_MAP_ALIASES, which is parsed exactly as this option's value, and which overrides any MAP_ALIASES in the config file, but not on the command line.")
                 .use_value_delimiter(true)
                 .value_parser(clap::builder::ValueParser::os_string())
                 .num_args(0..=1)
@@ -573,7 +829,1069 @@ fn parse_args() -> ArgMatches {
                 .display_order(35)
                 .action(ArgAction::SetTrue)
         )
-        .get_matches()
+        .arg(
+            Arg::new("SNAP_CREATION")
+                .long("snap-creation")
+                .aliases(&["creation", "snap-created"])
+                .help("in addition to each file version's modify time, display the creation time of the snapshot upon which the version resides, \
+                and order file versions by snapshot creation time, instead of by modify time. When versions share the same modify time, \
+                the version upon the earliest snapshot is the version displayed. Note: This is a ZFS only option, except that, for SMB shares which expose \
+                \"@GMT-YYYY.MM.DD-HH.MM.SS\" shadow copy directories, creation times are parsed from those GMT tokens, and are always displayed.")
+                .display_order(36)
+                .action(ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("SNAP_CLASS")
+                .long("snap-class")
+                .aliases(&["class", "snap-label", "labels"])
+                .value_name("CLASS")
+                .help("label each snapshot version by parsing its snapshot name according to common naming schemes \
+                (sanoid/syncoid, zfs-auto-snapshot, zrepl, Time Machine, snapper, and SMB shadow copies), and display the scheme, class (like \"hourly\" or \"daily\"), \
+                and timestamp parsed, when JSON output is also specified. When one or more classes are specified, as a comma delimited list, \
+                only display snapshot versions which belong to one of the classes specified (for example: \"--snap-class=daily,weekly\"). \
+                The default value, \"all\", labels but does not filter snapshot versions. Note: Snapshots not named according to a known scheme have no class. \
+                A ZFS dataset may also be given a default filter, for every user, by setting the user property \"httm:snap-filter\" upon the dataset, as in \"zfs set httm:snap-filter=daily,weekly rpool/home\". \
+                When SNAP_CLASS is specified, any such property is disregarded.")
+                .num_args(0..=1)
+                .require_equals(true)
+                .default_missing_value("all")
+                .display_order(37)
+        )
+        .arg(
+            Arg::new("OVERWRITE_CHANGED")
+                .long("overwrite-changed")
+                .aliases(&["force-overwrite", "clobber"])
+                .requires("RESTORE")
+                .help("by default, before a restore overwrites a live file, httm compares the live file's current modify time and size to those observed \
+                when the snapshot version was selected, and will refuse to restore, if the live file has changed in between. \
+                Here, you may disable this check, and overwrite the live file anyway.")
+                .display_order(38)
+                .action(ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("BWLIMIT")
+                .long("bwlimit")
+                .aliases(&["bw-limit", "io-limit", "throttle"])
+                .value_name("RATE")
+                .require_equals(true)
+                .help("limit the rate at which httm reads file data, when restoring or copying files, and when comparing file contents (as when --uniqueness=contents is specified), \
+                so that httm does not saturate a busy server. The rate is in bytes per second, with an optional unit suffix, as with MIN_SIZE (for example: \"--bwlimit=10M\"). \
+                Note: When a rate limit is specified, httm will not attempt a zero copy \"reflink\" clone.")
+                .display_order(39)
+                .action(ArgAction::Set)
+        )
+        .arg(
+            Arg::new("STASH")
+                .long("stash")
+                .aliases(&["quarantine", "soft-delete"])
+                .value_name("STASH_DIR")
+                .requires("PRUNE")
+                .num_args(0..=1)
+                .require_equals(true)
+                .value_parser(clap::value_parser!(PathBuf))
+                .help("when pruning, before any snapshot is destroyed, copy each version of the input file/s which resides upon the snapshot/s to be destroyed \
+                to a stash directory, so one has a grace period in which to recover a version one did not intend to lose. \
+                If any version cannot be stashed, no snapshot will be destroyed. \
+                This argument optionally takes a value, the stash directory. By default, versions are stashed to \"~/.httm_stash\". \
+                User may also set the default via the HTTM_STASH_DIR environment variable.")
+                .display_order(40)
+        )
+        .arg(
+            Arg::new("SESSION")
+                .long("session")
+                .value_name("NAME")
+                .require_equals(true)
+                .help("in the interactive modes, save the current selection, both the files selected and the snapshot versions chosen, to a named session, \
+                and, when a session of that name already exists, resume that session, skipping any selection already made. \
+                Sessions are saved to \"~/.httm_sessions\", or to the directory specified by the HTTM_SESSION_DIR environment variable.")
+                .display_order(41)
+                .action(ArgAction::Set)
+        )
+        .arg(
+            Arg::new("NETWORK_CONCURRENCY")
+                .long("network-concurrency")
+                .value_name("NUM")
+                .require_equals(true)
+                .help("limit the number of concurrent file operations (for instance, the stat of a snapshot version, or the read of file contents) upon each network dataset (SMB, NFS, or AFP mounts), \
+                while local datasets remain fully parallel, so that a slow network share does not become overwhelmed. \
+                The limit applies to each network dataset separately (for example: \"--network-concurrency=4\").")
+                .display_order(42)
+                .action(ArgAction::Set)
+        )
+        .arg(
+            Arg::new("MATERIALIZE")
+                .long("materialize")
+                .aliases(&["farm", "link-farm"])
+                .value_name("DIR")
+                .value_parser(clap::value_parser!(PathBuf))
+                .require_equals(true)
+                .help("create, within the directory specified, a hard link (or, when a hard link is not possible, a copy, which may be a reflink clone) of every unique snapshot version of the input file/s, \
+                named as the file name followed by the version's modify time (for example: \"notes.txt.2023-01-15-00:00:01\"), \
+                so that external diff and merge tools may operate upon all the versions of a file in a single, flat directory. \
+                Existing files within the directory will not be overwritten.")
+                .conflicts_with_all(&["BROWSE", "SELECT", "RESTORE", "PRUNE", "ROLL_FORWARD"])
+                .display_order(43)
+                .action(ArgAction::Set)
+        )
+        .arg(
+            Arg::new("DIR_DIFF")
+                .long("dir-diff")
+                .aliases(&["tree-diff"])
+                .value_name("SNAP_OR_DATE")
+                .require_equals(true)
+                .help("compare the live directory/ies specified to the same directory tree upon a snapshot, and list each file added, removed, or modified since that snapshot, \
+                with its change in size, and its modify time. The snapshot may be specified by its name (for example: \"--dir-diff=autosnap_2023-01-15_00:00:01_daily\"), \
+                or by a date (\"YYYY-MM-DD\" or \"YYYY-MM-DD HH:MM:SS\"), in which case httm will use the most recent snapshot taken at or before that date.")
+                .conflicts_with_all(&["BROWSE", "SELECT", "RESTORE", "PRUNE", "ROLL_FORWARD", "MATERIALIZE"])
+                .display_order(44)
+                .action(ArgAction::Set)
+        )
+        .arg(
+            Arg::new("GENERATIONS")
+                .long("generations")
+                .aliases(&["generation", "gen"])
+                .help("display a marker between file versions which, though they share the same name, are different files, as determined by their inode numbers and birth times. \
+                This distinguishes a file which was truncated and rewritten in place, from a different file which simply reused the name (as when an editor saves to a new file and renames it). \
+                Note: This option is only meaningful upon filesystems, like ZFS and btrfs, which preserve inode numbers across snapshots.")
+                .display_order(45)
+                .action(ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("DATASET")
+                .long("dataset")
+                .value_name("NAME")
+                .require_equals(true)
+                .help("when taking or pruning snapshots, operate upon the ZFS dataset specified, rather than those datasets inferred from the input file/s. \
+                This argument may be specified more than once to target multiple datasets (for example: \"--dataset=rpool/home --dataset=data/media\"). \
+                Note: This option is only available with SNAPSHOT or PRUNE.")
+                .display_order(46)
+                .action(ArgAction::Append)
+        )
+        .arg(
+            Arg::new("RECURSIVE_DATASETS")
+                .long("recursive-datasets")
+                .help("when used with DATASET, also operate upon all child datasets of each dataset specified. \
+                With SNAPSHOT, this takes a recursive snapshot (\"zfs snapshot -r\"), and, with PRUNE, this includes the snapshots of all child datasets.")
+                .requires("DATASET")
+                .display_order(47)
+                .action(ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("ARCHIVE_DIR")
+                .long("archive-dir")
+                .aliases(&["archives", "send-archives"])
+                .value_name("DIR")
+                .value_parser(clap::value_parser!(PathBuf))
+                .require_equals(true)
+                .help("index a directory of btrfs send streams (for example, btrbk archives), by replaying only the metadata of each stream, \
+                and list those versions each stream contains alongside the versions upon live snapshots. \
+                Versions within a stream are displayed as if the stream file were a directory (for example: \"/archives/home.20230115.btrfs/notes.txt\"). \
+                Note: The streams should be of the subvolume upon which the input file/s reside, and must be uncompressed and unencrypted. \
+                Archived versions are only available in the non-interactive display modes.")
+                .conflicts_with_all(&["BROWSE", "SELECT", "RESTORE", "PRUNE", "SNAPSHOT", "ROLL_FORWARD", "MATERIALIZE", "DIR_DIFF"])
+                .display_order(48)
+                .action(ArgAction::Set)
+        )
+        .arg(
+            Arg::new("LOAD_KEY")
+                .long("load-key")
+                .help("before searching for versions, load the key for, and mount, any encrypted ZFS dataset which is locked (its key is not loaded), \
+                and upon which the input file/s reside, or, for a directory, which resides beneath it. \
+                Without this option, httm will only notice that such versions may exist. \
+                Note: This is a ZFS only option which requires either superuser or 'zfs allow' load-key and mount privileges.")
+                .display_order(49)
+                .action(ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("PRIVILEGED_HELPER")
+                .long("privileged-helper")
+                .aliases(&["helper", "delegate"])
+                .value_name("CMD")
+                .num_args(0..=1)
+                .default_missing_value(DEFAULT_PRIVILEGED_HELPER)
+                .require_equals(true)
+                .help("snapshots, like btrfs snapshots, are often readable only by root. Instead of running httm as root, \
+                delegate only those operations upon snapshots for which permission is denied (finding snapshots, reading snapshot file metadata, \
+                and reading snapshot files, for previews and restores) to a helper command which runs what follows it with privileges. \
+                This argument optionally takes a value for the helper command. The default helper is \"sudo -n\" (for example: \"--privileged-helper=pkexec\"). \
+                The helper must never prompt for a password, as it would be hidden behind the interactive view. \
+                The helper may also be specified by the HTTM_PRIVILEGED_HELPER environment variable.")
+                .display_order(50)
+                .action(ArgAction::Set)
+        )
+        .arg(
+            Arg::new("HISTOGRAM")
+                .long("histogram")
+                .aliases(&["sparkline", "churn"])
+                .default_missing_value("auto")
+                .value_parser(["auto", "day", "week", "month"])
+                .num_args(0..=1)
+                .require_equals(true)
+                .help("display a sparkline, and a bar chart, of the number of unique versions of the input file/s, per day, per week, or per month, \
+                for an at-a-glance picture of when a file churned. \
+                This argument optionally takes a value. The default value, \"auto\", will choose the period according to the span of time the versions cover. \
+                When the output is not pretty, the period and number of versions are printed, tab delimited, one period per line.")
+                .conflicts_with_all(&["NUM_VERSIONS", "LAST_SNAP", "BROWSE", "SELECT", "RESTORE", "RECURSIVE", "SNAPSHOT", "PRUNE", "JSON"])
+                .display_order(51)
+                .action(ArgAction::Set)
+        )
+        .arg(
+            Arg::new("LIST_DATASETS")
+                .long("list-datasets")
+                .aliases(&["inventory", "list-mounts"])
+                .help("display every dataset httm has discovered, by mount point, with its source, filesystem type, mount type (local or network), \
+                and the number and names of its snapshots. Useful for determining why httm cannot find the versions of a file upon a dataset. \
+                Output may be formatted as JSON with the JSON option.")
+                .conflicts_with_all(&["BROWSE", "SELECT", "RESTORE", "PRUNE", "SNAPSHOT", "ROLL_FORWARD", "NUM_VERSIONS", "HISTOGRAM", "MATERIALIZE", "DIR_DIFF"])
+                .display_order(52)
+                .action(ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("MOUNT_BACKEND")
+                .long("mount-backend")
+                .value_parser(["proc", "mountinfo", "libmount", "cmd"])
+                .require_equals(true)
+                .help("choose the source from which httm enumerates the mounts upon the system. \
+                \"proc\" reads the mount table at /proc/mounts (or /etc/mnttab), \
+                \"mountinfo\" reads the more complete mount table at /proc/self/mountinfo, \
+                \"libmount\" reads the mount table via the C library (getmntent(3) on Linux, or getmntinfo(3) on Mac OS and FreeBSD), \
+                and \"cmd\" parses the output of the 'mount' command. \
+                By default, httm will read /proc/mounts or /etc/mnttab, if either exists, and otherwise, will read the mount table via the C library, \
+                falling back to the 'mount' command, which may be less robust against localized or unusual output.")
+                .display_order(53)
+                .action(ArgAction::Set)
+        )
+        .arg(
+            Arg::new("CHECK_ALIASES")
+                .long("check-aliases")
+                .help("validate each alias configured via MAP_ALIASES (or REMOTE_DIR and LOCAL_DIR, or their environment variables). \
+                For each alias, httm will report whether the local and snapshot directories exist, which filesystem type was detected, \
+                whether the snapshot directory is a mount httm knows, and how many snapshots were found (with a sample of their names). \
+                For each path specified (or the working directory), httm will also report which alias, if any, claimed that path. \
+                httm exits with an error if any alias is misconfigured.")
+                .conflicts_with_all(&["BROWSE", "SELECT", "RESTORE", "PRUNE", "SNAPSHOT", "ROLL_FORWARD", "NUM_VERSIONS", "HISTOGRAM", "MATERIALIZE", "DIR_DIFF", "LIST_DATASETS"])
+                .display_order(54)
+                .action(ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("SI")
+                .long("si")
+                .help("display file sizes in SI units, in powers of 1000 (like kB and MB), instead of in binary units, in powers of 1024 (like KiB and MiB).")
+                .display_order(55)
+                .action(ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("LOCALE")
+                .long("locale")
+                .default_missing_value("")
+                .num_args(0..=1)
+                .require_equals(true)
+                .help("display file sizes with the thousands and decimal separators of a locale (for example: \"--locale=de_DE\" displays \"1.023 bytes\" and \"1,5 KiB\"). \
+                If no value is specified, the locale is read from the LC_ALL, LC_NUMERIC, or LANG environment variables.")
+                .display_order(56)
+                .action(ArgAction::Set)
+        )
+        .arg(
+            Arg::new("DATE_FORMAT")
+                .long("date-format")
+                .require_equals(true)
+                .help("display dates according to a custom format description, \
+                for example: \"--date-format=[year]-[month]-[day] [hour]:[minute]:[second]\". \
+                See the documentation of the Rust 'time' crate's format descriptions for every component available.")
+                .display_order(57)
+                .action(ArgAction::Set)
+        )
+        .arg(
+            Arg::new("JSON_ERRORS")
+                .long("json-errors")
+                .help("print errors, warnings, and notices to stderr as JSON, one object per line, \
+                each with a level, a stable code (like \"not_found\" or \"no_datasets\"), a message, and the path and dataset concerned, if any. \
+                Errors, warnings, and notices are always printed as JSON when the JSON option is specified.")
+                .display_order(58)
+                .action(ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("BACKGROUND")
+                .long("background")
+                .help("when used with RESTORE, once the user has consented, submit each restore as a job to a queue, and return immediately, instead of waiting for the copy to complete. \
+                Queued jobs are run one at a time, by a worker process detached from the terminal, so a large restore may continue after the terminal session has ended. \
+                The state of each job is kept in the \"httm/jobs\" directory of XDG_STATE_HOME (or \"~/.local/state\"). \
+                In \"guard\" mode, the precautionary snapshot is taken when the job is submitted, and, should the job fail, httm will attempt to roll back to that snapshot. \
+                Has no effect upon a \"merge\".")
+                .display_order(59)
+                .action(ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("JOBS")
+                .long("jobs")
+                .help("display each restore job submitted with BACKGROUND, its id, its status (queued, running, completed, failed, or cancelled), \
+                when it was submitted, and its source and target. Output may be formatted as JSON with the JSON option.")
+                .conflicts_with_all(&["BROWSE", "SELECT", "RESTORE", "PRUNE", "SNAPSHOT", "ROLL_FORWARD", "NUM_VERSIONS", "HISTOGRAM", "MATERIALIZE", "DIR_DIFF", "LIST_DATASETS", "CHECK_ALIASES"])
+                .display_order(60)
+                .action(ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("CANCEL_JOB")
+                .long("cancel-job")
+                .require_equals(true)
+                .value_name("JOB_ID")
+                .help("cancel the restore job with the id specified. A queued job is simply removed from the queue. \
+                A running job is stopped, and its target may be left incomplete.")
+                .conflicts_with_all(&["BROWSE", "SELECT", "RESTORE", "PRUNE", "SNAPSHOT", "ROLL_FORWARD", "NUM_VERSIONS", "HISTOGRAM", "MATERIALIZE", "DIR_DIFF", "LIST_DATASETS", "CHECK_ALIASES", "JOBS"])
+                .display_order(61)
+                .action(ArgAction::Set)
+        )
+        .arg(
+            Arg::new("RUN_JOB")
+                .long("run-job")
+                .require_equals(true)
+                .hide(true)
+                .help("run the restore job with the id specified. Used internally by the worker process which BACKGROUND spawns.")
+                .conflicts_with_all(&["BROWSE", "SELECT", "RESTORE", "JOBS", "CANCEL_JOB"])
+                .display_order(62)
+                .action(ArgAction::Set)
+        )
+        .arg(
+            Arg::new("PROTECTION_REPORT")
+                .long("protection-report")
+                .aliases(&["coverage", "coverage-report"])
+                .help("for each directory specified (or the working directory), search the directory tree, and report which files reside upon datasets with snapshots, \
+                how many snapshots cover those files, and the age of the newest such snapshot, as well as which files reside upon unsupported filesystems, \
+                or upon datasets without any snapshots at all. Unsupported filesystems mounted beneath a directory specified are listed, but not searched. \
+                Snapshot ages are most accurate with SNAP_CREATION. Output may be formatted as JSON with the JSON option.")
+                .conflicts_with_all(&["BROWSE", "SELECT", "RESTORE", "PRUNE", "SNAPSHOT", "ROLL_FORWARD", "NUM_VERSIONS", "HISTOGRAM", "MATERIALIZE", "DIR_DIFF", "LIST_DATASETS", "CHECK_ALIASES", "JOBS", "CANCEL_JOB"])
+                .display_order(63)
+                .action(ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("INCLUDE_SNAP_MOUNTS")
+                .long("include-snap-mounts")
+                .default_missing_value("")
+                .num_args(0..=1)
+                .require_equals(true)
+                .help("by default, httm excludes snapshot mounts from the datasets it searches: any mount within a \".zfs\" directory, or within a btrfs Snapper \".snapshots\" directory, \
+                any ZFS snapshot (like \"pool/data@snap\"), or btrfs snapshot subvolume, mounted elsewhere, and any mount beneath such a mount. \
+                For users who intentionally mount snapshots, this option includes those snapshot mounts as datasets. \
+                If no value is specified, every snapshot mount is included. Otherwise, only the comma delimited list of mount points specified is included \
+                (for example: \"--include-snap-mounts=/mnt/yesterday,/mnt/last_week\").")
+                .display_order(64)
+                .action(ArgAction::Set)
+        )
+        .arg(
+            Arg::new("RECONSTRUCT")
+                .long("reconstruct")
+                .aliases(&["reconstruct-dir", "deleted-dir"])
+                .help("reconstruct the most complete listing of the directory/ies specified, which may since have been deleted, \
+                by taking the union of the entries of that directory tree across every snapshot. For each entry, httm displays whether it still exists, \
+                its size and modify time, the last snapshot upon which it was seen, and the number of snapshots upon which it was seen, \
+                so the user may see what was lost before restoring selectively. In RAW or ZEROS mode, the path to the last version seen is displayed, \
+                and output may be formatted as JSON with the JSON option.")
+                .conflicts_with_all(&["BROWSE", "SELECT", "RESTORE", "PRUNE", "SNAPSHOT", "ROLL_FORWARD", "NUM_VERSIONS", "HISTOGRAM", "MATERIALIZE", "DIR_DIFF", "LIST_DATASETS", "CHECK_ALIASES", "JOBS", "CANCEL_JOB", "PROTECTION_REPORT"])
+                .display_order(65)
+                .action(ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("SKIP_SPACE_CHECK")
+                .long("skip-space-check")
+                .aliases(&["no-space-check"])
+                .requires("RESTORE")
+                .help("by default, before a restore, httm computes the bytes the restore requires (the full size of the snapshot version, as a new copy may be written before the live version it replaces is removed), \
+                compares that to the space available upon the destination filesystem, and will refuse to restore, if the space available is insufficient. \
+                Here, you may disable this check, and restore anyway, as, for instance, when the destination filesystem compresses or deduplicates data.")
+                .display_order(66)
+                .action(ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("PREVIEW_POSITION")
+                .long("preview-position")
+                .help("place the preview window of the interactive views at the top (the default), bottom, left, or right of the terminal. \
+                On narrow terminals, \"top\" or \"bottom\" may be preferable. \
+                \"hidden\" starts with the preview window hidden. Whatever the position, the preview window may be shown or hidden at runtime with alt-v.")
+                .value_parser(["top", "bottom", "left", "right", "hidden"])
+                .display_order(67)
+                .action(ArgAction::Set)
+        )
+        .arg(
+            Arg::new("PREVIEW_SIZE")
+                .long("preview-size")
+                .value_name("PERCENT")
+                .help("the size of the preview window of the interactive views, as a percentage of the terminal, between 1 and 99. The default is 50.")
+                .display_order(68)
+                .action(ArgAction::Set)
+        )
+        .arg(
+            Arg::new("PREVIEW_WRAP")
+                .long("preview-wrap")
+                .help("wrap long lines within the preview window of the interactive views. Line wrapping may also be toggled at runtime with alt-w.")
+                .display_order(69)
+                .action(ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("WITH_FIELDS")
+                .long("with-fields")
+                .value_name("FIELDS")
+                .help("in RAW or ZEROS output modes, append tab delimited metadata fields after each path, so that shell pipelines may filter versions by age or size without re-reading the snapshot paths. \
+                Optionally, specify which fields, and in which order, as a comma delimited list of: \"mtime\" (the modify time in seconds since the epoch), \"size\" (in bytes), \
+                \"snap\" (the snapshot name, or \"-\" for a live version), \"type\" (the type detected from the file's leading bytes, see FILE_TYPE), \
+                \"id\" (the version's stable ID, see VERSION_ID), and \"note\" (the note upon the version, or \"-\" for none, see NOTE). The default is all fields: \"mtime,size,snap\".")
+                .value_parser(["mtime", "modify-time", "size", "snap", "snap-name", "type", "file-type", "id", "version-id", "note"])
+                .value_delimiter(',')
+                .num_args(0..)
+                .require_equals(true)
+                .default_missing_value("mtime,size,snap")
+                .display_order(70)
+                .action(ArgAction::Append)
+        )
+        .arg(
+            Arg::new("EXCLUDE_DATASET")
+                .long("exclude-dataset")
+                .value_name("MOUNT_OR_NAME")
+                .help("never search the dataset specified, by mount point (like \"/srv/media\") or by name (like \"tank/media\"), nor any dataset beneath it. \
+                Neither the dataset's snapshots nor its contents are read, which is useful for slow or irrelevant datasets, like a huge media pool on spinning disks. \
+                Multiple datasets may be specified by repeating the option, or as a comma delimited list. \
+                A ZFS dataset may also be excluded for every user, by setting the user property \"httm:ignore=true\" upon the dataset, which, like any user property, is inherited by the datasets beneath. httm reads these properties at most once a minute, so a property set, or unset, takes effect within a minute.")
+                .value_delimiter(',')
+                .display_order(71)
+                .action(ArgAction::Append)
+        )
+        .arg(
+            Arg::new("INCLUDE_ONLY_DATASET")
+                .long("include-only-dataset")
+                .value_name("MOUNT_OR_NAME")
+                .help("search only the dataset specified, by mount point (like \"/home\") or by name (like \"rpool/home\"), and any dataset beneath it, \
+                as if every other dataset were specified with EXCLUDE_DATASET. \
+                Multiple datasets may be specified by repeating the option, or as a comma delimited list.")
+                .value_delimiter(',')
+                .display_order(72)
+                .action(ArgAction::Append)
+        )
+        .arg(
+            Arg::new("INCLUDE_CHILD_DATASETS")
+                .long("include-child-datasets")
+                .aliases(&["child-datasets"])
+                .help("when the directory requested spans multiple datasets (like \"/home\", with a dataset per user), also search the snapshots of each child dataset mounted beneath the directory. \
+                Without RECURSIVE, the contents and deleted files of each child dataset are searched, as well as those of the directory requested. \
+                With ONE_FILESYSTEM, the recursive search descends into child datasets, though it still excludes any other filesystem.")
+                .display_order(73)
+                .action(ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("CHECK_SNAP_LIMITS")
+                .long("check-snap-limits")
+                .help("before httm takes any snapshot (with SNAPSHOT, or before a guarded restore or a roll forward), check whether any dataset to be snapshot has reached its ZFS \"snapshot_limit\", \
+                and, if so, refuse to take any snapshot. This check is also made whenever MAX_SNAPSHOTS or MAX_POOL_CAPACITY is specified.")
+                .display_order(74)
+                .action(ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("MAX_SNAPSHOTS")
+                .long("max-snapshots")
+                .value_name("COUNT")
+                .help("before httm takes any snapshot, check the number of snapshots of each dataset to be snapshot, and refuse to take any snapshot, \
+                if any dataset already has this number of snapshots, or more. This prevents runaway snapshot creation, as from a script which snapshots in a loop.")
+                .display_order(75)
+                .action(ArgAction::Set)
+        )
+        .arg(
+            Arg::new("MAX_POOL_CAPACITY")
+                .long("max-pool-capacity")
+                .value_name("PERCENT")
+                .help("before httm takes any snapshot, check the capacity of each pool containing a dataset to be snapshot, \
+                and refuse to take any snapshot, if any pool is already this percent full, or more.")
+                .display_order(76)
+                .action(ArgAction::Set)
+        )
+        .arg(
+            Arg::new("WARN_SNAP_LIMITS")
+                .long("warn-snap-limits")
+                .help("when a snapshot limit, MAX_SNAPSHOTS, or MAX_POOL_CAPACITY threshold is reached, only warn, and take the snapshot anyway, instead of refusing.")
+                .display_order(77)
+                .action(ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("EMIT_SHELL")
+                .long("emit-shell")
+                .value_parser(["bash", "zsh", "fish"])
+                .num_args(0..=1)
+                .default_missing_value("bash")
+                .require_equals(true)
+                .help("instead of the ordinary output, print, for each version, a 'diff' command comparing the live version to the snapshot version, \
+                and a 'cp' command which would restore the snapshot version alongside the live version, named with the snapshot name as a suffix. \
+                Paths are quoted for the shell specified, \"bash\" (the default), \"zsh\", or \"fish\", so the commands may be reviewed, and then run, by the user, and httm itself writes nothing. \
+                With SELECT, commands are printed for only the versions selected.")
+                .conflicts_with_all(&["JSON", "RESTORE", "NUM_VERSIONS", "HISTOGRAM"])
+                .display_order(78)
+                .action(ArgAction::Set)
+        )
+        .arg(
+            Arg::new("CACHE_METADATA")
+                .long("cache-metadata")
+                .help("cache the metadata of files upon ZFS snapshots, and whether those files exist at all, across runs, \
+                so repeated queries upon the same files need not stat each snapshot version again.  \
+                Because snapshots are immutable, cached entries remain valid until the list of snapshots for a dataset changes, \
+                at which point the cache for that dataset is discarded.  \
+                The cache is stored in \"$XDG_CACHE_HOME/httm/metadata\" (or \"$HOME/.cache/httm/metadata\").")
+                .display_order(79)
+                .action(ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("INDEX_DIR")
+                .long("index-dir")
+                .value_name("DIR")
+                .value_parser(clap::value_parser!(PathBuf))
+                .help("index the snapshot versions of the specified directory, a directory one queries repeatedly.  \
+                Upon first query, httm reads the directory upon each ZFS snapshot once, and records which snapshots contain each file name. \
+                Thereafter, deleted file searches within the directory, and version listings for files within the directory, use the index, \
+                and only stat versions upon snapshots known to contain the file, until the list of snapshots for the dataset changes. \
+                The index is stored in \"$XDG_CACHE_HOME/httm/index\" (or \"$HOME/.cache/httm/index\").  \
+                May be specified multiple times, for multiple directories.")
+                .display_order(80)
+                .action(ArgAction::Append)
+        )
+        .arg(
+            Arg::new("TOP")
+                .long("top")
+                .aliases(&["watch", "dashboard"])
+                .help("display a full screen dashboard of the most recently modified files within the directory specified (or the working directory), \
+                refreshed every 2 seconds.  For each file, httm displays whether the live version is protected (the same as the version upon the latest snapshot), \
+                modified since the latest snapshot, or unprotected (upon no snapshot at all), as well as the number of snapshot versions, and the age of the latest snapshot version. \
+                Press 'q' or 'esc' to quit.  Because the dashboard may run for a long time, httm restarts itself, in place, upon SIGHUP, \
+                re-reading the config file and aliases, and re-enumerating datasets, so pool changes need not require a restart by hand. \
+                Upon Linux, httm also watches the mount table, and restarts itself, just the same, whenever a dataset is mounted or unmounted, as when a pool is imported or exported, once the mount table has settled for a couple of seconds. \
+                Upon SIGUSR1, httm does the same, but also discards its cached verdicts upon network mounts, so each is probed again, and its cached snapshot metadata, so each snapshot path is read again.")
+                .conflicts_with_all(&["BROWSE", "SELECT", "RESTORE", "PRUNE", "SNAPSHOT", "ROLL_FORWARD", "NUM_VERSIONS", "HISTOGRAM", "MATERIALIZE", "DIR_DIFF", "LIST_DATASETS", "CHECK_ALIASES", "JOBS", "CANCEL_JOB", "PROTECTION_REPORT", "RECONSTRUCT", "JSON"])
+                .display_order(81)
+                .action(ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("CHANGED_ATTR")
+                .long("changed-attr")
+                .aliases(&["changed-attrs"])
+                .value_parser(["owner", "mode", "xattr"])
+                .value_delimiter(',')
+                .num_args(1..)
+                .require_equals(true)
+                .help("display only those snapshot versions whose attributes, as specified, differ from those of the live version, \
+                such as to answer: \"When did root take ownership of this file?\"  Attributes may be \"owner\" (the user or group owner), \"mode\" (the permissions), \
+                or \"xattr\" (the extended attributes and their values, only in a build with the \"xattrs\" feature, the default), and multiple attributes may be specified delimited by a comma, ',', \
+                in which case a snapshot version is displayed if any attribute specified differs.  Versions of files without a live version are not displayed.")
+                .conflicts_with_all(&["NUM_VERSIONS", "HISTOGRAM"])
+                .display_order(82)
+                .action(ArgAction::Append)
+        )
+        .arg(
+            Arg::new("EMIT_CHECKSUMS")
+                .long("emit-checksums")
+                .aliases(&["checksums"])
+                .value_parser(["sha256", "b3", "blake3"])
+                .num_args(0..=1)
+                .default_missing_value("sha256")
+                .require_equals(true)
+                .help("instead of the ordinary output, print a checksum manifest of the unique snapshot versions of the files specified, \
+                in the format of \"sha256sum\" (the default), or of \"b3sum\" (if \"b3\" is specified), that is, the checksum, two spaces, and the path of the snapshot version. \
+                Such a manifest may be verified with \"sha256sum --check\" or \"b3sum --check\", or consumed by other verification and deduplication tools. \
+                Only regular files are listed.")
+                .conflicts_with_all(&["JSON", "EMIT_SHELL", "NUM_VERSIONS", "HISTOGRAM", "LAST_SNAP"])
+                .display_order(83)
+                .action(ArgAction::Set)
+        )
+        .arg(
+            Arg::new("MIN_SIZE")
+                .long("min-size")
+                .value_name("SIZE")
+                .help("display only those snapshot versions, and, in any deleted mode, only those deleted files, at least as large as the size specified. \
+                A size is a number of bytes, with an optional unit suffix of K, M, G, or T (eg. \"512K\", \"1.5M\", or \"2GiB\"), where each unit is a power of 1024. \
+                Directories are never excluded, so a recursive deleted search still searches beneath them.")
+                .display_order(84)
+                .action(ArgAction::Set)
+        )
+        .arg(
+            Arg::new("MAX_SIZE")
+                .long("max-size")
+                .value_name("SIZE")
+                .help("display only those snapshot versions, and, in any deleted mode, only those deleted files, no larger than the size specified. \
+                A size is specified as with MIN_SIZE.")
+                .display_order(85)
+                .action(ArgAction::Set)
+        )
+        .arg(
+            Arg::new("FILE_TYPE")
+                .long("file-type")
+                .aliases(&["detect-type", "magic"])
+                .help("display the type of each version, as detected from its leading bytes (its \"magic\" number), in a column before its path, such as \"image/png\", or \"application/octet-stream\" for unrecognized binary data. \
+                Useful for finding the version where a file became corrupted or truncated, as such a version will usually no longer be recognized as its original type. \
+                When WITH_FIELDS is specified, this type may also be requested as the \"type\" field.")
+                .conflicts_with_all(&["JSON", "NUM_VERSIONS", "LAST_SNAP"])
+                .display_order(86)
+                .action(ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("ENCODING")
+                .long("encoding")
+                .value_name("ENCODING")
+                .help("the text encoding of the files to preview, to compare, or to print in the select view's \"contents\" mode, which are then transcoded to UTF-8 for display. \
+                By default, httm detects UTF-8, UTF-16 (with or without a byte order mark), and Latin-1 text, so that, for instance, a config recovered from Windows, which is often UTF-16, is not displayed as mojibake. \
+                Binary files are never transcoded. Possible values are: \"auto\" (the default), \"utf-8\", \"utf-16le\", \"utf-16be\", and \"latin1\".")
+                .require_equals(true)
+                .display_order(87)
+                .action(ArgAction::Set)
+        )
+        .arg(
+            Arg::new("TRANSCODE")
+                .long("transcode")
+                .value_name("FILE")
+                .require_equals(true)
+                .hide(true)
+                .help("print the file specified transcoded to UTF-8. Used internally by the preview and compare scripts.")
+                .value_parser(clap::value_parser!(PathBuf))
+                .conflicts_with_all(&["BROWSE", "SELECT", "RESTORE", "JOBS", "RUN_JOB", "CANCEL_JOB"])
+                .display_order(88)
+                .action(ArgAction::Set)
+        )
+        .arg(
+            Arg::new("LIMIT")
+                .long("limit")
+                .value_name("N")
+                .help("in a non-interactive deleted search, display only the first N deleted files found, after they are sorted (see SORT_DELETED). \
+                By default, the newest deletions are displayed first.")
+                .value_parser(clap::value_parser!(usize))
+                .requires("DELETED")
+                .display_order(89)
+                .action(ArgAction::Set)
+        )
+        .arg(
+            Arg::new("SORT_DELETED")
+                .long("sort-deleted")
+                .value_name("KEY")
+                .help("in a non-interactive deleted search, sort the deleted files found, before they are displayed, so the probably relevant files are displayed first, instead of in the order they were found. \
+                Deleted files may be sorted by \"mtime\", newest first, as of the last snapshot version of each deleted file (the default), by \"size\", largest first, or by \"path\". \
+                Because every deleted file must be found before any can be sorted, nothing is displayed until the search is complete.")
+                .value_parser(["mtime", "size", "path"])
+                .requires("DELETED")
+                .display_order(90)
+                .action(ArgAction::Set)
+        )
+        .arg(
+            Arg::new("OUTPUT")
+                .long("output")
+                .value_name("FILE")
+                .help("write results, in any output format, to the file specified, instead of to stdout, so long reports are not lost to terminal scrollback, and scripts may avoid shell redirection. \
+                Results are written to a temporary file, which replaces the file specified only once httm has completed successfully. Upon an error, any previous file is left as it was.")
+                .value_parser(clap::value_parser!(PathBuf))
+                .display_order(91)
+                .action(ArgAction::Set)
+        )
+        .arg(
+            Arg::new("TEE")
+                .long("tee")
+                .help("when an OUTPUT file is specified, also print results to stdout.")
+                .requires("OUTPUT")
+                .display_order(92)
+                .action(ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("RESTORE_MTIME")
+                .long("restore-mtime")
+                .value_name("POLICY")
+                .help("in any restore mode, specify the modify time of a restored file, as build systems and sync tools care which they see. \
+                \"source\" gives a restored file the modify time of the snapshot version restored, \"now\" gives it the current time, \
+                and \"preserve-live\" keeps the modify time of the live file overwritten (or, if no file was overwritten, that of the snapshot version). \
+                By default, a restore which preserves attributes gives the restored file the modify time of the snapshot version, and any other restore gives it the time of the copy.")
+                .value_parser(["source", "now", "preserve-live"])
+                .require_equals(true)
+                .requires("RESTORE")
+                .display_order(93)
+                .action(ArgAction::Set)
+        )
+        .arg(
+            Arg::new("VERSION_IDS")
+                .long("version-ids")
+                .aliases(&["ids"])
+                .help("display a short ID for each snapshot version, in a column before its path. \
+                An ID is a hash of the version's snapshot path, so the same version always has the same ID, and a later httm command may refer to the version by its ID (see VERSION_ID), \
+                making it possible to script a \"list, then act\" workflow without selecting the version again. \
+                When WITH_FIELDS is specified, this ID may also be requested as the \"id\" field.")
+                .conflicts_with_all(&["JSON", "NUM_VERSIONS", "LAST_SNAP"])
+                .display_order(94)
+                .action(ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("VERSION_ID")
+                .long("version-id")
+                .value_name("ID")
+                .help("given the IDs of snapshot versions, as displayed by VERSION_IDS, and the same paths given when the IDs were displayed, act upon only those versions (see VERSION_ACTION). \
+                Multiple IDs may be specified as a comma delimited list. An ID which matches no version, or more than one version, of the paths given is an error.")
+                .value_delimiter(',')
+                .require_equals(true)
+                .conflicts_with_all(&["BROWSE", "SELECT", "RESTORE", "DELETED", "RECURSIVE", "JSON", "NUM_VERSIONS", "LAST_SNAP", "PRUNE"])
+                .display_order(95)
+                .action(ArgAction::Append)
+        )
+        .arg(
+            Arg::new("VERSION_ACTION")
+                .long("version-action")
+                .value_name("ACTION")
+                .help("the action to take upon each version specified by VERSION_ID: \"path\" prints the version's snapshot path (the default), \"cat\" prints its contents, \
+                \"diff\" prints a unified diff of the version against its live version, \"restore\" copies the version alongside its live version, named with the snapshot's name as a suffix, never overwriting any file, \
+                and \"splice\" replaces only the RANGE specified of the live version with the same RANGE of the version (see RANGE).")
+                .value_parser(["path", "cat", "diff", "restore", "splice"])
+                .require_equals(true)
+                .requires("VERSION_ID")
+                .display_order(96)
+                .action(ArgAction::Set)
+        )
+        .arg(
+            Arg::new("PRESERVE_ORDER")
+                .long("preserve-order")
+                .aliases(&["input-order"])
+                .help("display results for the paths given in the order they were given, instead of in sorted order, so that output may be paired, path by path, with the input list by scripts. \
+                A path given more than once is displayed only once, in the position it was first given. The versions of each path are always displayed in the same order, oldest first.")
+                .display_order(97)
+                .action(ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("TABLE")
+                .long("table")
+                .help("display versions as an aligned table, with columns for the modify time, size, snapshot name, and path of each version, drawn with box drawing characters, instead of the ordinary output. \
+                When the table is wider than the terminal, the beginnings of long paths are elided, and then, if necessary, the snapshot and date columns are dropped.")
+                .conflicts_with_all(&["RAW", "ZEROS", "NOT_SO_PRETTY", "JSON", "NUM_VERSIONS", "HISTOGRAM", "LAST_SNAP", "EMIT_SHELL", "EMIT_CHECKSUMS", "SELECT", "RESTORE", "DELETED"])
+                .display_order(98)
+                .action(ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("REMOTE")
+                // "--remote" is already an alias of the deprecated REMOTE_DIR
+                .long("remote-host")
+                .aliases(&["compare-remote"])
+                .value_name("DESTINATION")
+                .help("given the same paths, list the snapshot versions found locally and on a remote host side by side, matched by snapshot name, \
+                and flag those versions found on only one side, or whose modify time or size differ, so as to verify that replication actually carries the history one believes it does. \
+                Every snapshot version is listed upon both sides, whatever the UNIQUENESS specified, as identical versions collapsed into one could not be matched by snapshot name. \
+                The remote host is specified as an ssh destination, like \"backup@nas\", and is reached with \"ssh\", non-interactively, so key based authentication is required. \
+                The remote host must have httm in its PATH, and the paths are assumed to be the same there, as they would be for a replicated dataset mounted at the same mount point.")
+                .require_equals(true)
+                .conflicts_with_all(&["BROWSE", "SELECT", "RESTORE", "DELETED", "RECURSIVE", "JSON", "RAW", "ZEROS", "NUM_VERSIONS", "LAST_SNAP", "PRUNE", "VERSION_ID", "TABLE"])
+                .display_order(99)
+                .action(ArgAction::Set)
+        )
+        .arg(
+            Arg::new("RESTORE_SPECIAL")
+                .long("restore-special")
+                .help("fifos, sockets, and device nodes found in snapshots are listed like any other version, but, as they have no contents of their own, they are never read, previewed, or compared by contents. \
+                Such special files are also not restored, and, within a directory restored, are skipped with a warning, unless this option is specified. \
+                Restoring a device node usually requires superuser privileges.")
+                .display_order(100)
+                .action(ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("PROBE_ORDER")
+                .long("probe-order")
+                .value_name("ORDER")
+                .help("the order in which the snapshots of a dataset are searched for versions: \"newest\" first (the default), \"oldest\" first, or by snapshot \"name\". \
+                Snapshots are ranked just as the versions found upon them are ranked, by creation time (see SNAP_CREATION), or else by snapshot path, and so, usually, by a snapshot name's timestamp. \
+                When newest first, searches which require only the last version of a file, like deleted searches, stop at the newest snapshot which contains the file, which, on pools with long retention, may be dramatically faster. \
+                The last version found is always the same, whatever the order.")
+                .value_parser(["newest", "oldest", "name"])
+                .require_equals(true)
+                .display_order(101)
+                .action(ArgAction::Set)
+        )
+        .arg(
+            Arg::new("WIDGET")
+                .long("widget")
+                .value_parser(["bash", "zsh", "fish"])
+                .num_args(0..=1)
+                .default_missing_value("bash")
+                .require_equals(true)
+                .help("select a snapshot version of the input file, and print only its path, quoted for the shell specified, \"bash\" (the default), \"zsh\", or \"fish\", without a trailing newline, \
+                so a shell key binding may insert the path into the command line being typed. If the input path is a directory, browse it for a file first. \
+                Metadata caching (see CACHE_METADATA) is enabled, so versions found once are found quickly thereafter. \
+                The zsh hot keys (see ZSH_HOT_KEYS) bind this to ALT-v, and the bash equivalent may be sourced from the 'httm-key-bindings.bash' script.")
+                .conflicts_with_all(&["SELECT", "RESTORE", "EMIT_SHELL", "JSON", "LAST_SNAP"])
+                .display_order(102)
+                .action(ArgAction::Set)
+        )
+        .arg(
+            Arg::new("PARENT_FALLBACK")
+                .long("parent-fallback")
+                .visible_alias("ancestor-fallback")
+                .help("when the most proximate dataset of a path has no snapshots, but an ancestor dataset does, as with a freshly created child dataset, \
+                search the snapshots of the nearest such ancestor for the path, at its older location within the ancestor, that is, where it resided before the child dataset was created. \
+                httm prints a notice whenever it falls back to an ancestor's snapshots.")
+                .display_order(103)
+                .action(ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("PROFILE")
+                .long("profile")
+                .value_name("NAME")
+                .help("use the default arguments of the named profile, a section of the config file which begins with a \"[NAME]\" line, in addition to the config file's other default arguments. \
+                A profile's arguments override the same arguments given outside of any profile. You may also set via the environment variable HTTM_PROFILE.")
+                .display_order(104)
+                .action(ArgAction::Set)
+        )
+        .arg(
+            Arg::new("MTIME_WINDOW")
+                .long("mtime-window")
+                .visible_alias("mtw")
+                .value_name("SECONDS")
+                .require_equals(true)
+                .help("treat versions whose modify times fall within a window of the given number of seconds (for example, \"--mtime-window=2\" or \"--mtime-window=0.5\") \
+                as a single version, even when their sizes differ, as when several snapshots are taken in quick succession, during a write, or a log rotation. \
+                Only the last in time version of each such cluster is shown. This option has no effect when uniqueness is \"all\".")
+                .display_order(105)
+                .action(ArgAction::Set)
+        )
+        .arg(
+            Arg::new("HOOK_PRE_RESTORE")
+                .long("hook-pre-restore")
+                .value_name("SCRIPT")
+                .value_parser(clap::value_parser!(PathBuf))
+                .require_equals(true)
+                .help("run the given script before each restore, with a JSON description of the restore (its source and destination paths, snapshot name and size) upon stdin, \
+                and the environment variable HTTM_HOOK_EVENT set to \"pre-restore\". Should the script exit non-zero, httm will not restore.")
+                .display_order(106)
+                .action(ArgAction::Set)
+        )
+        .arg(
+            Arg::new("HOOK_POST_RESTORE")
+                .long("hook-post-restore")
+                .value_name("SCRIPT")
+                .value_parser(clap::value_parser!(PathBuf))
+                .require_equals(true)
+                .help("run the given script after each restore, whether successful or not, with a JSON description of the restore and its outcome upon stdin, \
+                and the environment variable HTTM_HOOK_EVENT set to \"post-restore\". Should the script exit non-zero, httm only warns.")
+                .display_order(107)
+                .action(ArgAction::Set)
+        )
+        .arg(
+            Arg::new("HOOK_PRE_SNAP")
+                .long("hook-pre-snap")
+                .value_name("SCRIPT")
+                .value_parser(clap::value_parser!(PathBuf))
+                .require_equals(true)
+                .help("run the given script before httm takes snapshots with the SNAPSHOT option, with a JSON description of the snapshots to be taken upon stdin, \
+                and the environment variable HTTM_HOOK_EVENT set to \"pre-snap\". Should the script exit non-zero, httm will not take the snapshots.")
+                .display_order(108)
+                .action(ArgAction::Set)
+        )
+        .arg(
+            Arg::new("AUDIT_LOG")
+                .long("audit-log")
+                .value_name("PATH")
+                .value_parser(clap::value_parser!(PathBuf))
+                .require_equals(true)
+                .help("append a record, one JSON object per line, of every restore, snapshot, rollback and prune (snapshot destroy) httm performs, \
+                whether successful or not, to the log file at the given path. Each record includes a timestamp, the user, the action, and the paths and snapshots involved. \
+                Should the log file not be writable, httm will refuse to run. You may also set via the environment variable HTTM_AUDIT_LOG.")
+                .display_order(109)
+                .action(ArgAction::Set)
+        )
+        .arg(
+            Arg::new("RECOVER")
+                .long("recover")
+                .visible_alias("recover-deleted")
+                .help("a guided recovery of deleted files. Browse to a directory (beginning with the directory given, or the working directory), \
+                and httm searches the snapshots of that directory for files which existed there, but do not now. httm lists those files, grouped by the window of snapshots \
+                within which each was deleted, most recent first. Select as many as you like, pick a destination, either the original directory or another, and, \
+                after your consent, httm restores the last version of each file selected. httm never restores over a file which has since taken a deleted file's place.")
+                .conflicts_with_all(&["BROWSE", "SELECT", "RESTORE", "PRUNE", "SNAPSHOT", "ROLL_FORWARD", "NUM_VERSIONS", "HISTOGRAM", "MATERIALIZE", "DIR_DIFF", "LIST_DATASETS", "CHECK_ALIASES", "JOBS", "CANCEL_JOB", "PROTECTION_REPORT", "RECONSTRUCT", "WIDGET", "JSON"])
+                .display_order(110)
+                .action(ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("RANGE")
+                .long("range")
+                .value_name("RANGE")
+                .help("restore, or print, only part of a version, for instance, one section of a huge log, or a single stanza of a config file. \
+                Specify a range of lines, numbered from 1, as in \"lines:10-20\", or a range of bytes, numbered from 0, as in \"bytes:0-4095\". Both ends are inclusive, \
+                and an end left blank, as in \"lines:100-\", means the end of the file. With the \"cat\" VERSION_ACTION, httm prints only the range of the version. \
+                With the \"splice\" VERSION_ACTION, httm replaces the same range of the live file with the range of the version, leaving the rest of the live file as it is.")
+                .require_equals(true)
+                .requires("VERSION_ID")
+                .display_order(111)
+                .action(ArgAction::Set)
+        )
+        .arg(
+            Arg::new("RENAME")
+                .long("rename")
+                .value_name("TEMPLATE")
+                .help("name each restored copy by a template, rather than by httm's default, and restore it alongside its live version, \
+                so that a batch of copies lands beside the originals with predictable names, for instance: --rename='{stem}.{snap_date}{ext}'. \
+                The fields available are: \"name\", the file name of the live version, \"stem\", the file name without its last extension, \
+                \"ext\", the last extension, with its dot, \"snap\", the name of the snapshot, \"snap_date\", the time the snapshot was taken, and \"mtime\", the modify time of the version. \
+                Any field may be followed by a case transform, \"lower\", \"upper\", or \"title\", as in \"{stem:lower}\". Write \"{{\" and \"}}\" for literal braces. \
+                This option is available when restoring a copy, with the \"copy\", \"copy-and-preserve\", or \"pick\" RESTORE modes, the \"restore\" VERSION_ACTION, or EMIT_SHELL. \
+                httm never restores a copy over an existing file.")
+                .require_equals(true)
+                .display_order(112)
+                .action(ArgAction::Set)
+        )
+        .arg(
+            Arg::new("PROBE_SNAPSHOTS")
+                .long("probe-snapshots")
+                .help("touch each snapshot of each dataset httm has discovered, and report, per dataset, which snapshots could not be read, \
+                and which did not respond within a short timeout, as a hung automount, or an NFS or SMB share which does not export its snapshots, might not. \
+                Missing versions, on a network share, are often no more than snapshots which cannot be reached. \
+                httm exits with an error if any snapshot is unreachable or hung.")
+                .conflicts_with_all(&["BROWSE", "SELECT", "RESTORE", "PRUNE", "SNAPSHOT", "ROLL_FORWARD", "NUM_VERSIONS", "HISTOGRAM", "MATERIALIZE", "DIR_DIFF", "LIST_DATASETS", "CHECK_ALIASES", "JOBS", "CANCEL_JOB", "RUN_JOB", "PROTECTION_REPORT", "RECONSTRUCT", "TOP", "WIDGET", "RECOVER", "REMOTE", "VERSION_ID", "PRINT_NOTE", "TRANSCODE", "STAT", "TREE", "PATH_AT"])
+                .display_order(113)
+                .action(ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("NOTE")
+                .long("note")
+                .value_name("TEXT")
+                .help("attach a free text note to each version specified by VERSION_ID, like \"this is the good one\", replacing any note already upon the version. \
+                An empty note removes the note upon the version. Notes are displayed after the path of each version, in listings and in the interactive views, \
+                and above the contents of the version in the default preview. \
+                Notes are kept in \"$XDG_DATA_HOME/httm/notes.json\" (or \"$HOME/.local/share/httm/notes.json\"), or in the file specified by the HTTM_NOTES_FILE environment variable, \
+                so that a team may share a single notes file. A note is keyed by its snapshot's name, and by its dataset and its path relative to its dataset, \
+                so a note is found upon the same version from any machine which mounts the same dataset.")
+                .require_equals(true)
+                .requires("VERSION_ID")
+                .conflicts_with_all(&["VERSION_ACTION", "RANGE"])
+                .display_order(114)
+                .action(ArgAction::Set)
+        )
+        .arg(
+            Arg::new("PRINT_NOTE")
+                .long("print-note")
+                .value_name("FILE")
+                .require_equals(true)
+                .hide(true)
+                .help("print the note upon the snapshot version specified, if any. Used internally by the preview script.")
+                .value_parser(clap::value_parser!(PathBuf))
+                .conflicts_with_all(&["BROWSE", "SELECT", "RESTORE", "TRANSCODE"])
+                .display_order(115)
+                .action(ArgAction::Set)
+        )
+        .arg(
+            Arg::new("STAT")
+                .long("stat")
+                .help("display the full metadata of the live file and of each unique snapshot version of the input file/s, in aligned columns: \
+                the snapshot name, the mode, the owner and group, the size, the modify, change, and access times, and the names of any extended attributes. \
+                The dataset of each input file is displayed above its versions. Useful for inspecting how the attributes of a file have changed over time, without restoring anything. \
+                May be combined with JSON for output as JSON.")
+                .conflicts_with_all(&["BROWSE", "SELECT", "RESTORE", "DELETED", "RECURSIVE", "NUM_VERSIONS", "HISTOGRAM", "LAST_SNAP", "EMIT_SHELL", "EMIT_CHECKSUMS", "TABLE", "PRUNE", "REMOTE"])
+                .display_order(116)
+                .action(ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("TREE")
+                .long("tree")
+                .help("display the paths found as a tree, grouped hierarchically by directory, with the number of snapshot versions of each path, \
+                and the total number of snapshot versions of the paths within each directory, instead of the ordinary output. \
+                Most useful with many input files, or with RECURSIVE, where the ordinary output would run to hundreds of lines. \
+                A chain of directories, each with only a single directory within, is displayed as a single node. \
+                With NOT_SO_PRETTY, the tree is drawn with ASCII characters, and without colors.")
+                .conflicts_with_all(&["BROWSE", "SELECT", "RESTORE", "RAW", "ZEROS", "JSON", "NUM_VERSIONS", "HISTOGRAM", "LAST_SNAP", "EMIT_SHELL", "EMIT_CHECKSUMS", "TABLE", "STAT", "REMOTE"])
+                .display_order(117)
+                .action(ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("SELF_TEST")
+                .long("self-test")
+                .help("run the self test, and then exit. The self test creates a loopback file ZFS pool and a loopback file btrfs filesystem, in the temporary directory, \
+                populates each with files and snapshots, and then runs httm against them, checking that versions are found, deduplicated, read, and restored as they should be. \
+                The pools and filesystems are destroyed when the self test completes. A filesystem whose tools are not installed is skipped. \
+                The self test must be run as root, and is only available when httm is built with the \"self_test\" feature. Any config file is ignored.")
+                .display_order(118)
+                .action(ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("PARSE_MOUNTS_FROM")
+                .long("parse-mounts-from")
+                .value_name("FILE")
+                .help("parse the mount table in the file specified, instead of the mount table of this system, display each mount found, and what httm would make of it, \
+                and each line which could not be parsed, and then exit. The file may be a copy of \"/proc/mounts\", \"/proc/self/mountinfo\", or \"/etc/mnttab\", \
+                or the output of the 'mount' command, upon Linux, Mac OS, or FreeBSD, and its format is detected. \
+                Useful for diagnosing how httm sees another machine's mounts, without access to that machine. As the mounts are not upon this system, \
+                network shares are not probed for snapshots, and each mount is judged by its filesystem type alone. May be combined with JSON for output as JSON.")
+                .require_equals(true)
+                .value_parser(clap::value_parser!(PathBuf))
+                .conflicts_with_all(&["BROWSE", "SELECT", "RESTORE", "DELETED", "RECURSIVE", "SNAPSHOT", "LIST_SNAPS", "ROLL_FORWARD", "PRUNE", "FILE_MOUNT", "LAST_SNAP", "RAW", "ZEROS", "NUM_VERSIONS", "MATERIALIZE", "DIR_DIFF", "HISTOGRAM", "LIST_DATASETS", "CHECK_ALIASES", "JOBS", "CANCEL_JOB", "RUN_JOB", "PROTECTION_REPORT", "RECONSTRUCT", "TOP", "TRANSCODE", "VERSION_ID", "REMOTE", "RECOVER", "PROBE_SNAPSHOTS", "PRINT_NOTE", "STAT", "TREE", "SELF_TEST", "EMIT_RSYNC_LIST", "PATH_AT"])
+                .display_order(119)
+                .action(ArgAction::Set)
+        )
+        .arg(
+            Arg::new("OWNER_MAP")
+                .long("owner-map")
+                .value_name("MAP")
+                .help("when the owner of a restored file is preserved, map the owner, and group, of the snapshot version to another owner, and group, upon this system, \
+                as the raw user and group ids of a backup made upon another machine, like a backup tree found via MAP_ALIASES, rarely match those of the same users here. \
+                The map is a comma delimited list of entries, like \"1000:1001,g:100:1000\", where \"FROM:TO\" maps both the uid and the gid, \
+                and \"u:FROM:TO\" or \"g:FROM:TO\" map only the uid, or only the gid. FROM is a raw id of the machine which made the backup, and TO may be an id, \
+                or a user or group name, upon this system. The map may also be the path of a file, of the same entries, one or more per line, where \"#\" begins a comment. \
+                Ids not mapped are preserved as they are. The users and groups named by a POSIX ACL are mapped just the same, \
+                but an NFSv4 ACL, which is copied as is, is not, and so may still name the ids of the machine which made the backup.")
+                .require_equals(true)
+                .display_order(120)
+                .action(ArgAction::Set)
+        )
+        .arg(
+            Arg::new("REDACT")
+                .long("redact")
+                .value_name("FIELDS")
+                .help("rewrite output, and any errors or warnings, so the output may be shared publicly, as when filing a bug, without disclosing personal names. \
+                Specify which names to redact as a comma delimited list of: \"home\" (each component of a path beneath a home directory, like \"/home\" or \"/Users\", \
+                though an extension, and the location and name of a snapshot, are kept), \"hostnames\" (this machine's name, and the servers of network shares, \
+                like \"nas\" in \"//nas/share\" or \"nas:/export\"), and \"usernames\" (the names of users upon this machine). The default is all three. \
+                Each name is replaced by a hash of itself, keyed by this machine's ID, so the same name is always replaced by the same token, within the same report, \
+                and across reports from the same machine, but a reader of the output cannot recover a name by hashing likely names. Where there is no machine ID \
+                (/etc/machine-id), a random key is used, and tokens are only the same within a single report. Which paths share which directories remains plain.")
+                .value_parser(["home", "hostnames", "hostname", "hosts", "usernames", "username", "users"])
+                .value_delimiter(',')
+                .num_args(0..)
+                .require_equals(true)
+                .default_missing_value("home,hostnames,usernames")
+                .display_order(121)
+                .action(ArgAction::Append)
+        )
+        .arg(
+            Arg::new("EMIT_RSYNC_LIST")
+                .long("emit-rsync-list")
+                .aliases(&["rsync-list"])
+                .value_name("ROOT")
+                .num_args(0..=1)
+                .default_missing_value(ROOT_DIRECTORY)
+                .require_equals(true)
+                .help("instead of the ordinary output, print a list of the snapshot versions, relative to a root directory, in the format of rsync's \"--files-from\" option, \
+                so the copy itself may be handed off to rsync, with its ability to resume, and to preserve attributes, like: \"httm --emit-rsync-list=ROOT file | rsync -a --files-from=- ROOT DEST\". \
+                ROOT may be a directory, like \"/\" (the default), or the root of a single snapshot, like \"/home/.zfs/snapshot/snap_1\", \
+                or the name of a snapshot, like \"snap_1\", in which case versions are listed relative to the root of the snapshot of that name, and that root is printed to stderr. \
+                Versions outside of ROOT are not listed, and, if a snapshot name is given, and the versions lie within snapshots of that name upon more than one dataset, only those of the first are listed. \
+                With ZEROS, paths are delimited by a null byte, for rsync's \"--from0\" option. As rsync does not recurse into directories listed, unless \"-r\" is given explicitly, \
+                specify \"-r\" to copy the contents of directory versions. With SELECT, only the versions selected are listed.")
+                .conflicts_with_all(&["JSON", "RESTORE", "NUM_VERSIONS", "HISTOGRAM", "EMIT_SHELL", "EMIT_CHECKSUMS", "LAST_SNAP", "TABLE", "STAT", "TREE"])
+                .display_order(122)
+                .action(ArgAction::Set)
+        )
+        .arg(
+            Arg::new("PATH_AT")
+                .long("path-at")
+                .aliases(&["at"])
+                .value_name("SNAP_OR_DATE")
+                .require_equals(true)
+                .help("print only the path of the input file/s upon a snapshot, one per line, without listing every version, so the path may be used directly by the shell, \
+                like \"diff \"$(httm --path-at=2023-01-15 file)\" file\". The snapshot may be specified by its name (for example: \"--path-at=autosnap_2023-01-15_00:00:01_daily\"), \
+                or by a date (\"YYYY-MM-DD\" or \"YYYY-MM-DD HH:MM:SS\"), in which case httm prints the path upon the most recent snapshot taken at or before that date which contains the file. \
+                With ZEROS, paths are delimited by a null byte. May also be given as the action \"httm path-at --at=SNAP_OR_DATE file\".")
+                .conflicts_with_all(&["BROWSE", "SELECT", "RESTORE", "PRUNE", "ROLL_FORWARD", "MATERIALIZE", "DIR_DIFF", "LAST_SNAP", "NUM_VERSIONS", "HISTOGRAM", "JSON"])
+                .display_order(123)
+                .action(ArgAction::Set)
+        )
+        .arg(
+            Arg::new("IN_PLACE")
+                .long("in-place")
+                .requires("RESTORE")
+                .help("by default, a restore which overwrites an existing file writes the snapshot version to a temporary file beside the live file, \
+                and only renames it over the live file once complete, so an interrupted restore never leaves a truncated or partly written file. \
+                The renamed file is a new file, however, so any hard links to the live file keep the old contents, and blocks the live file shared with snapshots are no longer shared. \
+                Here, you may instead overwrite the live file in place, writing only the blocks which differ, at the risk of a partly written file, should the restore be interrupted.")
+                .display_order(124)
+                .action(ArgAction::SetTrue)
+        )
 }
 
 #[derive(Debug, Clone)]
@@ -588,13 +1906,58 @@ pub struct Config {
     pub opt_no_hidden: bool,
     pub opt_json: bool,
     pub opt_one_filesystem: bool,
+    pub opt_include_child_datasets: bool,
+    pub opt_snap_limits: Option<SnapLimits>,
+    pub opt_emit_shell: Option<ShellDialect>,
+    pub opt_emit_rsync_list: Option<RsyncListRoot>,
+    pub opt_cache_metadata: bool,
+    pub opt_index_dirs: Option<Vec<PathBuf>>,
+    pub opt_changed_attrs: Option<Vec<ChangedAttr>>,
+    pub opt_emit_checksums: Option<ChecksumAlgorithm>,
+    pub opt_size_filter: Option<SizeFilter>,
+    pub opt_file_type: bool,
+    pub opt_version_ids: bool,
+    pub opt_preserve_order: bool,
+    pub opt_table: bool,
+    pub opt_stat: bool,
+    pub opt_tree: bool,
+    pub opt_restore_special: bool,
+    pub opt_encoding: Option<TextEncoding>,
     pub opt_no_clones: bool,
+    pub opt_overwrite_changed: bool,
+    pub opt_background: bool,
+    pub opt_skip_space_check: bool,
+    pub opt_in_place: bool,
+    pub opt_bwlimit: Option<u64>,
+    pub opt_stash_root: Option<PathBuf>,
+    pub opt_session: Option<String>,
+    pub opt_network_concurrency: Option<usize>,
+    pub opt_generations: bool,
+    pub opt_datasets: Option<Vec<String>>,
+    pub opt_recursive_datasets: bool,
+    pub opt_privileged_helper: Option<PrivilegedHelper>,
+    pub opt_date_format: Option<String>,
+    pub number_format: NumberFormat,
     pub uniqueness: ListSnapsOfType,
+    pub probe_order: ProbeOrder,
+    pub opt_parent_fallback: bool,
+    pub opt_mtime_window: Option<Duration>,
+    pub hooks: Hooks,
+    pub opt_audit_log: Option<PathBuf>,
+    pub opt_rename_template: Option<RenameTemplate>,
+    pub opt_owner_map: Option<OwnerMap>,
     pub opt_bulk_exclusion: Option<BulkExclusion>,
+    pub opt_raw_fields: Option<Vec<RawField>>,
     pub opt_last_snap: Option<LastSnapMode>,
     pub opt_preview: Option<String>,
+    pub preview_window: PreviewWindow,
     pub opt_deleted_mode: Option<DeletedMode>,
+    pub opt_deleted_ranking: Option<DeletedRanking>,
+    pub opt_output: Option<PathBuf>,
+    pub opt_tee: bool,
+    pub opt_restore_mtime: Option<RestoreMtime>,
     pub opt_requested_dir: Option<PathBuf>,
+    pub opt_snap_classes: Option<Vec<String>>,
     pub requested_utc_offset: UtcOffset,
     pub exec_mode: ExecMode,
     pub print_mode: PrintMode,
@@ -613,10 +1976,23 @@ impl Config {
     }
 
     fn from_matches(matches: &ArgMatches) -> HttmResult<Self> {
+        if matches.get_flag("JSON_ERRORS") || matches.get_flag("JSON") {
+            Diagnostic::enable_json()
+        }
+
+        if let Some(values) = matches.get_many::<String>("REDACT") {
+            Redaction::enable(
+                values
+                    .filter_map(|value| RedactField::from_value(value))
+                    .collect(),
+            )
+        }
+
         if matches.get_flag("ZSH_HOT_KEYS") {
             install_hot_keys()?
         }
 
+        // the self test runs before any datasets are discovered, as it creates its own
         let requested_utc_offset = if matches.get_flag("UTC") {
             UtcOffset::UTC
         } else {
@@ -646,6 +2022,31 @@ impl Config {
             None
         };
 
+        let opt_raw_fields: Option<Vec<RawField>> = matches
+            .get_many::<String>("WITH_FIELDS")
+            .map(|fields| {
+                fields
+                    .map(|field| match field.as_str() {
+                        "mtime" | "modify-time" => RawField::ModifyTime,
+                        "size" => RawField::Size,
+                        "snap" | "snap-name" => RawField::SnapName,
+                        "type" | "file-type" => RawField::FileType,
+                        "id" | "version-id" => RawField::VersionId,
+                        "note" => RawField::Note,
+                        _ => unreachable!("value parser restricts the possible values"),
+                    })
+                    .collect()
+            });
+
+        if opt_raw_fields.is_some() {
+            if let PrintMode::FormattedNotPretty | PrintMode::FormattedDefault = print_mode {
+                return Err(HttmError::new(
+                    "WITH_FIELDS is only available if RAW or ZEROS are specified.",
+                )
+                .into());
+            }
+        }
+
         if let Some(BulkExclusion::NoSnap) = opt_bulk_exclusion {
             if let PrintMode::FormattedNotPretty | PrintMode::FormattedDefault = print_mode {
                 return Err(HttmError::new(
@@ -657,6 +2058,113 @@ impl Config {
 
         // force a raw mode if one is not set for no_snap mode
         let opt_one_filesystem = matches.get_flag("ONE_FILESYSTEM");
+        let opt_include_child_datasets = matches.get_flag("INCLUDE_CHILD_DATASETS");
+        let opt_widget = match matches.get_one::<String>("WIDGET").map(|inner| inner.as_str()) {
+            Some("zsh") => Some(ShellDialect::Zsh),
+            Some("fish") => Some(ShellDialect::Fish),
+            Some(_) => Some(ShellDialect::Bash),
+            None => None,
+        };
+        // a widget is run again and again upon the same files, so its searches should be as quick as possible
+        let opt_cache_metadata = matches.get_flag("CACHE_METADATA") || opt_widget.is_some();
+        let opt_size_filter = SizeFilter::new(
+            matches.get_one::<String>("MIN_SIZE").map(String::as_str),
+            matches.get_one::<String>("MAX_SIZE").map(String::as_str),
+        )?;
+        let opt_file_type = matches.get_flag("FILE_TYPE");
+        let opt_version_ids = matches.get_flag("VERSION_IDS");
+        let opt_preserve_order = matches.get_flag("PRESERVE_ORDER");
+        let opt_table = matches.get_flag("TABLE");
+        let opt_stat = matches.get_flag("STAT");
+        let opt_tree = matches.get_flag("TREE");
+        let opt_restore_special = matches.get_flag("RESTORE_SPECIAL");
+        let opt_encoding = match matches.get_one::<String>("ENCODING") {
+            Some(value) => TextEncoding::parse(value)?,
+            None => None,
+        };
+        let opt_emit_checksums = match matches
+            .get_one::<String>("EMIT_CHECKSUMS")
+            .map(|inner| inner.as_str())
+        {
+            Some("" | "sha256") => Some(ChecksumAlgorithm::Sha256),
+            Some("b3" | "blake3") => Some(ChecksumAlgorithm::Blake3),
+            _ => None,
+        };
+        let opt_changed_attrs: Option<Vec<ChangedAttr>> = matches
+            .get_many::<String>("CHANGED_ATTR")
+            .map(|attrs| {
+                attrs
+                    .map(|attr| match attr.as_str() {
+                        "owner" => Ok(ChangedAttr::Owner),
+                        "mode" => Ok(ChangedAttr::Mode),
+                        // without the xattrs feature, no extended attribute is ever read, so none could ever differ
+                        "xattr" if !cfg!(feature = "xattrs") => Err(HttmError::new(
+                            "CHANGED_ATTR \"xattr\" was specified, but this build of httm does not include the \"xattrs\" feature, \
+                            and so cannot read extended attributes. Rebuild httm with the \"xattrs\" feature, or specify another attribute.",
+                        )
+                        .with_code("xattrs_unavailable")),
+                        "xattr" => Ok(ChangedAttr::Xattr),
+                        _ => unreachable!(),
+                    })
+                    .collect::<Result<Vec<ChangedAttr>, HttmError>>()
+            })
+            .transpose()?;
+        let opt_index_dirs = match matches.get_many::<PathBuf>("INDEX_DIR") {
+            Some(index_dirs) => Some(
+                index_dirs
+                    .map(|index_dir| {
+                        index_dir.canonicalize().map_err(|err| {
+                            HttmError::new(&format!("INDEX_DIR could not be resolved: {err}"))
+                                .with_code("invalid_index_dir")
+                                .with_path(index_dir)
+                                .into()
+                        })
+                    })
+                    .collect::<HttmResult<Vec<PathBuf>>>()?,
+            ),
+            None => None,
+        };
+        let opt_emit_shell = match matches
+            .get_one::<String>("EMIT_SHELL")
+            .map(|inner| inner.as_str())
+        {
+            Some("" | "bash") => Some(ShellDialect::Bash),
+            Some("zsh") => Some(ShellDialect::Zsh),
+            Some("fish") => Some(ShellDialect::Fish),
+            _ => None,
+        };
+        let opt_emit_rsync_list = match matches
+            .get_one::<String>("EMIT_RSYNC_LIST")
+            .map(|inner| inner.as_str())
+        {
+            Some("") => Some(RsyncListRoot::Dir(PathBuf::from(ROOT_DIRECTORY))),
+            Some(value) if value.starts_with('/') => Some(RsyncListRoot::Dir(PathBuf::from(value))),
+            Some(value) => Some(RsyncListRoot::SnapName(value.to_owned())),
+            None => None,
+        };
+        let opt_snap_limits = {
+            let opt_max_snapshots = matches
+                .get_one::<String>("MAX_SNAPSHOTS")
+                .map(|value| SnapLimits::parse_max_snapshots(value))
+                .transpose()?;
+            let opt_max_pool_capacity = matches
+                .get_one::<String>("MAX_POOL_CAPACITY")
+                .map(|value| SnapLimits::parse_max_pool_capacity(value))
+                .transpose()?;
+
+            if matches.get_flag("CHECK_SNAP_LIMITS")
+                || opt_max_snapshots.is_some()
+                || opt_max_pool_capacity.is_some()
+            {
+                Some(SnapLimits {
+                    opt_max_snapshots,
+                    opt_max_pool_capacity,
+                    warn_only: matches.get_flag("WARN_SNAP_LIMITS"),
+                })
+            } else {
+                None
+            }
+        };
         let opt_recursive = matches.get_flag("RECURSIVE");
 
         let opt_exact = matches.get_flag("EXACT");
@@ -665,6 +2173,50 @@ impl Config {
         let opt_no_hidden = matches.get_flag("FILTER_HIDDEN");
         let opt_no_clones =
             matches.get_flag("NO_CLONES") || std::env::var_os("HTTM_NO_CLONE").is_some();
+        let opt_overwrite_changed = matches.get_flag("OVERWRITE_CHANGED");
+        let opt_background = matches.get_flag("BACKGROUND");
+        let opt_skip_space_check = matches.get_flag("SKIP_SPACE_CHECK");
+        let opt_in_place = matches.get_flag("IN_PLACE");
+        let opt_generations = matches.get_flag("GENERATIONS");
+        let opt_recursive_datasets = matches.get_flag("RECURSIVE_DATASETS");
+        let opt_datasets: Option<Vec<String>> = matches
+            .get_many::<String>("DATASET")
+            .map(|datasets| datasets.map(|dataset| dataset.trim_end_matches('/').to_owned()).collect());
+
+        if opt_datasets.is_some() && !(matches.contains_id("SNAPSHOT") || matches.get_flag("PRUNE")) {
+            return Err(HttmError::new(
+                "DATASET is only available with SNAPSHOT or PRUNE.",
+            )
+            .into());
+        }
+
+        if opt_datasets
+            .iter()
+            .flatten()
+            .any(|dataset| dataset.is_empty() || dataset.contains('@'))
+        {
+            return Err(HttmError::new(
+                "DATASET must be a dataset name, like \"rpool/home\", not a snapshot name or a path.",
+            )
+            .into());
+        }
+        let opt_bwlimit = matches
+            .get_one::<String>("BWLIMIT")
+            .map(|rate| Throttle::parse_rate(rate))
+            .transpose()?;
+        let opt_network_concurrency = matches
+            .get_one::<String>("NETWORK_CONCURRENCY")
+            .map(|limit| NetworkLimit::parse_limit(limit))
+            .transpose()?;
+        // STASH given without a value is the default stash directory, so any value given is a directory
+        let opt_stash_root = match matches
+            .get_many::<PathBuf>("STASH")
+            .map(|mut values| values.next())
+        {
+            Some(Some(stash_root)) => Some(stash_root.to_owned()),
+            Some(None) => Some(Stash::default_root()?),
+            None => None,
+        };
 
         let opt_last_snap = match matches.get_one::<String>("LAST_SNAP").map(|inner| inner.as_str()) {
             Some("" | "any") => Some(LastSnapMode::Any),
@@ -675,6 +2227,14 @@ impl Config {
             _ => None,
         };
 
+        let opt_histogram = match matches.get_one::<String>("HISTOGRAM").map(|inner| inner.as_str()) {
+            Some("day") => Some(HistogramBucket::Day),
+            Some("week") => Some(HistogramBucket::Week),
+            Some("month") => Some(HistogramBucket::Month),
+            Some(_) => Some(HistogramBucket::Auto),
+            None => None,
+        };
+
         let opt_num_versions = match matches.get_one::<String>("NUM_VERSIONS").map(|inner| inner.as_str()) {
             Some("" | "all") => Some(NumVersionsMode::AllNumerals),
             Some("graph") => Some(NumVersionsMode::AllGraph),
@@ -704,6 +2264,12 @@ impl Config {
             None => None,
         };
 
+        let preview_window = PreviewWindow::new(
+            matches.get_one::<String>("PREVIEW_POSITION").map(|inner| inner.as_str()),
+            matches.get_one::<String>("PREVIEW_SIZE").map(|inner| inner.as_str()),
+            matches.get_flag("PREVIEW_WRAP"),
+        )?;
+
         let mut opt_deleted_mode = match matches.get_one::<String>("DELETED").map(|inner| inner.as_str()) {
             Some("" | "all") => Some(DeletedMode::All),
             Some("single") => Some(DeletedMode::DepthOfOne),
@@ -732,12 +2298,17 @@ impl Config {
                 "copy-and-preserve" => {
                     Some(InteractiveMode::Restore(RestoreMode::CopyAndPreserve))
                 }
+                "pick" => Some(InteractiveMode::Restore(RestoreMode::PickDestination)),
+                "merge" => Some(InteractiveMode::Restore(RestoreMode::Merge)),
                 _ => Some(InteractiveMode::Restore(RestoreMode::CopyOnly)),
             }
+        } else if let Some(shell_dialect) = opt_widget {
+            Some(InteractiveMode::Select(SelectMode::Widget(shell_dialect)))
         } else if opt_select_mode.is_some() || opt_preview.is_some() {
             match opt_select_mode.map(|inner| inner.as_str()) {
                 Some("contents") => Some(InteractiveMode::Select(SelectMode::Contents)),
                 Some("preview") => Some(InteractiveMode::Select(SelectMode::Preview)),
+                Some("json") => Some(InteractiveMode::Select(SelectMode::Json)),
                 Some(_) | None => Some(InteractiveMode::Select(SelectMode::Path)),
             }
         // simply enable browse mode -- if deleted mode not enabled but recursive search is specified,
@@ -752,7 +2323,17 @@ impl Config {
             _ if matches.get_flag("PRUNE") =>  ListSnapsOfType::All,
             Some("all" | "no-filter") => ListSnapsOfType::All,
             Some("contents") => ListSnapsOfType::UniqueContents,
-            Some("metadata" | _) | None => ListSnapsOfType::UniqueMetadata,
+            Some("metadata") | None => ListSnapsOfType::UniqueMetadata,
+            Some(value) => match (value.strip_prefix("key="), value.strip_prefix("cmd:")) {
+                (Some(values), _) => ListSnapsOfType::UniqueKey(DedupKey::new(values)?),
+                (_, Some(command)) => ListSnapsOfType::UniqueCommand(CompareCommand::new(command)?),
+                _ => {
+                    let msg = format!(
+                        "Uniqueness value is not recognized: {value:?}.  Valid values are: all, no-filter, metadata, contents, a composite key, like \"key=mtime,size\", or a command, like \"cmd:cmp -s\"."
+                    );
+                    return Err(HttmError::new(&msg).into());
+                }
+            },
         };
 
         if opt_no_hidden && !opt_recursive && opt_interactive_mode.is_none() {
@@ -807,14 +2388,83 @@ impl Config {
             None
         };
 
-        let mut exec_mode = if let Some(full_snap_name) = matches.get_one::<String>("ROLL_FORWARD") {
+        let mut exec_mode = if let Some(path) = matches.get_one::<PathBuf>("TRANSCODE") {
+            ExecMode::Transcode(path.to_owned())
+        } else if let Some(path) = matches.get_one::<PathBuf>("PARSE_MOUNTS_FROM") {
+            ExecMode::ParseMounts(path.to_owned())
+        } else if let Some(path) = matches.get_one::<PathBuf>("PRINT_NOTE") {
+            ExecMode::PrintNote(path.to_owned())
+        } else if let Some(remote) = matches.get_one::<String>("REMOTE") {
+            ExecMode::CompareRemote(remote.to_owned())
+        } else if let Some(ids) = matches.get_many::<String>("VERSION_ID") {
+            let opt_range = matches
+                .get_one::<String>("RANGE")
+                .map(|value| ContentRange::new(value))
+                .transpose()?;
+
+            let opt_version_action = matches
+                .get_one::<String>("VERSION_ACTION")
+                .map(String::as_str);
+
+            let action = if let Some(note) = matches.get_one::<String>("NOTE") {
+                VersionAction::Note(note.to_owned())
+            } else {
+                match (opt_version_action, opt_range) {
+                    (Some("cat"), opt_range) => VersionAction::Cat(opt_range),
+                    (Some("splice"), Some(range)) => VersionAction::Splice(range),
+                    (Some("splice"), None) => {
+                        return Err(HttmError::new(
+                            "The \"splice\" VERSION_ACTION requires a RANGE.",
+                        )
+                        .into())
+                    }
+                    (_, Some(_)) => return Err(HttmError::new(
+                        "RANGE is only available with the \"cat\" or \"splice\" VERSION_ACTION.",
+                    )
+                    .into()),
+                    (Some("diff"), None) => VersionAction::Diff,
+                    (Some("restore"), None) => VersionAction::Restore,
+                    (_, None) => VersionAction::Path,
+                }
+            };
+
+            ExecMode::VersionIds(ids.cloned().collect(), action)
+        } else if let Some(id) = matches.get_one::<String>("RUN_JOB") {
+            ExecMode::Jobs(JobAction::Run(id.to_owned()))
+        } else if let Some(id) = matches.get_one::<String>("CANCEL_JOB") {
+            ExecMode::Jobs(JobAction::Cancel(id.to_owned()))
+        } else if matches.get_flag("JOBS") {
+            ExecMode::Jobs(JobAction::List)
+        } else if matches.get_flag("PROTECTION_REPORT") {
+            ExecMode::ProtectionReport
+        } else if matches.get_flag("LIST_DATASETS") {
+            ExecMode::ListDatasets
+        } else if matches.get_flag("CHECK_ALIASES") {
+            ExecMode::CheckAliases
+        } else if matches.get_flag("PROBE_SNAPSHOTS") {
+            ExecMode::ProbeSnapshots
+        } else if let Some(full_snap_name) = matches.get_one::<String>("ROLL_FORWARD") {
             ExecMode::RollForward(full_snap_name.to_string())
         } else if let Some(num_versions_mode) = opt_num_versions {
             ExecMode::NumVersions(num_versions_mode)
+        } else if let Some(histogram_bucket) = opt_histogram {
+            ExecMode::Histogram(histogram_bucket)
         } else if let Some(mount_display) = opt_mount_display {
             ExecMode::MountsForFiles(mount_display)
         } else if matches.get_flag("PRUNE") {
             ExecMode::Prune(opt_snap_mode_filters)
+        } else if matches.get_flag("RECONSTRUCT") {
+            ExecMode::Reconstruct
+        } else if matches.get_flag("RECOVER") {
+            ExecMode::Recover
+        } else if matches.get_flag("TOP") {
+            ExecMode::Top
+        } else if let Some(snap_or_date) = matches.get_one::<String>("DIR_DIFF") {
+            ExecMode::DirDiff(snap_or_date.to_owned())
+        } else if let Some(snap_or_date) = matches.get_one::<String>("PATH_AT") {
+            ExecMode::PathAt(snap_or_date.to_owned())
+        } else if let Some(farm_dir) = matches.get_one::<PathBuf>("MATERIALIZE") {
+            ExecMode::Materialize(farm_dir.to_owned())
         } else if opt_snap_mode_filters.is_some() {
             ExecMode::SnapsForFiles(opt_snap_mode_filters)
         } else if let Some(requested_snapshot_suffix) = opt_snap_file_mount {
@@ -849,24 +2499,213 @@ impl Config {
         };
 
         if opt_alt_store.is_some() && opt_map_aliases.is_some() {
-            eprintln!("WARN: httm has disabled any MAP_ALIASES in preference to an ALT_STORE specified.");
+            Diagnostic::warn(
+                "aliases_disabled",
+                "httm has disabled any MAP_ALIASES in preference to an ALT_STORE specified.",
+            )
+            .print();
             opt_map_aliases = None;
         }
 
-        let dataset_collection = FilesystemInfo::new(
-            matches.get_flag("ALT_REPLICATED"),
-            opt_debug,
-            matches.get_one::<String>("REMOTE_DIR").map(|inner| inner.as_str()),
-            matches.get_one::<String>("LOCAL_DIR").map(|inner| inner.as_str()),
-            opt_map_aliases,
-            opt_alt_store,
-            &pwd,
-        )?;
+        let opt_snap_class = matches
+            .get_one::<String>("SNAP_CLASS")
+            .map(|inner| inner.as_str());
+
+        // "all" only labels snapshot versions, any other value also filters by class
+        let opt_snap_classes: Option<Vec<String>> = match opt_snap_class {
+            None | Some("" | "all") => None,
+            Some(classes) => Some(
+                classes
+                    .split_terminator(',')
+                    .map(|class| class.trim().to_owned())
+                    .filter(|class| !class.is_empty())
+                    .collect(),
+            ),
+        };
+
+        // keys must be loaded, and datasets mounted, before we parse the mount table
+        if matches.get_flag("LOAD_KEY") {
+            let requested_paths: Vec<PathBuf> = match matches.get_many::<PathBuf>("INPUT_FILES") {
+                Some(input_files) => input_files.map(|path| pwd.join(path)).collect(),
+                None => vec![pwd.clone()],
+            };
+
+            LockedDatasets::load_keys(&requested_paths)?;
+        }
+
+        let opt_privileged_helper = match matches.get_one::<String>("PRIVILEGED_HELPER") {
+            Some(value) => Some(PrivilegedHelper::new(value)?),
+            None => std::env::var("HTTM_PRIVILEGED_HELPER")
+                .ok()
+                .map(|value| PrivilegedHelper::new(&value))
+                .transpose()?,
+        };
+
+        let number_format = NumberFormat {
+            opt_si: matches.get_flag("SI"),
+            opt_separators: matches.get_one::<String>("LOCALE").map(|value| {
+                if !value.is_empty() {
+                    return NumberSeparators::from_locale(value);
+                }
+
+                let env_locale = ["LC_ALL", "LC_NUMERIC", "LANG"]
+                    .into_iter()
+                    .filter_map(|key| std::env::var(key).ok())
+                    .find(|value| !value.is_empty())
+                    .unwrap_or_default();
+
+                NumberSeparators::from_locale(&env_locale)
+            }),
+        };
+
+        let opt_date_format = match matches.get_one::<String>("DATE_FORMAT") {
+            Some(value) => {
+                if let Err(err) = time::format_description::parse(value) {
+                    let msg = format!(
+                        "The DATE_FORMAT specified is not a valid format description: {err}"
+                    );
+                    return Err(HttmError::new(&msg).into());
+                }
+
+                Some(value.to_owned())
+            }
+            None => None,
+        };
+
+        let opt_mount_backend = match matches.get_one::<String>("MOUNT_BACKEND").map(|inner| inner.as_str()) {
+            Some("proc") => Some(MountBackend::Proc),
+            Some("mountinfo") => Some(MountBackend::MountInfo),
+            Some("libmount") => Some(MountBackend::LibMount),
+            Some("cmd") => Some(MountBackend::Cmd),
+            _ => None,
+        };
+
+        let opt_include_snap_mounts = matches.get_one::<String>("INCLUDE_SNAP_MOUNTS").map(|value| {
+            let mounts: Vec<PathBuf> = value
+                .split(',')
+                .map(str::trim)
+                .filter(|mount| !mount.is_empty())
+                .map(PathBuf::from)
+                .collect();
+
+            if mounts.is_empty() {
+                IncludeSnapMounts::All
+            } else {
+                IncludeSnapMounts::Only(mounts)
+            }
+        });
+
+        let opt_dataset_filter = {
+            let exclude: Vec<String> = matches
+                .get_many::<String>("EXCLUDE_DATASET")
+                .map(|values| values.cloned().collect())
+                .unwrap_or_default();
+            let opt_include_only: Option<Vec<String>> = matches
+                .get_many::<String>("INCLUDE_ONLY_DATASET")
+                .map(|values| values.cloned().collect());
+
+            if exclude.is_empty() && opt_include_only.is_none() {
+                None
+            } else {
+                Some(DatasetFilter {
+                    exclude,
+                    opt_include_only,
+                })
+            }
+        };
+
+        let probe_order = match matches.get_one::<String>("PROBE_ORDER").map(String::as_str) {
+            Some("oldest") => ProbeOrder::Oldest,
+            Some("name") => ProbeOrder::Name,
+            _ => ProbeOrder::Newest,
+        };
+
+        let opt_parent_fallback = matches.get_flag("PARENT_FALLBACK");
+
+        let opt_mtime_window = match matches.get_one::<String>("MTIME_WINDOW") {
+            Some(value) => match value.parse::<f64>() {
+                Ok(secs) if secs.is_finite() && secs > 0f64 => Some(Duration::from_secs_f64(secs)),
+                _ => {
+                    let msg = format!(
+                        "MTIME_WINDOW value must be a positive number of seconds: {:?}",
+                        value
+                    );
+                    return Err(HttmError::new(&msg).into());
+                }
+            },
+            None => None,
+        };
+
+        let hooks = Hooks {
+            opt_pre_restore: matches.get_one::<PathBuf>("HOOK_PRE_RESTORE").cloned(),
+            opt_post_restore: matches.get_one::<PathBuf>("HOOK_POST_RESTORE").cloned(),
+            opt_pre_snap: matches.get_one::<PathBuf>("HOOK_PRE_SNAP").cloned(),
+        };
+
+        if let Some(script) = [
+            &hooks.opt_pre_restore,
+            &hooks.opt_post_restore,
+            &hooks.opt_pre_snap,
+        ]
+        .into_iter()
+        .flatten()
+        .find(|script| !script.is_file())
+        {
+            let msg = format!("Hook script does not exist, or is not a file: {:?}", script);
+            return Err(HttmError::new(&msg).with_path(script).into());
+        }
+
+        let opt_audit_log = match matches.get_one::<PathBuf>("AUDIT_LOG") {
+            Some(value) => Some(value.clone()),
+            None => std::env::var_os("HTTM_AUDIT_LOG")
+                .filter(|value| !value.is_empty())
+                .map(PathBuf::from),
+        };
+
+        if let Some(audit_log) = &opt_audit_log {
+            AuditLog::check(audit_log)?;
+        }
+
+        // a foreign mount table is parsed instead of this system's, and so needs none of this system's datasets
+        let dataset_collection = if let ExecMode::ParseMounts(_) = exec_mode {
+            FilesystemInfo::default()
+        } else {
+            FilesystemInfo::new(
+                matches.get_flag("ALT_REPLICATED"),
+                matches.get_flag("SNAP_CREATION"),
+                opt_snap_class.is_some(),
+                opt_debug,
+                matches.get_one::<OsString>("REMOTE_DIR").map(|inner| inner.as_os_str()),
+                matches.get_one::<OsString>("LOCAL_DIR").map(|inner| inner.as_os_str()),
+                opt_map_aliases,
+                opt_alt_store,
+                matches.get_one::<PathBuf>("ARCHIVE_DIR").map(|inner| inner.as_path()),
+                opt_privileged_helper.as_ref(),
+                opt_mount_backend.as_ref(),
+                opt_include_snap_mounts.as_ref(),
+                opt_dataset_filter.as_ref(),
+                &pwd,
+            )?
+        };
 
         // paths are immediately converted to our PathData struct
         let opt_os_values = matches.get_many::<PathBuf>("INPUT_FILES");
 
-        let paths: Vec<PathData> = Self::paths(opt_os_values, &exec_mode, &pwd)?;
+        // explicitly requested datasets take the place of input files, so don't wait on stdin
+        let paths: Vec<PathData> = if opt_datasets.is_some() && opt_os_values.is_none() {
+            Vec::new()
+        } else {
+            // Windows paths, pasted from Explorer, are mapped onto the local mounts of their SMB shares
+            let unc_shares = MapOfUncShares::new(&dataset_collection.map_of_datasets)?;
+
+            Self::paths(
+                opt_os_values,
+                &exec_mode,
+                &pwd,
+                opt_preserve_order,
+                &unc_shares,
+            )?
+        };
 
         // for exec_modes in which we can only take a single directory, process how we handle those here
         let opt_requested_dir: Option<PathBuf> =
@@ -896,6 +2735,48 @@ impl Config {
             .into());
         }
 
+        let opt_deleted_ranking = match (
+            matches
+                .get_one::<String>("SORT_DELETED")
+                .map(|inner| inner.as_str()),
+            matches.get_one::<usize>("LIMIT").copied(),
+        ) {
+            (None, None) => None,
+            (opt_sort, opt_limit) => {
+                let sort = match opt_sort {
+                    Some("mtime") | None => DeletedSort::ModifyTime,
+                    Some("size") => DeletedSort::Size,
+                    Some("path") => DeletedSort::Path,
+                    _ => unreachable!("value parser restricts the possible values"),
+                };
+
+                Some(DeletedRanking { sort, opt_limit })
+            }
+        };
+
+        // interactive views display deleted files as they are found, and have their own sort
+        if opt_deleted_ranking.is_some()
+            && !matches!(exec_mode, ExecMode::NonInteractiveRecursive(_))
+        {
+            return Err(HttmError::new(
+                "LIMIT and SORT_DELETED are only available in a non-interactive deleted search. Quitting.",
+            )
+            .into());
+        }
+
+        let opt_output = match matches.get_one::<PathBuf>("OUTPUT") {
+            Some(path) if path.is_dir() => {
+                let msg = format!("OUTPUT file specified is a directory: {:?}", path);
+                return Err(HttmError::new(&msg).with_code("invalid_output").into());
+            }
+            Some(path) => Some(pwd.join(path)),
+            None => None,
+        };
+        let opt_tee = matches.get_flag("TEE");
+        let opt_restore_mtime = matches
+            .get_one::<String>("RESTORE_MTIME")
+            .and_then(|inner| RestoreMtime::from_str(inner));
+
         let opt_omit_ditto = matches.get_flag("OMIT_DITTO");
 
         // opt_omit_identical doesn't make sense in Display Recursive mode as no live files will exists?
@@ -906,6 +2787,39 @@ impl Config {
             .into());
         }
 
+        let opt_rename_template = matches
+            .get_one::<String>("RENAME")
+            .map(|template| RenameTemplate::new(template))
+            .transpose()?;
+
+        let opt_owner_map = matches
+            .get_one::<String>("OWNER_MAP")
+            .map(|value| OwnerMap::new(value))
+            .transpose()?;
+
+        if opt_rename_template.is_some()
+            && opt_emit_shell.is_none()
+            && !matches!(
+                exec_mode,
+                ExecMode::Interactive(InteractiveMode::Restore(
+                    RestoreMode::CopyOnly
+                        | RestoreMode::CopyAndPreserve
+                        | RestoreMode::PickDestination
+                )) | ExecMode::VersionIds(_, VersionAction::Restore)
+            )
+        {
+            return Err(HttmError::new(
+                "RENAME is only available when restoring a copy, with the \"copy\", \"copy-and-preserve\", or \"pick\" RESTORE modes, the \"restore\" VERSION_ACTION, or EMIT_SHELL.",
+            )
+            .into());
+        }
+
+        let opt_session = matches.get_one::<String>("SESSION").cloned();
+
+        if opt_session.is_some() && !matches!(exec_mode, ExecMode::Interactive(_)) {
+            return Err(HttmError::new("SESSION is only available in the interactive modes.").into());
+        }
+
         if opt_last_snap.is_some() && matches!(exec_mode, ExecMode::NonInteractiveRecursive(_)) {
             return Err(
                 HttmError::new("LAST_SNAP is not available in Display Recursive Mode.").into(),
@@ -915,6 +2829,7 @@ impl Config {
         let config = Config {
             paths,
             opt_bulk_exclusion,
+            opt_raw_fields,
             opt_recursive,
             opt_exact,
             opt_no_filter,
@@ -924,31 +2839,87 @@ impl Config {
             opt_no_hidden,
             opt_last_snap,
             opt_preview,
+            preview_window,
             opt_json,
             opt_one_filesystem,
+            opt_include_child_datasets,
+            opt_snap_limits,
+            opt_emit_shell,
+            opt_emit_rsync_list,
+            opt_cache_metadata,
+            opt_index_dirs,
+            opt_changed_attrs,
+            opt_emit_checksums,
+            opt_size_filter,
+            opt_file_type,
+            opt_version_ids,
+            opt_preserve_order,
+            opt_table,
+            opt_stat,
+            opt_tree,
+            opt_restore_special,
+            opt_encoding,
             opt_no_clones,
+            opt_overwrite_changed,
+            opt_background,
+            opt_skip_space_check,
+            opt_in_place,
+            opt_bwlimit,
+            opt_stash_root,
+            opt_session,
+            opt_network_concurrency,
+            opt_generations,
+            opt_datasets,
+            opt_recursive_datasets,
+            opt_privileged_helper,
             uniqueness,
+            probe_order,
+            opt_parent_fallback,
+            opt_mtime_window,
+            hooks,
+            opt_audit_log,
+            opt_rename_template,
+            opt_owner_map,
             requested_utc_offset,
             exec_mode,
             print_mode,
             opt_deleted_mode,
+            opt_deleted_ranking,
+            opt_output,
+            opt_tee,
+            opt_restore_mtime,
             dataset_collection,
             pwd,
             opt_requested_dir,
+            opt_snap_classes,
+            opt_date_format,
+            number_format,
         };
 
         Ok(config)
     }
 
+    // the date format of dates displayed to the user, instead of used in file names
+    pub fn display_date_format(&self) -> DateFormat {
+        match &self.opt_date_format {
+            Some(custom) => DateFormat::Custom(custom.clone()),
+            None => DateFormat::Display,
+        }
+    }
+
     pub fn paths(
         opt_os_values: Option<ValuesRef<'_, PathBuf>>,
         exec_mode: &ExecMode,
         pwd: &Path,
+        opt_preserve_order: bool,
+        unc_shares: &MapOfUncShares,
     ) -> HttmResult<Vec<PathData>> {
         let mut paths = if let Some(input_files) = opt_os_values {
+            // collecting first, unlike a par_bridge(), keeps the order of the paths given
             input_files
-                .into_iter()
-                .par_bridge()
+                .map(|path| unc_shares.resolve(path))
+                .collect::<HttmResult<Vec<PathBuf>>>()?
+                .into_par_iter()
                 // canonicalize() on a deleted relative path will not exist,
                 // so we have to join with the pwd to make a path that
                 // will exist on a snapshot
@@ -972,21 +2943,44 @@ impl Config {
                 // input, and waiting on one input from stdin is pretty silly
                 ExecMode::Interactive(_)
                 | ExecMode::NonInteractiveRecursive(_)
-                | ExecMode::RollForward(_) => {
+                | ExecMode::RollForward(_)
+                | ExecMode::ListDatasets
+                | ExecMode::CheckAliases
+                | ExecMode::ProbeSnapshots
+                | ExecMode::Jobs(_)
+                | ExecMode::ProtectionReport
+                | ExecMode::Top
+                | ExecMode::Transcode(_)
+                | ExecMode::PrintNote(_)
+                | ExecMode::ParseMounts(_)
+                | ExecMode::Recover => {
                     vec![PathData::from(pwd)]
                 }
                 ExecMode::BasicDisplay
                 | ExecMode::SnapFileMount(_)
                 | ExecMode::Prune(_)
+                | ExecMode::Materialize(_)
+                | ExecMode::DirDiff(_)
+                | ExecMode::PathAt(_)
+                | ExecMode::Reconstruct
                 | ExecMode::MountsForFiles(_)
                 | ExecMode::SnapsForFiles(_)
-                | ExecMode::NumVersions(_) => Self::read_stdin()?,
+                | ExecMode::NumVersions(_)
+                | ExecMode::Histogram(_)
+                | ExecMode::VersionIds(_, _)
+                | ExecMode::CompareRemote(_) => Self::read_stdin(unc_shares)?,
             }
         };
 
         // deduplicate pathdata and sort if in display mode --
         // so input of ./.z* and ./.zshrc will only print ./.zshrc once
-        paths = if paths.len() > 1 {
+        paths = if paths.len() > 1 && opt_preserve_order {
+            // keep only the first instance of each path, in the order given
+            let mut seen: HashSet<PathData> = HashSet::with_capacity(paths.len());
+            paths.retain(|pathdata| seen.insert(pathdata.clone()));
+
+            paths
+        } else if paths.len() > 1 {
             paths.sort_unstable();
             // dedup needs to be sorted/ordered first to work (not like a BTreeMap)
             paths.dedup();
@@ -999,7 +2993,7 @@ impl Config {
         Ok(paths)
     }
 
-    pub fn read_stdin() -> HttmResult<Vec<PathData>> {
+    pub fn read_stdin(unc_shares: &MapOfUncShares) -> HttmResult<Vec<PathData>> {
         let stdin = std::io::stdin();
         let mut stdin = stdin.lock();
         let mut buffer = Vec::new();
@@ -1012,8 +3006,8 @@ impl Config {
             buffer_string
                 .split(&['\n', '\0'])
                 .filter(|s| !s.is_empty())
-                .map(PathData::from)
-                .collect()
+                .map(|s| unc_shares.resolve(Path::new(s)).map(PathData::from))
+                .collect::<HttmResult<Vec<PathData>>>()?
         } else if buffer_string.contains('\"') {
             buffer_string
                 .split('\"')
@@ -1021,14 +3015,14 @@ impl Config {
                 .map(str::trim)
                 // remove any empty strings
                 .filter(|s| !s.is_empty())
-                .map(PathData::from)
-                .collect()
+                .map(|s| unc_shares.resolve(Path::new(s)).map(PathData::from))
+                .collect::<HttmResult<Vec<PathData>>>()?
         } else {
             buffer_string
                 .split_ascii_whitespace()
                 .filter(|s| !s.is_empty())
-                .map(PathData::from)
-                .collect()
+                .map(|s| unc_shares.resolve(Path::new(s)).map(PathData::from))
+                .collect::<HttmResult<Vec<PathData>>>()?
         };
 
         Ok(broken_string)
@@ -1089,9 +3083,26 @@ impl Config {
             | ExecMode::RollForward(_)
             | ExecMode::SnapFileMount(_)
             | ExecMode::Prune(_)
+            | ExecMode::Materialize(_)
+            | ExecMode::DirDiff(_)
+            | ExecMode::PathAt(_)
+            | ExecMode::Reconstruct
+            | ExecMode::Recover
             | ExecMode::MountsForFiles(_)
             | ExecMode::SnapsForFiles(_)
-            | ExecMode::NumVersions(_) => {
+            | ExecMode::NumVersions(_)
+            | ExecMode::Histogram(_)
+            | ExecMode::ListDatasets
+            | ExecMode::CheckAliases
+            | ExecMode::ProbeSnapshots
+            | ExecMode::Jobs(_)
+            | ExecMode::ProtectionReport
+            | ExecMode::Top
+            | ExecMode::Transcode(_)
+            | ExecMode::PrintNote(_)
+            | ExecMode::ParseMounts(_)
+            | ExecMode::VersionIds(_, _)
+            | ExecMode::CompareRemote(_) => {
                 // in non-interactive mode / display mode, requested dir is just a file
                 // like every other file and pwd must be the requested working dir.
                 None
@@ -15,17 +15,26 @@
 // For the full copyright and license information, please view the LICENSE file
 // that was distributed with this source code.
 
+use crate::library::privileged::PrivilegedHelper;
 use crate::library::results::HttmResult;
 use crate::parse::aliases::MapOfAliases;
 use crate::parse::alts::MapOfAlts;
-use crate::parse::mounts::{BaseFilesystemInfo, FilesystemType, FilterDirs, MapOfDatasets};
+use crate::parse::archives::MapOfArchives;
+use crate::parse::creation::MapOfCreationTimes;
+use crate::parse::dataset_props::MapOfDatasetProps;
+use crate::parse::mount_backends::MountBackend;
+use crate::parse::mounts::{
+    BaseFilesystemInfo, DatasetFilter, FilesystemType, FilterDirs, IncludeSnapMounts, MapOfDatasets,
+};
+use crate::parse::snap_labels::MapOfSnapLabels;
 use crate::parse::snaps::MapOfSnaps;
 use clap::parser::RawValues;
-use std::ffi::OsString;
+use std::ffi::{OsStr, OsString};
 use std::path::Path;
 use std::path::PathBuf;
+use std::time::SystemTime;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct FilesystemInfo {
     // key: mount, val: (dataset/subvol, fs_type, mount_type)
     pub map_of_datasets: MapOfDatasets,
@@ -39,19 +48,51 @@ pub struct FilesystemInfo {
     pub opt_map_of_aliases: Option<MapOfAliases>,
     // opt single dir to to be filtered re: btrfs common snap dir
     pub opt_common_snap_dir: Option<PathBuf>,
+    // key: snap mount, val: snapshot creation time
+    pub opt_map_of_creation_times: Option<MapOfCreationTimes>,
+    // key: snap mount, val: label parsed from the snapshot name
+    pub opt_map_of_snap_labels: Option<MapOfSnapLabels>,
+    // key: path relative to the subvolume sent, val: versions within btrfs send streams
+    pub opt_map_of_archives: Option<MapOfArchives>,
+    // key: mount, val: httm's ZFS user properties, and whether the dataset is read-only
+    pub opt_map_of_dataset_props: Option<MapOfDatasetProps>,
 }
 
 impl FilesystemInfo {
+    #[allow(clippy::too_many_arguments)]
     pub fn new<'a, 'b: 'a>(
         opt_alt_replicated: bool,
+        opt_snap_creation: bool,
+        opt_snap_labels: bool,
         opt_debug: bool,
-        opt_remote_dir: Option<&str>,
-        opt_local_dir: Option<&str>,
+        opt_remote_dir: Option<&OsStr>,
+        opt_local_dir: Option<&OsStr>,
         opt_map_aliases: Option<RawValues>,
         opt_alt_store: Option<&FilesystemType>,
+        opt_archive_dir: Option<&Path>,
+        opt_privileged_helper: Option<&PrivilegedHelper>,
+        opt_mount_backend: Option<&MountBackend>,
+        opt_include_snap_mounts: Option<&IncludeSnapMounts>,
+        opt_dataset_filter: Option<&DatasetFilter>,
         pwd: &Path,
     ) -> HttmResult<FilesystemInfo> {
-        let base_fs_info = BaseFilesystemInfo::new(opt_debug, opt_alt_store)?;
+        let mut base_fs_info = BaseFilesystemInfo::new(
+            opt_debug,
+            opt_alt_store,
+            opt_privileged_helper,
+            opt_mount_backend,
+            opt_include_snap_mounts,
+            opt_dataset_filter,
+        )?;
+
+        // a SNAP_CLASS specified by the user takes precedence over any "httm:snap-filter" property
+        if !opt_snap_labels {
+            if let Some(map_of_dataset_props) = &base_fs_info.opt_map_of_dataset_props {
+                base_fs_info
+                    .map_of_snaps
+                    .filter_by_props(map_of_dataset_props);
+            }
+        }
 
         // for a collection of btrfs mounts, indicates a common snapshot directory to ignore
         let opt_common_snap_dir = base_fs_info.common_snap_dir();
@@ -63,42 +104,58 @@ impl FilesystemInfo {
             None
         };
 
-        let raw_snap_dir = if let Some(value) = opt_remote_dir {
-            Some(OsString::from(value))
-        } else if std::env::var_os("HTTM_REMOTE_DIR").is_some() {
-            std::env::var_os("HTTM_REMOTE_DIR")
+        // only create a map of snapshot creation times if necessary, or if SMB shadow copies exist,
+        // as the modify times within a share are less reliable than the shadow copy's GMT token
+        let opt_map_of_creation_times = if opt_snap_creation {
+            Some(MapOfCreationTimes::new(
+                &base_fs_info.map_of_datasets,
+                &base_fs_info.map_of_snaps,
+            )?)
+        } else {
+            MapOfCreationTimes::from_shadow_copies(
+                &base_fs_info.map_of_datasets,
+                &base_fs_info.map_of_snaps,
+            )
+        };
+
+        // only create a map of snapshot labels if necessary
+        let opt_map_of_snap_labels = if opt_snap_labels {
+            Some(MapOfSnapLabels::new(&base_fs_info.map_of_snaps))
         } else {
+            None
+        };
+
+        // only index send stream archives if necessary
+        let opt_map_of_archives = opt_archive_dir.map(MapOfArchives::new).transpose()?;
+
+        // explicit flags take precedence over environment variables, which, in turn, take precedence over
+        // the config file, as the config file's values are dropped when an environment variable is set
+        let raw_snap_dir = match opt_remote_dir {
+            Some(value) => Some(OsString::from(value)),
             // legacy env var name
-            std::env::var_os("HTTM_SNAP_POINT")
+            None => MapOfAliases::env_value("HTTM_REMOTE_DIR")
+                .or_else(|| MapOfAliases::env_value("HTTM_SNAP_POINT")),
         };
 
-        let alias_values: Option<Vec<String>> = match std::env::var_os("HTTM_MAP_ALIASES") {
-            Some(env_map_alias) => Some(
-                env_map_alias
-                    .to_string_lossy()
-                    .split_terminator(',')
-                    .map(|s| s.to_owned())
-                    .collect(),
-            ),
-            None => opt_map_aliases.map(|map_aliases| {
+        let alias_values: Option<Vec<String>> = match opt_map_aliases {
+            Some(map_aliases) => Some(
                 map_aliases
                     .map(|os_str| os_str.to_string_lossy().to_string())
-                    .collect()
-            }),
+                    .collect(),
+            ),
+            None => MapOfAliases::env_value("HTTM_MAP_ALIASES")
+                .map(|env_map_aliases| MapOfAliases::split_values(&env_map_aliases)),
         };
 
         let opt_map_of_aliases = if raw_snap_dir.is_some() || alias_values.is_some() {
-            let env_local_dir = std::env::var_os("HTTM_LOCAL_DIR");
-
-            let raw_local_dir = if let Some(value) = opt_local_dir {
-                Some(OsString::from(value))
-            } else {
-                env_local_dir
+            let raw_local_dir = match opt_local_dir {
+                Some(value) => Some(OsString::from(value)),
+                None => MapOfAliases::env_value("HTTM_LOCAL_DIR"),
             };
 
             Some(MapOfAliases::new(
-                &raw_snap_dir,
                 &raw_local_dir,
+                &raw_snap_dir,
                 pwd,
                 &alias_values,
             )?)
@@ -113,6 +170,75 @@ impl FilesystemInfo {
             opt_map_of_alts,
             opt_common_snap_dir,
             opt_map_of_aliases,
+            opt_map_of_creation_times,
+            opt_map_of_snap_labels,
+            opt_map_of_archives,
+            opt_map_of_dataset_props: base_fs_info.opt_map_of_dataset_props,
         })
     }
+
+    // the mounts of datasets beneath a directory, nearest first, excluding the dataset of the directory itself
+    pub fn child_datasets(&self, dir: &Path) -> Vec<&Path> {
+        let mut child_datasets: Vec<&Path> = self
+            .map_of_datasets
+            .keys()
+            .map(|mount| mount.as_path())
+            .filter(|mount| *mount != dir && mount.starts_with(dir))
+            .collect();
+
+        child_datasets.sort_by_key(|mount| (mount.components().count(), mount.to_path_buf()));
+
+        child_datasets
+    }
+
+    // whether a path resides upon a dataset mounted beneath the directory
+    pub fn is_within_child_dataset(&self, dir: &Path, path: &Path) -> bool {
+        path.starts_with(dir)
+            && path
+                .ancestors()
+                .take_while(|ancestor| *ancestor != dir)
+                .any(|ancestor| self.map_of_datasets.contains_key(ancestor))
+    }
+
+    // a read-only dataset, like a received replica, may be listed, but may not be restored to in place
+    pub fn is_readonly(&self, mount: &Path) -> bool {
+        self.opt_map_of_dataset_props
+            .as_ref()
+            .is_some_and(|map_of_dataset_props| map_of_dataset_props.is_readonly(mount))
+    }
+
+    // the snapshot mount which contains a version, like "/home/.zfs/snapshot/snap_1" for "/home/.zfs/snapshot/snap_1/file.txt"
+    pub fn snap_mount_of<'a>(&'a self, snap_path: &Path) -> Option<&'a Path> {
+        snap_path.ancestors().skip(1).find_map(|ancestor| {
+            self.map_of_snaps
+                .values()
+                .flatten()
+                .find(|snap_mount| snap_mount.as_path() == ancestor)
+                .map(|snap_mount| snap_mount.as_path())
+        })
+    }
+
+    // the live path of a path within a snapshot, like "/home/file.txt" for "/home/.zfs/snapshot/snap_1/file.txt"
+    pub fn live_path_of(&self, snap_path: &Path) -> Option<PathBuf> {
+        let snap_mount = self.snap_mount_of(snap_path)?;
+        let relative_path = snap_path.strip_prefix(snap_mount).ok()?;
+
+        self.map_of_snaps
+            .iter()
+            .find(|(_mount, snap_mounts)| snap_mounts.iter().any(|sm| sm == snap_mount))
+            .map(|(mount, _snap_mounts)| mount.join(relative_path))
+    }
+
+    // the time a snapshot was taken: its creation time, when known, and otherwise the modify time of its root directory
+    pub fn snap_mount_time(&self, snap_mount: &Path) -> Option<SystemTime> {
+        self.opt_map_of_creation_times
+            .as_ref()
+            .and_then(|map_of_creation_times| map_of_creation_times.creation_time(snap_mount))
+            .or_else(|| {
+                snap_mount
+                    .metadata()
+                    .and_then(|metadata| metadata.modified())
+                    .ok()
+            })
+    }
 }
@@ -15,8 +15,12 @@
 // For the full copyright and license information, please view the LICENSE file
 // that was distributed with this source code.
 
-use crate::config::generate::{ListSnapsOfType, PrintMode};
+use crate::config::generate::{DedupKey, ListSnapsOfType, PrintMode};
+use crate::library::diagnostics::Diagnostic;
+use crate::library::network_limit::NetworkLimit;
 use crate::library::results::{HttmError, HttmResult};
+use crate::library::special_file::SpecialFile;
+use crate::library::throttle::Throttle;
 use crate::library::utility::{date_string, display_human_size, DateFormat};
 use crate::parse::mounts::FilesystemType;
 use crate::parse::mounts::MaxLen;
@@ -197,23 +201,17 @@ pub struct AliasedPath<'a> {
 impl<'a> AliasedPath<'a> {
     #[inline(always)]
     pub fn new(path: &'a Path) -> Option<Self> {
-        // find_map_first should return the first seq result with a par_iter
-        // but not with a par_bridge
+        let (local_dir, metadata) = GLOBAL_CONFIG
+            .dataset_collection
+            .opt_map_of_aliases
+            .as_ref()?
+            .resolve(path)?;
 
-        path.ancestors().find_map(|ancestor| {
-            GLOBAL_CONFIG
-                .dataset_collection
-                .opt_map_of_aliases
-                .as_ref()
-                .and_then(|map_of_aliases| {
-                    let md = map_of_aliases.get(ancestor);
-                    let relative_path = path.strip_prefix(ancestor).ok()?;
+        let relative_path = path.strip_prefix(local_dir).ok()?;
 
-                    md.map(|metadata| AliasedPath {
-                        proximate_dataset: metadata.remote_dir.as_ref(),
-                        relative_path,
-                    })
-                })
+        Some(AliasedPath {
+            proximate_dataset: metadata.remote_dir.as_ref(),
+            relative_path,
         })
     }
 }
@@ -318,11 +316,15 @@ impl<'a> PathDeconstruction<'a> for ZfsSnapPathGuard<'_> {
                 Some(PathBuf::from(res))
             }
             Some(_md) => {
-                eprintln!("WARN: {:?} is located on a non-ZFS dataset.  httm can only list snapshot names for ZFS datasets.", self.inner.path_buf);
+                Diagnostic::warn("unsupported_filesystem", format!("{:?} is located on a non-ZFS dataset.  httm can only list snapshot names for ZFS datasets.", self.inner.path_buf))
+                    .with_path(&self.inner.path_buf)
+                    .print();
                 None
             }
             _ => {
-                eprintln!("WARN: {:?} is not located on a discoverable dataset.  httm can only list snapshot names for ZFS datasets.", self.inner.path_buf);
+                Diagnostic::warn("no_dataset", format!("{:?} is not located on a discoverable dataset.  httm can only list snapshot names for ZFS datasets.", self.inner.path_buf))
+                    .with_path(&self.inner.path_buf)
+                    .print();
                 None
             }
         }
@@ -338,10 +340,37 @@ impl Serialize for PathData {
     where
         S: Serializer,
     {
-        let mut state = serializer.serialize_struct("PathData", 2)?;
+        let mut state = serializer.serialize_struct("PathData", 4)?;
 
         state.serialize_field("path", &self.path_buf)?;
         state.serialize_field("metadata", &self.metadata)?;
+
+        if let Some(map_of_creation_times) =
+            &GLOBAL_CONFIG.dataset_collection.opt_map_of_creation_times
+        {
+            let opt_creation_time = map_of_creation_times.creation_time(&self.path_buf);
+
+            if matches!(
+                GLOBAL_CONFIG.print_mode,
+                PrintMode::RawNewline | PrintMode::RawZero
+            ) {
+                state.serialize_field("snap_creation", &opt_creation_time)?;
+            } else {
+                let opt_date = opt_creation_time.map(|creation_time| {
+                    date_string(
+                        GLOBAL_CONFIG.requested_utc_offset,
+                        &creation_time,
+                        DateFormat::Display,
+                    )
+                });
+                state.serialize_field("snap_creation", &opt_date)?;
+            }
+        }
+
+        if let Some(map_of_snap_labels) = &GLOBAL_CONFIG.dataset_collection.opt_map_of_snap_labels {
+            state.serialize_field("snap_label", &map_of_snap_labels.snap_label(&self.path_buf))?;
+        }
+
         state.end()
     }
 }
@@ -375,10 +404,30 @@ impl Serialize for PathMetadata {
     }
 }
 
-#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+#[derive(Copy, Clone, Debug)]
 pub struct PathMetadata {
     pub size: u64,
     pub modify_time: SystemTime,
+    pub opt_identity: Option<FileIdentity>,
+}
+
+// identity is not content, so a version with the same size and modify time is the same version,
+// whatever its identity, and identity is excluded from comparisons
+impl PartialEq for PathMetadata {
+    #[inline(always)]
+    fn eq(&self, other: &Self) -> bool {
+        self.size == other.size && self.modify_time == other.modify_time
+    }
+}
+
+impl Eq for PathMetadata {}
+
+impl std::hash::Hash for PathMetadata {
+    #[inline(always)]
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.size.hash(state);
+        self.modify_time.hash(state);
+    }
 }
 
 impl PathMetadata {
@@ -389,6 +438,7 @@ impl PathMetadata {
         Self::modify_time(md).map(|time| PathMetadata {
             size: md.len(),
             modify_time: time,
+            opt_identity: Some(FileIdentity::new(md)),
         })
     }
 
@@ -410,8 +460,40 @@ pub const PHANTOM_SIZE: u64 = 0u64;
 pub const PHANTOM_PATH_METADATA: PathMetadata = PathMetadata {
     size: PHANTOM_SIZE,
     modify_time: PHANTOM_DATE,
+    opt_identity: None,
 };
 
+// the inode number and birth time of a version distinguish a file which was truncated and
+// rewritten in place (same identity) from a different file which reused the name (new identity)
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub struct FileIdentity {
+    pub ino: u64,
+    pub opt_birth_time: Option<SystemTime>,
+}
+
+impl FileIdentity {
+    #[inline(always)]
+    fn new(md: &Metadata) -> Self {
+        use std::os::unix::fs::MetadataExt;
+
+        Self {
+            ino: md.ino(),
+            // birth time is not available on every platform or filesystem
+            opt_birth_time: md.created().ok(),
+        }
+    }
+
+    // when either birth time is unavailable, fall back to comparing only inode numbers
+    pub fn is_same_generation(&self, other: &Self) -> bool {
+        match (self.opt_birth_time, other.opt_birth_time) {
+            (Some(self_birth), Some(other_birth)) => {
+                self.ino == other.ino && self_birth == other_birth
+            }
+            _ => self.ino == other.ino,
+        }
+    }
+}
+
 #[derive(Eq, PartialEq)]
 pub struct CompareVersionsContainer {
     pathdata: PathData,
@@ -460,7 +542,10 @@ impl CompareVersionsContainer {
     pub fn new(pathdata: PathData, snaps_of_type: &ListSnapsOfType) -> Self {
         let opt_hash = match snaps_of_type {
             ListSnapsOfType::UniqueContents => Some(OnceCell::new()),
-            ListSnapsOfType::UniqueMetadata | ListSnapsOfType::All => None,
+            ListSnapsOfType::UniqueMetadata
+            | ListSnapsOfType::All
+            | ListSnapsOfType::UniqueKey(_)
+            | ListSnapsOfType::UniqueCommand(_) => None,
         };
 
         CompareVersionsContainer { pathdata, opt_hash }
@@ -507,11 +592,91 @@ impl CompareVersionsContainer {
     }
 
     fn hash(&self) -> HttmResult<u64> {
+        // special files have no contents, so their metadata, the kind of file, the device it names,
+        // its permissions and its owner, stands in for their contents
+        if let Some(metadata) = self
+            .pathdata
+            .path_buf
+            .metadata()
+            .ok()
+            .filter(|md| SpecialFile::from_file_type(md.file_type()).is_some())
+        {
+            use std::hash::{Hash, Hasher};
+            use std::os::unix::fs::MetadataExt;
+
+            let mut hash = ahash::AHasher::default();
+            (
+                metadata.mode(),
+                metadata.rdev(),
+                metadata.uid(),
+                metadata.gid(),
+            )
+                .hash(&mut hash);
+
+            return Ok(hash.finish());
+        }
+
+        self.pathdata.content_hash()
+    }
+}
+
+// the values of those attributes requested by a DedupKey, attributes not requested are None
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DedupKeyValue {
+    opt_mtime: Option<SystemTime>,
+    opt_size: Option<u64>,
+    opt_ctime: Option<(i64, i64)>,
+    opt_hash: Option<u64>,
+    opt_perms: Option<u32>,
+}
+
+impl PathData {
+    // returns None if any attribute requested cannot be determined, and such a version
+    // should never be considered the same as another
+    pub fn dedup_key_value(&self, dedup_key: &DedupKey) -> Option<DedupKeyValue> {
+        use std::os::unix::fs::MetadataExt;
+
+        let md = self.metadata?;
+
+        let opt_full_md = if dedup_key.ctime || dedup_key.perms {
+            Some(symlink_metadata(&self.path_buf).ok()?)
+        } else {
+            None
+        };
+
+        let opt_hash = if dedup_key.hash && self.path_buf.is_file() {
+            Some(self.content_hash().ok()?)
+        } else {
+            None
+        };
+
+        Some(DedupKeyValue {
+            opt_mtime: dedup_key.mtime.then_some(md.modify_time),
+            opt_size: dedup_key.size.then_some(md.size),
+            opt_ctime: opt_full_md
+                .as_ref()
+                .filter(|_| dedup_key.ctime)
+                .map(|full_md| (full_md.ctime(), full_md.ctime_nsec())),
+            opt_hash,
+            opt_perms: opt_full_md
+                .as_ref()
+                .filter(|_| dedup_key.perms)
+                .map(|full_md| full_md.mode()),
+        })
+    }
+
+    pub fn content_hash(&self) -> HttmResult<u64> {
         use std::hash::Hasher;
 
         const IN_BUFFER_SIZE: usize = 131_072;
 
-        let file = File::open(&self.pathdata.path_buf)?;
+        // hold the permit for the whole read, not for each buffer
+        let _opt_permit = self.proximate_dataset().ok().and_then(NetworkLimit::permit);
+
+        // special files have no contents, so such versions may only be deduplicated by their metadata
+        SpecialFile::refuse_contents(&self.path_buf)?;
+
+        let file = File::open(&self.path_buf)?;
 
         let mut reader = BufReader::with_capacity(IN_BUFFER_SIZE, file);
 
@@ -524,6 +689,8 @@ impl CompareVersionsContainer {
                         return Ok(hash.finish());
                     }
 
+                    Throttle::consume(buf.len());
+
                     hash.write(buf);
                     buf.len()
                 }
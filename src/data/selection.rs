@@ -132,12 +132,54 @@ impl From<Vec<PathData>> for Config {
             opt_no_hidden: false,
             opt_json: false,
             opt_one_filesystem: false,
+            opt_include_child_datasets: false,
+            opt_snap_limits: None,
+            opt_emit_shell: None,
+            opt_emit_rsync_list: None,
+            opt_cache_metadata: false,
+            opt_index_dirs: None,
+            opt_changed_attrs: config.opt_changed_attrs.clone(),
+            opt_emit_checksums: None,
+            opt_size_filter: config.opt_size_filter,
+            opt_file_type: config.opt_file_type,
+            opt_version_ids: config.opt_version_ids,
+            opt_preserve_order: config.opt_preserve_order,
+            opt_table: config.opt_table,
+            opt_stat: config.opt_stat,
+            opt_tree: config.opt_tree,
+            opt_restore_special: false,
+            opt_encoding: config.opt_encoding,
             opt_no_clones: false,
+            opt_overwrite_changed: false,
+            opt_background: false,
+            opt_skip_space_check: false,
+            opt_in_place: false,
+            opt_bwlimit: config.opt_bwlimit,
+            opt_stash_root: None,
+            opt_session: None,
+            opt_network_concurrency: config.opt_network_concurrency,
+            opt_generations: config.opt_generations,
+            opt_datasets: config.opt_datasets.clone(),
+            opt_recursive_datasets: config.opt_recursive_datasets,
+            opt_privileged_helper: config.opt_privileged_helper.clone(),
             opt_bulk_exclusion: None,
+            opt_raw_fields: None,
             opt_last_snap: None,
             opt_preview: None,
+            preview_window: config.preview_window.clone(),
             opt_deleted_mode: None,
+            opt_deleted_ranking: None,
+            opt_output: None,
+            opt_tee: false,
+            opt_restore_mtime: None,
             uniqueness: ListSnapsOfType::UniqueMetadata,
+            probe_order: config.probe_order,
+            opt_parent_fallback: config.opt_parent_fallback,
+            opt_mtime_window: config.opt_mtime_window,
+            hooks: config.hooks.clone(),
+            opt_audit_log: config.opt_audit_log.clone(),
+            opt_rename_template: config.opt_rename_template.clone(),
+            opt_owner_map: config.opt_owner_map.clone(),
             opt_omit_ditto: config.opt_omit_ditto,
             requested_utc_offset: config.requested_utc_offset,
             exec_mode: ExecMode::BasicDisplay,
@@ -145,6 +187,9 @@ impl From<Vec<PathData>> for Config {
             dataset_collection: config.dataset_collection.clone(),
             pwd: config.pwd.clone(),
             opt_requested_dir: config.opt_requested_dir.clone(),
+            opt_snap_classes: config.opt_snap_classes.clone(),
+            opt_date_format: config.opt_date_format.clone(),
+            number_format: config.number_format.clone(),
         }
     }
 }
@@ -0,0 +1,356 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+use crate::config::generate::PrintMode;
+use crate::display_map::datasets::DatasetInventory;
+use crate::library::results::{HttmError, HttmResult};
+use crate::library::utility::{delimiter, fs_type_from_hidden_dir, print_output_buf};
+use crate::parse::aliases::MapOfAliases;
+use crate::parse::mounts::FilesystemType;
+use crate::{BTRFS_SNAPPER_HIDDEN_DIRECTORY, GLOBAL_CONFIG, ZFS_SNAPSHOT_DIRECTORY};
+use std::path::{Path, PathBuf};
+
+const NUM_SAMPLE_SNAP_NAMES: usize = 3;
+
+#[derive(Debug)]
+pub struct AliasCheck {
+    local_dir: PathBuf,
+    remote_dir: PathBuf,
+    opt_fs_type: Option<&'static str>,
+    is_known_mount: bool,
+    snap_names: Vec<String>,
+    opt_nested_within: Option<PathBuf>,
+    problems: Vec<String>,
+}
+
+impl AliasCheck {
+    fn new(configured: &[(PathBuf, PathBuf)], idx: usize) -> Self {
+        let (local_dir, remote_dir) = &configured[idx];
+        let mut problems = Vec::new();
+
+        if let Some((_later_local_dir, later_remote_dir)) =
+            MapOfAliases::shadowed_by(configured, idx)
+        {
+            problems.push(format!(
+                "The local directory {:?} is specified again, later, as an alias of {:?}, and the alias specified last takes precedence, \
+                so this alias is ignored.  Remove one of the two aliases.",
+                local_dir, later_remote_dir
+            ));
+        }
+
+        // nested aliases are permitted: paths beneath the nested local dir are claimed by the nested alias
+        let opt_nested_within = configured
+            .iter()
+            .map(|(other_local_dir, _other_remote_dir)| other_local_dir)
+            .filter(|other_local_dir| {
+                *other_local_dir != local_dir && local_dir.starts_with(other_local_dir)
+            })
+            .max_by_key(|other_local_dir| other_local_dir.components().count())
+            .cloned();
+
+        if !local_dir.exists() {
+            problems.push(format!(
+                "The local directory {:?} does not exist.  Check the spelling of the local half of the alias (before the ':').",
+                local_dir
+            ));
+        } else if !local_dir.is_dir() {
+            problems.push(format!(
+                "The local directory {:?} is not a directory.  An alias must map a directory, not a file.",
+                local_dir
+            ));
+        }
+
+        if !remote_dir.exists() {
+            problems.push(format!(
+                "The snapshot directory {:?} does not exist, or is not mounted.  Is the remote share mounted?",
+                remote_dir
+            ));
+        }
+
+        let opt_fs_type = fs_type_from_hidden_dir(remote_dir);
+
+        if remote_dir.exists() && opt_fs_type.is_none() {
+            problems.push(format!(
+                "No {:?} or {:?} directory was found within {:?}.  \
+                The remote half of the alias (after the ':') must be the mount point of a ZFS dataset or btrfs-snapper subvolume.  \
+                For ZFS shares, you may need to set 'snapdir=visible', or to export the '.zfs' directory.",
+                ZFS_SNAPSHOT_DIRECTORY, BTRFS_SNAPPER_HIDDEN_DIRECTORY, remote_dir
+            ));
+        }
+
+        let opt_snap_mounts = GLOBAL_CONFIG
+            .dataset_collection
+            .map_of_snaps
+            .get(remote_dir);
+
+        let is_known_mount = opt_snap_mounts.is_some();
+
+        let snap_names: Vec<String> = match opt_snap_mounts {
+            Some(snap_mounts) => snap_mounts
+                .iter()
+                .map(|snap_mount| DatasetInventory::snap_name(snap_mount))
+                .collect(),
+            None => Self::snap_names_from_hidden_dir(remote_dir, opt_fs_type.as_ref()),
+        };
+
+        if opt_fs_type.is_some() {
+            if !is_known_mount {
+                problems.push(format!(
+                    "{:?} is not a mount point httm has discovered, so httm will not search it for snapshots.  \
+                    The remote half of the alias must be the dataset's mount point itself, not a directory beneath it.  \
+                    Use --list-datasets to see the mount points httm has discovered.",
+                    remote_dir
+                ));
+            }
+
+            if snap_names.is_empty() {
+                problems.push(format!(
+                    "No snapshots were found within {:?}.  Have any snapshots been taken of this dataset, \
+                    and does this user have permission to read its snapshot directory?",
+                    remote_dir
+                ));
+            }
+        }
+
+        Self {
+            local_dir: local_dir.to_path_buf(),
+            remote_dir: remote_dir.to_path_buf(),
+            opt_fs_type: opt_fs_type.as_ref().map(DatasetInventory::fs_type_name),
+            is_known_mount,
+            snap_names,
+            opt_nested_within,
+            problems,
+        }
+    }
+
+    fn snap_names_from_hidden_dir(
+        remote_dir: &Path,
+        opt_fs_type: Option<&FilesystemType>,
+    ) -> Vec<String> {
+        let hidden_dir = match opt_fs_type {
+            Some(FilesystemType::Zfs) => remote_dir.join(ZFS_SNAPSHOT_DIRECTORY),
+            Some(FilesystemType::Btrfs(_)) => remote_dir.join(BTRFS_SNAPPER_HIDDEN_DIRECTORY),
+            _ => return Vec::new(),
+        };
+
+        let Ok(read_dir) = std::fs::read_dir(hidden_dir) else {
+            return Vec::new();
+        };
+
+        let mut snap_names: Vec<String> = read_dir
+            .flatten()
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .collect();
+
+        snap_names.sort();
+
+        snap_names
+    }
+
+    fn is_ok(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+// which alias, if any, claimed an input path, as (local dir, remote dir, relative path)
+#[derive(Debug)]
+pub struct AliasClaim {
+    input_path: PathBuf,
+    opt_claim: Option<(PathBuf, PathBuf, PathBuf)>,
+}
+
+impl AliasClaim {
+    fn new(map_of_aliases: &MapOfAliases, input_path: &Path) -> Self {
+        let opt_claim = map_of_aliases
+            .resolve(input_path)
+            .and_then(|(local_dir, remote)| {
+                input_path
+                    .strip_prefix(local_dir)
+                    .ok()
+                    .map(|relative_path| {
+                        (
+                            local_dir.to_path_buf(),
+                            remote.remote_dir.clone(),
+                            relative_path.to_path_buf(),
+                        )
+                    })
+            });
+
+        Self {
+            input_path: input_path.to_path_buf(),
+            opt_claim,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct AliasReport {
+    inner: Vec<AliasCheck>,
+    claims: Vec<AliasClaim>,
+}
+
+impl AliasReport {
+    pub fn new() -> HttmResult<Self> {
+        let Some(map_of_aliases) = &GLOBAL_CONFIG.dataset_collection.opt_map_of_aliases else {
+            return Err(HttmError::new(
+                "No aliases are configured.  Specify aliases with MAP_ALIASES (or the HTTM_MAP_ALIASES environment variable), \
+                or with REMOTE_DIR and LOCAL_DIR (or the HTTM_REMOTE_DIR and HTTM_LOCAL_DIR environment variables).",
+            )
+            .into());
+        };
+
+        let configured = map_of_aliases.configured();
+
+        let inner = (0..configured.len())
+            .map(|idx| AliasCheck::new(configured, idx))
+            .collect();
+
+        let claims = GLOBAL_CONFIG
+            .paths
+            .iter()
+            .map(|pathdata| AliasClaim::new(map_of_aliases, &pathdata.path_buf))
+            .collect();
+
+        Ok(Self { inner, claims })
+    }
+
+    pub fn exec() -> HttmResult<()> {
+        let report = Self::new()?;
+
+        print_output_buf(&report.to_string())?;
+
+        let num_misconfigured = report.inner.iter().filter(|check| !check.is_ok()).count();
+
+        if num_misconfigured > 0 {
+            let msg = format!(
+                "{} of {} alias/es configured is/are misconfigured.",
+                num_misconfigured,
+                report.inner.len()
+            );
+            return Err(HttmError::new(&msg).into());
+        }
+
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for AliasReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let delimiter = delimiter();
+
+        let buffer: String = match &GLOBAL_CONFIG.print_mode {
+            PrintMode::RawNewline | PrintMode::RawZero => self
+                .inner
+                .iter()
+                .map(|check| {
+                    format!(
+                        "{}\t{}\t{}\t{}\t{}\t{}{delimiter}",
+                        check.local_dir.to_string_lossy(),
+                        check.remote_dir.to_string_lossy(),
+                        check.opt_fs_type.unwrap_or("unknown"),
+                        check.is_known_mount,
+                        check.snap_names.len(),
+                        if check.is_ok() { "ok" } else { "error" }
+                    )
+                })
+                .chain(self.claims.iter().map(|claim| match &claim.opt_claim {
+                    Some((local_dir, remote_dir, relative_path)) => format!(
+                        "{}\t{}\t{}\t{}{delimiter}",
+                        claim.input_path.to_string_lossy(),
+                        local_dir.to_string_lossy(),
+                        remote_dir.to_string_lossy(),
+                        relative_path.to_string_lossy()
+                    ),
+                    None => format!(
+                        "{}\t-\t-\t-{delimiter}",
+                        claim.input_path.to_string_lossy()
+                    ),
+                }))
+                .collect(),
+            PrintMode::FormattedDefault | PrintMode::FormattedNotPretty => self
+                .inner
+                .iter()
+                .map(|check| {
+                    let sample: Vec<&str> = check
+                        .snap_names
+                        .iter()
+                        .take(NUM_SAMPLE_SNAP_NAMES)
+                        .map(String::as_str)
+                        .collect();
+
+                    let problems: String = check
+                        .problems
+                        .iter()
+                        .map(|problem| format!("\tERROR: {problem}\n"))
+                        .collect();
+
+                    let nested = match &check.opt_nested_within {
+                        Some(outer_local_dir) => format!(
+                            "\tnested within alias: \"{}\" (paths beneath \"{}\" are claimed by this alias)\n",
+                            outer_local_dir.to_string_lossy(),
+                            check.local_dir.to_string_lossy()
+                        ),
+                        None => String::new(),
+                    };
+
+                    format!(
+                        "\"{}\" : \"{}\" ({})\n\
+                        \tlocal dir exists: {}\n\
+                        \tsnapshot dir exists: {}\n\
+                        \tfilesystem type: {}\n\
+                        \tdiscovered mount: {}\n\
+                        \tsnapshots: {}{}\n\
+                        {}{}",
+                        check.local_dir.to_string_lossy(),
+                        check.remote_dir.to_string_lossy(),
+                        if check.is_ok() { "OK" } else { "MISCONFIGURED" },
+                        check.local_dir.exists(),
+                        check.remote_dir.exists(),
+                        check.opt_fs_type.unwrap_or("unknown"),
+                        check.is_known_mount,
+                        check.snap_names.len(),
+                        if sample.is_empty() {
+                            String::new()
+                        } else {
+                            format!(" (eg. {})", sample.join(", "))
+                        },
+                        nested,
+                        problems
+                    )
+                })
+                .chain(
+                    (!self.claims.is_empty())
+                        .then(|| "input paths (the alias with the longest local dir claims each path):\n".to_owned()),
+                )
+                .chain(self.claims.iter().map(|claim| match &claim.opt_claim {
+                    Some((local_dir, remote_dir, relative_path)) => format!(
+                        "\t\"{}\" : claimed by \"{}\" : \"{}\" (as \"{}\")\n",
+                        claim.input_path.to_string_lossy(),
+                        local_dir.to_string_lossy(),
+                        remote_dir.to_string_lossy(),
+                        relative_path.to_string_lossy()
+                    ),
+                    None => format!(
+                        "\t\"{}\" : claimed by no alias\n",
+                        claim.input_path.to_string_lossy()
+                    ),
+                }))
+                .collect(),
+        };
+
+        write!(f, "{buffer}")
+    }
+}
@@ -0,0 +1,203 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::config::generate::PrintMode;
+use crate::library::utility::delimiter;
+use crate::parse::mounts::{FilesystemType, MountType};
+use crate::{BTRFS_SNAPPER_SUFFIX, GLOBAL_CONFIG};
+use serde::ser::{SerializeMap, SerializeStruct};
+use serde::{Serialize, Serializer};
+use std::collections::BTreeMap;
+use std::ops::Deref;
+use std::path::Path;
+
+#[derive(Debug)]
+pub struct DatasetEntry {
+    source: String,
+    fs_type: &'static str,
+    mount_type: &'static str,
+    snap_names: Vec<String>,
+}
+
+impl Serialize for DatasetEntry {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("DatasetEntry", 5)?;
+        state.serialize_field("source", &self.source)?;
+        state.serialize_field("fs_type", &self.fs_type)?;
+        state.serialize_field("mount_type", &self.mount_type)?;
+        state.serialize_field("snapshot_count", &self.snap_names.len())?;
+        state.serialize_field("snapshot_names", &self.snap_names)?;
+        state.end()
+    }
+}
+
+// an inventory of what httm discovered: every dataset, by mount, and its snapshots
+#[derive(Debug)]
+pub struct DatasetInventory {
+    inner: BTreeMap<String, DatasetEntry>,
+}
+
+impl Deref for DatasetInventory {
+    type Target = BTreeMap<String, DatasetEntry>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl Serialize for DatasetInventory {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_map(Some(self.inner.len()))?;
+        self.inner
+            .iter()
+            .try_for_each(|(k, v)| state.serialize_entry(k, v))?;
+        state.end()
+    }
+}
+
+impl DatasetInventory {
+    pub fn new() -> Self {
+        let dataset_collection = &GLOBAL_CONFIG.dataset_collection;
+
+        let inner = dataset_collection
+            .map_of_datasets
+            .iter()
+            .map(|(mount, dataset_info)| {
+                let snap_names: Vec<String> = dataset_collection
+                    .map_of_snaps
+                    .get(mount)
+                    .into_iter()
+                    .flatten()
+                    .map(|snap_mount| Self::snap_name(snap_mount))
+                    .collect();
+
+                let entry = DatasetEntry {
+                    source: dataset_info.source.to_string_lossy().to_string(),
+                    fs_type: Self::fs_type_name(&dataset_info.fs_type),
+                    mount_type: match dataset_info.mount_type {
+                        MountType::Local => "local",
+                        MountType::Network => "network",
+                    },
+                    snap_names,
+                };
+
+                (mount.to_string_lossy().to_string(), entry)
+            })
+            .collect();
+
+        Self { inner }
+    }
+
+    pub fn fs_type_name(fs_type: &FilesystemType) -> &'static str {
+        match fs_type {
+            FilesystemType::Zfs => "zfs",
+            FilesystemType::Btrfs(_) => "btrfs",
+            FilesystemType::Nilfs2 => "nilfs2",
+            FilesystemType::Apfs => "apfs",
+            FilesystemType::Restic(_) => "restic",
+            FilesystemType::ShadowCopy => "smb",
+        }
+    }
+
+    // snapper snap mounts look like: /.snapshots/<number>/snapshot, so the name is the number
+    pub fn snap_name(snap_mount: &Path) -> String {
+        let named = if snap_mount.ends_with(BTRFS_SNAPPER_SUFFIX) {
+            snap_mount.parent().unwrap_or(snap_mount)
+        } else {
+            snap_mount
+        };
+
+        named
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| snap_mount.to_string_lossy().to_string())
+    }
+
+    pub fn to_json(&self) -> String {
+        let res = match GLOBAL_CONFIG.print_mode {
+            PrintMode::FormattedNotPretty | PrintMode::RawNewline | PrintMode::RawZero => {
+                serde_json::to_string(self)
+            }
+            PrintMode::FormattedDefault => serde_json::to_string_pretty(self),
+        };
+
+        match res {
+            Ok(s) => {
+                let delimiter = delimiter();
+                format!("{s}{delimiter}")
+            }
+            Err(error) => {
+                eprintln!("Error: {error}");
+                std::process::exit(1)
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for DatasetInventory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if GLOBAL_CONFIG.opt_json {
+            return write!(f, "{}", self.to_json());
+        }
+
+        let delimiter = delimiter();
+
+        let buffer: String = match &GLOBAL_CONFIG.print_mode {
+            PrintMode::RawNewline | PrintMode::RawZero => self
+                .iter()
+                .map(|(mount, entry)| {
+                    format!(
+                        "{}\t{}\t{}\t{}\t{}{delimiter}",
+                        mount,
+                        entry.source,
+                        entry.fs_type,
+                        entry.mount_type,
+                        entry.snap_names.len()
+                    )
+                })
+                .collect(),
+            PrintMode::FormattedDefault | PrintMode::FormattedNotPretty => self
+                .iter()
+                .map(|(mount, entry)| {
+                    let snap_names: String = entry
+                        .snap_names
+                        .iter()
+                        .map(|name| format!("\t{name}\n"))
+                        .collect();
+
+                    format!(
+                        "\"{}\" : {} ({}, {}), {} snapshot/s\n{}",
+                        mount,
+                        entry.source,
+                        entry.fs_type,
+                        entry.mount_type,
+                        entry.snap_names.len(),
+                        snap_names
+                    )
+                })
+                .collect(),
+        };
+
+        write!(f, "{buffer}")
+    }
+}
@@ -21,27 +21,39 @@ use crate::data::paths::ZfsSnapPathGuard;
 use crate::display_versions::format::{NOT_SO_PRETTY_FIXED_WIDTH_PADDING, QUOTATION_MARKS_LEN};
 use crate::library::utility::delimiter;
 use crate::{MountsForFiles, SnapNameMap, VersionsMap, GLOBAL_CONFIG};
+use hashbrown::HashMap;
+use indexmap::IndexMap;
 use serde::ser::SerializeMap;
 use serde::{Serialize, Serializer};
-use std::collections::BTreeMap;
 use std::ops::Deref;
 
 #[derive(Debug)]
 pub struct PrintAsMap {
-    inner: BTreeMap<String, Vec<String>>,
+    inner: IndexMap<String, Vec<String>>,
+    // key: (key, value), val: a label displayed after the value, in formatted output only,
+    // so that raw and JSON output remain the values alone
+    labels: HashMap<(String, String), &'static str>,
 }
 
 impl Deref for PrintAsMap {
-    type Target = BTreeMap<String, Vec<String>>;
+    type Target = IndexMap<String, Vec<String>>;
 
     fn deref(&self) -> &Self::Target {
         &self.inner
     }
 }
 
-impl From<BTreeMap<String, Vec<String>>> for PrintAsMap {
-    fn from(map: BTreeMap<String, Vec<String>>) -> Self {
-        Self { inner: map }
+impl From<IndexMap<String, Vec<String>>> for PrintAsMap {
+    fn from(mut map: IndexMap<String, Vec<String>>) -> Self {
+        // by default, paths are displayed in sorted order, else in the order given
+        if !GLOBAL_CONFIG.opt_preserve_order {
+            map.sort_keys();
+        }
+
+        Self {
+            inner: map,
+            labels: HashMap::new(),
+        }
     }
 }
 
@@ -62,25 +74,45 @@ impl<'a> From<&MountsForFiles<'a>> for PrintAsMap {
     fn from(mounts_for_files: &MountsForFiles) -> Self {
         let mount_display = mounts_for_files.mount_display();
 
+        let mut labels = HashMap::new();
+
         let inner = mounts_for_files
             .iter()
             .map(|prox| {
                 let pathdata = prox.pathdata;
+                let key = pathdata.path_buf.to_string_lossy().to_string();
 
                 let res = prox
                     .datasets_of_interest()
                     .map(PathData::from)
-                    .filter_map(|mount| match &ZfsSnapPathGuard::new(prox.pathdata) {
-                        Some(spg) => mount_display.display(spg, &mount),
-                        None => mount_display.display(pathdata, &mount),
+                    .filter_map(|mount| {
+                        let opt_display = match &ZfsSnapPathGuard::new(prox.pathdata) {
+                            Some(spg) => mount_display.display(spg, &mount),
+                            None => mount_display.display(pathdata, &mount),
+                        };
+
+                        opt_display.map(|path| (path.to_string_lossy().to_string(), mount))
+                    })
+                    .map(|(value, mount)| {
+                        // received replicas, and other read-only datasets, may be listed, but not restored to
+                        if GLOBAL_CONFIG
+                            .dataset_collection
+                            .is_readonly(&mount.path_buf)
+                        {
+                            labels.insert((key.clone(), value.clone()), "read-only");
+                        }
+
+                        value
                     })
-                    .map(|path| path.to_string_lossy().to_string())
                     .collect();
 
-                (pathdata.path_buf.to_string_lossy().to_string(), res)
+                (key, res)
             })
-            .collect();
-        Self { inner }
+            .collect::<IndexMap<String, Vec<String>>>();
+
+        let mut print_as_map = Self::from(inner);
+        print_as_map.labels = labels;
+        print_as_map
     }
 }
 
@@ -95,8 +127,8 @@ impl From<&VersionsMap> for PrintAsMap {
                     .collect();
                 (key.path_buf.to_string_lossy().to_string(), res)
             })
-            .collect();
-        Self { inner }
+            .collect::<IndexMap<String, Vec<String>>>();
+        Self::from(inner)
     }
 }
 
@@ -105,8 +137,8 @@ impl From<&SnapNameMap> for PrintAsMap {
         let inner = map
             .iter()
             .map(|(key, value)| (key.path_buf.to_string_lossy().to_string(), value.clone()))
-            .collect();
-        Self { inner }
+            .collect::<IndexMap<String, Vec<String>>>();
+        Self::from(inner)
     }
 }
 
@@ -184,17 +216,23 @@ impl PrintAsMap {
                     .iter()
                     .enumerate()
                     .map(|(idx, value)| {
+                        let label = match self.labels.get(&(key.clone(), value.clone())) {
+                            Some(label) => format!(" ({label})"),
+                            None => String::new(),
+                        };
+
                         if matches!(&GLOBAL_CONFIG.print_mode, PrintMode::FormattedNotPretty) {
-                            format!("{NOT_SO_PRETTY_FIXED_WIDTH_PADDING}{value}")
+                            format!("{NOT_SO_PRETTY_FIXED_WIDTH_PADDING}{value}{label}")
                         } else if idx == 0 {
                             format!(
-                                "{:<width$} : \"{}\"\n",
+                                "{:<width$} : \"{}\"{}\n",
                                 display_path,
                                 value,
+                                label,
                                 width = padding
                             )
                         } else {
-                            format!("{:<padding$} : \"{value}\"\n", "")
+                            format!("{:<padding$} : \"{value}\"{label}\n", "")
                         }
                     })
                     .collect::<String>();
@@ -0,0 +1,397 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::config::generate::PrintMode;
+use crate::data::paths::PathData;
+use crate::display_map::datasets::DatasetInventory;
+use crate::library::diagnostics::Diagnostic;
+use crate::library::results::HttmResult;
+use crate::library::utility::{date_string, delimiter, path_is_filter_dir, print_output_buf};
+use crate::lookup::versions::ProximateDatasetAndOptAlts;
+use crate::{BTRFS_SNAPPER_HIDDEN_DIRECTORY, GLOBAL_CONFIG, ZFS_HIDDEN_DIRECTORY};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs::read_dir;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+#[derive(Debug)]
+pub struct DatasetCoverage {
+    fs_type: &'static str,
+    num_files: usize,
+    num_snaps: usize,
+    opt_newest_snap: Option<SystemTime>,
+}
+
+impl DatasetCoverage {
+    fn new(prox_opt_alts: &ProximateDatasetAndOptAlts) -> Self {
+        let dataset_collection = &GLOBAL_CONFIG.dataset_collection;
+
+        let snap_mounts: Vec<&PathBuf> = prox_opt_alts
+            .datasets_of_interest()
+            .filter_map(|dataset| dataset_collection.map_of_snaps.get(dataset))
+            .flatten()
+            .collect();
+
+        let opt_newest_snap = snap_mounts
+            .iter()
+            .filter_map(|snap_mount| dataset_collection.snap_mount_time(snap_mount))
+            .max();
+
+        let fs_type = dataset_collection
+            .map_of_datasets
+            .get(prox_opt_alts.proximate_dataset)
+            .map(|dataset_info| DatasetInventory::fs_type_name(&dataset_info.fs_type))
+            .unwrap_or("alias");
+
+        Self {
+            fs_type,
+            num_files: 0,
+            num_snaps: snap_mounts.len(),
+            opt_newest_snap,
+        }
+    }
+
+    fn is_protected(&self) -> bool {
+        self.num_snaps > 0
+    }
+}
+
+#[derive(Debug)]
+pub struct UncoveredDir {
+    // unsupported filesystems mounted beneath the directory requested are not searched,
+    // as these are just as likely to be /proc or /sys as a scratch disk
+    opt_num_files: Option<usize>,
+}
+
+// a coverage audit of a directory tree: which files are on datasets with snapshots, the age
+// of the newest snapshot covering those files, and which files are not covered at all
+#[derive(Debug, Default)]
+pub struct ProtectionReport {
+    datasets: BTreeMap<PathBuf, DatasetCoverage>,
+    uncovered: BTreeMap<PathBuf, UncoveredDir>,
+}
+
+enum Coverage {
+    Dataset(PathBuf),
+    Uncovered(PathBuf),
+}
+
+impl ProtectionReport {
+    pub fn exec() -> HttmResult<()> {
+        let report = Self::new()?;
+
+        print_output_buf(&report.to_string())
+    }
+
+    fn new() -> HttmResult<Self> {
+        let mut report = Self::default();
+
+        GLOBAL_CONFIG
+            .paths
+            .iter()
+            .try_for_each(|pathdata| report.search(&pathdata.path_buf))?;
+
+        Ok(report)
+    }
+
+    fn search(&mut self, requested: &Path) -> HttmResult<()> {
+        if !requested.is_dir() {
+            let coverage = self.coverage(requested, None);
+            self.add_file(&coverage);
+            return Ok(());
+        }
+
+        // a queue of dirs to search, each with the topmost uncovered dir above, if any
+        let mut queue: Vec<(PathBuf, Option<PathBuf>)> = vec![(requested.to_path_buf(), None)];
+
+        while let Some((dir, opt_uncovered_root)) = queue.pop() {
+            // a dataset can only be mounted upon a directory, so files always share the coverage of their directory
+            let coverage = self.coverage(&dir, opt_uncovered_root);
+
+            let read_dir = match read_dir(&dir) {
+                Ok(read_dir) => read_dir,
+                Err(err) => {
+                    Diagnostic::warn(
+                        "unreadable_dir",
+                        format!(
+                            "Could not read directory {:?}, and its files are not included in the report: {}",
+                            dir, err
+                        ),
+                    )
+                    .with_path(&dir)
+                    .print();
+                    continue;
+                }
+            };
+
+            for entry in read_dir.flatten() {
+                let path = entry.path();
+
+                let Ok(file_type) = entry.file_type() else {
+                    continue;
+                };
+
+                if !file_type.is_dir() {
+                    self.add_file(&coverage);
+                    continue;
+                }
+
+                // never search the hidden snapshot directory for live files
+                if path.ends_with(ZFS_HIDDEN_DIRECTORY)
+                    || path.ends_with(BTRFS_SNAPPER_HIDDEN_DIRECTORY)
+                    || GLOBAL_CONFIG
+                        .dataset_collection
+                        .opt_common_snap_dir
+                        .as_ref()
+                        .is_some_and(|common_snap_dir| path == *common_snap_dir)
+                {
+                    continue;
+                }
+
+                if path_is_filter_dir(&path) {
+                    self.uncovered.entry(path).or_insert(UncoveredDir {
+                        opt_num_files: None,
+                    });
+                    continue;
+                }
+
+                let opt_child_uncovered_root = match &coverage {
+                    Coverage::Dataset(_) => None,
+                    Coverage::Uncovered(root) => Some(root.clone()),
+                };
+
+                queue.push((path, opt_child_uncovered_root));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn coverage(&mut self, path: &Path, opt_uncovered_root: Option<PathBuf>) -> Coverage {
+        let pathdata = PathData::from(path);
+
+        match ProximateDatasetAndOptAlts::new(&pathdata) {
+            Ok(prox_opt_alts) => {
+                let proximate_dataset = prox_opt_alts.proximate_dataset.to_path_buf();
+
+                if !self.datasets.contains_key(&proximate_dataset) {
+                    self.datasets.insert(
+                        proximate_dataset.clone(),
+                        DatasetCoverage::new(&prox_opt_alts),
+                    );
+                }
+
+                Coverage::Dataset(proximate_dataset)
+            }
+            Err(_) => {
+                let root = opt_uncovered_root.unwrap_or_else(|| path.to_path_buf());
+
+                self.uncovered.entry(root.clone()).or_insert(UncoveredDir {
+                    opt_num_files: Some(0),
+                });
+
+                Coverage::Uncovered(root)
+            }
+        }
+    }
+
+    fn add_file(&mut self, coverage: &Coverage) {
+        match coverage {
+            Coverage::Dataset(dataset) => {
+                if let Some(dataset_coverage) = self.datasets.get_mut(dataset) {
+                    dataset_coverage.num_files += 1;
+                }
+            }
+            Coverage::Uncovered(root) => {
+                if let Some(num_files) = self
+                    .uncovered
+                    .get_mut(root)
+                    .and_then(|uncovered| uncovered.opt_num_files.as_mut())
+                {
+                    *num_files += 1;
+                }
+            }
+        }
+    }
+
+    fn display_time(time: &SystemTime) -> String {
+        date_string(
+            GLOBAL_CONFIG.requested_utc_offset,
+            time,
+            GLOBAL_CONFIG.display_date_format(),
+        )
+    }
+
+    pub fn display_age(time: &SystemTime) -> String {
+        let Ok(age) = SystemTime::now().duration_since(*time) else {
+            return "just now".to_owned();
+        };
+
+        let secs = age.as_secs();
+
+        match secs {
+            0..=59 => format!("{secs} second/s ago"),
+            60..=3599 => format!("{} minute/s ago", secs / 60),
+            3600..=86399 => format!("{} hour/s ago", secs / 3600),
+            _ => format!("{} day/s ago", secs / 86400),
+        }
+    }
+
+    fn to_json(&self) -> String {
+        let datasets: serde_json::Map<String, Value> = self
+            .datasets
+            .iter()
+            .map(|(dataset, coverage)| {
+                let value = serde_json::json!({
+                    "fs_type": coverage.fs_type,
+                    "protected": coverage.is_protected(),
+                    "file_count": coverage.num_files,
+                    "snapshot_count": coverage.num_snaps,
+                    "newest_snapshot": coverage.opt_newest_snap.as_ref().map(Self::display_time),
+                });
+
+                (dataset.to_string_lossy().to_string(), value)
+            })
+            .collect();
+
+        let uncovered: serde_json::Map<String, Value> = self
+            .uncovered
+            .iter()
+            .map(|(dir, uncovered)| {
+                let value = serde_json::json!({
+                    "file_count": uncovered.opt_num_files,
+                    "searched": uncovered.opt_num_files.is_some(),
+                });
+
+                (dir.to_string_lossy().to_string(), value)
+            })
+            .collect();
+
+        let value = serde_json::json!({
+            "datasets": datasets,
+            "unsupported": uncovered,
+        });
+
+        let res = match GLOBAL_CONFIG.print_mode {
+            PrintMode::FormattedNotPretty | PrintMode::RawNewline | PrintMode::RawZero => {
+                serde_json::to_string(&value)
+            }
+            PrintMode::FormattedDefault => serde_json::to_string_pretty(&value),
+        };
+
+        match res {
+            Ok(s) => {
+                let delimiter = delimiter();
+                format!("{s}{delimiter}")
+            }
+            Err(error) => {
+                eprintln!("Error: {error}");
+                std::process::exit(1)
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for ProtectionReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if GLOBAL_CONFIG.opt_json {
+            return write!(f, "{}", self.to_json());
+        }
+
+        let delimiter = delimiter();
+
+        let buffer: String = match &GLOBAL_CONFIG.print_mode {
+            PrintMode::RawNewline | PrintMode::RawZero => {
+                let datasets = self.datasets.iter().map(|(dataset, coverage)| {
+                    format!(
+                        "{}\t{}\t{}\t{}\t{}\t{}{delimiter}",
+                        dataset.to_string_lossy(),
+                        coverage.fs_type,
+                        if coverage.is_protected() {
+                            "protected"
+                        } else {
+                            "unsnapshotted"
+                        },
+                        coverage.num_files,
+                        coverage.num_snaps,
+                        coverage
+                            .opt_newest_snap
+                            .as_ref()
+                            .map(Self::display_time)
+                            .unwrap_or_default()
+                    )
+                });
+
+                let uncovered = self.uncovered.iter().map(|(dir, uncovered)| {
+                    format!(
+                        "{}\t-\tunsupported\t{}\t0\t{delimiter}",
+                        dir.to_string_lossy(),
+                        uncovered
+                            .opt_num_files
+                            .map(|num_files| num_files.to_string())
+                            .unwrap_or_else(|| "-".to_owned())
+                    )
+                });
+
+                datasets.chain(uncovered).collect()
+            }
+            PrintMode::FormattedDefault | PrintMode::FormattedNotPretty => {
+                let datasets = self.datasets.iter().map(|(dataset, coverage)| {
+                    let status = match &coverage.opt_newest_snap {
+                        Some(newest) if coverage.is_protected() => format!(
+                            "{} snapshot/s, newest: {} ({})",
+                            coverage.num_snaps,
+                            Self::display_time(newest),
+                            Self::display_age(newest)
+                        ),
+                        _ if coverage.is_protected() => {
+                            format!("{} snapshot/s", coverage.num_snaps)
+                        }
+                        _ => "UNPROTECTED (dataset has no snapshots)".to_owned(),
+                    };
+
+                    format!(
+                        "\"{}\" : {}, {} file/s, {}\n",
+                        dataset.to_string_lossy(),
+                        coverage.fs_type,
+                        coverage.num_files,
+                        status
+                    )
+                });
+
+                let uncovered = self.uncovered.iter().map(|(dir, uncovered)| {
+                    match uncovered.opt_num_files {
+                        Some(num_files) => format!(
+                            "\"{}\" : UNPROTECTED (unsupported filesystem), {} file/s\n",
+                            dir.to_string_lossy(),
+                            num_files
+                        ),
+                        None => format!(
+                            "\"{}\" : UNPROTECTED (unsupported filesystem mounted beneath, not searched)\n",
+                            dir.to_string_lossy()
+                        ),
+                    }
+                });
+
+                datasets.chain(uncovered).collect()
+            }
+        };
+
+        write!(f, "{buffer}")
+    }
+}
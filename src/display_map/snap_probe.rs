@@ -0,0 +1,279 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+use crate::config::generate::PrintMode;
+use crate::display_map::datasets::DatasetInventory;
+use crate::library::results::{HttmError, HttmResult};
+use crate::library::utility::{delimiter, print_output_buf};
+use crate::parse::mounts::MountType;
+use crate::parse::network_probe::{with_timeout, PROBE_TIMEOUT};
+use crate::GLOBAL_CONFIG;
+use rayon::prelude::*;
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ProbeOutcome {
+    Reachable,
+    // the snapshot could not be read, with the error given
+    Unreachable(String),
+    // the snapshot did not respond within the probe timeout, as a hung automount, or NFS export, might not
+    Hung,
+}
+
+impl ProbeOutcome {
+    // reading a snapshot's directory is what triggers an automount, so the directory is read, not merely stat-ed
+    fn new(snap_mount: &Path) -> Self {
+        let snap_mount_clone = snap_mount.to_path_buf();
+
+        let Some(res) = with_timeout(move || {
+            std::fs::read_dir(&snap_mount_clone)
+                .and_then(|mut read_dir| read_dir.next().transpose())
+                .map_err(|err| err.to_string())
+        }) else {
+            return ProbeOutcome::Hung;
+        };
+
+        match res {
+            Ok(_) => ProbeOutcome::Reachable,
+            Err(err) => ProbeOutcome::Unreachable(err),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct DatasetProbe {
+    source: String,
+    fs_type: &'static str,
+    mount_type: &'static str,
+    num_reachable: usize,
+    // (snapshot name, error)
+    unreachable: Vec<(String, String)>,
+    hung: Vec<String>,
+}
+
+impl DatasetProbe {
+    fn new(mount: &Path, snap_mounts: &[PathBuf]) -> Option<Self> {
+        let dataset_info = GLOBAL_CONFIG
+            .dataset_collection
+            .map_of_datasets
+            .get(mount)?;
+
+        let outcomes: Vec<(String, ProbeOutcome)> = snap_mounts
+            .par_iter()
+            .map(|snap_mount| {
+                (
+                    DatasetInventory::snap_name(snap_mount),
+                    ProbeOutcome::new(snap_mount),
+                )
+            })
+            .collect();
+
+        let mut num_reachable = 0usize;
+        let mut unreachable = Vec::new();
+        let mut hung = Vec::new();
+
+        outcomes
+            .into_iter()
+            .for_each(|(snap_name, outcome)| match outcome {
+                ProbeOutcome::Reachable => num_reachable += 1,
+                ProbeOutcome::Unreachable(err) => unreachable.push((snap_name, err)),
+                ProbeOutcome::Hung => hung.push(snap_name),
+            });
+
+        Some(Self {
+            source: dataset_info.source.to_string_lossy().to_string(),
+            fs_type: DatasetInventory::fs_type_name(&dataset_info.fs_type),
+            mount_type: match dataset_info.mount_type {
+                MountType::Local => "local",
+                MountType::Network => "network",
+            },
+            num_reachable,
+            unreachable,
+            hung,
+        })
+    }
+
+    fn num_snaps(&self) -> usize {
+        self.num_reachable + self.unreachable.len() + self.hung.len()
+    }
+
+    fn num_problems(&self) -> usize {
+        self.unreachable.len() + self.hung.len()
+    }
+}
+
+impl Serialize for DatasetProbe {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let unreachable: Vec<BTreeMap<&str, &str>> = self
+            .unreachable
+            .iter()
+            .map(|(snap_name, err)| {
+                BTreeMap::from([("snapshot", snap_name.as_str()), ("error", err.as_str())])
+            })
+            .collect();
+
+        let mut state = serializer.serialize_struct("DatasetProbe", 7)?;
+        state.serialize_field("source", &self.source)?;
+        state.serialize_field("fs_type", &self.fs_type)?;
+        state.serialize_field("mount_type", &self.mount_type)?;
+        state.serialize_field("snapshot_count", &self.num_snaps())?;
+        state.serialize_field("reachable_count", &self.num_reachable)?;
+        state.serialize_field("unreachable", &unreachable)?;
+        state.serialize_field("hung", &self.hung)?;
+        state.end()
+    }
+}
+
+// missing versions are sometimes no more than snapshots which cannot be reached, say, because a NAS
+// does not export its snapshots, or an automount hangs, so every snapshot of every dataset is touched,
+// and those which cannot be read, or which do not respond in time, are reported, per dataset
+#[derive(Debug)]
+pub struct SnapshotProbe {
+    // key: mount
+    inner: BTreeMap<String, DatasetProbe>,
+}
+
+impl SnapshotProbe {
+    pub fn new() -> Self {
+        let inner = GLOBAL_CONFIG
+            .dataset_collection
+            .map_of_snaps
+            .iter()
+            .filter_map(|(mount, snap_mounts)| {
+                DatasetProbe::new(mount, snap_mounts)
+                    .map(|dataset_probe| (mount.to_string_lossy().to_string(), dataset_probe))
+            })
+            .collect();
+
+        Self { inner }
+    }
+
+    pub fn exec() -> HttmResult<()> {
+        let report = Self::new();
+
+        print_output_buf(&report.to_string())?;
+
+        let num_problems: usize = report.inner.values().map(DatasetProbe::num_problems).sum();
+
+        if num_problems > 0 {
+            let num_snaps: usize = report.inner.values().map(DatasetProbe::num_snaps).sum();
+
+            let msg = format!(
+                "{} of {} snapshot/s could not be reached.",
+                num_problems, num_snaps
+            );
+            return Err(HttmError::new(&msg)
+                .with_code("snapshots_unreachable")
+                .into());
+        }
+
+        Ok(())
+    }
+
+    fn to_json(&self) -> String {
+        let res = match GLOBAL_CONFIG.print_mode {
+            PrintMode::FormattedNotPretty | PrintMode::RawNewline | PrintMode::RawZero => {
+                serde_json::to_string(&self.inner)
+            }
+            PrintMode::FormattedDefault => serde_json::to_string_pretty(&self.inner),
+        };
+
+        match res {
+            Ok(s) => {
+                let delimiter = delimiter();
+                format!("{s}{delimiter}")
+            }
+            Err(error) => {
+                eprintln!("Error: {error}");
+                std::process::exit(1)
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for SnapshotProbe {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if GLOBAL_CONFIG.opt_json {
+            return write!(f, "{}", self.to_json());
+        }
+
+        let delimiter = delimiter();
+
+        let buffer: String = match &GLOBAL_CONFIG.print_mode {
+            PrintMode::RawNewline | PrintMode::RawZero => self
+                .inner
+                .iter()
+                .map(|(mount, probe)| {
+                    format!(
+                        "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}{delimiter}",
+                        mount,
+                        probe.source,
+                        probe.fs_type,
+                        probe.mount_type,
+                        probe.num_snaps(),
+                        probe.num_reachable,
+                        probe.unreachable.len(),
+                        probe.hung.len()
+                    )
+                })
+                .collect(),
+            PrintMode::FormattedDefault | PrintMode::FormattedNotPretty => self
+                .inner
+                .iter()
+                .map(|(mount, probe)| {
+                    let unreachable: String = probe
+                        .unreachable
+                        .iter()
+                        .map(|(snap_name, err)| format!("\tUNREACHABLE: {snap_name} ({err})\n"))
+                        .collect();
+
+                    let hung: String = probe
+                        .hung
+                        .iter()
+                        .map(|snap_name| {
+                            format!(
+                                "\tHUNG: {snap_name} (no response within {} seconds)\n",
+                                PROBE_TIMEOUT.as_secs()
+                            )
+                        })
+                        .collect();
+
+                    format!(
+                        "\"{}\" : {} ({}, {}), {} snapshot/s, {} reachable, {} unreachable, {} hung\n{}{}",
+                        mount,
+                        probe.source,
+                        probe.fs_type,
+                        probe.mount_type,
+                        probe.num_snaps(),
+                        probe.num_reachable,
+                        probe.unreachable.len(),
+                        probe.hung.len(),
+                        unreachable,
+                        hung
+                    )
+                })
+                .collect(),
+        };
+
+        write!(f, "{buffer}")
+    }
+}
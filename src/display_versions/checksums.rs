@@ -0,0 +1,118 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::config::generate::ChecksumAlgorithm;
+use crate::data::paths::PathData;
+use crate::library::diagnostics::Diagnostic;
+use crate::library::results::HttmResult;
+use crate::VersionsDisplayWrapper;
+use rayon::prelude::*;
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::{ErrorKind, Read};
+use std::path::Path;
+
+const READ_BUFFER_SIZE: usize = 65_536;
+
+impl<'a> VersionsDisplayWrapper<'a> {
+    // the manifest lists snapshot versions only, as the live version may change before the manifest is checked
+    pub fn format_as_checksums(&self, algorithm: &ChecksumAlgorithm) -> String {
+        let snap_versions: Vec<&PathData> = self
+            .values()
+            .flatten()
+            .filter(|snap| {
+                snap.path_buf
+                    .symlink_metadata()
+                    .map(|md| md.is_file())
+                    .unwrap_or(false)
+            })
+            .collect();
+
+        snap_versions
+            .into_par_iter()
+            .filter_map(|snap| match Checksum::of(algorithm, &snap.path_buf) {
+                Ok(hex_digest) => Some(Checksum::manifest_line(&hex_digest, &snap.path_buf)),
+                Err(err) => {
+                    Diagnostic::warn(
+                        "checksum_failed",
+                        format!("Could not compute a checksum of the version: {err}"),
+                    )
+                    .with_path(&snap.path_buf)
+                    .print();
+                    None
+                }
+            })
+            .collect::<Vec<String>>()
+            .concat()
+    }
+}
+
+pub struct Checksum;
+
+impl Checksum {
+    pub fn of(algorithm: &ChecksumAlgorithm, path: &Path) -> HttmResult<String> {
+        let mut file = File::open(path)?;
+        let mut buffer = vec![0u8; READ_BUFFER_SIZE];
+
+        match algorithm {
+            ChecksumAlgorithm::Sha256 => {
+                let mut hasher = Sha256::new();
+                Self::read_into(&mut file, &mut buffer, |bytes| hasher.update(bytes))?;
+                Ok(Self::to_hex(&hasher.finalize()))
+            }
+            ChecksumAlgorithm::Blake3 => {
+                let mut hasher = blake3::Hasher::new();
+                Self::read_into(&mut file, &mut buffer, |bytes| {
+                    hasher.update(bytes);
+                })?;
+                Ok(hasher.finalize().to_hex().to_string())
+            }
+        }
+    }
+
+    fn read_into(
+        file: &mut File,
+        buffer: &mut [u8],
+        mut update: impl FnMut(&[u8]),
+    ) -> HttmResult<()> {
+        loop {
+            match file.read(buffer) {
+                Ok(0) => return Ok(()),
+                Ok(num_bytes) => update(&buffer[..num_bytes]),
+                Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err.into()),
+            }
+        }
+    }
+
+    fn to_hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+    }
+
+    // like sha256sum and b3sum, a file name containing a backslash or newline is escaped,
+    // and its line is prefixed with a backslash, so "--check" reads back the same file name
+    fn manifest_line(hex_digest: &str, path: &Path) -> String {
+        let path_string = path.to_string_lossy();
+
+        if path_string.contains(['\\', '\n']) {
+            let escaped = path_string.replace('\\', "\\\\").replace('\n', "\\n");
+            format!("\\{hex_digest}  {escaped}\n")
+        } else {
+            format!("{hex_digest}  {path_string}\n")
+        }
+    }
+}
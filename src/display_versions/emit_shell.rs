@@ -0,0 +1,169 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::config::generate::ShellDialect;
+use crate::data::paths::{PathData, PathDeconstruction, ZfsSnapPathGuard};
+use crate::display_map::datasets::DatasetInventory;
+use crate::interactive::restore::InteractiveRestore;
+use crate::{VersionsDisplayWrapper, GLOBAL_CONFIG};
+use std::ffi::OsString;
+use std::fmt::Write;
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+
+impl<'a> VersionsDisplayWrapper<'a> {
+    pub fn format_as_shell(&self, shell_dialect: &ShellDialect) -> String {
+        self.iter()
+            .flat_map(|(live_version, snaps)| {
+                snaps.iter().map(move |snap| {
+                    ShellCommands::for_version(
+                        shell_dialect,
+                        Some(live_version.path_buf.as_path()),
+                        snap,
+                    )
+                })
+            })
+            .collect()
+    }
+}
+
+// commands are printed, never run, so a user may review, or edit, each before anything is written
+pub struct ShellCommands;
+
+impl ShellCommands {
+    pub fn for_version(
+        shell_dialect: &ShellDialect,
+        opt_live_path: Option<&Path>,
+        snap_pathdata: &PathData,
+    ) -> String {
+        let snap_path = snap_pathdata.path_buf.as_path();
+
+        let opt_live_path: Option<PathBuf> = opt_live_path.map(Path::to_path_buf).or_else(|| {
+            ZfsSnapPathGuard::new(snap_pathdata).and_then(|snap_guard| snap_guard.live_path())
+        });
+
+        let mut buffer = String::new();
+
+        // interactive zsh does not permit comments, unless "interactivecomments" is set
+        if !matches!(shell_dialect, ShellDialect::Zsh) {
+            let _ = writeln!(buffer, "# version: {}", snap_path.to_string_lossy());
+        }
+
+        let Some(live_path) = opt_live_path else {
+            let _ = writeln!(
+                buffer,
+                "cp -pR -- {} .",
+                Self::quote(shell_dialect, snap_path.as_os_str().as_bytes())
+            );
+            return buffer;
+        };
+
+        // a deleted file has nothing to compare against
+        if live_path.exists() {
+            let _ = writeln!(
+                buffer,
+                "diff -u -- {} {}",
+                Self::quote(shell_dialect, live_path.as_os_str().as_bytes()),
+                Self::quote(shell_dialect, snap_path.as_os_str().as_bytes())
+            );
+        }
+
+        let _ = writeln!(
+            buffer,
+            "cp -pR -- {} {}",
+            Self::quote(shell_dialect, snap_path.as_os_str().as_bytes()),
+            Self::quote(
+                shell_dialect,
+                Self::restore_path(&live_path, snap_pathdata)
+                    .as_os_str()
+                    .as_bytes()
+            )
+        );
+
+        buffer
+    }
+
+    // restored alongside the live version, and named, like a quick copy, with the snapshot name as a suffix
+    fn restore_path(live_path: &Path, snap_pathdata: &PathData) -> PathBuf {
+        if let Some(template) = &GLOBAL_CONFIG.opt_rename_template {
+            let live_file_name = live_path
+                .file_name()
+                .map(|file_name| file_name.to_string_lossy())
+                .unwrap_or_default();
+
+            if let Ok(new_file_name) = template.render(snap_pathdata, &live_file_name) {
+                return live_path.with_file_name(new_file_name);
+            }
+        }
+
+        let opt_suffix = GLOBAL_CONFIG
+            .dataset_collection
+            .snap_mount_of(&snap_pathdata.path_buf)
+            .map(DatasetInventory::snap_name);
+
+        match (opt_suffix, live_path.file_name()) {
+            (Some(suffix), Some(live_file_name)) => {
+                let mut new_file_name: OsString = live_file_name.to_os_string();
+                new_file_name.push(".");
+                new_file_name.push(suffix);
+                live_path.with_file_name(new_file_name)
+            }
+            _ => match InteractiveRestore::timestamped_file_name(snap_pathdata) {
+                Ok(new_file_name) => live_path.with_file_name(new_file_name),
+                Err(_) => live_path.with_extension("httm_restored"),
+            },
+        }
+    }
+
+    // single quotes are the only quotes within which nothing is special, except, in fish,
+    // backslashes, and bytes which are not printable UTF-8 must be escaped outside of them
+    pub fn quote(shell_dialect: &ShellDialect, bytes: &[u8]) -> String {
+        match std::str::from_utf8(bytes) {
+            Ok(string) if !string.chars().any(char::is_control) => match shell_dialect {
+                ShellDialect::Bash | ShellDialect::Zsh => {
+                    format!("'{}'", string.replace('\'', r"'\''"))
+                }
+                ShellDialect::Fish => {
+                    format!("'{}'", string.replace('\\', r"\\").replace('\'', r"\'"))
+                }
+            },
+            _ => {
+                let escaped: String = bytes
+                    .iter()
+                    .map(|byte| match byte {
+                        b'\'' | b'\\' => format!("\\{}", *byte as char),
+                        0x20..=0x7e => (*byte as char).to_string(),
+                        _ => format!("\\x{byte:02x}"),
+                    })
+                    .collect();
+
+                match shell_dialect {
+                    ShellDialect::Bash | ShellDialect::Zsh => format!("$'{escaped}'"),
+                    // fish has no ANSI-C quoting, but interprets these escapes when unquoted
+                    ShellDialect::Fish => escaped
+                        .chars()
+                        .map(|c| match c {
+                            ' ' | '"' | '$' | '*' | '?' | '~' | '#' | '(' | ')' | '{' | '}'
+                            | '[' | ']' | '<' | '>' | '&' | '|' | ';' | '%' => format!("\\{c}"),
+                            _ => c.to_string(),
+                        })
+                        .collect(),
+                }
+            }
+        }
+    }
+}
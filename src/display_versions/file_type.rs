@@ -0,0 +1,82 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::data::paths::PathData;
+use std::fs::File;
+use std::io::Read;
+use std::os::unix::fs::FileTypeExt;
+
+// nearly every signature is found within the first few hundred bytes, but some, like tar, are further in
+const MAGIC_BUFFER_LEN: u64 = 8192;
+
+impl PathData {
+    // names the type of a version in the style of "file --mime-type", so a version which
+    // no longer looks like its siblings, like one truncated or overwritten with zeros, stands out
+    pub fn detect_type(&self) -> &'static str {
+        let Ok(metadata) = self.path_buf.symlink_metadata() else {
+            return "-";
+        };
+
+        let file_type = metadata.file_type();
+
+        if file_type.is_dir() {
+            return "inode/directory";
+        } else if file_type.is_symlink() {
+            return "inode/symlink";
+        } else if file_type.is_fifo() {
+            return "inode/fifo";
+        } else if file_type.is_socket() {
+            return "inode/socket";
+        } else if file_type.is_block_device() {
+            return "inode/blockdevice";
+        } else if file_type.is_char_device() {
+            return "inode/chardevice";
+        } else if metadata.len() == 0 {
+            return "inode/x-empty";
+        }
+
+        let mut buffer = Vec::with_capacity(MAGIC_BUFFER_LEN as usize);
+
+        let Ok(_) = File::open(&self.path_buf)
+            .and_then(|file| file.take(MAGIC_BUFFER_LEN).read_to_end(&mut buffer))
+        else {
+            return "-";
+        };
+
+        if let Some(kind) = infer::get(&buffer) {
+            return kind.mime_type();
+        }
+
+        if Self::is_text(&buffer) {
+            return "text/plain";
+        }
+
+        "application/octet-stream"
+    }
+
+    fn is_text(buffer: &[u8]) -> bool {
+        if buffer.contains(&0u8) {
+            return false;
+        }
+
+        match std::str::from_utf8(buffer) {
+            Ok(_) => true,
+            // our buffer may end in the middle of a multi-byte character
+            Err(err) => err.error_len().is_none(),
+        }
+    }
+}
@@ -15,15 +15,18 @@
 // For the full copyright and license information, please view the LICENSE file
 // that was distributed with this source code.
 
-use crate::config::generate::{BulkExclusion, Config, PrintMode};
-use crate::data::paths::{PathData, PHANTOM_DATE, PHANTOM_SIZE};
-use crate::library::utility::{
-    date_string, delimiter, display_human_size, paint_string, path_is_filter_dir, DateFormat,
-};
+use crate::config::generate::{BulkExclusion, Config, PrintMode, RawField};
+use crate::data::paths::{FileIdentity, PathData, PHANTOM_DATE, PHANTOM_SIZE};
+use crate::display_map::datasets::DatasetInventory;
+use crate::library::utility::{date_string, delimiter, paint_string, path_is_filter_dir};
+use crate::library::version_notes::VERSION_NOTES;
 use crate::lookup::versions::ProximateDatasetAndOptAlts;
 use crate::VersionsDisplayWrapper;
+use hashbrown::{HashMap, HashSet};
 use std::borrow::Cow;
 use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
 use terminal_size::{terminal_size, Height, Width};
 // 2 space wide padding - used between date and size, and size and path
 pub const PRETTY_FIXED_WIDTH_PADDING: &str = "  ";
@@ -33,6 +36,11 @@ pub const PRETTY_FIXED_WIDTH_PADDING_LEN_X2: usize = 4;
 pub const NOT_SO_PRETTY_FIXED_WIDTH_PADDING: &str = "\t";
 // and we add 2 quotation marks to the path when we format
 pub const QUOTATION_MARKS_LEN: usize = 2;
+// displayed between versions which are different files which share the same name
+pub const GENERATION_MARKER: &str =
+    "── new file generation: a different file reused this name ──\n";
+// displayed in the version ID column of a live version, as wide as any ID
+pub const VERSION_ID_BLANK: &str = "        ";
 
 impl<'a> VersionsDisplayWrapper<'a> {
     pub fn format(&self) -> String {
@@ -51,6 +59,25 @@ impl<'a> VersionsDisplayWrapper<'a> {
             return global_display_set.format(self.config, &padding_collection);
         }
 
+        // only needed to name the snapshot of each version, or to tell snapshot versions from live versions,
+        // so only collected when requested
+        let opt_snap_mounts: Option<HashSet<&Path>> = self
+            .config
+            .opt_raw_fields
+            .as_ref()
+            .filter(|fields| {
+                fields.contains(&RawField::SnapName) || fields.contains(&RawField::VersionId)
+            })
+            .map(|_| {
+                self.config
+                    .dataset_collection
+                    .map_of_snaps
+                    .values()
+                    .flatten()
+                    .map(|snap_mount| snap_mount.as_path())
+                    .collect()
+            });
+
         // else re compute for each instance and print per instance, now with uniform padding
         self.iter()
             .map(|(key, values)| {
@@ -77,7 +104,18 @@ impl<'a> VersionsDisplayWrapper<'a> {
                             })
                             .flat_map(|(_idx, snap_or_live_set)| snap_or_live_set)
                             .fold(String::new(), |mut buffer, pathdata| {
-                                buffer += &format!("{}{}", pathdata.path_buf.display(), delimiter);
+                                let fields = Self::raw_fields(
+                                    pathdata,
+                                    self.config,
+                                    opt_snap_mounts.as_ref(),
+                                );
+
+                                buffer += &format!(
+                                    "{}{}{}",
+                                    pathdata.path_buf.display(),
+                                    fields,
+                                    delimiter
+                                );
                                 buffer
                             })
                     }
@@ -85,6 +123,63 @@ impl<'a> VersionsDisplayWrapper<'a> {
             })
             .collect::<String>()
     }
+
+    fn raw_fields(
+        pathdata: &PathData,
+        config: &Config,
+        opt_snap_mounts: Option<&HashSet<&Path>>,
+    ) -> String {
+        let Some(fields) = &config.opt_raw_fields else {
+            return String::new();
+        };
+
+        fields
+            .iter()
+            .map(|field| match field {
+                RawField::ModifyTime => pathdata
+                    .metadata
+                    .map(|md| md.modify_time)
+                    .unwrap_or(PHANTOM_DATE)
+                    .duration_since(UNIX_EPOCH)
+                    .map(|duration| duration.as_secs().to_string())
+                    .unwrap_or_else(|_| "0".to_owned()),
+                RawField::Size => pathdata
+                    .metadata
+                    .map(|md| md.size)
+                    .unwrap_or(PHANTOM_SIZE)
+                    .to_string(),
+                // a live version has no snapshot name
+                RawField::SnapName => opt_snap_mounts
+                    .and_then(|snap_mounts| {
+                        pathdata
+                            .path_buf
+                            .ancestors()
+                            .find(|ancestor| snap_mounts.contains(ancestor))
+                    })
+                    .map(DatasetInventory::snap_name)
+                    .unwrap_or_else(|| "-".to_owned()),
+                RawField::FileType => pathdata.detect_type().to_owned(),
+                // likewise, only snapshot versions have an ID
+                RawField::VersionId => opt_snap_mounts
+                    .filter(|snap_mounts| {
+                        pathdata
+                            .path_buf
+                            .ancestors()
+                            .any(|ancestor| snap_mounts.contains(ancestor))
+                    })
+                    .map(|_| pathdata.version_id())
+                    .unwrap_or_else(|| "-".to_owned()),
+                RawField::Note => VERSION_NOTES
+                    .get(&pathdata.path_buf)
+                    .map(|note| note.display())
+                    .unwrap_or_else(|| "-".to_owned()),
+            })
+            .fold(String::new(), |mut buffer, field| {
+                buffer.push('\t');
+                buffer += &field;
+                buffer
+            })
+    }
 }
 
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
@@ -158,12 +253,17 @@ impl<'a> DisplaySet<'a> {
             .fold(
                 String::new(),
                 |mut display_set_buffer, (display_set_type, snap_or_live_set)| {
-                    let mut component_buffer: String = snap_or_live_set
-                        .iter()
-                        .map(|pathdata| {
-                            pathdata.format(config, &display_set_type, padding_collection)
-                        })
-                        .collect();
+                    let mut component_buffer: String = match display_set_type {
+                        DisplaySetType::IsSnap if config.opt_generations => {
+                            self.format_generations(config, snap_or_live_set, padding_collection)
+                        }
+                        _ => snap_or_live_set
+                            .iter()
+                            .map(|pathdata| {
+                                pathdata.format(config, &display_set_type, padding_collection)
+                            })
+                            .collect(),
+                    };
 
                     // add each buffer to the set - print fancy border string above, below and between sets
                     if matches!(config.print_mode, PrintMode::FormattedNotPretty) {
@@ -198,6 +298,60 @@ impl<'a> DisplaySet<'a> {
                 },
             )
     }
+
+    // formats snap versions, with a marker wherever the identity of the file changes,
+    // including between the last snap version and the live version
+    fn format_generations(
+        &self,
+        config: &Config,
+        snaps: &[&PathData],
+        padding_collection: &PaddingCollection,
+    ) -> String {
+        let identity = |pathdata: &PathData| pathdata.metadata.and_then(|md| md.opt_identity);
+
+        let mut opt_previous: Option<FileIdentity> = None;
+
+        let mut buffer: String = snaps
+            .iter()
+            .map(|pathdata| {
+                let opt_current = identity(pathdata);
+
+                let marker = if Self::is_new_generation(opt_previous, opt_current) {
+                    GENERATION_MARKER
+                } else {
+                    ""
+                };
+
+                if opt_current.is_some() {
+                    opt_previous = opt_current;
+                }
+
+                marker.to_owned()
+                    + &pathdata.format(config, &DisplaySetType::IsSnap, padding_collection)
+            })
+            .collect();
+
+        let opt_live = match self.inner[1].as_slice() {
+            [live] => identity(live),
+            _ => None,
+        };
+
+        if Self::is_new_generation(opt_previous, opt_live) {
+            buffer += GENERATION_MARKER;
+        }
+
+        buffer
+    }
+
+    fn is_new_generation(
+        opt_previous: Option<FileIdentity>,
+        opt_current: Option<FileIdentity>,
+    ) -> bool {
+        match (opt_previous, opt_current) {
+            (Some(previous), Some(current)) => !previous.is_same_generation(&current),
+            _ => false,
+        }
+    }
 }
 
 impl PathData {
@@ -218,7 +372,7 @@ impl PathData {
                 // we use a dummy instead of a None value here.  Basically, sometimes, we want
                 // to print the request even if a live file does not exist
                 let size = if self.metadata.is_some() {
-                    Cow::Owned(display_human_size(metadata.size))
+                    Cow::Owned(config.number_format.display_size(metadata.size))
                 } else {
                     Cow::Borrowed(&padding_collection.phantom_size_pad_str)
                 };
@@ -230,7 +384,7 @@ impl PathData {
                 // print with padding and pretty border lines and ls colors
                 let size = {
                     let size = if self.metadata.is_some() {
-                        Cow::Owned(display_human_size(metadata.size))
+                        Cow::Owned(config.number_format.display_size(metadata.size))
                     } else {
                         Cow::Borrowed(&padding_collection.phantom_size_pad_str)
                     };
@@ -267,15 +421,81 @@ impl PathData {
             Cow::Owned(date_string(
                 config.requested_utc_offset,
                 &metadata.modify_time,
-                DateFormat::Display,
+                config.display_date_format(),
             ))
         } else {
             Cow::Borrowed(&padding_collection.phantom_date_pad_str)
         };
 
+        // only display a snapshot creation time column when requested,
+        // live versions have no snapshot, so display blanks
+        let display_creation = match &config.dataset_collection.opt_map_of_creation_times {
+            Some(map_of_creation_times) => {
+                let creation = match map_of_creation_times.creation_time(&self.path_buf) {
+                    Some(creation_time) => Cow::Owned(date_string(
+                        config.requested_utc_offset,
+                        &creation_time,
+                        config.display_date_format(),
+                    )),
+                    None => Cow::Borrowed(padding_collection.phantom_date_pad_str.as_str()),
+                };
+
+                creation + display_padding
+            }
+            None => Cow::Borrowed(""),
+        };
+
+        // only display a file type column when requested, types are detected once, when padding is calculated
+        let display_file_type = match &padding_collection.opt_file_types {
+            Some(file_types) => {
+                let file_type = file_types.get(&self.path_buf).copied().unwrap_or("-");
+
+                let file_type = match &config.print_mode {
+                    PrintMode::FormattedNotPretty => Cow::Borrowed(file_type),
+                    _ => Cow::Owned(format!(
+                        "{:<width$}",
+                        file_type,
+                        width = padding_collection.file_type_padding_len
+                    )),
+                };
+
+                file_type + display_padding
+            }
+            None => Cow::Borrowed(""),
+        };
+
+        // only display a version ID column when requested, live versions have no ID, so display blanks
+        let display_version_id = if config.opt_version_ids {
+            let version_id = match display_set_type {
+                DisplaySetType::IsSnap => Cow::Owned(self.version_id()),
+                DisplaySetType::IsLive => Cow::Borrowed(VERSION_ID_BLANK),
+            };
+
+            version_id + display_padding
+        } else {
+            Cow::Borrowed("")
+        };
+
+        // only snapshot versions may have a note, displayed after the path, so the path remains easy to parse
+        let display_note = match display_set_type {
+            DisplaySetType::IsSnap => VERSION_NOTES
+                .get(&self.path_buf)
+                .map(|note| Cow::Owned(format!("{display_padding}NOTE: {}", note.display())))
+                .unwrap_or(Cow::Borrowed("")),
+            DisplaySetType::IsLive => Cow::Borrowed(""),
+        };
+
         format!(
-            "{}{}{}{}{}\n",
-            display_date, display_padding, display_size, display_padding, display_path
+            "{}{}{}{}{}{}{}{}{}\n",
+            display_creation,
+            display_date,
+            display_padding,
+            display_size,
+            display_padding,
+            display_file_type,
+            display_version_id,
+            display_path,
+            display_note
         )
     }
 
@@ -299,6 +519,8 @@ impl PathData {
 
 pub struct PaddingCollection {
     pub size_padding_len: usize,
+    pub file_type_padding_len: usize,
+    pub opt_file_types: Option<HashMap<PathBuf, &'static str>>,
     pub fancy_border_string: String,
     pub phantom_date_pad_str: String,
     pub phantom_size_pad_str: String,
@@ -306,6 +528,22 @@ pub struct PaddingCollection {
 
 impl PaddingCollection {
     pub fn new(config: &Config, display_set: &DisplaySet) -> PaddingCollection {
+        let opt_file_types: Option<HashMap<PathBuf, &'static str>> =
+            config.opt_file_type.then(|| {
+                display_set
+                    .iter()
+                    .flatten()
+                    .map(|pathdata| (pathdata.path_buf.clone(), pathdata.detect_type()))
+                    .collect()
+            });
+
+        let file_type_padding_len = opt_file_types
+            .iter()
+            .flat_map(|file_types| file_types.values())
+            .map(|file_type| file_type.chars().count())
+            .max()
+            .unwrap_or_default();
+
         // calculate padding and borders for display later
         let (size_padding_len, fancy_border_len) = display_set.iter().flatten().fold(
             (0usize, 0usize),
@@ -316,11 +554,11 @@ impl PaddingCollection {
                     let date = date_string(
                         config.requested_utc_offset,
                         &metadata.modify_time,
-                        DateFormat::Display,
+                        config.display_date_format(),
                     );
                     let size = format!(
                         "{:>width$}",
-                        display_human_size(metadata.size),
+                        config.number_format.display_size(metadata.size),
                         width = size_padding_len
                     );
                     let path = pathdata.path_buf.to_string_lossy();
@@ -328,8 +566,30 @@ impl PaddingCollection {
                     (date, size, path)
                 };
 
-                let display_size_len = display_human_size(metadata.size).chars().count();
-                let formatted_line_len = display_date.chars().count()
+                let display_size_len = config.number_format.display_size(metadata.size).chars().count();
+                let creation_len = if config
+                    .dataset_collection
+                    .opt_map_of_creation_times
+                    .is_some()
+                {
+                    display_date.chars().count() + PRETTY_FIXED_WIDTH_PADDING.chars().count()
+                } else {
+                    0
+                };
+                let file_type_len = if opt_file_types.is_some() {
+                    file_type_padding_len + PRETTY_FIXED_WIDTH_PADDING.chars().count()
+                } else {
+                    0
+                };
+                let version_id_len = if config.opt_version_ids {
+                    VERSION_ID_BLANK.chars().count() + PRETTY_FIXED_WIDTH_PADDING.chars().count()
+                } else {
+                    0
+                };
+                let formatted_line_len = creation_len
+                    + file_type_len
+                    + version_id_len
+                    + display_date.chars().count()
                     + display_size.chars().count()
                     + display_path.chars().count()
                     + PRETTY_FIXED_WIDTH_PADDING_LEN_X2
@@ -349,7 +609,7 @@ impl PaddingCollection {
             width = date_string(
                 config.requested_utc_offset,
                 &PHANTOM_DATE,
-                DateFormat::Display
+                config.display_date_format()
             )
             .chars()
             .count()
@@ -357,11 +617,13 @@ impl PaddingCollection {
         let phantom_size_pad_str = format!(
             "{:<width$}",
             "",
-            width = display_human_size(PHANTOM_SIZE).chars().count()
+            width = config.number_format.display_size(PHANTOM_SIZE).chars().count()
         );
 
         PaddingCollection {
             size_padding_len,
+            file_type_padding_len,
+            opt_file_types,
             fancy_border_string,
             phantom_date_pad_str,
             phantom_size_pad_str,
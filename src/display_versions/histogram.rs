@@ -0,0 +1,218 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::config::generate::{HistogramBucket, PrintMode};
+use crate::data::paths::PathData;
+use crate::library::utility::delimiter;
+use crate::lookup::versions::VersionsMap;
+use crate::{VersionsDisplayWrapper, GLOBAL_CONFIG};
+use std::collections::BTreeMap;
+use time::{Date, Duration, Month, OffsetDateTime};
+
+const SPARK_CHARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+const BAR_CHAR: char = '█';
+const MAX_BAR_WIDTH: usize = 40;
+
+// with auto, the bucket is chosen by the span of days the versions cover
+const AUTO_MAX_DAYS_PER_DAY: i64 = 62;
+const AUTO_MAX_DAYS_PER_WEEK: i64 = 730;
+
+impl<'a> VersionsDisplayWrapper<'a> {
+    pub fn format_as_histogram(&self, requested_bucket: &HistogramBucket) -> String {
+        let delimiter = delimiter();
+
+        let write_out_buffer: String = self
+            .iter()
+            .filter_map(|(live_version, snaps)| {
+                Self::parse_histogram(requested_bucket, delimiter, live_version, snaps)
+            })
+            .collect();
+
+        if write_out_buffer.is_empty() {
+            eprintln!("Notification: No versions exist from which to build a histogram.");
+        }
+
+        write_out_buffer
+    }
+
+    fn parse_histogram(
+        requested_bucket: &HistogramBucket,
+        delimiter: char,
+        live_version: &PathData,
+        snaps: &[PathData],
+    ) -> Option<String> {
+        let utc_offset = GLOBAL_CONFIG.requested_utc_offset;
+
+        // like num versions, a live version which is the same as the last snap version is not a new version
+        let live_iter = live_version
+            .metadata
+            .filter(|_md| !VersionsMap::is_live_version_redundant(live_version, snaps));
+
+        let dates: Vec<Date> = snaps
+            .iter()
+            .filter_map(|snap| snap.metadata)
+            .chain(live_iter)
+            .map(|md| {
+                OffsetDateTime::from(md.modify_time)
+                    .to_offset(utc_offset)
+                    .date()
+            })
+            .collect();
+
+        let first = *dates.iter().min()?;
+        let last = *dates.iter().max()?;
+
+        let bucket = match requested_bucket {
+            HistogramBucket::Auto => {
+                let span = (last - first).whole_days();
+
+                if span <= AUTO_MAX_DAYS_PER_DAY {
+                    HistogramBucket::Day
+                } else if span <= AUTO_MAX_DAYS_PER_WEEK {
+                    HistogramBucket::Week
+                } else {
+                    HistogramBucket::Month
+                }
+            }
+            bucket => bucket.clone(),
+        };
+
+        // every period between the first and the last is included, so periods without versions are visible
+        let mut counts: BTreeMap<Date, usize> = BTreeMap::new();
+        let last_start = bucket.start(last);
+        let mut period = bucket.start(first);
+
+        while period <= last_start {
+            counts.insert(period, 0);
+            period = bucket.next(period)?;
+        }
+
+        dates.iter().for_each(|date| {
+            if let Some(count) = counts.get_mut(&bucket.start(*date)) {
+                *count += 1;
+            }
+        });
+
+        let display_path = live_version.path_buf.display();
+
+        match GLOBAL_CONFIG.print_mode {
+            PrintMode::FormattedDefault => {
+                let max = counts.values().copied().max().unwrap_or(0).max(1);
+
+                let sparkline: String = counts
+                    .values()
+                    .map(|count| match count {
+                        0 => ' ',
+                        count => SPARK_CHARS[(count * SPARK_CHARS.len() - 1) / max],
+                    })
+                    .collect();
+
+                let bar_chart: String = counts
+                    .iter()
+                    .filter(|(_period, count)| **count != 0)
+                    .map(|(period, count)| {
+                        let bar_width = (count * MAX_BAR_WIDTH).div_ceil(max);
+                        let bar: String = BAR_CHAR.to_string().repeat(bar_width);
+
+                        format!(
+                            "{:<10} │{:<width$} {}\n",
+                            bucket.label(*period),
+                            bar,
+                            count,
+                            width = MAX_BAR_WIDTH
+                        )
+                    })
+                    .collect();
+
+                Some(format!(
+                    "\"{}\" : {} version/s, per {}, from {} to {}\n│{}│\n{}{}",
+                    display_path,
+                    dates.len(),
+                    bucket.name(),
+                    bucket.label(first),
+                    bucket.label(last),
+                    sparkline,
+                    bar_chart,
+                    delimiter
+                ))
+            }
+            PrintMode::FormattedNotPretty | PrintMode::RawNewline | PrintMode::RawZero => Some(
+                counts
+                    .iter()
+                    .map(|(period, count)| {
+                        format!(
+                            "{}\t{}\t{}{}",
+                            display_path,
+                            bucket.label(*period),
+                            count,
+                            delimiter
+                        )
+                    })
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl HistogramBucket {
+    fn start(&self, date: Date) -> Date {
+        match self {
+            HistogramBucket::Auto | HistogramBucket::Day => date,
+            HistogramBucket::Week => {
+                date - Duration::days(date.weekday().number_days_from_monday() as i64)
+            }
+            HistogramBucket::Month => date.replace_day(1).unwrap_or(date),
+        }
+    }
+
+    fn next(&self, start: Date) -> Option<Date> {
+        match self {
+            HistogramBucket::Auto | HistogramBucket::Day => start.next_day(),
+            HistogramBucket::Week => start.checked_add(Duration::weeks(1)),
+            HistogramBucket::Month => {
+                let year = if start.month() == Month::December {
+                    start.year() + 1
+                } else {
+                    start.year()
+                };
+
+                Date::from_calendar_date(year, start.month().next(), 1).ok()
+            }
+        }
+    }
+
+    fn label(&self, date: Date) -> String {
+        let start = self.start(date);
+
+        match self {
+            HistogramBucket::Month => {
+                format!("{}-{:02}", start.year(), start.month() as u8)
+            }
+            HistogramBucket::Auto | HistogramBucket::Day | HistogramBucket::Week => {
+                start.to_string()
+            }
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            HistogramBucket::Auto | HistogramBucket::Day => "day",
+            HistogramBucket::Week => "week",
+            HistogramBucket::Month => "month",
+        }
+    }
+}
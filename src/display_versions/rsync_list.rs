@@ -0,0 +1,122 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+use crate::config::generate::RsyncListRoot;
+use crate::display_map::datasets::DatasetInventory;
+use crate::library::diagnostics::Diagnostic;
+use crate::library::utility::delimiter;
+use crate::{VersionsDisplayWrapper, GLOBAL_CONFIG};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+impl<'a> VersionsDisplayWrapper<'a> {
+    pub fn format_as_rsync_list(&self, root: &RsyncListRoot) -> String {
+        RsyncList::format(
+            root,
+            self.values()
+                .flatten()
+                .map(|snap_version| snap_version.path_buf.as_path()),
+        )
+    }
+}
+
+// a list of paths, relative to a single root, which rsync reads with "--files-from", so httm only chooses
+// the versions, and rsync makes the copy
+pub struct RsyncList;
+
+impl RsyncList {
+    pub fn format<'b>(root: &RsyncListRoot, snap_paths: impl Iterator<Item = &'b Path>) -> String {
+        let mut num_outside = 0usize;
+
+        // key: root, val: paths relative to that root, a version selected twice is only listed once
+        let mut by_root: BTreeMap<PathBuf, BTreeSet<PathBuf>> = BTreeMap::new();
+
+        snap_paths.for_each(|snap_path| match Self::relative_to_root(root, snap_path) {
+            Some((root_dir, relative_path)) => {
+                by_root.entry(root_dir).or_default().insert(relative_path);
+            }
+            None => num_outside += 1,
+        });
+
+        // rsync takes only one source directory per list
+        let mut roots = by_root.into_iter();
+
+        let Some((root_dir, relative_paths)) = roots.next() else {
+            Self::warn_outside(root, num_outside);
+            return String::new();
+        };
+
+        roots.for_each(|(_other_root, other_paths)| num_outside += other_paths.len());
+        Self::warn_outside(root, num_outside);
+
+        if let RsyncListRoot::SnapName(_) = root {
+            Diagnostic::notice(
+                "rsync_list_root",
+                format!("Versions are listed relative to: {:?}", root_dir),
+            )
+            .with_path(&root_dir)
+            .print();
+        }
+
+        let delimiter = delimiter();
+
+        relative_paths
+            .iter()
+            .map(|relative_path| {
+                // the root itself, as when the version is the whole snapshot
+                if relative_path.as_os_str().is_empty() {
+                    return format!(".{delimiter}");
+                }
+
+                format!("{}{delimiter}", relative_path.to_string_lossy())
+            })
+            .collect()
+    }
+
+    fn relative_to_root(root: &RsyncListRoot, snap_path: &Path) -> Option<(PathBuf, PathBuf)> {
+        let root_dir: &Path = match root {
+            RsyncListRoot::Dir(dir) => dir,
+            RsyncListRoot::SnapName(snap_name) => GLOBAL_CONFIG
+                .dataset_collection
+                .snap_mount_of(snap_path)
+                .filter(|snap_mount| DatasetInventory::snap_name(snap_mount) == *snap_name)?,
+        };
+
+        snap_path
+            .strip_prefix(root_dir)
+            .ok()
+            .map(|relative_path| (root_dir.to_path_buf(), relative_path.to_path_buf()))
+    }
+
+    fn warn_outside(root: &RsyncListRoot, num_outside: usize) {
+        if num_outside == 0 {
+            return;
+        }
+
+        let root_desc = match root {
+            RsyncListRoot::Dir(dir) => format!("the directory {:?}", dir),
+            RsyncListRoot::SnapName(snap_name) => {
+                format!("the first snapshot named {:?}", snap_name)
+            }
+        };
+
+        Diagnostic::warn(
+            "outside_rsync_root",
+            format!("{num_outside} version(s) do not lie within {root_desc}, and are not listed."),
+        )
+        .print();
+    }
+}
@@ -0,0 +1,366 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::config::generate::{BulkExclusion, Config, PrintMode};
+use crate::data::paths::{PathData, PathDeconstruction};
+use crate::display_map::datasets::DatasetInventory;
+use crate::library::utility::{date_string, delimiter};
+use crate::VersionsDisplayWrapper;
+use serde_json::{json, Value};
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const HEADERS: [&str; 8] = [
+    "SNAPSHOT", "MODE", "OWNER", "SIZE", "MODIFIED", "CHANGED", "ACCESSED", "XATTRS",
+];
+
+// the full metadata of a single version, as read from the version itself, not as cached in its PathData
+struct StatRow {
+    snapshot: String,
+    path: String,
+    mode: u32,
+    uid: u32,
+    gid: u32,
+    owner: String,
+    group: String,
+    size: u64,
+    modified: SystemTime,
+    changed: SystemTime,
+    accessed: SystemTime,
+    xattrs: Vec<String>,
+}
+
+impl StatRow {
+    fn new(pathdata: &PathData, snapshot: String) -> Option<Self> {
+        // a live version which does not exist has no metadata to display
+        let md = pathdata.path_buf.symlink_metadata().ok()?;
+
+        let owner = nix::unistd::User::from_uid(md.uid().into())
+            .ok()
+            .flatten()
+            .map(|user| user.name)
+            .unwrap_or_else(|| md.uid().to_string());
+
+        let group = nix::unistd::Group::from_gid(md.gid().into())
+            .ok()
+            .flatten()
+            .map(|group| group.name)
+            .unwrap_or_else(|| md.gid().to_string());
+
+        Some(Self {
+            snapshot,
+            path: pathdata.path_buf.to_string_lossy().to_string(),
+            mode: md.mode(),
+            uid: md.uid(),
+            gid: md.gid(),
+            owner,
+            group,
+            size: md.size(),
+            modified: Self::time(md.mtime(), md.mtime_nsec()),
+            changed: Self::time(md.ctime(), md.ctime_nsec()),
+            accessed: Self::time(md.atime(), md.atime_nsec()),
+            xattrs: Self::xattr_names(&pathdata.path_buf),
+        })
+    }
+
+    fn time(secs: i64, nsecs: i64) -> SystemTime {
+        let nsecs = Duration::from_nanos(nsecs as u64);
+
+        if secs >= 0 {
+            UNIX_EPOCH + Duration::from_secs(secs as u64) + nsecs
+        } else {
+            UNIX_EPOCH - Duration::from_secs(secs.unsigned_abs()) + nsecs
+        }
+    }
+
+    #[cfg(feature = "xattrs")]
+    fn xattr_names(path: &Path) -> Vec<String> {
+        let Ok(attrs) = xattr::list(path) else {
+            return Vec::new();
+        };
+
+        attrs
+            .map(|attr| attr.to_string_lossy().to_string())
+            .collect()
+    }
+
+    #[cfg(not(feature = "xattrs"))]
+    fn xattr_names(_path: &Path) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn columns(&self, config: &Config) -> [String; 8] {
+        let date = |time: &SystemTime| {
+            date_string(
+                config.requested_utc_offset,
+                time,
+                config.display_date_format(),
+            )
+        };
+
+        let xattrs = if self.xattrs.is_empty() {
+            "-".to_owned()
+        } else {
+            self.xattrs.join(",")
+        };
+
+        [
+            self.snapshot.clone(),
+            format!("{} ({:04o})", mode_string(self.mode), self.mode & 0o7777),
+            format!("{}:{}", self.owner, self.group),
+            config.number_format.display_size(self.size),
+            date(&self.modified),
+            date(&self.changed),
+            date(&self.accessed),
+            xattrs,
+        ]
+    }
+
+    fn to_value(&self, config: &Config) -> Value {
+        let date = |time: &SystemTime| {
+            date_string(
+                config.requested_utc_offset,
+                time,
+                config.display_date_format(),
+            )
+        };
+
+        json!({
+            "snapshot": self.snapshot,
+            "path": self.path,
+            "mode": format!("{:04o}", self.mode & 0o7777),
+            "file_type": mode_string(self.mode).chars().next().map(String::from),
+            "uid": self.uid,
+            "gid": self.gid,
+            "owner": self.owner,
+            "group": self.group,
+            "size": self.size,
+            "modified": date(&self.modified),
+            "changed": date(&self.changed),
+            "accessed": date(&self.accessed),
+            "xattrs": self.xattrs,
+        })
+    }
+}
+
+// like "ls -l" displays the mode, "-rw-r--r--"
+fn mode_string(mode: u32) -> String {
+    let file_type = match mode & 0o170000 {
+        0o040000 => 'd',
+        0o120000 => 'l',
+        0o010000 => 'p',
+        0o140000 => 's',
+        0o020000 => 'c',
+        0o060000 => 'b',
+        _ => '-',
+    };
+
+    let bits = [
+        (0o400, 'r'),
+        (0o200, 'w'),
+        (0o100, 'x'),
+        (0o040, 'r'),
+        (0o020, 'w'),
+        (0o010, 'x'),
+        (0o004, 'r'),
+        (0o002, 'w'),
+        (0o001, 'x'),
+    ];
+
+    let mut res: Vec<char> = std::iter::once(file_type)
+        .chain(
+            bits.iter()
+                .map(|(bit, c)| if mode & bit != 0 { *c } else { '-' }),
+        )
+        .collect();
+
+    // setuid, setgid, and sticky bits replace the execute bit of their class
+    [(0o4000, 3, 's'), (0o2000, 6, 's'), (0o1000, 9, 't')]
+        .iter()
+        .filter(|(bit, _idx, _c)| mode & bit != 0)
+        .for_each(|(_bit, idx, c)| {
+            res[*idx] = if res[*idx] == 'x' {
+                *c
+            } else {
+                c.to_ascii_uppercase()
+            }
+        });
+
+    res.into_iter().collect()
+}
+
+struct StatGroup {
+    live_path: String,
+    dataset: String,
+    rows: Vec<StatRow>,
+}
+
+impl<'a> VersionsDisplayWrapper<'a> {
+    pub fn format_as_stat(&self) -> String {
+        let groups: Vec<StatGroup> = self
+            .iter()
+            .map(|(live_version, snaps)| {
+                let snap_rows = snaps.iter().filter_map(|snap| {
+                    let snap_name = self
+                        .config
+                        .dataset_collection
+                        .snap_mount_of(&snap.path_buf)
+                        .map(DatasetInventory::snap_name)
+                        .unwrap_or_else(|| "-".to_owned());
+
+                    StatRow::new(snap, snap_name)
+                });
+
+                let live_row = std::iter::once(live_version)
+                    .filter_map(|live| StatRow::new(live, "live".to_owned()));
+
+                let rows = match &self.config.opt_bulk_exclusion {
+                    Some(BulkExclusion::NoLive) => snap_rows.collect(),
+                    Some(BulkExclusion::NoSnap) => live_row.collect(),
+                    None => snap_rows.chain(live_row).collect(),
+                };
+
+                StatGroup {
+                    live_path: live_version.path_buf.to_string_lossy().to_string(),
+                    dataset: Self::dataset_of(live_version),
+                    rows,
+                }
+            })
+            .collect();
+
+        if self.config.opt_json {
+            return self.stat_json(&groups);
+        }
+
+        match self.config.print_mode {
+            PrintMode::FormattedDefault => self.stat_columns(&groups),
+            PrintMode::FormattedNotPretty | PrintMode::RawNewline | PrintMode::RawZero => {
+                self.stat_delimited(&groups)
+            }
+        }
+    }
+
+    fn dataset_of(pathdata: &PathData) -> String {
+        pathdata
+            .proximate_dataset()
+            .ok()
+            .and_then(|mount| pathdata.source(Some(mount)))
+            .map(|source| source.to_string_lossy().to_string())
+            .unwrap_or_else(|| "-".to_owned())
+    }
+
+    fn stat_columns(&self, groups: &[StatGroup]) -> String {
+        let all_columns: Vec<[String; 8]> = groups
+            .iter()
+            .flat_map(|group| group.rows.iter())
+            .map(|row| row.columns(self.config))
+            .collect();
+
+        let widths: [usize; 8] = std::array::from_fn(|idx| {
+            all_columns
+                .iter()
+                .map(|columns| columns[idx].chars().count())
+                .chain(std::iter::once(HEADERS[idx].len()))
+                .max()
+                .unwrap_or_default()
+        });
+
+        let line = |columns: &[String]| -> String {
+            let mut res = columns
+                .iter()
+                .zip(widths.iter())
+                .map(|(column, width)| format!("{column:<width$}"))
+                .collect::<Vec<String>>()
+                .join("  ");
+
+            res.truncate(res.trim_end().len());
+            res.push('\n');
+            res
+        };
+
+        let headers = HEADERS.map(str::to_owned);
+
+        groups
+            .iter()
+            .filter(|group| !group.rows.is_empty())
+            .map(|group| {
+                let mut buffer = format!("{} (dataset: {})\n", group.live_path, group.dataset);
+
+                buffer += &line(&headers);
+
+                group
+                    .rows
+                    .iter()
+                    .for_each(|row| buffer += &line(&row.columns(self.config)));
+
+                buffer
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    // one version per line, its path last, so the output may be split upon tabs by scripts
+    fn stat_delimited(&self, groups: &[StatGroup]) -> String {
+        let delimiter = delimiter();
+
+        groups
+            .iter()
+            .flat_map(|group| group.rows.iter())
+            .map(|row| {
+                let mut columns = row.columns(self.config).to_vec();
+                columns.push(row.path.clone());
+                format!("{}{delimiter}", columns.join("\t"))
+            })
+            .collect()
+    }
+
+    fn stat_json(&self, groups: &[StatGroup]) -> String {
+        let value: Value = groups
+            .iter()
+            .map(|group| {
+                json!({
+                    "path": group.live_path,
+                    "dataset": group.dataset,
+                    "versions": group
+                        .rows
+                        .iter()
+                        .map(|row| row.to_value(self.config))
+                        .collect::<Vec<Value>>(),
+                })
+            })
+            .collect();
+
+        let res = match self.config.print_mode {
+            PrintMode::FormattedNotPretty | PrintMode::RawNewline | PrintMode::RawZero => {
+                serde_json::to_string(&value)
+            }
+            PrintMode::FormattedDefault => serde_json::to_string_pretty(&value),
+        };
+
+        match res {
+            Ok(s) => {
+                let delimiter = delimiter();
+                format!("{s}{delimiter}")
+            }
+            Err(error) => {
+                eprintln!("Error: {error}");
+                std::process::exit(1)
+            }
+        }
+    }
+}
@@ -0,0 +1,240 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::config::generate::{BulkExclusion, Config};
+use crate::data::paths::PathData;
+use crate::display_map::datasets::DatasetInventory;
+use crate::library::utility::date_string;
+use crate::VersionsDisplayWrapper;
+use terminal_size::{terminal_size, Height, Width};
+
+// the narrowest a path may be elided to, before whole columns are dropped instead
+const MIN_PATH_WIDTH: usize = 16;
+const ELLIPSIS: char = '…';
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Column {
+    Date,
+    Size,
+    Snapshot,
+    Path,
+}
+
+impl Column {
+    fn header(&self) -> &'static str {
+        match self {
+            Column::Date => "DATE",
+            Column::Size => "SIZE",
+            Column::Snapshot => "SNAPSHOT",
+            Column::Path => "PATH",
+        }
+    }
+}
+
+impl<'a> VersionsDisplayWrapper<'a> {
+    pub fn format_as_table(&self) -> String {
+        let groups: Vec<Vec<[String; 4]>> = self
+            .iter()
+            .map(|(live_version, snaps)| {
+                let snap_rows = snaps.iter().map(|snap| {
+                    let snap_name = self
+                        .config
+                        .dataset_collection
+                        .snap_mount_of(&snap.path_buf)
+                        .map(DatasetInventory::snap_name)
+                        .unwrap_or_else(|| "-".to_owned());
+
+                    Self::table_row(self.config, snap, snap_name)
+                });
+
+                let live_row = std::iter::once(Self::table_row(
+                    self.config,
+                    live_version,
+                    "live".to_owned(),
+                ));
+
+                match &self.config.opt_bulk_exclusion {
+                    Some(BulkExclusion::NoLive) => snap_rows.collect(),
+                    Some(BulkExclusion::NoSnap) => live_row.collect(),
+                    None => snap_rows.chain(live_row).collect(),
+                }
+            })
+            .filter(|rows: &Vec<[String; 4]>| !rows.is_empty())
+            .collect();
+
+        Table::new(groups).render()
+    }
+
+    fn table_row(config: &Config, pathdata: &PathData, snap_name: String) -> [String; 4] {
+        // a live version which does not exist has no date or size to display
+        let (date, size) = match pathdata.metadata {
+            Some(metadata) => (
+                date_string(
+                    config.requested_utc_offset,
+                    &metadata.modify_time,
+                    config.display_date_format(),
+                ),
+                config.number_format.display_size(metadata.size),
+            ),
+            None => (String::new(), String::new()),
+        };
+
+        [
+            date,
+            size,
+            snap_name,
+            pathdata.path_buf.to_string_lossy().to_string(),
+        ]
+    }
+}
+
+// each group of rows, the versions of a single file, is separated from the next by a rule
+struct Table {
+    columns: Vec<Column>,
+    widths: [usize; 4],
+    groups: Vec<Vec<[String; 4]>>,
+}
+
+impl Table {
+    fn new(groups: Vec<Vec<[String; 4]>>) -> Self {
+        let all_columns = [Column::Date, Column::Size, Column::Snapshot, Column::Path];
+
+        let widths: [usize; 4] = std::array::from_fn(|idx| {
+            groups
+                .iter()
+                .flatten()
+                .map(|row| row[idx].chars().count())
+                .chain(std::iter::once(all_columns[idx].header().len()))
+                .max()
+                .unwrap_or_default()
+        });
+
+        let mut table = Self {
+            columns: all_columns.to_vec(),
+            widths,
+            groups,
+        };
+
+        if let Some((Width(width), Height(_height))) = terminal_size() {
+            table.fit(width as usize);
+        }
+
+        table
+    }
+
+    // when too wide for the terminal, first elide the beginning of long paths, then drop
+    // the columns least needed to tell versions apart, the snapshot name, and then the date
+    fn fit(&mut self, terminal_width: usize) {
+        let path_idx = Column::Path as usize;
+
+        let excess = self.line_width().saturating_sub(terminal_width);
+
+        if excess > 0 {
+            let path_width = self.widths[path_idx];
+            self.widths[path_idx] = path_width
+                .saturating_sub(excess)
+                .max(MIN_PATH_WIDTH.min(path_width));
+        }
+
+        [Column::Snapshot, Column::Date].iter().for_each(|column| {
+            if self.line_width() > terminal_width {
+                self.columns.retain(|retained| retained != column);
+            }
+        });
+    }
+
+    // every column is padded by a space on each side, and bordered by a box drawing character
+    fn line_width(&self) -> usize {
+        self.columns
+            .iter()
+            .map(|column| self.widths[*column as usize] + 3)
+            .sum::<usize>()
+            + 1
+    }
+
+    fn render(&self) -> String {
+        let mut buffer = self.rule('┌', '─', '┬', '┐');
+
+        let headers = [Column::Date, Column::Size, Column::Snapshot, Column::Path]
+            .map(|column| column.header().to_owned());
+        buffer += &self.line(&headers);
+        buffer += &self.rule('╞', '═', '╪', '╡');
+
+        self.groups
+            .iter()
+            .enumerate()
+            .for_each(|(group_idx, rows)| {
+                if group_idx > 0 {
+                    buffer += &self.rule('├', '─', '┼', '┤');
+                }
+
+                rows.iter().for_each(|row| buffer += &self.line(row));
+            });
+
+        buffer += &self.rule('└', '─', '┴', '┘');
+        buffer
+    }
+
+    fn rule(&self, left: char, horizontal: char, middle: char, right: char) -> String {
+        let segments: Vec<String> = self
+            .columns
+            .iter()
+            .map(|column| {
+                horizontal
+                    .to_string()
+                    .repeat(self.widths[*column as usize] + 2)
+            })
+            .collect();
+
+        format!(
+            "{left}{}{right}\n",
+            segments.join(middle.to_string().as_str())
+        )
+    }
+
+    fn line(&self, row: &[String; 4]) -> String {
+        let cells: Vec<String> = self
+            .columns
+            .iter()
+            .map(|column| {
+                let width = self.widths[*column as usize];
+                let cell = Self::elide(&row[*column as usize], width);
+
+                // sizes are right aligned, like the ordinary output
+                match column {
+                    Column::Size => format!(" {cell:>width$} "),
+                    _ => format!(" {cell:<width$} "),
+                }
+            })
+            .collect();
+
+        format!("│{}│\n", cells.join("│"))
+    }
+
+    // the end of a path, its file name, is usually the most informative part, so elide its beginning
+    fn elide(value: &str, width: usize) -> String {
+        let len = value.chars().count();
+
+        if len <= width {
+            return value.to_owned();
+        }
+
+        let tail: String = value.chars().skip(len - width + 1).collect();
+
+        format!("{ELLIPSIS}{tail}")
+    }
+}
@@ -0,0 +1,197 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::config::generate::{BulkExclusion, PrintMode};
+use crate::data::paths::PathData;
+use crate::library::utility::paint_string;
+use crate::VersionsDisplayWrapper;
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::path::{Component, Path, PathBuf};
+
+// the branches drawn before each child, the first for the default, the second for NOT_SO_PRETTY
+struct Branches {
+    child: &'static str,
+    last_child: &'static str,
+    descent: &'static str,
+    last_descent: &'static str,
+}
+
+const BOX_BRANCHES: Branches = Branches {
+    child: "├── ",
+    last_child: "└── ",
+    descent: "│   ",
+    last_descent: "    ",
+};
+
+const ASCII_BRANCHES: Branches = Branches {
+    child: "|-- ",
+    last_child: "`-- ",
+    descent: "|   ",
+    last_descent: "    ",
+};
+
+#[derive(Default)]
+struct TreeNode<'a> {
+    // only a path given, or found, has versions, the directories between such paths are only for grouping
+    opt_pathdata: Option<&'a PathData>,
+    num_versions: usize,
+    children: BTreeMap<OsString, TreeNode<'a>>,
+}
+
+impl<'a> TreeNode<'a> {
+    fn insert(&mut self, relative_path: &Path, pathdata: &'a PathData, num_versions: usize) {
+        let node = relative_path.components().fold(self, |node, component| {
+            node.children
+                .entry(component.as_os_str().to_os_string())
+                .or_default()
+        });
+
+        node.opt_pathdata = Some(pathdata);
+        node.num_versions = num_versions;
+    }
+
+    fn total_versions(&self) -> usize {
+        self.num_versions
+            + self
+                .children
+                .values()
+                .map(TreeNode::total_versions)
+                .sum::<usize>()
+    }
+
+    fn is_grouping_dir(&self) -> bool {
+        self.opt_pathdata.is_none() && !self.children.is_empty()
+    }
+
+    fn label(&self, name: &str, is_pretty: bool) -> String {
+        let display_name = if self.children.is_empty() {
+            name.to_owned()
+        } else {
+            format!("{name}/")
+        };
+
+        let painted_name = match self.opt_pathdata {
+            Some(pathdata) if is_pretty => paint_string(pathdata, &display_name).to_string(),
+            _ => display_name,
+        };
+
+        let total = self.total_versions();
+
+        let plural = if total == 1 { "" } else { "s" };
+
+        format!("{painted_name}  ({total} version{plural})")
+    }
+
+    fn render(&self, prefix: &str, branches: &Branches, is_pretty: bool, buffer: &mut String) {
+        let num_children = self.children.len();
+
+        self.children
+            .iter()
+            .enumerate()
+            .for_each(|(idx, (name, child))| {
+                let is_last = idx + 1 == num_children;
+
+                // a chain of directories, each with only a single directory within, is displayed as one node,
+                // so deeply nested paths don't push the files of interest off the right of the screen
+                let mut name = PathBuf::from(name);
+                let mut node = child;
+
+                while node.is_grouping_dir() && node.children.len() == 1 {
+                    let Some((grandchild_name, grandchild)) = node.children.iter().next() else {
+                        break;
+                    };
+
+                    if !grandchild.is_grouping_dir() {
+                        break;
+                    }
+
+                    name.push(grandchild_name);
+                    node = grandchild;
+                }
+
+                let (branch, descent) = if is_last {
+                    (branches.last_child, branches.last_descent)
+                } else {
+                    (branches.child, branches.descent)
+                };
+
+                *buffer += &format!(
+                    "{prefix}{branch}{}\n",
+                    node.label(&name.to_string_lossy(), is_pretty)
+                );
+
+                node.render(&format!("{prefix}{descent}"), branches, is_pretty, buffer);
+            });
+    }
+}
+
+impl<'a> VersionsDisplayWrapper<'a> {
+    pub fn format_as_tree(&self) -> String {
+        let Some(root) = self.tree_root() else {
+            return String::new();
+        };
+
+        let mut tree = TreeNode::default();
+
+        self.iter().for_each(|(live_version, snaps)| {
+            let num_versions = match &self.config.opt_bulk_exclusion {
+                Some(BulkExclusion::NoSnap) => 0,
+                Some(BulkExclusion::NoLive) | None => snaps.len(),
+            };
+
+            let Ok(relative_path) = live_version.path_buf.strip_prefix(&root) else {
+                return;
+            };
+
+            tree.insert(relative_path, live_version, num_versions);
+        });
+
+        let (branches, is_pretty) = match self.config.print_mode {
+            PrintMode::FormattedDefault => (&BOX_BRANCHES, true),
+            PrintMode::FormattedNotPretty | PrintMode::RawNewline | PrintMode::RawZero => {
+                (&ASCII_BRANCHES, false)
+            }
+        };
+
+        // the root is labelled like any other directory, with a trailing slash
+        let root_name = root.to_string_lossy();
+        let root_name = root_name.trim_end_matches('/');
+
+        let mut buffer = format!("{}\n", tree.label(root_name, false));
+
+        tree.render("", branches, is_pretty, &mut buffer);
+
+        buffer
+    }
+
+    // the deepest directory which contains every path displayed
+    fn tree_root(&self) -> Option<PathBuf> {
+        self.keys()
+            .filter_map(|live_version| live_version.path_buf.parent())
+            .map(|parent| parent.components().collect::<Vec<Component>>())
+            .reduce(|common, components| {
+                common
+                    .into_iter()
+                    .zip(components)
+                    .take_while(|(a, b)| a == b)
+                    .map(|(a, _b)| a)
+                    .collect()
+            })
+            .map(|components| components.iter().collect())
+    }
+}
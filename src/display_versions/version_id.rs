@@ -0,0 +1,236 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::config::generate::VersionAction;
+use crate::data::paths::PathData;
+use crate::interactive::restore::InteractiveRestore;
+use crate::library::content_range::ContentRange;
+use crate::library::file_ops::Copy;
+use crate::library::results::{HttmError, HttmResult};
+use crate::library::special_file::SpecialFile;
+use crate::library::utility::print_output_buf;
+use crate::library::version_notes::VersionNotes;
+use crate::lookup::versions::VersionsMap;
+use crate::GLOBAL_CONFIG;
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::BufReader;
+use std::os::unix::ffi::OsStrExt;
+use std::process::Command as ExecProcess;
+use which::which;
+
+// 8 hex digits are plenty to tell apart the versions of the handful of files given on a command line
+const VERSION_ID_LEN: usize = 8;
+
+impl PathData {
+    // a version's ID is a hash of its snapshot path, which names both the snapshot and the file,
+    // so the same version has the same ID in every listing, for as long as its snapshot exists
+    pub fn version_id(&self) -> String {
+        let digest = Sha256::digest(self.path_buf.as_os_str().as_bytes());
+
+        digest
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect::<String>()
+            .split_at(VERSION_ID_LEN)
+            .0
+            .to_owned()
+    }
+}
+
+pub struct VersionIds;
+
+impl VersionIds {
+    pub fn exec(requested_ids: &[String], action: &VersionAction) -> HttmResult<()> {
+        let versions_map = VersionsMap::new(&GLOBAL_CONFIG, &GLOBAL_CONFIG.paths)?;
+
+        let resolved: Vec<(&PathData, &PathData)> = requested_ids
+            .iter()
+            .map(|requested_id| Self::resolve(&versions_map, requested_id))
+            .collect::<HttmResult<Vec<(&PathData, &PathData)>>>()?;
+
+        resolved
+            .into_iter()
+            .try_for_each(|(live, snap)| match action {
+                VersionAction::Path => {
+                    let output_buf = format!("{}\n", snap.path_buf.display());
+                    print_output_buf(&output_buf)
+                }
+                VersionAction::Cat(opt_range) => Self::cat(snap, opt_range.as_ref()),
+                VersionAction::Diff => Self::diff(live, snap),
+                VersionAction::Restore => {
+                    let new_file_path_buf =
+                        InteractiveRestore::quick_copy(snap, live.path_buf.to_str())?;
+
+                    let output_buf = format!(
+                        "httm copied a file from a snapshot:\n\n\
+                        \tfrom: {:?}\n\
+                        \tto:   {:?}\n",
+                        snap.path_buf, new_file_path_buf
+                    );
+                    print_output_buf(&output_buf)
+                }
+                VersionAction::Splice(range) => Self::splice(live, snap, range),
+                VersionAction::Note(note) => {
+                    let output_buf = match VersionNotes::set(live, snap, note)? {
+                        Some(version_note) => format!(
+                            "httm noted a version:\n\n\
+                            \tversion: {:?}\n\
+                            \tnote:    {}\n",
+                            snap.path_buf,
+                            version_note.display()
+                        ),
+                        None => format!(
+                            "httm removed any note upon a version:\n\n\
+                            \tversion: {:?}\n",
+                            snap.path_buf
+                        ),
+                    };
+                    print_output_buf(&output_buf)
+                }
+            })
+    }
+
+    // IDs are only looked up among the versions of the paths given, just as they were listed,
+    // so an ID which matches more than one version is an error, rather than a guess
+    fn resolve<'a>(
+        versions_map: &'a VersionsMap,
+        requested_id: &str,
+    ) -> HttmResult<(&'a PathData, &'a PathData)> {
+        let requested_id = requested_id.trim().to_ascii_lowercase();
+
+        let matches: Vec<(&PathData, &PathData)> = versions_map
+            .iter()
+            .flat_map(|(live, snaps)| snaps.iter().map(move |snap| (live, snap)))
+            .filter(|(_live, snap)| snap.version_id() == requested_id)
+            .collect();
+
+        match matches.as_slice() {
+            [found] => Ok(*found),
+            [] => {
+                let msg = format!(
+                    "No version of the paths specified has the version ID: {requested_id}. \
+                    Version IDs are only found among the versions of the paths given, \
+                    which should be the same paths given when the IDs were listed."
+                );
+                Err(HttmError::new(&msg)
+                    .with_code("version_id_not_found")
+                    .into())
+            }
+            _ => {
+                let msg = format!(
+                    "More than one version of the paths specified has the version ID: {requested_id}. \
+                    Please specify fewer paths."
+                );
+                Err(HttmError::new(&msg)
+                    .with_code("version_id_ambiguous")
+                    .into())
+            }
+        }
+    }
+
+    // contents may be binary, so they are copied as they are, not transcoded
+    fn cat(snap: &PathData, opt_range: Option<&ContentRange>) -> HttmResult<()> {
+        SpecialFile::refuse_contents(&snap.path_buf)?;
+
+        let mut file = File::open(&snap.path_buf)?;
+
+        let out = std::io::stdout();
+        let mut out_locked = out.lock();
+
+        let Some(range) = opt_range else {
+            std::io::copy(&mut file, &mut out_locked)?;
+            return Ok(());
+        };
+
+        let (_num_before, num_within) = range.partition(
+            &mut BufReader::new(file),
+            &mut std::io::sink(),
+            &mut out_locked,
+            &mut std::io::sink(),
+        )?;
+
+        if num_within == 0 {
+            let msg = format!(
+                "The range specified, {}, is empty upon the snapshot version.",
+                range.describe()
+            );
+            return Err(HttmError::new(&msg)
+                .with_code("range_out_of_bounds")
+                .with_path(&snap.path_buf)
+                .into());
+        }
+
+        Ok(())
+    }
+
+    fn splice(live: &PathData, snap: &PathData, range: &ContentRange) -> HttmResult<()> {
+        if live.metadata.is_none() {
+            let msg = format!(
+                "A range may only be spliced into a live file which exists: {:?}",
+                live.path_buf
+            );
+            return Err(HttmError::new(&msg)
+                .with_code("live_not_found")
+                .with_path(&live.path_buf)
+                .into());
+        }
+
+        Copy::splice(&snap.path_buf, &live.path_buf, range)?;
+
+        let output_buf = format!(
+            "httm spliced {} of a snapshot version into a live file:\n\n\
+            \tfrom: {:?}\n\
+            \tto:   {:?}\n",
+            range.describe(),
+            snap.path_buf,
+            live.path_buf
+        );
+        print_output_buf(&output_buf)
+    }
+
+    fn diff(live: &PathData, snap: &PathData) -> HttmResult<()> {
+        SpecialFile::refuse_contents(&snap.path_buf)?;
+        SpecialFile::refuse_contents(&live.path_buf)?;
+
+        let Ok(diff_command) = which("diff") else {
+            return Err(HttmError::new(
+                "'diff' executable could not be found in the user's PATH. 'diff' is necessary for diffing a version.",
+            )
+            .into());
+        };
+
+        let status = ExecProcess::new(diff_command)
+            .arg("-u")
+            .arg("--")
+            .arg(&snap.path_buf)
+            .arg(&live.path_buf)
+            .status()?;
+
+        // like diff itself, exit status 1 means only that the files differ
+        match status.code() {
+            Some(0 | 1) => Ok(()),
+            _ => {
+                let msg = format!(
+                    "diff exited unsuccessfully when comparing the version to its live version: {:?}",
+                    snap.path_buf
+                );
+                Err(HttmError::new(&msg).with_path(&snap.path_buf).into())
+            }
+        }
+    }
+}
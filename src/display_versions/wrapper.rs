@@ -20,9 +20,9 @@ use crate::data::paths::PathData;
 use crate::display_map::format::PrintAsMap;
 use crate::library::utility::delimiter;
 use crate::lookup::versions::VersionsMap;
+use indexmap::IndexMap;
 use serde::ser::SerializeMap;
 use serde::{Serialize, Serializer};
-use std::collections::BTreeMap;
 use std::ops::Deref;
 
 pub struct VersionsDisplayWrapper<'a> {
@@ -36,16 +36,41 @@ impl<'a> std::string::ToString for VersionsDisplayWrapper<'a> {
             ExecMode::NumVersions(num_versions_mode) => {
                 self.format_as_num_versions(num_versions_mode)
             }
+            ExecMode::Histogram(histogram_bucket) => self.format_as_histogram(histogram_bucket),
             _ => {
                 if self.config.opt_last_snap.is_some() {
                     let printable_map = PrintAsMap::from(&self.map);
                     return printable_map.to_string();
                 }
 
+                if let Some(algorithm) = &self.config.opt_emit_checksums {
+                    return self.format_as_checksums(algorithm);
+                }
+
+                if let Some(shell_dialect) = &self.config.opt_emit_shell {
+                    return self.format_as_shell(shell_dialect);
+                }
+
+                if let Some(root) = &self.config.opt_emit_rsync_list {
+                    return self.format_as_rsync_list(root);
+                }
+
+                if self.config.opt_stat {
+                    return self.format_as_stat();
+                }
+
                 if self.config.opt_json {
                     return self.to_json();
                 }
 
+                if self.config.opt_table {
+                    return self.format_as_table();
+                }
+
+                if self.config.opt_tree {
+                    return self.format_as_tree();
+                }
+
                 self.format()
             }
         }
@@ -53,7 +78,7 @@ impl<'a> std::string::ToString for VersionsDisplayWrapper<'a> {
 }
 
 impl<'a> Deref for VersionsDisplayWrapper<'a> {
-    type Target = BTreeMap<PathData, Vec<PathData>>;
+    type Target = IndexMap<PathData, Vec<PathData>>;
 
     fn deref(&self) -> &Self::Target {
         &self.map
@@ -91,8 +116,8 @@ impl<'a> Serialize for VersionsDisplayWrapper<'a> {
     where
         S: Serializer,
     {
-        // add live file key to values if needed before serializing
-        let new_map: BTreeMap<String, Vec<PathData>> = self
+        // add live file key to values if needed before serializing, in the same order as displayed
+        let new_map: IndexMap<String, Vec<PathData>> = self
             .deref()
             .clone()
             .into_iter()
@@ -17,6 +17,9 @@
 
 use crate::background::recursive::RecursiveSearch;
 use crate::data::paths::PathData;
+use crate::interactive::key_bindings::KeyBindings;
+use crate::interactive::preview::PreviewWindow;
+use crate::interactive::session::InteractiveSession;
 use crate::interactive::view_mode::ViewMode;
 use crate::library::results::{HttmError, HttmResult};
 use crate::library::utility::Never;
@@ -30,10 +33,49 @@ use std::thread::{self, JoinHandle};
 pub struct InteractiveBrowse {
     pub selected_pathdata: Vec<PathData>,
     pub opt_background_handle: Option<JoinHandle<()>>,
+    pub opt_session: Option<InteractiveSession>,
 }
 
 impl InteractiveBrowse {
     pub fn new() -> HttmResult<Self> {
+        let Some(session_name) = &GLOBAL_CONFIG.opt_session else {
+            return Self::browse();
+        };
+
+        let mut session = InteractiveSession::open(session_name)?;
+
+        // resume with the files selected in a prior session, instead of browsing again
+        let mut browse_result = if session.files.is_empty() {
+            let browse_result = Self::browse()?;
+
+            session.files = browse_result
+                .selected_pathdata
+                .iter()
+                .map(|pathdata| pathdata.path_buf.clone())
+                .collect();
+            session.save()?;
+
+            browse_result
+        } else {
+            eprintln!(
+                "NOTICE: Resuming session {:?} with {} file/s selected.",
+                session_name,
+                session.files.len()
+            );
+
+            Self {
+                selected_pathdata: session.files.iter().map(PathData::from).collect(),
+                opt_background_handle: None,
+                opt_session: None,
+            }
+        };
+
+        browse_result.opt_session = Some(session);
+
+        Ok(browse_result)
+    }
+
+    fn browse() -> HttmResult<Self> {
         let browse_result = match &GLOBAL_CONFIG.opt_requested_dir {
             // collect string paths from what we get from lookup_view
             Some(requested_dir) => {
@@ -59,6 +101,7 @@ impl InteractiveBrowse {
                         Self {
                             selected_pathdata: vec![selected_file],
                             opt_background_handle: None,
+                            opt_session: None,
                         }
                     }
                     // Config::from should never allow us to have an instance where we don't
@@ -99,11 +142,20 @@ impl InteractiveBrowse {
 
         let opt_multi = GLOBAL_CONFIG.opt_preview.is_none();
 
+        let preview_window = GLOBAL_CONFIG.preview_window.as_skim_str();
+        let key_bindings = KeyBindings::new(
+            &ViewMode::Browse,
+            PreviewWindow::bindings(),
+            &GLOBAL_CONFIG.uniqueness,
+        )?;
+        let bindings = key_bindings.bindings();
+
         let display_thread = thread::spawn(move || {
             // create the skim component for previews
             let skim_opts = SkimOptionsBuilder::default()
-                .preview_window(Some("up:50%"))
+                .preview_window(Some(&preview_window))
                 .preview(Some(""))
+                .bind(bindings.iter().map(String::as_str).collect())
                 .nosort(true)
                 .exact(GLOBAL_CONFIG.opt_exact)
                 .header(Some(&header))
@@ -139,6 +191,7 @@ impl InteractiveBrowse {
                 Ok(Self {
                     selected_pathdata,
                     opt_background_handle: Some(background_handle),
+                    opt_session: None,
                 })
             }
             None => Err(HttmError::new("httm interactive file browse session failed.").into()),
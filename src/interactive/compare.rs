@@ -0,0 +1,94 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+use crate::interactive::key_bindings::{KeyAction, KeyBinding};
+use crate::library::results::{HttmError, HttmResult};
+use crate::library::text_encoding::Transcode;
+use std::path::PathBuf;
+use which::which;
+
+pub const PIN_BASE_KEY: &str = "alt-p";
+pub const COMPARE_KEY: &str = "alt-c";
+
+// pin one version as a "base", and compare any other version against it, side by side,
+// without leaving the select view.  the picker runs our compare script for each key press,
+// and the script remembers the base version pinned in a file which lives as long as the view
+pub struct CompareVersions {
+    script_path: PathBuf,
+    base_path: PathBuf,
+}
+
+impl CompareVersions {
+    pub fn new() -> HttmResult<Self> {
+        if which("bash").is_err() {
+            return Err(HttmError::new(
+                "'bash' executable could not be found in the user's PATH. 'bash' is necessary for comparing versions.",
+            )
+            .into());
+        }
+
+        let temp_dir = std::env::temp_dir();
+        let unique = format!("httm-compare-{}", std::process::id());
+
+        let script_path = temp_dir.join(format!("{unique}.bash"));
+        let base_path = temp_dir.join(format!("{unique}.base"));
+
+        let base_path_str = base_path.to_string_lossy();
+
+        // the picker's bind syntax cannot contain a closing parenthesis
+        if script_path.to_string_lossy().contains([')', '\'']) || base_path_str.contains('"') {
+            return Err(HttmError::new(
+                "The temporary directory path contains characters which cannot be used to compare versions.",
+            )
+            .into());
+        }
+
+        let script = include_str!("../../scripts/compare-bootstrap.bash")
+            .replace("{base_file}", &base_path_str)
+            .replace("{transcode}", &Transcode::command()?);
+
+        std::fs::write(&script_path, script)?;
+
+        Ok(Self {
+            script_path,
+            base_path,
+        })
+    }
+
+    pub fn bindings(&self) -> Vec<KeyBinding> {
+        let script = self.script_path.to_string_lossy();
+
+        vec![
+            KeyBinding::new(
+                PIN_BASE_KEY,
+                "pin the highlighted version as the base for comparison",
+                KeyAction::Bind(format!("execute-silent(bash '{script}' pin {{}})")),
+            ),
+            KeyBinding::new(
+                COMPARE_KEY,
+                "compare the highlighted version with the pinned base",
+                KeyAction::Bind(format!("execute(bash '{script}' compare {{}})")),
+            ),
+        ]
+    }
+}
+
+impl Drop for CompareVersions {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.script_path);
+        let _ = std::fs::remove_file(&self.base_path);
+    }
+}
@@ -0,0 +1,246 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::data::paths::PathData;
+use crate::interactive::restore::InteractiveRestore;
+use crate::interactive::view_mode::MultiSelect;
+use crate::interactive::view_mode::ViewMode;
+use crate::library::results::{HttmError, HttmResult};
+use crate::GLOBAL_CONFIG;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+const ORIGINAL_LOCATION: &str = "ORIGINAL LOCATION (OVERWRITE)";
+const ORIGINAL_DIRECTORY: &str = "ORIGINAL DIRECTORY (TIMESTAMPED NAME)";
+const BROWSE_DIRECTORY: &str = "BROWSE TO A DIRECTORY";
+const CUSTOM_PATH: &str = "CUSTOM PATH";
+
+const SELECT_DIRECTORY: &str = "[ RESTORE TO THIS DIRECTORY ]";
+const GO_BACK: &str = "[ GO BACK ]";
+const PARENT_DIRECTORY: &str = "..";
+
+pub struct RestoreDestination {
+    pub path_buf: PathBuf,
+    pub should_preserve: bool,
+}
+
+// each step of the destination dialog is a state, and each state
+// produces the next, until a destination is chosen
+enum DestinationState {
+    ChooseKind,
+    BrowseDirectory(PathBuf),
+    CustomPath,
+    Chosen(RestoreDestination),
+}
+
+impl RestoreDestination {
+    pub fn pick(
+        interactive_restore: &InteractiveRestore,
+        snap_pathdata: &PathData,
+    ) -> HttmResult<Self> {
+        let mut state = DestinationState::ChooseKind;
+
+        loop {
+            state = match state {
+                DestinationState::ChooseKind => {
+                    Self::choose_kind(interactive_restore, snap_pathdata)?
+                }
+                DestinationState::BrowseDirectory(dir) => {
+                    Self::browse_directory(&dir, snap_pathdata)?
+                }
+                DestinationState::CustomPath => Self::custom_path(snap_pathdata)?,
+                DestinationState::Chosen(destination) => return Ok(destination),
+            }
+        }
+    }
+
+    fn choose_kind(
+        interactive_restore: &InteractiveRestore,
+        snap_pathdata: &PathData,
+    ) -> HttmResult<DestinationState> {
+        let opt_live_path = interactive_restore.opt_live_version(snap_pathdata).ok();
+
+        let live_path_string = opt_live_path.as_ref().map_or_else(
+            || "(could not be determined)".to_owned(),
+            |live_path| format!("{live_path:?}"),
+        );
+
+        let choose_buffer = format!(
+            "Where would httm restore the snapshot version:\n\n\
+            \tsource:\t{:?}\n\
+            \tlive:\t{live_path_string}\n\n\
+            Please pick a restore destination:\n\
+            ─────────────────────────────────────────────────────────────────────────────────────────\n\
+            {ORIGINAL_LOCATION}\n\
+            {ORIGINAL_DIRECTORY}\n\
+            {BROWSE_DIRECTORY}\n\
+            {CUSTOM_PATH}",
+            snap_pathdata.path_buf
+        );
+
+        let selection = ViewMode::Restore.view_buffer(&choose_buffer, MultiSelect::Off)?;
+
+        let choice = selection
+            .first()
+            .ok_or_else(|| HttmError::new("Could not obtain the first match selected."))?;
+
+        let next_state = match choice.as_str() {
+            ORIGINAL_LOCATION => match opt_live_path {
+                Some(live_path) => DestinationState::Chosen(RestoreDestination {
+                    path_buf: live_path,
+                    should_preserve: true,
+                }),
+                None => {
+                    eprintln!("WARN: Could not determine the original location of the snapshot version selected.");
+                    DestinationState::ChooseKind
+                }
+            },
+            ORIGINAL_DIRECTORY => match opt_live_path.as_deref().and_then(Path::parent) {
+                Some(live_dir) => {
+                    let new_file_name = InteractiveRestore::restored_file_name(snap_pathdata)?;
+                    Self::chosen_or_retry(live_dir.join(new_file_name))
+                }
+                None => {
+                    eprintln!("WARN: Could not determine the original directory of the snapshot version selected.");
+                    DestinationState::ChooseKind
+                }
+            },
+            BROWSE_DIRECTORY => {
+                let start_dir = opt_live_path
+                    .as_deref()
+                    .and_then(Path::parent)
+                    .filter(|dir| dir.is_dir())
+                    .map_or_else(|| GLOBAL_CONFIG.pwd.clone(), Path::to_path_buf);
+
+                DestinationState::BrowseDirectory(start_dir)
+            }
+            CUSTOM_PATH => DestinationState::CustomPath,
+            // if not a valid choice, then noop and ask again
+            _ => DestinationState::ChooseKind,
+        };
+
+        Ok(next_state)
+    }
+
+    fn browse_directory(dir: &Path, snap_pathdata: &PathData) -> HttmResult<DestinationState> {
+        let mut sub_dirs: Vec<String> = std::fs::read_dir(dir)?
+            .flatten()
+            .filter(|entry| entry.file_type().map(|ft| ft.is_dir()).unwrap_or(false))
+            .map(|entry| entry.file_name().to_string_lossy().into_owned())
+            .collect();
+
+        sub_dirs.sort_unstable();
+
+        // view is displayed in reverse order, so our special entries go last
+        let browse_buffer: String = sub_dirs
+            .iter()
+            .map(String::as_str)
+            .rev()
+            .chain([PARENT_DIRECTORY, GO_BACK, SELECT_DIRECTORY])
+            .map(|line| line.to_owned() + "\n")
+            .collect();
+
+        eprintln!("Browsing for a restore destination: {dir:?}");
+
+        let selection = ViewMode::Restore.view_buffer(&browse_buffer, MultiSelect::Off)?;
+
+        let choice = selection
+            .first()
+            .ok_or_else(|| HttmError::new("Could not obtain the first match selected."))?;
+
+        let next_state = match choice.as_str() {
+            SELECT_DIRECTORY => Self::chosen_or_retry(dir.join(Self::file_name(snap_pathdata)?)),
+            GO_BACK => DestinationState::ChooseKind,
+            PARENT_DIRECTORY => {
+                DestinationState::BrowseDirectory(dir.parent().unwrap_or(dir).to_path_buf())
+            }
+            sub_dir => DestinationState::BrowseDirectory(dir.join(sub_dir)),
+        };
+
+        Ok(next_state)
+    }
+
+    fn custom_path(snap_pathdata: &PathData) -> HttmResult<DestinationState> {
+        // skim does not return free form input, so read a line directly from the terminal
+        let mut tty = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open("/dev/tty")?;
+
+        write!(
+            tty,
+            "Please enter a custom restore path (leave empty to go back): "
+        )?;
+        tty.flush()?;
+
+        let mut input = String::new();
+        BufReader::new(tty).read_line(&mut input)?;
+
+        let input = input.trim();
+
+        if input.is_empty() {
+            return Ok(DestinationState::ChooseKind);
+        }
+
+        let custom_path = GLOBAL_CONFIG.pwd.join(input);
+
+        // a custom path which is an existing directory is where we put the file
+        let new_file_path_buf = if custom_path.is_dir() {
+            custom_path.join(Self::file_name(snap_pathdata)?)
+        } else {
+            custom_path
+        };
+
+        match new_file_path_buf.parent() {
+            Some(parent) if parent.is_dir() => Ok(Self::chosen_or_retry(new_file_path_buf)),
+            _ => {
+                eprintln!(
+                    "WARN: The parent directory of the custom path requested does not exist: {new_file_path_buf:?}"
+                );
+                Ok(DestinationState::ChooseKind)
+            }
+        }
+    }
+
+    // don't let the user rewrite an existing file unless the original location was chosen
+    fn chosen_or_retry(new_file_path_buf: PathBuf) -> DestinationState {
+        if new_file_path_buf.exists() {
+            eprintln!(
+                "WARN: httm will not restore to that path, as a file with the same path name already exists: {new_file_path_buf:?}"
+            );
+            return DestinationState::ChooseKind;
+        }
+
+        DestinationState::Chosen(RestoreDestination {
+            path_buf: new_file_path_buf,
+            should_preserve: false,
+        })
+    }
+
+    fn file_name(snap_pathdata: &PathData) -> HttmResult<String> {
+        snap_pathdata
+            .path_buf
+            .file_name()
+            .map(|file_name| file_name.to_string_lossy().into_owned())
+            .ok_or_else(|| {
+                HttmError::new(
+                    "Could not obtain a file name for the snap file version of path given",
+                )
+                .into()
+            })
+    }
+}
@@ -0,0 +1,244 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::config::generate::{DeletedMode, ListSnapsOfType};
+use crate::interactive::view_mode::ViewMode;
+use crate::library::results::{HttmError, HttmResult};
+use crate::library::utility::create_unique_file;
+use crate::GLOBAL_CONFIG;
+use std::io::Write;
+use std::path::PathBuf;
+use which::which;
+
+pub const HELP_KEY: &str = "?";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyAction {
+    // a key the picker handles itself, listed only so the overlay is complete
+    Default,
+    // a key bound to a picker action, like "toggle-preview"
+    Bind(String),
+    // a key which accepts the highlighted item, like enter, but is reported distinctly
+    Expect,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyBinding {
+    pub key: &'static str,
+    pub description: &'static str,
+    pub action: KeyAction,
+}
+
+impl KeyBinding {
+    pub fn new(key: &'static str, description: &'static str, action: KeyAction) -> Self {
+        Self {
+            key,
+            description,
+            action,
+        }
+    }
+}
+
+// every key active in a view, which both drives the picker's key handling and generates
+// the help overlay, so the overlay never drifts from the keys actually bound.
+// the overlay is written to a file which lives as long as the view
+pub struct KeyBindings {
+    inner: Vec<KeyBinding>,
+    help_path: PathBuf,
+}
+
+impl KeyBindings {
+    pub fn new(
+        view_mode: &ViewMode,
+        extra: Vec<KeyBinding>,
+        uniqueness: &ListSnapsOfType,
+    ) -> HttmResult<Self> {
+        let mut inner = vec![
+            KeyBinding::new("enter", "select the highlighted item", KeyAction::Default),
+            KeyBinding::new("esc", "exit", KeyAction::Default),
+            KeyBinding::new("up/down", "move the highlight", KeyAction::Default),
+            KeyBinding::new(
+                "page up/down",
+                "move the highlight a page at a time",
+                KeyAction::Default,
+            ),
+            KeyBinding::new("shift+up/down", "scroll the preview", KeyAction::Default),
+        ];
+
+        if !matches!(view_mode, ViewMode::Restore) {
+            inner.push(KeyBinding::new(
+                "tab/shift+tab",
+                "select multiple items",
+                KeyAction::Default,
+            ));
+        }
+
+        inner.extend(extra);
+
+        let temp_dir = std::env::temp_dir();
+
+        // the picker's bind syntax cannot contain a closing parenthesis
+        if temp_dir.to_string_lossy().contains([')', '\'']) {
+            return Err(HttmError::new(
+                "The temporary directory path contains characters which cannot be used to display help.",
+            )
+            .into());
+        }
+
+        let (help_path, mut help_file) = create_unique_file(&temp_dir, "httm-help-", ".txt")?;
+
+        let mut res = Self { inner, help_path };
+
+        let help_action = match which("less") {
+            Ok(_) => format!("execute(less -R '{}')", res.help_path.display()),
+            Err(_) => format!(
+                "execute(cat '{}'; read -r _ </dev/tty)",
+                res.help_path.display()
+            ),
+        };
+
+        res.inner.push(KeyBinding::new(
+            HELP_KEY,
+            "display this help",
+            KeyAction::Bind(help_action),
+        ));
+
+        help_file.write_all(res.overlay(view_mode, uniqueness).as_bytes())?;
+
+        Ok(res)
+    }
+
+    pub fn bindings(&self) -> Vec<String> {
+        self.inner
+            .iter()
+            .filter_map(|key_binding| match &key_binding.action {
+                KeyAction::Bind(action) => Some(format!("{}:{}", key_binding.key, action)),
+                KeyAction::Default | KeyAction::Expect => None,
+            })
+            .collect()
+    }
+
+    pub fn opt_expect(&self) -> Option<String> {
+        let expect: Vec<&str> = self
+            .inner
+            .iter()
+            .filter(|key_binding| matches!(key_binding.action, KeyAction::Expect))
+            .map(|key_binding| key_binding.key)
+            .collect();
+
+        if expect.is_empty() {
+            return None;
+        }
+
+        Some(expect.join(","))
+    }
+
+    fn overlay(&self, view_mode: &ViewMode, uniqueness: &ListSnapsOfType) -> String {
+        let key_width = self
+            .inner
+            .iter()
+            .map(|key_binding| key_binding.key.chars().count())
+            .max()
+            .unwrap_or_default();
+
+        let keys: String = self
+            .inner
+            .iter()
+            .map(|key_binding| {
+                format!(
+                    "  {:<key_width$}  {}\n",
+                    key_binding.key, key_binding.description
+                )
+            })
+            .collect();
+
+        let settings: String = Self::settings(view_mode, uniqueness)
+            .into_iter()
+            .map(|(setting, value)| format!("  {:<key_width$}  {}\n", setting, value))
+            .collect();
+
+        format!(
+            "httm {} help (press q, or enter, to return)\n\nKEYS:\n{keys}\nSETTINGS:\n{settings}",
+            view_mode.name()
+        )
+    }
+
+    // the uniqueness is that of the versions displayed, which may be toggled within the select view
+    fn settings(view_mode: &ViewMode, uniqueness: &ListSnapsOfType) -> Vec<(&'static str, String)> {
+        let on_off = |value: bool| if value { "on" } else { "off" }.to_owned();
+
+        let uniqueness = match uniqueness {
+            ListSnapsOfType::All => "all versions".to_owned(),
+            ListSnapsOfType::UniqueMetadata => "unique by metadata".to_owned(),
+            ListSnapsOfType::UniqueContents => "unique by contents".to_owned(),
+            ListSnapsOfType::UniqueKey(dedup_key) => {
+                let fields: Vec<&str> = [
+                    (dedup_key.mtime, "mtime"),
+                    (dedup_key.size, "size"),
+                    (dedup_key.ctime, "ctime"),
+                    (dedup_key.hash, "hash"),
+                    (dedup_key.perms, "perms"),
+                ]
+                .into_iter()
+                .filter(|(is_key, _name)| *is_key)
+                .map(|(_is_key, name)| name)
+                .collect();
+
+                format!("unique by {}", fields.join(","))
+            }
+            ListSnapsOfType::UniqueCommand(compare_command) => {
+                format!("unique by {:?}", compare_command.display())
+            }
+        };
+
+        let deleted = match &GLOBAL_CONFIG.opt_deleted_mode {
+            None => "off",
+            Some(DeletedMode::All) => "all",
+            Some(DeletedMode::DepthOfOne) => "single",
+            Some(DeletedMode::Only) => "only",
+        };
+
+        let mut res = vec![("uniqueness", uniqueness)];
+
+        if matches!(view_mode, ViewMode::Browse) {
+            res.extend([
+                ("deleted", deleted.to_owned()),
+                ("recursive", on_off(GLOBAL_CONFIG.opt_recursive)),
+                ("exact", on_off(GLOBAL_CONFIG.opt_exact)),
+                ("hidden", on_off(!GLOBAL_CONFIG.opt_no_hidden)),
+                ("filter", on_off(!GLOBAL_CONFIG.opt_no_filter)),
+            ]);
+        }
+
+        res.extend([
+            ("omit ditto", on_off(GLOBAL_CONFIG.opt_omit_ditto)),
+            (
+                "size filter",
+                on_off(GLOBAL_CONFIG.opt_size_filter.is_some()),
+            ),
+            ("preview", on_off(GLOBAL_CONFIG.opt_preview.is_some())),
+        ]);
+
+        res
+    }
+}
+
+impl Drop for KeyBindings {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.help_path);
+    }
+}
@@ -0,0 +1,234 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::data::paths::PathData;
+use crate::library::diff_copy::HttmCopy;
+use crate::library::file_ops::{Copy, SnapDestination};
+use crate::library::results::{HttmError, HttmResult};
+use crate::library::utility::create_unique_file;
+use crate::lookup::versions::VersionsMap;
+use crate::GLOBAL_CONFIG;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Command as ExecProcess;
+use which::which;
+
+// a three-way merge of the live file, a common ancestor snapshot version, and the
+// snapshot version chosen, so the user may restore only part of a snapshot version,
+// while keeping any edits made to the live file since the ancestor
+pub struct ThreeWayMerge {
+    pub live: PathBuf,
+    pub ancestor: PathData,
+    pub chosen: PathData,
+}
+
+pub enum MergeResult {
+    Clean,
+    Conflicts,
+}
+
+impl ThreeWayMerge {
+    pub fn new(live: PathBuf, chosen: PathData) -> HttmResult<Self> {
+        if !live.is_file() || !chosen.path_buf.is_file() {
+            let msg = format!(
+                "A three-way merge is only possible when both the live version and the snapshot version are regular files: {:?}",
+                live
+            );
+            return Err(HttmError::new(&msg).into());
+        }
+
+        // a merge replaces the live file
+        SnapDestination::refuse(&live)?;
+
+        let ancestor = Self::ancestor(&live, &chosen)?;
+
+        Ok(Self {
+            live,
+            ancestor,
+            chosen,
+        })
+    }
+
+    // the common ancestor is the most recent snapshot version, other than the version chosen,
+    // which is no newer than the live file, as this is most likely the version the live file diverged from
+    fn ancestor(live: &Path, chosen: &PathData) -> HttmResult<PathData> {
+        let live_pathdata = PathData::from(live);
+        let live_modify_time = live_pathdata.md_infallible().modify_time;
+
+        let versions_map = VersionsMap::new(&GLOBAL_CONFIG, &[live_pathdata])?;
+
+        versions_map
+            .values()
+            .flatten()
+            .filter(|snap_pathdata| snap_pathdata.path_buf != chosen.path_buf)
+            .filter(|snap_pathdata| snap_pathdata.md_infallible().modify_time <= live_modify_time)
+            .max_by_key(|snap_pathdata| snap_pathdata.md_infallible().modify_time)
+            .cloned()
+            .ok_or_else(|| {
+                let msg = format!(
+                    "Could not find a common ancestor snapshot version for a three-way merge of: {:?}",
+                    live
+                );
+                HttmError::new(&msg).into()
+            })
+    }
+
+    // launch the user's merge tool, if set, else write the merge, with any conflict markers, to the live file,
+    // either way, a merge is a restore like any other, so it is bracketed by the hooks, locked, and audited
+    pub fn exec(&self) -> HttmResult<MergeResult> {
+        let mut merge_result = MergeResult::Clean;
+
+        Copy::run_restore(&self.chosen.path_buf, &self.live, || {
+            merge_result = match std::env::var("MERGE_TOOL") {
+                Ok(merge_tool) if !merge_tool.trim().is_empty() => self.merge_tool(&merge_tool)?,
+                _ => self.diff3()?,
+            };
+
+            Ok(())
+        })?;
+
+        Ok(merge_result)
+    }
+
+    fn merge_tool(&self, merge_tool: &str) -> HttmResult<MergeResult> {
+        let mut words = merge_tool.split_whitespace();
+
+        let Some(program) = words.next() else {
+            return Err(HttmError::new("MERGE_TOOL environment variable is empty.").into());
+        };
+
+        let merge_tool_command = which(program).map_err(|_err| {
+            let msg = format!(
+                "MERGE_TOOL command {:?} could not be found. Make sure the command is in your path.",
+                program
+            );
+            HttmError::new(&msg)
+        })?;
+
+        let working_copy = WorkingCopy::new(&self.live)?;
+
+        // the merge tool is passed a copy of the live file, the ancestor, and the chosen version, in that order,
+        // and is expected to write its result to the copy, which then replaces the live file
+        let status = ExecProcess::new(merge_tool_command)
+            .args(words)
+            .arg(&working_copy.path)
+            .arg(&self.ancestor.path_buf)
+            .arg(&self.chosen.path_buf)
+            .status()?;
+
+        if !status.success() {
+            let msg = format!(
+                "MERGE_TOOL {:?} exited with an error: {}",
+                merge_tool, status
+            );
+            return Err(HttmError::new(&msg).into());
+        }
+
+        HttmCopy::with_contents(&self.live, |tmp_file| {
+            std::io::copy(&mut File::open(&working_copy.path)?, tmp_file)?;
+
+            Ok(())
+        })?;
+
+        Ok(MergeResult::Clean)
+    }
+
+    fn diff3(&self) -> HttmResult<MergeResult> {
+        let diff3_command = which("diff3").map_err(|_err| {
+            HttmError::new(
+                "'diff3' command not found, and MERGE_TOOL is not set. Make sure the command 'diff3' is in your path, or set MERGE_TOOL.",
+            )
+        })?;
+
+        let live_label = format!("live: {}", self.live.display());
+        let ancestor_label = format!("ancestor: {}", self.ancestor.path_buf.display());
+        let chosen_label = format!("snapshot: {}", self.chosen.path_buf.display());
+
+        let output = ExecProcess::new(diff3_command)
+            .arg("-m")
+            .args([
+                "-L",
+                &live_label,
+                "-L",
+                &ancestor_label,
+                "-L",
+                &chosen_label,
+            ])
+            .arg(&self.live)
+            .arg(&self.ancestor.path_buf)
+            .arg(&self.chosen.path_buf)
+            .output()?;
+
+        // diff3 exits 0 when clean, 1 when there are conflicts, and 2 on error
+        let merge_result = match output.status.code() {
+            Some(0) => MergeResult::Clean,
+            Some(1) => MergeResult::Conflicts,
+            _ => {
+                let stderr_string = String::from_utf8_lossy(&output.stderr);
+                let msg = format!(
+                    "diff3 could not merge {:?}: {}",
+                    self.live,
+                    stderr_string.trim()
+                );
+                return Err(HttmError::new(&msg).into());
+            }
+        };
+
+        HttmCopy::with_contents(&self.live, |tmp_file| {
+            tmp_file.write_all(&output.stdout)?;
+
+            Ok(())
+        })?;
+
+        Ok(merge_result)
+    }
+}
+
+// a copy of the live file, within the temporary directory, for the merge tool to write its result to,
+// which is removed once the merge is complete
+struct WorkingCopy {
+    path: PathBuf,
+}
+
+impl WorkingCopy {
+    fn new(live: &Path) -> HttmResult<Self> {
+        // keep the live file's name, so the merge tool may recognize the type of the file
+        let file_name = live
+            .file_name()
+            .map(|file_name| file_name.to_string_lossy())
+            .unwrap_or_default();
+
+        let (path, mut file) = create_unique_file(
+            &std::env::temp_dir(),
+            "httm-merge-",
+            &format!("-{file_name}"),
+        )?;
+
+        let working_copy = Self { path };
+
+        std::io::copy(&mut File::open(live)?, &mut file)?;
+
+        Ok(working_copy)
+    }
+}
+
+impl Drop for WorkingCopy {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
@@ -15,12 +15,123 @@
 // For the full copyright and license information, please view the LICENSE file
 // that was distributed with this source code.
 
+use crate::config::generate::ShellDialect;
+use crate::display_versions::emit_shell::ShellCommands;
+use crate::interactive::key_bindings::{KeyAction, KeyBinding};
 use crate::interactive::view_mode::ViewMode;
 use crate::library::results::{HttmError, HttmResult};
+use crate::library::text_encoding::Transcode;
+use crate::library::version_notes::VERSION_NOTES;
 use crate::GLOBAL_CONFIG;
+use std::os::unix::ffi::OsStrExt;
 use std::path::PathBuf;
 use which::which;
 
+pub const TOGGLE_PREVIEW_KEY: &str = "alt-v";
+pub const TOGGLE_PREVIEW_WRAP_KEY: &str = "alt-w";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PreviewPosition {
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreviewWindow {
+    pub position: PreviewPosition,
+    pub size: u8,
+    pub wrap: bool,
+    pub hidden: bool,
+}
+
+impl Default for PreviewWindow {
+    fn default() -> Self {
+        Self {
+            position: PreviewPosition::Top,
+            size: 50,
+            wrap: false,
+            hidden: false,
+        }
+    }
+}
+
+impl PreviewWindow {
+    pub fn new(opt_position: Option<&str>, opt_size: Option<&str>, wrap: bool) -> HttmResult<Self> {
+        let mut preview_window = Self {
+            wrap,
+            ..Default::default()
+        };
+
+        match opt_position {
+            Some("top" | "up") => preview_window.position = PreviewPosition::Top,
+            Some("bottom" | "down") => preview_window.position = PreviewPosition::Bottom,
+            Some("left") => preview_window.position = PreviewPosition::Left,
+            Some("right") => preview_window.position = PreviewPosition::Right,
+            // a hidden preview may still be shown, in its default position, with the toggle key
+            Some("hidden") => preview_window.hidden = true,
+            _ => (),
+        }
+
+        if let Some(size) = opt_size {
+            preview_window.size = Self::parse_size(size)?;
+        }
+
+        Ok(preview_window)
+    }
+
+    fn parse_size(size: &str) -> HttmResult<u8> {
+        match size.trim().trim_end_matches('%').parse::<u8>() {
+            Ok(percent) if (1..=99).contains(&percent) => Ok(percent),
+            _ => {
+                let msg = format!(
+                    "Preview size must be a percentage of the terminal between 1 and 99, but the value given was: {:?}",
+                    size
+                );
+                Err(HttmError::new(&msg).into())
+            }
+        }
+    }
+
+    // the picker's preview window syntax, e.g. "up:50%:wrap"
+    pub fn as_skim_str(&self) -> String {
+        let position = match self.position {
+            PreviewPosition::Top => "up",
+            PreviewPosition::Bottom => "down",
+            PreviewPosition::Left => "left",
+            PreviewPosition::Right => "right",
+        };
+
+        let mut res = format!("{}:{}%", position, self.size);
+
+        if self.wrap {
+            res.push_str(":wrap");
+        }
+
+        if self.hidden {
+            res.push_str(":hidden");
+        }
+
+        res
+    }
+
+    pub fn bindings() -> Vec<KeyBinding> {
+        vec![
+            KeyBinding::new(
+                TOGGLE_PREVIEW_KEY,
+                "toggle the preview window",
+                KeyAction::Bind("toggle-preview".to_owned()),
+            ),
+            KeyBinding::new(
+                TOGGLE_PREVIEW_WRAP_KEY,
+                "toggle line wrapping in the preview window",
+                KeyAction::Bind("toggle-preview-wrap".to_owned()),
+            ),
+        ]
+    }
+}
+
 pub struct PreviewSelection {
     pub opt_preview_window: Option<String>,
     pub opt_preview_command: Option<String>,
@@ -43,7 +154,7 @@ impl PreviewSelection {
                 )?);
 
                 PreviewSelection {
-                    opt_preview_window: Some("up:50%".to_owned()),
+                    opt_preview_window: Some(GLOBAL_CONFIG.preview_window.as_skim_str()),
                     opt_preview_command,
                 }
             }
@@ -66,7 +177,19 @@ impl PreviewSelection {
                     format!("bowie --direct \"$snap_file\" \"{live_version}\"")
                 },
                 _ => match which("cat") {
-                    Ok(_) => "if [[ -s \"$snap_file\" ]]; then cat \"$snap_file\"; else printf \"WARN: \"$snap_file\" is empty\"; fi".to_string(),
+                    // we can't test whether a file we can't read is empty, so just ask the helper to read it
+                    Ok(_) if GLOBAL_CONFIG.opt_privileged_helper.is_some() => {
+                        let privileged_helper = GLOBAL_CONFIG.opt_privileged_helper.as_ref().expect("checked is_some above");
+                        let transcode = Transcode::command()?;
+
+                        format!("if [[ -r \"$snap_file\" ]]; then {transcode}\"$snap_file\"; else {} cat \"$snap_file\"; fi", privileged_helper.shell_prefix())
+                    },
+                    // files in another text encoding, like UTF-16, are transcoded, so they are not displayed as mojibake
+                    Ok(_) => {
+                        let transcode = Transcode::command()?;
+
+                        format!("if [[ -s \"$snap_file\" ]]; then {transcode}\"$snap_file\"; else printf \"WARN: \"$snap_file\" is empty\"; fi")
+                    },
                     Err(_) => {
                         return Err(HttmError::new(
                             "'cat' executable could not be found in the user's PATH. 'cat' is necessary for executing a bare preview command.",
@@ -90,21 +213,30 @@ impl PreviewSelection {
                 }
             }
 
+            // these variables are set, per selection, by the preview bootstrap script
+            let templated_command = defined_command
+                .replace("{snap_file}", "\"$snap_file\"")
+                .replace("{path}", "\"$snap_file\"")
+                .replace("{snap_name}", "\"$snap_name\"")
+                .replace("{mtime}", "\"$mtime\"")
+                .replace("{size}", "\"$size\"");
+
             let parsed_command = match opt_live_version {
-                Some(live_version) if defined_command.contains("{live_file}") && !PathBuf::from(live_version).exists() => {
+                Some(live_version) if (defined_command.contains("{live_file}") || defined_command.contains("{live_path}")) && !PathBuf::from(live_version).exists() => {
                     return Err(HttmError::new("User specified a preview variable for a live version, but a live version for the file selected does not exist.").into())
                 },
                 Some(live_version) => {
-                    defined_command
-                        .replace("{snap_file}", "\"$snap_file\"")
+                    templated_command
                         .replace("{live_file}", format!("\"{live_version}\"").as_str())
+                        .replace("{live_path}", format!("\"{live_version}\"").as_str())
                 },
                 None if defined_command.contains("{live_file}") => {
                     return Err(HttmError::new("User specified a preview variable for a live version, but a live version could not be determined.").into())
                 },
+                // when multiple files are selected, the live path is derived from each snapshot path
                 None => {
-                    defined_command
-                        .replace("{snap_file}", "\"$snap_file\"")
+                    templated_command
+                        .replace("{live_path}", "\"$live_path\"")
                 },
             };
 
@@ -113,7 +245,7 @@ impl PreviewSelection {
             if parsed_command.contains("\"$snap_file\"") {
                 parsed_command
             } else {
-                [defined_command, " \"$snap_file\""].into_iter().collect()
+                parsed_command + " \"$snap_file\""
             }
         };
 
@@ -121,6 +253,18 @@ impl PreviewSelection {
             Ok(_) => {
                 let script = include_str!("../../scripts/preview-bootstrap.bash");
 
+                // the note upon a version, if any, is printed above its contents
+                let command = if VERSION_NOTES.is_empty() {
+                    command
+                } else {
+                    let httm_command = std::env::current_exe()?;
+
+                    format!(
+                        "{} --print-note=\"$snap_file\" || true; {command}",
+                        ShellCommands::quote(&ShellDialect::Bash, httm_command.as_os_str().as_bytes())
+                    )
+                };
+
                 let res = script.replace("{command}", &command);
 
                 Ok(res)
@@ -18,9 +18,17 @@
 use crate::config::generate::ListSnapsFilters;
 use crate::interactive::view_mode::MultiSelect;
 use crate::interactive::view_mode::ViewMode;
+use crate::library::audit_log::{AuditAction, AuditLog};
+use crate::library::diagnostics::Diagnostic;
+use crate::library::i18n::MESSAGES;
 use crate::library::results::{HttmError, HttmResult};
+use crate::library::snap_guard::ZfsAllowPriv;
+use crate::library::stash::Stash;
 use crate::lookup::snap_names::SnapNameMap;
 use crate::lookup::versions::VersionsMap;
+use crate::GLOBAL_CONFIG;
+use std::collections::BTreeSet;
+use std::path::Path;
 use std::process::Command as ExecProcess;
 
 pub struct PruneSnaps;
@@ -30,15 +38,70 @@ impl PruneSnaps {
         versions_map: VersionsMap,
         opt_filters: &Option<ListSnapsFilters>,
     ) -> HttmResult<()> {
+        // keep the versions themselves around, if we need to stash them before destroying snapshots
+        let opt_stash_versions = GLOBAL_CONFIG
+            .opt_stash_root
+            .as_ref()
+            .map(|_| versions_map.clone());
+
         let snap_name_map: SnapNameMap = SnapNameMap::new(versions_map, opt_filters)?;
 
+        Self::confirm(&snap_name_map, opt_filters, opt_stash_versions.as_ref())
+    }
+
+    pub fn exec_datasets(
+        datasets: &[String],
+        opt_filters: &Option<ListSnapsFilters>,
+    ) -> HttmResult<()> {
+        if GLOBAL_CONFIG.opt_stash_root.is_some() {
+            Diagnostic::warn(
+                "stash_ignored",
+                "STASH requires input files, and is ignored when pruning by DATASET.",
+            )
+            .print();
+        }
+
+        let snap_name_map: SnapNameMap = SnapNameMap::from_datasets(
+            datasets,
+            GLOBAL_CONFIG.opt_recursive_datasets,
+            opt_filters,
+        )?;
+
+        Self::confirm(&snap_name_map, opt_filters, None)
+    }
+
+    fn confirm(
+        snap_name_map: &SnapNameMap,
+        opt_filters: &Option<ListSnapsFilters>,
+        opt_stash_versions: Option<&VersionsMap>,
+    ) -> HttmResult<()> {
+        // check for privileges before asking for consent, instead of failing part way through
+        snap_name_map
+            .values()
+            .flatten()
+            .filter_map(|snapshot_name| snapshot_name.split_once('@'))
+            .map(|(dataset, _snap)| dataset)
+            .collect::<BTreeSet<&str>>()
+            .into_iter()
+            .try_for_each(|dataset| ZfsAllowPriv::Destroy.from_fs_name(dataset))?;
+
         let select_mode = if let Some(filters) = opt_filters {
             filters.select_mode
         } else {
             false
         };
 
-        InteractivePrune::new(&snap_name_map, select_mode)
+        InteractivePrune::exec(snap_name_map, select_mode, opt_stash_versions)
+    }
+
+    fn stash(snap_name_map: &SnapNameMap, stash_versions: &VersionsMap) -> HttmResult<()> {
+        let Some(stash_root) = GLOBAL_CONFIG.opt_stash_root.as_ref() else {
+            return Ok(());
+        };
+
+        let snap_names: Vec<&String> = snap_name_map.values().flatten().collect();
+
+        Stash::new(stash_root)?.exec(stash_versions, &snap_names)
     }
 
     fn prune(snap_name_map: &SnapNameMap) -> HttmResult<()> {
@@ -50,35 +113,45 @@ impl PruneSnaps {
             .values()
             .flatten()
             .try_for_each(|snapshot_name| {
-                let process_args = vec!["destroy".to_owned(), snapshot_name.clone()];
-
-                let process_output = ExecProcess::new(&zfs_command)
-                    .args(&process_args)
-                    .output()?;
-                let stderr_string = std::str::from_utf8(&process_output.stderr)?.trim();
-
-                // stderr_string is a string not an error, so here we build an err or output
-                if !stderr_string.is_empty() {
-                    let msg = if stderr_string.contains("cannot destroy snapshots: permission denied") {
-                        "httm must have root privileges to destroy a snapshot filesystem".to_owned()
-                    } else {
-                        "httm was unable to destroy snapshots. The 'zfs' command issued the following error: "
-                        .to_owned()
-                        + stderr_string
-                    };
-
-                    Err(HttmError::new(&msg).into())
+                let res = Self::destroy(&zfs_command, snapshot_name);
+
+                AuditLog::record(AuditAction::Destroy, [], [snapshot_name.as_str()], &res);
+
+                res
+            })
+    }
+
+    fn destroy(zfs_command: &Path, snapshot_name: &str) -> HttmResult<()> {
+        let process_args = vec!["destroy".to_owned(), snapshot_name.to_owned()];
+
+        let process_output = ExecProcess::new(zfs_command).args(&process_args).output()?;
+        let stderr_string = std::str::from_utf8(&process_output.stderr)?.trim();
+
+        // stderr_string is a string not an error, so here we build an err or output
+        if !stderr_string.is_empty() {
+            let msg = if stderr_string.contains("cannot destroy snapshots: permission denied") {
+                "httm must have root privileges, or delegated 'zfs allow' privileges, to destroy a snapshot filesystem".to_owned()
             } else {
-                Ok(())
-            }
-      })
+                "httm was unable to destroy snapshots. The 'zfs' command issued the following error: "
+                .to_owned()
+                + stderr_string
+            };
+
+            Err(HttmError::new(&msg).into())
+        } else {
+            Ok(())
+        }
     }
 }
 
 struct InteractivePrune;
 
 impl InteractivePrune {
-    fn new(snap_name_map: &SnapNameMap, select_mode: bool) -> HttmResult<()> {
+    fn exec(
+        snap_name_map: &SnapNameMap,
+        select_mode: bool,
+        opt_stash_versions: Option<&VersionsMap>,
+    ) -> HttmResult<()> {
         let file_names_string: String =
             snap_name_map.keys().fold(String::new(), |mut buffer, key| {
                 buffer += format!("{:?}\n", key.path_buf).as_str();
@@ -106,15 +179,7 @@ impl InteractivePrune {
             .map(|name| format!("{name}\n"))
             .collect();
 
-        let prune_buffer = format!(
-            "User has requested snapshots related to the following file/s be pruned:\n\n{}\n\
-            httm will destroy the following snapshot/s:\n\n{}\n\
-            Before httm destroys these snapshot/s, it would like your consent. Continue? (YES/NO)\n\
-            ─────────────────────────────────────────────────────────────────────────────\n\
-            YES\n\
-            NO",
-            file_names_string, snap_names_string
-        );
+        let prune_buffer = Self::consent_buffer(&file_names_string, &snap_names_string);
 
         // loop until user consents or doesn't
         loop {
@@ -124,27 +189,53 @@ impl InteractivePrune {
 
             let user_consent = selection
                 .get(0)
-                .ok_or_else(|| HttmError::new("Could not obtain the first match selected"))?;
+                .ok_or_else(|| HttmError::new(&MESSAGES.get("no-selection")))?;
+
+            match MESSAGES.consent(user_consent) {
+                Some(true) => {
+                    if let Some(stash_versions) = opt_stash_versions {
+                        PruneSnaps::stash(snap_name_map, stash_versions)?;
+                    }
 
-            match user_consent.to_ascii_uppercase().as_ref() {
-                "YES" | "Y" => {
                     PruneSnaps::prune(snap_name_map)?;
 
                     let result_buffer = format!(
-                        "httm pruned snapshots related to the following file/s:\n\n{}\n\
-                        By destroying the following snapshot/s:\n\n{}\n\
-                        Prune completed successfully.",
-                        file_names_string, snap_names_string
+                        "{}\n\n{}\n\
+                        {}\n\n{}\n\
+                        {}",
+                        MESSAGES.get("prune-done"),
+                        file_names_string,
+                        MESSAGES.get("prune-done-snapshots"),
+                        snap_names_string,
+                        MESSAGES.get("prune-success")
                     );
 
                     break eprintln!("{result_buffer}");
                 }
-                "NO" | "N" => break eprintln!("User declined prune.  No files were pruned."),
+                Some(false) => break eprintln!("{}", MESSAGES.get("prune-declined")),
                 // if not yes or no, then noop and continue to the next iter of loop
-                _ => {}
+                None => {}
             }
         }
 
         Ok(())
     }
+
+    fn consent_buffer(file_names_string: &str, snap_names_string: &str) -> String {
+        let yes = MESSAGES.get("consent-yes");
+        let no = MESSAGES.get("consent-no");
+        let prompt = MESSAGES.format("consent-prompt", &[("yes", &yes), ("no", &no)]);
+
+        format!(
+            "{}\n\n{file_names_string}\n\
+            {}\n\n{snap_names_string}\n\
+            {} {prompt}\n\
+            ─────────────────────────────────────────────────────────────────────────────\n\
+            {yes}\n\
+            {no}",
+            MESSAGES.get("prune-intro"),
+            MESSAGES.get("prune-snapshots"),
+            MESSAGES.get("prune-consent"),
+        )
+    }
 }
@@ -0,0 +1,393 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::data::paths::PathData;
+use crate::display_map::datasets::DatasetInventory;
+use crate::interactive::restore::InteractiveRestore;
+use crate::interactive::select::InteractiveSelect;
+use crate::interactive::view_mode::MultiSelect;
+use crate::interactive::view_mode::ViewMode;
+use crate::library::diagnostics::Diagnostic;
+use crate::library::file_ops::Copy;
+use crate::library::i18n::MESSAGES;
+use crate::library::results::{HttmError, HttmResult};
+use crate::library::utility::{date_string, DateFormat};
+use crate::lookup::deleted::{DeletedFiles, LastInTimeSet};
+use crate::parse::snaps::MapOfSnaps;
+use crate::GLOBAL_CONFIG;
+use rayon::prelude::*;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+const PARENT_DIRECTORY: &str = "..";
+
+// the snapshots between which a file was deleted: the last snapshot upon which the file was seen,
+// and, should one exist, the next snapshot of the same dataset, upon which it was not
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct DeletionWindow {
+    after: (Option<SystemTime>, String),
+    opt_before: Option<(Option<SystemTime>, String)>,
+}
+
+impl DeletionWindow {
+    fn new(last_version: &Path) -> Option<Self> {
+        let dataset_collection = &GLOBAL_CONFIG.dataset_collection;
+        let opt_map_of_creation_times = dataset_collection.opt_map_of_creation_times.as_ref();
+
+        let snap_mount = dataset_collection.snap_mount_of(last_version)?;
+
+        let snap_time = |snap_mount: &Path| {
+            (
+                MapOfSnaps::snap_time(snap_mount, opt_map_of_creation_times),
+                DatasetInventory::snap_name(snap_mount),
+            )
+        };
+
+        let after = snap_time(snap_mount);
+
+        let opt_before = dataset_collection
+            .map_of_snaps
+            .values()
+            .find(|snap_mounts| snap_mounts.iter().any(|other| other == snap_mount))
+            .and_then(|snap_mounts| {
+                snap_mounts
+                    .iter()
+                    .map(|other| snap_time(other))
+                    .filter(|other| other.0 > after.0)
+                    .min()
+            });
+
+        Some(Self { after, opt_before })
+    }
+
+    fn label(&self) -> String {
+        let describe = |(opt_time, snap_name): &(Option<SystemTime>, String)| match opt_time {
+            Some(time) => format!(
+                "{} ({snap_name})",
+                date_string(
+                    GLOBAL_CONFIG.requested_utc_offset,
+                    time,
+                    DateFormat::Display
+                )
+            ),
+            None => snap_name.clone(),
+        };
+
+        match &self.opt_before {
+            Some(before) => MESSAGES.format(
+                "recover-deleted-between",
+                &[
+                    ("after", &describe(&self.after)),
+                    ("before", &describe(before)),
+                ],
+            ),
+            None => MESSAGES.format(
+                "recover-deleted-after",
+                &[("after", &describe(&self.after))],
+            ),
+        }
+    }
+}
+
+struct DeletedEntry {
+    window: DeletionWindow,
+    last_version: PathBuf,
+}
+
+// a guided recovery of deleted files: pick a directory, select among the files deleted from that
+// directory, grouped by when they were deleted, pick a destination, and restore, all in one session
+pub struct RecoverWizard;
+
+impl RecoverWizard {
+    pub fn exec() -> HttmResult<()> {
+        let start_dir = GLOBAL_CONFIG
+            .paths
+            .first()
+            .map(|pathdata| pathdata.path_buf.clone())
+            .filter(|path| path.is_dir())
+            .unwrap_or_else(|| GLOBAL_CONFIG.pwd.clone());
+
+        let requested_dir = Self::pick_directory(start_dir)?;
+
+        let deleted = Self::scan(&requested_dir)?;
+
+        if deleted.is_empty() {
+            println!(
+                "{}",
+                MESSAGES.format("recover-none", &[("dir", &format!("{:?}", requested_dir))])
+            );
+            return Ok(());
+        }
+
+        let selected = Self::select(&deleted)?;
+
+        let dst_dir = Self::pick_destination(&requested_dir)?;
+
+        Self::restore(&selected, &dst_dir)
+    }
+
+    fn pick_directory(start_dir: PathBuf) -> HttmResult<PathBuf> {
+        let search_here = MESSAGES.get("recover-search-here");
+        let mut dir = start_dir;
+
+        loop {
+            let mut sub_dirs: Vec<String> = std::fs::read_dir(&dir)?
+                .flatten()
+                .filter(|entry| entry.file_type().is_ok_and(|file_type| file_type.is_dir()))
+                .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                .collect();
+
+            sub_dirs.sort_unstable();
+
+            // view is displayed in reverse order, so our special entries go last
+            let browse_buffer: String = sub_dirs
+                .iter()
+                .map(String::as_str)
+                .rev()
+                .chain([PARENT_DIRECTORY, search_here.as_str()])
+                .map(|line| line.to_owned() + "\n")
+                .collect();
+
+            eprintln!(
+                "{}",
+                MESSAGES.format("recover-pick-dir", &[("dir", &format!("{:?}", dir))])
+            );
+
+            let selection = ViewMode::Restore.view_buffer(&browse_buffer, MultiSelect::Off)?;
+
+            let choice = selection
+                .first()
+                .ok_or_else(|| HttmError::new(&MESSAGES.get("no-selection")))?;
+
+            dir = match choice.as_str() {
+                choice if choice == search_here => return Ok(dir),
+                PARENT_DIRECTORY => dir.parent().unwrap_or(&dir).to_path_buf(),
+                sub_dir => dir.join(sub_dir),
+            };
+        }
+    }
+
+    fn scan(requested_dir: &Path) -> HttmResult<Vec<DeletedEntry>> {
+        let mut deleted: Vec<DeletedEntry> = DeletedFiles::new(requested_dir)?
+            .into_inner()
+            .into_par_iter()
+            .filter_map(|basic_info| {
+                let live_path = requested_dir.join(basic_info.filename());
+
+                // the version upon the last snapshot which contains the file is the one most worth restoring
+                let last_version = LastInTimeSet::new(vec![PathData::from(live_path.as_path())])
+                    .ok()
+                    .and_then(|last_in_time| last_in_time.first().cloned())
+                    .unwrap_or(basic_info.path);
+
+                let window = DeletionWindow::new(&last_version)?;
+
+                Some(DeletedEntry {
+                    window,
+                    last_version,
+                })
+            })
+            .collect();
+
+        // the most recent deletions are listed first, and grouped by window
+        deleted.sort_by(|a, b| {
+            b.window
+                .cmp(&a.window)
+                .then_with(|| a.last_version.cmp(&b.last_version))
+        });
+
+        Ok(deleted)
+    }
+
+    fn select(deleted: &[DeletedEntry]) -> HttmResult<Vec<PathBuf>> {
+        let buffer: String = deleted
+            .iter()
+            .map(|entry| {
+                format!(
+                    "{}\t\"{}\"\n",
+                    entry.window.label(),
+                    entry.last_version.display()
+                )
+            })
+            .collect();
+
+        let selection = ViewMode::Restore.view_buffer(&buffer, MultiSelect::On)?;
+
+        let selected: Vec<PathBuf> = selection
+            .iter()
+            .filter_map(|line| InteractiveSelect::quoted_path(line))
+            .map(PathBuf::from)
+            .collect();
+
+        if selected.is_empty() {
+            return Err(HttmError::new(&MESSAGES.get("no-selection")).into());
+        }
+
+        Ok(selected)
+    }
+
+    fn pick_destination(requested_dir: &Path) -> HttmResult<PathBuf> {
+        let original_dir = MESSAGES.get("recover-original-dir");
+        let custom_dir = MESSAGES.get("recover-custom-dir");
+
+        loop {
+            let choose_buffer = format!(
+                "{}\n\n\
+                \t{}:\t{:?}\n\n\
+                ─────────────────────────────────────────────────────────────────────────────────────────\n\
+                {original_dir}\n\
+                {custom_dir}",
+                MESSAGES.get("recover-pick-dest"),
+                MESSAGES.get("label-live"),
+                requested_dir
+            );
+
+            let selection = ViewMode::Restore.view_buffer(&choose_buffer, MultiSelect::Off)?;
+
+            let choice = selection
+                .first()
+                .ok_or_else(|| HttmError::new(&MESSAGES.get("no-selection")))?;
+
+            if *choice == original_dir {
+                return Ok(requested_dir.to_path_buf());
+            }
+
+            if *choice == custom_dir {
+                if let Some(custom_dir) = Self::custom_dir()? {
+                    return Ok(custom_dir);
+                }
+            }
+        }
+    }
+
+    fn custom_dir() -> HttmResult<Option<PathBuf>> {
+        // skim does not return free form input, so read a line directly from the terminal
+        let mut tty = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open("/dev/tty")?;
+
+        write!(tty, "{} ", MESSAGES.get("recover-custom-prompt"))?;
+        tty.flush()?;
+
+        let mut input = String::new();
+        BufReader::new(tty).read_line(&mut input)?;
+
+        let input = input.trim();
+
+        if input.is_empty() {
+            return Ok(None);
+        }
+
+        let custom_dir = GLOBAL_CONFIG.pwd.join(input);
+
+        if !custom_dir.is_dir() {
+            Diagnostic::warn(
+                "not_a_directory",
+                format!("The custom directory requested does not exist: {custom_dir:?}"),
+            )
+            .with_path(&custom_dir)
+            .print();
+            return Ok(None);
+        }
+
+        Ok(Some(custom_dir))
+    }
+
+    fn restore(selected: &[PathBuf], dst_dir: &Path) -> HttmResult<()> {
+        let pairs: Vec<(&Path, PathBuf)> = selected
+            .iter()
+            .filter_map(|src| {
+                let dst = dst_dir.join(src.file_name()?);
+                Some((src.as_path(), dst))
+            })
+            .collect();
+
+        let rows: Vec<(&str, String)> = pairs
+            .iter()
+            .map(|(src, dst)| ("label-source", format!("{src:?} -> {dst:?}")))
+            .collect();
+
+        let consent_buffer = InteractiveRestore::consent_buffer(
+            &MESSAGES.format("recover-intro", &[("count", &pairs.len().to_string())]),
+            &rows,
+            &MESSAGES.get("recover-consent"),
+        );
+
+        loop {
+            let selection = ViewMode::Restore.view_buffer(&consent_buffer, MultiSelect::Off)?;
+
+            let user_consent = selection
+                .first()
+                .ok_or_else(|| HttmError::new(&MESSAGES.get("no-selection")))?;
+
+            match MESSAGES.consent(user_consent) {
+                Some(true) => break,
+                Some(false) => {
+                    println!(
+                        "{}",
+                        MESSAGES.format("recover-declined", &[("dir", &format!("{:?}", dst_dir))])
+                    );
+                    return Ok(());
+                }
+                // if not yes or no, then noop and continue to the next iter of loop
+                None => {}
+            }
+        }
+
+        let mut num_restored = 0usize;
+
+        for (src, dst) in pairs.iter() {
+            // a deleted file is never restored over a file which has since taken its place
+            if dst.symlink_metadata().is_ok() {
+                Diagnostic::warn(
+                    "destination_exists",
+                    format!(
+                        "httm will not restore to that path, as a file with the same path name already exists: {dst:?}"
+                    ),
+                )
+                .with_path(dst)
+                .print();
+                continue;
+            }
+
+            match Copy::restore(src, dst, true, GLOBAL_CONFIG.opt_restore_mtime) {
+                Ok(_) => num_restored += 1,
+                Err(err) => {
+                    let msg = MESSAGES.format("restore-failed", &[("reason", &err.to_string())]);
+                    Diagnostic::warn("restore_failed", msg)
+                        .with_path(src)
+                        .print();
+                }
+            }
+        }
+
+        println!(
+            "{}",
+            MESSAGES.format(
+                "recover-done",
+                &[
+                    ("count", &num_restored.to_string()),
+                    ("dir", &format!("{:?}", dst_dir))
+                ]
+            )
+        );
+
+        Ok(())
+    }
+}
@@ -19,25 +19,33 @@ use crate::config::generate::{ExecMode, InteractiveMode, RestoreMode, RestoreSna
 use crate::data::paths::PathData;
 use crate::data::paths::PathDeconstruction;
 use crate::data::paths::ZfsSnapPathGuard;
+use crate::display_map::datasets::DatasetInventory;
+use crate::interactive::destination::RestoreDestination;
+use crate::interactive::merge::{MergeResult, ThreeWayMerge};
 use crate::interactive::select::InteractiveSelect;
 use crate::interactive::view_mode::MultiSelect;
 use crate::interactive::view_mode::ViewMode;
-use crate::library::file_ops::Copy;
+use crate::library::file_ops::{Copy, SnapDestination, SpaceCheck};
+use crate::library::i18n::MESSAGES;
+use crate::library::jobs::RestoreJob;
 use crate::library::results::{HttmError, HttmResult};
 use crate::library::snap_guard::SnapGuard;
-use crate::library::utility::{date_string, DateFormat};
+use crate::library::special_file::SpecialFile;
+use crate::library::utility::{date_string, print_output_buf, DateFormat};
 use crate::GLOBAL_CONFIG;
 
 use nu_ansi_term::Color::LightYellow;
 use terminal_size::Height;
 use terminal_size::Width;
 
+use std::ffi::OsString;
 use std::path::{Path, PathBuf};
 
 pub struct InteractiveRestore {
     pub view_mode: ViewMode,
     pub snap_path_strings: Vec<String>,
     pub opt_live_version: Option<String>,
+    pub live_versions: Vec<PathData>,
 }
 
 impl From<InteractiveSelect> for InteractiveRestore {
@@ -46,6 +54,7 @@ impl From<InteractiveSelect> for InteractiveRestore {
             view_mode: ViewMode::Restore,
             snap_path_strings: interactive_select.snap_path_strings,
             opt_live_version: interactive_select.opt_live_version,
+            live_versions: interactive_select.live_versions,
         }
     }
 }
@@ -64,21 +73,37 @@ impl InteractiveRestore {
         // if snap_pathdata is_phantom below
         let snap_pathdata = PathData::from(Path::new(snap_path_string));
 
-        // build new place to send file
-        let new_file_path_buf = self.build_new_file_path(&snap_pathdata)?;
+        if matches!(
+            GLOBAL_CONFIG.exec_mode,
+            ExecMode::Interactive(InteractiveMode::Restore(RestoreMode::Merge))
+        ) {
+            return self.merge_per_path(snap_pathdata);
+        }
+
+        // build new place to send file, either as the user picks, or per restore mode
+        let (new_file_path_buf, should_preserve) = if matches!(
+            GLOBAL_CONFIG.exec_mode,
+            ExecMode::Interactive(InteractiveMode::Restore(RestoreMode::PickDestination))
+        ) {
+            let destination = RestoreDestination::pick(self, &snap_pathdata)?;
+            (destination.path_buf, destination.should_preserve)
+        } else {
+            (
+                self.build_new_file_path(&snap_pathdata)?,
+                Self::should_preserve_attributes(),
+            )
+        };
 
-        let should_preserve = Self::should_preserve_attributes();
+        SnapDestination::refuse(&new_file_path_buf)?;
 
         // tell the user what we're up to, and get consent
-        let restore_buffer = format!(
-            "httm will perform a copy from snapshot:\n\n\
-            \tsource:\t{:?}\n\
-            \ttarget:\t{new_file_path_buf:?}\n\n\
-            Before httm performs a restore, it would like your consent. Continue? (YES/NO)\n\
-            ─────────────────────────────────────────────────────────────────────────────────────────\n\
-            YES\n\
-            NO",
-            snap_pathdata.path_buf
+        let restore_buffer = Self::consent_buffer(
+            &MESSAGES.get("restore-intro"),
+            &[
+                ("label-source", format!("{:?}", snap_pathdata.path_buf)),
+                ("label-target", format!("{new_file_path_buf:?}")),
+            ],
+            &MESSAGES.get("restore-consent"),
         );
 
         // loop until user consents or doesn't
@@ -89,10 +114,22 @@ impl InteractiveRestore {
 
             let user_consent = selection
                 .get(0)
-                .ok_or_else(|| HttmError::new("Could not obtain the first match selected."))?;
+                .ok_or_else(|| HttmError::new(&MESSAGES.get("no-selection")))?;
+
+            match MESSAGES.consent(user_consent) {
+                Some(true) => {
+                    self.live_version_unchanged(&new_file_path_buf)?;
+
+                    SpaceCheck::exec(&snap_pathdata.path_buf, &new_file_path_buf)?;
+
+                    if GLOBAL_CONFIG.opt_background {
+                        break Self::submit_job(
+                            &snap_pathdata.path_buf,
+                            &new_file_path_buf,
+                            should_preserve,
+                        )?;
+                    }
 
-            match user_consent.to_ascii_uppercase().as_ref() {
-                "YES" | "Y" => {
                     if matches!(
                         GLOBAL_CONFIG.exec_mode,
                         ExecMode::Interactive(InteractiveMode::Restore(RestoreMode::Overwrite(
@@ -102,67 +139,233 @@ impl InteractiveRestore {
                         let snap_guard: SnapGuard =
                             SnapGuard::try_from(new_file_path_buf.as_path())?;
 
-                        if let Err(err) = Copy::recursive(
+                        if let Err(err) = Copy::restore(
                             &snap_pathdata.path_buf,
                             &new_file_path_buf,
                             should_preserve,
+                            GLOBAL_CONFIG.opt_restore_mtime,
                         ) {
                             let msg = format!(
-                                "httm restore failed for the following reason: {}.\n\
-                            Attempting roll back to precautionary pre-execution snapshot.",
-                                err
+                                "{}\n{}",
+                                MESSAGES.format("restore-failed", &[("reason", &err.to_string())]),
+                                MESSAGES.get("restore-rollback")
                             );
 
                             eprintln!("{}", msg);
 
-                            snap_guard
-                                .rollback()
-                                .map(|_| println!("Rollback succeeded."))?;
+                            snap_guard.rollback()?;
+                            print_output_buf(&format!("{}\n", MESSAGES.get("rollback-succeeded")))?;
 
                             std::process::exit(1);
                         }
                     } else {
-                        if let Err(err) = Copy::recursive(
+                        if let Err(err) = Copy::restore(
                             &snap_pathdata.path_buf,
                             &new_file_path_buf,
                             should_preserve,
+                            GLOBAL_CONFIG.opt_restore_mtime,
                         ) {
                             let msg =
-                                format!("httm restore failed for the following reason: {}.", err);
+                                MESSAGES.format("restore-failed", &[("reason", &err.to_string())]);
                             return Err(HttmError::new(&msg).into());
                         }
                     }
 
-                    let result_buffer = format!(
-                        "httm copied from snapshot:\n\n\
-                            \tsource:\t{:?}\n\
-                            \ttarget:\t{new_file_path_buf:?}\n\n\
-                            Restore completed successfully.",
-                        snap_pathdata.path_buf
+                    let result_buffer = Self::result_buffer(
+                        &MESSAGES.get("restore-done"),
+                        &snap_pathdata.path_buf,
+                        &new_file_path_buf,
+                        &MESSAGES.get("restore-success"),
                     );
 
                     let summary_string = LightYellow.paint(Self::summary_string());
 
-                    break println!("{summary_string}{result_buffer}");
+                    break print_output_buf(&format!("{summary_string}{result_buffer}\n"))?;
                 }
-                "NO" | "N" => {
-                    break println!("User declined restore of: {:?}", snap_pathdata.path_buf)
+                Some(false) => {
+                    break print_output_buf(&format!(
+                        "{}\n",
+                        MESSAGES.format(
+                            "restore-declined",
+                            &[("path", &format!("{:?}", snap_pathdata.path_buf))]
+                        )
+                    ))?
                 }
                 // if not yes or no, then noop and continue to the next iter of loop
-                _ => {}
+                None => {}
             }
         }
 
         Ok(())
     }
 
+    fn submit_job(src: &Path, dst: &Path, should_preserve: bool) -> HttmResult<()> {
+        // take the precautionary snapshot now, while the user is present to observe any failure
+        let opt_snap_guard = if matches!(
+            GLOBAL_CONFIG.exec_mode,
+            ExecMode::Interactive(InteractiveMode::Restore(RestoreMode::Overwrite(
+                RestoreSnapGuard::Guarded
+            )))
+        ) {
+            Some(SnapGuard::try_from(dst)?)
+        } else {
+            None
+        };
+
+        let job = RestoreJob::submit(src, dst, should_preserve, opt_snap_guard.as_ref())?;
+
+        let result_buffer = Self::result_buffer(
+            &MESSAGES.get("restore-queued"),
+            src,
+            dst,
+            &MESSAGES.format("restore-queued-job", &[("id", &job.id.to_string())]),
+        );
+
+        let summary_string = LightYellow.paint(Self::summary_string());
+
+        print_output_buf(&format!("{summary_string}{result_buffer}\n"))?;
+
+        Ok(())
+    }
+
+    fn merge_per_path(&self, snap_pathdata: PathData) -> HttmResult<()> {
+        let live_path = self.opt_live_version(&snap_pathdata)?;
+
+        let three_way_merge = ThreeWayMerge::new(live_path, snap_pathdata)?;
+
+        // tell the user what we're up to, and get consent
+        let merge_buffer = Self::consent_buffer(
+            &MESSAGES.get("merge-intro"),
+            &[
+                ("label-live", format!("{:?}", three_way_merge.live)),
+                (
+                    "label-ancestor",
+                    format!("{:?}", three_way_merge.ancestor.path_buf),
+                ),
+                (
+                    "label-snapshot",
+                    format!("{:?}", three_way_merge.chosen.path_buf),
+                ),
+            ],
+            &MESSAGES.get("merge-consent"),
+        );
+
+        // loop until user consents or doesn't
+        loop {
+            let selection = ViewMode::Restore.view_buffer(&merge_buffer, MultiSelect::Off)?;
+
+            let user_consent = selection
+                .first()
+                .ok_or_else(|| HttmError::new(&MESSAGES.get("no-selection")))?;
+
+            match MESSAGES.consent(user_consent) {
+                Some(true) => {
+                    self.live_version_unchanged(&three_way_merge.live)?;
+
+                    SpaceCheck::exec(&three_way_merge.chosen.path_buf, &three_way_merge.live)?;
+
+                    let result_string = match three_way_merge.exec()? {
+                        MergeResult::Clean => MESSAGES.get("merge-clean"),
+                        MergeResult::Conflicts => MESSAGES.get("merge-conflicts"),
+                    };
+
+                    let result_buffer = Self::result_buffer(
+                        &MESSAGES.get("merge-done"),
+                        &three_way_merge.chosen.path_buf,
+                        &three_way_merge.live,
+                        &result_string,
+                    );
+
+                    let summary_string = LightYellow.paint(Self::summary_string());
+
+                    break print_output_buf(&format!("{summary_string}{result_buffer}\n"))?;
+                }
+                Some(false) => {
+                    break print_output_buf(&format!(
+                        "{}\n",
+                        MESSAGES.format(
+                            "merge-declined",
+                            &[("path", &format!("{:?}", three_way_merge.chosen.path_buf))]
+                        )
+                    ))?
+                }
+                // if not yes or no, then noop and continue to the next iter of loop
+                None => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    // refuse to overwrite a live file which has changed since the user selected a snapshot version,
+    // so as not to clobber any edits made while the user was browsing versions
+    fn live_version_unchanged(&self, new_file_path_buf: &Path) -> HttmResult<()> {
+        if GLOBAL_CONFIG.opt_overwrite_changed {
+            return Ok(());
+        }
+
+        let Some(observed) = self
+            .live_versions
+            .iter()
+            .find(|live_version| live_version.path_buf.as_path() == new_file_path_buf)
+        else {
+            return Ok(());
+        };
+
+        let current = PathData::from(new_file_path_buf);
+
+        if current.metadata != observed.metadata {
+            let msg = MESSAGES.format(
+                "live-version-changed",
+                &[("path", &format!("{:?}", new_file_path_buf))],
+            );
+            return Err(HttmError::new(&msg).into());
+        }
+
+        Ok(())
+    }
+
     fn summary_string() -> String {
         let width = match terminal_size::terminal_size() {
             Some((Width(width), Height(_height))) => width as usize,
             None => 80usize,
         };
 
-        format!("{:^width$}\n", "====> [ httm recovery summary ] <====")
+        let summary = format!("====> [ {} ] <====", MESSAGES.get("recovery-summary"));
+
+        format!("{:^width$}\n", summary)
+    }
+
+    // the layout of the prompt is fixed, only its prose is translated
+    pub fn consent_buffer(intro: &str, rows: &[(&str, String)], consent: &str) -> String {
+        let rows: String = rows
+            .iter()
+            .map(|(label, value)| format!("\t{}:\t{value}\n", MESSAGES.get(label)))
+            .collect();
+
+        let yes = MESSAGES.get("consent-yes");
+        let no = MESSAGES.get("consent-no");
+        let prompt = MESSAGES.format("consent-prompt", &[("yes", &yes), ("no", &no)]);
+
+        format!(
+            "{intro}\n\n\
+            {rows}\n\
+            {consent} {prompt}\n\
+            ─────────────────────────────────────────────────────────────────────────────────────────\n\
+            {yes}\n\
+            {no}"
+        )
+    }
+
+    fn result_buffer(heading: &str, source: &Path, target: &Path, outcome: &str) -> String {
+        format!(
+            "{heading}\n\n\
+            \t{}:\t{source:?}\n\
+            \t{}:\t{target:?}\n\n\
+            {outcome}",
+            MESSAGES.get("label-source"),
+            MESSAGES.get("label-target"),
+        )
     }
 
     fn should_preserve_attributes() -> bool {
@@ -198,6 +401,113 @@ impl InteractiveRestore {
             return self.opt_live_version(snap_pathdata);
         }
 
+        let new_filename = Self::restored_file_name(snap_pathdata)?;
+
+        // a copy named by a template is restored alongside its live version, rather than in the working directory
+        let new_file_path_buf: PathBuf = match &GLOBAL_CONFIG.opt_rename_template {
+            Some(_) => self
+                .opt_live_version(snap_pathdata)?
+                .with_file_name(new_filename),
+            None => GLOBAL_CONFIG.pwd.join(new_filename),
+        };
+
+        // don't let the user rewrite one restore over another in non-overwrite mode
+        if new_file_path_buf.exists() {
+            Err(
+                    HttmError::new("httm will not restore to that file, as a file with the same path name already exists. Quitting.").into(),
+                )
+        } else {
+            Ok(new_file_path_buf)
+        }
+    }
+
+    // restores a copy of a version alongside its live version, named with the snapshot's name as a suffix, or by the RENAME template,
+    // without any prompt, as the copy never overwrites anything
+    pub fn quick_copy(
+        snap_pathdata: &PathData,
+        opt_live_version: Option<&str>,
+    ) -> HttmResult<PathBuf> {
+        let live_path = match opt_live_version {
+            Some(live_version) => Some(PathBuf::from(live_version)),
+            None => {
+                ZfsSnapPathGuard::new(snap_pathdata).and_then(|snap_guard| snap_guard.live_path())
+            }
+        }
+        .ok_or_else(|| HttmError::new("Could not determine a possible live version."))?;
+
+        let Some(snap_mount) = GLOBAL_CONFIG
+            .dataset_collection
+            .snap_mount_of(&snap_pathdata.path_buf)
+        else {
+            let msg = format!(
+                "Could not determine the snapshot of the version selected: {:?}",
+                snap_pathdata.path_buf
+            );
+            return Err(HttmError::new(&msg).into());
+        };
+
+        let Some(live_file_name) = live_path.file_name() else {
+            let msg = format!(
+                "Could not obtain a file name for the live version: {:?}",
+                live_path
+            );
+            return Err(HttmError::new(&msg).into());
+        };
+
+        let new_file_name = match &GLOBAL_CONFIG.opt_rename_template {
+            Some(template) => {
+                OsString::from(template.render(snap_pathdata, &live_file_name.to_string_lossy())?)
+            }
+            None => {
+                let mut new_file_name = live_file_name.to_os_string();
+                new_file_name.push(".");
+                new_file_name.push(DatasetInventory::snap_name(snap_mount));
+                new_file_name
+            }
+        };
+
+        let new_file_path_buf = live_path.with_file_name(new_file_name);
+
+        if new_file_path_buf.symlink_metadata().is_ok() {
+            let msg = format!(
+                "httm will not restore to {:?}, as a file with the same path name already exists.",
+                new_file_path_buf
+            );
+            return Err(HttmError::new(&msg).with_path(&new_file_path_buf).into());
+        }
+
+        SpecialFile::refuse_restore(&snap_pathdata.path_buf)?;
+        SnapDestination::refuse(&new_file_path_buf)?;
+
+        SpaceCheck::exec(&snap_pathdata.path_buf, &new_file_path_buf)?;
+
+        // a quick copy is a restore like any other, so it is bracketed by the hooks, locked, and audited
+        Copy::run_restore(&snap_pathdata.path_buf, &new_file_path_buf, || {
+            Copy::recursive(&snap_pathdata.path_buf, &new_file_path_buf, true)
+        })?;
+
+        Ok(new_file_path_buf)
+    }
+
+    // the file name of a restored copy, by the RENAME template, if one was given, or else with a timestamp
+    pub fn restored_file_name(snap_pathdata: &PathData) -> HttmResult<String> {
+        match &GLOBAL_CONFIG.opt_rename_template {
+            Some(template) => {
+                let Some(snap_filename) = snap_pathdata.path_buf.file_name() else {
+                    let msg = format!(
+                        "Could not obtain a file name for the snapshot version: {:?}",
+                        snap_pathdata.path_buf
+                    );
+                    return Err(HttmError::new(&msg).into());
+                };
+
+                template.render(snap_pathdata, &snap_filename.to_string_lossy())
+            }
+            None => Self::timestamped_file_name(snap_pathdata),
+        }
+    }
+
+    pub fn timestamped_file_name(snap_pathdata: &PathData) -> HttmResult<String> {
         let snap_filename = snap_pathdata
             .path_buf
             .file_name()
@@ -224,16 +534,7 @@ impl InteractiveRestore {
                 &snap_metadata.modify_time,
                 DateFormat::Timestamp,
             );
-        let new_file_dir = GLOBAL_CONFIG.pwd.as_path();
-        let new_file_path_buf: PathBuf = new_file_dir.join(new_filename);
 
-        // don't let the user rewrite one restore over another in non-overwrite mode
-        if new_file_path_buf.exists() {
-            Err(
-                    HttmError::new("httm will not restore to that file, as a file with the same path name already exists. Quitting.").into(),
-                )
-        } else {
-            Ok(new_file_path_buf)
-        }
+        Ok(new_filename)
     }
 }
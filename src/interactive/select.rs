@@ -16,33 +16,50 @@
 // that was distributed with this source code.
 
 use crate::config::generate::{PrintMode, SelectMode};
+use crate::data::paths::PathData;
+use crate::display_map::datasets::DatasetInventory;
+use crate::display_versions::emit_shell::ShellCommands;
+use crate::display_versions::rsync_list::RsyncList;
 use crate::display_versions::wrapper::VersionsDisplayWrapper;
 use crate::interactive::preview::PreviewSelection;
-use crate::interactive::view_mode::MultiSelect;
+use crate::interactive::restore::InteractiveRestore;
 use crate::interactive::view_mode::ViewMode;
+use crate::interactive::view_mode::{MultiSelect, ViewAction};
+use crate::library::diagnostics::Diagnostic;
 use crate::library::results::{HttmError, HttmResult};
-use crate::library::utility::{delimiter, print_output_buf};
+use crate::library::text_encoding::TextEncoding;
+use crate::library::utility::{date_string, delimiter, print_output_buf, DateFormat};
+use crate::library::version_notes::VERSION_NOTES;
 use crate::lookup::versions::VersionsMap;
 use crate::Config;
 use crate::GLOBAL_CONFIG;
 
+use hashbrown::HashMap;
+use nu_ansi_term::Color::DarkGray;
+use serde_json::Value;
 use std::io::Read;
 use std::path::{Path, PathBuf};
 use std::process::Command as ExecProcess;
 
 use super::browse::InteractiveBrowse;
 
+const SEARCH_TOKEN_DATE_FORMAT: &str = "[year]-[month]-[day] [hour]:[minute]";
+
 pub struct InteractiveSelect {
     pub view_mode: ViewMode,
     pub snap_path_strings: Vec<String>,
     pub opt_live_version: Option<String>,
+    pub live_versions: Vec<PathData>,
 }
 
 impl TryFrom<&mut InteractiveBrowse> for InteractiveSelect {
     type Error = Box<dyn std::error::Error + Send + Sync>;
 
     fn try_from(interactive_browse: &mut InteractiveBrowse) -> HttmResult<Self> {
-        let versions_map = VersionsMap::new(&GLOBAL_CONFIG, &interactive_browse.selected_pathdata)?;
+        // every version is kept, so that a toggle of uniqueness need only deduplicate these again
+        let all_versions =
+            VersionsMap::all_versions(&GLOBAL_CONFIG, &interactive_browse.selected_pathdata)?;
+        let versions_map = all_versions.with_uniqueness(&GLOBAL_CONFIG, &GLOBAL_CONFIG.uniqueness);
 
         // snap and live set has no snaps
         if versions_map.is_empty() {
@@ -70,15 +87,34 @@ impl TryFrom<&mut InteractiveBrowse> for InteractiveSelect {
 
         let view_mode = ViewMode::Select(opt_live_version.clone());
 
-        let snap_path_strings = if GLOBAL_CONFIG.opt_last_snap.is_some() {
+        // live versions, and their metadata, as observed at selection time
+        let live_versions: Vec<PathData> = versions_map.keys().cloned().collect();
+
+        // resume with the versions chosen in a prior session, instead of selecting again
+        let opt_session_versions = interactive_browse
+            .opt_session
+            .as_ref()
+            .map(|session| session.versions.clone())
+            .filter(|versions| !versions.is_empty());
+
+        let snap_path_strings = if let Some(session_versions) = opt_session_versions {
+            eprintln!(
+                "NOTICE: Resuming session with {} version/s chosen.",
+                session_versions.len()
+            );
+            session_versions
+        } else if GLOBAL_CONFIG.opt_last_snap.is_some() {
             Self::last_snap(&versions_map)
         } else {
             // same stuff we do at fn exec, snooze...
             let display_config = Config::from(interactive_browse.selected_pathdata.clone());
 
-            let display_map = VersionsDisplayWrapper::from(&display_config, versions_map);
+            let mut display_map = VersionsDisplayWrapper::from(&display_config, versions_map);
+
+            let mut selection_buffer =
+                Self::with_search_tokens(&display_map.to_string(), &display_map);
 
-            let selection_buffer = display_map.to_string();
+            let mut uniqueness = GLOBAL_CONFIG.uniqueness.clone();
 
             display_map.map.iter().try_for_each(|(live, snaps)| {
                 if snaps.is_empty() {
@@ -92,21 +128,32 @@ impl TryFrom<&mut InteractiveBrowse> for InteractiveSelect {
             // loop until user selects a valid snapshot version
             loop {
                 // get the file name
-                let selected_line = view_mode.view_buffer(&selection_buffer, MultiSelect::On)?;
+                let (selected_line, view_action) = view_mode.view_buffer_with_action(
+                    &selection_buffer,
+                    MultiSelect::On,
+                    &uniqueness,
+                )?;
 
                 if let Some(background_handle) = interactive_browse.opt_background_handle.take() {
                     let _ = background_handle.join();
                 }
 
+                // the versions are deduplicated again, with the uniqueness chosen, and the user is returned to the same view
+                if let ViewAction::Uniqueness(toggle) = view_action {
+                    uniqueness = toggle.uniqueness();
+
+                    let versions_map = all_versions.with_uniqueness(&display_config, &uniqueness);
+
+                    display_map = VersionsDisplayWrapper::from(&display_config, versions_map);
+                    selection_buffer =
+                        Self::with_search_tokens(&display_map.to_string(), &display_map);
+
+                    continue;
+                }
+
                 let requested_file_names = selected_line
                     .iter()
-                    .filter_map(|selection| {
-                        // ... we want everything between the quotes
-                        selection
-                            .split_once("\"")
-                            .and_then(|(_lhs, rhs)| rhs.rsplit_once("\""))
-                            .map(|(lhs, _rhs)| lhs)
-                    })
+                    .filter_map(|selection| Self::quoted_path(selection))
                     .filter(|selection_buffer| {
                         // and cannot select a 'live' version or other invalid value.
                         display_map
@@ -120,19 +167,107 @@ impl TryFrom<&mut InteractiveBrowse> for InteractiveSelect {
                     continue;
                 }
 
+                // a quick copy returns the user to the same view, to grab another version, or to select
+                if let ViewAction::QuickCopy = view_action {
+                    requested_file_names.iter().for_each(|snap_path_string| {
+                        let snap_pathdata = PathData::from(Path::new(snap_path_string));
+
+                        match InteractiveRestore::quick_copy(
+                            &snap_pathdata,
+                            opt_live_version.as_deref(),
+                        ) {
+                            Ok(new_file_path_buf) => eprintln!(
+                                "httm copied from snapshot: {:?} to: {:?}",
+                                snap_pathdata.path_buf, new_file_path_buf
+                            ),
+                            Err(err) => Diagnostic::warn(
+                                "quick_copy_failed",
+                                format!(
+                                    "Could not copy from snapshot: {:?}: {err}",
+                                    snap_pathdata.path_buf
+                                ),
+                            )
+                            .with_path(&snap_pathdata.path_buf)
+                            .print(),
+                        }
+                    });
+
+                    continue;
+                }
+
                 break requested_file_names;
             }
         };
 
+        if let Some(session) = interactive_browse.opt_session.as_mut() {
+            session.versions = snap_path_strings.clone();
+            session.save()?;
+        }
+
         Ok(Self {
             view_mode,
             snap_path_strings,
             opt_live_version,
+            live_versions,
         })
     }
 }
 
 impl InteractiveSelect {
+    // ... we want everything between the quotes
+    pub fn quoted_path(line: &str) -> Option<&str> {
+        line.split_once('"')
+            .and_then(|(_lhs, rhs)| rhs.rsplit_once('"'))
+            .map(|(lhs, _rhs)| lhs)
+    }
+
+    // the displayed date and size make a poor fuzzy search, so each snapshot version line also carries,
+    // dimmed, its snapshot name, its modify time as an ISO date, and its size in bytes, like
+    // "daily-2024-05-01 2024-05-01 12:00 4096B", such that typing "daily 05-01" finds that version
+    fn with_search_tokens(selection_buffer: &str, display_map: &VersionsDisplayWrapper) -> String {
+        let tokens: HashMap<String, String> = display_map
+            .map
+            .values()
+            .flatten()
+            .map(|snap| {
+                let path_string = snap.path_buf.to_string_lossy().to_string();
+
+                let snap_name = GLOBAL_CONFIG
+                    .dataset_collection
+                    .snap_mount_of(&snap.path_buf)
+                    .map(DatasetInventory::snap_name)
+                    // a quote would confuse our search for the path between the quotes
+                    .map(|snap_name| snap_name.replace('"', "'"));
+
+                let metadata = snap.md_infallible();
+
+                let iso_date = date_string(
+                    GLOBAL_CONFIG.requested_utc_offset,
+                    &metadata.modify_time,
+                    DateFormat::Custom(SEARCH_TOKEN_DATE_FORMAT.to_owned()),
+                );
+
+                let tokens = snap_name
+                    .into_iter()
+                    .chain([iso_date, format!("{}B", metadata.size)])
+                    .collect::<Vec<String>>()
+                    .join(" ");
+
+                (path_string, tokens)
+            })
+            .collect();
+
+        selection_buffer
+            .lines()
+            .map(|line| {
+                match Self::quoted_path(line).and_then(|path_string| tokens.get(path_string)) {
+                    Some(tokens) => format!("{line}  {}\n", DarkGray.paint(tokens)),
+                    None => format!("{line}\n"),
+                }
+            })
+            .collect()
+    }
+
     fn last_snap(map: &VersionsMap) -> Vec<String> {
         map.iter()
             .filter_map(|(key, values)| {
@@ -152,14 +287,117 @@ impl InteractiveSelect {
     }
 
     pub fn print_selections(&self, select_mode: &SelectMode) -> HttmResult<()> {
+        if let Some(shell_dialect) = &GLOBAL_CONFIG.opt_emit_shell {
+            let output_buf: String = self
+                .snap_path_strings
+                .iter()
+                .map(|snap_path_string| {
+                    ShellCommands::for_version(
+                        shell_dialect,
+                        self.opt_live_version.as_deref().map(Path::new),
+                        &PathData::from(Path::new(snap_path_string)),
+                    )
+                })
+                .collect();
+
+            return print_output_buf(&output_buf);
+        }
+
+        if let Some(root) = &GLOBAL_CONFIG.opt_emit_rsync_list {
+            let output_buf = RsyncList::format(
+                root,
+                self.snap_path_strings
+                    .iter()
+                    .map(Path::new),
+            );
+
+            return print_output_buf(&output_buf);
+        }
+
+        // all versions selected upon one line, and no newline, as the shell inserts exactly what is printed
+        if let SelectMode::Widget(shell_dialect) = select_mode {
+            let output_buf = self
+                .snap_path_strings
+                .iter()
+                .map(|snap_path_string| {
+                    ShellCommands::quote(shell_dialect, snap_path_string.as_bytes())
+                })
+                .collect::<Vec<String>>()
+                .join(" ");
+
+            return print_output_buf(&output_buf);
+        }
+
+        if let SelectMode::Json = select_mode {
+            return print_output_buf(&self.to_json()?);
+        }
+
         self.snap_path_strings
             .iter()
             .map(Path::new)
             .try_for_each(|snap_path| self.print_snap_path(snap_path, select_mode))
     }
 
+    // a manifest of the versions selected, for a script to act upon, instead of httm
+    fn to_json(&self) -> HttmResult<String> {
+        let values: Vec<Value> = self
+            .snap_path_strings
+            .iter()
+            .map(|snap_path_string| {
+                let snap_pathdata = PathData::from(Path::new(snap_path_string));
+
+                let opt_live_path = match &self.opt_live_version {
+                    Some(live_version) => Some(PathBuf::from(live_version)),
+                    None => GLOBAL_CONFIG
+                        .dataset_collection
+                        .live_path_of(&snap_pathdata.path_buf),
+                };
+
+                // the live version, with its metadata as observed at selection time, where possible
+                let opt_live_pathdata = opt_live_path.map(|live_path| {
+                    self.live_versions
+                        .iter()
+                        .find(|live_version| live_version.path_buf == live_path)
+                        .cloned()
+                        .unwrap_or_else(|| PathData::from(live_path.as_path()))
+                });
+
+                let opt_snap_name = GLOBAL_CONFIG
+                    .dataset_collection
+                    .snap_mount_of(&snap_pathdata.path_buf)
+                    .map(DatasetInventory::snap_name);
+
+                let opt_note = VERSION_NOTES
+                    .get(&snap_pathdata.path_buf)
+                    .map(|version_note| version_note.note.clone());
+
+                serde_json::json!({
+                    "version": snap_pathdata,
+                    "live": opt_live_pathdata,
+                    "snapshot": opt_snap_name,
+                    "version_id": snap_pathdata.version_id(),
+                    "note": opt_note,
+                })
+            })
+            .collect();
+
+        let json_string = match GLOBAL_CONFIG.print_mode {
+            PrintMode::FormattedNotPretty | PrintMode::RawNewline | PrintMode::RawZero => {
+                serde_json::to_string(&values)?
+            }
+            PrintMode::FormattedDefault => serde_json::to_string_pretty(&values)?,
+        };
+
+        let delimiter = delimiter();
+
+        Ok(format!("{json_string}{delimiter}"))
+    }
+
     fn print_snap_path(&self, snap_path: &Path, select_mode: &SelectMode) -> HttmResult<()> {
         match select_mode {
+            SelectMode::Widget(_) | SelectMode::Json => {
+                unreachable!("Widget and JSON selections are printed together, never per path")
+            }
             SelectMode::Path => {
                 let delimiter = delimiter();
                 let output_buf = match GLOBAL_CONFIG.print_mode {
@@ -182,6 +420,12 @@ impl InteractiveSelect {
                 let mut contents = Vec::new();
                 f.read_to_end(&mut contents)?;
 
+                // text in another encoding, like UTF-16, is transcoded to UTF-8 for display
+                match TextEncoding::requested_or_detected(&contents) {
+                    None | Some(TextEncoding::Utf8) => {}
+                    Some(encoding) => return print_output_buf(&encoding.decode(&contents)),
+                }
+
                 // SAFETY: Panic here is not the end of the world as we are just printing the bytes.
                 // This is the same as simply `cat`-ing the file.
                 let output_buf = unsafe { std::str::from_utf8_unchecked(&contents) };
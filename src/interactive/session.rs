@@ -0,0 +1,114 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::library::results::{HttmError, HttmResult};
+use crate::library::utility::make_tmp_path;
+use serde_json::Value;
+use std::fs::{create_dir_all, read_to_string, rename, write};
+use std::path::PathBuf;
+
+const SESSION_DIRECTORY: &str = ".httm_sessions";
+const SESSION_EXTENSION: &str = "json";
+
+// a session persists the interactive selection, both the files and the versions chosen,
+// between runs, so a long triage effort may survive a terminal restart
+#[derive(Debug, Default)]
+pub struct InteractiveSession {
+    session_path: PathBuf,
+    pub files: Vec<PathBuf>,
+    pub versions: Vec<String>,
+}
+
+impl InteractiveSession {
+    // opens an existing session, or starts a new and empty session of that name
+    pub fn open(name: &str) -> HttmResult<Self> {
+        if name.is_empty() || name.contains('/') || name.starts_with('.') {
+            let msg = format!(
+                "Session name is invalid: {name:?}.  A session name must not be empty, contain a '/', or begin with a '.'."
+            );
+            return Err(HttmError::new(&msg).into());
+        }
+
+        let session_path = Self::session_dir()?.join(format!("{name}.{SESSION_EXTENSION}"));
+
+        if !session_path.exists() {
+            return Ok(Self {
+                session_path,
+                ..Default::default()
+            });
+        }
+
+        let contents = read_to_string(&session_path)?;
+
+        let value: Value = serde_json::from_str(&contents).map_err(|err| {
+            let msg = format!("Session file {session_path:?} could not be parsed: {err}");
+            HttmError::new(&msg)
+        })?;
+
+        let strings = |key: &str| -> Vec<String> {
+            value
+                .get(key)
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+                .map(str::to_owned)
+                .collect()
+        };
+
+        let files = strings("files").into_iter().map(PathBuf::from).collect();
+        let versions = strings("versions");
+
+        Ok(Self {
+            session_path,
+            files,
+            versions,
+        })
+    }
+
+    pub fn save(&self) -> HttmResult<()> {
+        if let Some(parent) = self.session_path.parent() {
+            create_dir_all(parent)?;
+        }
+
+        let value = serde_json::json!({
+            "files": self.files,
+            "versions": self.versions,
+        });
+
+        // write to a tmp file then rename, so an interrupted save never leaves a truncated session
+        let tmp_path = make_tmp_path(&self.session_path);
+        write(&tmp_path, serde_json::to_string_pretty(&value)?)?;
+        rename(&tmp_path, &self.session_path)?;
+
+        Ok(())
+    }
+
+    fn session_dir() -> HttmResult<PathBuf> {
+        if let Some(session_dir) = std::env::var_os("HTTM_SESSION_DIR") {
+            return Ok(PathBuf::from(session_dir));
+        }
+
+        match std::env::var_os("HOME") {
+            Some(home) => Ok(PathBuf::from(home).join(SESSION_DIRECTORY)),
+            None => Err(HttmError::new(
+                "$HOME does not appear to be set in your environment, and HTTM_SESSION_DIR is not set",
+            )
+            .into()),
+        }
+    }
+}
@@ -0,0 +1,261 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::data::paths::PathData;
+use crate::display_map::protection::ProtectionReport;
+use crate::library::results::{HttmError, HttmResult};
+use crate::library::signals::{SignalRequest, Signals};
+use crate::library::utility::{date_string, display_human_size};
+use crate::lookup::versions::Versions;
+use crate::parse::mount_watch::MountWatch;
+use crate::GLOBAL_CONFIG;
+use rayon::prelude::*;
+use std::fs::read_dir;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+use tuikit::prelude::*;
+
+const REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+// title, column headers, and key help
+const NUM_HEADER_ROWS: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Protection {
+    // the live version is the same as the version upon the latest snapshot
+    Protected,
+    // the live version has been modified since the latest snapshot version
+    Modified,
+    // no snapshot version exists at all
+    Unprotected,
+}
+
+impl Protection {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Protection::Protected => "PROTECTED",
+            Protection::Modified => "MODIFIED",
+            Protection::Unprotected => "UNPROTECTED",
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            Protection::Protected => Color::GREEN,
+            Protection::Modified => Color::YELLOW,
+            Protection::Unprotected => Color::RED,
+        }
+    }
+}
+
+struct TopRow {
+    file_name: String,
+    modify_time: SystemTime,
+    size: u64,
+    num_versions: usize,
+    opt_last_snap_time: Option<SystemTime>,
+    protection: Protection,
+}
+
+impl TopRow {
+    fn new(pathdata: PathData) -> Self {
+        let file_name = pathdata
+            .path_buf
+            .file_name()
+            .map(|file_name| file_name.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        let live_metadata = pathdata.md_infallible();
+
+        let snap_versions = Versions::new(&pathdata, &GLOBAL_CONFIG)
+            .map(|versions| versions.destructure().1)
+            .unwrap_or_default();
+
+        let opt_latest = snap_versions.last();
+
+        let opt_last_snap_time = opt_latest.and_then(|latest| {
+            GLOBAL_CONFIG
+                .dataset_collection
+                .snap_mount_of(&latest.path_buf)
+                .and_then(|snap_mount| GLOBAL_CONFIG.dataset_collection.snap_mount_time(snap_mount))
+        });
+
+        let protection = match opt_latest {
+            None => Protection::Unprotected,
+            Some(latest) if latest.md_infallible() == live_metadata => Protection::Protected,
+            Some(_) => Protection::Modified,
+        };
+
+        Self {
+            file_name,
+            modify_time: live_metadata.modify_time,
+            size: live_metadata.size,
+            num_versions: snap_versions.len(),
+            opt_last_snap_time,
+            protection,
+        }
+    }
+}
+
+// a full screen dashboard of the most recently modified files within a directory, and whether
+// each is protected by a snapshot, refreshed until the user quits
+pub struct TopDashboard {
+    requested_dir: PathBuf,
+}
+
+impl TopDashboard {
+    pub fn exec() -> HttmResult<()> {
+        let requested_dir = match GLOBAL_CONFIG.paths.as_slice() {
+            [pathdata] if pathdata.path_buf.is_dir() => pathdata.path_buf.clone(),
+            [pathdata] => {
+                return Err(HttmError::new("TOP requires a directory to watch.")
+                    .with_code("not_a_directory")
+                    .with_path(&pathdata.path_buf)
+                    .into())
+            }
+            _ => return Err(HttmError::new("TOP may only watch one directory at a time.").into()),
+        };
+
+        let top_dashboard = Self { requested_dir };
+
+        top_dashboard.run()
+    }
+
+    fn run(&self) -> HttmResult<()> {
+        Signals::install();
+
+        match self.watch()? {
+            Some(request) => Signals::restart(request),
+            None => Ok(()),
+        }
+    }
+
+    // returns any signal received, only after the terminal has been restored, by dropping the term
+    fn watch(&self) -> HttmResult<Option<SignalRequest>> {
+        let term: Term<()> = Term::new()?;
+
+        let mut opt_mount_watch = MountWatch::new();
+
+        loop {
+            if let Some(request) = Signals::take() {
+                return Ok(Some(request));
+            }
+
+            // a pool imported or exported is answered just as a SIGHUP, by re-enumerating datasets
+            if opt_mount_watch
+                .as_mut()
+                .is_some_and(|mount_watch| mount_watch.datasets_changed())
+            {
+                return Ok(Some(SignalRequest::Reload));
+            }
+
+            self.draw(&term)?;
+
+            // a timeout, or any key but a quit key, simply refreshes the dashboard
+            match term.peek_event(REFRESH_INTERVAL) {
+                Ok(Event::Key(Key::ESC | Key::Char('q') | Key::Ctrl('c'))) => return Ok(None),
+                _ => continue,
+            }
+        }
+    }
+
+    fn draw(&self, term: &Term<()>) -> HttmResult<()> {
+        let (width, height) = term.term_size()?;
+        let num_rows = height.saturating_sub(NUM_HEADER_ROWS);
+
+        let rows = self.recently_modified(num_rows)?;
+
+        term.clear()?;
+
+        let title = format!(
+            "httm top: {:?}, refreshed {}",
+            self.requested_dir,
+            Self::display_time(&SystemTime::now())
+        );
+        term.print_with_attr(0, 0, &Self::truncate(&title, width), Effect::BOLD)?;
+
+        let header = format!(
+            "{:<12} {:<26} {:>10} {:>8} {:<18} {}",
+            "STATUS", "MODIFIED", "SIZE", "VERSIONS", "LAST SNAPSHOT", "NAME"
+        );
+        term.print_with_attr(1, 0, &Self::truncate(&header, width), Effect::UNDERLINE)?;
+
+        rows.iter().enumerate().try_for_each(|(idx, row)| {
+            let last_snap = row
+                .opt_last_snap_time
+                .as_ref()
+                .map(ProtectionReport::display_age)
+                .unwrap_or_else(|| "never".to_owned());
+
+            let line = format!(
+                "{:<12} {:<26} {:>10} {:>8} {:<18} {}",
+                row.protection.as_str(),
+                Self::display_time(&row.modify_time),
+                display_human_size(row.size),
+                row.num_versions,
+                last_snap,
+                row.file_name
+            );
+
+            term.print(idx + 2, 0, &Self::truncate(&line, width))?;
+            term.print_with_attr(idx + 2, 0, row.protection.as_str(), row.protection.color())?;
+
+            HttmResult::Ok(())
+        })?;
+
+        term.print_with_attr(
+            height.saturating_sub(1),
+            0,
+            &Self::truncate("q/esc: quit | any other key: refresh", width),
+            Effect::DIM,
+        )?;
+
+        term.present()?;
+
+        Ok(())
+    }
+
+    // only those files which fit upon the screen are searched for versions, most recently modified first
+    fn recently_modified(&self, num_rows: usize) -> HttmResult<Vec<TopRow>> {
+        let mut files: Vec<PathData> = read_dir(&self.requested_dir)?
+            .flatten()
+            .filter(|dir_entry| {
+                dir_entry
+                    .file_type()
+                    .map(|file_type| !file_type.is_dir())
+                    .unwrap_or(false)
+            })
+            .map(|dir_entry| PathData::from(dir_entry.path()))
+            .collect();
+
+        files.sort_by_key(|pathdata| std::cmp::Reverse(pathdata.md_infallible().modify_time));
+        files.truncate(num_rows);
+
+        Ok(files.into_par_iter().map(TopRow::new).collect())
+    }
+
+    fn display_time(time: &SystemTime) -> String {
+        date_string(
+            GLOBAL_CONFIG.requested_utc_offset,
+            time,
+            GLOBAL_CONFIG.display_date_format(),
+        )
+    }
+
+    fn truncate(line: &str, width: usize) -> String {
+        line.chars().take(width).collect()
+    }
+}
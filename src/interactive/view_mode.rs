@@ -15,7 +15,11 @@
 // For the full copyright and license information, please view the LICENSE file
 // that was distributed with this source code.
 
-use crate::interactive::preview::PreviewSelection;
+use crate::config::generate::ListSnapsOfType;
+use crate::interactive::compare::{CompareVersions, COMPARE_KEY, PIN_BASE_KEY};
+use crate::interactive::key_bindings::{KeyAction, KeyBinding, KeyBindings, HELP_KEY};
+use crate::interactive::preview::{PreviewSelection, PreviewWindow};
+use crate::library::i18n::MESSAGES;
 use crate::library::results::HttmError;
 use crate::HttmResult;
 use crate::GLOBAL_CONFIG;
@@ -34,36 +38,162 @@ pub enum MultiSelect {
     Off,
 }
 
+pub const QUICK_COPY_KEY: &str = "alt-r";
+pub const ALL_VERSIONS_KEY: &str = "alt-a";
+pub const UNIQUE_METADATA_KEY: &str = "alt-m";
+pub const UNIQUE_CONTENTS_KEY: &str = "alt-u";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ViewAction {
+    Select,
+    QuickCopy,
+    Uniqueness(UniquenessToggle),
+}
+
+// the classes of uniqueness which may be toggled between within the select view, without restarting httm
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UniquenessToggle {
+    All,
+    Metadata,
+    Contents,
+}
+
+impl UniquenessToggle {
+    const ALL: [UniquenessToggle; 3] = [
+        UniquenessToggle::All,
+        UniquenessToggle::Metadata,
+        UniquenessToggle::Contents,
+    ];
+
+    fn key(&self) -> &'static str {
+        match self {
+            UniquenessToggle::All => ALL_VERSIONS_KEY,
+            UniquenessToggle::Metadata => UNIQUE_METADATA_KEY,
+            UniquenessToggle::Contents => UNIQUE_CONTENTS_KEY,
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        match self {
+            UniquenessToggle::All => "display all versions, even those which are the same",
+            UniquenessToggle::Metadata => "display only versions unique by metadata",
+            UniquenessToggle::Contents => "display only versions unique by contents",
+        }
+    }
+
+    pub fn uniqueness(&self) -> ListSnapsOfType {
+        match self {
+            UniquenessToggle::All => ListSnapsOfType::All,
+            UniquenessToggle::Metadata => ListSnapsOfType::UniqueMetadata,
+            UniquenessToggle::Contents => ListSnapsOfType::UniqueContents,
+        }
+    }
+
+    fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|toggle| toggle.key() == key)
+    }
+}
+
 impl ViewMode {
     pub fn print_header(&self) -> String {
+        let compare_keys = match self {
+            ViewMode::Select(_) => format!(
+                "PIN BASE:   {PIN_BASE_KEY}    | COMPARE:      {COMPARE_KEY}      | QUICK COPY:       {QUICK_COPY_KEY}\n\
+                ALL:        {}    | BY METADATA:  {}      | BY CONTENTS:      {}\n",
+                UniquenessToggle::All.key(),
+                UniquenessToggle::Metadata.key(),
+                UniquenessToggle::Contents.key(),
+            ),
+            _ => String::new(),
+        };
+
         format!(
             "PREVIEW UP: shift+up | PREVIEW DOWN: shift+down | {}\n\
-        PAGE UP:    page up  | PAGE DOWN:    page down \n\
+        PAGE UP:    page up  | PAGE DOWN:    page down  | HELP:             {HELP_KEY}\n\
+        {}\
         EXIT:       esc      | SELECT:       enter      | SELECT, MULTIPLE: shift+tab\n\
         ──────────────────────────────────────────────────────────────────────────────",
-            self.print_mode()
+            self.print_mode(),
+            compare_keys
         )
     }
 
-    fn print_mode(&self) -> &str {
+    pub fn name(&self) -> &str {
         match self {
-            ViewMode::Browse => "====> [ Browse Mode ] <====",
-            ViewMode::Select(_) => "====> [ Select Mode ] <====",
-            ViewMode::Restore => "====> [ Restore Mode ] <====",
-            ViewMode::Prune => "====> [ Prune Mode ] <====",
+            ViewMode::Browse => "browse",
+            ViewMode::Select(_) => "select",
+            ViewMode::Restore => "restore",
+            ViewMode::Prune => "prune",
         }
     }
 
+    fn print_mode(&self) -> String {
+        let id = match self {
+            ViewMode::Browse => "mode-browse",
+            ViewMode::Select(_) => "mode-select",
+            ViewMode::Restore => "mode-restore",
+            ViewMode::Prune => "mode-prune",
+        };
+
+        format!("====> [ {} ] <====", MESSAGES.get(id))
+    }
+
     pub fn view_buffer(&self, buffer: &str, opt_multi: MultiSelect) -> HttmResult<Vec<String>> {
+        self.view_buffer_with_action(buffer, opt_multi, &GLOBAL_CONFIG.uniqueness)
+            .map(|(selection, _view_action)| selection)
+    }
+
+    // like view_buffer, but also reports whether the user chose a quick action, instead of an ordinary selection,
+    // given the uniqueness of the versions in the buffer, which may differ from that requested on the command line
+    pub fn view_buffer_with_action(
+        &self,
+        buffer: &str,
+        opt_multi: MultiSelect,
+        uniqueness: &ListSnapsOfType,
+    ) -> HttmResult<(Vec<String>, ViewAction)> {
         let preview_selection = PreviewSelection::new(&self)?;
 
         let header = self.print_header();
 
+        // only versions may be compared, and the script lives only as long as the view
+        let opt_compare_versions = match self {
+            ViewMode::Select(_) => Some(CompareVersions::new()?),
+            _ => None,
+        };
+
+        let mut extra: Vec<KeyBinding> = opt_compare_versions
+            .as_ref()
+            .map(|compare_versions| compare_versions.bindings())
+            .unwrap_or_default();
+
+        if preview_selection.opt_preview_command.is_some() {
+            extra.extend(PreviewWindow::bindings());
+        }
+
+        // the quick copy key accepts the highlighted version, like enter, but is reported distinctly
+        if matches!(self, ViewMode::Select(_)) {
+            extra.push(KeyBinding::new(
+                QUICK_COPY_KEY,
+                "restore a copy of the highlighted version beside its live version",
+                KeyAction::Expect,
+            ));
+
+            // each uniqueness key also accepts, so the versions may be looked up again, and redisplayed
+            extra.extend(UniquenessToggle::ALL.iter().map(|toggle| {
+                KeyBinding::new(toggle.key(), toggle.description(), KeyAction::Expect)
+            }));
+        }
+
+        let key_bindings = KeyBindings::new(self, extra, uniqueness)?;
+        let bindings = key_bindings.bindings();
+
         let opt_multi = match opt_multi {
             MultiSelect::On => true,
             MultiSelect::Off => false,
         };
 
+        let opt_expect = key_bindings.opt_expect();
+
         // build our browse view - less to do than before - no previews, looking through one 'lil buffer
         let skim_opts = SkimOptionsBuilder::default()
             .preview_window(preview_selection.opt_preview_window.as_deref())
@@ -77,6 +207,8 @@ impl ViewMode {
             .regex(false)
             .tiebreak(Some("length,index".to_string()))
             .header(Some(&header))
+            .bind(bindings.iter().map(String::as_str).collect())
+            .expect(opt_expect)
             .build()
             .expect("Could not initialized skim options for select_restore_view");
 
@@ -89,16 +221,29 @@ impl ViewMode {
         // run_with() reads and shows items from the thread stream created above
         let res = match skim::Skim::run_with(&skim_opts, Some(items)) {
             Some(output) if output.is_abort => {
-                eprintln!("httm select/restore/prune session was aborted.  Quitting.");
+                eprintln!("{}", MESSAGES.get("session-aborted"));
                 std::process::exit(0);
             }
-            Some(output) => output
-                .selected_items
-                .iter()
-                .map(|i| i.output().into_owned())
-                .collect(),
+            Some(output) => {
+                let view_action = match &output.final_event {
+                    Event::EvActAccept(Some(key)) if key == QUICK_COPY_KEY => ViewAction::QuickCopy,
+                    Event::EvActAccept(Some(key)) => match UniquenessToggle::from_key(key) {
+                        Some(toggle) => ViewAction::Uniqueness(toggle),
+                        None => ViewAction::Select,
+                    },
+                    _ => ViewAction::Select,
+                };
+
+                let selection = output
+                    .selected_items
+                    .iter()
+                    .map(|i| i.output().into_owned())
+                    .collect();
+
+                (selection, view_action)
+            }
             None => {
-                return Err(HttmError::new("httm select/restore/prune session failed.").into());
+                return Err(HttmError::new(&MESSAGES.get("session-failed")).into());
             }
         };
 
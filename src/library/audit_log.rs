@@ -0,0 +1,137 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::library::diagnostics::Diagnostic;
+use crate::library::results::{HttmError, HttmResult};
+use crate::GLOBAL_CONFIG;
+use nix::fcntl::{Flock, FlockArg};
+use serde_json::json;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::Path;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditAction {
+    Restore,
+    Snapshot,
+    Destroy,
+    Rollback,
+}
+
+impl AuditAction {
+    fn as_str(&self) -> &'static str {
+        match self {
+            AuditAction::Restore => "restore",
+            AuditAction::Snapshot => "snapshot",
+            AuditAction::Destroy => "destroy",
+            AuditAction::Rollback => "rollback",
+        }
+    }
+}
+
+// an append-only log, one JSON object per line, of every action by which httm modifies a filesystem,
+// whether that action succeeded or not.  the log is only ever opened for append, and each record is
+// written whole, under an exclusive lock, so concurrent httm processes never interleave records
+pub struct AuditLog;
+
+impl AuditLog {
+    // called when the config is parsed, so an unwritable log refuses every action, instead of none
+    pub fn check(path: &Path) -> HttmResult<()> {
+        Self::open(path).map(|_file| ())
+    }
+
+    pub fn record<'a>(
+        action: AuditAction,
+        paths: impl IntoIterator<Item = &'a Path>,
+        snapshots: impl IntoIterator<Item = &'a str>,
+        res: &HttmResult<()>,
+    ) {
+        let Some(log_path) = &GLOBAL_CONFIG.opt_audit_log else {
+            return;
+        };
+
+        let uid = nix::unistd::getuid();
+
+        let opt_user_name = nix::unistd::User::from_uid(uid)
+            .ok()
+            .flatten()
+            .map(|user| user.name);
+
+        let timestamp = OffsetDateTime::now_utc()
+            .format(&Rfc3339)
+            .unwrap_or_default();
+
+        let record = json!({
+            "timestamp": timestamp,
+            "user": opt_user_name,
+            "uid": uid.as_raw(),
+            "pid": std::process::id(),
+            "action": action.as_str(),
+            "paths": paths
+                .into_iter()
+                .map(|path| path.to_string_lossy())
+                .collect::<Vec<_>>(),
+            "snapshots": snapshots.into_iter().collect::<Vec<_>>(),
+            "success": res.is_ok(),
+            "error": res.as_ref().err().map(|err| err.to_string()),
+        });
+
+        if let Err(err) = Self::append(log_path, &format!("{record}\n")) {
+            Diagnostic::warn(
+                "audit_log_failed",
+                format!(
+                    "httm could not write to the audit log at {:?}: {}",
+                    log_path, err
+                ),
+            )
+            .with_path(log_path)
+            .print();
+        }
+    }
+
+    fn append(path: &Path, line: &str) -> HttmResult<()> {
+        let file = Self::open(path)?;
+
+        let mut locked = Flock::lock(file, FlockArg::LockExclusive).map_err(|(_, errno)| {
+            let msg = format!("Could not obtain the audit log lock: {errno}");
+            HttmError::new(&msg)
+        })?;
+
+        locked.write_all(line.as_bytes())?;
+        locked.sync_data()?;
+
+        Ok(())
+    }
+
+    fn open(path: &Path) -> HttmResult<File> {
+        OpenOptions::new()
+            .append(true)
+            .create(true)
+            .mode(0o600)
+            .open(path)
+            .map_err(|err| {
+                let msg = format!("httm could not open the audit log at {:?}: {}", path, err);
+                HttmError::new(&msg)
+                    .with_code("audit_log_failed")
+                    .with_path(path)
+                    .into()
+            })
+    }
+}
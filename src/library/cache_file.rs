@@ -0,0 +1,75 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use std::fs::{create_dir_all, read, rename, write};
+use std::path::Path;
+
+// [magic][format version][zstd frame], any file without the magic is read as written by an older httm
+const COMPRESSED_MAGIC: &[u8; 4] = b"HTZ\0";
+const COMPRESSED_FORMAT_VERSION: u8 = 1;
+const HEADER_LEN: usize = COMPRESSED_MAGIC.len() + 1;
+// a small cache is cheaper to read uncompressed, and remains easy to inspect
+const COMPRESSION_THRESHOLD: usize = 65_536;
+const COMPRESSION_LEVEL: i32 = 3;
+
+// caches are rewritten whenever they change, so a cache written by an older httm, uncompressed,
+// is read as is, and is migrated, compressed, the next time it is saved
+pub struct CacheFile;
+
+impl CacheFile {
+    // a cache is only an optimization, so a cache which cannot be read is simply None
+    pub fn read(path: &Path) -> Option<Vec<u8>> {
+        let contents = read(path).ok()?;
+
+        let Some(header) = contents.get(..HEADER_LEN) else {
+            return Some(contents);
+        };
+
+        if &header[..COMPRESSED_MAGIC.len()] != COMPRESSED_MAGIC {
+            return Some(contents);
+        }
+
+        // a cache written by a newer httm, in a format this httm does not know, is ignored
+        if header[COMPRESSED_MAGIC.len()] != COMPRESSED_FORMAT_VERSION {
+            return None;
+        }
+
+        zstd::decode_all(&contents[HEADER_LEN..]).ok()
+    }
+
+    pub fn write(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+        if let Some(parent) = path.parent() {
+            create_dir_all(parent)?;
+        }
+
+        let buffer = if contents.len() > COMPRESSION_THRESHOLD {
+            let mut buffer = Vec::with_capacity(contents.len() / 4);
+            buffer.extend_from_slice(COMPRESSED_MAGIC);
+            buffer.push(COMPRESSED_FORMAT_VERSION);
+            buffer.extend(zstd::encode_all(contents, COMPRESSION_LEVEL)?);
+            buffer
+        } else {
+            contents.to_vec()
+        };
+
+        // write to a tmp file then rename, so a concurrent httm never reads a truncated cache
+        let tmp_path = path.with_extension(format!("tmp.{}", std::process::id()));
+
+        write(&tmp_path, buffer)?;
+        rename(&tmp_path, path)
+    }
+}
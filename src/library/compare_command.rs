@@ -0,0 +1,130 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+use crate::data::paths::PathData;
+use crate::library::diagnostics::Diagnostic;
+use crate::library::results::{HttmError, HttmResult};
+use rayon::prelude::*;
+use std::path::{Path, PathBuf};
+use std::process::{Command as ExecProcess, Stdio};
+use std::sync::Once;
+use which::which;
+
+// an external program, like "cmp -s", or a domain specific tool which compares JSON or SQLite files logically,
+// is asked whether two versions are the same, as the program is given the paths of both, and, like 'cmp'
+// or 'diff', it exits 0 when they are the same, and 1 when they differ
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompareCommand {
+    program: PathBuf,
+    args: Vec<String>,
+}
+
+impl CompareCommand {
+    pub fn new(value: &str) -> HttmResult<Self> {
+        let mut words = value.split_whitespace();
+
+        let Some(program_name) = words.next() else {
+            return Err(HttmError::new(
+                "Uniqueness command is empty.  Specify a program, like \"cmd:cmp -s\".",
+            )
+            .into());
+        };
+
+        let Ok(program) = which(program_name) else {
+            let msg = format!(
+                "Uniqueness command {program_name:?} could not be found in the user's PATH, nor as a path to an executable."
+            );
+            return Err(HttmError::new(&msg).into());
+        };
+
+        Ok(Self {
+            program,
+            args: words.map(str::to_owned).collect(),
+        })
+    }
+
+    pub fn display(&self) -> String {
+        std::iter::once(self.program.to_string_lossy().into_owned())
+            .chain(self.args.iter().cloned())
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+
+    // each version is compared only to the version just before it, in the order given, so a version is
+    // kept whenever it differs from its predecessor, and every pair may be compared at once
+    pub fn dedup(&self, vec: Vec<PathData>) -> Vec<PathData> {
+        let is_same_as_prev: Vec<bool> = std::iter::once(false)
+            .chain(
+                vec.par_windows(2)
+                    .map(|pair| self.is_same(&pair[0], &pair[1]))
+                    .collect::<Vec<bool>>(),
+            )
+            .collect();
+
+        vec.into_iter()
+            .zip(is_same_as_prev)
+            .filter_map(|(pd, is_same)| (!is_same).then_some(pd))
+            .collect()
+    }
+
+    fn is_same(&self, prev: &PathData, next: &PathData) -> bool {
+        // versions with the same size and modify time are the same version, as ever, without asking
+        if prev.metadata.is_some() && prev.metadata == next.metadata {
+            return true;
+        }
+
+        match self.exec(&prev.path_buf, &next.path_buf) {
+            Ok(Some(0)) => true,
+            Ok(Some(1)) => false,
+            res => {
+                // a program which fails is not an answer, so both versions are kept
+                static WARN_ONCE: Once = Once::new();
+
+                WARN_ONCE.call_once(|| {
+                    let reason = match res {
+                        Ok(Some(code)) => format!("exited with status {code}"),
+                        Ok(None) => "was terminated by a signal".to_owned(),
+                        Err(err) => err.to_string(),
+                    };
+
+                    Diagnostic::warn(
+                        "uniqueness_cmd_failed",
+                        format!(
+                            "Uniqueness command {:?} {}, and should exit 0, when versions are the same, or 1, when they differ.  Versions it cannot compare are all displayed.",
+                            self.display(),
+                            reason
+                        ),
+                    )
+                    .print();
+                });
+
+                false
+            }
+        }
+    }
+
+    fn exec(&self, prev: &Path, next: &Path) -> std::io::Result<Option<i32>> {
+        ExecProcess::new(&self.program)
+            .args(&self.args)
+            .arg(prev)
+            .arg(next)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .map(|status| status.code())
+    }
+}
@@ -0,0 +1,158 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+use crate::library::results::{HttmError, HttmResult};
+use std::io::{BufRead, Read, Write};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RangeUnit {
+    Bytes,
+    Lines,
+}
+
+impl RangeUnit {
+    fn as_str(&self) -> &'static str {
+        match self {
+            RangeUnit::Bytes => "bytes",
+            RangeUnit::Lines => "lines",
+        }
+    }
+}
+
+// a part of a file's contents, given as "lines:START-END", where lines are numbered from 1, as in an editor,
+// or "bytes:START-END", where bytes are numbered from 0, as offsets.  Both ends are inclusive, and
+// an END left blank, as in "lines:100-", means the rest of the file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContentRange {
+    pub unit: RangeUnit,
+    // internally, both lines and bytes are numbered from 0
+    start: u64,
+    opt_end: Option<u64>,
+}
+
+impl ContentRange {
+    pub fn new(value: &str) -> HttmResult<Self> {
+        let invalid = || {
+            let msg = format!(
+                "RANGE must be specified as \"lines:START-END\" or \"bytes:START-END\", where END may be omitted to mean the end of the file, not: {value:?}"
+            );
+            HttmError::new(&msg).with_code("invalid_range")
+        };
+
+        let (unit, span) = match value.trim().split_once(':') {
+            Some(("lines" | "line", span)) => (RangeUnit::Lines, span),
+            Some(("bytes" | "byte", span)) => (RangeUnit::Bytes, span),
+            _ => return Err(invalid().into()),
+        };
+
+        let Some((start, end)) = span.split_once('-') else {
+            return Err(invalid().into());
+        };
+
+        let Ok(start) = start.trim().parse::<u64>() else {
+            return Err(invalid().into());
+        };
+
+        let opt_end = match end.trim() {
+            "" => None,
+            end => Some(end.parse::<u64>().map_err(|_| invalid())?),
+        };
+
+        let (start, opt_end) = match unit {
+            RangeUnit::Lines if start == 0 || opt_end == Some(0) => {
+                return Err(HttmError::new("RANGE lines are numbered from 1.")
+                    .with_code("invalid_range")
+                    .into())
+            }
+            RangeUnit::Lines => (start - 1, opt_end.map(|end| end - 1)),
+            RangeUnit::Bytes => (start, opt_end),
+        };
+
+        if opt_end.is_some_and(|end| end < start) {
+            return Err(HttmError::new("RANGE must not end before it begins.")
+                .with_code("invalid_range")
+                .into());
+        }
+
+        Ok(Self {
+            unit,
+            start,
+            opt_end,
+        })
+    }
+
+    // copy the contents before, within, and after the range, each to its own writer, so a file
+    // of any size may be streamed, returning the number of units before, and within, the range
+    pub fn partition<R: BufRead>(
+        &self,
+        reader: &mut R,
+        before: &mut dyn Write,
+        within: &mut dyn Write,
+        after: &mut dyn Write,
+    ) -> HttmResult<(u64, u64)> {
+        match self.unit {
+            RangeUnit::Bytes => {
+                let num_before = std::io::copy(&mut reader.take(self.start), before)?;
+
+                let num_within = match self.opt_end {
+                    Some(end) => std::io::copy(&mut reader.take(end - self.start + 1), within)?,
+                    None => std::io::copy(reader, within)?,
+                };
+
+                std::io::copy(reader, after)?;
+
+                Ok((num_before, num_within))
+            }
+            RangeUnit::Lines => {
+                let mut line = Vec::new();
+                let mut idx = 0u64;
+                let mut num_within = 0u64;
+
+                while reader.read_until(b'\n', &mut line)? > 0 {
+                    if idx < self.start {
+                        before.write_all(&line)?;
+                    } else if self.opt_end.is_none_or(|end| idx <= end) {
+                        within.write_all(&line)?;
+                        num_within += 1;
+                    } else {
+                        after.write_all(&line)?;
+                    }
+
+                    idx += 1;
+                    line.clear();
+                }
+
+                Ok((idx.min(self.start), num_within))
+            }
+        }
+    }
+
+    pub fn begins_after(&self, num_before: u64) -> bool {
+        num_before < self.start
+    }
+
+    pub fn describe(&self) -> String {
+        let (start, opt_end) = match self.unit {
+            RangeUnit::Lines => (self.start + 1, self.opt_end.map(|end| end + 1)),
+            RangeUnit::Bytes => (self.start, self.opt_end),
+        };
+
+        match opt_end {
+            Some(end) => format!("{} {}-{}", self.unit.as_str(), start, end),
+            None => format!("{} {}-", self.unit.as_str(), start),
+        }
+    }
+}
@@ -0,0 +1,157 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+use crate::library::i18n::MESSAGES;
+use crate::library::redact::Redaction;
+use crate::library::results::HttmError;
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+use std::borrow::Cow;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// set once, when the config is parsed, but read by diagnostics emitted while the config
+// is still being generated, so this cannot be a member of the global config
+static JSON_DIAGNOSTICS: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    Error,
+    Warning,
+    Notice,
+}
+
+impl Level {
+    fn prefix(&self) -> String {
+        let id = match self {
+            Level::Error => "diagnostic-error",
+            Level::Warning => "diagnostic-warning",
+            Level::Notice => "diagnostic-notice",
+        };
+
+        format!("{}: ", MESSAGES.get(id))
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Level::Error => "error",
+            Level::Warning => "warning",
+            Level::Notice => "notice",
+        }
+    }
+}
+
+// an error, warning or notice printed to stderr, either as prose, or as a single line of JSON,
+// so wrappers may present meaningful messages without parsing English sentences
+#[derive(Debug)]
+pub struct Diagnostic<'a> {
+    level: Level,
+    code: &'static str,
+    message: Cow<'a, str>,
+    opt_path: Option<&'a Path>,
+    opt_dataset: Option<&'a Path>,
+}
+
+impl<'a> Serialize for Diagnostic<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("Diagnostic", 5)?;
+
+        state.serialize_field("level", self.level.name())?;
+        state.serialize_field("code", self.code)?;
+        state.serialize_field("message", self.message.trim())?;
+        state.serialize_field("path", &self.opt_path)?;
+        state.serialize_field("dataset", &self.opt_dataset)?;
+        state.end()
+    }
+}
+
+impl<'a> Diagnostic<'a> {
+    pub fn enable_json() {
+        JSON_DIAGNOSTICS.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_json() -> bool {
+        JSON_DIAGNOSTICS.load(Ordering::Relaxed)
+    }
+
+    pub fn new(level: Level, code: &'static str, message: impl Into<Cow<'a, str>>) -> Self {
+        Self {
+            level,
+            code,
+            message: message.into(),
+            opt_path: None,
+            opt_dataset: None,
+        }
+    }
+
+    pub fn warn(code: &'static str, message: impl Into<Cow<'a, str>>) -> Self {
+        Self::new(Level::Warning, code, message)
+    }
+
+    pub fn notice(code: &'static str, message: impl Into<Cow<'a, str>>) -> Self {
+        Self::new(Level::Notice, code, message)
+    }
+
+    pub fn with_path(mut self, path: &'a Path) -> Self {
+        self.opt_path = Some(path);
+        self
+    }
+
+    pub fn with_dataset(mut self, dataset: &'a Path) -> Self {
+        self.opt_dataset = Some(dataset);
+        self
+    }
+
+    pub fn from_error(error: &'a (dyn std::error::Error + Send + Sync + 'static)) -> Self {
+        if let Some(httm_error) = error.downcast_ref::<HttmError>() {
+            return Self {
+                level: Level::Error,
+                code: httm_error.opt_code.unwrap_or("error"),
+                message: Cow::Borrowed(&httm_error.details),
+                opt_path: httm_error.opt_path.as_deref(),
+                opt_dataset: httm_error.opt_dataset.as_deref(),
+            };
+        }
+
+        let code = match error.downcast_ref::<std::io::Error>().map(|err| err.kind()) {
+            Some(ErrorKind::NotFound) => "not_found",
+            Some(ErrorKind::PermissionDenied) => "permission_denied",
+            Some(ErrorKind::AlreadyExists) => "already_exists",
+            Some(_) => "io_error",
+            None => "error",
+        };
+
+        Self::new(Level::Error, code, error.to_string())
+    }
+
+    pub fn print(&self) {
+        let message = Redaction::apply(&self.message);
+
+        if !Self::is_json() {
+            eprintln!("{}{}", self.level.prefix(), message);
+            return;
+        }
+
+        match serde_json::to_string(self) {
+            Ok(json) => eprintln!("{}", Redaction::apply(&json)),
+            Err(_err) => eprintln!("{}{}", self.level.prefix(), message),
+        }
+    }
+}
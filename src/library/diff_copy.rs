@@ -47,14 +47,17 @@ use crate::config::generate::ListSnapsOfType;
 use crate::data::paths::{CompareVersionsContainer, PathData};
 use crate::library::results::HttmError;
 use crate::library::results::HttmResult;
+use crate::library::throttle::Throttle;
+use crate::library::write_lock::WriteLock;
 use crate::GLOBAL_CONFIG;
 use once_cell::sync::Lazy;
 use std::fs::{File, OpenOptions};
 use std::io::{BufRead, BufReader, BufWriter, ErrorKind, Seek, SeekFrom, Write};
-use std::os::fd::{AsFd, BorrowedFd};
-use std::path::Path;
+use std::os::fd::{AsFd, AsRawFd, BorrowedFd};
+use std::os::unix::fs::{MetadataExt, OpenOptionsExt};
+use std::path::{Path, PathBuf};
 use std::process::Command as ExecProcess;
-use std::sync::atomic::AtomicBool;
+use std::sync::atomic::{AtomicBool, AtomicUsize};
 
 const CHUNK_SIZE: usize = 65_536;
 
@@ -86,6 +89,9 @@ static IS_CLONE_COMPATIBLE: Lazy<AtomicBool> = Lazy::new(|| {
     AtomicBool::new(true)
 });
 
+// not every filesystem which accepts O_TMPFILE will also allow such a file to be linked into place
+static IS_TMPFILE_COMPATIBLE: AtomicBool = AtomicBool::new(true);
+
 enum DstFileState {
     Exists,
     DoesNotExist,
@@ -104,20 +110,102 @@ impl DstFileState {
 pub struct HttmCopy;
 
 impl HttmCopy {
+    // the copy is written to a temporary file upon the destination's filesystem, and only
+    // renamed into place once complete, so an interrupted copy never leaves a truncated file,
+    // unless IN_PLACE was specified, when an existing destination is overwritten in place,
+    // so blocks shared with snapshots stay shared, and the inode, and any hard links, are kept
     pub fn new(src: &Path, dst: &Path) -> HttmResult<()> {
         // create source file reader
         let src_file = File::open(src)?;
+
+        if GLOBAL_CONFIG.opt_in_place && Self::is_existing_file(dst) {
+            Self::overwrite_in_place(&src_file, dst)?;
+
+            if GLOBAL_CONFIG.opt_debug {
+                DiffCopy::confirm(src, dst)?
+            }
+
+            return Ok(());
+        }
+
         let src_len = src_file.metadata()?.len();
 
-        let mut dst_file = OpenOptions::new()
-            .write(true)
-            .read(true)
-            .create(true)
-            .open(dst)?;
-        dst_file.set_len(src_len)?;
+        let _opt_lock = Self::lock_existing(dst)?;
+
+        let mut tmp_file = TmpFile::new(dst)?;
+        tmp_file.file.set_len(src_len)?;
+
+        let amt_written = DiffCopy::new(&src_file, &mut tmp_file.file)?;
+
+        Self::check_amt_written(amt_written, src_len)?;
+
+        let tmp_path = tmp_file.link(dst)?;
+        tmp_file.match_dst(dst)?;
+
+        if GLOBAL_CONFIG.opt_debug {
+            DiffCopy::confirm(src, &tmp_path)?
+        }
+
+        tmp_file.persist(dst)
+    }
+
+    // as above, but the new contents are written by the caller, rather than copied from a source file
+    pub fn with_contents<F>(dst: &Path, write_contents: F) -> HttmResult<()>
+    where
+        F: FnOnce(&mut File) -> HttmResult<()>,
+    {
+        let _opt_lock = Self::lock_existing(dst)?;
+
+        let mut tmp_file = TmpFile::new(dst)?;
+
+        write_contents(&mut tmp_file.file)?;
+        tmp_file.file.sync_data()?;
+
+        // with IN_PLACE, the contents are staged in the temporary file, and then diffed against the destination
+        if GLOBAL_CONFIG.opt_in_place && Self::is_existing_file(dst) {
+            tmp_file.file.seek(SeekFrom::Start(0))?;
+
+            return Self::overwrite_in_place(&tmp_file.file, dst);
+        }
+
+        tmp_file.link(dst)?;
+        tmp_file.match_dst(dst)?;
+
+        tmp_file.persist(dst)
+    }
+
+    fn is_existing_file(dst: &Path) -> bool {
+        dst.symlink_metadata()
+            .map(|md| md.is_file())
+            .unwrap_or(false)
+    }
 
-        let amt_written = DiffCopy::new(&src_file, &mut dst_file)?;
+    // the file replaced is locked until the new file is renamed over it, so two restores never replace it at once
+    fn lock_existing(dst: &Path) -> HttmResult<Option<WriteLock>> {
+        if !Self::is_existing_file(dst) {
+            return Ok(None);
+        }
+
+        let dst_file = File::open(dst)?;
+
+        WriteLock::acquire(dst_file, dst).map(Some)
+    }
+
+    fn overwrite_in_place(src_file: &File, dst: &Path) -> HttmResult<()> {
+        let src_len = src_file.metadata()?.len();
 
+        let dst_file = OpenOptions::new().write(true).read(true).open(dst)?;
+
+        // every file overwritten, including each file within a directory restore, is locked while it is written
+        let mut locked_file = WriteLock::acquire(dst_file, dst)?;
+        locked_file.set_len(src_len)?;
+
+        let amt_written = DiffCopy::new(src_file, &mut locked_file)?;
+
+        Self::check_amt_written(amt_written, src_len)
+    }
+
+    fn check_amt_written(amt_written: usize, src_len: u64) -> HttmResult<()> {
         if amt_written != src_len as usize {
             let msg = format!(
                 "Amount written (\"{}\") != Source length (\"{}\").  Quitting.",
@@ -126,21 +214,212 @@ impl HttmCopy {
             return Err(HttmError::new(&msg).into());
         }
 
-        if GLOBAL_CONFIG.opt_debug {
-            DiffCopy::confirm(src, dst)?
+        Ok(())
+    }
+}
+
+struct TmpFile {
+    file: File,
+    parent: PathBuf,
+    opt_path: Option<PathBuf>,
+}
+
+impl TmpFile {
+    fn new(dst: &Path) -> HttmResult<Self> {
+        let Some(parent) = dst.parent() else {
+            let msg = format!("Could not detect a parent for destination file: {:?}", dst);
+            return Err(HttmError::new(&msg).into());
+        };
+
+        // an unnamed temporary file disappears by itself, should httm be interrupted,
+        // but not every filesystem supports O_TMPFILE, so fall back to a named file
+        #[cfg(target_os = "linux")]
+        if IS_TMPFILE_COMPATIBLE.load(std::sync::atomic::Ordering::Relaxed) {
+            if let Ok(file) = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .custom_flags(libc::O_TMPFILE)
+                .mode(0o666)
+                .open(parent)
+            {
+                return Ok(Self {
+                    file,
+                    parent: parent.to_path_buf(),
+                    opt_path: None,
+                });
+            }
+        }
+
+        let path = Self::unique_path(parent, dst)?;
+
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .mode(0o666)
+            .open(&path)?;
+
+        Ok(Self {
+            file,
+            parent: parent.to_path_buf(),
+            opt_path: Some(path),
+        })
+    }
+
+    fn unique_path(parent: &Path, dst: &Path) -> HttmResult<PathBuf> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let file_name = dst
+            .file_name()
+            .map(|file_name| file_name.to_string_lossy())
+            .unwrap_or_default();
+
+        (0..100)
+            .map(|_| {
+                parent.join(format!(
+                    ".{}.httm-{}-{}.tmp",
+                    file_name,
+                    std::process::id(),
+                    COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed)
+                ))
+            })
+            .find(|path| path.symlink_metadata().is_err())
+            .ok_or_else(|| {
+                let msg = format!(
+                    "Could not create a unique temporary file name for destination file: {:?}",
+                    dst
+                );
+                HttmError::new(&msg).into()
+            })
+    }
+
+    // a temporary file is created with default attributes, so the file it replaces keeps its own,
+    // chown before chmod, as a chown clears any setuid and setgid bits
+    fn match_dst(&self, dst: &Path) -> HttmResult<()> {
+        let Ok(dst_metadata) = dst.symlink_metadata() else {
+            return Ok(());
+        };
+
+        if !dst_metadata.is_file() {
+            return Ok(());
+        }
+
+        // only root may give a file away, so an unprivileged restore keeps its own ownership
+        let _ = std::os::unix::fs::fchown(
+            &self.file,
+            Some(dst_metadata.uid()),
+            Some(dst_metadata.gid()),
+        );
+
+        self.file.set_permissions(dst_metadata.permissions())?;
+
+        // POSIX ACLs are also stored as xattrs, and are carried over with them
+        #[cfg(feature = "xattrs")]
+        if let Ok(xattrs) = xattr::list(dst) {
+            use xattr::FileExt;
+
+            xattrs
+                .flat_map(|attr| xattr::get(dst, &attr).map(|opt_value| (attr, opt_value)))
+                .filter_map(|(attr, opt_value)| opt_value.map(|value| (attr, value)))
+                .for_each(|(attr, value)| {
+                    // some namespaces, like "security", may only be written by root
+                    let _ = self.file.set_xattr(attr, value.as_slice());
+                });
+        }
+
+        Ok(())
+    }
+
+    // give an unnamed temporary file a name, so that it may be verified, and then renamed
+    fn link(&mut self, dst: &Path) -> HttmResult<PathBuf> {
+        if let Some(path) = &self.opt_path {
+            return Ok(path.clone());
+        }
+
+        let fd_path = PathBuf::from(format!("/proc/self/fd/{}", self.file.as_raw_fd()));
+
+        let path = Self::unique_path(&self.parent, dst)?;
+
+        if nix::unistd::linkat(
+            None,
+            &fd_path,
+            None,
+            &path,
+            nix::fcntl::AtFlags::AT_SYMLINK_FOLLOW,
+        )
+        .is_err()
+        {
+            // copy the contents out to a named file instead, and don't try an unnamed file again
+            IS_TMPFILE_COMPATIBLE.store(false, std::sync::atomic::Ordering::Relaxed);
+
+            let mut named_file = OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .mode(0o666)
+                .open(&path)?;
+            self.opt_path = Some(path.clone());
+
+            self.file.seek(SeekFrom::Start(0))?;
+            std::io::copy(&mut self.file, &mut named_file)?;
+            named_file.sync_data()?;
+
+            // chown before chmod, as a chown clears any setuid and setgid bits
+            let tmp_metadata = self.file.metadata()?;
+            let _ = std::os::unix::fs::fchown(
+                &named_file,
+                Some(tmp_metadata.uid()),
+                Some(tmp_metadata.gid()),
+            );
+            named_file.set_permissions(tmp_metadata.permissions())?;
+
+            // the named file is the file renamed into place, so its attributes are the ones to match
+            self.file = named_file;
+
+            return Ok(path);
+        }
+
+        self.opt_path = Some(path.clone());
+
+        Ok(path)
+    }
+
+    fn persist(mut self, dst: &Path) -> HttmResult<()> {
+        let Some(path) = self.opt_path.take() else {
+            return Err(HttmError::new("Temporary file was never linked into place.").into());
+        };
+
+        if let Err(err) = std::fs::rename(&path, dst) {
+            let _ = std::fs::remove_file(&path);
+            return Err(err.into());
+        }
+
+        // make the rename itself durable
+        if let Ok(parent) = File::open(&self.parent) {
+            let _ = parent.sync_all();
         }
 
         Ok(())
     }
 }
 
+impl Drop for TmpFile {
+    fn drop(&mut self) {
+        // a named temporary file which was never renamed into place should be removed
+        if let Some(path) = self.opt_path.take() {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
 struct DiffCopy;
 
 impl DiffCopy {
     fn new(src_file: &File, dst_file: &mut File) -> HttmResult<usize> {
         let src_len = src_file.metadata()?.len();
 
+        // a clone is not subject to a rate limit, so don't attempt one when throttled
         if !GLOBAL_CONFIG.opt_no_clones
+            && !Throttle::is_enabled()
             && IS_CLONE_COMPATIBLE.load(std::sync::atomic::Ordering::Relaxed)
         {
             let src_fd = src_file.as_fd();
@@ -213,6 +492,8 @@ impl DiffCopy {
                         break;
                     }
 
+                    Throttle::consume(src_amt_read);
+
                     match dst_exists {
                         DstFileState::DoesNotExist => Self::write_to_offset(
                             &mut dst_writer,
@@ -15,19 +15,32 @@
 // For the full copyright and license information, please view the LICENSE file
 // that was distributed with this source code.
 
+use crate::config::generate::RestoreMtime;
 use crate::data::paths::PathData;
 use crate::data::paths::PathDeconstruction;
+use crate::data::paths::ZfsSnapPathGuard;
+use crate::display_map::datasets::DatasetInventory;
+use crate::library::audit_log::{AuditAction, AuditLog};
+use crate::library::content_range::ContentRange;
 use crate::library::diff_copy::HttmCopy;
+use crate::library::nfs4_acls::Nfs4Acl;
 use crate::library::results::{HttmError, HttmResult};
+use crate::library::special_file::SpecialFile;
+use crate::parse::dataset_props::MapOfDatasetProps;
+use crate::GLOBAL_CONFIG;
+use hashbrown::{HashMap, HashSet};
 use nix::sys::stat::SFlag;
+use nix::sys::statvfs::FsFlags;
 use nu_ansi_term::Color::{Blue, Red};
 use std::os::unix::fs::chown;
 use std::os::unix::fs::FileTypeExt;
 use std::os::unix::fs::MetadataExt;
 
-use std::fs::{create_dir_all, read_dir, set_permissions};
+use std::fs::{create_dir_all, read_dir, set_permissions, File};
+use std::io::{BufReader, BufWriter, Write};
 use std::iter::Iterator;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
 const CHAR_KIND: SFlag = nix::sys::stat::SFlag::S_IFCHR;
 const BLK_KIND: SFlag = nix::sys::stat::SFlag::S_IFBLK;
@@ -53,6 +66,16 @@ impl Copy {
     }
 
     pub fn direct_quiet(src: &Path, dst: &Path, should_preserve: bool) -> HttmResult<()> {
+        // a privileged snapshot file may only be read by the privileged helper
+        if let Some(privileged_helper) = GLOBAL_CONFIG.opt_privileged_helper.as_ref() {
+            if let Err(err) = src.symlink_metadata() {
+                if err.kind() == std::io::ErrorKind::PermissionDenied {
+                    Self::generate_dst_parent(dst)?;
+                    return privileged_helper.copy(src, dst);
+                }
+            }
+        }
+
         if src.is_dir() {
             create_dir_all(&dst)?;
         } else {
@@ -89,13 +112,14 @@ impl Copy {
         #[cfg(any(target_os = "macos", target_os = "freebsd"))]
         let dst_mode = nix::sys::stat::Mode::from_bits_truncate(src_mode_bits as u16);
 
+        // the device a device node names, not the device upon which the node itself resides
         let is_blk = src_file_type.is_block_device();
         let is_char = src_file_type.is_char_device();
         let is_fifo = src_file_type.is_fifo();
         let is_socket = src_file_type.is_socket();
 
         if is_blk || is_char {
-            let dev = src_metadata.dev();
+            let dev = src_metadata.rdev();
             let kind = if is_blk { BLK_KIND } else { CHAR_KIND };
             #[cfg(target_os = "linux")]
             nix::sys::stat::mknod(dst, kind, dst_mode, dev)?;
@@ -126,7 +150,148 @@ impl Copy {
         Ok(())
     }
 
+    // a restore is a recursive copy, after which the restored file's modify time is set per RESTORE_MTIME
+    pub fn restore(
+        src: &Path,
+        dst: &Path,
+        should_preserve: bool,
+        opt_restore_mtime: Option<RestoreMtime>,
+    ) -> HttmResult<()> {
+        SpecialFile::refuse_restore(src)?;
+        SnapDestination::refuse(dst)?;
+
+        Self::run_restore(src, dst, || {
+            Self::restore_with_mtime(src, dst, should_preserve, opt_restore_mtime)
+        })
+    }
+
+    // replace only a range of the live file with the same range of the snapshot version,
+    // leaving the rest of the live file as it is
+    pub fn splice(src: &Path, dst: &Path, range: &ContentRange) -> HttmResult<()> {
+        SpecialFile::refuse_contents(src)?;
+        SpecialFile::refuse_contents(dst)?;
+        SnapDestination::refuse(dst)?;
+
+        Self::run_restore(src, dst, || Self::splice_contents(src, dst, range))
+    }
+
+    fn splice_contents(src: &Path, dst: &Path, range: &ContentRange) -> HttmResult<()> {
+        HttmCopy::with_contents(dst, |tmp_file| {
+            let mut writer = BufWriter::new(tmp_file);
+
+            let (num_before, _num_within) = range.partition(
+                &mut BufReader::new(File::open(dst)?),
+                &mut writer,
+                &mut std::io::sink(),
+                &mut std::io::sink(),
+            )?;
+
+            if range.begins_after(num_before) {
+                let msg = format!(
+                    "The range specified, {}, begins after the end of the live file.",
+                    range.describe()
+                );
+                return Err(HttmError::new(&msg)
+                    .with_code("range_out_of_bounds")
+                    .with_path(dst)
+                    .into());
+            }
+
+            let (_num_before, num_within) = range.partition(
+                &mut BufReader::new(File::open(src)?),
+                &mut std::io::sink(),
+                &mut writer,
+                &mut std::io::sink(),
+            )?;
+
+            if num_within == 0 {
+                let msg = format!(
+                    "The range specified, {}, is empty upon the snapshot version.",
+                    range.describe()
+                );
+                return Err(HttmError::new(&msg)
+                    .with_code("range_out_of_bounds")
+                    .with_path(src)
+                    .into());
+            }
+
+            range.partition(
+                &mut BufReader::new(File::open(dst)?),
+                &mut std::io::sink(),
+                &mut std::io::sink(),
+                &mut writer,
+            )?;
+
+            writer.flush()?;
+
+            Ok(())
+        })
+    }
+
+    // every restore, whole or partial, is bracketed by the restore hooks, and recorded in the audit log
+    pub fn run_restore<F>(src: &Path, dst: &Path, restore_fn: F) -> HttmResult<()>
+    where
+        F: FnOnce() -> HttmResult<()>,
+    {
+        GLOBAL_CONFIG.hooks.pre_restore(src, dst)?;
+
+        // each file is locked as it is overwritten, after the pre-restore hook, which may have stopped a service using it
+        let res = restore_fn();
+
+        GLOBAL_CONFIG.hooks.post_restore(src, dst, &res);
+
+        let opt_snapshot = GLOBAL_CONFIG
+            .dataset_collection
+            .snap_mount_of(src)
+            .map(DatasetInventory::snap_name);
+
+        AuditLog::record(
+            AuditAction::Restore,
+            [src, dst],
+            opt_snapshot.as_deref(),
+            &res,
+        );
+
+        res
+    }
+
+    fn restore_with_mtime(
+        src: &Path,
+        dst: &Path,
+        should_preserve: bool,
+        opt_restore_mtime: Option<RestoreMtime>,
+    ) -> HttmResult<()> {
+        // the modify time of the live file must be read before it is overwritten
+        let opt_live_mtime = dst.symlink_metadata().and_then(|md| md.modified()).ok();
+
+        Self::recursive(src, dst, should_preserve)?;
+
+        if let Some(restore_mtime) = opt_restore_mtime {
+            Preserve::restore_mtime(src, dst, restore_mtime, opt_live_mtime)?
+        }
+
+        Ok(())
+    }
+
     pub fn recursive(src: &Path, dst: &Path, should_preserve: bool) -> HttmResult<()> {
+        // key: (dev, inode) of a snapshot file, val: the first destination to which it was restored
+        let mut hard_links: HashMap<(u64, u64), PathBuf> = HashMap::new();
+
+        Self::recursive_with_links(src, dst, should_preserve, &mut hard_links)?;
+
+        if should_preserve {
+            Preserve::recursive(src, dst)?
+        }
+
+        Ok(())
+    }
+
+    fn recursive_with_links(
+        src: &Path,
+        dst: &Path,
+        should_preserve: bool,
+        hard_links: &mut HashMap<(u64, u64), PathBuf>,
+    ) -> HttmResult<()> {
         if src.is_dir() {
             Self::direct(src, dst, should_preserve)?;
 
@@ -137,8 +302,15 @@ impl Copy {
 
                 if entry_src.exists() {
                     if file_type.is_dir() {
-                        Self::recursive(&entry_src, &entry_dst, should_preserve)?;
-                    } else {
+                        Self::recursive_with_links(
+                            &entry_src,
+                            &entry_dst,
+                            should_preserve,
+                            hard_links,
+                        )?;
+                    } else if file_type.is_file() {
+                        Self::direct_or_link(&entry_src, &entry_dst, should_preserve, hard_links)?;
+                    } else if !SpecialFile::should_skip_restore(&entry_src) {
                         Self::direct(&entry_src, &entry_dst, should_preserve)?;
                     }
                 }
@@ -147,12 +319,254 @@ impl Copy {
             Self::direct(&src, dst, should_preserve)?;
         }
 
-        if should_preserve {
-            Preserve::recursive(src, dst)?
+        Ok(())
+    }
+
+    // files hard linked to each other in the snapshot are recreated as hard links
+    // at the destination, instead of duplicating their data
+    fn direct_or_link(
+        src: &Path,
+        dst: &Path,
+        should_preserve: bool,
+        hard_links: &mut HashMap<(u64, u64), PathBuf>,
+    ) -> HttmResult<()> {
+        let src_metadata = src.symlink_metadata()?;
+
+        if src_metadata.nlink() <= 1 {
+            return Self::direct(src, dst, should_preserve);
+        }
+
+        let key = (src_metadata.dev(), src_metadata.ino());
+
+        match hard_links.get(&key) {
+            Some(link_target) => {
+                if dst.exists() {
+                    Remove::recursive_quiet(dst)?;
+                }
+
+                std::fs::hard_link(link_target, dst)?;
+                eprintln!("{}: {:?} -> {:?}", Blue.paint("Linked   "), src, dst);
+
+                Ok(())
+            }
+            None => {
+                Self::direct(src, dst, should_preserve)?;
+                let _ = hard_links.insert(key, dst.to_path_buf());
+
+                Ok(())
+            }
+        }
+    }
+}
+
+pub struct SpaceCheck;
+
+impl SpaceCheck {
+    // refuse a restore which will not fit upon the destination filesystem, rather than fail halfway through
+    pub fn exec(src: &Path, dst: &Path) -> HttmResult<()> {
+        if GLOBAL_CONFIG.opt_skip_space_check {
+            return Ok(());
+        }
+
+        // no credit is given for the live version overwritten, as the new copy may be written in full before the
+        // live version is removed
+        let required = Self::tree_bytes(src);
+        let available = Self::available_bytes(dst)?;
+
+        if required > available {
+            let msg = format!(
+                "httm will not restore to {:?}, as the restore requires {} but only {} is available upon the destination filesystem. \
+                To restore anyway, specify SKIP_SPACE_CHECK.",
+                dst,
+                GLOBAL_CONFIG.number_format.display_size(required),
+                GLOBAL_CONFIG.number_format.display_size(available)
+            );
+            return Err(HttmError::new(&msg)
+                .with_code("insufficient_space")
+                .with_path(dst)
+                .into());
+        }
+
+        Ok(())
+    }
+
+    // files hard linked to each other are restored as hard links, so are only counted once
+    fn tree_bytes(path: &Path) -> u64 {
+        let mut hard_links: HashSet<(u64, u64)> = HashSet::new();
+        let mut queue: Vec<PathBuf> = vec![path.to_path_buf()];
+        let mut total: u64 = 0;
+
+        while let Some(item) = queue.pop() {
+            let Ok(metadata) = item.symlink_metadata() else {
+                continue;
+            };
+
+            if metadata.is_dir() {
+                queue.extend(
+                    read_dir(&item)
+                        .into_iter()
+                        .flatten()
+                        .flatten()
+                        .map(|entry| entry.path()),
+                );
+                continue;
+            }
+
+            if metadata.nlink() > 1 && !hard_links.insert((metadata.dev(), metadata.ino())) {
+                continue;
+            }
+
+            total += metadata.len();
+        }
+
+        total
+    }
+
+    fn available_bytes(dst: &Path) -> HttmResult<u64> {
+        // the destination may not exist yet, so ask of the nearest ancestor which does
+        let Some(existing) = dst.ancestors().find(|ancestor| ancestor.exists()) else {
+            let msg = format!(
+                "Could not determine the filesystem upon which the destination resides: {:?}",
+                dst
+            );
+            return Err(HttmError::new(&msg).with_path(dst).into());
+        };
+
+        let stat = nix::sys::statvfs::statvfs(existing)?;
+
+        Ok(stat.blocks_available() as u64 * stat.fragment_size() as u64)
+    }
+}
+
+pub struct SnapDestination;
+
+impl SnapDestination {
+    // snapshots are read only, so refuse a destination within one now, rather than fail with EROFS halfway through a copy
+    pub fn refuse(dst: &Path) -> HttmResult<()> {
+        let resolved = Self::resolve(dst);
+        let resolved_pathdata = PathData::from(resolved.as_path());
+
+        let is_snap_path = ZfsSnapPathGuard::is_zfs_snap_path(&resolved_pathdata)
+            || GLOBAL_CONFIG
+                .dataset_collection
+                .snap_mount_of(&resolved)
+                .is_some();
+
+        if is_snap_path {
+            let opt_live_path = GLOBAL_CONFIG
+                .dataset_collection
+                .live_path_of(&resolved)
+                .or_else(|| {
+                    ZfsSnapPathGuard::new(&resolved_pathdata)
+                        .and_then(|snap_guard| snap_guard.live_path())
+                });
+
+            let suggestion = match opt_live_path {
+                Some(live_path) => format!(
+                    "  Perhaps you meant to restore to its live path: {:?}",
+                    live_path
+                ),
+                None => String::new(),
+            };
+
+            let msg = format!(
+                "httm will not restore to {:?}, as the destination resides within a snapshot, and snapshots are read only.{}",
+                dst, suggestion
+            );
+            return Err(HttmError::new(&msg)
+                .with_code("destination_in_snapshot")
+                .with_path(dst)
+                .into());
+        }
+
+        // a read-only dataset, like a received replica, may never be restored to, but its writable origin may be
+        if let Ok(mount) = resolved_pathdata.proximate_dataset() {
+            if GLOBAL_CONFIG.dataset_collection.is_readonly(mount) {
+                return Err(Self::readonly_dataset_error(dst, mount).into());
+            }
+        }
+
+        // other read only mounts, like a snapshot mounted elsewhere by hand, are caught here
+        if let Some(existing) = resolved.ancestors().find(|ancestor| ancestor.exists()) {
+            if let Ok(stat) = nix::sys::statvfs::statvfs(existing) {
+                if stat.flags().contains(FsFlags::ST_RDONLY) {
+                    let msg = format!(
+                        "httm will not restore to {:?}, as the destination resides upon a read only filesystem.",
+                        dst
+                    );
+                    return Err(HttmError::new(&msg)
+                        .with_code("destination_read_only")
+                        .with_path(dst)
+                        .into());
+                }
+            }
         }
 
         Ok(())
     }
+
+    fn readonly_dataset_error(dst: &Path, mount: &Path) -> HttmError {
+        let dataset_collection = &GLOBAL_CONFIG.dataset_collection;
+
+        let source = dataset_collection
+            .map_of_datasets
+            .get(mount)
+            .map(|dataset_info| dataset_info.source.to_string_lossy().into_owned())
+            .unwrap_or_else(|| mount.to_string_lossy().into_owned());
+
+        let suggestion = match MapOfDatasetProps::writable_origin(&source) {
+            Some(origin) => {
+                let opt_origin_mount = dataset_collection
+                    .map_of_datasets
+                    .iter()
+                    .find(|(_mount, dataset_info)| dataset_info.source.as_os_str() == origin.as_str())
+                    .map(|(origin_mount, _dataset_info)| origin_mount);
+
+                match opt_origin_mount {
+                    Some(origin_mount) => format!(
+                        "  Its snapshots were replicated from the writable dataset {origin}, mounted at {:?}.  Perhaps restore there instead.",
+                        origin_mount
+                    ),
+                    None => format!(
+                        "  Its snapshots were replicated from the writable dataset {origin}.  Perhaps restore there instead."
+                    ),
+                }
+            }
+            None => "  Perhaps restore upon the writable dataset from which it is replicated, which may reside upon another machine, \
+                or restore a copy to a writable directory instead."
+                .to_owned(),
+        };
+
+        let msg = format!(
+            "httm will not restore to {:?}, as the destination resides upon the dataset {source}, which is read-only (readonly=on), as is usual of a received replica.{}",
+            dst, suggestion
+        );
+
+        HttmError::new(&msg)
+            .with_code("destination_read_only_dataset")
+            .with_path(dst)
+    }
+
+    // the destination may not exist yet, so resolve the nearest ancestor which does,
+    // as a symlink may lead from a live directory into a snapshot
+    fn resolve(dst: &Path) -> PathBuf {
+        let absolute = if dst.is_relative() {
+            std::env::current_dir()
+                .map(|pwd| pwd.join(dst))
+                .unwrap_or_else(|_| dst.to_path_buf())
+        } else {
+            dst.to_path_buf()
+        };
+
+        absolute
+            .ancestors()
+            .find_map(|ancestor| {
+                let canonical = ancestor.canonicalize().ok()?;
+                let remainder = absolute.strip_prefix(ancestor).ok()?;
+                Some(canonical.join(remainder))
+            })
+            .unwrap_or(absolute)
+    }
 }
 
 pub struct Preserve;
@@ -160,11 +574,19 @@ pub struct Preserve;
 impl Preserve {
     pub fn direct(src: &Path, dst: &Path) -> HttmResult<()> {
         let src_metadata = src.symlink_metadata()?;
-        let dst_file = std::fs::File::options()
-            .create(false)
-            .read(true)
-            .write(false)
-            .open(&dst)?;
+
+        // Ownership - before the mode, as a chown clears any setuid and setgid bits
+        {
+            let (dst_uid, dst_gid) = match &GLOBAL_CONFIG.opt_owner_map {
+                Some(owner_map) => (
+                    owner_map.uid(src_metadata.uid()),
+                    owner_map.gid(src_metadata.gid()),
+                ),
+                None => (src_metadata.uid(), src_metadata.gid()),
+            };
+
+            chown(dst, Some(dst_uid), Some(dst_gid))?
+        }
 
         // Mode
         {
@@ -175,16 +597,18 @@ impl Preserve {
         #[cfg(feature = "acls")]
         {
             if let Ok(acls) = exacl::getfacl(src, None) {
+                let acls = match &GLOBAL_CONFIG.opt_owner_map {
+                    Some(owner_map) => owner_map.map_acl(acls),
+                    None => acls,
+                };
+
                 exacl::setfacl(&[dst], &acls, None)?;
             }
         }
 
-        // Ownership
+        // NFSv4 ACLs - ZFS datasets with acltype=nfsv4, and NFSv4 mounts
         {
-            let dst_uid = src_metadata.uid();
-            let dst_gid = src_metadata.gid();
-
-            chown(dst, Some(dst_uid), Some(dst_gid))?
+            Nfs4Acl::preserve(src, dst)?
         }
 
         // XAttrs
@@ -202,13 +626,62 @@ impl Preserve {
 
         // Timestamps
         {
-            let src_times = std::fs::FileTimes::new()
-                .set_accessed(src_metadata.accessed()?)
-                .set_modified(src_metadata.modified()?);
+            if SpecialFile::of(dst).is_some() {
+                SpecialFile::set_times(
+                    dst,
+                    Some(src_metadata.accessed()?),
+                    src_metadata.modified()?,
+                )?;
+            } else {
+                let dst_file = std::fs::File::options()
+                    .create(false)
+                    .read(true)
+                    .write(false)
+                    .open(dst)?;
 
-            dst_file.set_times(src_times)?;
+                let src_times = std::fs::FileTimes::new()
+                    .set_accessed(src_metadata.accessed()?)
+                    .set_modified(src_metadata.modified()?);
+
+                dst_file.set_times(src_times)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn restore_mtime(
+        src: &Path,
+        dst: &Path,
+        restore_mtime: RestoreMtime,
+        opt_live_mtime: Option<SystemTime>,
+    ) -> HttmResult<()> {
+        let modified = match (restore_mtime, opt_live_mtime) {
+            (RestoreMtime::Now, _) => SystemTime::now(),
+            (RestoreMtime::PreserveLive, Some(live_mtime)) => live_mtime,
+            // with no live file overwritten, there is no other modify time to keep
+            (RestoreMtime::Source | RestoreMtime::PreserveLive, _) => {
+                src.symlink_metadata()?.modified()?
+            }
+        };
+
+        // opening a symlink would set the times of its target, which was not restored
+        if dst.is_symlink() {
+            return Ok(());
+        }
+
+        if SpecialFile::of(dst).is_some() {
+            return SpecialFile::set_times(dst, None, modified);
         }
 
+        let dst_file = std::fs::File::options()
+            .create(false)
+            .read(true)
+            .write(false)
+            .open(dst)?;
+
+        dst_file.set_times(std::fs::FileTimes::new().set_modified(modified))?;
+
         Ok(())
     }
 
@@ -0,0 +1,173 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::display_map::datasets::DatasetInventory;
+use crate::library::diagnostics::Diagnostic;
+use crate::library::results::{HttmError, HttmResult};
+use crate::GLOBAL_CONFIG;
+use serde_json::{json, Value};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command as ExecProcess, Stdio};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookEvent {
+    PreRestore,
+    PostRestore,
+    PreSnap,
+}
+
+impl HookEvent {
+    fn as_str(&self) -> &'static str {
+        match self {
+            HookEvent::PreRestore => "pre-restore",
+            HookEvent::PostRestore => "post-restore",
+            HookEvent::PreSnap => "pre-snap",
+        }
+    }
+}
+
+// user scripts run before or after an httm action, which receive a JSON description of the action
+// upon stdin, so a site may record, or refuse, such actions.  a pre-action hook which exits non-zero
+// refuses the action, while the failure of a post-action hook is only a warning
+#[derive(Debug, Clone, Default)]
+pub struct Hooks {
+    pub opt_pre_restore: Option<PathBuf>,
+    pub opt_post_restore: Option<PathBuf>,
+    pub opt_pre_snap: Option<PathBuf>,
+}
+
+impl Hooks {
+    pub fn pre_restore(&self, src: &Path, dst: &Path) -> HttmResult<()> {
+        let Some(script) = &self.opt_pre_restore else {
+            return Ok(());
+        };
+
+        let payload = json!({
+            "event": HookEvent::PreRestore.as_str(),
+            "paths": [Self::restore_path(src, dst)],
+        });
+
+        Self::run(script, HookEvent::PreRestore, &payload)
+    }
+
+    pub fn post_restore(&self, src: &Path, dst: &Path, res: &HttmResult<()>) {
+        let Some(script) = &self.opt_post_restore else {
+            return;
+        };
+
+        let payload = json!({
+            "event": HookEvent::PostRestore.as_str(),
+            "paths": [Self::restore_path(src, dst)],
+            "success": res.is_ok(),
+            "error": res.as_ref().err().map(|err| err.to_string()),
+        });
+
+        if let Err(err) = Self::run(script, HookEvent::PostRestore, &payload) {
+            Diagnostic::warn("hook_failed", err.to_string())
+                .with_path(dst)
+                .print();
+        }
+    }
+
+    pub fn pre_snap<'a>(&self, snapshot_names: impl Iterator<Item = &'a String>) -> HttmResult<()> {
+        let Some(script) = &self.opt_pre_snap else {
+            return Ok(());
+        };
+
+        let snapshots: Vec<Value> = snapshot_names
+            .map(|snapshot_name| {
+                let (dataset, snap) = snapshot_name
+                    .split_once('@')
+                    .unwrap_or((snapshot_name.as_str(), ""));
+
+                json!({
+                    "name": snapshot_name,
+                    "dataset": dataset,
+                    "snapshot": snap,
+                })
+            })
+            .collect();
+
+        let payload = json!({
+            "event": HookEvent::PreSnap.as_str(),
+            "snapshots": snapshots,
+        });
+
+        Self::run(script, HookEvent::PreSnap, &payload)
+    }
+
+    fn restore_path(src: &Path, dst: &Path) -> Value {
+        let opt_snapshot = GLOBAL_CONFIG
+            .dataset_collection
+            .snap_mount_of(src)
+            .map(DatasetInventory::snap_name);
+
+        json!({
+            "source": src.to_string_lossy(),
+            "destination": dst.to_string_lossy(),
+            "snapshot": opt_snapshot,
+            "size": src.symlink_metadata().ok().map(|md| md.len()),
+        })
+    }
+
+    fn run(script: &Path, event: HookEvent, payload: &Value) -> HttmResult<()> {
+        let mut child = ExecProcess::new(script)
+            .env("HTTM_HOOK_EVENT", event.as_str())
+            .stdin(Stdio::piped())
+            .spawn()
+            .map_err(|err| {
+                let msg = format!(
+                    "httm could not run the {} hook {:?}: {}",
+                    event.as_str(),
+                    script,
+                    err
+                );
+                HttmError::new(&msg)
+                    .with_code("hook_failed")
+                    .with_path(script)
+            })?;
+
+        if let Some(mut stdin) = child.stdin.take() {
+            // a hook need not read its payload, so a closed pipe is no error
+            let _ = writeln!(stdin, "{}", payload);
+        }
+
+        let status = child.wait()?;
+
+        if !status.success() {
+            let consequence = match event {
+                HookEvent::PreRestore | HookEvent::PreSnap => "so httm will not proceed",
+                HookEvent::PostRestore => "though the restore itself has already finished",
+            };
+
+            let msg = format!(
+                "The {} hook {:?} exited unsuccessfully ({}), {}.",
+                event.as_str(),
+                script,
+                status,
+                consequence
+            );
+            return Err(HttmError::new(&msg)
+                .with_code("hook_refused")
+                .with_path(script)
+                .into());
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,142 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource};
+use once_cell::sync::Lazy;
+use unic_langid::LanguageIdentifier;
+
+// catalogs are compiled into the binary, so httm never depends upon files installed elsewhere
+const CATALOGS: &[(&str, &str)] = &[
+    ("en", include_str!("../../i18n/en.ftl")),
+    ("de", include_str!("../../i18n/de.ftl")),
+    ("es", include_str!("../../i18n/es.ftl")),
+    ("fr", include_str!("../../i18n/fr.ftl")),
+];
+
+const DEFAULT_LANGUAGE: &str = "en";
+
+// HTTM_LANG overrides the usual POSIX locale variables, in their usual order of precedence
+const LOCALE_VARS: [&str; 4] = ["HTTM_LANG", "LC_ALL", "LC_MESSAGES", "LANG"];
+
+pub static MESSAGES: Lazy<Messages> = Lazy::new(Messages::new);
+
+pub struct Messages {
+    // in order of preference, the requested locale first, then the English reference catalog
+    bundles: Vec<FluentBundle<FluentResource>>,
+}
+
+impl Messages {
+    fn new() -> Self {
+        let mut languages = vec![Self::requested_language()];
+
+        if languages[0] != DEFAULT_LANGUAGE {
+            languages.push(DEFAULT_LANGUAGE.to_owned());
+        }
+
+        let bundles = languages
+            .iter()
+            .filter_map(|language| Self::bundle(language))
+            .collect();
+
+        Self { bundles }
+    }
+
+    fn requested_language() -> String {
+        let Some(value) = LOCALE_VARS
+            .iter()
+            .find_map(|var| std::env::var(var).ok().filter(|value| !value.is_empty()))
+        else {
+            return DEFAULT_LANGUAGE.to_owned();
+        };
+
+        // a POSIX locale looks like "de_DE.UTF-8@euro", but we only need its language
+        let tag = value
+            .split(['.', '@'])
+            .next()
+            .unwrap_or_default()
+            .replace('_', "-");
+
+        match tag.parse::<LanguageIdentifier>() {
+            Ok(langid) => langid.language.as_str().to_owned(),
+            Err(_) => DEFAULT_LANGUAGE.to_owned(),
+        }
+    }
+
+    fn bundle(language: &str) -> Option<FluentBundle<FluentResource>> {
+        let (_language, source) = CATALOGS.iter().find(|(lang, _source)| *lang == language)?;
+
+        let langid: LanguageIdentifier = language.parse().ok()?;
+
+        // a catalog with a syntax error still yields every message which did parse,
+        // and the rest fall back to the English catalog
+        let resource = FluentResource::try_new(source.to_string())
+            .unwrap_or_else(|(resource, _errors)| resource);
+
+        let mut bundle = FluentBundle::new_concurrent(vec![langid]);
+
+        // unicode isolation marks around placeables only confuse a terminal
+        bundle.set_use_isolating(false);
+        bundle.add_resource(resource).ok()?;
+
+        Some(bundle)
+    }
+
+    pub fn get(&self, id: &str) -> String {
+        self.format(id, &[])
+    }
+
+    pub fn format(&self, id: &str, args: &[(&str, &str)]) -> String {
+        let mut fluent_args = FluentArgs::new();
+
+        args.iter()
+            .for_each(|(name, value)| fluent_args.set(*name, *value));
+
+        self.bundles
+            .iter()
+            .find_map(|bundle| {
+                let pattern = bundle.get_message(id)?.value()?;
+                let mut errors = Vec::new();
+
+                Some(
+                    bundle
+                        .format_pattern(pattern, Some(&fluent_args), &mut errors)
+                        .into_owned(),
+                )
+            })
+            // a message missing from every catalog is a bug, but its id is better than nothing
+            .unwrap_or_else(|| id.to_owned())
+    }
+
+    // the user may always answer in English, as well as in the language of the prompt
+    pub fn consent(&self, answer: &str) -> Option<bool> {
+        let answer = answer.trim().to_uppercase();
+
+        if answer == self.get("consent-yes").to_uppercase()
+            || matches!(answer.as_str(), "YES" | "Y")
+        {
+            return Some(true);
+        }
+
+        if answer == self.get("consent-no").to_uppercase() || matches!(answer.as_str(), "NO" | "N")
+        {
+            return Some(false);
+        }
+
+        None
+    }
+}
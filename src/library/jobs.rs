@@ -0,0 +1,590 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::config::generate::{JobAction, RestoreMtime};
+use crate::library::file_ops::Copy;
+use crate::library::results::{HttmError, HttmResult};
+use crate::library::snap_guard::SnapGuard;
+use crate::library::utility::{date_string, make_tmp_path, print_output_buf};
+use crate::GLOBAL_CONFIG;
+use nix::errno::Errno;
+use nix::fcntl::{Flock, FlockArg};
+use nix::sys::signal::{kill, Signal};
+use nix::unistd::Pid;
+use serde_json::Value;
+use std::ffi::OsString;
+use std::fs::{create_dir_all, read_dir, read_to_string, rename, write, File, OpenOptions};
+use std::path::{Path, PathBuf};
+use std::process::{Command as ExecProcess, Stdio};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const JOB_EXTENSION: &str = "json";
+const QUEUE_LOCK: &str = ".queue.lock";
+const JOB_LOCK_EXTENSION: &str = "lock";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+impl JobStatus {
+    fn as_str(&self) -> &'static str {
+        match self {
+            JobStatus::Queued => "queued",
+            JobStatus::Running => "running",
+            JobStatus::Completed => "completed",
+            JobStatus::Failed => "failed",
+            JobStatus::Cancelled => "cancelled",
+        }
+    }
+
+    fn from_str(value: &str) -> Option<Self> {
+        match value {
+            "queued" => Some(JobStatus::Queued),
+            "running" => Some(JobStatus::Running),
+            "completed" => Some(JobStatus::Completed),
+            "failed" => Some(JobStatus::Failed),
+            "cancelled" => Some(JobStatus::Cancelled),
+            _ => None,
+        }
+    }
+
+    fn is_finished(&self) -> bool {
+        matches!(
+            self,
+            JobStatus::Completed | JobStatus::Failed | JobStatus::Cancelled
+        )
+    }
+}
+
+// a restore job is a single snapshot version, submitted from an interactive restore, to be copied
+// by a detached worker process, so restoring a large directory never ties up a terminal session.
+// each job persists its state as a JSON file, so it may be tracked, or cancelled, from another session
+#[derive(Debug, Clone)]
+pub struct RestoreJob {
+    pub id: String,
+    pub source: PathBuf,
+    pub target: PathBuf,
+    pub should_preserve: bool,
+    pub opt_restore_mtime: Option<RestoreMtime>,
+    pub opt_snap_guard: Option<String>,
+    // the options which govern how the copy is made, given to the worker process as arguments
+    pub options: Vec<String>,
+    pub status: JobStatus,
+    pub opt_pid: Option<u32>,
+    pub submitted: SystemTime,
+    pub opt_finished: Option<SystemTime>,
+    pub opt_error: Option<String>,
+}
+
+impl RestoreJob {
+    // record the job, then spawn a worker in its own process group, so the worker survives
+    // the terminal which submitted the job being closed
+    pub fn submit(
+        source: &Path,
+        target: &Path,
+        should_preserve: bool,
+        opt_snap_guard: Option<&SnapGuard>,
+    ) -> HttmResult<Self> {
+        let submitted = SystemTime::now();
+
+        let since_epoch = submitted.duration_since(UNIX_EPOCH).unwrap_or_default();
+
+        // the nanos keep the ids of several jobs submitted within the same second distinct
+        let id = format!(
+            "{}-{:x}",
+            since_epoch.as_secs(),
+            since_epoch.subsec_nanos() ^ std::process::id()
+        );
+
+        let job = Self {
+            id,
+            source: source.to_path_buf(),
+            target: target.to_path_buf(),
+            should_preserve,
+            // the worker process is not passed our options, so the policy is recorded with the job
+            opt_restore_mtime: GLOBAL_CONFIG.opt_restore_mtime,
+            opt_snap_guard: opt_snap_guard.map(|snap_guard| snap_guard.snap_name().to_owned()),
+            options: Self::forwarded_options(),
+            status: JobStatus::Queued,
+            opt_pid: None,
+            submitted,
+            opt_finished: None,
+            opt_error: None,
+        };
+
+        job.save()?;
+
+        let httm_command = std::env::current_exe()?;
+
+        {
+            use std::os::unix::process::CommandExt;
+
+            let mut command = ExecProcess::new(httm_command);
+
+            // the worker reads the rest of its options from the job, see worker_args
+            command
+                .arg(format!("--run-job={}", job.id))
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .process_group(0)
+                .spawn()?;
+        }
+
+        Ok(job)
+    }
+
+    pub fn open(id: &str) -> HttmResult<Self> {
+        if id.is_empty() || id.contains('/') || id.starts_with('.') {
+            let msg = format!("Job id is invalid: {id:?}");
+            return Err(HttmError::new(&msg).into());
+        }
+
+        let job_path = Self::jobs_dir()?.join(format!("{id}.{JOB_EXTENSION}"));
+
+        if !job_path.exists() {
+            let msg = format!(
+                "No job with the id {id:?} could be found.  Use the JOBS option to list every job."
+            );
+            return Err(HttmError::new(&msg).with_code("not_found").into());
+        }
+
+        Self::read(&job_path)
+    }
+
+    // each job is read, modified and saved under its own lock, apart from the queue lock, which a worker
+    // holds for the whole of its restore, so a cancellation and a worker never overwrite each other's update
+    fn lock(id: &str) -> HttmResult<Flock<File>> {
+        let lock_file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(Self::jobs_dir()?.join(format!(".{id}.{JOB_LOCK_EXTENSION}")))?;
+
+        Flock::lock(lock_file, FlockArg::LockExclusive).map_err(|(_, errno)| {
+            let msg = format!("Could not obtain the lock upon job {id}: {errno}");
+            HttmError::new(&msg).into()
+        })
+    }
+
+    fn read(job_path: &Path) -> HttmResult<Self> {
+        let contents = read_to_string(job_path)?;
+
+        let value: Value = serde_json::from_str(&contents).map_err(|err| {
+            let msg = format!("Job file {job_path:?} could not be parsed: {err}");
+            HttmError::new(&msg)
+        })?;
+
+        let string = |key: &str| -> Option<String> {
+            value.get(key).and_then(Value::as_str).map(str::to_owned)
+        };
+
+        let time = |key: &str| -> Option<SystemTime> {
+            value
+                .get(key)
+                .and_then(Value::as_u64)
+                .map(|secs| UNIX_EPOCH + Duration::from_secs(secs))
+        };
+
+        let (Some(id), Some(source), Some(target), Some(status)) = (
+            string("id"),
+            string("source"),
+            string("target"),
+            string("status").as_deref().and_then(JobStatus::from_str),
+        ) else {
+            let msg = format!("Job file {job_path:?} is missing a required field.");
+            return Err(HttmError::new(&msg).into());
+        };
+
+        Ok(Self {
+            id,
+            source: PathBuf::from(source),
+            target: PathBuf::from(target),
+            should_preserve: value
+                .get("preserve")
+                .and_then(Value::as_bool)
+                .unwrap_or(false),
+            opt_restore_mtime: string("restore_mtime")
+                .as_deref()
+                .and_then(RestoreMtime::from_str),
+            opt_snap_guard: string("snap_guard"),
+            options: value
+                .get("options")
+                .and_then(Value::as_array)
+                .map(|options| {
+                    options
+                        .iter()
+                        .filter_map(Value::as_str)
+                        .map(str::to_owned)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            status,
+            opt_pid: value
+                .get("pid")
+                .and_then(Value::as_u64)
+                .map(|pid| pid as u32),
+            submitted: time("submitted").unwrap_or(UNIX_EPOCH),
+            opt_finished: time("finished"),
+            opt_error: string("error"),
+        })
+    }
+
+    fn save(&self) -> HttmResult<()> {
+        let job_path = self.job_path()?;
+
+        if let Some(parent) = job_path.parent() {
+            create_dir_all(parent)?;
+        }
+
+        let secs = |time: &SystemTime| {
+            time.duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs())
+                .unwrap_or_default()
+        };
+
+        let value = serde_json::json!({
+            "id": self.id,
+            "source": self.source,
+            "target": self.target,
+            "preserve": self.should_preserve,
+            "restore_mtime": self.opt_restore_mtime.as_ref().map(RestoreMtime::as_str),
+            "snap_guard": self.opt_snap_guard,
+            "options": self.options,
+            "status": self.status.as_str(),
+            "pid": self.opt_pid,
+            "submitted": secs(&self.submitted),
+            "finished": self.opt_finished.as_ref().map(secs),
+            "error": self.opt_error,
+        });
+
+        // write to a tmp file then rename, so a reader never observes a truncated job
+        let tmp_path = make_tmp_path(&job_path);
+        write(&tmp_path, serde_json::to_string_pretty(&value)?)?;
+        rename(&tmp_path, &job_path)?;
+
+        Ok(())
+    }
+
+    // a queued restore must behave just as a foreground restore would, but these options are applied
+    // deep within the copy, by way of the config, and so become the worker's own arguments
+    fn forwarded_options() -> Vec<String> {
+        let config = &GLOBAL_CONFIG;
+
+        let path_arg = |option: &str, path: &Path| format!("{option}={}", path.to_string_lossy());
+
+        [
+            config.opt_bwlimit.map(|rate| format!("--bwlimit={rate}")),
+            config.opt_no_clones.then(|| "--no-clones".to_owned()),
+            config.opt_in_place.then(|| "--in-place".to_owned()),
+            config
+                .opt_privileged_helper
+                .as_ref()
+                .map(|helper| format!("--privileged-helper={}", helper.to_arg())),
+            config
+                .opt_network_concurrency
+                .map(|limit| format!("--network-concurrency={limit}")),
+            config.opt_debug.then(|| "--debug".to_owned()),
+            config
+                .hooks
+                .opt_pre_restore
+                .as_deref()
+                .map(|script| path_arg("--hook-pre-restore", script)),
+            config
+                .hooks
+                .opt_post_restore
+                .as_deref()
+                .map(|script| path_arg("--hook-post-restore", script)),
+            config
+                .opt_audit_log
+                .as_deref()
+                .map(|audit_log| path_arg("--audit-log", audit_log)),
+            config
+                .opt_owner_map
+                .as_ref()
+                .map(|owner_map| format!("--owner-map={}", owner_map.to_arg())),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+
+    // the worker is spawned only with "--run-job=<ID>", so its remaining arguments are those recorded with the job
+    pub fn worker_args(user_args: &mut Vec<OsString>) {
+        let Some(id) = user_args.iter().find_map(|arg| {
+            arg.to_str()
+                .and_then(|arg_str| arg_str.strip_prefix("--run-job="))
+        }) else {
+            return;
+        };
+
+        let Ok(job) = Self::open(id) else {
+            return;
+        };
+
+        user_args.extend(job.options.into_iter().map(OsString::from));
+    }
+
+    fn finish(&mut self, status: JobStatus, opt_error: Option<String>) -> HttmResult<()> {
+        self.status = status;
+        self.opt_finished = Some(SystemTime::now());
+        self.opt_error = opt_error;
+        self.save()
+    }
+
+    fn job_path(&self) -> HttmResult<PathBuf> {
+        Ok(Self::jobs_dir()?.join(format!("{}.{JOB_EXTENSION}", self.id)))
+    }
+
+    fn jobs_dir() -> HttmResult<PathBuf> {
+        let state_dir = match std::env::var_os("XDG_STATE_HOME") {
+            Some(value) if !value.is_empty() => PathBuf::from(value),
+            _ => match std::env::var_os("HOME") {
+                Some(home) => PathBuf::from(home).join(".local").join("state"),
+                None => {
+                    return Err(HttmError::new(
+                        "$HOME does not appear to be set in your environment, and XDG_STATE_HOME is not set",
+                    )
+                    .into())
+                }
+            },
+        };
+
+        Ok(state_dir.join("httm").join("jobs"))
+    }
+}
+
+pub struct RestoreJobs;
+
+impl RestoreJobs {
+    pub fn exec(job_action: &JobAction) -> HttmResult<()> {
+        match job_action {
+            JobAction::List => Self::list(),
+            JobAction::Cancel(id) => Self::cancel(id),
+            JobAction::Run(id) => Self::run(id),
+        }
+    }
+
+    fn list() -> HttmResult<()> {
+        let jobs_dir = RestoreJob::jobs_dir()?;
+
+        let mut jobs: Vec<RestoreJob> = match read_dir(&jobs_dir) {
+            Ok(read_dir) => read_dir
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| {
+                    path.extension()
+                        .is_some_and(|extension| extension == JOB_EXTENSION)
+                })
+                .filter_map(|path| RestoreJob::read(&path).ok())
+                .collect(),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(err) => return Err(err.into()),
+        };
+
+        jobs.sort_by_key(|job| job.submitted);
+
+        if GLOBAL_CONFIG.opt_json {
+            let values: Vec<Value> = jobs
+                .iter()
+                .map(|job| {
+                    serde_json::json!({
+                        "id": job.id,
+                        "status": job.status.as_str(),
+                        "source": job.source,
+                        "target": job.target,
+                        "submitted": Self::display_time(&job.submitted),
+                        "finished": job.opt_finished.as_ref().map(Self::display_time),
+                        "snap_guard": job.opt_snap_guard,
+                        "error": job.opt_error,
+                    })
+                })
+                .collect();
+
+            let output_buf = serde_json::to_string_pretty(&values)? + "\n";
+
+            return print_output_buf(&output_buf);
+        }
+
+        if jobs.is_empty() {
+            return print_output_buf("No restore jobs have been submitted.\n");
+        }
+
+        let output_buf: String = jobs
+            .iter()
+            .map(|job| {
+                let mut buffer = format!(
+                    "{}\t{}\t{}\n\tsource:\t{:?}\n\ttarget:\t{:?}\n",
+                    job.id,
+                    job.status.as_str(),
+                    Self::display_time(&job.submitted),
+                    job.source,
+                    job.target
+                );
+
+                if let Some(snap_guard) = &job.opt_snap_guard {
+                    buffer += &format!("\tguard:\t{snap_guard}\n");
+                }
+
+                if let Some(error) = &job.opt_error {
+                    buffer += &format!("\terror:\t{error}\n");
+                }
+
+                buffer
+            })
+            .collect();
+
+        print_output_buf(&output_buf)
+    }
+
+    fn cancel(id: &str) -> HttmResult<()> {
+        // the job is opened once before its lock is taken, so an unknown id is reported as such
+        RestoreJob::open(id)?;
+
+        let _job_lock = RestoreJob::lock(id)?;
+
+        let mut job = RestoreJob::open(id)?;
+
+        if job.status.is_finished() {
+            let msg = format!(
+                "Job {id} cannot be cancelled, as it is already {}.",
+                job.status.as_str()
+            );
+            return Err(HttmError::new(&msg).into());
+        }
+
+        // a job without a pid has not been picked up by its worker, and the worker
+        // will observe the cancellation when it starts
+        if let Some(pid) = job.opt_pid {
+            match kill(Pid::from_raw(pid as i32), Signal::SIGTERM) {
+                // a worker which has already exited has nothing left to stop
+                Ok(_) | Err(Errno::ESRCH) => {}
+                Err(errno) => {
+                    let msg = format!("Could not signal the worker of job {id}: {errno}");
+                    return Err(HttmError::new(&msg).into());
+                }
+            }
+        }
+
+        let was_running = job.status == JobStatus::Running;
+
+        job.finish(JobStatus::Cancelled, None)?;
+
+        let output_buf = if was_running {
+            format!(
+                "httm cancelled job {id}.  The restore was in progress, and the target may be incomplete: {:?}\n",
+                job.target
+            )
+        } else {
+            format!("httm cancelled job {id}.\n")
+        };
+
+        print_output_buf(&output_buf)
+    }
+
+    // the worker waits its turn upon the queue lock, so jobs are run one at a time,
+    // and a large restore does not compete with another for the same disks
+    fn run(id: &str) -> HttmResult<()> {
+        let job = RestoreJob::open(id)?;
+
+        if job.status != JobStatus::Queued {
+            return Ok(());
+        }
+
+        let lock_file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(RestoreJob::jobs_dir()?.join(QUEUE_LOCK))?;
+
+        let _queue_lock =
+            Flock::lock(lock_file, FlockArg::LockExclusive).map_err(|(_, errno)| {
+                let msg = format!("Could not obtain the restore job queue lock: {errno}");
+                HttmError::new(&msg)
+            })?;
+
+        // the job may have been cancelled while its worker waited upon the queue, and the pid is only
+        // recorded now, as a save before taking the lock would overwrite such a cancellation
+        let mut job = {
+            let _job_lock = RestoreJob::lock(id)?;
+
+            let mut job = RestoreJob::open(id)?;
+
+            if job.status != JobStatus::Queued {
+                return Ok(());
+            }
+
+            job.status = JobStatus::Running;
+            job.opt_pid = Some(std::process::id());
+            job.save()?;
+
+            job
+        };
+
+        let res = Copy::restore(
+            &job.source,
+            &job.target,
+            job.should_preserve,
+            job.opt_restore_mtime,
+        );
+
+        let _job_lock = RestoreJob::lock(id)?;
+
+        // a cancellation which came as the restore ended stands, as the user was told the target may be incomplete
+        if RestoreJob::open(id)?.status == JobStatus::Cancelled {
+            return Ok(());
+        }
+
+        let Err(err) = res else {
+            return job.finish(JobStatus::Completed, None);
+        };
+
+        let mut error = format!("httm restore failed for the following reason: {err}.");
+
+        if let Some(snap_guard) = job
+            .opt_snap_guard
+            .as_deref()
+            .and_then(SnapGuard::from_snap_name)
+        {
+            match snap_guard.rollback() {
+                Ok(_) => {
+                    error += "  Rollback to the precautionary pre-execution snapshot succeeded."
+                }
+                Err(rollback_err) => {
+                    error += &format!(
+                    "  Rollback to the precautionary pre-execution snapshot failed: {rollback_err}"
+                )
+                }
+            }
+        }
+
+        job.finish(JobStatus::Failed, Some(error))
+    }
+
+    fn display_time(time: &SystemTime) -> String {
+        date_string(
+            GLOBAL_CONFIG.requested_utc_offset,
+            time,
+            GLOBAL_CONFIG.display_date_format(),
+        )
+    }
+}
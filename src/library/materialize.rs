@@ -0,0 +1,126 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::data::paths::PathData;
+use crate::library::diagnostics::Diagnostic;
+use crate::library::file_ops::Copy;
+use crate::library::results::{HttmError, HttmResult};
+use crate::library::utility::{date_string, DateFormat};
+use crate::lookup::versions::VersionsMap;
+use crate::GLOBAL_CONFIG;
+use nu_ansi_term::Color::Blue;
+use std::fs::create_dir_all;
+use std::path::{Path, PathBuf};
+
+// a flat directory of every unique version of the requested paths, named <file>.<timestamp>,
+// so external diff and merge tools may operate upon all versions at once
+pub struct Materialize;
+
+impl Materialize {
+    pub fn exec(versions_map: VersionsMap, farm_dir: &Path) -> HttmResult<()> {
+        if farm_dir.exists() && !farm_dir.is_dir() {
+            let msg = format!("Materialize destination is not a directory: {:?}", farm_dir);
+            return Err(HttmError::new(&msg)
+                .with_code("not_a_directory")
+                .with_path(farm_dir)
+                .into());
+        }
+
+        create_dir_all(farm_dir)?;
+
+        let mut num_materialized = 0usize;
+
+        for snap_pathdata in versions_map.values().flatten() {
+            if snap_pathdata.path_buf.is_dir() {
+                Diagnostic::notice(
+                    "directory_skipped",
+                    format!(
+                        "Skipping a directory version, as only files may be materialized: {:?}",
+                        snap_pathdata.path_buf
+                    ),
+                )
+                .with_path(&snap_pathdata.path_buf)
+                .print();
+                continue;
+            }
+
+            let dst = farm_dir.join(Self::file_name(snap_pathdata)?);
+
+            if dst.exists() {
+                Diagnostic::warn(
+                    "already_exists",
+                    format!(
+                        "httm will not overwrite a file which already exists: {:?}",
+                        dst
+                    ),
+                )
+                .with_path(&dst)
+                .print();
+                continue;
+            }
+
+            Self::link_or_copy(&snap_pathdata.path_buf, &dst)?;
+            num_materialized += 1;
+        }
+
+        if num_materialized == 0 {
+            Diagnostic::notice(
+                "nothing_materialized",
+                "No file versions were materialized.",
+            )
+            .print();
+        }
+
+        Ok(())
+    }
+
+    // snapshots usually reside upon a different device than the destination, and hard links
+    // cannot cross devices, so fall back to a copy, which will attempt a reflink clone
+    fn link_or_copy(src: &Path, dst: &Path) -> HttmResult<()> {
+        if std::fs::hard_link(src, dst).is_ok() {
+            eprintln!("{}: {:?} -> {:?}", Blue.paint("Linked   "), src, dst);
+            return Ok(());
+        }
+
+        // don't preserve attributes, as ownership may only be changed by the super user
+        Copy::direct_quiet(src, dst, false)?;
+        eprintln!("{}: {:?} -> {:?}", Blue.paint("Copied   "), src, dst);
+
+        Ok(())
+    }
+
+    fn file_name(snap_pathdata: &PathData) -> HttmResult<PathBuf> {
+        let Some(file_name) = snap_pathdata.path_buf.file_name() else {
+            let msg = format!(
+                "Could not obtain a file name for the snap file version of path given: {:?}",
+                snap_pathdata.path_buf
+            );
+            return Err(HttmError::new(&msg).into());
+        };
+
+        let timestamp = date_string(
+            GLOBAL_CONFIG.requested_utc_offset,
+            &snap_pathdata.md_infallible().modify_time,
+            DateFormat::Timestamp,
+        );
+
+        Ok(PathBuf::from(format!(
+            "{}.{timestamp}",
+            file_name.to_string_lossy()
+        )))
+    }
+}
@@ -0,0 +1,130 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::library::results::{HttmError, HttmResult};
+use crate::parse::mounts::MountType;
+use crate::GLOBAL_CONFIG;
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+
+// network datasets each receive their own limit, so a slow NAS only throttles itself,
+// and local datasets are never limited at all
+static NETWORK_LIMIT: Lazy<Option<NetworkLimit>> =
+    Lazy::new(|| GLOBAL_CONFIG.opt_network_concurrency.map(NetworkLimit::new));
+
+struct Semaphore {
+    available: Mutex<usize>,
+    condvar: Condvar,
+}
+
+impl Semaphore {
+    fn lock(&self) -> MutexGuard<'_, usize> {
+        match self.available.lock() {
+            Ok(available) => available,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
+
+pub struct NetworkPermit {
+    semaphore: Arc<Semaphore>,
+}
+
+impl Drop for NetworkPermit {
+    fn drop(&mut self) {
+        *self.semaphore.lock() += 1;
+        self.semaphore.condvar.notify_one();
+    }
+}
+
+pub struct NetworkLimit {
+    max_concurrent: usize,
+    semaphores: Mutex<HashMap<PathBuf, Arc<Semaphore>>>,
+}
+
+impl NetworkLimit {
+    fn new(max_concurrent: usize) -> Self {
+        Self {
+            max_concurrent,
+            semaphores: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // blocks the calling thread until an operation upon the dataset is permitted,
+    // the permit is released when dropped
+    #[inline]
+    pub fn permit(dataset: &Path) -> Option<NetworkPermit> {
+        let network_limit = NETWORK_LIMIT.as_ref()?;
+
+        let is_network = GLOBAL_CONFIG
+            .dataset_collection
+            .map_of_datasets
+            .get(dataset)
+            .map(|metadata| matches!(metadata.mount_type, MountType::Network))
+            .unwrap_or(false);
+
+        if !is_network {
+            return None;
+        }
+
+        let semaphore = {
+            let mut semaphores = match network_limit.semaphores.lock() {
+                Ok(semaphores) => semaphores,
+                Err(poisoned) => poisoned.into_inner(),
+            };
+
+            semaphores
+                .entry(dataset.to_path_buf())
+                .or_insert_with(|| {
+                    Arc::new(Semaphore {
+                        available: Mutex::new(network_limit.max_concurrent),
+                        condvar: Condvar::new(),
+                    })
+                })
+                .clone()
+        };
+
+        {
+            let mut available = semaphore.lock();
+
+            while *available == 0 {
+                available = match semaphore.condvar.wait(available) {
+                    Ok(available) => available,
+                    Err(poisoned) => poisoned.into_inner(),
+                };
+            }
+
+            *available -= 1;
+        }
+
+        Some(NetworkPermit { semaphore })
+    }
+
+    pub fn parse_limit(value: &str) -> HttmResult<usize> {
+        match value.trim().parse::<usize>() {
+            Ok(limit) if limit > 0 => Ok(limit),
+            _ => {
+                let msg = format!(
+                    "Network concurrency limit must be a whole number greater than zero: {value:?}"
+                );
+                Err(HttmError::new(&msg).into())
+            }
+        }
+    }
+}
@@ -0,0 +1,184 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::library::results::HttmResult;
+use std::path::Path;
+
+// POSIX-only preservation silently loses NFSv4 ACL entries, as found upon ZFS datasets
+// with acltype=nfsv4, so we copy the NFSv4 ACL separately, when one exists.
+//
+// Note: When the source has a non-trivial NFSv4 ACL, and the ACL cannot be applied to
+// the destination, we return an error, as to continue would lose ACL entries.
+pub struct Nfs4Acl;
+
+#[cfg(target_os = "linux")]
+impl Nfs4Acl {
+    // NFSv4 mounts (and OpenZFS acltype=nfsv4) expose the ACL as an opaque xattr
+    #[cfg(feature = "xattrs")]
+    const NFS4_ACL_XATTRS: [&'static str; 2] = ["system.nfs4_acl", "system.nfs4_acl_xdr"];
+
+    pub fn preserve(src: &Path, dst: &Path) -> HttmResult<()> {
+        if src.is_symlink() {
+            return Ok(());
+        }
+
+        #[cfg(feature = "xattrs")]
+        Self::NFS4_ACL_XATTRS
+            .iter()
+            .filter_map(|attr| {
+                xattr::get(src, attr)
+                    .ok()
+                    .flatten()
+                    .map(|value| (attr, value))
+            })
+            .try_for_each(|(attr, value)| xattr::set(dst, attr, value.as_slice()))?;
+
+        #[cfg(not(feature = "xattrs"))]
+        let _ = dst;
+
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "freebsd")]
+impl Nfs4Acl {
+    pub fn preserve(src: &Path, dst: &Path) -> HttmResult<()> {
+        use crate::library::results::HttmError;
+        use std::ffi::CString;
+        use std::os::raw::{c_char, c_int, c_void};
+        use std::os::unix::ffi::OsStrExt;
+
+        type AclT = *mut c_void;
+        const ACL_TYPE_NFS4: c_int = 0x00000004;
+
+        // FreeBSD's libc wraps the __acl_* syscalls
+        extern "C" {
+            fn acl_get_file(path_p: *const c_char, acl_type: c_int) -> AclT;
+            fn acl_set_file(path_p: *const c_char, acl_type: c_int, acl: AclT) -> c_int;
+            fn acl_is_trivial_np(acl: AclT, trivialp: *mut c_int) -> c_int;
+            fn acl_free(obj_p: *mut c_void) -> c_int;
+        }
+
+        if src.is_symlink() {
+            return Ok(());
+        }
+
+        let src_c_str = CString::new(src.as_os_str().as_bytes())?;
+        let dst_c_str = CString::new(dst.as_os_str().as_bytes())?;
+
+        // SAFETY: acl_get_file returns either a null pointer on error (for instance, when the
+        // filesystem does not support NFSv4 ACLs), or a valid acl_t which we free below
+        let acl = unsafe { acl_get_file(src_c_str.as_ptr(), ACL_TYPE_NFS4) };
+
+        if acl.is_null() {
+            return Ok(());
+        }
+
+        let mut trivial: c_int = 0;
+
+        // SAFETY: acl is non-null and valid, and is freed exactly once, after we read errno
+        let res = unsafe {
+            if acl_is_trivial_np(acl, &mut trivial) == 0 && trivial != 0 {
+                // a trivial ACL is equivalent to the mode bits, which we have already preserved
+                0
+            } else {
+                acl_set_file(dst_c_str.as_ptr(), ACL_TYPE_NFS4, acl)
+            }
+        };
+        let err = std::io::Error::last_os_error();
+
+        unsafe { acl_free(acl) };
+
+        if res != 0 {
+            let msg = format!(
+                "Could not preserve the NFSv4 ACL of {:?} upon {:?}: {}",
+                src, dst, err
+            );
+            return Err(HttmError::new(&msg).into());
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(any(target_os = "illumos", target_os = "solaris"))]
+impl Nfs4Acl {
+    pub fn preserve(src: &Path, dst: &Path) -> HttmResult<()> {
+        use crate::library::results::HttmError;
+        use std::ffi::CString;
+        use std::os::raw::{c_char, c_int, c_void};
+        use std::os::unix::ffi::OsStrExt;
+
+        type AclT = c_void;
+
+        #[link(name = "sec")]
+        extern "C" {
+            fn acl_trivial(path: *const c_char) -> c_int;
+            fn acl_get(path: *const c_char, flag: c_int, aclp: *mut *mut AclT) -> c_int;
+            fn acl_set(path: *const c_char, aclp: *mut AclT) -> c_int;
+            fn acl_free(aclp: *mut AclT);
+        }
+
+        if src.is_symlink() {
+            return Ok(());
+        }
+
+        let src_c_str = CString::new(src.as_os_str().as_bytes())?;
+        let dst_c_str = CString::new(dst.as_os_str().as_bytes())?;
+
+        // SAFETY: acl_trivial returns 1 only for a non-trivial ACL, 0 for trivial, and -1 on error
+        if unsafe { acl_trivial(src_c_str.as_ptr()) } != 1 {
+            return Ok(());
+        }
+
+        let mut acl: *mut AclT = std::ptr::null_mut();
+
+        // SAFETY: on success, acl_get allocates an acl_t which we free below
+        if unsafe { acl_get(src_c_str.as_ptr(), 0, &mut acl) } != 0 || acl.is_null() {
+            return Ok(());
+        }
+
+        // SAFETY: acl is non-null and valid, and is freed exactly once, after we read errno
+        let res = unsafe { acl_set(dst_c_str.as_ptr(), acl) };
+        let err = std::io::Error::last_os_error();
+
+        unsafe { acl_free(acl) };
+
+        if res != 0 {
+            let msg = format!(
+                "Could not preserve the NFSv4 ACL of {:?} upon {:?}: {}",
+                src, dst, err
+            );
+            return Err(HttmError::new(&msg).into());
+        }
+
+        Ok(())
+    }
+}
+
+// macOS ACLs are NFSv4-style, and are already preserved by the "acls" feature
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "freebsd",
+    target_os = "illumos",
+    target_os = "solaris"
+)))]
+impl Nfs4Acl {
+    pub fn preserve(_src: &Path, _dst: &Path) -> HttmResult<()> {
+        Ok(())
+    }
+}
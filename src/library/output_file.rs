@@ -0,0 +1,102 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::library::results::{HttmError, HttmResult};
+use crate::library::utility::create_unique_file;
+use crate::GLOBAL_CONFIG;
+use once_cell::sync::Lazy;
+use std::fs::{rename, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+// opened upon the first write, so an error before any output leaves no file behind
+static OUTPUT_FILE: Lazy<Mutex<Option<OutputFile>>> = Lazy::new(|| Mutex::new(None));
+
+// results are written to a temporary file beside the output file requested,
+// which is only renamed into place once httm has completed successfully
+pub struct OutputFile {
+    file: File,
+    tmp_path: PathBuf,
+}
+
+impl OutputFile {
+    pub fn write(output_buf: &str, path: &Path) -> HttmResult<()> {
+        let mut locked = OUTPUT_FILE
+            .lock()
+            .map_err(|_| HttmError::new("Could not obtain a lock on the OUTPUT file."))?;
+
+        let output_file = match locked.as_mut() {
+            Some(output_file) => output_file,
+            None => locked.insert(Self::open(path)?),
+        };
+
+        output_file.file.write_all(output_buf.as_bytes())?;
+
+        Ok(())
+    }
+
+    // the temporary file is created beside the output file, so it may be renamed into place,
+    // but that may be a shared directory, like "/tmp", so its name must not be predictable
+    fn open(path: &Path) -> HttmResult<Self> {
+        let Some(parent) = path.parent() else {
+            let msg = format!("Could not detect a parent for the OUTPUT file: {:?}", path);
+            return Err(HttmError::new(&msg).into());
+        };
+
+        let file_name = path
+            .file_name()
+            .map(|file_name| file_name.to_string_lossy())
+            .unwrap_or_default();
+
+        let (tmp_path, file) = create_unique_file(parent, &format!(".{file_name}.httm-"), ".tmp")?;
+
+        // an output file which is being replaced keeps its permissions
+        if let Ok(metadata) = path.metadata() {
+            file.set_permissions(metadata.permissions())?;
+        }
+
+        Ok(Self { file, tmp_path })
+    }
+
+    // renames our results into place, or, upon an error, discards them,
+    // so a previous output file is never replaced with a partial report
+    pub fn finish(is_success: bool) -> HttmResult<()> {
+        let Some(path) = &GLOBAL_CONFIG.opt_output else {
+            return Ok(());
+        };
+
+        let opt_output_file = OUTPUT_FILE.lock().ok().and_then(|mut locked| locked.take());
+
+        let output_file = match opt_output_file {
+            Some(output_file) => output_file,
+            // a successful run without any results still leaves an empty output file
+            None if is_success => Self::open(path)?,
+            None => return Ok(()),
+        };
+
+        if !is_success {
+            let _ = std::fs::remove_file(&output_file.tmp_path);
+            return Ok(());
+        }
+
+        output_file.file.sync_all()?;
+        rename(&output_file.tmp_path, path)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,218 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::library::results::{HttmError, HttmResult};
+use hashbrown::HashMap;
+use std::path::Path;
+
+const UNKNOWN_NAME_CODE: &str = "owner_map_unknown_name";
+
+// raw uids and gids rarely match across machines, so the owner of a version restored from another machine's backup
+// may be mapped to the id of the same user upon this machine
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OwnerMap {
+    uids: HashMap<u32, u32>,
+    gids: HashMap<u32, u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum IdKind {
+    Uid,
+    Gid,
+    Both,
+}
+
+impl OwnerMap {
+    // the value is either a comma delimited list of mappings, or the path of a file of mappings,
+    // one or more per line, where "#" begins a comment
+    pub fn new(value: &str) -> HttmResult<Self> {
+        let path = Path::new(value);
+
+        let contents = if path.is_file() {
+            std::fs::read_to_string(path).map_err(|err| {
+                let msg = format!("httm could not read the owner map file {:?}: {err}", path);
+                HttmError::new(&msg).with_path(path)
+            })?
+        } else {
+            value.to_owned()
+        };
+
+        let mut res = Self::default();
+
+        contents
+            .lines()
+            .map(|line| {
+                line.split_once('#')
+                    .map_or(line, |(before, _comment)| before)
+            })
+            .flat_map(|line| line.split(','))
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .try_for_each(|entry| res.insert(entry))?;
+
+        if res.uids.is_empty() && res.gids.is_empty() {
+            let msg = format!("OWNER_MAP contains no mappings: {value:?}");
+            return Err(HttmError::new(&msg).into());
+        }
+
+        Ok(res)
+    }
+
+    // an entry is "FROM:TO", which maps both the uid and the gid, or "u:FROM:TO" or "g:FROM:TO", which maps only one,
+    // where FROM is the raw id upon the machine which made the backup, and TO is an id, or a name, upon this machine
+    fn insert(&mut self, entry: &str) -> HttmResult<()> {
+        let invalid = || {
+            let msg = format!(
+                "OWNER_MAP entry is invalid: {entry:?}. An entry should be \"FROM:TO\", \"u:FROM:TO\", or \"g:FROM:TO\", like \"1000:1001\"."
+            );
+            HttmError::new(&msg)
+        };
+
+        let (kind, mapping) = match entry.split_once(':') {
+            Some(("u" | "uid", rest)) => (IdKind::Uid, rest),
+            Some(("g" | "gid", rest)) => (IdKind::Gid, rest),
+            Some(_) => (IdKind::Both, entry),
+            None => return Err(invalid().into()),
+        };
+
+        let (from, to) = mapping.split_once(':').ok_or_else(invalid)?;
+
+        let from: u32 = from.trim().parse().map_err(|_| invalid())?;
+        let to = to.trim();
+
+        if matches!(kind, IdKind::Uid | IdKind::Both) {
+            self.uids.insert(from, Self::resolve_uid(to)?);
+        }
+
+        if matches!(kind, IdKind::Gid | IdKind::Both) {
+            self.gids.insert(from, Self::resolve_gid(to, kind)?);
+        }
+
+        Ok(())
+    }
+
+    fn resolve_uid(to: &str) -> HttmResult<u32> {
+        if let Ok(uid) = to.parse::<u32>() {
+            return Ok(uid);
+        }
+
+        match nix::unistd::User::from_name(to).ok().flatten() {
+            Some(user) => Ok(user.uid.as_raw()),
+            None => {
+                let msg =
+                    format!("OWNER_MAP names a user which does not exist upon this system: {to:?}");
+                Err(HttmError::new(&msg).with_code(UNKNOWN_NAME_CODE).into())
+            }
+        }
+    }
+
+    fn resolve_gid(to: &str, kind: IdKind) -> HttmResult<u32> {
+        if let Ok(gid) = to.parse::<u32>() {
+            return Ok(gid);
+        }
+
+        if let Some(group) = nix::unistd::Group::from_name(to).ok().flatten() {
+            return Ok(group.gid.as_raw());
+        }
+
+        // a name which maps both ids need only name a user, whose primary group is then the gid
+        if kind == IdKind::Both {
+            if let Some(user) = nix::unistd::User::from_name(to).ok().flatten() {
+                return Ok(user.gid.as_raw());
+            }
+        }
+
+        let msg = format!("OWNER_MAP names a group which does not exist upon this system: {to:?}");
+        Err(HttmError::new(&msg).with_code(UNKNOWN_NAME_CODE).into())
+    }
+
+    pub fn uid(&self, uid: u32) -> u32 {
+        self.uids.get(&uid).copied().unwrap_or(uid)
+    }
+
+    pub fn gid(&self, gid: u32) -> u32 {
+        self.gids.get(&gid).copied().unwrap_or(gid)
+    }
+
+    // a POSIX ACL names its users and groups by the names of this system, where the raw id has one,
+    // so each is resolved back to its raw id, mapped, and then named by the mapped id
+    #[cfg(feature = "acls")]
+    pub fn map_acl(&self, acl: Vec<exacl::AclEntry>) -> Vec<exacl::AclEntry> {
+        acl.into_iter()
+            .map(|mut entry| {
+                let opt_mapped = match entry.kind {
+                    exacl::AclEntryKind::User => {
+                        Self::acl_uid(&entry.name).map(|uid| self.uid(uid))
+                    }
+                    exacl::AclEntryKind::Group => {
+                        Self::acl_gid(&entry.name).map(|gid| self.gid(gid))
+                    }
+                    _ => None,
+                };
+
+                if let Some(mapped) = opt_mapped {
+                    entry.name = mapped.to_string();
+                }
+
+                entry
+            })
+            .collect()
+    }
+
+    // an empty name is the owning user or group of the file, which is mapped by chown instead
+    #[cfg(feature = "acls")]
+    fn acl_uid(name: &str) -> Option<u32> {
+        if name.is_empty() {
+            return None;
+        }
+
+        name.parse::<u32>().ok().or_else(|| {
+            nix::unistd::User::from_name(name)
+                .ok()
+                .flatten()
+                .map(|user| user.uid.as_raw())
+        })
+    }
+
+    #[cfg(feature = "acls")]
+    fn acl_gid(name: &str) -> Option<u32> {
+        if name.is_empty() {
+            return None;
+        }
+
+        name.parse::<u32>().ok().or_else(|| {
+            nix::unistd::Group::from_name(name)
+                .ok()
+                .flatten()
+                .map(|group| group.gid.as_raw())
+        })
+    }
+
+    // the same mappings, with every name resolved to an id, as may be given again to a worker process
+    pub fn to_arg(&self) -> String {
+        let mut uids: Vec<(&u32, &u32)> = self.uids.iter().collect();
+        let mut gids: Vec<(&u32, &u32)> = self.gids.iter().collect();
+        uids.sort();
+        gids.sort();
+
+        uids.into_iter()
+            .map(|(from, to)| format!("u:{from}:{to}"))
+            .chain(gids.into_iter().map(|(from, to)| format!("g:{from}:{to}")))
+            .collect::<Vec<String>>()
+            .join(",")
+    }
+}
@@ -0,0 +1,211 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::config::generate::ShellDialect;
+use crate::data::paths::PathMetadata;
+use crate::display_versions::emit_shell::ShellCommands;
+use crate::library::results::{HttmError, HttmResult};
+use std::ffi::OsStr;
+use std::fs::{File, FileTimes};
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command as ExecProcess, Output, Stdio};
+use std::time::{Duration, SystemTime};
+use which::which;
+
+pub const DEFAULT_PRIVILEGED_HELPER: &str = "sudo -n";
+
+// snapshots, like btrfs snapshots, are often only readable by root, so, instead of running
+// all of httm as root, we delegate only those reads which were denied to a helper command,
+// like "sudo -n" or "pkexec", which runs the command which follows it with privileges
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrivilegedHelper {
+    inner: Vec<String>,
+}
+
+impl PrivilegedHelper {
+    pub fn new(value: &str) -> HttmResult<Self> {
+        let inner: Vec<String> = value
+            .split_ascii_whitespace()
+            .map(|arg| arg.to_owned())
+            .collect();
+
+        let Some(executable) = inner.first() else {
+            return Err(HttmError::new(
+                "httm could not determine a valid privileged helper command from user's input.",
+            )
+            .into());
+        };
+
+        if which(executable).is_err() {
+            let msg = format!(
+                "Privileged helper executable {executable:?} could not be found in the user's PATH."
+            );
+            return Err(HttmError::new(&msg).into());
+        }
+
+        Ok(Self { inner })
+    }
+
+    // the value of the option, as given, for a process which must run with the same helper
+    pub fn to_arg(&self) -> String {
+        self.inner.join(" ")
+    }
+
+    // for use within a shell command, like the preview command, so each argument is quoted
+    pub fn shell_prefix(&self) -> String {
+        self.inner
+            .iter()
+            .map(|arg| ShellCommands::quote(&ShellDialect::Bash, arg.as_bytes()))
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+
+    fn exec<I, S>(&self, args: I) -> HttmResult<Output>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        let (executable, helper_args) = self
+            .inner
+            .split_first()
+            .expect("helper command is never empty");
+
+        // the helper must never wait on a password prompt, which would appear behind the UI
+        let output = ExecProcess::new(executable)
+            .args(helper_args)
+            .args(args)
+            .stdin(Stdio::null())
+            .output()?;
+
+        Ok(output)
+    }
+
+    pub fn output<I, S>(&self, args: I) -> HttmResult<String>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        let output = self.exec(args)?;
+
+        if !output.status.success() {
+            let stderr_string = std::str::from_utf8(&output.stderr)?.trim();
+            let msg = "The privileged helper command failed with the following error: ".to_owned()
+                + stderr_string;
+            return Err(HttmError::new(&msg).into());
+        }
+
+        Ok(std::str::from_utf8(&output.stdout)?.to_owned())
+    }
+
+    // like symlink_metadata, GNU stat does not follow symlinks by default
+    pub fn stat(&self, path: &Path) -> Option<PathMetadata> {
+        let output = self
+            .exec([
+                OsStr::new("stat"),
+                OsStr::new("--printf=%s\t%.9Y"),
+                path.as_os_str(),
+            ])
+            .ok()?;
+
+        if !output.status.success() {
+            return None;
+        }
+
+        let stdout_string = std::str::from_utf8(&output.stdout).ok()?;
+        let (size, mtime) = stdout_string.trim().split_once('\t')?;
+
+        let (secs, nanos) = match mtime.split_once('.') {
+            Some((secs, nanos)) => (secs.parse::<u64>().ok()?, nanos.parse::<u32>().ok()?),
+            None => (mtime.parse::<u64>().ok()?, 0),
+        };
+
+        Some(PathMetadata {
+            size: size.parse::<u64>().ok()?,
+            modify_time: SystemTime::UNIX_EPOCH + Duration::new(secs, nanos),
+            opt_identity: None,
+        })
+    }
+
+    pub fn read_dir(&self, dir: &Path) -> HttmResult<Vec<PathBuf>> {
+        let output = self.exec([
+            OsStr::new("find"),
+            dir.as_os_str(),
+            OsStr::new("-mindepth"),
+            OsStr::new("1"),
+            OsStr::new("-maxdepth"),
+            OsStr::new("1"),
+            OsStr::new("-print0"),
+        ])?;
+
+        if !output.status.success() {
+            let msg =
+                format!("The privileged helper command could not read the directory: {dir:?}");
+            return Err(HttmError::new(&msg)
+                .with_code("privileged_helper_failed")
+                .with_path(dir)
+                .into());
+        }
+
+        let entries = output
+            .stdout
+            .split(|byte| *byte == b'\0')
+            .filter(|bytes| !bytes.is_empty())
+            .map(|bytes| PathBuf::from(OsStr::from_bytes(bytes)))
+            .collect();
+
+        Ok(entries)
+    }
+
+    // the copy is made by the user, so the file restored is owned by the user, not by root
+    pub fn copy(&self, src: &Path, dst: &Path) -> HttmResult<()> {
+        let opt_src_metadata = self.stat(src);
+
+        let (executable, helper_args) = self
+            .inner
+            .split_first()
+            .expect("helper command is never empty");
+
+        let dst_file = File::create(dst)?;
+
+        let output = ExecProcess::new(executable)
+            .args(helper_args)
+            .arg("cat")
+            .arg(src)
+            .stdin(Stdio::null())
+            .stdout(dst_file.try_clone()?)
+            .stderr(Stdio::piped())
+            .output()?;
+
+        if !output.status.success() {
+            let stderr_string = std::str::from_utf8(&output.stderr)?.trim();
+            let msg = format!(
+                "The privileged helper command could not read the file: {src:?}, and issued the following error: {stderr_string}"
+            );
+            return Err(HttmError::new(&msg)
+                .with_code("privileged_helper_failed")
+                .with_path(src)
+                .into());
+        }
+
+        if let Some(src_metadata) = opt_src_metadata {
+            dst_file.set_times(FileTimes::new().set_modified(src_metadata.modify_time))?;
+        }
+
+        Ok(())
+    }
+}
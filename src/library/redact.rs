@@ -0,0 +1,389 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+use nix::unistd::{getuid, User};
+use std::borrow::Cow;
+use std::fs::{read_to_string, File};
+use std::io::Read;
+use std::sync::OnceLock;
+
+// set once, when the config is parsed, but read by diagnostics emitted while the config
+// is still being generated, so, like JSON diagnostics, this cannot be a member of the global config
+static REDACTION: OnceLock<Redaction> = OnceLock::new();
+
+// directories beneath which every path component is personal
+const HOME_PREFIXES: [&str; 4] = ["/home/", "/Users/", "/var/home/", "/root/"];
+// the first of these which exists keys the hash of each name
+const MACHINE_ID_FILES: [&str; 2] = ["/etc/machine-id", "/var/lib/dbus/machine-id"];
+const KEY_CONTEXT: &str = "httm redact 2026-10-17 name tokens";
+// components which describe where a snapshot is, not whose it is, so are left as they are
+const SNAP_COMPONENTS: [&str; 5] = [".zfs", "snapshot", ".snapshots", ".nilfs", ".httm"];
+
+fn is_host_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '.' || c == '-'
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '-' || c == '.'
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RedactField {
+    // path components beneath a home directory
+    Home,
+    // this machine's name, and the servers of network shares
+    Hostnames,
+    // the names of users upon this machine
+    Usernames,
+}
+
+impl RedactField {
+    pub fn from_value(value: &str) -> Option<Self> {
+        match value {
+            "home" => Some(RedactField::Home),
+            "hostnames" | "hostname" | "hosts" => Some(RedactField::Hostnames),
+            "usernames" | "username" | "users" => Some(RedactField::Usernames),
+            _ => None,
+        }
+    }
+}
+
+// rewrites output, so it may be shared publicly, as when filing a bug, without disclosing personal names,
+// each name is replaced by a keyed hash of itself, so the same name is always replaced by the same token,
+// and the structure of the output, which paths share which directories, survives
+#[derive(Debug)]
+pub struct Redaction {
+    fields: Vec<RedactField>,
+    key: [u8; 32],
+    usernames: Vec<String>,
+    hostnames: Vec<String>,
+}
+
+impl Redaction {
+    pub fn enable(fields: Vec<RedactField>) {
+        if fields.is_empty() {
+            return;
+        }
+
+        let usernames = if fields.contains(&RedactField::Usernames) {
+            Self::usernames()
+        } else {
+            Vec::new()
+        };
+
+        let hostnames = if fields.contains(&RedactField::Hostnames) {
+            Self::hostnames()
+        } else {
+            Vec::new()
+        };
+
+        let _ = REDACTION.set(Self {
+            fields,
+            key: Self::key(),
+            usernames,
+            hostnames,
+        });
+    }
+
+    // output is returned as it is, unless REDACT was specified
+    pub fn apply(text: &str) -> Cow<'_, str> {
+        let Some(redaction) = REDACTION.get() else {
+            return Cow::Borrowed(text);
+        };
+
+        let mut redacted = text.to_owned();
+
+        // usernames before home directories, so "/home/alice" becomes the same "/home/user-..." either way
+        if redaction.fields.contains(&RedactField::Usernames) {
+            redacted = redaction.usernames.iter().fold(redacted, |acc, username| {
+                Self::replace_word(
+                    &acc,
+                    username,
+                    &redaction.token("user", username),
+                    is_name_char,
+                )
+            });
+        }
+
+        if redaction.fields.contains(&RedactField::Hostnames) {
+            redacted = redaction.hostnames.iter().fold(redacted, |acc, hostname| {
+                Self::replace_word(
+                    &acc,
+                    hostname,
+                    &redaction.token("host", hostname),
+                    is_host_char,
+                )
+            });
+
+            redacted = redaction.redact_share_hosts(&redacted);
+        }
+
+        if redaction.fields.contains(&RedactField::Home) {
+            redacted = redaction.redact_home(&redacted);
+        }
+
+        Cow::Owned(redacted)
+    }
+
+    // a hash is keyed by a secret of this machine, so a reader of the output cannot simply hash a list of likely names,
+    // and find which match, but a hash is still stable across runs, so two reports from the same machine may be compared
+    fn token(&self, kind: &str, name: &str) -> String {
+        let hash = blake3::keyed_hash(&self.key, name.as_bytes()).to_hex();
+
+        format!("{kind}-{}", &hash[..8])
+    }
+
+    // the machine ID is readable by any user of this machine, but is never itself printed, and so is unknown
+    // to a reader of the output, and, where there is none, a random key makes each run's tokens its own
+    fn key() -> [u8; 32] {
+        let opt_machine_id = MACHINE_ID_FILES
+            .iter()
+            .find_map(|path| read_to_string(path).ok())
+            .map(|machine_id| machine_id.trim().to_owned())
+            .filter(|machine_id| !machine_id.is_empty());
+
+        match opt_machine_id {
+            Some(machine_id) => blake3::derive_key(KEY_CONTEXT, machine_id.as_bytes()),
+            None => {
+                let mut key = [0u8; 32];
+
+                let _ =
+                    File::open("/dev/urandom").and_then(|mut urandom| urandom.read_exact(&mut key));
+
+                key
+            }
+        }
+    }
+
+    // the current user, the user who invoked sudo, and every user with a home directory
+    fn usernames() -> Vec<String> {
+        let opt_current = User::from_uid(getuid())
+            .ok()
+            .flatten()
+            .map(|user| user.name);
+
+        let home_dirs = HOME_PREFIXES
+            .iter()
+            .filter(|prefix| **prefix != "/root/")
+            .filter_map(|prefix| std::fs::read_dir(prefix).ok())
+            .flat_map(|read_dir| read_dir.flatten())
+            .map(|dir_entry| dir_entry.file_name().to_string_lossy().to_string());
+
+        let mut usernames: Vec<String> = opt_current
+            .into_iter()
+            .chain(std::env::var("USER").ok())
+            .chain(std::env::var("SUDO_USER").ok())
+            .chain(home_dirs)
+            // "root" names a well known directory, and discloses nothing
+            .filter(|name| !name.is_empty() && name != "root" && !name.starts_with('.'))
+            .collect();
+
+        // longest first, so a name is never partially replaced by a name it contains
+        usernames.sort_unstable();
+        usernames.dedup();
+        usernames.sort_by_key(|name| std::cmp::Reverse(name.len()));
+        usernames
+    }
+
+    // this machine's name, both as it is, and without its domain
+    fn hostnames() -> Vec<String> {
+        let mut buf = [0u8; 256];
+
+        let res = unsafe { libc::gethostname(buf.as_mut_ptr() as *mut libc::c_char, buf.len()) };
+
+        if res != 0 {
+            return Vec::new();
+        }
+
+        let len = buf.iter().position(|byte| *byte == 0).unwrap_or(buf.len());
+        let hostname = String::from_utf8_lossy(&buf[..len]).to_string();
+
+        let mut hostnames: Vec<String> = hostname
+            .split_once('.')
+            .map(|(short, _domain)| short.to_owned())
+            .into_iter()
+            .chain(std::iter::once(hostname))
+            .filter(|name| !name.is_empty() && name != "localhost")
+            .collect();
+
+        hostnames.sort_unstable();
+        hostnames.dedup();
+        hostnames.sort_by_key(|name| std::cmp::Reverse(name.len()));
+        hostnames
+    }
+
+    // replaces only whole words, so the user "al" is never found within "/home/alice"
+    fn replace_word(
+        text: &str,
+        word: &str,
+        replacement: &str,
+        is_word_char: fn(char) -> bool,
+    ) -> String {
+        let mut res = String::with_capacity(text.len());
+        let mut rest = text;
+
+        while let Some(idx) = rest.find(word) {
+            let before = rest[..idx].chars().next_back();
+            let after = rest[idx + word.len()..].chars().next();
+
+            res.push_str(&rest[..idx]);
+
+            if before.is_some_and(is_word_char) || after.is_some_and(is_word_char) {
+                res.push_str(word);
+            } else {
+                res.push_str(replacement);
+            }
+
+            rest = &rest[idx + word.len()..];
+        }
+
+        res.push_str(rest);
+        res
+    }
+
+    // the servers of network shares, like "nas" in "//nas/share", "\\nas\share", or "nas:/export"
+    fn redact_share_hosts(&self, text: &str) -> String {
+        let mut res = String::with_capacity(text.len());
+        let mut chars = text.char_indices().peekable();
+        let mut word_start = 0;
+
+        while let Some((idx, c)) = chars.next() {
+            if is_host_char(c) {
+                continue;
+            }
+
+            let word = &text[word_start..idx];
+            let rest = &text[idx..];
+
+            let is_unc_prefix =
+                word.is_empty() && (rest.starts_with("//") || rest.starts_with("\\\\"));
+
+            // a URL scheme, like "https://", or a drive letter, like "C:/", is not a server
+            let is_nfs_host = word.len() > 1 && rest.starts_with(":/") && !rest.starts_with("://");
+
+            if is_unc_prefix {
+                // a path, like "/a//b", which merely repeats a separator, is not a share
+                let after = &rest[2..];
+                let host_len = after
+                    .find(|c: char| !is_host_char(c))
+                    .unwrap_or(after.len());
+                let is_share = text[..idx].chars().next_back().is_none_or(|prev| {
+                    prev.is_whitespace() || prev == '"' || prev == '\'' || prev == ':'
+                });
+
+                if host_len > 0 && is_share {
+                    res.push_str(&rest[..2]);
+                    res.push_str(&self.token("host", &after[..host_len]));
+
+                    let skip_to = idx + 2 + host_len;
+                    while chars
+                        .peek()
+                        .is_some_and(|(next_idx, _c)| *next_idx < skip_to)
+                    {
+                        chars.next();
+                    }
+
+                    word_start = skip_to;
+                    continue;
+                }
+            }
+
+            if is_nfs_host {
+                res.push_str(&self.token("host", word));
+            } else {
+                res.push_str(word);
+            }
+
+            res.push(c);
+            word_start = idx + c.len_utf8();
+        }
+
+        res.push_str(&text[word_start..]);
+        res
+    }
+
+    // every component of a path beneath a home directory is hashed, but an extension is kept,
+    // as is each component which only locates a snapshot, and the snapshot's name
+    fn redact_home(&self, text: &str) -> String {
+        let mut res = String::with_capacity(text.len());
+        let mut rest = text;
+
+        while let Some((idx, prefix)) = HOME_PREFIXES
+            .iter()
+            .filter_map(|prefix| rest.find(prefix).map(|idx| (idx, *prefix)))
+            .min_by_key(|(idx, _prefix)| *idx)
+        {
+            // a quoted path may contain spaces, a bare path ends at the first
+            let is_quoted = rest[..idx].ends_with('"') || rest[..idx].ends_with('\'');
+            let path_start = idx + prefix.len();
+            let path = &rest[path_start..];
+
+            let path_len = path
+                .find(|c: char| {
+                    c == '"'
+                        || c == '\''
+                        || c == '\n'
+                        || c == '\t'
+                        || (!is_quoted && c.is_whitespace())
+                })
+                .unwrap_or(path.len());
+
+            res.push_str(&rest[..path_start]);
+
+            let components: Vec<&str> = path[..path_len].split('/').collect();
+
+            let redacted_path: Vec<Cow<str>> = components
+                .iter()
+                .enumerate()
+                .map(|(idx, component)| {
+                    // the name of a snapshot follows its snapshot directory
+                    let is_snap_name = idx
+                        .checked_sub(1)
+                        .is_some_and(|prev| matches!(components[prev], "snapshot" | ".snapshots"));
+
+                    if is_snap_name {
+                        Cow::Borrowed(*component)
+                    } else {
+                        self.redact_component(component)
+                    }
+                })
+                .collect();
+
+            res.push_str(&redacted_path.join("/"));
+
+            rest = &path[path_len..];
+        }
+
+        res.push_str(rest);
+        res
+    }
+
+    fn redact_component<'a>(&self, component: &'a str) -> Cow<'a, str> {
+        if component.is_empty()
+            || component.starts_with("user-")
+            || SNAP_COMPONENTS.contains(&component)
+        {
+            return Cow::Borrowed(component);
+        }
+
+        match component.rsplit_once('.') {
+            Some((stem, ext)) if !stem.is_empty() && !ext.is_empty() && ext.len() <= 5 => {
+                Cow::Owned(format!("{}.{ext}", self.token("path", stem)))
+            }
+            _ => Cow::Owned(self.token("path", component)),
+        }
+    }
+}
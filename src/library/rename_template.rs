@@ -0,0 +1,299 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+use crate::data::paths::PathData;
+use crate::display_map::datasets::DatasetInventory;
+use crate::library::results::{HttmError, HttmResult};
+use crate::library::utility::{date_string, DateFormat};
+use crate::parse::snaps::MapOfSnaps;
+use crate::GLOBAL_CONFIG;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    // the whole file name of the live version
+    Name,
+    // the file name, without its last extension
+    Stem,
+    // the last extension, with its leading dot, or nothing, if there is none
+    Ext,
+    // the name of the snapshot upon which the version resides
+    Snap,
+    // the time the snapshot was taken
+    SnapDate,
+    // the modify time of the version
+    Mtime,
+}
+
+impl Field {
+    fn from_str(value: &str) -> Option<Self> {
+        match value {
+            "name" => Some(Field::Name),
+            "stem" => Some(Field::Stem),
+            "ext" => Some(Field::Ext),
+            "snap" => Some(Field::Snap),
+            "snap_date" => Some(Field::SnapDate),
+            "mtime" => Some(Field::Mtime),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CaseTransform {
+    Lower,
+    Upper,
+    Title,
+}
+
+impl CaseTransform {
+    fn from_str(value: &str) -> Option<Self> {
+        match value {
+            "lower" => Some(CaseTransform::Lower),
+            "upper" => Some(CaseTransform::Upper),
+            "title" => Some(CaseTransform::Title),
+            _ => None,
+        }
+    }
+
+    fn apply(&self, value: &str) -> String {
+        match self {
+            CaseTransform::Lower => value.to_lowercase(),
+            CaseTransform::Upper => value.to_uppercase(),
+            // the first letter of each word upper case, and the rest lower case
+            CaseTransform::Title => {
+                let mut is_word_start = true;
+
+                value
+                    .chars()
+                    .flat_map(|c| {
+                        let transformed: Vec<char> = if is_word_start {
+                            c.to_uppercase().collect()
+                        } else {
+                            c.to_lowercase().collect()
+                        };
+
+                        is_word_start = !c.is_alphanumeric();
+                        transformed
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    Field(Field, Option<CaseTransform>),
+}
+
+// a template by which restored copies are named, like "{stem}.{snap_date}{ext}", so a batch restore
+// lands each copy alongside its live version, with a predictable name.  A field may be followed by
+// a case transform, like "{stem:lower}", and literal braces are written "{{" and "}}"
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenameTemplate {
+    segments: Vec<Segment>,
+}
+
+impl RenameTemplate {
+    pub fn new(template: &str) -> HttmResult<Self> {
+        let invalid = |reason: &str| {
+            let msg = format!("RENAME template {template:?} is invalid: {reason}");
+            HttmError::new(&msg).with_code("invalid_rename_template")
+        };
+
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let mut spec = String::new();
+
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => spec.push(c),
+                            None => {
+                                return Err(invalid(
+                                    "unmatched \"{\", write \"{{\" for a literal brace",
+                                )
+                                .into())
+                            }
+                        }
+                    }
+
+                    let (field, opt_transform) = match spec.split_once(':') {
+                        Some((field, transform)) => (field, Some(transform)),
+                        None => (spec.as_str(), None),
+                    };
+
+                    let Some(field) = Field::from_str(field.trim()) else {
+                        let reason = format!(
+                            "unknown field {field:?}, fields are: name, stem, ext, snap, snap_date, and mtime"
+                        );
+                        return Err(invalid(&reason).into());
+                    };
+
+                    let opt_transform = match opt_transform {
+                        Some(transform) => match CaseTransform::from_str(transform.trim()) {
+                            Some(transform) => Some(transform),
+                            None => {
+                                let reason = format!(
+                                    "unknown case transform {transform:?}, transforms are: lower, upper, and title"
+                                );
+                                return Err(invalid(&reason).into());
+                            }
+                        },
+                        None => None,
+                    };
+
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                    }
+
+                    segments.push(Segment::Field(field, opt_transform));
+                }
+                '}' => {
+                    return Err(invalid("unmatched \"}\", write \"}}\" for a literal brace").into())
+                }
+                '/' => {
+                    return Err(invalid(
+                        "a restored copy may only be renamed, not moved, so \"/\" is not allowed",
+                    )
+                    .into())
+                }
+                c => literal.push(c),
+            }
+        }
+
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+
+        if !segments
+            .iter()
+            .any(|segment| matches!(segment, Segment::Field(..)))
+        {
+            return Err(invalid(
+                "a template without any field would give every copy the same name",
+            )
+            .into());
+        }
+
+        Ok(Self { segments })
+    }
+
+    pub fn render(&self, snap_pathdata: &PathData, live_file_name: &str) -> HttmResult<String> {
+        let (stem, ext) = match Path::new(live_file_name).extension() {
+            Some(ext) => {
+                let ext = ext.to_string_lossy();
+                let stem = &live_file_name[..live_file_name.len() - ext.len() - 1];
+                (stem.to_owned(), format!(".{ext}"))
+            }
+            None => (live_file_name.to_owned(), String::new()),
+        };
+
+        let opt_snap_mount = || {
+            GLOBAL_CONFIG
+                .dataset_collection
+                .snap_mount_of(&snap_pathdata.path_buf)
+        };
+
+        let timestamp = |time| {
+            date_string(
+                GLOBAL_CONFIG.requested_utc_offset,
+                &time,
+                DateFormat::Timestamp,
+            )
+        };
+
+        let rendered: String = self
+            .segments
+            .iter()
+            .map(|segment| {
+                let (field, opt_transform) = match segment {
+                    Segment::Literal(literal) => return Ok(literal.clone()),
+                    Segment::Field(field, opt_transform) => (field, opt_transform),
+                };
+
+                let value = match field {
+                    Field::Name => live_file_name.to_owned(),
+                    Field::Stem => stem.clone(),
+                    Field::Ext => ext.clone(),
+                    Field::Snap => opt_snap_mount()
+                        .map(DatasetInventory::snap_name)
+                        .ok_or_else(|| Self::missing("snap", snap_pathdata))?,
+                    Field::SnapDate => opt_snap_mount()
+                        .and_then(|snap_mount| {
+                            MapOfSnaps::snap_time(
+                                snap_mount,
+                                GLOBAL_CONFIG
+                                    .dataset_collection
+                                    .opt_map_of_creation_times
+                                    .as_ref(),
+                            )
+                        })
+                        .map(timestamp)
+                        .ok_or_else(|| Self::missing("snap_date", snap_pathdata))?,
+                    Field::Mtime => snap_pathdata
+                        .metadata
+                        .map(|metadata| timestamp(metadata.modify_time))
+                        .ok_or_else(|| Self::missing("mtime", snap_pathdata))?,
+                };
+
+                Ok(match opt_transform {
+                    Some(transform) => transform.apply(&value),
+                    None => value,
+                })
+            })
+            .collect::<HttmResult<String>>()?;
+
+        match rendered.as_str() {
+            "" | "." | ".." => {
+                let msg = format!(
+                    "RENAME template gave the version an invalid file name: {:?}",
+                    snap_pathdata.path_buf
+                );
+                Err(HttmError::new(&msg)
+                    .with_code("invalid_rename_template")
+                    .with_path(&snap_pathdata.path_buf)
+                    .into())
+            }
+            _ => Ok(rendered),
+        }
+    }
+
+    fn missing(field: &str, snap_pathdata: &PathData) -> Box<dyn std::error::Error + Send + Sync> {
+        let msg = format!(
+            "Could not determine the \"{field}\" field of the RENAME template for the version: {:?}",
+            snap_pathdata.path_buf
+        );
+        HttmError::new(&msg)
+            .with_path(&snap_pathdata.path_buf)
+            .into()
+    }
+}
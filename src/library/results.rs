@@ -18,6 +18,7 @@
 use std::error::Error;
 use std::fmt;
 use std::io::Error as IoError;
+use std::path::{Path, PathBuf};
 
 // wrap this complex looking error type, which is used everywhere,
 // into something more simple looking. This error, FYI, is really easy to use with rayon.
@@ -26,20 +27,37 @@ pub type HttmResult<T> = Result<T, Box<dyn std::error::Error + Send + Sync>>;
 #[derive(Debug)]
 pub struct HttmError {
     pub details: String,
+    // a stable, machine readable code, and the path and dataset concerned, if any, for JSON errors
+    pub opt_code: Option<&'static str>,
+    pub opt_path: Option<PathBuf>,
+    pub opt_dataset: Option<PathBuf>,
 }
 
 impl HttmError {
     pub fn new(msg: &str) -> Self {
         HttmError {
             details: msg.to_owned(),
+            opt_code: None,
+            opt_path: None,
+            opt_dataset: None,
         }
     }
     pub fn with_context(msg: &str, err: &dyn Error) -> Self {
         let msg_plus_context = format!("{msg} : {err:?}");
 
-        HttmError {
-            details: msg_plus_context,
-        }
+        HttmError::new(&msg_plus_context)
+    }
+    pub fn with_code(mut self, code: &'static str) -> Self {
+        self.opt_code = Some(code);
+        self
+    }
+    pub fn with_path(mut self, path: &Path) -> Self {
+        self.opt_path = Some(path.to_path_buf());
+        self
+    }
+    pub fn with_dataset(mut self, dataset: &Path) -> Self {
+        self.opt_dataset = Some(dataset.to_path_buf());
+        self
     }
 }
 
@@ -58,13 +76,13 @@ impl Error for HttmError {
 impl From<&dyn Error> for HttmError {
     fn from(err: &dyn Error) -> Self {
         let context = format!("{err:?}");
-        HttmError { details: context }
+        HttmError::new(&context)
     }
 }
 
 impl From<IoError> for HttmError {
     fn from(err: IoError) -> Self {
         let context = format!("{err:?}");
-        HttmError { details: context }
+        HttmError::new(&context)
     }
 }
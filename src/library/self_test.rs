@@ -0,0 +1,557 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::library::results::{HttmError, HttmResult};
+
+// the self test creates its own pools and filesystems, and so needs neither the config file, nor this
+// system's datasets, and is dispatched from the raw arguments, before the config is built
+pub fn self_test_from_args() -> Option<HttmResult<()>> {
+    std::env::args_os()
+        .skip(1)
+        .take_while(|arg| arg != "--")
+        .any(|arg| arg == "--self-test")
+        .then(self_test)
+}
+
+// the self test is built only with the "self_test" feature, as it creates and destroys pools and filesystems,
+// which no ordinary build of httm should ever be able to do
+#[cfg(feature = "self_test")]
+pub fn self_test() -> HttmResult<()> {
+    harness::SelfTest::exec()
+}
+
+#[cfg(not(feature = "self_test"))]
+pub fn self_test() -> HttmResult<()> {
+    Err(HttmError::new(
+        "This build of httm does not include the self test. Rebuild httm with the \"self_test\" feature, like \"cargo build --features self_test\".",
+    )
+    .with_code("self_test_unavailable")
+    .into())
+}
+
+#[cfg(feature = "self_test")]
+mod harness {
+    use super::{HttmError, HttmResult};
+    use crate::library::diagnostics::Diagnostic;
+    use nu_ansi_term::Color::{Green, Red, Yellow};
+    use sha2::{Digest, Sha256};
+    use std::fs::{create_dir_all, read_to_string, remove_dir_all, remove_file, write, File};
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::{Path, PathBuf};
+    use std::process::Command as ExecProcess;
+    use which::which;
+
+    // large enough for the smallest btrfs filesystem mkfs.btrfs will create
+    const IMAGE_SIZE: u64 = 256 * 1024 * 1024;
+    const SNAP_PREFIX: &str = "httm_self_test_";
+    const TEST_FILE: &str = "file.txt";
+    const DELETED_FILE: &str = "deleted.txt";
+
+    type Check = fn(&Fixture) -> HttmResult<()>;
+
+    #[derive(Debug, Clone, Copy)]
+    enum Backend {
+        Zfs,
+        Btrfs,
+    }
+
+    impl Backend {
+        fn name(&self) -> &'static str {
+            match self {
+                Backend::Zfs => "zfs",
+                Backend::Btrfs => "btrfs",
+            }
+        }
+
+        fn required_commands(&self) -> &'static [&'static str] {
+            match self {
+                Backend::Zfs => &["zpool", "zfs"],
+                Backend::Btrfs => &["mkfs.btrfs", "btrfs", "losetup", "mount", "umount"],
+            }
+        }
+    }
+
+    // a loopback filesystem, made for the test, and torn down when dropped, whether or not the test passed
+    struct Fixture {
+        backend: Backend,
+        work_dir: PathBuf,
+        pool_name: String,
+        // the directory within the filesystem where the test files live
+        data_dir: PathBuf,
+        mounts: Vec<PathBuf>,
+        opt_loop_device: Option<String>,
+    }
+
+    impl Fixture {
+        fn new(backend: Backend, work_dir: &Path) -> HttmResult<Self> {
+            let work_dir = work_dir.join(backend.name());
+            create_dir_all(&work_dir)?;
+
+            let image = work_dir.join("image");
+            File::create(&image)?.set_len(IMAGE_SIZE)?;
+
+            let pool_name = format!("{SNAP_PREFIX}{}", std::process::id());
+
+            let mut fixture = Self {
+                backend,
+                data_dir: PathBuf::new(),
+                work_dir,
+                pool_name,
+                mounts: Vec::new(),
+                opt_loop_device: None,
+            };
+
+            match backend {
+                Backend::Zfs => {
+                    let mount = fixture.work_dir.join("pool");
+
+                    run(
+                        "zpool",
+                        &[
+                            "create".as_ref(),
+                            "-m".as_ref(),
+                            mount.as_os_str(),
+                            fixture.pool_name.as_ref(),
+                            image.as_os_str(),
+                        ],
+                    )?;
+
+                    fixture.data_dir = mount;
+                }
+                Backend::Btrfs => {
+                    // the top level subvolume must be mounted too, as httm finds btrfs snapshots relative to it
+                    let top = fixture.work_dir.join("top");
+                    let data = fixture.work_dir.join("data");
+                    create_dir_all(&top)?;
+                    create_dir_all(&data)?;
+
+                    run("mkfs.btrfs", &["-q".as_ref(), image.as_os_str()])?;
+
+                    // both mounts are of the same loop device, so both are of the same filesystem
+                    let loop_device = attach_loop_device(&image)?;
+                    fixture.opt_loop_device = Some(loop_device.clone());
+
+                    run("mount", &[loop_device.as_ref(), top.as_os_str()])?;
+                    fixture.mounts.push(top.clone());
+
+                    run(
+                        "btrfs",
+                        &[
+                            "subvolume".as_ref(),
+                            "create".as_ref(),
+                            top.join("@data").as_os_str(),
+                        ],
+                    )?;
+                    create_dir_all(top.join("@snapshots"))?;
+
+                    run(
+                        "mount",
+                        &[
+                            "-o".as_ref(),
+                            "subvol=@data".as_ref(),
+                            loop_device.as_ref(),
+                            data.as_os_str(),
+                        ],
+                    )?;
+                    fixture.mounts.insert(0, data.clone());
+
+                    fixture.data_dir = data;
+                }
+            }
+
+            Ok(fixture)
+        }
+
+        fn snapshot(&self, idx: usize) -> HttmResult<()> {
+            nix::unistd::sync();
+
+            let snap_name = format!("{SNAP_PREFIX}{idx}");
+
+            match self.backend {
+                Backend::Zfs => run(
+                    "zfs",
+                    &[
+                        "snapshot".as_ref(),
+                        format!("{}@{snap_name}", self.pool_name).as_ref(),
+                    ],
+                ),
+                Backend::Btrfs => {
+                    let top = self.work_dir.join("top");
+
+                    run(
+                        "btrfs",
+                        &[
+                            "subvolume".as_ref(),
+                            "snapshot".as_ref(),
+                            "-r".as_ref(),
+                            top.join("@data").as_os_str(),
+                            top.join("@snapshots").join(snap_name).as_os_str(),
+                        ],
+                    )
+                }
+            }
+        }
+    }
+
+    impl Drop for Fixture {
+        fn drop(&mut self) {
+            let res = match self.backend {
+                Backend::Zfs => run(
+                    "zpool",
+                    &["destroy".as_ref(), "-f".as_ref(), self.pool_name.as_ref()],
+                ),
+                Backend::Btrfs => self
+                    .mounts
+                    .iter()
+                    .try_for_each(|mount| run("umount", &[mount.as_os_str()]))
+                    .and_then(|_| match &self.opt_loop_device {
+                        Some(loop_device) => run("losetup", &["-d".as_ref(), loop_device.as_ref()]),
+                        None => Ok(()),
+                    }),
+            };
+
+            if let Err(err) = res.and_then(|_| Ok(remove_dir_all(&self.work_dir)?)) {
+                Diagnostic::warn(
+                    "self_test_teardown_failed",
+                    format!(
+                        "Could not tear down the {} self test at {:?}: {err}",
+                        self.backend.name(),
+                        self.work_dir
+                    ),
+                )
+                .with_path(&self.work_dir)
+                .print();
+            }
+        }
+    }
+
+    pub struct SelfTest;
+
+    impl SelfTest {
+        pub fn exec() -> HttmResult<()> {
+            if !nix::unistd::geteuid().is_root() {
+                return Err(HttmError::new(
+                    "The self test creates loopback pools and filesystems, and must be run as root.",
+                )
+                .with_code("self_test_requires_root")
+                .into());
+            }
+
+            let work_dir =
+                std::env::temp_dir().join(format!("httm-self-test-{}", std::process::id()));
+
+            let mut num_failed = 0usize;
+            let mut num_backends = 0usize;
+
+            [Backend::Zfs, Backend::Btrfs].iter().for_each(|backend| {
+                if let Some(missing) = backend
+                    .required_commands()
+                    .iter()
+                    .find(|command| which(command).is_err())
+                {
+                    println!(
+                        "{}: {} ('{missing}' not found)",
+                        backend.name(),
+                        Yellow.paint("skipped")
+                    );
+                    return;
+                }
+
+                num_backends += 1;
+                num_failed += Self::exec_backend(*backend, &work_dir);
+            });
+
+            let _ = remove_dir_all(&work_dir);
+
+            if num_backends == 0 {
+                return Err(HttmError::new(
+                    "The self test found neither the ZFS nor the btrfs tools, and so could test nothing.",
+                )
+                .with_code("self_test_no_backends")
+                .into());
+            }
+
+            if num_failed > 0 {
+                let msg = format!("{num_failed} self test check/s failed.");
+                return Err(HttmError::new(&msg).with_code("self_test_failed").into());
+            }
+
+            println!("All self test checks passed.");
+
+            Ok(())
+        }
+
+        // returns the number of failed checks
+        fn exec_backend(backend: Backend, work_dir: &Path) -> usize {
+            let fixture = match Fixture::new(backend, work_dir).and_then(|fixture| {
+                Self::populate(&fixture)?;
+                Ok(fixture)
+            }) {
+                Ok(fixture) => fixture,
+                Err(err) => {
+                    println!("{}: setup {}: {err}", backend.name(), Red.paint("FAILED"));
+                    return 1;
+                }
+            };
+
+            let checks: [(&str, Check); 6] = [
+                (
+                    "lookup finds each unique version, oldest first",
+                    Self::check_lookup,
+                ),
+                (
+                    "uniqueness=all finds a version in every snapshot",
+                    Self::check_all_versions,
+                ),
+                ("deleted files are found", Self::check_deleted),
+                ("a version's contents are read by its ID", Self::check_cat),
+                (
+                    "a version is restored alongside its live version",
+                    Self::check_restore,
+                ),
+                (
+                    "the live version is left unchanged",
+                    Self::check_live_unchanged,
+                ),
+            ];
+
+            checks
+                .iter()
+                .filter(|(description, check)| match check(&fixture) {
+                    Ok(_) => {
+                        println!("{}: {description}: {}", backend.name(), Green.paint("ok"));
+                        false
+                    }
+                    Err(err) => {
+                        println!(
+                            "{}: {description}: {}: {err}",
+                            backend.name(),
+                            Red.paint("FAILED")
+                        );
+                        true
+                    }
+                })
+                .count()
+        }
+
+        // three snapshots, the last unchanged from the second, so two unique versions, and then a live version unlike either
+        fn populate(fixture: &Fixture) -> HttmResult<()> {
+            let file = fixture.data_dir.join(TEST_FILE);
+            let deleted = fixture.data_dir.join(DELETED_FILE);
+
+            write(&file, "one\n")?;
+            write(&deleted, "gone\n")?;
+            fixture.snapshot(1)?;
+
+            write(&file, "two\n")?;
+            remove_file(&deleted)?;
+            fixture.snapshot(2)?;
+
+            fixture.snapshot(3)?;
+
+            write(&file, "three\n")?;
+
+            Ok(())
+        }
+
+        fn check_lookup(fixture: &Fixture) -> HttmResult<()> {
+            let versions = Self::versions(fixture, &[])?;
+
+            let contents: Vec<String> = versions
+                .iter()
+                .map(read_to_string)
+                .collect::<Result<Vec<String>, _>>()?;
+
+            expect(contents == ["one\n", "two\n"], || {
+                format!("expected the versions \"one\" and \"two\", found: {contents:?}")
+            })
+        }
+
+        fn check_all_versions(fixture: &Fixture) -> HttmResult<()> {
+            let versions = Self::versions(fixture, &["--uniqueness=all"])?;
+
+            expect(versions.len() == 3, || {
+                format!("expected 3 versions, found: {versions:?}")
+            })
+        }
+
+        fn check_deleted(fixture: &Fixture) -> HttmResult<()> {
+            let stdout = httm(&[
+                "--deleted=only".as_ref(),
+                "-n".as_ref(),
+                fixture.data_dir.as_os_str(),
+            ])?;
+
+            expect(
+                stdout
+                    .lines()
+                    .any(|line| Path::new(line).file_name() == Some(DELETED_FILE.as_ref())),
+                || format!("expected to find {DELETED_FILE:?}, found: {stdout:?}"),
+            )
+        }
+
+        fn check_cat(fixture: &Fixture) -> HttmResult<()> {
+            let oldest = Self::oldest_version(fixture)?;
+
+            let stdout = httm(&[
+                format!("--version-id={}", version_id(&oldest)).as_ref(),
+                "--version-action=cat".as_ref(),
+                fixture.data_dir.join(TEST_FILE).as_os_str(),
+            ])?;
+
+            expect(stdout == "one\n", || {
+                format!("expected the contents \"one\", found: {stdout:?}")
+            })
+        }
+
+        fn check_restore(fixture: &Fixture) -> HttmResult<()> {
+            let oldest = Self::oldest_version(fixture)?;
+
+            httm(&[
+                format!("--version-id={}", version_id(&oldest)).as_ref(),
+                "--version-action=restore".as_ref(),
+                fixture.data_dir.join(TEST_FILE).as_os_str(),
+            ])?;
+
+            let prefix = format!("{TEST_FILE}.");
+
+            let restored: Vec<PathBuf> = std::fs::read_dir(&fixture.data_dir)?
+                .flatten()
+                .filter(|entry| entry.file_name().to_string_lossy().starts_with(&prefix))
+                .map(|entry| entry.path())
+                .collect();
+
+            match restored.as_slice() {
+                [restored] => {
+                    let contents = read_to_string(restored)?;
+
+                    expect(contents == "one\n", || {
+                        format!("expected the restored contents \"one\", found: {contents:?}")
+                    })
+                }
+                _ => expect(false, || {
+                    format!("expected a single restored copy, found: {restored:?}")
+                }),
+            }
+        }
+
+        fn check_live_unchanged(fixture: &Fixture) -> HttmResult<()> {
+            let contents = read_to_string(fixture.data_dir.join(TEST_FILE))?;
+
+            expect(contents == "three\n", || {
+                format!("expected the live contents \"three\", found: {contents:?}")
+            })
+        }
+
+        fn versions(fixture: &Fixture, args: &[&str]) -> HttmResult<Vec<PathBuf>> {
+            let file = fixture.data_dir.join(TEST_FILE);
+
+            let mut all_args: Vec<&std::ffi::OsStr> = args.iter().map(|arg| arg.as_ref()).collect();
+            all_args.extend(["--no-live".as_ref(), "-n".as_ref(), file.as_os_str()]);
+
+            let stdout = httm(&all_args)?;
+
+            Ok(stdout.lines().map(PathBuf::from).collect())
+        }
+
+        fn oldest_version(fixture: &Fixture) -> HttmResult<PathBuf> {
+            Self::versions(fixture, &[])?
+                .into_iter()
+                .next()
+                .ok_or_else(|| HttmError::new("No snapshot versions were found.").into())
+        }
+    }
+
+    fn expect<F>(condition: bool, msg: F) -> HttmResult<()>
+    where
+        F: FnOnce() -> String,
+    {
+        if condition {
+            return Ok(());
+        }
+
+        Err(HttmError::new(&msg()).into())
+    }
+
+    // the same as PathData::version_id, as it must be, but computed here, so the check does not merely agree with itself
+    fn version_id(snap_path: &Path) -> String {
+        Sha256::digest(snap_path.as_os_str().as_bytes())
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect::<String>()[..8]
+            .to_owned()
+    }
+
+    fn attach_loop_device(image: &Path) -> HttmResult<String> {
+        let output = ExecProcess::new("losetup")
+            .args(["--find", "--show"])
+            .arg(image)
+            .output()?;
+
+        let loop_device = String::from_utf8_lossy(&output.stdout).trim().to_owned();
+
+        if !output.status.success() || loop_device.is_empty() {
+            let msg = format!(
+                "Could not attach a loop device to {:?}: {}",
+                image,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+            return Err(HttmError::new(&msg).into());
+        }
+
+        Ok(loop_device)
+    }
+
+    fn run(command: &str, args: &[&std::ffi::OsStr]) -> HttmResult<()> {
+        let output = ExecProcess::new(command).args(args).output()?;
+
+        if !output.status.success() {
+            let msg = format!(
+                "'{command}' failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+            return Err(HttmError::new(&msg).into());
+        }
+
+        Ok(())
+    }
+
+    // each check runs httm anew, so that httm discovers the pools and filesystems made after this process began,
+    // and without any config file or environment of the user's, which might change its output
+    fn httm(args: &[&std::ffi::OsStr]) -> HttmResult<String> {
+        let mut command = ExecProcess::new(std::env::current_exe()?);
+
+        std::env::vars_os()
+            .filter(|(key, _value)| key.to_string_lossy().starts_with("HTTM_"))
+            .for_each(|(key, _value)| {
+                command.env_remove(key);
+            });
+
+        let output = command.env("HTTM_CONFIG_FILE", "").args(args).output()?;
+
+        if !output.status.success() {
+            let msg = format!(
+                "httm {:?} failed: {}",
+                args,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+            return Err(HttmError::new(&msg).into());
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
@@ -0,0 +1,92 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+use crate::library::results::{HttmError, HttmResult};
+use crate::lookup::metadata_cache::METADATA_CACHE;
+use crate::parse::network_probe::NetworkProbe;
+use std::os::unix::process::CommandExt;
+use std::process::Command as ExecProcess;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static RELOAD_REQUESTED: AtomicBool = AtomicBool::new(false);
+static INVALIDATE_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignalRequest {
+    // SIGHUP: re-read the config file and aliases, and re-enumerate datasets
+    Reload,
+    // SIGUSR1: as above, but first discard any cached verdicts upon network mounts, and any cached snapshot metadata
+    Invalidate,
+}
+
+// the config, aliases, and map of datasets are all generated once, at startup, so a long-running
+// mode, like TOP, answers a signal by restarting itself, in place, with the same arguments
+pub struct Signals;
+
+impl Signals {
+    pub fn install() {
+        // a signal handler may do very little safely, so it only records the request, for the main loop
+        let handler = Self::handler as extern "C" fn(libc::c_int);
+
+        unsafe {
+            libc::signal(libc::SIGHUP, handler as libc::sighandler_t);
+            libc::signal(libc::SIGUSR1, handler as libc::sighandler_t);
+        }
+    }
+
+    extern "C" fn handler(signum: libc::c_int) {
+        match signum {
+            libc::SIGHUP => RELOAD_REQUESTED.store(true, Ordering::SeqCst),
+            libc::SIGUSR1 => INVALIDATE_REQUESTED.store(true, Ordering::SeqCst),
+            _ => {}
+        }
+    }
+
+    pub fn take() -> Option<SignalRequest> {
+        if INVALIDATE_REQUESTED.swap(false, Ordering::SeqCst) {
+            RELOAD_REQUESTED.store(false, Ordering::SeqCst);
+            return Some(SignalRequest::Invalidate);
+        }
+
+        if RELOAD_REQUESTED.swap(false, Ordering::SeqCst) {
+            return Some(SignalRequest::Reload);
+        }
+
+        None
+    }
+
+    // only returns upon error, the caller must have restored the terminal before calling
+    pub fn restart(request: SignalRequest) -> HttmResult<()> {
+        match request {
+            SignalRequest::Reload => METADATA_CACHE.save(),
+            SignalRequest::Invalidate => {
+                METADATA_CACHE.invalidate();
+                NetworkProbe::invalidate();
+            }
+        }
+
+        let current_exe = std::env::current_exe()?;
+
+        let error = ExecProcess::new(&current_exe)
+            .args(std::env::args_os().skip(1))
+            .exec();
+
+        Err(HttmError::new(&format!("Could not restart httm: {error}"))
+            .with_code("restart_failed")
+            .with_path(&current_exe)
+            .into())
+    }
+}
@@ -0,0 +1,49 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::library::results::{HttmError, HttmResult};
+use crate::library::utility::parse_bytes;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SizeFilter {
+    opt_min: Option<u64>,
+    opt_max: Option<u64>,
+}
+
+impl SizeFilter {
+    pub fn new(opt_min: Option<&str>, opt_max: Option<&str>) -> HttmResult<Option<Self>> {
+        let opt_min = opt_min.map(parse_bytes).transpose()?;
+        let opt_max = opt_max.map(parse_bytes).transpose()?;
+
+        if let (Some(min), Some(max)) = (opt_min, opt_max) {
+            if min > max {
+                return Err(HttmError::new("MIN_SIZE may not be greater than MAX_SIZE.").into());
+            }
+        }
+
+        if opt_min.is_none() && opt_max.is_none() {
+            return Ok(None);
+        }
+
+        Ok(Some(Self { opt_min, opt_max }))
+    }
+
+    // both bounds are inclusive
+    pub fn is_match(&self, size: u64) -> bool {
+        self.opt_min.iter().all(|min| size >= *min) && self.opt_max.iter().all(|max| size <= *max)
+    }
+}
@@ -17,10 +17,12 @@
 
 use crate::data::paths::PathData;
 use crate::data::paths::PathDeconstruction;
+use crate::library::audit_log::{AuditAction, AuditLog};
 use crate::library::results::{HttmError, HttmResult};
 use crate::library::utility::user_has_effective_root;
 use crate::library::utility::{date_string, DateFormat};
 use crate::{print_output_buf, GLOBAL_CONFIG};
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
 use std::process::Command as ExecProcess;
 use std::time::SystemTime;
@@ -63,6 +65,13 @@ impl SnapGuard {
     pub fn new(dataset_name: &str, snap_type: PrecautionarySnapType) -> HttmResult<Self> {
         let zfs_command = which("zfs")?;
 
+        // a post-execution snapshot records work already done, so is never refused
+        if let Some(snap_limits) = &GLOBAL_CONFIG.opt_snap_limits {
+            if !matches!(snap_type, PrecautionarySnapType::PostRollForward(_)) {
+                snap_limits.check([dataset_name])?;
+            }
+        }
+
         let timestamp = date_string(
             GLOBAL_CONFIG.requested_utc_offset,
             &SystemTime::now(),
@@ -101,10 +110,17 @@ impl SnapGuard {
         let process_output = ExecProcess::new(zfs_command).args(&process_args).output()?;
         let stderr_string = std::str::from_utf8(&process_output.stderr)?.trim();
 
+        AuditLog::record(
+            AuditAction::Snapshot,
+            [],
+            [new_snap_name.as_str()],
+            &Self::zfs_result(stderr_string),
+        );
+
         // stderr_string is a string not an error, so here we build an err or output
         if !stderr_string.is_empty() {
             let msg = if stderr_string.contains("cannot create snapshots : permission denied") {
-                "httm must have root privileges to snapshot a filesystem".to_owned()
+                "httm must have root privileges, or delegated 'zfs allow' privileges, to snapshot a filesystem".to_owned()
             } else {
                 "httm was unable to take snapshots. The 'zfs' command issued the following error: "
                     .to_owned()
@@ -137,6 +153,20 @@ impl SnapGuard {
         }
     }
 
+    // rebuild a guard from the full name of a snapshot previously taken, like "pool/data@snap_pre_..."
+    pub fn from_snap_name(full_snap_name: &str) -> Option<Self> {
+        let (dataset_name, _snap_name) = full_snap_name.split_once('@')?;
+
+        Some(SnapGuard {
+            new_snap_name: full_snap_name.to_owned(),
+            dataset_name: dataset_name.to_owned(),
+        })
+    }
+
+    pub fn snap_name(&self) -> &str {
+        &self.new_snap_name
+    }
+
     pub fn rollback(&self) -> HttmResult<()> {
         ZfsAllowPriv::Rollback.from_fs_name(&self.dataset_name)?;
 
@@ -146,6 +176,13 @@ impl SnapGuard {
         let process_output = ExecProcess::new(zfs_command).args(&process_args).output()?;
         let stderr_string = std::str::from_utf8(&process_output.stderr)?.trim();
 
+        AuditLog::record(
+            AuditAction::Rollback,
+            [],
+            [self.new_snap_name.as_str()],
+            &Self::zfs_result(stderr_string),
+        );
+
         // stderr_string is a string not an error, so here we build an err or output
         if !stderr_string.is_empty() {
             let msg = if stderr_string.contains("cannot destroy snapshots: permission denied") {
@@ -159,11 +196,21 @@ impl SnapGuard {
 
         Ok(())
     }
+
+    // the 'zfs' command reports failure upon stderr, which is recorded as is
+    fn zfs_result(stderr_string: &str) -> HttmResult<()> {
+        if stderr_string.is_empty() {
+            Ok(())
+        } else {
+            Err(HttmError::new(stderr_string).into())
+        }
+    }
 }
 
 pub enum ZfsAllowPriv {
     Snapshot,
     Rollback,
+    Destroy,
 }
 
 impl ZfsAllowPriv {
@@ -175,7 +222,10 @@ impl ZfsAllowPriv {
                 "Could not determine dataset name from path given: {:?}",
                 new_file_path
             );
-            return Err(HttmError::new(&msg).into());
+            return Err(HttmError::new(&msg)
+                .with_code("no_dataset")
+                .with_path(new_file_path)
+                .into());
         };
 
         Self::from_fs_name(&self, &fs_name.to_string_lossy())
@@ -184,12 +234,17 @@ impl ZfsAllowPriv {
     pub fn from_fs_name(&self, fs_name: &str) -> HttmResult<()> {
         let msg = match self {
             ZfsAllowPriv::Rollback => "A rollback after a restore action",
-            ZfsAllowPriv::Snapshot => "A snapshot guard before restore action",
+            ZfsAllowPriv::Snapshot => "A snapshot of a dataset",
+            ZfsAllowPriv::Destroy => "A destroy of a snapshot",
         };
 
         if let Err(root_error) = user_has_effective_root(msg) {
-            if let Err(_allow_priv_error) = self.user_has_zfs_allow_priv(fs_name) {
-                return Err(root_error);
+            if let Err(allow_priv_error) = self.user_has_zfs_allow_priv(fs_name) {
+                let msg = format!("{root_error}  Alternatively, {allow_priv_error}");
+                return Err(HttmError::new(&msg)
+                    .with_code("permission_denied")
+                    .with_dataset(Path::new(fs_name))
+                    .into());
             }
         }
 
@@ -200,6 +255,8 @@ impl ZfsAllowPriv {
         match self {
             ZfsAllowPriv::Rollback => &["rollback"],
             ZfsAllowPriv::Snapshot => &["snapshot", "mount"],
+            // destroying a snapshot also requires the ability to unmount it
+            ZfsAllowPriv::Destroy => &["destroy", "mount"],
         }
     }
 
@@ -219,19 +276,128 @@ impl ZfsAllowPriv {
             return Err(HttmError::new(&msg).into());
         }
 
-        let user_name = std::env::var("USER")?;
-
-        if !stdout_string.contains(&user_name)
-            || !self
-                .as_zfs_cmd_strings()
-                .iter()
-                .all(|p| stdout_string.contains(p))
-        {
-            let msg = "User does not have 'zfs allow' privileges for the path given.";
+        let uid = nix::unistd::getuid();
+
+        let user_name = nix::unistd::User::from_uid(uid)
+            .ok()
+            .flatten()
+            .map(|user| user.name)
+            .or_else(|| std::env::var("USER").ok())
+            .unwrap_or_else(|| uid.to_string());
+
+        let group_names: Vec<String> = nix::unistd::getgroups()
+            .unwrap_or_default()
+            .into_iter()
+            .chain([nix::unistd::getgid()])
+            .filter_map(|gid| nix::unistd::Group::from_gid(gid).ok().flatten())
+            .map(|group| group.name)
+            .collect();
+
+        let delegated = ZfsAllowDelegated::new(stdout_string, fs_name, &user_name, &group_names);
+
+        let missing: Vec<&str> = self
+            .as_zfs_cmd_strings()
+            .iter()
+            .copied()
+            .filter(|perm| !delegated.contains(perm))
+            .collect();
+
+        if !missing.is_empty() {
+            let msg = format!(
+                "user {user_name:?} has not been delegated the 'zfs allow' permission/s: {}, upon dataset {fs_name:?}.  \
+                An administrator may delegate these permissions via: \"zfs allow -u {user_name} {} {fs_name}\"",
+                missing.join(", "),
+                missing.join(",")
+            );
 
-            return Err(HttmError::new(msg).into());
+            return Err(HttmError::new(&msg).into());
         }
 
         Ok(())
     }
 }
+
+// the permissions delegated to a user upon a dataset, as parsed from 'zfs allow' output, like so:
+//
+// ---- Permissions on pool/fs ------------------------------------------
+// Permission sets:
+//         @snapper mount,snapshot
+// Local+Descendent permissions:
+//         user alice @snapper,destroy
+//         group staff rollback
+struct ZfsAllowDelegated {
+    inner: HashSet<String>,
+}
+
+impl ZfsAllowDelegated {
+    fn new(stdout_string: &str, fs_name: &str, user_name: &str, group_names: &[String]) -> Self {
+        let mut permission_sets: HashMap<String, Vec<String>> = HashMap::new();
+        let mut granted: Vec<String> = Vec::new();
+
+        let mut is_requested_dataset = false;
+        let mut section = "";
+
+        for line in stdout_string.lines() {
+            let trimmed = line.trim();
+
+            if let Some(header) = trimmed.strip_prefix("---- Permissions on ") {
+                is_requested_dataset = header.trim_end_matches(['-', ' ']) == fs_name;
+                continue;
+            }
+
+            if let Some(new_section) = trimmed.strip_suffix(':') {
+                section = new_section;
+                continue;
+            }
+
+            let mut words = trimmed.split_whitespace();
+
+            match section {
+                "Permission sets" => {
+                    if let (Some(set_name), Some(perms)) = (words.next(), words.next()) {
+                        permission_sets.insert(
+                            set_name.to_owned(),
+                            perms.split(',').map(str::to_owned).collect(),
+                        );
+                    }
+                }
+                // local permissions upon an ancestor are not inherited, and descendent
+                // permissions upon the requested dataset apply only to its children
+                "Local permissions" if !is_requested_dataset => {}
+                "Descendent permissions" if is_requested_dataset => {}
+                "Local permissions" | "Descendent permissions" | "Local+Descendent permissions" => {
+                    let opt_perms = match (words.next(), words.next(), words.next()) {
+                        (Some("user"), Some(name), Some(perms)) if name == user_name => Some(perms),
+                        (Some("group"), Some(name), Some(perms))
+                            if group_names.iter().any(|group| group == name) =>
+                        {
+                            Some(perms)
+                        }
+                        (Some("everyone"), Some(perms), None) => Some(perms),
+                        _ => None,
+                    };
+
+                    if let Some(perms) = opt_perms {
+                        granted.extend(perms.split(',').map(str::to_owned));
+                    }
+                }
+                // "Create time permissions" only apply to datasets the user creates
+                _ => {}
+            }
+        }
+
+        let inner = granted
+            .into_iter()
+            .flat_map(|perm| match permission_sets.get(&perm) {
+                Some(set_perms) => set_perms.clone(),
+                None => vec![perm],
+            })
+            .collect();
+
+        Self { inner }
+    }
+
+    fn contains(&self, perm: &str) -> bool {
+        self.inner.contains(perm)
+    }
+}
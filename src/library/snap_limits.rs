@@ -0,0 +1,203 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::library::diagnostics::Diagnostic;
+use crate::library::results::{HttmError, HttmResult};
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::process::Command as ExecProcess;
+use which::which;
+
+// ZFS reports an unset snapshot_limit as the largest unsigned 64 bit integer
+const ZFS_NO_LIMIT: u64 = u64::MAX;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SnapLimits {
+    pub opt_max_snapshots: Option<u64>,
+    pub opt_max_pool_capacity: Option<u8>,
+    pub warn_only: bool,
+}
+
+impl SnapLimits {
+    pub fn parse_max_pool_capacity(value: &str) -> HttmResult<u8> {
+        match value.trim().trim_end_matches('%').parse::<u8>() {
+            Ok(percent) if percent <= 100 => Ok(percent),
+            _ => {
+                let msg = format!(
+                    "MAX_POOL_CAPACITY must be a percentage between 0 and 100, but the value given was: {:?}",
+                    value
+                );
+                Err(HttmError::new(&msg).into())
+            }
+        }
+    }
+
+    pub fn parse_max_snapshots(value: &str) -> HttmResult<u64> {
+        value.trim().parse::<u64>().map_err(|_err| {
+            let msg = format!(
+                "MAX_SNAPSHOTS must be a whole number, but the value given was: {:?}",
+                value
+            );
+            HttmError::new(&msg).into()
+        })
+    }
+
+    // checked before any snapshot is taken, so a runaway loop of snapshots stops before it fills a pool
+    pub fn check<'a>(&self, datasets: impl IntoIterator<Item = &'a str>) -> HttmResult<()> {
+        let zfs_command = which("zfs").map_err(|_err| {
+            HttmError::new("'zfs' command not found. Make sure the command 'zfs' is in your path.")
+        })?;
+
+        let datasets: BTreeSet<&str> = datasets.into_iter().collect();
+
+        let pools: BTreeSet<&str> = datasets
+            .iter()
+            .map(|dataset| {
+                dataset
+                    .split_once('/')
+                    .map(|(pool, _rest)| pool)
+                    .unwrap_or(dataset)
+            })
+            .collect();
+
+        datasets
+            .iter()
+            .try_for_each(|dataset| self.check_dataset(&zfs_command, dataset))?;
+
+        if let Some(max_pool_capacity) = self.opt_max_pool_capacity {
+            pools
+                .iter()
+                .try_for_each(|pool| self.check_pool(pool, max_pool_capacity))?;
+        }
+
+        Ok(())
+    }
+
+    fn check_dataset(&self, zfs_command: &Path, dataset: &str) -> HttmResult<()> {
+        let properties = Self::command_output(
+            zfs_command,
+            &[
+                "get",
+                "-H",
+                "-p",
+                "-o",
+                "value",
+                "snapshot_count,snapshot_limit",
+                dataset,
+            ],
+        )?;
+
+        let mut values = properties
+            .lines()
+            .map(|value| value.trim().parse::<u64>().ok());
+        let opt_snapshot_count = values.next().flatten();
+        let opt_snapshot_limit = values
+            .next()
+            .flatten()
+            .filter(|limit| *limit != ZFS_NO_LIMIT);
+
+        // the snapshot_count property counts the snapshots of descendants too, and is only
+        // maintained when a limit is set, so a max number of snapshots counts the dataset's own
+        let num_snapshots = Self::command_output(
+            zfs_command,
+            &[
+                "list", "-H", "-t", "snapshot", "-o", "name", "-d", "1", dataset,
+            ],
+        )?
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .count() as u64;
+
+        if let Some(snapshot_limit) = opt_snapshot_limit {
+            let snapshot_count = opt_snapshot_count.unwrap_or(num_snapshots);
+
+            if snapshot_count >= snapshot_limit {
+                let msg = format!(
+                    "Dataset {dataset} has {snapshot_count} snapshots, and has reached its snapshot_limit of {snapshot_limit}."
+                );
+                self.refuse_or_warn("snapshot_limit", dataset, &msg)?;
+            }
+        }
+
+        if let Some(max_snapshots) = self.opt_max_snapshots {
+            if num_snapshots >= max_snapshots {
+                let msg = format!(
+                    "Dataset {dataset} has {num_snapshots} snapshots, and has reached the MAX_SNAPSHOTS threshold of {max_snapshots}."
+                );
+                self.refuse_or_warn("max_snapshots", dataset, &msg)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn check_pool(&self, pool: &str, max_pool_capacity: u8) -> HttmResult<()> {
+        let zpool_command = which("zpool").map_err(|_err| {
+            HttmError::new(
+                "'zpool' command not found. Make sure the command 'zpool' is in your path.",
+            )
+        })?;
+
+        let capacity_output = Self::command_output(
+            &zpool_command,
+            &["list", "-H", "-p", "-o", "capacity", pool],
+        )?;
+
+        let Ok(capacity) = capacity_output.trim().trim_end_matches('%').parse::<u8>() else {
+            let msg = format!("Could not determine the capacity of pool: {pool}");
+            return Err(HttmError::new(&msg).into());
+        };
+
+        if capacity >= max_pool_capacity {
+            let msg = format!(
+                "Pool {pool} is {capacity}% full, and has reached the MAX_POOL_CAPACITY threshold of {max_pool_capacity}%."
+            );
+            self.refuse_or_warn("max_pool_capacity", pool, &msg)?;
+        }
+
+        Ok(())
+    }
+
+    fn refuse_or_warn(&self, code: &'static str, dataset: &str, msg: &str) -> HttmResult<()> {
+        if self.warn_only {
+            Diagnostic::warn(code, format!("{msg}  httm will snapshot anyway."))
+                .with_dataset(Path::new(dataset))
+                .print();
+            return Ok(());
+        }
+
+        let msg = format!("{msg}  httm will not take any snapshot.");
+        Err(HttmError::new(&msg)
+            .with_code(code)
+            .with_dataset(Path::new(dataset))
+            .into())
+    }
+
+    fn command_output(command: &Path, args: &[&str]) -> HttmResult<String> {
+        let process_output = ExecProcess::new(command).args(args).output()?;
+        let stderr_string = std::str::from_utf8(&process_output.stderr)?.trim();
+
+        if !stderr_string.is_empty() {
+            let msg = format!(
+                "httm was unable to check snapshot limits. The command issued the following error: {stderr_string}"
+            );
+            return Err(HttmError::new(&msg).into());
+        }
+
+        Ok(std::str::from_utf8(&process_output.stdout)?.to_owned())
+    }
+}
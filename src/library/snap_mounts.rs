@@ -16,6 +16,7 @@
 // that was distributed with this source code.
 
 use crate::config::generate::PrintMode;
+use crate::library::audit_log::{AuditAction, AuditLog};
 use crate::library::iter_extensions::HttmIter;
 use crate::library::results::{HttmError, HttmResult};
 use crate::library::utility::{date_string, delimiter, print_output_buf, DateFormat};
@@ -23,7 +24,9 @@ use crate::lookup::file_mounts::MountDisplay;
 use crate::lookup::file_mounts::MountsForFiles;
 use crate::parse::mounts::FilesystemType;
 use crate::GLOBAL_CONFIG;
-use std::collections::BTreeMap;
+use crate::library::snap_guard::ZfsAllowPriv;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
 use std::process::Command as ExecProcess;
 use std::time::SystemTime;
 
@@ -31,40 +34,66 @@ pub struct SnapshotMounts;
 
 impl SnapshotMounts {
     pub fn exec(requested_snapshot_suffix: &str) -> HttmResult<()> {
-        let mounts_for_files: MountsForFiles = MountsForFiles::new(&MountDisplay::Target)?;
+        // explicitly requested datasets take the place of datasets inferred from paths
+        let datasets: Vec<String> = match &GLOBAL_CONFIG.opt_datasets {
+            Some(datasets) => datasets.clone(),
+            None => {
+                let mounts_for_files: MountsForFiles = MountsForFiles::new(&MountDisplay::Target)?;
+                Self::datasets_from_mounts(&mounts_for_files)?
+            }
+        };
 
-        Self::snapshot_mounts(&mounts_for_files, requested_snapshot_suffix)
+        Self::snapshot_mounts(&datasets, requested_snapshot_suffix)
     }
 
-    fn snapshot_mounts(
-        mounts_for_files: &MountsForFiles,
-        requested_snapshot_suffix: &str,
-    ) -> HttmResult<()> {
+    fn snapshot_mounts(datasets: &[String], requested_snapshot_suffix: &str) -> HttmResult<()> {
         let zfs_command = which::which("zfs").map_err(|_err| {
             HttmError::new("'zfs' command not found. Make sure the command 'zfs' is in your path.")
         })?;
-        let map_snapshot_names = Self::snapshot_names(mounts_for_files, requested_snapshot_suffix)?;
+        let map_snapshot_names = Self::snapshot_names(datasets, requested_snapshot_suffix)?;
+
+        // check for privileges before taking any snapshot, instead of failing part way through
+        map_snapshot_names
+            .values()
+            .flatten()
+            .filter_map(|snapshot_name| snapshot_name.split_once('@'))
+            .map(|(dataset, _snap)| dataset)
+            .collect::<BTreeSet<&str>>()
+            .into_iter()
+            .try_for_each(|dataset| ZfsAllowPriv::Snapshot.from_fs_name(dataset))?;
+
+        if let Some(snap_limits) = &GLOBAL_CONFIG.opt_snap_limits {
+            snap_limits.check(
+                map_snapshot_names
+                    .values()
+                    .flatten()
+                    .filter_map(|snapshot_name| snapshot_name.split_once('@'))
+                    .map(|(dataset, _snap)| dataset),
+            )?;
+        }
+
+        GLOBAL_CONFIG
+            .hooks
+            .pre_snap(map_snapshot_names.values().flatten())?;
 
         map_snapshot_names.iter().try_for_each(|(_pool_name, snapshot_names)| {
             let mut process_args = vec!["snapshot".to_owned()];
+            if GLOBAL_CONFIG.opt_recursive_datasets {
+                process_args.push("-r".to_owned());
+            }
             process_args.extend_from_slice(snapshot_names);
 
-            let process_output = ExecProcess::new(&zfs_command)
-            .args(&process_args)
-            .output()?;
-            let stderr_string = std::str::from_utf8(&process_output.stderr)?.trim();
-
-            // stderr_string is a string not an error, so here we build an err or output
-            if !stderr_string.is_empty() {
-                let msg = if stderr_string.contains("cannot create snapshots : permission denied") {
-                    "httm must have root privileges to snapshot a filesystem".to_owned()
-                } else {
-                    "httm was unable to take snapshots. The 'zfs' command issued the following error: "
-                    .to_owned()
-                    + stderr_string
-                };
+            let res = Self::take_snapshots(&zfs_command, &process_args);
 
-                Err(HttmError::new(&msg).into())
+            AuditLog::record(
+                AuditAction::Snapshot,
+                [],
+                snapshot_names.iter().map(String::as_str),
+                &res,
+            );
+
+            if let Err(err) = res {
+                Err(err)
             } else {
                 let output_buf: String = snapshot_names
                     .iter()
@@ -87,18 +116,30 @@ impl SnapshotMounts {
         Ok(())
     }
 
-    fn snapshot_names(
-        mounts_for_files: &MountsForFiles,
-        requested_snapshot_suffix: &str,
-    ) -> HttmResult<BTreeMap<String, Vec<String>>> {
-        // all snapshots should have the same timestamp
-        let timestamp = date_string(
-            GLOBAL_CONFIG.requested_utc_offset,
-            &SystemTime::now(),
-            DateFormat::Timestamp,
-        );
+    fn take_snapshots(zfs_command: &Path, process_args: &[String]) -> HttmResult<()> {
+        let process_output = ExecProcess::new(zfs_command)
+            .args(process_args)
+            .output()?;
+        let stderr_string = std::str::from_utf8(&process_output.stderr)?.trim();
+
+        // stderr_string is a string not an error, so here we build an err or output
+        if !stderr_string.is_empty() {
+            let msg = if stderr_string.contains("cannot create snapshots : permission denied") {
+                "httm must have root privileges, or delegated 'zfs allow' privileges, to snapshot a filesystem".to_owned()
+            } else {
+                "httm was unable to take snapshots. The 'zfs' command issued the following error: "
+                .to_owned()
+                + stderr_string
+            };
+
+            return Err(HttmError::new(&msg).into());
+        }
 
-        let vec_snapshot_names: Vec<String> = mounts_for_files
+        Ok(())
+    }
+
+    fn datasets_from_mounts(mounts_for_files: &MountsForFiles) -> HttmResult<Vec<String>> {
+        let datasets: Vec<String> = mounts_for_files
             .iter()
             .flat_map(|prox| prox.datasets_of_interest())
             .map(|mount| {
@@ -130,14 +171,33 @@ impl SnapshotMounts {
                     )),
                 }?;
 
-                let snapshot_name = format!(
+                Ok(dataset.to_string())
+            })
+            .collect::<Result<Vec<String>, HttmError>>()?;
+
+        Ok(datasets)
+    }
+
+    fn snapshot_names(
+        datasets: &[String],
+        requested_snapshot_suffix: &str,
+    ) -> HttmResult<BTreeMap<String, Vec<String>>> {
+        // all snapshots should have the same timestamp
+        let timestamp = date_string(
+            GLOBAL_CONFIG.requested_utc_offset,
+            &SystemTime::now(),
+            DateFormat::Timestamp,
+        );
+
+        let vec_snapshot_names: Vec<String> = datasets
+            .iter()
+            .map(|dataset| {
+                format!(
                     "{}@snap_{}_{}",
                     dataset, timestamp, requested_snapshot_suffix,
-                );
-
-                Ok(snapshot_name)
+                )
             })
-            .collect::<Result<Vec<String>, HttmError>>()?;
+            .collect();
 
         if vec_snapshot_names.is_empty() {
             return Err(HttmError::new(
@@ -0,0 +1,158 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::library::diagnostics::Diagnostic;
+use crate::library::results::{HttmError, HttmResult};
+use crate::GLOBAL_CONFIG;
+use nix::sys::stat::{utimensat, UtimensatFlags};
+use nix::sys::time::TimeSpec;
+use std::fs::FileType;
+use std::os::unix::fs::FileTypeExt;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// fifos, sockets and device nodes have metadata, but no contents of their own: reading a fifo blocks
+// until something writes to it, and reading a device node reads the device, which may never end
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpecialFile {
+    Fifo,
+    Socket,
+    BlockDevice,
+    CharDevice,
+}
+
+impl SpecialFile {
+    pub fn from_file_type(file_type: FileType) -> Option<Self> {
+        if file_type.is_fifo() {
+            Some(SpecialFile::Fifo)
+        } else if file_type.is_socket() {
+            Some(SpecialFile::Socket)
+        } else if file_type.is_block_device() {
+            Some(SpecialFile::BlockDevice)
+        } else if file_type.is_char_device() {
+            Some(SpecialFile::CharDevice)
+        } else {
+            None
+        }
+    }
+
+    // the path itself, a symlink to a special file is not a special file
+    pub fn of(path: &Path) -> Option<Self> {
+        path.symlink_metadata()
+            .ok()
+            .and_then(|md| Self::from_file_type(md.file_type()))
+    }
+
+    // whatever would be read by opening the path, following any symlink
+    pub fn of_target(path: &Path) -> Option<Self> {
+        path.metadata()
+            .ok()
+            .and_then(|md| Self::from_file_type(md.file_type()))
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SpecialFile::Fifo => "fifo",
+            SpecialFile::Socket => "socket",
+            SpecialFile::BlockDevice => "block device",
+            SpecialFile::CharDevice => "character device",
+        }
+    }
+
+    // only metadata may be compared, displayed or restored, never contents
+    pub fn refuse_contents(path: &Path) -> HttmResult<()> {
+        let Some(special_file) = Self::of_target(path) else {
+            return Ok(());
+        };
+
+        let msg = format!(
+            "The path is a {}, which has no contents of its own to read: {:?}",
+            special_file.as_str(),
+            path
+        );
+
+        Err(HttmError::new(&msg)
+            .with_code("special_file")
+            .with_path(path)
+            .into())
+    }
+
+    // a restored device node grants access to whatever device it names, and a restored fifo or socket
+    // is rarely what a program expects to find, so each is only restored when explicitly requested
+    pub fn refuse_restore(path: &Path) -> HttmResult<()> {
+        match Self::of(path) {
+            Some(special_file) if !GLOBAL_CONFIG.opt_restore_special => {
+                let msg = format!(
+                    "httm will not restore a {} unless RESTORE_SPECIAL is specified: {:?}",
+                    special_file.as_str(),
+                    path
+                );
+
+                Err(HttmError::new(&msg)
+                    .with_code("special_file")
+                    .with_path(path)
+                    .into())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    // within a directory restored, special files are skipped, with a warning, instead
+    pub fn should_skip_restore(path: &Path) -> bool {
+        match Self::of(path) {
+            Some(special_file) if !GLOBAL_CONFIG.opt_restore_special => {
+                Diagnostic::warn(
+                    "special_file_skipped",
+                    format!(
+                        "Skipping a {}, as RESTORE_SPECIAL was not specified.",
+                        special_file.as_str()
+                    ),
+                )
+                .with_path(path)
+                .print();
+
+                true
+            }
+            _ => false,
+        }
+    }
+
+    // opening a special file to set its times could block, or touch a device, so times are set by path
+    pub fn set_times(
+        path: &Path,
+        opt_accessed: Option<SystemTime>,
+        modified: SystemTime,
+    ) -> HttmResult<()> {
+        let to_timespec = |time: SystemTime| -> TimeSpec {
+            time.duration_since(UNIX_EPOCH)
+                .map(TimeSpec::from_duration)
+                .unwrap_or(TimeSpec::new(0, 0))
+        };
+
+        let accessed = opt_accessed.map_or(TimeSpec::UTIME_OMIT, to_timespec);
+
+        utimensat(
+            None,
+            path,
+            &accessed,
+            &to_timespec(modified),
+            UtimensatFlags::NoFollowSymlink,
+        )?;
+
+        Ok(())
+    }
+}
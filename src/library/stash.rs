@@ -0,0 +1,141 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::data::paths::{PathData, PathDeconstruction, ZfsSnapPathGuard};
+use crate::library::diagnostics::Diagnostic;
+use crate::library::file_ops::Copy;
+use crate::library::results::{HttmError, HttmResult};
+use crate::library::utility::{date_string, DateFormat};
+use crate::lookup::versions::VersionsMap;
+use crate::GLOBAL_CONFIG;
+use nu_ansi_term::Color::Yellow;
+use std::fs::create_dir_all;
+use std::path::{Component, Path, PathBuf};
+use std::time::SystemTime;
+
+const STASH_DIRECTORY: &str = ".httm_stash";
+
+// before snapshots are destroyed, the stash copies each version which resides upon
+// those snapshots to a quarantine directory, so the user has a grace period in which
+// to recover any version they did not intend to lose
+pub struct Stash {
+    stash_dir: PathBuf,
+}
+
+impl Stash {
+    pub fn new(stash_root: &Path) -> HttmResult<Self> {
+        let timestamp = date_string(
+            GLOBAL_CONFIG.requested_utc_offset,
+            &SystemTime::now(),
+            DateFormat::Timestamp,
+        );
+
+        let stash_dir = stash_root.join(timestamp);
+
+        if stash_dir.exists() {
+            let msg = format!(
+                "httm will not stash to {:?}, as a stash with the same path name already exists.",
+                stash_dir
+            );
+            return Err(HttmError::new(&msg).into());
+        }
+
+        create_dir_all(&stash_dir)?;
+
+        Ok(Self { stash_dir })
+    }
+
+    // default stash location, if the user does not specify one
+    pub fn default_root() -> HttmResult<PathBuf> {
+        if let Some(stash_dir) = std::env::var_os("HTTM_STASH_DIR") {
+            return Ok(PathBuf::from(stash_dir));
+        }
+
+        match std::env::var_os("HOME") {
+            Some(home) => Ok(PathBuf::from(home).join(STASH_DIRECTORY)),
+            None => Err(HttmError::new(
+                "$HOME does not appear to be set in your environment, and no stash directory was specified",
+            )
+            .into()),
+        }
+    }
+
+    pub fn exec(&self, versions_map: &VersionsMap, snap_names: &[&String]) -> HttmResult<()> {
+        let to_stash: Vec<(&PathData, String)> = versions_map
+            .values()
+            .flatten()
+            .filter_map(|snap_pathdata| {
+                ZfsSnapPathGuard::new(snap_pathdata)
+                    .and_then(|spd| spd.source(None))
+                    .map(|snap_name| (snap_pathdata, snap_name.to_string_lossy().into_owned()))
+            })
+            .filter(|(_snap_pathdata, snap_name)| snap_names.contains(&snap_name))
+            .collect();
+
+        if to_stash.is_empty() {
+            Diagnostic::notice(
+                "nothing_stashed",
+                "No file versions reside upon the snapshots to be destroyed.  Nothing was stashed.",
+            )
+            .print();
+            return Ok(());
+        }
+
+        // any failure here is returned before a single snapshot is destroyed
+        to_stash.iter().try_for_each(|(snap_pathdata, snap_name)| {
+            self.stash_version(snap_pathdata, snap_name)
+        })?;
+
+        eprintln!(
+            "{}: {} file version/s -> {:?}",
+            Yellow.paint("Stashed  "),
+            to_stash.len(),
+            self.stash_dir
+        );
+
+        Ok(())
+    }
+
+    fn stash_version(&self, snap_pathdata: &PathData, snap_name: &str) -> HttmResult<()> {
+        let Some(live_path) = ZfsSnapPathGuard::new(snap_pathdata).and_then(|spd| spd.live_path())
+        else {
+            let msg = format!(
+                "Could not determine a live path for snapshot version: {:?}",
+                snap_pathdata.path_buf
+            );
+            return Err(HttmError::new(&msg).into());
+        };
+
+        // <stash dir>/<dataset@snap>/<live path>, so versions of the same path upon
+        // different snapshots never collide
+        let relative_live_path: PathBuf = live_path
+            .components()
+            .filter(|component| matches!(component, Component::Normal(_)))
+            .collect();
+
+        let stash_path = self
+            .stash_dir
+            .join(snap_name.replace('/', "_"))
+            .join(relative_live_path);
+
+        Copy::generate_dst_parent(&stash_path)?;
+
+        // don't preserve attributes here, as preserving the attributes of ancestors requires
+        // the stash directory reside upon a supported dataset, which it need not
+        Copy::recursive(&snap_pathdata.path_buf, &stash_path, false)
+    }
+}
@@ -0,0 +1,248 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::config::generate::ShellDialect;
+use crate::display_versions::emit_shell::ShellCommands;
+use crate::library::results::{HttmError, HttmResult};
+use crate::library::special_file::SpecialFile;
+use crate::GLOBAL_CONFIG;
+use std::borrow::Cow;
+use std::ffi::OsStr;
+use std::io::Write;
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+
+// only the head of a file is sampled to guess at its encoding
+const SAMPLE_LEN: usize = 4096;
+
+const UTF8_BOM: &[u8] = &[0xEF, 0xBB, 0xBF];
+const UTF16LE_BOM: &[u8] = &[0xFF, 0xFE];
+const UTF16BE_BOM: &[u8] = &[0xFE, 0xFF];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextEncoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    Latin1,
+}
+
+impl TextEncoding {
+    pub fn parse(value: &str) -> HttmResult<Option<Self>> {
+        let res = match value.to_ascii_lowercase().replace('_', "-").as_str() {
+            "auto" => None,
+            "utf-8" | "utf8" => Some(TextEncoding::Utf8),
+            "utf-16le" | "utf16le" => Some(TextEncoding::Utf16Le),
+            "utf-16be" | "utf16be" => Some(TextEncoding::Utf16Be),
+            "latin1" | "latin-1" | "iso-8859-1" => Some(TextEncoding::Latin1),
+            _ => {
+                let msg = format!(
+                    "Could not parse encoding: {value:?}.  Valid encodings are: \"auto\", \"utf-8\", \"utf-16le\", \"utf-16be\", and \"latin1\"."
+                );
+                return Err(HttmError::new(&msg).into());
+            }
+        };
+
+        Ok(res)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TextEncoding::Utf8 => "utf-8",
+            TextEncoding::Utf16Le => "utf-16le",
+            TextEncoding::Utf16Be => "utf-16be",
+            TextEncoding::Latin1 => "latin1",
+        }
+    }
+
+    // returns None when the bytes look like binary data, rather than text in any encoding we know
+    pub fn detect(bytes: &[u8]) -> Option<Self> {
+        if bytes.starts_with(UTF8_BOM) {
+            return Some(TextEncoding::Utf8);
+        } else if bytes.starts_with(UTF16LE_BOM) {
+            return Some(TextEncoding::Utf16Le);
+        } else if bytes.starts_with(UTF16BE_BOM) {
+            return Some(TextEncoding::Utf16Be);
+        }
+
+        let sample = &bytes[..bytes.len().min(SAMPLE_LEN)];
+
+        // mostly ASCII text, without a BOM, in UTF-16 is every other byte a NUL,
+        // and, because NUL is valid UTF-8, this must be checked before UTF-8
+        if let Some(utf16) = Self::detect_utf16(sample) {
+            return Some(utf16);
+        }
+
+        if sample.contains(&0u8) {
+            return None;
+        }
+
+        match std::str::from_utf8(sample) {
+            Ok(_) => Some(TextEncoding::Utf8),
+            // our sample may end in the middle of a multi-byte character
+            Err(err) if err.error_len().is_none() => Some(TextEncoding::Utf8),
+            // every byte is a valid Latin-1 character, so text which is not UTF-8, and has no NULs, is Latin-1
+            Err(_) => Some(TextEncoding::Latin1),
+        }
+    }
+
+    fn detect_utf16(sample: &[u8]) -> Option<Self> {
+        let pairs = sample.len() / 2;
+
+        if pairs == 0 {
+            return None;
+        }
+
+        let (even_nuls, odd_nuls) =
+            sample
+                .chunks_exact(2)
+                .fold((0usize, 0usize), |(even, odd), pair| {
+                    (
+                        even + (pair[0] == 0) as usize,
+                        odd + (pair[1] == 0) as usize,
+                    )
+                });
+
+        // allow for a few non-ASCII characters, but not for NULs on both sides
+        if odd_nuls * 2 > pairs && even_nuls * 10 < pairs {
+            Some(TextEncoding::Utf16Le)
+        } else if even_nuls * 2 > pairs && odd_nuls * 10 < pairs {
+            Some(TextEncoding::Utf16Be)
+        } else {
+            None
+        }
+    }
+
+    pub fn decode<'a>(&self, bytes: &'a [u8]) -> Cow<'a, str> {
+        match self {
+            TextEncoding::Utf8 => {
+                String::from_utf8_lossy(bytes.strip_prefix(UTF8_BOM).unwrap_or(bytes))
+            }
+            TextEncoding::Utf16Le => {
+                let bytes = bytes.strip_prefix(UTF16LE_BOM).unwrap_or(bytes);
+
+                let units = bytes
+                    .chunks_exact(2)
+                    .map(|pair| u16::from_le_bytes([pair[0], pair[1]]));
+
+                Cow::Owned(
+                    char::decode_utf16(units)
+                        .map(|res| res.unwrap_or(char::REPLACEMENT_CHARACTER))
+                        .collect(),
+                )
+            }
+            TextEncoding::Utf16Be => {
+                let bytes = bytes.strip_prefix(UTF16BE_BOM).unwrap_or(bytes);
+
+                let units = bytes
+                    .chunks_exact(2)
+                    .map(|pair| u16::from_be_bytes([pair[0], pair[1]]));
+
+                Cow::Owned(
+                    char::decode_utf16(units)
+                        .map(|res| res.unwrap_or(char::REPLACEMENT_CHARACTER))
+                        .collect(),
+                )
+            }
+            // each Latin-1 byte is the Unicode code point of the same value
+            TextEncoding::Latin1 => Cow::Owned(bytes.iter().map(|byte| *byte as char).collect()),
+        }
+    }
+
+    // the encoding requested by the user, or our best guess at the encoding of these bytes
+    pub fn requested_or_detected(bytes: &[u8]) -> Option<Self> {
+        GLOBAL_CONFIG.opt_encoding.or_else(|| Self::detect(bytes))
+    }
+}
+
+// prints a file transcoded to UTF-8, used internally by the preview and compare scripts,
+// so that versions of a file in another encoding are not displayed as mojibake
+pub struct Transcode;
+
+impl Transcode {
+    // the preview and compare scripts run "httm --transcode=FILE" once for every file displayed, so it is
+    // dispatched from the raw arguments, before the config, and its enumeration of every dataset, is built
+    pub fn from_args() -> Option<HttmResult<()>> {
+        let mut opt_path: Option<PathBuf> = None;
+        let mut opt_encoding_value: Option<String> = None;
+
+        std::env::args_os()
+            .skip(1)
+            .take_while(|arg| arg != "--")
+            .for_each(|arg| {
+                let bytes = arg.as_bytes();
+
+                if let Some(path) = bytes.strip_prefix(b"--transcode=") {
+                    opt_path = Some(PathBuf::from(OsStr::from_bytes(path)));
+                } else if let Some(value) = bytes.strip_prefix(b"--encoding=") {
+                    opt_encoding_value = Some(String::from_utf8_lossy(value).into_owned());
+                }
+            });
+
+        let path = opt_path?;
+
+        let res = opt_encoding_value
+            .as_deref()
+            .map_or(Ok(None), TextEncoding::parse)
+            .and_then(|opt_encoding| Self::exec(&path, opt_encoding));
+
+        Some(res)
+    }
+
+    pub fn exec(path: &Path, opt_encoding: Option<TextEncoding>) -> HttmResult<()> {
+        let mut stdout = std::io::stdout().lock();
+
+        // a preview of a special file describes it, instead of waiting forever upon its contents
+        if let Some(special_file) = SpecialFile::of_target(path) {
+            writeln!(
+                stdout,
+                "{:?} is a {}, which has no contents of its own to display.",
+                path,
+                special_file.as_str()
+            )?;
+
+            return Ok(());
+        }
+
+        let bytes = std::fs::read(path)?;
+
+        match opt_encoding.or_else(|| TextEncoding::detect(&bytes)) {
+            // binary data, and UTF-8 which needs no transcoding, are written as is, like "cat"
+            None | Some(TextEncoding::Utf8) => stdout.write_all(&bytes)?,
+            Some(encoding) => stdout.write_all(encoding.decode(&bytes).as_bytes())?,
+        }
+
+        stdout.flush()?;
+
+        Ok(())
+    }
+
+    // the command which the preview and compare scripts use in place of "cat", the path to be transcoded is simply appended
+    pub fn command() -> HttmResult<String> {
+        let httm_command = std::env::current_exe()?;
+
+        let encoding = GLOBAL_CONFIG
+            .opt_encoding
+            .map(|encoding| encoding.as_str())
+            .unwrap_or("auto");
+
+        Ok(format!(
+            "{} --encoding={encoding} --transcode=",
+            ShellCommands::quote(&ShellDialect::Bash, httm_command.as_os_str().as_bytes())
+        ))
+    }
+}
@@ -0,0 +1,105 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::library::results::{HttmError, HttmResult};
+use crate::library::utility::parse_bytes;
+use crate::GLOBAL_CONFIG;
+use once_cell::sync::Lazy;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+// a single bucket is shared by all threads, so the limit applies to httm as a whole
+static THROTTLE: Lazy<Option<Throttle>> =
+    Lazy::new(|| GLOBAL_CONFIG.opt_bwlimit.map(Throttle::new));
+
+struct TokenBucket {
+    // tokens may go negative, which is a debt the next consumer must wait out
+    tokens: f64,
+    last_refill: Instant,
+}
+
+pub struct Throttle {
+    bytes_per_sec: f64,
+    bucket: Mutex<TokenBucket>,
+}
+
+impl Throttle {
+    fn new(bytes_per_sec: u64) -> Self {
+        let bytes_per_sec = bytes_per_sec as f64;
+
+        Self {
+            bytes_per_sec,
+            bucket: Mutex::new(TokenBucket {
+                tokens: bytes_per_sec,
+                last_refill: Instant::now(),
+            }),
+        }
+    }
+
+    pub fn is_enabled() -> bool {
+        THROTTLE.is_some()
+    }
+
+    // blocks the calling thread until the bytes requested fit within the rate limit
+    #[inline]
+    pub fn consume(amt: usize) {
+        let Some(throttle) = THROTTLE.as_ref() else {
+            return;
+        };
+
+        let opt_wait = {
+            let mut bucket = match throttle.bucket.lock() {
+                Ok(bucket) => bucket,
+                Err(poisoned) => poisoned.into_inner(),
+            };
+
+            let now = Instant::now();
+            let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
+
+            // allow a burst of at most one second's worth of tokens
+            bucket.tokens =
+                (bucket.tokens + elapsed * throttle.bytes_per_sec).min(throttle.bytes_per_sec);
+            bucket.last_refill = now;
+            bucket.tokens -= amt as f64;
+
+            if bucket.tokens < 0.0 {
+                Some(Duration::from_secs_f64(
+                    -bucket.tokens / throttle.bytes_per_sec,
+                ))
+            } else {
+                None
+            }
+        };
+
+        if let Some(wait) = opt_wait {
+            std::thread::sleep(wait);
+        }
+    }
+
+    // parses a rate like "500K", "10M", "1.5G" or "4096" into bytes per second, as any other quantity of bytes
+    pub fn parse_rate(value: &str) -> HttmResult<u64> {
+        match parse_bytes(value) {
+            Ok(rate) if rate >= 1 => Ok(rate),
+            _ => {
+                let msg = format!(
+                    "Rate limit must be a number of bytes per second greater than zero, with an optional K, M, G, or T suffix: {value:?}"
+                );
+                Err(HttmError::new(&msg).into())
+            }
+        }
+    }
+}
@@ -18,6 +18,8 @@
 use crate::config::generate::PrintMode;
 use crate::data::paths::{BasicDirEntryInfo, PathData, PathMetadata, PHANTOM_DATE};
 use crate::data::selection::SelectionCandidate;
+use crate::library::output_file::OutputFile;
+use crate::library::redact::Redaction;
 use crate::library::results::{HttmError, HttmResult};
 
 use crate::parse::mounts::FilesystemType;
@@ -28,10 +30,13 @@ use nu_ansi_term::Style as AnsiTermStyle;
 use number_prefix::NumberPrefix;
 use once_cell::sync::Lazy;
 use std::borrow::Cow;
-use std::fs::FileType;
-use std::io::Write;
+use std::collections::hash_map::RandomState;
+use std::fs::{File, FileType, OpenOptions};
+use std::hash::{BuildHasher, Hasher};
+use std::io::{ErrorKind, Write};
 use std::iter::Iterator;
 use std::ops::Deref;
+use std::os::unix::fs::OpenOptionsExt;
 use std::path::{Path, PathBuf};
 use std::time::SystemTime;
 use time::{format_description, OffsetDateTime, UtcOffset};
@@ -71,6 +76,82 @@ pub fn make_tmp_path(path: &Path) -> PathBuf {
     PathBuf::from(res)
 }
 
+// another user may create a file of a predictable name within a shared directory first,
+// as a symlink to some file of ours, so the name is random, and the file must be newly created
+pub fn create_unique_file(dir: &Path, prefix: &str, suffix: &str) -> HttmResult<(PathBuf, File)> {
+    const MAX_ATTEMPTS: usize = 100;
+
+    for _ in 0..MAX_ATTEMPTS {
+        let random = RandomState::new().build_hasher().finish();
+        let path = dir.join(format!("{prefix}{random:016x}{suffix}"));
+
+        match OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .mode(0o600)
+            .open(&path)
+        {
+            Ok(file) => return Ok((path, file)),
+            Err(err) if err.kind() == ErrorKind::AlreadyExists => continue,
+            Err(err) => return Err(err.into()),
+        }
+    }
+
+    let msg = format!(
+        "Could not create a uniquely named temporary file within: {:?}",
+        dir
+    );
+    Err(HttmError::new(&msg).into())
+}
+
+// parses a quantity of bytes, like "512", "10K", "1.5M", "2GiB" or "1TB", where units are always powers of 1024,
+// so every option which takes a quantity of bytes, like MIN_SIZE or BWLIMIT, accepts the same
+pub fn parse_bytes(value: &str) -> HttmResult<u64> {
+    let value = value.trim();
+
+    let split_idx = value
+        .find(|c: char| c.is_ascii_alphabetic())
+        .unwrap_or(value.len());
+
+    let (number, unit) = value.split_at(split_idx);
+
+    let multiplier: u64 = match unit.to_ascii_uppercase().as_str() {
+        "" | "B" => 1,
+        "K" | "KB" | "KIB" => 1024,
+        "M" | "MB" | "MIB" => 1024u64.pow(2),
+        "G" | "GB" | "GIB" => 1024u64.pow(3),
+        "T" | "TB" | "TIB" => 1024u64.pow(4),
+        _ => {
+            let msg = format!("Unit of bytes is not recognized: {value:?}");
+            return Err(HttmError::new(&msg).into());
+        }
+    };
+
+    match number
+        .trim()
+        .parse::<f64>()
+        .map(|number| number * multiplier as f64)
+    {
+        Ok(bytes) if bytes.is_finite() && bytes >= 0.0 => Ok(bytes as u64),
+        _ => {
+            let msg = format!(
+                "Value must be a number of bytes, with an optional K, M, G, or T suffix: {value:?}"
+            );
+            Err(HttmError::new(&msg).into())
+        }
+    }
+}
+
+// httm's persistent caches live in "$XDG_CACHE_HOME/httm", or "$HOME/.cache/httm"
+pub fn httm_cache_dir() -> Option<PathBuf> {
+    let cache_dir = match std::env::var_os("XDG_CACHE_HOME") {
+        Some(value) if !value.is_empty() => PathBuf::from(value),
+        _ => PathBuf::from(std::env::var_os("HOME")?).join(".cache"),
+    };
+
+    Some(cache_dir.join("httm"))
+}
+
 pub fn find_common_path<I, P>(paths: I) -> Option<PathBuf>
 where
     I: IntoIterator<Item = P>,
@@ -101,6 +182,18 @@ fn cmp_path<A: AsRef<Path>, B: AsRef<Path>>(a: A, b: B) -> Option<PathBuf> {
 }
 
 pub fn print_output_buf(output_buf: &str) -> HttmResult<()> {
+    // with REDACT, personal names are hashed before output is ever written, to a file or to stdout
+    let output_buf: &str = &Redaction::apply(output_buf);
+
+    // results may be written to an output file instead of, or, with TEE, as well as, stdout
+    if let Some(path) = &GLOBAL_CONFIG.opt_output {
+        OutputFile::write(output_buf, path)?;
+
+        if !GLOBAL_CONFIG.opt_tee {
+            return Ok(());
+        }
+    }
+
     // mutex keeps threads from writing over each other
     let out = std::io::stdout();
     let mut out_locked = out.lock();
@@ -252,6 +345,7 @@ pub fn fs_type_from_hidden_dir(dataset_mount: &Path) -> Option<FilesystemType> {
 pub enum DateFormat {
     Display,
     Timestamp,
+    Custom(String),
 }
 
 static DATE_FORMAT_DISPLAY: &str =
@@ -277,25 +371,117 @@ pub fn date_string(
         return match &date_format {
             DateFormat::Timestamp => raw_string + "_UTC",
             DateFormat::Display => raw_string + " UTC",
+            // a custom format may include its own offset, like "[offset_hour]"
+            DateFormat::Custom(_) => raw_string,
         };
     }
 
     raw_string
 }
 
-fn date_string_format<'a>(format: &DateFormat) -> &'a str {
+fn date_string_format(format: &DateFormat) -> &str {
     match format {
         DateFormat::Display => DATE_FORMAT_DISPLAY,
         DateFormat::Timestamp => DATE_FORMAT_TIMESTAMP,
+        DateFormat::Custom(custom) => custom,
     }
 }
 
 pub fn display_human_size(size: u64) -> String {
-    let size = size as f64;
+    NumberFormat::default().display_size(size)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumberSeparators {
+    thousands: char,
+    decimal: char,
+}
+
+impl NumberSeparators {
+    // the C library's locale database is frequently not installed, so we keep our own small table
+    // of the separators used by each language, ex: "de_DE.UTF-8" -> '.' and ','
+    pub fn from_locale(locale: &str) -> Self {
+        let (language, territory) = locale
+            .split(['.', '@'])
+            .next()
+            .unwrap_or_default()
+            .split_once('_')
+            .unwrap_or((locale, ""));
+
+        let (thousands, decimal) = match (language, territory) {
+            ("de" | "it", "CH") => ('\'', '.'),
+            (
+                "de" | "nl" | "it" | "es" | "pt" | "da" | "id" | "tr" | "el" | "ro" | "hr" | "sl"
+                | "sr",
+                _,
+            ) => ('.', ','),
+            (
+                "fr" | "ru" | "pl" | "cs" | "sk" | "fi" | "sv" | "nb" | "nn" | "no" | "uk" | "hu"
+                | "bg" | "lt" | "lv" | "et",
+                _,
+            ) => ('\u{a0}', ','),
+            _ => (',', '.'),
+        };
+
+        Self { thousands, decimal }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NumberFormat {
+    pub opt_si: bool,
+    pub opt_separators: Option<NumberSeparators>,
+}
+
+impl NumberFormat {
+    pub fn display_size(&self, size: u64) -> String {
+        let size = size as f64;
+
+        let number_prefix = if self.opt_si {
+            NumberPrefix::decimal(size)
+        } else {
+            NumberPrefix::binary(size)
+        };
+
+        match number_prefix {
+            NumberPrefix::Standalone(bytes) => {
+                format!("{} bytes", self.localize(&format!("{bytes}")))
+            }
+            NumberPrefix::Prefixed(prefix, n) => {
+                format!("{} {prefix}B", self.localize(&format!("{n:.1}")))
+            }
+        }
+    }
+
+    fn localize(&self, number: &str) -> String {
+        let Some(separators) = &self.opt_separators else {
+            return number.to_owned();
+        };
+
+        let (integer, opt_fraction) = match number.split_once('.') {
+            Some((integer, fraction)) => (integer, Some(fraction)),
+            None => (number, None),
+        };
+
+        let num_digits = integer.chars().count();
+
+        let mut res: String = integer
+            .chars()
+            .enumerate()
+            .flat_map(|(idx, digit)| {
+                let opt_separator =
+                    (idx > 0 && (num_digits - idx) % 3 == 0).then_some(separators.thousands);
+
+                opt_separator.into_iter().chain(std::iter::once(digit))
+            })
+            .collect();
+
+        if let Some(fraction) = opt_fraction {
+            res.push(separators.decimal);
+            res.push_str(fraction);
+        }
 
-    match NumberPrefix::binary(size) {
-        NumberPrefix::Standalone(bytes) => format!("{bytes} bytes"),
-        NumberPrefix::Prefixed(prefix, n) => format!("{n:.1} {prefix}B"),
+        res
     }
 }
 
@@ -334,6 +520,7 @@ impl<T: AsRef<Path>> ComparePathMetadata for T {
         opt_md.map(|md| PathMetadata {
             size: md.len(),
             modify_time: md.modified().unwrap_or(PHANTOM_DATE),
+            opt_identity: None,
         })
     }
 
@@ -0,0 +1,340 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+use crate::data::paths::PathData;
+use crate::display_map::datasets::DatasetInventory;
+use crate::library::diagnostics::Diagnostic;
+use crate::library::results::{HttmError, HttmResult};
+use crate::library::utility::{date_string, make_tmp_path};
+use crate::GLOBAL_CONFIG;
+use hashbrown::HashMap;
+use nix::fcntl::{Flock, FlockArg};
+use once_cell::sync::Lazy;
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs::{create_dir_all, read_to_string, rename, write, File, OpenOptions};
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const NOTES_FILE: &str = "notes.json";
+const LOCK_SUFFIX: &str = ".lock";
+// 16 hex digits, unlike a version ID, because a notes file may be shared, and may grow to hold notes upon many versions
+const NOTE_KEY_HASH_LEN: usize = 16;
+
+// notes are read once, when first displayed, and a notes file which cannot be read is warned about, then ignored,
+// so a bad notes file never prevents a listing
+pub static VERSION_NOTES: Lazy<VersionNotes> = Lazy::new(|| {
+    VersionNotes::notes_file()
+        .and_then(|notes_file| VersionNotes::read(&notes_file))
+        .unwrap_or_else(|err| {
+            Diagnostic::warn(
+                "notes_unreadable",
+                format!("httm could not read the version notes file: {err}"),
+            )
+            .print();
+            VersionNotes::default()
+        })
+});
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionNote {
+    pub snapshot: String,
+    pub path: PathBuf,
+    pub note: String,
+    pub opt_author: Option<String>,
+    pub updated: SystemTime,
+}
+
+impl VersionNote {
+    fn from_value(value: &Value) -> Option<Self> {
+        let string = |key: &str| value.get(key).and_then(Value::as_str).map(str::to_owned);
+
+        Some(Self {
+            snapshot: string("snapshot")?,
+            path: PathBuf::from(string("path")?),
+            note: string("note")?,
+            opt_author: string("author"),
+            updated: value
+                .get("updated")
+                .and_then(Value::as_u64)
+                .map(|secs| UNIX_EPOCH + Duration::from_secs(secs))
+                .unwrap_or(UNIX_EPOCH),
+        })
+    }
+
+    fn to_value(&self) -> Value {
+        serde_json::json!({
+            "snapshot": self.snapshot,
+            "path": self.path,
+            "note": self.note,
+            "author": self.opt_author,
+            "updated": self.updated
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs())
+                .unwrap_or_default(),
+        })
+    }
+
+    // the one line form, displayed in listings and the preview window, so a note's newlines and tabs never break a line
+    pub fn display(&self) -> String {
+        let note: String = self
+            .note
+            .chars()
+            .map(|c| if c.is_control() { ' ' } else { c })
+            .collect();
+
+        let updated = date_string(
+            GLOBAL_CONFIG.requested_utc_offset,
+            &self.updated,
+            GLOBAL_CONFIG.display_date_format(),
+        );
+
+        match &self.opt_author {
+            Some(author) => format!("{note} ({author}, {updated})"),
+            None => format!("{note} ({updated})"),
+        }
+    }
+}
+
+// free text notes, attached to specific versions, so those triaging an incident can mark a version as,
+// for instance, "this is the good one".  notes are kept in a single JSON file, which may be shared,
+// and are keyed by the snapshot's name, and a hash of the dataset and the path relative to the dataset,
+// so that the same version has the same key on any machine which mounts the same dataset
+#[derive(Debug, Clone, Default)]
+pub struct VersionNotes {
+    // key: "<snapshot name>:<hash>"
+    inner: BTreeMap<String, VersionNote>,
+    // key: snap mount, value: the mount of its dataset
+    snap_mounts: HashMap<PathBuf, PathBuf>,
+}
+
+impl VersionNotes {
+    // "$HTTM_NOTES_FILE", or "$XDG_DATA_HOME/httm/notes.json", or "$HOME/.local/share/httm/notes.json"
+    pub fn notes_file() -> HttmResult<PathBuf> {
+        if let Some(notes_file) = std::env::var_os("HTTM_NOTES_FILE") {
+            return Ok(PathBuf::from(notes_file));
+        }
+
+        let data_dir = match std::env::var_os("XDG_DATA_HOME") {
+            Some(value) if !value.is_empty() => PathBuf::from(value),
+            _ => match std::env::var_os("HOME") {
+                Some(home) => PathBuf::from(home).join(".local").join("share"),
+                None => {
+                    return Err(HttmError::new(
+                        "$HOME does not appear to be set in your environment, and neither XDG_DATA_HOME nor HTTM_NOTES_FILE is set",
+                    )
+                    .into())
+                }
+            },
+        };
+
+        Ok(data_dir.join("httm").join(NOTES_FILE))
+    }
+
+    pub fn read(notes_file: &Path) -> HttmResult<Self> {
+        let contents = match read_to_string(notes_file) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(err) => return Err(err.into()),
+        };
+
+        let value: Value = serde_json::from_str(&contents)?;
+
+        let Some(object) = value.as_object() else {
+            let msg = format!(
+                "Notes file {:?} is not a JSON object of version notes.",
+                notes_file
+            );
+            return Err(HttmError::new(&msg).into());
+        };
+
+        let inner: BTreeMap<String, VersionNote> = object
+            .iter()
+            .filter_map(|(key, value)| {
+                VersionNote::from_value(value).map(|note| (key.clone(), note))
+            })
+            .collect();
+
+        // no need to index the snap mounts, if there are no notes to look up
+        let snap_mounts = if inner.is_empty() {
+            HashMap::new()
+        } else {
+            Self::index_snap_mounts()
+        };
+
+        Ok(Self { inner, snap_mounts })
+    }
+
+    fn index_snap_mounts() -> HashMap<PathBuf, PathBuf> {
+        GLOBAL_CONFIG
+            .dataset_collection
+            .map_of_snaps
+            .iter()
+            .flat_map(|(mount, snap_mounts)| {
+                snap_mounts
+                    .iter()
+                    .map(move |snap_mount| (snap_mount.clone(), mount.clone()))
+            })
+            .collect()
+    }
+
+    fn save(&self, notes_file: &Path) -> HttmResult<()> {
+        if let Some(parent) = notes_file.parent() {
+            create_dir_all(parent)?;
+        }
+
+        let object: Map<String, Value> = self
+            .inner
+            .iter()
+            .map(|(key, note)| (key.clone(), note.to_value()))
+            .collect();
+
+        // write to a tmp file then rename, so another reader of a shared notes file never observes a truncated file
+        let tmp_path = make_tmp_path(notes_file);
+        write(&tmp_path, serde_json::to_string_pretty(&object)? + "\n")?;
+        rename(&tmp_path, notes_file)?;
+
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    pub fn get(&self, snap: &Path) -> Option<&VersionNote> {
+        if self.inner.is_empty() {
+            return None;
+        }
+
+        let (key, _snap_name) = self.key(snap)?;
+
+        self.inner.get(&key)
+    }
+
+    // returns the key, and the name of the snapshot
+    fn key(&self, snap: &Path) -> Option<(String, String)> {
+        let (snap_mount, mount) = snap.ancestors().find_map(|ancestor| {
+            self.snap_mounts
+                .get_key_value(ancestor)
+                .map(|(snap_mount, mount)| (snap_mount.as_path(), mount.as_path()))
+        })?;
+
+        let relative_path = snap.strip_prefix(snap_mount).ok()?;
+
+        let source = GLOBAL_CONFIG
+            .dataset_collection
+            .map_of_datasets
+            .get(mount)
+            .map(|dataset_info| dataset_info.source.as_os_str())
+            .unwrap_or_else(|| mount.as_os_str());
+
+        let mut hasher = Sha256::new();
+        hasher.update(source.as_bytes());
+        hasher.update([0u8]);
+        hasher.update(relative_path.as_os_str().as_bytes());
+
+        let hash: String = hasher
+            .finalize()
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect::<String>()
+            .split_at(NOTE_KEY_HASH_LEN)
+            .0
+            .to_owned();
+
+        let snap_name = DatasetInventory::snap_name(snap_mount);
+
+        Some((format!("{snap_name}:{hash}"), snap_name))
+    }
+
+    // a notes file may be shared, so a note is set under a lock, which is held from the read until the write,
+    // so that two users setting notes at once never lose one of them.  The lock is upon a file beside
+    // the notes file, as the notes file itself is replaced by a rename upon every write.
+    fn lock(notes_file: &Path) -> HttmResult<Flock<File>> {
+        if let Some(parent) = notes_file.parent() {
+            create_dir_all(parent)?;
+        }
+
+        let mut lock_path = notes_file.as_os_str().to_os_string();
+        lock_path.push(LOCK_SUFFIX);
+
+        let lock_file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&lock_path)?;
+
+        Flock::lock(lock_file, FlockArg::LockExclusive).map_err(|(_, errno)| {
+            let msg = format!("Could not obtain a lock upon {:?}: {errno}", lock_path);
+            HttmError::new(&msg).into()
+        })
+    }
+
+    // an empty note removes any note upon the version
+    pub fn set(live: &PathData, snap: &PathData, note: &str) -> HttmResult<Option<VersionNote>> {
+        let notes_file = Self::notes_file()?;
+
+        let _lock = Self::lock(&notes_file)?;
+
+        let mut notes = Self::read(&notes_file)?;
+
+        // a notes file without notes has no index of snap mounts
+        if notes.snap_mounts.is_empty() {
+            notes.snap_mounts = Self::index_snap_mounts();
+        }
+
+        let Some((key, snap_name)) = notes.key(&snap.path_buf) else {
+            let msg = format!(
+                "httm could not determine the snapshot of version: {:?}",
+                snap.path_buf
+            );
+            return Err(HttmError::new(&msg).into());
+        };
+
+        let note = note.trim();
+
+        let res = if note.is_empty() {
+            notes.inner.remove(&key);
+            None
+        } else {
+            let version_note = VersionNote {
+                snapshot: snap_name,
+                path: live.path_buf.clone(),
+                note: note.to_owned(),
+                opt_author: std::env::var("USER").ok().filter(|user| !user.is_empty()),
+                updated: SystemTime::now(),
+            };
+
+            notes.inner.insert(key, version_note.clone());
+            Some(version_note)
+        };
+
+        notes.save(&notes_file)?;
+
+        Ok(res)
+    }
+
+    // used internally by the preview script, which prints the note upon a version, if any, above its contents
+    pub fn exec(snap: &Path) -> HttmResult<()> {
+        if let Some(note) = VERSION_NOTES.get(snap) {
+            println!("NOTE: {}\n--", note.display());
+        }
+
+        Ok(())
+    }
+}
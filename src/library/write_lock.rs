@@ -0,0 +1,183 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::library::diagnostics::Diagnostic;
+use crate::library::results::{HttmError, HttmResult};
+use nix::errno::Errno;
+use nix::fcntl::{Flock, FlockArg};
+use std::fs::{read_dir, read_to_string, File};
+use std::ops::{Deref, DerefMut};
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+
+// the access mode bits of the "flags" line of /proc/<pid>/fdinfo/<fd>, in octal
+const O_ACCMODE: u32 = 0o3;
+const O_RDONLY: u32 = 0o0;
+
+// another process which has the live file open, as "fuser" would report it
+struct OtherOpener {
+    pid: u32,
+    name: String,
+    is_writer: bool,
+}
+
+// an advisory lock upon the very file which a restore overwrites, held for as long as it is written or replaced,
+// so that two restores, or any other program which respects flock(2), never write the file at once
+pub struct WriteLock {
+    flock: Flock<File>,
+}
+
+impl Deref for WriteLock {
+    type Target = File;
+
+    fn deref(&self) -> &Self::Target {
+        &self.flock
+    }
+}
+
+impl DerefMut for WriteLock {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.flock
+    }
+}
+
+impl WriteLock {
+    // the lock is taken upon the open file which is then written, never upon a path,
+    // which a rename could point to another inode
+    pub fn acquire(dst_file: File, dst: &Path) -> HttmResult<Self> {
+        if Self::may_have_other_openers(&dst_file) {
+            let md = dst_file.metadata()?;
+            Self::warn_other_openers(dst, md.dev(), md.ino());
+        }
+
+        let flock = Flock::lock(dst_file, FlockArg::LockExclusiveNonblock).map_err(
+            |(_, errno)| match errno {
+                Errno::EWOULDBLOCK => {
+                    let msg = format!(
+                        "httm will not overwrite {:?}, as another process holds a lock upon the file. \
+                        Stop the process which uses the file, or restore to another location.",
+                        dst
+                    );
+                    HttmError::new(&msg)
+                        .with_code("destination_locked")
+                        .with_path(dst)
+                }
+                _ => {
+                    let msg = format!("Could not obtain a lock upon {:?}: {errno}", dst);
+                    HttmError::new(&msg).with_path(dst)
+                }
+            },
+        )?;
+
+        Ok(Self { flock })
+    }
+
+    // the kernel refuses a write lease upon a file which any other file description has open,
+    // so a lease, taken and at once released, tells us cheaply whether /proc is worth a search
+    #[cfg(target_os = "linux")]
+    fn may_have_other_openers(dst_file: &File) -> bool {
+        use std::os::fd::AsRawFd;
+
+        let fd = dst_file.as_raw_fd();
+
+        match unsafe { libc::fcntl(fd, libc::F_SETLEASE, libc::F_WRLCK) } {
+            0 => {
+                unsafe { libc::fcntl(fd, libc::F_SETLEASE, libc::F_UNLCK) };
+                false
+            }
+            // EAGAIN, the file is open elsewhere, but also EACCES, as when we do not own the file,
+            // or EINVAL, as upon a filesystem which does not support leases
+            _ => true,
+        }
+    }
+
+    // without a /proc, as on most systems other than Linux, there is no one to warn about
+    #[cfg(not(target_os = "linux"))]
+    fn may_have_other_openers(_dst_file: &File) -> bool {
+        false
+    }
+
+    // an open file is not locked unless its owner chose to lock it, so we also look for processes
+    // which merely have the file open, like a running service, whose state a restore would silently corrupt
+    fn warn_other_openers(dst: &Path, dev: u64, ino: u64) {
+        Self::other_openers(dev, ino).iter().for_each(|opener| {
+            let access = if opener.is_writer {
+                "open for writing"
+            } else {
+                "open"
+            };
+
+            Diagnostic::warn(
+                "destination_in_use",
+                format!(
+                    "{:?} is {access} by another process, {} (pid {}). Restoring over a file in use may corrupt the state of that process.",
+                    dst, opener.name, opener.pid
+                ),
+            )
+            .with_path(dst)
+            .print();
+        });
+    }
+
+    fn other_openers(dev: u64, ino: u64) -> Vec<OtherOpener> {
+        let Ok(proc_dir) = read_dir("/proc") else {
+            return Vec::new();
+        };
+
+        let own_pid = std::process::id();
+
+        proc_dir
+            .flatten()
+            .filter_map(|entry| entry.file_name().to_str()?.parse::<u32>().ok())
+            .filter(|pid| *pid != own_pid)
+            .filter_map(|pid| {
+                // we may only read the fds of processes of our own user, unless we are root
+                let fds = read_dir(format!("/proc/{pid}/fd")).ok()?;
+
+                let opt_is_writer = fds
+                    .flatten()
+                    .filter(|fd| {
+                        fd.path()
+                            .metadata()
+                            .is_ok_and(|md| md.dev() == dev && md.ino() == ino)
+                    })
+                    .map(|fd| Self::is_writer(pid, &fd.file_name().to_string_lossy()))
+                    .reduce(|acc, is_writer| acc || is_writer);
+
+                opt_is_writer.map(|is_writer| OtherOpener {
+                    pid,
+                    name: read_to_string(format!("/proc/{pid}/comm"))
+                        .map(|comm| comm.trim_end().to_owned())
+                        .unwrap_or_else(|_| "unknown".to_owned()),
+                    is_writer,
+                })
+            })
+            .collect()
+    }
+
+    fn is_writer(pid: u32, fd: &str) -> bool {
+        read_to_string(format!("/proc/{pid}/fdinfo/{fd}"))
+            .ok()
+            .and_then(|fdinfo| {
+                fdinfo
+                    .lines()
+                    .find_map(|line| line.strip_prefix("flags:"))
+                    .and_then(|flags| u32::from_str_radix(flags.trim(), 8).ok())
+            })
+            .is_some_and(|flags| flags & O_ACCMODE != O_RDONLY)
+    }
+}
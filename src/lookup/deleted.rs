@@ -17,6 +17,7 @@
 
 use crate::data::paths::{BasicDirEntryInfo, PathData};
 use crate::library::results::HttmResult;
+use crate::lookup::snap_index::{DirIndex, SnapIndex, SNAP_INDEX};
 use crate::lookup::versions::{ProximateDatasetAndOptAlts, RelativePathAndSnapMounts};
 use hashbrown::{HashMap, HashSet};
 use std::ffi::OsString;
@@ -75,8 +76,27 @@ impl DeletedFiles {
             .map(|dir_entry| dir_entry.file_name())
             .collect();
 
-        let unique_snap_filenames: HashMap<OsString, BasicDirEntryInfo> =
-            Self::unique_snap_filenames(search_bundle.snap_mounts, search_bundle.relative_path);
+        let opt_dir_index = Some(search_bundle)
+            .filter(|_| SnapIndex::is_indexed_dir(requested_dir))
+            .and_then(|search_bundle| {
+                SNAP_INDEX.get(
+                    search_bundle.dataset_of_interest,
+                    search_bundle.relative_path,
+                    search_bundle.snap_mounts,
+                )
+            });
+
+        let unique_snap_filenames: HashMap<OsString, BasicDirEntryInfo> = match opt_dir_index {
+            // an index which lacks some snapshot is no list of every file name upon every snapshot
+            Some(dir_index) if dir_index.is_complete() => Self::indexed_snap_filenames(
+                &dir_index,
+                search_bundle.relative_path,
+                &local_filenames_set,
+            ),
+            _ => {
+                Self::unique_snap_filenames(search_bundle.snap_mounts, search_bundle.relative_path)
+            }
+        };
 
         // compare local filenames to all unique snap filenames - none values are unique, here
         let all_deleted_versions = unique_snap_filenames
@@ -88,6 +108,26 @@ impl DeletedFiles {
         Ok(all_deleted_versions)
     }
 
+    // only the deleted files need be stat-ed for their file types, and only upon a single snapshot
+    fn indexed_snap_filenames(
+        dir_index: &DirIndex,
+        relative_path: &Path,
+        local_filenames_set: &HashSet<OsString>,
+    ) -> HashMap<OsString, BasicDirEntryInfo> {
+        dir_index
+            .latest_paths(relative_path)
+            .filter(|(file_name, _path)| !local_filenames_set.contains(*file_name))
+            .map(|(file_name, path)| {
+                let file_type = path.symlink_metadata().ok().map(|md| md.file_type());
+
+                (
+                    file_name.to_os_string(),
+                    BasicDirEntryInfo { path, file_type },
+                )
+            })
+            .collect()
+    }
+
     fn unique_snap_filenames(
         mounts: &[PathBuf],
         relative_path: &Path,
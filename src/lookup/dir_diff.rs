@@ -0,0 +1,307 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::data::paths::{PathData, PathMetadata};
+use crate::library::results::{HttmError, HttmResult};
+use crate::library::utility::{date_string, display_human_size, print_output_buf, DateFormat};
+use crate::lookup::versions::ProximateDatasetAndOptAlts;
+use crate::GLOBAL_CONFIG;
+use nu_ansi_term::Color::{Green, Red, Yellow};
+use rayon::prelude::*;
+use std::collections::BTreeMap;
+use std::fs::read_dir;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use time::{format_description, PrimitiveDateTime, Time};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EntryInfo {
+    pub is_dir: bool,
+    pub metadata: PathMetadata,
+}
+
+enum DirDiffKind {
+    Added(EntryInfo),
+    Removed(EntryInfo),
+    Modified { snap: EntryInfo, live: EntryInfo },
+}
+
+// compares a live directory tree to the same tree upon a single snapshot, and lists
+// each path which was added, removed, or modified since that snapshot
+pub struct DirDiff;
+
+impl DirDiff {
+    pub fn exec(snap_or_date: &str) -> HttmResult<()> {
+        GLOBAL_CONFIG.paths.iter().try_for_each(|pathdata| {
+            let output_buf = Self::dir_diff(pathdata, snap_or_date)?;
+
+            print_output_buf(&output_buf)
+        })
+    }
+
+    fn dir_diff(pathdata: &PathData, snap_or_date: &str) -> HttmResult<String> {
+        if !pathdata.path_buf.is_dir() {
+            let msg = format!(
+                "Directory diff requires a directory as input, and this path is not a directory: {:?}",
+                pathdata.path_buf
+            );
+            return Err(HttmError::new(&msg).into());
+        }
+
+        let snap_dir = Self::snap_dir(pathdata, snap_or_date)?;
+
+        if !snap_dir.is_dir() {
+            let msg = format!(
+                "The directory requested does not exist upon the snapshot specified: {:?}",
+                snap_dir
+            );
+            return Err(HttmError::new(&msg).into());
+        }
+
+        let (live_tree, snap_tree) =
+            rayon::join(|| Self::walk(&pathdata.path_buf), || Self::walk(&snap_dir));
+
+        let mut diffs: BTreeMap<&Path, DirDiffKind> = BTreeMap::new();
+
+        live_tree
+            .iter()
+            .for_each(|(path, live)| match snap_tree.get(path) {
+                None => {
+                    diffs.insert(path, DirDiffKind::Added(*live));
+                }
+                // directory metadata changes whenever an entry changes, so only compare files
+                Some(snap) if !live.is_dir && snap.metadata != live.metadata => {
+                    diffs.insert(
+                        path,
+                        DirDiffKind::Modified {
+                            snap: *snap,
+                            live: *live,
+                        },
+                    );
+                }
+                Some(_) => {}
+            });
+
+        snap_tree
+            .iter()
+            .filter(|(path, _snap)| !live_tree.contains_key(*path))
+            .for_each(|(path, snap)| {
+                diffs.insert(path, DirDiffKind::Removed(*snap));
+            });
+
+        let mut buffer = format!(
+            "Comparing live directory {:?} to snapshot directory {:?}\n",
+            pathdata.path_buf, snap_dir
+        );
+
+        if diffs.is_empty() {
+            buffer += "No changes found.\n";
+            return Ok(buffer);
+        }
+
+        diffs.iter().for_each(|(path, kind)| {
+            buffer += &Self::format_line(path, kind);
+        });
+
+        Ok(buffer)
+    }
+
+    fn format_line(path: &Path, kind: &DirDiffKind) -> String {
+        let date = |info: &EntryInfo| {
+            date_string(
+                GLOBAL_CONFIG.requested_utc_offset,
+                &info.metadata.modify_time,
+                DateFormat::Display,
+            )
+        };
+
+        let display_path = |info: &EntryInfo| {
+            if info.is_dir {
+                format!("{}/", path.display())
+            } else {
+                path.display().to_string()
+            }
+        };
+
+        match kind {
+            DirDiffKind::Added(live) => format!(
+                "{}: {}\t+{}\t{}\n",
+                Green.paint("Added    "),
+                display_path(live),
+                display_human_size(live.metadata.size),
+                date(live)
+            ),
+            DirDiffKind::Removed(snap) => format!(
+                "{}: {}\t-{}\t{}\n",
+                Red.paint("Removed  "),
+                display_path(snap),
+                display_human_size(snap.metadata.size),
+                date(snap)
+            ),
+            DirDiffKind::Modified { snap, live } => {
+                let size_delta = if live.metadata.size >= snap.metadata.size {
+                    format!(
+                        "+{}",
+                        display_human_size(live.metadata.size - snap.metadata.size)
+                    )
+                } else {
+                    format!(
+                        "-{}",
+                        display_human_size(snap.metadata.size - live.metadata.size)
+                    )
+                };
+
+                format!(
+                    "{}: {}\t{}\t{} -> {}\n",
+                    Yellow.paint("Modified "),
+                    display_path(live),
+                    size_delta,
+                    date(snap),
+                    date(live)
+                )
+            }
+        }
+    }
+
+    // walks the tree in parallel, returning each entry keyed by its path relative to the root
+    pub fn walk(root: &Path) -> BTreeMap<PathBuf, EntryInfo> {
+        Self::walk_dir(root, Path::new("")).collect()
+    }
+
+    fn walk_dir(
+        root: &Path,
+        relative_dir: &Path,
+    ) -> impl ParallelIterator<Item = (PathBuf, EntryInfo)> {
+        let entries: Vec<(PathBuf, EntryInfo)> = read_dir(root.join(relative_dir))
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter_map(|dir_entry| {
+                let md = dir_entry.metadata().ok()?;
+                let metadata = PathMetadata::new(&md)?;
+                let relative_path = relative_dir.join(dir_entry.file_name());
+
+                Some((
+                    relative_path,
+                    EntryInfo {
+                        // don't follow symlinks to directories
+                        is_dir: md.is_dir(),
+                        metadata,
+                    },
+                ))
+            })
+            .collect();
+
+        let sub_dirs: Vec<PathBuf> = entries
+            .iter()
+            .filter(|(_path, info)| info.is_dir)
+            .map(|(path, _info)| path.clone())
+            .collect();
+
+        let root = root.to_path_buf();
+
+        let nested: Vec<(PathBuf, EntryInfo)> = sub_dirs
+            .into_par_iter()
+            .flat_map(|sub_dir| Self::walk_dir(&root, &sub_dir).collect::<Vec<_>>())
+            .collect();
+
+        entries.into_par_iter().chain(nested)
+    }
+
+    // the value may be a snapshot name, or a date, in which case we use the most recent
+    // snapshot taken at or before that date
+    fn snap_dir(pathdata: &PathData, snap_or_date: &str) -> HttmResult<PathBuf> {
+        let prox_opt_alts = ProximateDatasetAndOptAlts::new(pathdata)?;
+
+        let search_bundles: Vec<_> = prox_opt_alts.into_search_bundles().collect();
+
+        let by_name = search_bundles.iter().find_map(|bundle| {
+            bundle
+                .snap_mounts
+                .iter()
+                .find(|snap_mount| {
+                    snap_mount
+                        .file_name()
+                        .map(|name| name == snap_or_date)
+                        .unwrap_or(false)
+                })
+                .map(|snap_mount| snap_mount.join(bundle.relative_path))
+        });
+
+        if let Some(snap_dir) = by_name {
+            return Ok(snap_dir);
+        }
+
+        let Some(date) = Self::parse_date(snap_or_date) else {
+            let msg = format!(
+                "No snapshot named {snap_or_date:?} could be found for {:?}, and the value could not be parsed as a date, like \"2023-01-15\" or \"2023-01-15 13:45:00\".",
+                pathdata.path_buf
+            );
+            return Err(HttmError::new(&msg).into());
+        };
+
+        // the modify time of a snapshot's root directory is a good approximation of when the
+        // snapshot was taken, and is available for every supported filesystem
+        search_bundles
+            .iter()
+            .flat_map(|bundle| {
+                bundle.snap_mounts.iter().filter_map(|snap_mount| {
+                    snap_mount
+                        .symlink_metadata()
+                        .and_then(|md| md.modified())
+                        .ok()
+                        .filter(|snap_time| snap_time <= &date)
+                        .map(|snap_time| (snap_time, snap_mount.join(bundle.relative_path)))
+                })
+            })
+            .max_by_key(|(snap_time, _snap_dir)| *snap_time)
+            .map(|(_snap_time, snap_dir)| snap_dir)
+            .ok_or_else(|| {
+                let msg = format!(
+                    "No snapshot taken at or before {snap_or_date:?} could be found for {:?}",
+                    pathdata.path_buf
+                );
+                HttmError::new(&msg).into()
+            })
+    }
+
+    pub fn parse_date(value: &str) -> Option<SystemTime> {
+        const DATE_TIME_FORMAT: &str = "[year]-[month]-[day] [hour]:[minute]:[second]";
+        const DATE_FORMAT: &str = "[year]-[month]-[day]";
+
+        let value = value.trim();
+
+        let date_time = format_description::parse(DATE_TIME_FORMAT)
+            .ok()
+            .and_then(|format| PrimitiveDateTime::parse(value, &format).ok())
+            .or_else(|| {
+                // a date alone includes every snapshot taken during that day
+                format_description::parse(DATE_FORMAT)
+                    .ok()
+                    .and_then(|format| time::Date::parse(value, &format).ok())
+                    .and_then(|date| {
+                        Time::from_hms(23, 59, 59)
+                            .ok()
+                            .map(|time| PrimitiveDateTime::new(date, time))
+                    })
+            })?;
+
+        Some(SystemTime::from(
+            date_time.assume_offset(GLOBAL_CONFIG.requested_utc_offset),
+        ))
+    }
+}
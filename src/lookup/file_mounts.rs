@@ -17,6 +17,7 @@
 
 use crate::data::paths::PathData;
 use crate::data::paths::PathDeconstruction;
+use crate::library::diagnostics::Diagnostic;
 use crate::library::results::{HttmError, HttmResult};
 use crate::lookup::versions::ProximateDatasetAndOptAlts;
 use crate::ExecMode;
@@ -80,10 +81,15 @@ impl<'a> MountsForFiles<'a> {
                 Ok(prox_opt_alts) => Some(prox_opt_alts),
                 Err(_) => {
                     if !is_interactive_mode {
-                        eprintln!(
-                            "WARN: Filesystem upon which the path resides is not supported: {:?}",
-                            pd.path_buf
+                        Diagnostic::warn(
+                            "unsupported_filesystem",
+                            format!(
+                                "Filesystem upon which the path resides is not supported: {:?}",
+                                pd.path_buf
+                            ),
                         )
+                        .with_path(&pd.path_buf)
+                        .print()
                     }
                     None
                 }
@@ -93,10 +99,15 @@ impl<'a> MountsForFiles<'a> {
                     && prox_opt_alts.pathdata.metadata.is_none()
                     && prox_opt_alts.datasets_of_interest().count() == 0
                 {
-                    eprintln!(
-                        "WARN: Input file may have never existed: {:?}",
-                        prox_opt_alts.pathdata.path_buf
-                    );
+                    Diagnostic::warn(
+                        "never_existed",
+                        format!(
+                            "Input file may have never existed: {:?}",
+                            prox_opt_alts.pathdata.path_buf
+                        ),
+                    )
+                    .with_path(&prox_opt_alts.pathdata.path_buf)
+                    .print();
                 }
 
                 prox_opt_alts
@@ -0,0 +1,281 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::data::paths::{FileIdentity, PathMetadata};
+use crate::library::cache_file::CacheFile;
+use crate::library::utility::httm_cache_dir;
+use crate::parse::mounts::FilesystemType;
+use crate::GLOBAL_CONFIG;
+use hashbrown::HashMap;
+use once_cell::sync::Lazy;
+use serde_json::Value;
+use std::collections::hash_map::DefaultHasher;
+use std::fs::remove_dir_all;
+use std::hash::{Hash, Hasher};
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const CACHE_DIR_NAME: &str = "metadata";
+// a cache which grows without bound would soon cost more to read than the stats it saves
+const MAX_ENTRIES_PER_DATASET: usize = 100_000;
+
+pub static METADATA_CACHE: Lazy<MetadataCache> = Lazy::new(MetadataCache::default);
+
+// snapshot contents are immutable, so the stat of a path upon a snapshot, or its absence,
+// remains valid for so long as the snapshot exists, and, so long as the list of snapshots
+// for a dataset is unchanged, every cached entry for that dataset remains valid
+#[derive(Default)]
+pub struct MetadataCache {
+    // key: dataset of interest
+    inner: Mutex<HashMap<PathBuf, DatasetCache>>,
+}
+
+#[derive(Default)]
+struct DatasetCache {
+    fingerprint: u64,
+    // key: path upon the snapshot, val: metadata, or None, if the path does not exist upon the snapshot
+    entries: HashMap<PathBuf, Option<PathMetadata>>,
+    is_modified: bool,
+}
+
+impl MetadataCache {
+    // load the dataset's cache from disk, once per run, discarding it if the snapshot list has changed
+    pub fn prepare(&self, dataset: &Path, snap_mounts: &[PathBuf]) {
+        let Ok(mut inner) = self.inner.lock() else {
+            return;
+        };
+
+        if inner.contains_key(dataset) {
+            return;
+        }
+
+        let fingerprint = Self::fingerprint(snap_mounts);
+
+        let dataset_cache = DatasetCache::load(dataset)
+            .filter(|loaded| loaded.fingerprint == fingerprint)
+            .unwrap_or_else(|| DatasetCache {
+                fingerprint,
+                entries: HashMap::new(),
+                // an invalidated cache must be rewritten, even if nothing new is inserted
+                is_modified: true,
+            });
+
+        inner.insert(dataset.to_path_buf(), dataset_cache);
+    }
+
+    // outer None: nothing is cached, inner None: cached as not present upon the snapshot
+    pub fn get(&self, dataset: &Path, snap_path: &Path) -> Option<Option<PathMetadata>> {
+        let inner = self.inner.lock().ok()?;
+
+        inner.get(dataset)?.entries.get(snap_path).copied()
+    }
+
+    pub fn insert(&self, dataset: &Path, snap_path: &Path, opt_metadata: Option<PathMetadata>) {
+        let Ok(mut inner) = self.inner.lock() else {
+            return;
+        };
+
+        let Some(dataset_cache) = inner.get_mut(dataset) else {
+            return;
+        };
+
+        if dataset_cache.entries.len() >= MAX_ENTRIES_PER_DATASET {
+            return;
+        }
+
+        dataset_cache
+            .entries
+            .insert(snap_path.to_path_buf(), opt_metadata);
+        dataset_cache.is_modified = true;
+    }
+
+    pub fn save(&self) {
+        let Ok(inner) = self.inner.lock() else {
+            return;
+        };
+
+        inner
+            .iter()
+            .filter(|(_dataset, dataset_cache)| dataset_cache.is_modified)
+            .for_each(|(dataset, dataset_cache)| dataset_cache.save(dataset));
+    }
+
+    // a restart which re-enumerates snapshots should stat each path anew, so the caches upon disk are discarded,
+    // and nothing held in memory is saved over them
+    pub fn invalidate(&self) {
+        if let Ok(mut inner) = self.inner.lock() {
+            inner.clear();
+        }
+
+        if let Some(cache_dir) = httm_cache_dir() {
+            let _ = remove_dir_all(cache_dir.join(CACHE_DIR_NAME));
+        }
+    }
+
+    // only ZFS snapshots are guaranteed to be immutable, btrfs snapshots, for instance, may be writable
+    pub fn is_immutable(dataset: &Path) -> bool {
+        GLOBAL_CONFIG
+            .dataset_collection
+            .map_of_datasets
+            .get(dataset)
+            .map(|metadata| matches!(metadata.fs_type, FilesystemType::Zfs))
+            .unwrap_or(false)
+    }
+
+    // a snapshot destroyed, and taken again, under the same name, has a new inode and modify time,
+    // so those of each snap mount are part of the fingerprint, as well as its path
+    pub fn fingerprint(snap_mounts: &[PathBuf]) -> u64 {
+        let mut sorted: Vec<&PathBuf> = snap_mounts.iter().collect();
+        sorted.sort_unstable();
+
+        let mut hasher = DefaultHasher::new();
+
+        sorted.into_iter().for_each(|snap_mount| {
+            snap_mount.hash(&mut hasher);
+
+            snap_mount
+                .symlink_metadata()
+                .ok()
+                .map(|metadata| (metadata.ino(), metadata.mtime(), metadata.mtime_nsec()))
+                .hash(&mut hasher);
+        });
+
+        hasher.finish()
+    }
+
+    fn cache_path(dataset: &Path) -> Option<PathBuf> {
+        let mut hasher = DefaultHasher::new();
+        dataset.hash(&mut hasher);
+
+        httm_cache_dir().map(|cache_dir| {
+            cache_dir
+                .join(CACHE_DIR_NAME)
+                .join(format!("{:016x}.json", hasher.finish()))
+        })
+    }
+}
+
+impl DatasetCache {
+    // the cache is only an optimization, so a cache which cannot be read is simply ignored
+    fn load(dataset: &Path) -> Option<Self> {
+        let contents = CacheFile::read(&MetadataCache::cache_path(dataset)?)?;
+        let value = serde_json::from_slice::<Value>(&contents).ok()?;
+
+        // guard against the unlikely collision of two dataset names' hashes
+        if value.get("dataset").and_then(Value::as_str) != dataset.to_str() {
+            return None;
+        }
+
+        let fingerprint = value.get("fingerprint").and_then(Value::as_u64)?;
+
+        let entries = value
+            .get("entries")?
+            .as_object()?
+            .iter()
+            .filter_map(|(path, entry)| {
+                let opt_metadata = match entry {
+                    Value::Null => None,
+                    _ => Some(Self::metadata_from_value(entry)?),
+                };
+
+                Some((PathBuf::from(path), opt_metadata))
+            })
+            .collect();
+
+        Some(Self {
+            fingerprint,
+            entries,
+            is_modified: false,
+        })
+    }
+
+    fn save(&self, dataset: &Path) {
+        let (Some(cache_path), Some(dataset_str)) =
+            (MetadataCache::cache_path(dataset), dataset.to_str())
+        else {
+            return;
+        };
+
+        // paths which are not valid UTF-8 cannot be JSON keys, and are simply not cached
+        let entries: serde_json::Map<String, Value> = self
+            .entries
+            .iter()
+            .filter_map(|(path, opt_metadata)| {
+                let value = match opt_metadata {
+                    Some(metadata) => Self::metadata_to_value(metadata)?,
+                    None => Value::Null,
+                };
+
+                Some((path.to_str()?.to_owned(), value))
+            })
+            .collect();
+
+        let object = serde_json::json!({
+            "dataset": dataset_str,
+            "fingerprint": self.fingerprint,
+            "entries": Value::Object(entries),
+        });
+
+        let Ok(contents) = serde_json::to_vec(&object) else {
+            return;
+        };
+
+        let _ = CacheFile::write(&cache_path, &contents);
+    }
+
+    // [size, modify secs, modify nanos, inode, birth secs or null, birth nanos or null]
+    fn metadata_to_value(metadata: &PathMetadata) -> Option<Value> {
+        let modify = metadata.modify_time.duration_since(UNIX_EPOCH).ok()?;
+        let identity = metadata.opt_identity?;
+        let opt_birth = identity
+            .opt_birth_time
+            .and_then(|birth| birth.duration_since(UNIX_EPOCH).ok());
+
+        Some(serde_json::json!([
+            metadata.size,
+            modify.as_secs(),
+            modify.subsec_nanos(),
+            identity.ino,
+            opt_birth.map(|birth| birth.as_secs()),
+            opt_birth.map(|birth| birth.subsec_nanos()),
+        ]))
+    }
+
+    fn metadata_from_value(value: &Value) -> Option<PathMetadata> {
+        let array = value.as_array()?;
+        let field = |idx: usize| array.get(idx).and_then(Value::as_u64);
+        let time = |secs: u64, nanos: u64| -> Option<SystemTime> {
+            UNIX_EPOCH.checked_add(Duration::new(secs, u32::try_from(nanos).ok()?))
+        };
+
+        let opt_birth_time = match (field(4), field(5)) {
+            (Some(secs), Some(nanos)) => Some(time(secs, nanos)?),
+            _ => None,
+        };
+
+        Some(PathMetadata {
+            size: field(0)?,
+            modify_time: time(field(1)?, field(2)?)?,
+            opt_identity: Some(FileIdentity {
+                ino: field(3)?,
+                opt_birth_time,
+            }),
+        })
+    }
+}
@@ -0,0 +1,154 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+use crate::data::paths::PathData;
+use crate::display_map::datasets::DatasetInventory;
+use crate::library::diagnostics::Diagnostic;
+use crate::library::results::{HttmError, HttmResult};
+use crate::library::utility::{date_string, delimiter, print_output_buf, DateFormat};
+use crate::lookup::dir_diff::DirDiff;
+use crate::lookup::versions::ProximateDatasetAndOptAlts;
+use crate::GLOBAL_CONFIG;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+// prints only the path of a file upon the snapshot named, or upon the most recent snapshot taken
+// at or before the date given, which contains the file, so a shell may use that path directly
+pub struct PathAt;
+
+impl PathAt {
+    pub fn exec(snap_or_date: &str) -> HttmResult<()> {
+        let delimiter = delimiter();
+
+        let output_buf = GLOBAL_CONFIG
+            .paths
+            .iter()
+            .map(|pathdata| {
+                Self::snap_path(pathdata, snap_or_date)
+                    .map(|snap_path| format!("{}{delimiter}", snap_path.to_string_lossy()))
+            })
+            .collect::<HttmResult<String>>()?;
+
+        print_output_buf(&output_buf)
+    }
+
+    fn snap_path(pathdata: &PathData, snap_or_date: &str) -> HttmResult<PathBuf> {
+        let prox_opt_alts = ProximateDatasetAndOptAlts::new(pathdata)?;
+
+        // key: snap time, val: the path of the file upon that snapshot
+        let candidates: Vec<(Option<SystemTime>, PathBuf)> = prox_opt_alts
+            .into_search_bundles()
+            .flat_map(|bundle| {
+                bundle.snap_mounts.iter().map(move |snap_mount| {
+                    (
+                        GLOBAL_CONFIG.dataset_collection.snap_mount_time(snap_mount),
+                        snap_mount.join(bundle.relative_path),
+                    )
+                })
+            })
+            .collect();
+
+        let by_name = candidates.iter().find(|(_snap_time, snap_path)| {
+            GLOBAL_CONFIG
+                .dataset_collection
+                .snap_mount_of(snap_path)
+                .map(DatasetInventory::snap_name)
+                .is_some_and(|name| name == snap_or_date)
+        });
+
+        if let Some((_snap_time, snap_path)) = by_name {
+            if snap_path.symlink_metadata().is_ok() {
+                return Ok(snap_path.clone());
+            }
+
+            let msg = format!(
+                "The snapshot named {snap_or_date:?} does not contain the path: {:?}",
+                pathdata.path_buf
+            );
+            return Err(HttmError::new(&msg)
+                .with_code("not_found")
+                .with_path(&pathdata.path_buf)
+                .into());
+        }
+
+        let Some(date) = DirDiff::parse_date(snap_or_date) else {
+            let msg = format!(
+                "No snapshot named {snap_or_date:?} could be found for {:?}, and the value could not be parsed as a date, like \"2023-01-15\" or \"2023-01-15 13:45:00\".",
+                pathdata.path_buf
+            );
+            return Err(HttmError::new(&msg).with_path(&pathdata.path_buf).into());
+        };
+
+        // candidates at or before the date, newest first
+        let mut at_or_before: Vec<(SystemTime, PathBuf)> = candidates
+            .into_iter()
+            .filter_map(|(opt_snap_time, snap_path)| {
+                opt_snap_time
+                    .filter(|snap_time| snap_time <= &date)
+                    .map(|snap_time| (snap_time, snap_path))
+            })
+            .collect();
+
+        at_or_before.sort_by_key(|(snap_time, _snap_path)| std::cmp::Reverse(*snap_time));
+
+        // where the nearest snapshot does not contain the file, as when the file had already been deleted
+        // by that date, the file's path upon the latest snapshot which does is given instead
+        let Some((snap_time, snap_path)) = at_or_before
+            .iter()
+            .find(|(_snap_time, snap_path)| snap_path.symlink_metadata().is_ok())
+        else {
+            let msg = format!(
+                "No snapshot taken at or before {snap_or_date:?} contains the path: {:?}",
+                pathdata.path_buf
+            );
+            return Err(HttmError::new(&msg)
+                .with_code("not_found")
+                .with_path(&pathdata.path_buf)
+                .into());
+        };
+
+        if let Some((nearest_time, nearest_path)) = at_or_before.first() {
+            if nearest_time != snap_time {
+                Diagnostic::notice(
+                    "older_snapshot",
+                    format!(
+                        "The nearest snapshot at or before {snap_or_date:?}, {:?}, does not contain the path: {:?}. \
+                        The path upon an older snapshot, taken {}, is given instead.",
+                        Self::snap_name_of(nearest_path),
+                        pathdata.path_buf,
+                        date_string(
+                            GLOBAL_CONFIG.requested_utc_offset,
+                            snap_time,
+                            DateFormat::Display
+                        )
+                    ),
+                )
+                .with_path(&pathdata.path_buf)
+                .print();
+            }
+        }
+
+        Ok(snap_path.clone())
+    }
+
+    fn snap_name_of(snap_path: &Path) -> String {
+        GLOBAL_CONFIG
+            .dataset_collection
+            .snap_mount_of(snap_path)
+            .map(DatasetInventory::snap_name)
+            .unwrap_or_else(|| snap_path.to_string_lossy().into_owned())
+    }
+}
@@ -0,0 +1,248 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::config::generate::PrintMode;
+use crate::data::paths::PathData;
+use crate::display_map::datasets::DatasetInventory;
+use crate::library::results::{HttmError, HttmResult};
+use crate::library::utility::{date_string, delimiter, print_output_buf};
+use crate::lookup::dir_diff::{DirDiff, EntryInfo};
+use crate::lookup::versions::ProximateDatasetAndOptAlts;
+use crate::GLOBAL_CONFIG;
+use nu_ansi_term::Color::Red;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+struct ReconstructedEntry {
+    info: EntryInfo,
+    last_seen_mount: PathBuf,
+    last_seen_version: PathBuf,
+    opt_last_seen_time: Option<SystemTime>,
+    num_snaps: usize,
+    is_live: bool,
+}
+
+// reconstructs the most complete listing of a directory, perhaps long deleted, by taking the union
+// of its entries across every snapshot, so a user may see what was lost before restoring selectively
+pub struct ReconstructDir;
+
+impl ReconstructDir {
+    pub fn exec() -> HttmResult<()> {
+        GLOBAL_CONFIG.paths.iter().try_for_each(|pathdata| {
+            let output_buf = Self::reconstruct(pathdata)?;
+
+            print_output_buf(&output_buf)
+        })
+    }
+
+    fn reconstruct(pathdata: &PathData) -> HttmResult<String> {
+        let prox_opt_alts = ProximateDatasetAndOptAlts::new(pathdata)?;
+
+        let mut snap_dirs: Vec<(PathBuf, PathBuf, Option<SystemTime>)> = prox_opt_alts
+            .into_search_bundles()
+            .flat_map(|search_bundle| {
+                search_bundle
+                    .snap_mounts
+                    .iter()
+                    .map(move |snap_mount| {
+                        (
+                            snap_mount.clone(),
+                            snap_mount.join(search_bundle.relative_path),
+                        )
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .filter(|(_snap_mount, snap_dir)| snap_dir.is_dir())
+            .map(|(snap_mount, snap_dir)| {
+                let opt_time = GLOBAL_CONFIG
+                    .dataset_collection
+                    .snap_mount_time(&snap_mount);
+                (snap_mount, snap_dir, opt_time)
+            })
+            .collect();
+
+        if snap_dirs.is_empty() {
+            let msg = format!(
+                "The directory requested does not exist upon any snapshot: {:?}",
+                pathdata.path_buf
+            );
+            return Err(HttmError::new(&msg).with_path(&pathdata.path_buf).into());
+        }
+
+        // oldest first, so the newest snapshot upon which an entry appears is the last seen
+        snap_dirs.sort_by_key(|(_snap_mount, _snap_dir, opt_time)| *opt_time);
+
+        let live_paths: BTreeSet<PathBuf> = if pathdata.path_buf.is_dir() {
+            DirDiff::walk(&pathdata.path_buf).into_keys().collect()
+        } else {
+            BTreeSet::new()
+        };
+
+        let mut entries: BTreeMap<PathBuf, ReconstructedEntry> = BTreeMap::new();
+
+        snap_dirs
+            .iter()
+            .for_each(|(snap_mount, snap_dir, opt_time)| {
+                DirDiff::walk(snap_dir)
+                    .into_iter()
+                    .for_each(|(relative_path, info)| {
+                        let num_snaps = entries
+                            .get(&relative_path)
+                            .map(|entry| entry.num_snaps)
+                            .unwrap_or_default();
+
+                        let is_live = live_paths.contains(&relative_path);
+                        let last_seen_version = snap_dir.join(&relative_path);
+
+                        entries.insert(
+                            relative_path,
+                            ReconstructedEntry {
+                                info,
+                                last_seen_mount: snap_mount.clone(),
+                                last_seen_version,
+                                opt_last_seen_time: *opt_time,
+                                num_snaps: num_snaps + 1,
+                                is_live,
+                            },
+                        );
+                    })
+            });
+
+        if GLOBAL_CONFIG.opt_json {
+            return Ok(Self::to_json(pathdata, &entries, snap_dirs.len()));
+        }
+
+        let delimiter = delimiter();
+
+        match GLOBAL_CONFIG.print_mode {
+            PrintMode::RawNewline | PrintMode::RawZero => Ok(entries
+                .iter()
+                .map(|(relative_path, entry)| {
+                    format!(
+                        "{}\t{}\t{}\t{}{delimiter}",
+                        if entry.is_live { "live" } else { "deleted" },
+                        pathdata.path_buf.join(relative_path).to_string_lossy(),
+                        entry.last_seen_version.to_string_lossy(),
+                        entry.num_snaps
+                    )
+                })
+                .collect()),
+            PrintMode::FormattedDefault | PrintMode::FormattedNotPretty => {
+                let num_deleted = entries.values().filter(|entry| !entry.is_live).count();
+
+                let mut buffer = format!(
+                    "Reconstructed directory {:?} from {} snapshot/s: {} of {} entries no longer exist\n",
+                    pathdata.path_buf,
+                    snap_dirs.len(),
+                    num_deleted,
+                    entries.len()
+                );
+
+                entries.iter().for_each(|(relative_path, entry)| {
+                    let display_path = if entry.info.is_dir {
+                        format!("{}/", relative_path.display())
+                    } else {
+                        relative_path.display().to_string()
+                    };
+
+                    let last_seen = match &entry.opt_last_seen_time {
+                        Some(time) => format!(
+                            "{} ({})",
+                            DatasetInventory::snap_name(&entry.last_seen_mount),
+                            Self::display_time(time)
+                        ),
+                        None => DatasetInventory::snap_name(&entry.last_seen_mount),
+                    };
+
+                    buffer += &format!(
+                        "{}: {}\t{}\t{}\tlast seen: {}, upon {} snapshot/s\n",
+                        if entry.is_live {
+                            "Live   ".to_owned()
+                        } else {
+                            Red.paint("Deleted").to_string()
+                        },
+                        display_path,
+                        GLOBAL_CONFIG
+                            .number_format
+                            .display_size(entry.info.metadata.size),
+                        Self::display_time(&entry.info.metadata.modify_time),
+                        last_seen,
+                        entry.num_snaps
+                    );
+                });
+
+                Ok(buffer)
+            }
+        }
+    }
+
+    fn to_json(
+        pathdata: &PathData,
+        entries: &BTreeMap<PathBuf, ReconstructedEntry>,
+        num_snaps: usize,
+    ) -> String {
+        let values: Vec<serde_json::Value> = entries
+            .iter()
+            .map(|(relative_path, entry)| {
+                serde_json::json!({
+                    "path": pathdata.path_buf.join(relative_path),
+                    "is_dir": entry.info.is_dir,
+                    "deleted": !entry.is_live,
+                    "size": entry.info.metadata.size,
+                    "modify_time": Self::display_time(&entry.info.metadata.modify_time),
+                    "last_seen_snapshot": entry.last_seen_mount,
+                    "last_seen_version": entry.last_seen_version,
+                    "last_seen_time": entry.opt_last_seen_time.as_ref().map(Self::display_time),
+                    "snapshot_count": entry.num_snaps,
+                })
+            })
+            .collect();
+
+        let value = serde_json::json!({
+            "directory": pathdata.path_buf,
+            "snapshot_count": num_snaps,
+            "entries": values,
+        });
+
+        let res = match GLOBAL_CONFIG.print_mode {
+            PrintMode::FormattedNotPretty | PrintMode::RawNewline | PrintMode::RawZero => {
+                serde_json::to_string(&value)
+            }
+            PrintMode::FormattedDefault => serde_json::to_string_pretty(&value),
+        };
+
+        match res {
+            Ok(s) => {
+                let delimiter = delimiter();
+                format!("{s}{delimiter}")
+            }
+            Err(error) => {
+                eprintln!("Error: {error}");
+                std::process::exit(1)
+            }
+        }
+    }
+
+    fn display_time(time: &SystemTime) -> String {
+        date_string(
+            GLOBAL_CONFIG.requested_utc_offset,
+            time,
+            GLOBAL_CONFIG.display_date_format(),
+        )
+    }
+}
@@ -0,0 +1,280 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::config::generate::ShellDialect;
+use crate::data::paths::PathData;
+use crate::display_map::datasets::DatasetInventory;
+use crate::display_versions::emit_shell::ShellCommands;
+use crate::library::results::{HttmError, HttmResult};
+use crate::library::utility::{date_string, print_output_buf};
+use crate::lookup::versions::VersionsMap;
+use crate::GLOBAL_CONFIG;
+use std::collections::BTreeMap;
+use std::os::unix::ffi::OsStrExt;
+use std::process::Command as ExecProcess;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use which::which;
+
+// ssh's own exit status upon a connection or authentication failure, as opposed to the remote command's
+const SSH_FAILURE_EXIT_CODE: i32 = 255;
+// the exit status of a shell which could not find the command requested
+const COMMAND_NOT_FOUND_EXIT_CODE: i32 = 127;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct VersionStat {
+    modify_time: SystemTime,
+    size: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Presence {
+    Both,
+    Differs,
+    LocalOnly,
+    RemoteOnly,
+}
+
+impl Presence {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Presence::Both => "both",
+            Presence::Differs => "differs",
+            Presence::LocalOnly => "local only",
+            Presence::RemoteOnly => "remote only",
+        }
+    }
+}
+
+// versions are matched by snapshot name, as replication carries a snapshot's name along with its data,
+// so a version found on only one side is history the user may believe is replicated, but is not
+pub struct RemoteVersions;
+
+impl RemoteVersions {
+    pub fn exec(remote: &str) -> HttmResult<()> {
+        let Ok(ssh_command) = which("ssh") else {
+            return Err(HttmError::new(
+                "'ssh' executable could not be found in the user's PATH. 'ssh' is necessary for comparing versions with a remote host.",
+            )
+            .into());
+        };
+
+        // versions are matched by snapshot name, so neither side may collapse identical versions,
+        // else a snapshot present upon both hosts could appear to be upon only one
+        let versions_map = VersionsMap::all_versions(&GLOBAL_CONFIG, &GLOBAL_CONFIG.paths)?;
+
+        let mut totals: BTreeMap<&'static str, usize> = BTreeMap::new();
+
+        let output_buf = versions_map
+            .iter()
+            .map(|(live, snaps)| {
+                let local = Self::local_versions(snaps);
+                let remote = Self::remote_versions(&ssh_command, remote, live)?;
+
+                let rows = Self::compare(&local, &remote);
+
+                rows.iter().for_each(|(presence, _snap_name, _stat)| {
+                    *totals.entry(presence.as_str()).or_default() += 1;
+                });
+
+                Ok(Self::format(live, &rows))
+            })
+            .collect::<HttmResult<Vec<String>>>()?
+            .concat();
+
+        let summary = [
+            Presence::Both,
+            Presence::Differs,
+            Presence::LocalOnly,
+            Presence::RemoteOnly,
+        ]
+        .iter()
+        .map(|presence| {
+            let count = totals.get(presence.as_str()).copied().unwrap_or_default();
+            format!("{count} {}", presence.as_str())
+        })
+        .collect::<Vec<String>>()
+        .join(", ");
+
+        print_output_buf(&format!("{output_buf}Versions: {summary}\n"))
+    }
+
+    fn local_versions(snaps: &[PathData]) -> BTreeMap<String, VersionStat> {
+        snaps
+            .iter()
+            .filter_map(|snap| {
+                let snap_mount = GLOBAL_CONFIG
+                    .dataset_collection
+                    .snap_mount_of(&snap.path_buf)?;
+                let metadata = snap.metadata?;
+
+                Some((
+                    DatasetInventory::snap_name(snap_mount),
+                    VersionStat {
+                        modify_time: metadata.modify_time,
+                        size: metadata.size,
+                    },
+                ))
+            })
+            .collect()
+    }
+
+    // the remote host must also have httm in its PATH, and the path is assumed to be the same there,
+    // as it would be for a replicated dataset mounted at the same mount point
+    fn remote_versions(
+        ssh_command: &std::path::Path,
+        remote: &str,
+        live: &PathData,
+    ) -> HttmResult<BTreeMap<String, VersionStat>> {
+        let quoted_path =
+            ShellCommands::quote(&ShellDialect::Bash, live.path_buf.as_os_str().as_bytes());
+
+        let remote_command = format!(
+            "httm --no-live --raw --uniqueness=all --with-fields=mtime,size,snap -- {quoted_path}"
+        );
+
+        let output = ExecProcess::new(ssh_command)
+            .arg("-o")
+            .arg("BatchMode=yes")
+            // a remote which begins with a dash is a host, never an option
+            .arg("--")
+            .arg(remote)
+            .arg(remote_command)
+            .output()?;
+
+        match output.status.code() {
+            Some(SSH_FAILURE_EXIT_CODE) | None => {
+                let msg = format!(
+                    "httm could not connect to the remote host specified: {remote}: {}",
+                    String::from_utf8_lossy(&output.stderr).trim_end()
+                );
+                return Err(HttmError::new(&msg).with_code("remote_unreachable").into());
+            }
+            Some(COMMAND_NOT_FOUND_EXIT_CODE) => {
+                let msg = format!(
+                    "httm could not be found in the PATH of the remote host specified: {remote}"
+                );
+                return Err(HttmError::new(&msg).with_code("remote_no_httm").into());
+            }
+            // a remote httm which finds no versions simply prints nothing
+            Some(0) => {}
+            Some(code) => {
+                let msg = format!(
+                    "httm failed upon the remote host specified: {remote}, with exit status {code}: {}",
+                    String::from_utf8_lossy(&output.stderr).trim_end()
+                );
+                return Err(HttmError::new(&msg).with_code("remote_failed").into());
+            }
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+
+        let remote_versions = stdout
+            .lines()
+            .filter_map(|line| {
+                // a path may itself contain a tab, so fields are parsed from the end of the line
+                let mut fields = line.rsplitn(4, '\t');
+                let snap_name = fields.next()?;
+                let size = fields.next()?.parse::<u64>().ok()?;
+                let modify_secs = fields.next()?.parse::<u64>().ok()?;
+
+                Some((
+                    snap_name.to_owned(),
+                    VersionStat {
+                        modify_time: UNIX_EPOCH + Duration::from_secs(modify_secs),
+                        size,
+                    },
+                ))
+            })
+            .collect();
+
+        Ok(remote_versions)
+    }
+
+    // the remote's modify times are whole seconds, so local times are compared at the same precision
+    fn compare<'a>(
+        local: &'a BTreeMap<String, VersionStat>,
+        remote: &'a BTreeMap<String, VersionStat>,
+    ) -> Vec<(Presence, &'a str, VersionStat)> {
+        let as_secs = |stat: &VersionStat| {
+            stat.modify_time
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs())
+                .unwrap_or_default()
+        };
+
+        let mut rows: Vec<(Presence, &str, VersionStat)> = local
+            .iter()
+            .map(|(snap_name, local_stat)| match remote.get(snap_name) {
+                Some(remote_stat)
+                    if as_secs(remote_stat) == as_secs(local_stat)
+                        && remote_stat.size == local_stat.size =>
+                {
+                    (Presence::Both, snap_name.as_str(), *local_stat)
+                }
+                Some(_) => (Presence::Differs, snap_name.as_str(), *local_stat),
+                None => (Presence::LocalOnly, snap_name.as_str(), *local_stat),
+            })
+            .chain(
+                remote
+                    .iter()
+                    .filter(|(snap_name, _remote_stat)| !local.contains_key(*snap_name))
+                    .map(|(snap_name, remote_stat)| {
+                        (Presence::RemoteOnly, snap_name.as_str(), *remote_stat)
+                    }),
+            )
+            .collect();
+
+        rows.sort_by_key(|(_presence, snap_name, stat)| (stat.modify_time, *snap_name));
+
+        rows
+    }
+
+    fn format(live: &PathData, rows: &[(Presence, &str, VersionStat)]) -> String {
+        let presence_width = rows
+            .iter()
+            .map(|(presence, _snap_name, _stat)| presence.as_str().len())
+            .max()
+            .unwrap_or_default();
+        let snap_name_width = rows
+            .iter()
+            .map(|(_presence, snap_name, _stat)| snap_name.chars().count())
+            .max()
+            .unwrap_or_default();
+
+        let mut buffer = format!("{:?}\n", live.path_buf);
+
+        if rows.is_empty() {
+            buffer += "  No versions found locally or remotely.\n";
+        }
+
+        rows.iter().for_each(|(presence, snap_name, stat)| {
+            buffer += &format!(
+                "  {:<presence_width$}  {:<snap_name_width$}  {}  {}\n",
+                presence.as_str(),
+                snap_name,
+                date_string(
+                    GLOBAL_CONFIG.requested_utc_offset,
+                    &stat.modify_time,
+                    GLOBAL_CONFIG.display_date_format(),
+                ),
+                GLOBAL_CONFIG.number_format.display_size(stat.size),
+            );
+        });
+
+        buffer
+    }
+}
@@ -0,0 +1,302 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::library::cache_file::CacheFile;
+use crate::library::utility::httm_cache_dir;
+use crate::lookup::metadata_cache::MetadataCache;
+use crate::GLOBAL_CONFIG;
+use hashbrown::HashMap;
+use once_cell::sync::Lazy;
+use rayon::prelude::*;
+use roaring::RoaringBitmap;
+use std::collections::hash_map::DefaultHasher;
+use std::ffi::{OsStr, OsString};
+use std::fs::read_dir;
+use std::hash::{Hash, Hasher};
+use std::io::{ErrorKind, Read, Write};
+use std::os::unix::ffi::{OsStrExt, OsStringExt};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+const INDEX_DIR_NAME: &str = "index";
+const INDEX_MAGIC: &[u8; 8] = b"HTTMIDX1";
+// no path, or file name, stored within an index may be longer than PATH_MAX
+const MAX_STORED_BYTES: usize = 4096;
+
+pub static SNAP_INDEX: Lazy<SnapIndex> = Lazy::new(SnapIndex::default);
+
+// for directories the user has asked to index, rather than stat-ing, or reading, the directory
+// upon every snapshot, once per query, we read each snapshot's directory once, record which
+// snapshots contain each file name, and persist that index until the snapshot list changes
+#[derive(Default)]
+pub struct SnapIndex {
+    // key: (dataset of interest, directory relative to the dataset)
+    inner: Mutex<HashMap<(PathBuf, PathBuf), Arc<DirIndex>>>,
+}
+
+impl SnapIndex {
+    pub fn is_indexed_dir(dir: &Path) -> bool {
+        GLOBAL_CONFIG
+            .opt_index_dirs
+            .as_ref()
+            .map(|index_dirs| index_dirs.iter().any(|index_dir| index_dir == dir))
+            .unwrap_or(false)
+    }
+
+    // loads the index from disk, or, if it is missing or stale, builds and persists a new index
+    pub fn get(
+        &self,
+        dataset: &Path,
+        relative_dir: &Path,
+        snap_mounts: &[PathBuf],
+    ) -> Option<Arc<DirIndex>> {
+        if !MetadataCache::is_immutable(dataset) {
+            return None;
+        }
+
+        let key = (dataset.to_path_buf(), relative_dir.to_path_buf());
+
+        if let Some(dir_index) = self.inner.lock().ok()?.get(&key) {
+            return Some(dir_index.clone());
+        }
+
+        let fingerprint = MetadataCache::fingerprint(snap_mounts);
+        let cache_path = Self::cache_path(dataset, relative_dir)?;
+
+        let dir_index =
+            DirIndex::load(&cache_path, dataset, relative_dir, fingerprint, snap_mounts)
+                .unwrap_or_else(|| {
+                    let built = DirIndex::build(relative_dir, fingerprint, snap_mounts);
+                    // the index is only an optimization, so an index which cannot be saved is simply rebuilt next time,
+                    // as is an index of a directory which could not be read upon every snapshot, as by another user
+                    if built.is_complete() {
+                        let _ = built.save(&cache_path, dataset, relative_dir);
+                    }
+                    built
+                });
+
+        let dir_index = Arc::new(dir_index);
+
+        self.inner.lock().ok()?.insert(key, dir_index.clone());
+
+        Some(dir_index)
+    }
+
+    fn cache_path(dataset: &Path, relative_dir: &Path) -> Option<PathBuf> {
+        let mut hasher = DefaultHasher::new();
+        dataset.hash(&mut hasher);
+        relative_dir.hash(&mut hasher);
+
+        httm_cache_dir().map(|cache_dir| {
+            cache_dir
+                .join(INDEX_DIR_NAME)
+                .join(format!("{:016x}.idx", hasher.finish()))
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct DirIndex {
+    fingerprint: u64,
+    // sorted, so a snapshot's position is the same across runs, so long as the snapshot list is unchanged
+    snap_mounts: Vec<PathBuf>,
+    // key: file name, val: positions of the snapshots which contain that file name
+    names: HashMap<OsString, RoaringBitmap>,
+    // positions of the snapshots upon which the directory could not be read, which are never indexed
+    unreadable: RoaringBitmap,
+}
+
+impl DirIndex {
+    fn build(relative_dir: &Path, fingerprint: u64, snap_mounts: &[PathBuf]) -> Self {
+        let mut sorted = snap_mounts.to_vec();
+        sorted.sort_unstable();
+
+        let listings: Vec<(u32, Option<Vec<OsString>>)> = sorted
+            .par_iter()
+            .enumerate()
+            .map(|(idx, snap_mount)| (idx as u32, Self::list(&snap_mount.join(relative_dir))))
+            .collect();
+
+        let mut names: HashMap<OsString, RoaringBitmap> = HashMap::new();
+        let mut unreadable = RoaringBitmap::new();
+
+        listings
+            .into_iter()
+            .for_each(|(idx, opt_file_names)| match opt_file_names {
+                Some(file_names) => file_names.into_iter().for_each(|file_name| {
+                    names.entry(file_name).or_default().insert(idx);
+                }),
+                None => {
+                    unreadable.insert(idx);
+                }
+            });
+
+        Self {
+            fingerprint,
+            snap_mounts: sorted,
+            names,
+            unreadable,
+        }
+    }
+
+    // None, if the directory could not be read, rather than an empty listing, as where the
+    // directory does not exist upon the snapshot at all
+    fn list(dir: &Path) -> Option<Vec<OsString>> {
+        match read_dir(dir) {
+            Ok(read_dir) => read_dir
+                .map(|res| res.map(|dir_entry| dir_entry.file_name()))
+                .collect::<Result<Vec<OsString>, _>>()
+                .ok(),
+            Err(err) if matches!(err.kind(), ErrorKind::NotFound | ErrorKind::NotADirectory) => {
+                Some(Vec::new())
+            }
+            Err(_) => None,
+        }
+    }
+
+    // does the snapshot contain the file name?  snapshots unknown to the index, or upon which the
+    // directory could not be read, are assumed to contain the file, so that they are still searched
+    pub fn contains(&self, snap_mount: &Path, file_name: &OsStr) -> bool {
+        let Ok(idx) = self
+            .snap_mounts
+            .binary_search_by(|probe| probe.as_path().cmp(snap_mount))
+        else {
+            return true;
+        };
+
+        if self.unreadable.contains(idx as u32) {
+            return true;
+        }
+
+        self.names
+            .get(file_name)
+            .map(|bitmap| bitmap.contains(idx as u32))
+            .unwrap_or(false)
+    }
+
+    // was the directory read upon every snapshot?
+    pub fn is_complete(&self) -> bool {
+        self.unreadable.is_empty()
+    }
+
+    // each file name which exists upon any snapshot, and its path upon the latest snapshot which contains it
+    pub fn latest_paths<'a>(
+        &'a self,
+        relative_dir: &'a Path,
+    ) -> impl Iterator<Item = (&'a OsStr, PathBuf)> + 'a {
+        self.names.iter().filter_map(move |(file_name, bitmap)| {
+            let snap_mount = self.snap_mounts.get(bitmap.max()? as usize)?;
+
+            Some((
+                file_name.as_os_str(),
+                snap_mount.join(relative_dir).join(file_name),
+            ))
+        })
+    }
+
+    // the index is only an optimization, so an index which cannot be read is simply ignored
+    fn load(
+        cache_path: &Path,
+        dataset: &Path,
+        relative_dir: &Path,
+        fingerprint: u64,
+        snap_mounts: &[PathBuf],
+    ) -> Option<Self> {
+        let contents = CacheFile::read(cache_path)?;
+        let mut reader = contents.as_slice();
+
+        let mut magic = [0u8; 8];
+        reader.read_exact(&mut magic).ok()?;
+
+        if &magic != INDEX_MAGIC {
+            return None;
+        }
+
+        if Self::read_u64(&mut reader)? != fingerprint {
+            return None;
+        }
+
+        // guard against the unlikely collision of two directories' hashes
+        if Self::read_bytes(&mut reader)? != dataset.as_os_str().as_bytes()
+            || Self::read_bytes(&mut reader)? != relative_dir.as_os_str().as_bytes()
+        {
+            return None;
+        }
+
+        let num_names = Self::read_u64(&mut reader)?;
+
+        let names = (0..num_names)
+            .map(|_| {
+                let file_name = OsString::from_vec(Self::read_bytes(&mut reader)?);
+                let bitmap = RoaringBitmap::deserialize_from(&mut reader).ok()?;
+
+                Some((file_name, bitmap))
+            })
+            .collect::<Option<HashMap<OsString, RoaringBitmap>>>()?;
+
+        let mut sorted = snap_mounts.to_vec();
+        sorted.sort_unstable();
+
+        Some(Self {
+            fingerprint,
+            snap_mounts: sorted,
+            names,
+            unreadable: RoaringBitmap::new(),
+        })
+    }
+
+    // [magic][fingerprint][dataset][relative dir][num names]([file name][bitmap])*, compressed when large, see CacheFile
+    fn save(&self, cache_path: &Path, dataset: &Path, relative_dir: &Path) -> std::io::Result<()> {
+        let mut writer: Vec<u8> = Vec::new();
+
+        writer.write_all(INDEX_MAGIC)?;
+        writer.write_all(&self.fingerprint.to_le_bytes())?;
+        Self::write_bytes(&mut writer, dataset.as_os_str().as_bytes())?;
+        Self::write_bytes(&mut writer, relative_dir.as_os_str().as_bytes())?;
+        writer.write_all(&(self.names.len() as u64).to_le_bytes())?;
+
+        self.names.iter().try_for_each(|(file_name, bitmap)| {
+            Self::write_bytes(&mut writer, file_name.as_bytes())?;
+            bitmap.serialize_into(&mut writer)
+        })?;
+
+        CacheFile::write(cache_path, &writer)
+    }
+
+    fn read_u64(reader: &mut impl Read) -> Option<u64> {
+        let mut buf = [0u8; 8];
+        reader.read_exact(&mut buf).ok()?;
+        Some(u64::from_le_bytes(buf))
+    }
+
+    fn read_bytes(reader: &mut impl Read) -> Option<Vec<u8>> {
+        let len = usize::try_from(Self::read_u64(reader)?).ok()?;
+
+        if len > MAX_STORED_BYTES {
+            return None;
+        }
+
+        let mut buf = vec![0u8; len];
+        reader.read_exact(&mut buf).ok()?;
+        Some(buf)
+    }
+
+    fn write_bytes(writer: &mut impl Write, bytes: &[u8]) -> std::io::Result<()> {
+        writer.write_all(&(bytes.len() as u64).to_le_bytes())?;
+        writer.write_all(bytes)
+    }
+}
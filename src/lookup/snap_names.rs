@@ -18,27 +18,31 @@
 use crate::config::generate::ListSnapsFilters;
 use crate::data::paths::PathDeconstruction;
 use crate::data::paths::{PathData, ZfsSnapPathGuard};
+use crate::library::diagnostics::Diagnostic;
 use crate::library::results::{HttmError, HttmResult};
 use crate::lookup::versions::VersionsMap;
 use crate::GLOBAL_CONFIG;
+use indexmap::IndexMap;
 use rayon::prelude::*;
 use std::collections::BTreeMap;
 use std::ops::Deref;
+use std::path::PathBuf;
+use std::process::Command as ExecProcess;
 use crate::parse::mounts::FilesystemType;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct SnapNameMap {
-    inner: BTreeMap<PathData, Vec<String>>,
+    inner: IndexMap<PathData, Vec<String>>,
 }
 
-impl From<BTreeMap<PathData, Vec<String>>> for SnapNameMap {
-    fn from(map: BTreeMap<PathData, Vec<String>>) -> Self {
+impl From<IndexMap<PathData, Vec<String>>> for SnapNameMap {
+    fn from(map: IndexMap<PathData, Vec<String>>) -> Self {
         Self { inner: map }
     }
 }
 
 impl Deref for SnapNameMap {
-    type Target = BTreeMap<PathData, Vec<String>>;
+    type Target = IndexMap<PathData, Vec<String>>;
 
     fn deref(&self) -> &Self::Target {
         &self.inner
@@ -60,7 +64,9 @@ impl SnapNameMap {
                             "{:?} is not located on a ZFS filesystem.",
                             pathdata.path_buf
                         );
-                        eprintln!("WARN: {msg}");
+                        Diagnostic::warn("unsupported_filesystem", msg)
+                            .with_path(&pathdata.path_buf)
+                            .print();
                         return false;
                     },
                 }
@@ -75,7 +81,7 @@ impl SnapNameMap {
             );
         }
 
-        let inner: BTreeMap<PathData, Vec<String>> = less_unsupported
+        let inner: IndexMap<PathData, Vec<String>> = less_unsupported
             .into_iter()
             .filter(|(pathdata, snaps)| {
                 if snaps.is_empty() {
@@ -83,7 +89,9 @@ impl SnapNameMap {
                         "httm could not find any snapshots for the file specified: {:?}",
                         pathdata.path_buf
                     );
-                    eprintln!("WARN: {msg}");
+                    Diagnostic::warn("no_snapshots", msg)
+                        .with_path(&pathdata.path_buf)
+                        .print();
                     return false;
                 }
 
@@ -96,40 +104,13 @@ impl SnapNameMap {
                     .filter_map(|pd| {
                         ZfsSnapPathGuard::new(pd).and_then(|spd| spd.source(None))
                     })
-                    .filter(|snap| {
-                        if let Some(filters) = opt_filters {
-                            if let Some(names) = &filters.name_filters {
-                                return names.iter().any(|pattern| snap.to_string_lossy().contains(pattern));
-                            }
-                        }
-                        true
-                    })
                     .map(|path| path.to_string_lossy().to_string())
                     .collect();
 
                 (pathdata, snap_names)
             })
-            .filter_map(|(pathdata, mut vec_snaps)| {
-                if let Some(mode_filter) = opt_filters {
-                    if mode_filter.omit_num_snaps != 0 {
-                        let opt_amt_less = vec_snaps.len().checked_sub(mode_filter.omit_num_snaps);
-
-                        match opt_amt_less {
-                            Some(amt_less) => {
-                                let _ = vec_snaps.split_off(amt_less);
-                            }
-                            None => {
-                                eprintln!(
-                                    "Number of snapshots requested to omit larger than number of snapshots.",
-                                );
-                                return None
-                                
-                            }
-                        }
-                    }
-                }
-
-                Some((pathdata.to_owned(), vec_snaps))
+            .filter_map(|(pathdata, vec_snaps)| {
+                Self::apply_filters(vec_snaps, opt_filters).map(|vec_snaps| (pathdata.to_owned(), vec_snaps))
             })
             .collect();
 
@@ -144,4 +125,108 @@ impl SnapNameMap {
 
         Ok(inner.into())
     }
+
+    // snapshots of explicitly requested datasets, rather than datasets inferred from paths,
+    // keyed by the dataset upon which each snapshot resides
+    pub fn from_datasets(
+        datasets: &[String],
+        recursive: bool,
+        opt_filters: &Option<ListSnapsFilters>,
+    ) -> HttmResult<Self> {
+        let zfs_command = which::which("zfs").map_err(|_err| {
+            HttmError::new("'zfs' command not found. Make sure the command 'zfs' is in your path.")
+        })?;
+
+        let depth_arg = if recursive { "-r" } else { "-d1" };
+
+        let mut map_of_snaps: BTreeMap<String, Vec<String>> = BTreeMap::new();
+
+        datasets.iter().try_for_each(|dataset| -> HttmResult<()> {
+            let process_args = vec!["list", "-H", "-t", "snapshot", "-o", "name", "-s", "creation", depth_arg, dataset];
+
+            let process_output = ExecProcess::new(&zfs_command).args(&process_args).output()?;
+            let stderr_string = std::str::from_utf8(&process_output.stderr)?.trim();
+
+            if !stderr_string.is_empty() {
+                let msg = "httm was unable to list snapshots. The 'zfs' command issued the following error: "
+                    .to_owned()
+                    + stderr_string;
+
+                return Err(HttmError::new(&msg).into());
+            }
+
+            std::str::from_utf8(&process_output.stdout)?
+                .lines()
+                .filter_map(|snap_name| snap_name.split_once('@').map(|(dataset, _snap)| (dataset, snap_name)))
+                .for_each(|(dataset, snap_name)| {
+                    map_of_snaps
+                        .entry(dataset.to_owned())
+                        .or_default()
+                        .push(snap_name.to_owned())
+                });
+
+            Ok(())
+        })?;
+
+        let inner: IndexMap<PathData, Vec<String>> = map_of_snaps
+            .into_iter()
+            .filter_map(|(dataset, vec_snaps)| {
+                Self::apply_filters(vec_snaps, opt_filters).map(|vec_snaps| {
+                    let pathdata = PathData {
+                        path_buf: PathBuf::from(dataset),
+                        metadata: None,
+                    };
+
+                    (pathdata, vec_snaps)
+                })
+            })
+            .filter(|(_pathdata, vec_snaps)| !vec_snaps.is_empty())
+            .collect();
+
+        if inner.is_empty() {
+            return Err(
+                HttmError::new(
+                "httm could not find any snapshots for the dataset/s specified. Quitting.",
+                )
+                .into(),
+            );
+        }
+
+        Ok(inner.into())
+    }
+
+    fn apply_filters(
+        vec_snaps: Vec<String>,
+        opt_filters: &Option<ListSnapsFilters>,
+    ) -> Option<Vec<String>> {
+        let Some(mode_filter) = opt_filters else {
+            return Some(vec_snaps);
+        };
+
+        let mut vec_snaps: Vec<String> = match &mode_filter.name_filters {
+            Some(names) => vec_snaps
+                .into_iter()
+                .filter(|snap| names.iter().any(|pattern| snap.contains(pattern)))
+                .collect(),
+            None => vec_snaps,
+        };
+
+        if mode_filter.omit_num_snaps != 0 {
+            let opt_amt_less = vec_snaps.len().checked_sub(mode_filter.omit_num_snaps);
+
+            match opt_amt_less {
+                Some(amt_less) => {
+                    let _ = vec_snaps.split_off(amt_less);
+                }
+                None => {
+                    eprintln!(
+                        "Number of snapshots requested to omit larger than number of snapshots.",
+                    );
+                    return None;
+                }
+            }
+        }
+
+        Some(vec_snaps)
+    }
 }
@@ -15,31 +15,45 @@
 // For the full copyright and license information, please view the LICENSE file
 // that was distributed with this source code.
 
-use crate::config::generate::{Config, ExecMode, LastSnapMode, ListSnapsOfType};
+use crate::config::generate::{
+    ChangedAttr, Config, DedupKey, ExecMode, LastSnapMode, ListSnapsOfType, ProbeOrder,
+};
 use crate::data::paths::PathDeconstruction;
 use crate::data::paths::PathMetadata;
-use crate::data::paths::{CompareVersionsContainer, PathData};
+use crate::data::paths::{CompareVersionsContainer, DedupKeyValue, PathData};
+use crate::library::diagnostics::{Diagnostic, Level};
+use crate::library::network_limit::NetworkLimit;
 use crate::library::results::{HttmError, HttmResult};
+use crate::lookup::metadata_cache::{MetadataCache, METADATA_CACHE};
+use crate::lookup::snap_index::{DirIndex, SnapIndex, SNAP_INDEX};
+use crate::parse::archives::MapOfArchives;
+use crate::parse::encrypted::LockedDatasets;
+use crate::parse::mounts::FilesystemType;
 use crate::GLOBAL_CONFIG;
+use indexmap::IndexMap;
+use once_cell::sync::Lazy;
 use rayon::prelude::*;
-use std::collections::{BTreeMap, BTreeSet};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::ffi::{OsStr, OsString};
 use std::io::ErrorKind;
 use std::ops::{Deref, DerefMut};
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct VersionsMap {
-    inner: BTreeMap<PathData, Vec<PathData>>,
+    inner: IndexMap<PathData, Vec<PathData>>,
 }
 
-impl From<BTreeMap<PathData, Vec<PathData>>> for VersionsMap {
-    fn from(map: BTreeMap<PathData, Vec<PathData>>) -> Self {
+impl From<IndexMap<PathData, Vec<PathData>>> for VersionsMap {
+    fn from(map: IndexMap<PathData, Vec<PathData>>) -> Self {
         Self { inner: map }
     }
 }
 
 impl Deref for VersionsMap {
-    type Target = BTreeMap<PathData, Vec<PathData>>;
+    type Target = IndexMap<PathData, Vec<PathData>>;
 
     fn deref(&self) -> &Self::Target {
         &self.inner
@@ -47,25 +61,68 @@ impl Deref for VersionsMap {
 }
 
 impl DerefMut for VersionsMap {
-    fn deref_mut(&mut self) -> &mut BTreeMap<PathData, Vec<PathData>> {
+    fn deref_mut(&mut self) -> &mut IndexMap<PathData, Vec<PathData>> {
         &mut self.inner
     }
 }
 
 impl VersionsMap {
     pub fn new(config: &Config, path_set: &[PathData]) -> HttmResult<VersionsMap> {
+        let mut versions_map = Self::search(config, path_set)?;
+
+        versions_map.filter(config);
+
+        Ok(versions_map)
+    }
+
+    // every version of each path, as yet unfiltered, from which a map of any uniqueness may be
+    // derived, with with_uniqueness, without searching the snapshots again
+    pub fn all_versions(config: &Config, path_set: &[PathData]) -> HttmResult<VersionsMap> {
+        let mut all_config = config.clone();
+        all_config.uniqueness = ListSnapsOfType::All;
+
+        Self::search(&all_config, path_set)
+    }
+
+    pub fn with_uniqueness(&self, config: &Config, uniqueness: &ListSnapsOfType) -> VersionsMap {
+        let mut versions_map: VersionsMap = self
+            .iter()
+            .map(|(pathdata, snaps)| {
+                (
+                    pathdata.clone(),
+                    RelativePathAndSnapMounts::deduplicate(snaps.clone(), uniqueness),
+                )
+            })
+            .collect::<IndexMap<PathData, Vec<PathData>>>()
+            .into();
+
+        versions_map.filter(config);
+
+        versions_map
+    }
+
+    fn search(config: &Config, path_set: &[PathData]) -> HttmResult<VersionsMap> {
         let is_interactive_mode = matches!(GLOBAL_CONFIG.exec_mode, ExecMode::Interactive(_));
 
-        let all_snap_versions: BTreeMap<PathData, Vec<PathData>> = path_set
-            .par_iter()
-            .filter_map(|pathdata| match Versions::new(pathdata, config) {
+        // results are returned in the order of the paths given
+        let all_results = Versions::new_batch(path_set, config);
+
+        let all_snap_versions: Vec<(PathData, Vec<PathData>)> = all_results
+            .into_iter()
+            .zip(path_set)
+            .filter_map(|(res, pathdata)| match res {
                 Ok(versions) => Some(versions),
                 Err(_err) => {
                     if !is_interactive_mode {
-                        eprintln!(
-                            "WARN: Filesystem upon which the path resides is not supported: {:?}\n",
-                            pathdata.path_buf
+                        Diagnostic::warn(
+                            "unsupported_filesystem",
+                            format!(
+                                "Filesystem upon which the path resides is not supported: {:?}\n",
+                                pathdata.path_buf
+                            ),
                         )
+                        .with_path(&pathdata.path_buf)
+                        .print()
                     }
                     None
                 }
@@ -75,17 +132,34 @@ impl VersionsMap {
                     && versions.live_path.metadata.is_none()
                     && versions.snap_versions.is_empty()
                 {
-                    eprintln!(
-                        "WARN: Input file may have never existed: {:?}",
-                        versions.live_path.path_buf
-                    );
+                    Diagnostic::warn(
+                        "never_existed",
+                        format!(
+                            "Input file may have never existed: {:?}",
+                            versions.live_path.path_buf
+                        ),
+                    )
+                    .with_path(&versions.live_path.path_buf)
+                    .print();
                 }
 
                 versions.destructure()
             })
             .collect();
 
-        let mut versions_map: VersionsMap = all_snap_versions.into();
+        let mut versions_map: VersionsMap = all_snap_versions
+            .into_iter()
+            .collect::<IndexMap<PathData, Vec<PathData>>>()
+            .into();
+
+        // by default, paths are displayed in sorted order, else in the order given
+        if !config.opt_preserve_order {
+            versions_map.sort_keys();
+        }
+
+        if !is_interactive_mode {
+            versions_map.warn_locked_datasets();
+        }
 
         // check if all files (snap and live) do not exist, if this is true, then user probably messed up
         // and entered a file that never existed (that is, perhaps a wrong file name)?
@@ -100,16 +174,68 @@ impl VersionsMap {
             .into());
         }
 
+        Ok(versions_map)
+    }
+
+    fn filter(&mut self, config: &Config) {
         // process last snap mode after omit_ditto
         if config.opt_omit_ditto {
-            versions_map.omit_ditto()
+            self.omit_ditto()
+        }
+
+        if let Some(changed_attrs) = &config.opt_changed_attrs {
+            self.changed_attrs(changed_attrs)
+        }
+
+        if let Some(size_filter) = &config.opt_size_filter {
+            self.values_mut().for_each(|snaps| {
+                snaps.retain(|snap| size_filter.is_match(snap.md_infallible().size))
+            });
         }
 
         if let Some(last_snap_mode) = &config.opt_last_snap {
-            versions_map.last_snap(last_snap_mode)
+            self.last_snap(last_snap_mode)
         }
+    }
 
-        Ok(versions_map)
+    // an encrypted dataset with an unloaded key hides its versions, which would otherwise
+    // look exactly like a file without versions, so explain why before the user gives up
+    fn warn_locked_datasets(&self) {
+        let without_versions: Vec<&Path> = self
+            .iter()
+            .filter(|(_pathdata, snaps)| snaps.is_empty())
+            .map(|(pathdata, _snaps)| pathdata.path_buf.as_path())
+            .collect();
+
+        if without_versions.is_empty()
+            || !GLOBAL_CONFIG
+                .dataset_collection
+                .map_of_datasets
+                .values()
+                .any(|dataset_info| dataset_info.fs_type == FilesystemType::Zfs)
+        {
+            return;
+        }
+
+        let Ok(locked_datasets) = LockedDatasets::new() else {
+            return;
+        };
+
+        without_versions.into_iter().for_each(|path| {
+            locked_datasets.for_path(path).for_each(|locked| {
+                Diagnostic::notice(
+                    "dataset_locked",
+                    format!(
+                        "Versions of {:?} may exist, but the encrypted dataset {:?} is locked (its key is not loaded). \
+                        Load the key with LOAD_KEY, or with \"zfs load-key\", and try again.",
+                        path, locked.name
+                    ),
+                )
+                .with_path(path)
+                .with_dataset(Path::new(&locked.name))
+                .print()
+            })
+        });
     }
 
     pub fn is_live_version_redundant(live_pathdata: &PathData, snaps: &[PathData]) -> bool {
@@ -129,6 +255,23 @@ impl VersionsMap {
         });
     }
 
+    // retain only those versions whose attributes differ from the live version's, any versions
+    // of a file without a live version have nothing to differ from, and are not retained
+    fn changed_attrs(&mut self, changed_attrs: &[ChangedAttr]) {
+        self.iter_mut().for_each(|(pathdata, snaps)| {
+            let Some(live_attrs) = VersionAttrs::new(&pathdata.path_buf, changed_attrs) else {
+                snaps.clear();
+                return;
+            };
+
+            snaps.retain(|snap| {
+                VersionAttrs::new(&snap.path_buf, changed_attrs)
+                    .map(|snap_attrs| snap_attrs != live_attrs)
+                    .unwrap_or(false)
+            });
+        });
+    }
+
     fn last_snap(&mut self, last_snap_mode: &LastSnapMode) {
         self.iter_mut().for_each(|(pathdata, snaps)| {
             *snaps = match snaps.last() {
@@ -163,28 +306,198 @@ pub struct Versions {
 
 impl Versions {
     #[inline(always)]
-    fn new(pathdata: &PathData, config: &Config) -> HttmResult<Self> {
-        let prox_opt_alts = ProximateDatasetAndOptAlts::new(pathdata)?;
-        let live_path = prox_opt_alts.pathdata.clone();
-        let snap_versions: Vec<PathData> = prox_opt_alts
-            .into_search_bundles()
-            .par_bridge()
-            .flat_map(|relative_path_snap_mounts| {
-                relative_path_snap_mounts.versions_processed(&config.uniqueness)
+    pub fn new(pathdata: &PathData, config: &Config) -> HttmResult<Self> {
+        Self::new_batch(std::slice::from_ref(pathdata), config).remove(0)
+    }
+
+    // the expensive part of a search is probing each snapshot for each path, so, instead of parallel
+    // searches per path, each of which is parallel per snapshot, every (path, snapshot) pair is one task,
+    // within one flat set of tasks, which remains well balanced, even when one path has thousands of
+    // snapshots and the rest only a few
+    pub fn new_batch(path_set: &[PathData], config: &Config) -> Vec<HttmResult<Self>> {
+        let all_prox_opt_alts: Vec<HttmResult<ProximateDatasetAndOptAlts>> = path_set
+            .par_iter()
+            .map(ProximateDatasetAndOptAlts::new)
+            .collect();
+
+        // key: index of the bundle, val: index of the path to which the bundle belongs, and the bundle
+        let bundles: Vec<(usize, RelativePathAndSnapMounts)> = all_prox_opt_alts
+            .iter()
+            .enumerate()
+            .filter_map(|(path_idx, res)| res.as_ref().ok().map(|prox| (path_idx, prox)))
+            .flat_map(|(path_idx, prox_opt_alts)| {
+                prox_opt_alts
+                    .into_search_bundles()
+                    .map(move |bundle| (path_idx, bundle))
             })
             .collect();
 
-        Ok(Self {
-            live_path,
-            snap_versions,
-        })
+        let probes: Vec<(bool, Option<Arc<DirIndex>>)> = bundles
+            .par_iter()
+            .map(|(_path_idx, bundle)| bundle.prepare_probe())
+            .collect();
+
+        let tasks: Vec<(usize, &Path)> = bundles
+            .iter()
+            .zip(probes.iter())
+            .enumerate()
+            .flat_map(
+                |(bundle_idx, ((_path_idx, bundle), (_is_cached, opt_dir_index)))| {
+                    let opt_file_name = bundle.relative_path.file_name();
+
+                    bundle
+                        .snap_mounts
+                        .iter()
+                        .filter(move |snap_mount| {
+                            RelativePathAndSnapMounts::is_candidate(
+                                snap_mount,
+                                opt_dir_index,
+                                opt_file_name,
+                            )
+                        })
+                        .map(move |snap_mount| (bundle_idx, snap_mount.as_path()))
+                },
+            )
+            .collect();
+
+        let mut versions_by_bundle: Vec<Vec<PathData>> = vec![Vec::new(); bundles.len()];
+
+        tasks
+            .into_par_iter()
+            .filter_map(|(bundle_idx, snap_mount)| {
+                let (_path_idx, bundle) = &bundles[bundle_idx];
+                let (is_cached, _opt_dir_index) = &probes[bundle_idx];
+
+                bundle
+                    .probe(snap_mount.join(bundle.relative_path), *is_cached)
+                    .map(|pathdata| (bundle_idx, pathdata))
+            })
+            .collect::<Vec<(usize, PathData)>>()
+            .into_iter()
+            .for_each(|(bundle_idx, pathdata)| versions_by_bundle[bundle_idx].push(pathdata));
+
+        // versions are deduplicated per bundle, exactly as when each bundle is searched alone
+        let processed: Vec<Vec<PathData>> = versions_by_bundle
+            .into_par_iter()
+            .map(|versions| {
+                let deduped = RelativePathAndSnapMounts::sort_dedup_versions(
+                    versions.into_par_iter(),
+                    &config.uniqueness,
+                );
+
+                RelativePathAndSnapMounts::cluster_versions(deduped, &config.uniqueness)
+            })
+            .collect();
+
+        let mut versions_by_path: Vec<Vec<PathData>> = vec![Vec::new(); path_set.len()];
+
+        bundles
+            .iter()
+            .zip(processed)
+            .for_each(|((path_idx, _bundle), versions)| {
+                versions_by_path[*path_idx].extend(versions)
+            });
+
+        all_prox_opt_alts
+            .into_iter()
+            .zip(versions_by_path)
+            .map(|(res, mut snap_versions)| {
+                let prox_opt_alts = res?;
+
+                if let Some(map_of_archives) = &config.dataset_collection.opt_map_of_archives {
+                    Self::add_archived_versions(
+                        &mut snap_versions,
+                        map_of_archives,
+                        prox_opt_alts.relative_path,
+                        &config.uniqueness,
+                    );
+                }
+
+                Ok(Self {
+                    live_path: prox_opt_alts.pathdata.clone(),
+                    snap_versions,
+                })
+            })
+            .collect()
+    }
+    // archived versions cannot be read as files, so, unless all versions are requested,
+    // we only omit those with the same metadata as a version already listed
+    fn add_archived_versions(
+        snap_versions: &mut Vec<PathData>,
+        map_of_archives: &MapOfArchives,
+        relative_path: &Path,
+        uniqueness: &ListSnapsOfType,
+    ) {
+        let archived_versions = map_of_archives.versions(relative_path);
+
+        if archived_versions.is_empty() {
+            return;
+        }
+
+        archived_versions.into_iter().for_each(|archived| {
+            if matches!(uniqueness, ListSnapsOfType::All)
+                || !snap_versions
+                    .iter()
+                    .any(|snap| snap.metadata == archived.metadata)
+            {
+                snap_versions.push(archived)
+            }
+        });
+
+        snap_versions.sort_by_cached_key(|pd| (pd.md_infallible().modify_time, pd.path_buf.clone()));
     }
     #[inline(always)]
-    fn destructure(self) -> (PathData, Vec<PathData>) {
+    pub fn destructure(self) -> (PathData, Vec<PathData>) {
         (self.live_path, self.snap_versions)
     }
 }
 
+// only those attributes requested are collected, so only those attributes are compared
+#[derive(Debug, Default, PartialEq, Eq)]
+struct VersionAttrs {
+    opt_owner: Option<(u32, u32)>,
+    opt_mode: Option<u32>,
+    opt_xattrs: Option<BTreeMap<OsString, Option<Vec<u8>>>>,
+}
+
+impl VersionAttrs {
+    fn new(path: &Path, changed_attrs: &[ChangedAttr]) -> Option<Self> {
+        use std::os::unix::fs::MetadataExt;
+
+        let md = path.symlink_metadata().ok()?;
+
+        let mut res = Self::default();
+
+        changed_attrs.iter().for_each(|attr| match attr {
+            ChangedAttr::Owner => res.opt_owner = Some((md.uid(), md.gid())),
+            // the permission bits only, as the file type is not an attribute which may be changed
+            ChangedAttr::Mode => res.opt_mode = Some(md.mode() & 0o7777),
+            ChangedAttr::Xattr => res.opt_xattrs = Some(Self::xattrs(path)),
+        });
+
+        Some(res)
+    }
+
+    #[cfg(feature = "xattrs")]
+    fn xattrs(path: &Path) -> BTreeMap<OsString, Option<Vec<u8>>> {
+        let Ok(attrs) = xattr::list(path) else {
+            return BTreeMap::new();
+        };
+
+        attrs
+            .map(|attr| {
+                let opt_value = xattr::get(path, &attr).ok().flatten();
+                (attr, opt_value)
+            })
+            .collect()
+    }
+
+    #[cfg(not(feature = "xattrs"))]
+    fn xattrs(_path: &Path) -> BTreeMap<OsString, Option<Vec<u8>>> {
+        BTreeMap::new()
+    }
+}
+
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 pub struct ProximateDatasetAndOptAlts<'a> {
     pub pathdata: &'a PathData,
@@ -237,6 +550,12 @@ impl<'a> ProximateDatasetAndOptAlts<'a> {
                 Ok,
             )?;
 
+        let (proximate_dataset, relative_path) =
+            match Self::opt_parent_fallback(pathdata, proximate_dataset) {
+                Some(fallback) => fallback,
+                None => (proximate_dataset, relative_path),
+            };
+
         let opt_alts = GLOBAL_CONFIG
             .dataset_collection
             .opt_map_of_alts
@@ -251,6 +570,63 @@ impl<'a> ProximateDatasetAndOptAlts<'a> {
             opt_alts,
         })
     }
+
+    // a freshly created child dataset has no snapshots, but its files may have resided within its parent
+    // dataset before the child was created, so search the nearest ancestor dataset which has snapshots
+    fn opt_parent_fallback(
+        pathdata: &'a PathData,
+        proximate_dataset: &'a Path,
+    ) -> Option<(&'a Path, &'a Path)> {
+        if !GLOBAL_CONFIG.opt_parent_fallback {
+            return None;
+        }
+
+        let map_of_snaps = &GLOBAL_CONFIG.dataset_collection.map_of_snaps;
+
+        let has_snaps = |dataset: &Path| {
+            map_of_snaps
+                .get(dataset)
+                .is_some_and(|snap_mounts| !snap_mounts.is_empty())
+        };
+
+        if has_snaps(proximate_dataset) {
+            return None;
+        }
+
+        let ancestor = proximate_dataset
+            .ancestors()
+            .skip(1)
+            .find(|ancestor| has_snaps(ancestor))?;
+
+        let relative_path = pathdata.path_buf.strip_prefix(ancestor).ok()?;
+
+        static NOTICED: Lazy<Mutex<HashSet<PathBuf>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+        let is_first_notice = NOTICED
+            .lock()
+            .map(|mut noticed| noticed.insert(proximate_dataset.to_path_buf()))
+            .unwrap_or(false);
+
+        if is_first_notice && !matches!(GLOBAL_CONFIG.exec_mode, ExecMode::Interactive(_)) {
+            Diagnostic::notice(
+                "parent_fallback",
+                format!(
+                    "Dataset {:?} has no snapshots, so httm searched the snapshots of its ancestor dataset {:?} instead.",
+                    proximate_dataset, ancestor
+                ),
+            )
+            .with_dataset(proximate_dataset)
+            .print();
+        }
+
+        // the ancestor is a key of the map of snaps, so lives as long as the global config
+        let ancestor = map_of_snaps
+            .get_key_value(ancestor)
+            .map(|(key, _snap_mounts)| key.as_path())?;
+
+        Some((ancestor, relative_path))
+    }
+
     #[inline(always)]
     pub fn datasets_of_interest(&'a self) -> impl Iterator<Item = &'a Path> {
         let alts = self
@@ -266,21 +642,35 @@ impl<'a> ProximateDatasetAndOptAlts<'a> {
     }
     #[inline(always)]
     pub fn into_search_bundles(&'a self) -> impl Iterator<Item = RelativePathAndSnapMounts<'a>> {
-        self.datasets_of_interest().flat_map(|dataset_of_interest| {
-            RelativePathAndSnapMounts::new(&self.relative_path, &dataset_of_interest)
-        })
+        let is_indexed = self
+            .pathdata
+            .path_buf
+            .parent()
+            .map(SnapIndex::is_indexed_dir)
+            .unwrap_or(false);
+
+        self.datasets_of_interest()
+            .flat_map(move |dataset_of_interest| {
+                RelativePathAndSnapMounts::new(self.relative_path, dataset_of_interest, is_indexed)
+            })
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct RelativePathAndSnapMounts<'a> {
     pub relative_path: &'a Path,
+    pub dataset_of_interest: &'a Path,
     pub snap_mounts: &'a [PathBuf],
+    pub is_indexed: bool,
 }
 
 impl<'a> RelativePathAndSnapMounts<'a> {
     #[inline(always)]
-    fn new(relative_path: &'a Path, dataset_of_interest: &Path) -> Option<Self> {
+    fn new(
+        relative_path: &'a Path,
+        dataset_of_interest: &'a Path,
+        is_indexed: bool,
+    ) -> Option<Self> {
         // building our relative path by removing parent below the snap dir
         //
         // for native searches the prefix is are the dirs below the most proximate dataset
@@ -292,58 +682,218 @@ impl<'a> RelativePathAndSnapMounts<'a> {
 
         Some(Self {
             relative_path,
+            dataset_of_interest,
             snap_mounts,
+            is_indexed,
         })
     }
     #[inline(always)]
     pub fn versions_processed(&'a self, uniqueness: &ListSnapsOfType) -> Vec<PathData> {
         let all_versions = self.versions_unprocessed();
 
-        Self::sort_dedup_versions(all_versions, uniqueness)
+        let deduped = Self::sort_dedup_versions(all_versions, uniqueness);
+
+        Self::cluster_versions(deduped, uniqueness)
+    }
+
+    // versions already found are deduplicated again, just as versions_processed would deduplicate them
+    pub fn deduplicate(versions: Vec<PathData>, uniqueness: &ListSnapsOfType) -> Vec<PathData> {
+        let deduped = Self::sort_dedup_versions(versions.into_par_iter(), uniqueness);
+
+        Self::cluster_versions(deduped, uniqueness)
     }
 
     pub fn last_version(&self) -> Option<PathData> {
+        if matches!(GLOBAL_CONFIG.probe_order, ProbeOrder::Newest) {
+            return self.last_version_newest_first();
+        }
+
         let mut sorted_versions = self.versions_processed(&ListSnapsOfType::All);
 
         sorted_versions.pop()
     }
+
+    // the snap mounts of this lookup are ranked just as sort_dedup_versions ranks the versions upon them,
+    // newest first, so the first rank which contains the file holds the same last version as above
+    fn last_version_newest_first(&self) -> Option<PathData> {
+        let (is_cached, opt_dir_index) = self.prepare_probe();
+        let opt_file_name = self.relative_path.file_name();
+        let opt_map_of_creation_times = GLOBAL_CONFIG
+            .dataset_collection
+            .opt_map_of_creation_times
+            .as_ref();
+
+        let mut ranked: Vec<(Option<SystemTime>, PathBuf)> = self
+            .snap_mounts
+            .iter()
+            .filter(|snap_mount| Self::is_candidate(snap_mount, &opt_dir_index, opt_file_name))
+            .map(|snap_mount| {
+                let joined_path = snap_mount.join(self.relative_path);
+                let opt_creation_time =
+                    opt_map_of_creation_times.and_then(|map_of_creation_times| {
+                        map_of_creation_times.creation_time(&joined_path)
+                    });
+
+                (opt_creation_time, joined_path)
+            })
+            .collect();
+
+        ranked.sort_unstable_by(|a, b| b.cmp(a));
+
+        // without creation times, versions are ranked by path alone
+        if opt_map_of_creation_times.is_none() {
+            return ranked
+                .into_iter()
+                .find_map(|(_opt_creation_time, joined_path)| self.probe(joined_path, is_cached));
+        }
+
+        // snapshots which share a creation time are ranked by the modify time of the versions upon them
+        ranked
+            .chunk_by(|a, b| a.0 == b.0)
+            .find_map(|same_creation_time| {
+                same_creation_time
+                    .iter()
+                    .filter_map(|(_opt_creation_time, joined_path)| {
+                        self.probe(joined_path.clone(), is_cached)
+                    })
+                    .max_by_key(|pd| (pd.md_infallible().modify_time, pd.path_buf.clone()))
+            })
+    }
     #[inline(always)]
     fn versions_unprocessed(&'a self) -> impl ParallelIterator<Item = PathData> + 'a {
+        let (is_cached, opt_dir_index) = self.prepare_probe();
+        let opt_file_name = self.relative_path.file_name();
+
         // get the DirEntry for our snapshot path which will have all our possible
         // snapshots, like so: .zfs/snapshots/<some snap name>/
-        self
-            .snap_mounts
+        self.snap_mounts
             .par_iter()
+            .filter(move |snap_mount| Self::is_candidate(snap_mount, &opt_dir_index, opt_file_name))
             .map(|path| path.join(self.relative_path))
-            .filter_map(|joined_path| {
-                match joined_path.symlink_metadata() {
-                    Ok(md) => {
-                        // why not PathData::new()? because symlinks will resolve!
-                        // symlinks from a snap will end up looking just like the link target, so this is very confusing...
-                        let path_metadata = PathMetadata::new(&md);
-
-                        Some(PathData {
+            .filter_map(move |joined_path| self.probe(joined_path, is_cached))
+    }
+
+    fn prepare_probe(&self) -> (bool, Option<Arc<DirIndex>>) {
+        let is_cached = self.is_cacheable();
+
+        if is_cached {
+            METADATA_CACHE.prepare(self.dataset_of_interest, self.snap_mounts);
+        }
+
+        // when the file's directory is indexed, only those snapshots known to contain the file are searched
+        let opt_dir_index = self
+            .relative_path
+            .parent()
+            .filter(|_| self.is_indexed)
+            .and_then(|relative_dir| {
+                SNAP_INDEX.get(self.dataset_of_interest, relative_dir, self.snap_mounts)
+            });
+
+        (is_cached, opt_dir_index)
+    }
+
+    #[inline(always)]
+    fn is_candidate(
+        snap_mount: &Path,
+        opt_dir_index: &Option<Arc<DirIndex>>,
+        opt_file_name: Option<&OsStr>,
+    ) -> bool {
+        if !Self::is_requested_snap_class(snap_mount) {
+            return false;
+        }
+
+        match (opt_dir_index, opt_file_name) {
+            (Some(dir_index), Some(file_name)) => dir_index.contains(snap_mount, file_name),
+            _ => true,
+        }
+    }
+
+    fn probe(&self, joined_path: PathBuf, is_cached: bool) -> Option<PathData> {
+        if is_cached {
+            if let Some(opt_metadata) = METADATA_CACHE.get(self.dataset_of_interest, &joined_path) {
+                return opt_metadata.map(|path_metadata| PathData {
+                    path_buf: joined_path,
+                    metadata: Some(path_metadata),
+                });
+            }
+        }
+
+        let _opt_permit = NetworkLimit::permit(self.dataset_of_interest);
+
+        match joined_path.symlink_metadata() {
+            Ok(md) => {
+                // why not PathData::new()? because symlinks will resolve!
+                // symlinks from a snap will end up looking just like the link target, so this is very confusing...
+                let path_metadata = PathMetadata::new(&md);
+
+                if is_cached {
+                    if let Some(path_metadata) = path_metadata {
+                        METADATA_CACHE.insert(self.dataset_of_interest, &joined_path, Some(path_metadata));
+                    }
+                }
+
+                Some(PathData {
+                    path_buf: joined_path,
+                    metadata: path_metadata,
+                })
+            },
+            // a file not found upon an immutable snapshot will never be found there
+            Err(err) if is_cached && err.kind() == ErrorKind::NotFound => {
+                METADATA_CACHE.insert(self.dataset_of_interest, &joined_path, None);
+                None
+            },
+            Err(err) => {
+                match err.kind() {
+                    // if the user has specified a privileged helper, delegate the stat to the helper
+                    ErrorKind::PermissionDenied if GLOBAL_CONFIG.opt_privileged_helper.is_some() => {
+                        let privileged_helper = GLOBAL_CONFIG.opt_privileged_helper.as_ref()?;
+
+                        privileged_helper.stat(&joined_path).map(|path_metadata| PathData {
                             path_buf: joined_path,
-                            metadata: path_metadata,
+                            metadata: Some(path_metadata),
                         })
                     },
-                    Err(err) => {
-                        match err.kind() {
-                            // if we do not have permissions to read the snapshot directories
-                            // fail/panic printing a descriptive error instead of flattening
-                            ErrorKind::PermissionDenied => {
-                                eprintln!("Error: When httm tried to find a file contained within a snapshot directory, permission was denied.  \
-                                Perhaps you need to use sudo or equivalent to view the contents of this snapshot (for instance, btrfs by default creates privileged snapshots).  \
-                                \nDetails: {err}");
-                                std::process::exit(1)
-                            },
-                            // if file metadata is not found, or is otherwise not available, 
-                            // continue, it simply means we do not have a snapshot of this file
-                            _ => None,
-                        }
+                    // if we do not have permissions to read the snapshot directories
+                    // fail/panic printing a descriptive error instead of flattening
+                    ErrorKind::PermissionDenied => {
+                        Diagnostic::new(
+                            Level::Error,
+                            "permission_denied",
+                            format!("When httm tried to find a file contained within a snapshot directory, permission was denied.  \
+                            Perhaps you need to use sudo or equivalent, or specify a PRIVILEGED_HELPER, to view the contents of this snapshot (for instance, btrfs by default creates privileged snapshots).  \
+                            \nDetails: {err}"),
+                        )
+                        .with_path(&joined_path)
+                        .with_dataset(self.dataset_of_interest)
+                        .print();
+                        std::process::exit(1)
                     },
+                    // if file metadata is not found, or is otherwise not available, 
+                    // continue, it simply means we do not have a snapshot of this file
+                    _ => None,
                 }
-            })
+            },
+        }
+    }
+
+    #[inline(always)]
+    fn is_cacheable(&self) -> bool {
+        GLOBAL_CONFIG.opt_cache_metadata && MetadataCache::is_immutable(self.dataset_of_interest)
+    }
+
+    #[inline(always)]
+    fn is_requested_snap_class(snap_mount: &Path) -> bool {
+        let Some(snap_classes) = &GLOBAL_CONFIG.opt_snap_classes else {
+            return true;
+        };
+
+        GLOBAL_CONFIG
+            .dataset_collection
+            .opt_map_of_snap_labels
+            .as_ref()
+            .and_then(|map_of_snap_labels| map_of_snap_labels.get(snap_mount))
+            .map(|label| snap_classes.iter().any(|class| label.is_class(class)))
+            .unwrap_or(false)
     }
 
     // remove duplicates with the same system modify time and size/file len (or contents! See --uniqueness)
@@ -353,18 +903,137 @@ impl<'a> RelativePathAndSnapMounts<'a> {
         iter: impl ParallelIterator<Item = PathData>,
         uniqueness: &ListSnapsOfType,
     ) -> Vec<PathData> {
+        let Some(map_of_creation_times) =
+            &GLOBAL_CONFIG.dataset_collection.opt_map_of_creation_times
+        else {
+            return match uniqueness {
+                ListSnapsOfType::All => {
+                    let mut vec: Vec<PathData> = iter.collect();
+                    vec.sort_unstable();
+                    vec
+                }
+                ListSnapsOfType::UniqueContents | ListSnapsOfType::UniqueMetadata => {
+                    let sorted_and_deduped: BTreeSet<CompareVersionsContainer> = iter
+                        .map(|pd| CompareVersionsContainer::new(pd, uniqueness))
+                        .collect();
+                    sorted_and_deduped.into_iter().map(PathData::from).collect()
+                }
+                ListSnapsOfType::UniqueKey(dedup_key) => {
+                    let mut vec: Vec<PathData> = iter.collect();
+                    vec.sort_by_cached_key(|pd| {
+                        (pd.md_infallible().modify_time, pd.path_buf.clone())
+                    });
+                    Self::dedup_by_key(vec, dedup_key)
+                }
+                ListSnapsOfType::UniqueCommand(compare_command) => {
+                    let mut vec: Vec<PathData> = iter.collect();
+                    vec.sort_by_cached_key(|pd| {
+                        (pd.md_infallible().modify_time, pd.path_buf.clone())
+                    });
+                    compare_command.dedup(vec)
+                }
+            };
+        };
+
+        // sort by snapshot creation time first, so, when deduplicating, we insert in order,
+        // and the version upon the earliest snapshot always wins, instead of an arbitrary one
+        let mut vec: Vec<PathData> = iter.collect();
+        vec.sort_by_cached_key(|pd| {
+            (
+                map_of_creation_times.creation_time(&pd.path_buf),
+                pd.md_infallible().modify_time,
+                pd.path_buf.clone(),
+            )
+        });
+
         match uniqueness {
-            ListSnapsOfType::All => {
-                let mut vec: Vec<PathData> = iter.collect();
-                vec.sort_unstable();
-                vec
-            }
+            ListSnapsOfType::All => vec,
+            // dedup by key retains the order given, so no re-sort is necessary
+            ListSnapsOfType::UniqueKey(dedup_key) => Self::dedup_by_key(vec, dedup_key),
+            ListSnapsOfType::UniqueCommand(compare_command) => compare_command.dedup(vec),
             ListSnapsOfType::UniqueContents | ListSnapsOfType::UniqueMetadata => {
-                let sorted_and_deduped: BTreeSet<CompareVersionsContainer> = iter
+                let deduped: BTreeSet<CompareVersionsContainer> = vec
+                    .into_iter()
                     .map(|pd| CompareVersionsContainer::new(pd, uniqueness))
                     .collect();
-                sorted_and_deduped.into_iter().map(PathData::from).collect()
+
+                let mut vec: Vec<PathData> = deduped.into_iter().map(PathData::from).collect();
+
+                // and then re-sort, as our BTreeSet is ordered by modify time
+                vec.sort_by_cached_key(|pd| {
+                    (
+                        map_of_creation_times.creation_time(&pd.path_buf),
+                        pd.md_infallible().modify_time,
+                    )
+                });
+                vec
+            }
+        }
+    }
+
+    fn cluster_versions(vec: Vec<PathData>, uniqueness: &ListSnapsOfType) -> Vec<PathData> {
+        match GLOBAL_CONFIG.opt_mtime_window {
+            Some(window) if !matches!(uniqueness, ListSnapsOfType::All) => {
+                Self::cluster_by_mtime(vec, window)
             }
+            _ => vec,
         }
     }
+
+    // versions whose modify times fall within the window beginning at the earliest modify time
+    // of a cluster are one version, and the last in time of each cluster wins, in the order given
+    fn cluster_by_mtime(vec: Vec<PathData>, window: Duration) -> Vec<PathData> {
+        let mut by_mtime: Vec<(SystemTime, usize)> = vec
+            .iter()
+            .enumerate()
+            .map(|(idx, pd)| (pd.md_infallible().modify_time, idx))
+            .collect();
+
+        by_mtime.sort_unstable();
+
+        let mut keep = vec![false; vec.len()];
+        let mut opt_cluster_start: Option<SystemTime> = None;
+        let mut opt_last_idx: Option<usize> = None;
+
+        by_mtime.iter().for_each(|(mtime, idx)| {
+            let is_same_cluster = opt_cluster_start.is_some_and(|start| {
+                mtime
+                    .duration_since(start)
+                    .is_ok_and(|elapsed| elapsed <= window)
+            });
+
+            match opt_last_idx {
+                // the previous last version of this cluster is superseded
+                Some(last_idx) if is_same_cluster => keep[last_idx] = false,
+                _ => opt_cluster_start = Some(*mtime),
+            }
+
+            keep[*idx] = true;
+            opt_last_idx = Some(*idx);
+        });
+
+        vec.into_iter()
+            .zip(keep)
+            .filter_map(|(pd, is_kept)| is_kept.then_some(pd))
+            .collect()
+    }
+
+    // the first version (in the order given) with a given key value wins
+    fn dedup_by_key(vec: Vec<PathData>, dedup_key: &DedupKey) -> Vec<PathData> {
+        let key_values: Vec<Option<DedupKeyValue>> = vec
+            .par_iter()
+            .map(|pd| pd.dedup_key_value(dedup_key))
+            .collect();
+
+        let mut seen: HashSet<DedupKeyValue> = HashSet::new();
+
+        vec.into_iter()
+            .zip(key_values)
+            .filter(|(_pd, opt_key_value)| match opt_key_value {
+                Some(key_value) => seen.insert(key_value.clone()),
+                None => true,
+            })
+            .map(|(pd, _opt_key_value)| pd)
+            .collect()
+    }
 }
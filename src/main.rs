@@ -21,23 +21,44 @@ mod data {
     pub mod selection;
 }
 mod display_map {
+    pub mod aliases;
+    pub mod datasets;
     pub mod format;
+    pub mod protection;
+    pub mod snap_probe;
 }
 mod display_versions {
+    pub mod checksums;
+    pub mod emit_shell;
+    pub mod file_type;
     pub mod format;
+    pub mod histogram;
     pub mod num_versions;
+    pub mod rsync_list;
+    pub mod stat;
+    pub mod table;
+    pub mod tree;
+    pub mod version_id;
     pub mod wrapper;
 }
 mod background {
     pub mod deleted;
+    pub mod ranked;
     pub mod recursive;
 }
 mod interactive {
     pub mod browse;
+    pub mod compare;
+    pub mod destination;
+    pub mod key_bindings;
+    pub mod merge;
     pub mod preview;
     pub mod prune;
+    pub mod recover;
     pub mod restore;
     pub mod select;
+    pub mod session;
+    pub mod top;
     pub mod view_mode;
 }
 mod roll_forward {
@@ -46,47 +67,112 @@ mod roll_forward {
     pub mod preserve_hard_links;
 }
 mod config {
+    pub mod actions;
+    pub mod config_file;
     pub mod generate;
     pub mod install_hot_keys;
 }
 mod library {
+    pub mod audit_log;
+    pub mod cache_file;
+    pub mod compare_command;
+    pub mod content_range;
+    pub mod diagnostics;
     pub mod diff_copy;
     pub mod file_ops;
+    pub mod hooks;
+    pub mod i18n;
     pub mod iter_extensions;
+    pub mod jobs;
+    pub mod materialize;
+    pub mod network_limit;
+    pub mod nfs4_acls;
+    pub mod output_file;
+    pub mod owner_map;
+    pub mod privileged;
+    pub mod redact;
+    pub mod rename_template;
     pub mod results;
+    pub mod self_test;
+    pub mod signals;
+    pub mod size_filter;
     pub mod snap_guard;
+    pub mod snap_limits;
     pub mod snap_mounts;
+    pub mod special_file;
+    pub mod stash;
+    pub mod text_encoding;
+    pub mod throttle;
     pub mod utility;
+    pub mod version_notes;
+    pub mod write_lock;
 }
 mod lookup {
     pub mod deleted;
+    pub mod dir_diff;
     pub mod file_mounts;
+    pub mod metadata_cache;
+    pub mod path_at;
+    pub mod reconstruct;
+    pub mod remote;
+    pub mod snap_index;
     pub mod snap_names;
     pub mod versions;
 }
 mod parse {
     pub mod aliases;
     pub mod alts;
+    pub mod archives;
+    pub mod creation;
+    pub mod dataset_props;
+    pub mod encrypted;
+    pub mod mount_backends;
+    pub mod mount_table;
+    pub mod mount_watch;
     pub mod mounts;
+    pub mod network_probe;
+    pub mod snap_labels;
     pub mod snaps;
+    pub mod unc_paths;
 }
 
 use crate::config::generate::InteractiveMode;
 use crate::interactive::browse::InteractiveBrowse;
+use crate::interactive::recover::RecoverWizard;
 use crate::interactive::select::InteractiveSelect;
 use background::recursive::NonInteractiveRecursiveWrapper;
 use config::generate::{Config, ExecMode};
+use display_map::aliases::AliasReport;
+use display_map::datasets::DatasetInventory;
 use display_map::format::PrintAsMap;
+use display_map::protection::ProtectionReport;
+use display_map::snap_probe::SnapshotProbe;
+use display_versions::version_id::VersionIds;
 use display_versions::wrapper::VersionsDisplayWrapper;
 use interactive::prune::PruneSnaps;
 use interactive::restore::InteractiveRestore;
+use interactive::top::TopDashboard;
+use library::diagnostics::Diagnostic;
+use library::jobs::RestoreJobs;
+use library::materialize::Materialize;
+use library::output_file::OutputFile;
+use library::redact::Redaction;
 use library::results::HttmResult;
+use library::self_test::self_test_from_args;
 use library::snap_mounts::SnapshotMounts;
+use library::text_encoding::Transcode;
 use library::utility::print_output_buf;
+use library::version_notes::VersionNotes;
+use lookup::dir_diff::DirDiff;
 use lookup::file_mounts::MountsForFiles;
+use lookup::metadata_cache::METADATA_CACHE;
+use lookup::path_at::PathAt;
+use lookup::reconstruct::ReconstructDir;
+use lookup::remote::RemoteVersions;
 use lookup::snap_names::SnapNameMap;
 use lookup::versions::VersionsMap;
 use once_cell::sync::Lazy;
+use parse::mount_table::MountTable;
 use roll_forward::exec::RollForward;
 
 pub const ZFS_HIDDEN_DIRECTORY: &str = ".zfs";
@@ -100,10 +186,29 @@ pub const NILFS2_SNAPSHOT_ID_KEY: &str = "cp=";
 pub const RESTIC_SNAPSHOT_DIRECTORY: &str = "snapshots";
 
 fn main() {
-    match exec() {
+    if let Some(result) = Transcode::from_args().or_else(self_test_from_args) {
+        if let Err(error) = result {
+            eprintln!("ERROR: {error}");
+            std::process::exit(1)
+        }
+
+        std::process::exit(0)
+    }
+
+    let result = exec().and_then(|_| OutputFile::finish(true));
+
+    METADATA_CACHE.save();
+
+    match result {
         Ok(_) => std::process::exit(0),
         Err(error) => {
-            eprintln!("ERROR: {error}");
+            let _ = OutputFile::finish(false);
+
+            if Diagnostic::is_json() {
+                Diagnostic::from_error(&*error).print();
+            } else {
+                eprintln!("ERROR: {}", Redaction::apply(&error.to_string()));
+            }
             std::process::exit(1)
         }
     }
@@ -114,7 +219,7 @@ fn main() {
 static GLOBAL_CONFIG: Lazy<Config> = Lazy::new(|| {
     Config::new()
         .map_err(|error| {
-            eprintln!("Error: {error}");
+            Diagnostic::from_error(&*error).print();
             std::process::exit(1)
         })
         .unwrap()
@@ -153,7 +258,7 @@ fn exec() -> HttmResult<()> {
             }
         }
         // ExecMode::BasicDisplay will be just printed, we already know the paths
-        ExecMode::BasicDisplay | ExecMode::NumVersions(_) => {
+        ExecMode::BasicDisplay | ExecMode::NumVersions(_) | ExecMode::Histogram(_) => {
             let versions_map = VersionsMap::new(&GLOBAL_CONFIG, &GLOBAL_CONFIG.paths)?;
             let output_buf = VersionsDisplayWrapper::from(&GLOBAL_CONFIG, versions_map).to_string();
 
@@ -172,9 +277,36 @@ fn exec() -> HttmResult<()> {
             print_output_buf(&output_buf)
         }
         ExecMode::Prune(opt_filters) => {
+            if let Some(datasets) = &GLOBAL_CONFIG.opt_datasets {
+                return PruneSnaps::exec_datasets(datasets, opt_filters);
+            }
+
             let versions_map = VersionsMap::new(&GLOBAL_CONFIG, &GLOBAL_CONFIG.paths)?;
             PruneSnaps::exec(versions_map, opt_filters)
         }
+        ExecMode::DirDiff(snap_or_date) => DirDiff::exec(snap_or_date),
+        ExecMode::PathAt(snap_or_date) => PathAt::exec(snap_or_date),
+        ExecMode::Reconstruct => ReconstructDir::exec(),
+        ExecMode::Top => TopDashboard::exec(),
+        ExecMode::Transcode(path) => Transcode::exec(path, GLOBAL_CONFIG.opt_encoding),
+        ExecMode::PrintNote(path) => VersionNotes::exec(path),
+        ExecMode::VersionIds(ids, action) => VersionIds::exec(ids, action),
+        ExecMode::CompareRemote(remote) => RemoteVersions::exec(remote),
+        ExecMode::CheckAliases => AliasReport::exec(),
+        ExecMode::ProbeSnapshots => SnapshotProbe::exec(),
+        ExecMode::Jobs(job_action) => RestoreJobs::exec(job_action),
+        ExecMode::ProtectionReport => ProtectionReport::exec(),
+        ExecMode::Recover => RecoverWizard::exec(),
+        ExecMode::ParseMounts(path) => MountTable::exec(path),
+        ExecMode::ListDatasets => {
+            let output_buf = DatasetInventory::new().to_string();
+
+            print_output_buf(&output_buf)
+        }
+        ExecMode::Materialize(farm_dir) => {
+            let versions_map = VersionsMap::new(&GLOBAL_CONFIG, &GLOBAL_CONFIG.paths)?;
+            Materialize::exec(versions_map, farm_dir)
+        }
         ExecMode::MountsForFiles(mount_display) => {
             let mounts_map = &MountsForFiles::new(mount_display)?;
             let printable_map: PrintAsMap = mounts_map.into();
@@ -15,11 +15,14 @@
 // For the full copyright and license information, please view the LICENSE file
 // that was distributed with this source code.
 
+use crate::library::diagnostics::Diagnostic;
 use crate::library::results::{HttmError, HttmResult};
 use crate::library::utility::fs_type_from_hidden_dir;
 use crate::parse::mounts::FilesystemType;
+use crate::parse::network_probe::{with_timeout, PROBE_TIMEOUT};
 use hashbrown::HashMap;
-use std::ffi::OsString;
+use rayon::prelude::*;
+use std::ffi::{OsStr, OsString};
 use std::ops::Deref;
 use std::path::{Path, PathBuf};
 
@@ -32,11 +35,21 @@ pub struct RemotePathAndFsType {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct MapOfAliases {
     inner: HashMap<PathBuf, RemotePathAndFsType>,
+    // every alias as the user configured it, valid or not, as (local dir, remote dir)
+    configured: Vec<(PathBuf, PathBuf)>,
 }
 
 impl From<HashMap<PathBuf, RemotePathAndFsType>> for MapOfAliases {
     fn from(map: HashMap<PathBuf, RemotePathAndFsType>) -> Self {
-        Self { inner: map }
+        let configured = map
+            .iter()
+            .map(|(local_dir, remote)| (local_dir.clone(), remote.remote_dir.clone()))
+            .collect();
+
+        Self {
+            inner: map,
+            configured,
+        }
     }
 }
 
@@ -49,6 +62,20 @@ impl Deref for MapOfAliases {
 }
 
 impl MapOfAliases {
+    // an environment variable which is set, but empty, is treated as unset
+    pub fn env_value(key: &str) -> Option<OsString> {
+        std::env::var_os(key).filter(|value| !value.is_empty())
+    }
+
+    // an environment variable's value is split exactly as the MAP_ALIASES flag's value delimiter splits its value
+    pub fn split_values(value: &OsStr) -> Vec<String> {
+        value
+            .to_string_lossy()
+            .split(',')
+            .map(str::to_owned)
+            .collect()
+    }
+
     pub fn new(
         raw_local_dir: &Option<OsString>,
         raw_snap_dir: &Option<OsString>,
@@ -66,7 +93,7 @@ impl MapOfAliases {
                 None => pwd.to_path_buf(),
             };
 
-            (snap_dir, local_dir)
+            (local_dir, snap_dir)
         });
 
         let mut aliases_iter: Vec<(PathBuf, PathBuf)> = match opt_input_aliases {
@@ -93,37 +120,146 @@ impl MapOfAliases {
             aliases_iter.push(value)
         }
 
-        let map_of_aliases: HashMap<PathBuf, RemotePathAndFsType> = aliases_iter
-            .into_iter()
-            .filter_map(|(local_dir, snap_dir)| {
-                if !local_dir.exists() || !snap_dir.exists() {
-                    [local_dir, snap_dir]
-                        .into_iter()
-                        .filter(|dir| !dir.exists())
-                        .for_each(|dir| {
-                            eprintln!(
-                "WARN: An alias path specified does not exist, or is not mounted: {:?}",
-                dir
-              )
-                        });
-                    return None;
+        // input paths are canonical, so local dirs must be too, else "./data" or "/data/" would never match,
+        // a local dir which does not exist is reported when probed below
+        aliases_iter
+            .iter_mut()
+            .for_each(|(local_dir, _remote_dir)| {
+                if let Ok(canonical) = local_dir.canonicalize() {
+                    *local_dir = canonical;
                 }
+            });
+
+        let effective_aliases: Vec<(PathBuf, PathBuf)> = aliases_iter
+            .iter()
+            .enumerate()
+            .filter(|(idx, (local_dir, remote_dir))| {
+                let Some((later_local_dir, later_remote_dir)) =
+                    Self::shadowed_by(&aliases_iter, *idx)
+                else {
+                    return true;
+                };
+
+                Diagnostic::warn(
+                    "alias_shadowed",
+                    format!(
+                        "The alias {:?}:{:?} is ignored, because a later alias for the same local directory, {:?}:{:?}, takes precedence.",
+                        local_dir, remote_dir, later_local_dir, later_remote_dir
+                    ),
+                )
+                .with_path(local_dir)
+                .print();
 
-                Some((local_dir, snap_dir))
+                false
             })
+            .map(|(_idx, alias)| alias.clone())
+            .collect();
+
+        // aliases are often network mounts, which may be slow, or hung, so each is probed in parallel,
+        // and a probe which does not respond in time is treated as not mounted
+        let map_of_aliases: HashMap<PathBuf, RemotePathAndFsType> = effective_aliases
+            .into_par_iter()
             .filter_map(|(local_dir, remote_dir)| {
-                fs_type_from_hidden_dir(&remote_dir).map(|fs_type| {
-                    (
-                        local_dir,
-                        RemotePathAndFsType {
-                            remote_dir,
-                            fs_type,
-                        },
+                let local_dir_clone = local_dir.clone();
+                let remote_dir_clone = remote_dir.clone();
+
+                let Some(probe) = with_timeout(move || {
+                    let missing: Vec<PathBuf> = [local_dir_clone, remote_dir_clone.clone()]
+                        .into_iter()
+                        .filter(|dir| !dir.exists())
+                        .collect();
+
+                    if !missing.is_empty() {
+                        return Err(missing);
+                    }
+
+                    Ok(fs_type_from_hidden_dir(&remote_dir_clone))
+                }) else {
+                    Diagnostic::warn(
+                        "probe_timeout",
+                        format!(
+                            "An alias path specified did not respond within {} seconds: {:?}",
+                            PROBE_TIMEOUT.as_secs(),
+                            remote_dir
+                        ),
                     )
-                })
+                    .with_path(&remote_dir)
+                    .print();
+                    return None;
+                };
+
+                let opt_fs_type = match probe {
+                    Ok(opt_fs_type) => opt_fs_type,
+                    Err(missing) => {
+                        missing.into_iter().for_each(|dir| {
+                            Diagnostic::warn(
+                                "alias_not_found",
+                                format!(
+                                    "An alias path specified does not exist, or is not mounted: {:?}",
+                                    dir
+                                ),
+                            )
+                            .with_path(&dir)
+                            .print()
+                        });
+                        return None;
+                    }
+                };
+
+                let Some(fs_type) = opt_fs_type else {
+                    Diagnostic::warn(
+                        "alias_unsupported",
+                        format!(
+                            "Could not detect a ZFS or btrfs-snapper hidden snapshot directory within the alias path specified: {:?}",
+                            remote_dir
+                        ),
+                    )
+                    .with_path(&remote_dir)
+                    .print();
+                    return None;
+                };
+
+                Some((
+                    local_dir,
+                    RemotePathAndFsType {
+                        remote_dir,
+                        fs_type,
+                    },
+                ))
             })
             .collect();
 
-        Ok(map_of_aliases.into())
+        Ok(Self {
+            inner: map_of_aliases,
+            configured: aliases_iter,
+        })
+    }
+
+    pub fn configured(&self) -> &[(PathBuf, PathBuf)] {
+        &self.configured
+    }
+
+    // where the same local dir is specified more than once, the alias specified last takes precedence,
+    // and each alias it shadows is ignored
+    pub fn shadowed_by(
+        configured: &[(PathBuf, PathBuf)],
+        idx: usize,
+    ) -> Option<&(PathBuf, PathBuf)> {
+        let (local_dir, _remote_dir) = configured.get(idx)?;
+
+        configured
+            .iter()
+            .skip(idx + 1)
+            .rfind(|(later_local_dir, _later_remote_dir)| later_local_dir == local_dir)
+    }
+
+    // aliases may be nested, such as "/data" and "/data/projects", and the alias with the longest
+    // local dir containing the path, that is, the nearest ancestor, claims the path
+    pub fn resolve<'a>(&'a self, path: &'a Path) -> Option<(&'a Path, &'a RemotePathAndFsType)> {
+        path.ancestors().find_map(|ancestor| {
+            self.inner
+                .get(ancestor)
+                .map(|remote_path_and_fs_type| (ancestor, remote_path_and_fs_type))
+        })
     }
 }
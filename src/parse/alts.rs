@@ -16,11 +16,13 @@
 // that was distributed with this source code.
 
 use crate::library::results::{HttmError, HttmResult};
-use crate::parse::mounts::MapOfDatasets;
-use hashbrown::HashMap;
+use crate::parse::mounts::{FilesystemType, MapOfDatasets};
+use hashbrown::{HashMap, HashSet};
 use rayon::prelude::*;
 use std::ops::Deref;
 use std::path::{Path, PathBuf};
+use std::process::Command as ExecProcess;
+use which::which;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct MapOfAlts {
@@ -50,19 +52,103 @@ impl Deref for MapOfAlts {
 impl MapOfAlts {
     // instead of looking up, precompute possible alt replicated mounts before exec
     pub fn new(map_of_datasets: &MapOfDatasets) -> Self {
+        // a replica need not share a name with its source, so we also look for datasets
+        // which share snapshot GUIDs, which are preserved by zfs send/receive
+        let map_of_lineage = Self::snap_guid_lineage(map_of_datasets).unwrap_or_default();
+
         let res: HashMap<PathBuf, AltMetadata> = map_of_datasets
             .par_iter()
             .flat_map(|(mount, _dataset_info)| {
-                Self::from_mount(mount, map_of_datasets).map(|datasets| (mount.clone(), datasets))
+                Self::from_mount(mount, map_of_datasets, &map_of_lineage)
+                    .map(|datasets| (mount.clone(), datasets))
             })
             .collect();
 
         res.into()
     }
 
+    // key: mount, val: set of the GUIDs of the snapshots of that mount's dataset
+    fn snap_guid_lineage(
+        map_of_datasets: &MapOfDatasets,
+    ) -> HttmResult<HashMap<PathBuf, HashSet<u64>>> {
+        let zfs_datasets: Vec<(&PathBuf, &PathBuf)> = map_of_datasets
+            .iter()
+            .filter(|(_mount, dataset_info)| dataset_info.fs_type == FilesystemType::Zfs)
+            .map(|(mount, dataset_info)| (mount, &dataset_info.source))
+            .collect();
+
+        // lineage is only interesting if there is more than one dataset to compare
+        if zfs_datasets.len() < 2 {
+            return Ok(HashMap::new());
+        }
+
+        let zfs_command = which("zfs").map_err(|_err| {
+            HttmError::new("'zfs' command not found. Make sure the command 'zfs' is in your path.")
+        })?;
+
+        // -H: tab separated, -p: parsable, -d 1: only immediate snapshots
+        let mut process_args: Vec<String> = [
+            "get",
+            "-H",
+            "-p",
+            "-d",
+            "1",
+            "-t",
+            "snapshot",
+            "-o",
+            "name,value",
+            "guid",
+        ]
+        .into_iter()
+        .map(|arg| arg.to_owned())
+        .collect();
+
+        process_args.extend(
+            zfs_datasets
+                .iter()
+                .map(|(_mount, source)| source.to_string_lossy().to_string()),
+        );
+
+        let process_output = ExecProcess::new(zfs_command).args(process_args).output()?;
+        let stderr_string = std::str::from_utf8(&process_output.stderr)?.trim();
+
+        if !stderr_string.is_empty() {
+            let msg = "httm was unable to determine snapshot GUIDs. The 'zfs' command issued the following error: ".to_owned() + stderr_string;
+
+            return Err(HttmError::new(&msg).into());
+        }
+
+        let stdout_string = std::str::from_utf8(&process_output.stdout)?;
+
+        let mut map_of_guids: HashMap<&str, HashSet<u64>> = HashMap::new();
+
+        stdout_string
+            .lines()
+            .filter_map(|line| line.split_once('\t'))
+            .filter_map(|(snap_name, value)| {
+                let (dataset, _snap) = snap_name.split_once('@')?;
+                value.trim().parse::<u64>().ok().map(|guid| (dataset, guid))
+            })
+            .for_each(|(dataset, guid)| {
+                map_of_guids.entry(dataset).or_default().insert(guid);
+            });
+
+        let map_of_lineage = zfs_datasets
+            .into_iter()
+            .filter_map(|(mount, source)| {
+                map_of_guids
+                    .remove(source.to_string_lossy().as_ref())
+                    .map(|guids| (mount.clone(), guids))
+            })
+            .collect();
+
+        Ok(map_of_lineage)
+    }
+
     fn from_mount(
         proximate_dataset_mount: &Path,
         map_of_datasets: &MapOfDatasets,
+        map_of_lineage: &HashMap<PathBuf, HashSet<u64>>,
     ) -> HttmResult<AltMetadata> {
         let proximate_dataset_fs_name = match &map_of_datasets.get(proximate_dataset_mount) {
             Some(dataset_info) => dataset_info.source.as_os_str(),
@@ -71,15 +157,21 @@ impl MapOfAlts {
             }
         };
 
+        let opt_proximate_guids = map_of_lineage.get(proximate_dataset_mount);
+
         // find a filesystem that ends with our most local filesystem name
         // but which has a prefix, like a different pool name: rpool might be
-        // replicated to tank/rpool
+        // replicated to tank/rpool, or a filesystem which shares a snapshot
+        // with our most local filesystem, and is therefore a replica, whatever its name
         let mut alt_replicated_mounts: Vec<PathBuf> = map_of_datasets
             .iter()
             .map(|(mount, dataset_info)| (mount, Path::new(&dataset_info.source)))
-            .filter(|(_mount, source)| {
-                source.as_os_str() != proximate_dataset_fs_name
-                    && source.ends_with(proximate_dataset_fs_name)
+            .filter(|(_mount, source)| source.as_os_str() != proximate_dataset_fs_name)
+            .filter(|(mount, source)| {
+                source.ends_with(proximate_dataset_fs_name)
+                    || opt_proximate_guids
+                        .zip(map_of_lineage.get(mount.as_path()))
+                        .is_some_and(|(proximate_guids, guids)| !proximate_guids.is_disjoint(guids))
             })
             .map(|(mount, _source)| mount)
             .cloned()
@@ -0,0 +1,521 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::data::paths::{PathData, PathMetadata};
+use crate::library::diagnostics::Diagnostic;
+use crate::library::results::{HttmError, HttmResult};
+use hashbrown::HashMap;
+use hashbrown::HashSet;
+use rayon::prelude::*;
+use std::ffi::OsStr;
+use std::fs::File;
+use std::io::{BufReader, ErrorKind, Read};
+use std::ops::Deref;
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+// a btrfs send stream begins with this magic, followed by a little endian u32 version
+const BTRFS_SEND_STREAM_MAGIC: &[u8] = b"btrfs-stream\0";
+
+// command types, see the kernel's fs/btrfs/send.h
+const BTRFS_SEND_C_SUBVOL: u16 = 1;
+const BTRFS_SEND_C_SNAPSHOT: u16 = 2;
+const BTRFS_SEND_C_MKFILE: u16 = 3;
+const BTRFS_SEND_C_MKDIR: u16 = 4;
+const BTRFS_SEND_C_RENAME: u16 = 9;
+const BTRFS_SEND_C_UNLINK: u16 = 11;
+const BTRFS_SEND_C_RMDIR: u16 = 12;
+const BTRFS_SEND_C_WRITE: u16 = 15;
+const BTRFS_SEND_C_CLONE: u16 = 16;
+const BTRFS_SEND_C_TRUNCATE: u16 = 17;
+const BTRFS_SEND_C_UTIMES: u16 = 20;
+const BTRFS_SEND_C_END: u16 = 21;
+const BTRFS_SEND_C_UPDATE_EXTENT: u16 = 22;
+const BTRFS_SEND_C_FALLOCATE: u16 = 23;
+const BTRFS_SEND_C_ENCODED_WRITE: u16 = 25;
+
+// attribute types, see the kernel's fs/btrfs/send.h
+const BTRFS_SEND_A_CTRANSID: u16 = 2;
+const BTRFS_SEND_A_SIZE: u16 = 4;
+const BTRFS_SEND_A_MTIME: u16 = 10;
+const BTRFS_SEND_A_PATH: u16 = 15;
+const BTRFS_SEND_A_PATH_TO: u16 = 16;
+const BTRFS_SEND_A_FILE_OFFSET: u16 = 18;
+const BTRFS_SEND_A_DATA: u16 = 19;
+const BTRFS_SEND_A_CLONE_LEN: u16 = 24;
+const BTRFS_SEND_A_FALLOCATE_MODE: u16 = 25;
+const BTRFS_SEND_A_UNENCODED_FILE_LEN: u16 = 27;
+
+const FALLOC_FL_KEEP_SIZE: u32 = 0x01;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchivedVersion {
+    pub stream: PathBuf,
+    pub metadata: PathMetadata,
+}
+
+impl ArchivedVersion {
+    // an archived version has no path of its own, so we name it as if the stream were a directory
+    pub fn pathdata(&self, relative_path: &Path) -> PathData {
+        PathData {
+            path_buf: self.stream.join(relative_path),
+            metadata: Some(self.metadata),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MapOfArchives {
+    // key: path relative to the subvolume sent, val: versions of that path, oldest first
+    inner: HashMap<PathBuf, Vec<ArchivedVersion>>,
+}
+
+impl From<HashMap<PathBuf, Vec<ArchivedVersion>>> for MapOfArchives {
+    fn from(map: HashMap<PathBuf, Vec<ArchivedVersion>>) -> Self {
+        Self { inner: map }
+    }
+}
+
+impl Deref for MapOfArchives {
+    type Target = HashMap<PathBuf, Vec<ArchivedVersion>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl MapOfArchives {
+    // index every send stream within the archive dir, by replaying only each stream's metadata,
+    // so we never need to store or receive the streams themselves
+    pub fn new(archive_dir: &Path) -> HttmResult<Self> {
+        let stream_paths: Vec<PathBuf> = std::fs::read_dir(archive_dir)
+            .map_err(|err| {
+                HttmError::with_context("httm could not read the archive directory specified", &err)
+            })?
+            .flatten()
+            .filter(|entry| entry.file_type().map(|ft| ft.is_file()).unwrap_or(false))
+            .map(|entry| entry.path())
+            .collect();
+
+        let mut streams: Vec<StreamIndex> = stream_paths
+            .par_iter()
+            .filter_map(|stream_path| match StreamIndex::new(stream_path) {
+                Ok(index) => Some(index),
+                Err(err) => {
+                    Diagnostic::warn(
+                        "invalid_send_stream",
+                        format!(
+                            "Skipping {:?}, which could not be read as a btrfs send stream: {}",
+                            stream_path, err
+                        ),
+                    )
+                    .with_path(stream_path)
+                    .print();
+                    None
+                }
+            })
+            .collect();
+
+        if streams.is_empty() {
+            return Err(HttmError::new(
+                "httm could not find any uncompressed btrfs send streams within the archive directory specified.",
+            )
+            .into());
+        }
+
+        // the generation at which each stream was sent orders the streams,
+        // as an incremental stream only records what changed since its parent
+        streams.sort_by_key(|index| (index.ctransid, index.stream_mtime, index.stream.clone()));
+
+        let mut map_of_archives: HashMap<PathBuf, Vec<ArchivedVersion>> = HashMap::new();
+
+        streams.into_iter().for_each(|index| {
+            index
+                .records
+                .into_iter()
+                .filter_map(|(relative_path, record)| {
+                    // an incremental stream may not record an attribute which did not change,
+                    // so fall back to the most recent prior version, at its prior name, if renamed
+                    let opt_prior = map_of_archives
+                        .get(record.opt_origin.as_ref().unwrap_or(&relative_path))
+                        .and_then(|versions| versions.last())
+                        .map(|version| version.metadata);
+
+                    let opt_base_size = record.opt_size.or_else(|| opt_prior.map(|md| md.size));
+                    let size = match (opt_base_size, record.opt_written_end) {
+                        (Some(base_size), Some(written_end)) => base_size.max(written_end),
+                        (opt_base_size, opt_written_end) => opt_base_size.or(opt_written_end)?,
+                    };
+                    let modify_time = record
+                        .opt_modify_time
+                        .or_else(|| opt_prior.map(|md| md.modify_time))?;
+
+                    let version = ArchivedVersion {
+                        stream: index.stream.clone(),
+                        metadata: PathMetadata {
+                            size,
+                            modify_time,
+                            opt_identity: None,
+                        },
+                    };
+
+                    Some((relative_path, version))
+                })
+                .collect::<Vec<(PathBuf, ArchivedVersion)>>()
+                .into_iter()
+                .for_each(|(relative_path, version)| {
+                    map_of_archives
+                        .entry(relative_path)
+                        .or_default()
+                        .push(version)
+                });
+        });
+
+        Ok(map_of_archives.into())
+    }
+
+    pub fn versions(&self, relative_path: &Path) -> Vec<PathData> {
+        self.inner
+            .get(relative_path)
+            .into_iter()
+            .flatten()
+            .map(|version| version.pathdata(relative_path))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct FileRecord {
+    // the path of this file before this stream, when it was renamed by this stream
+    opt_origin: Option<PathBuf>,
+    // exact size, when the file was created or truncated by this stream
+    opt_size: Option<u64>,
+    // a write past the end of the file extends it, but a write within it does not tell us its size
+    opt_written_end: Option<u64>,
+    opt_modify_time: Option<SystemTime>,
+}
+
+impl FileRecord {
+    fn extend_size(&mut self, end: u64) {
+        self.opt_written_end = Some(
+            self.opt_written_end
+                .map_or(end, |written_end| written_end.max(end)),
+        );
+    }
+}
+
+struct StreamIndex {
+    stream: PathBuf,
+    stream_mtime: Option<SystemTime>,
+    ctransid: u64,
+    // key: path relative to the subvolume sent, val: those attributes of the file this stream changed
+    records: HashMap<PathBuf, FileRecord>,
+}
+
+impl StreamIndex {
+    fn new(stream: &Path) -> HttmResult<Self> {
+        let file = File::open(stream)?;
+        let stream_mtime = file.metadata().and_then(|md| md.modified()).ok();
+        let mut reader = BufReader::new(file);
+
+        let mut magic = [0u8; BTRFS_SEND_STREAM_MAGIC.len()];
+        reader.read_exact(&mut magic)?;
+
+        if magic != BTRFS_SEND_STREAM_MAGIC {
+            return Err(HttmError::new(
+                "stream does not begin with the btrfs send magic (compressed or encrypted streams are not supported)",
+            )
+            .into());
+        }
+
+        let version = read_u32(&mut reader)?;
+
+        let mut index = Self {
+            stream: stream.to_path_buf(),
+            stream_mtime,
+            ctransid: 0,
+            records: HashMap::new(),
+        };
+
+        let mut dirs: HashSet<PathBuf> = HashSet::new();
+        let mut payload: Vec<u8> = Vec::new();
+
+        loop {
+            // command header: u32 payload len, u16 command, u32 crc
+            let len = match read_u32(&mut reader) {
+                Ok(len) => len as usize,
+                Err(err) if err.kind() == ErrorKind::UnexpectedEof => break,
+                Err(err) => return Err(err.into()),
+            };
+            let cmd = read_u16(&mut reader)?;
+            let _crc = read_u32(&mut reader)?;
+
+            payload.resize(len, 0);
+            reader.read_exact(&mut payload)?;
+
+            let attrs = Attributes::new(&payload, version);
+
+            match cmd {
+                BTRFS_SEND_C_SUBVOL | BTRFS_SEND_C_SNAPSHOT => {
+                    if let Some(ctransid) = attrs.u64(BTRFS_SEND_A_CTRANSID) {
+                        index.ctransid = index.ctransid.max(ctransid);
+                    }
+                }
+                BTRFS_SEND_C_MKFILE => {
+                    if let Some(path) = attrs.path(BTRFS_SEND_A_PATH) {
+                        // a new file is empty until it is written
+                        index.records.insert(
+                            path,
+                            FileRecord {
+                                opt_size: Some(0),
+                                ..Default::default()
+                            },
+                        );
+                    }
+                }
+                BTRFS_SEND_C_MKDIR => {
+                    if let Some(path) = attrs.path(BTRFS_SEND_A_PATH) {
+                        dirs.insert(path);
+                    }
+                }
+                BTRFS_SEND_C_RENAME => {
+                    if let (Some(from), Some(to)) = (
+                        attrs.path(BTRFS_SEND_A_PATH),
+                        attrs.path(BTRFS_SEND_A_PATH_TO),
+                    ) {
+                        index.rename(&mut dirs, &from, &to);
+                    }
+                }
+                BTRFS_SEND_C_UNLINK => {
+                    if let Some(path) = attrs.path(BTRFS_SEND_A_PATH) {
+                        index.records.remove(&path);
+                    }
+                }
+                BTRFS_SEND_C_RMDIR => {
+                    if let Some(path) = attrs.path(BTRFS_SEND_A_PATH) {
+                        dirs.remove(&path);
+                    }
+                }
+                BTRFS_SEND_C_WRITE => {
+                    if let (Some(path), Some(offset), Some(data)) = (
+                        attrs.path(BTRFS_SEND_A_PATH),
+                        attrs.u64(BTRFS_SEND_A_FILE_OFFSET),
+                        attrs.get(BTRFS_SEND_A_DATA),
+                    ) {
+                        index.record(path).extend_size(offset + data.len() as u64);
+                    }
+                }
+                BTRFS_SEND_C_UPDATE_EXTENT => {
+                    if let (Some(path), Some(offset), Some(len)) = (
+                        attrs.path(BTRFS_SEND_A_PATH),
+                        attrs.u64(BTRFS_SEND_A_FILE_OFFSET),
+                        attrs.u64(BTRFS_SEND_A_SIZE),
+                    ) {
+                        index.record(path).extend_size(offset + len);
+                    }
+                }
+                BTRFS_SEND_C_CLONE => {
+                    if let (Some(path), Some(offset), Some(len)) = (
+                        attrs.path(BTRFS_SEND_A_PATH),
+                        attrs.u64(BTRFS_SEND_A_FILE_OFFSET),
+                        attrs.u64(BTRFS_SEND_A_CLONE_LEN),
+                    ) {
+                        index.record(path).extend_size(offset + len);
+                    }
+                }
+                BTRFS_SEND_C_ENCODED_WRITE => {
+                    if let (Some(path), Some(offset), Some(len)) = (
+                        attrs.path(BTRFS_SEND_A_PATH),
+                        attrs.u64(BTRFS_SEND_A_FILE_OFFSET),
+                        attrs.u64(BTRFS_SEND_A_UNENCODED_FILE_LEN),
+                    ) {
+                        index.record(path).extend_size(offset + len);
+                    }
+                }
+                BTRFS_SEND_C_FALLOCATE => {
+                    let keep_size = attrs
+                        .u32(BTRFS_SEND_A_FALLOCATE_MODE)
+                        .is_some_and(|mode| mode & FALLOC_FL_KEEP_SIZE != 0);
+
+                    if let (false, Some(path), Some(offset), Some(len)) = (
+                        keep_size,
+                        attrs.path(BTRFS_SEND_A_PATH),
+                        attrs.u64(BTRFS_SEND_A_FILE_OFFSET),
+                        attrs.u64(BTRFS_SEND_A_SIZE),
+                    ) {
+                        index.record(path).extend_size(offset + len);
+                    }
+                }
+                BTRFS_SEND_C_TRUNCATE => {
+                    if let (Some(path), Some(size)) =
+                        (attrs.path(BTRFS_SEND_A_PATH), attrs.u64(BTRFS_SEND_A_SIZE))
+                    {
+                        let record = index.record(path);
+                        record.opt_size = Some(size);
+                        record.opt_written_end = None;
+                    }
+                }
+                BTRFS_SEND_C_UTIMES => {
+                    if let (Some(path), Some(modify_time)) = (
+                        attrs.path(BTRFS_SEND_A_PATH),
+                        attrs.timespec(BTRFS_SEND_A_MTIME),
+                    ) {
+                        // directories are not versions, but the utimes of a pre-existing dir
+                        // is indistinguishable from a file's, such dirs have no size and are dropped later
+                        if !dirs.contains(&path) {
+                            index.record(path).opt_modify_time = Some(modify_time);
+                        }
+                    }
+                }
+                BTRFS_SEND_C_END => break,
+                _ => {}
+            }
+        }
+
+        Ok(index)
+    }
+
+    fn record(&mut self, path: PathBuf) -> &mut FileRecord {
+        self.records.entry(path).or_default()
+    }
+
+    fn rename(&mut self, dirs: &mut HashSet<PathBuf>, from: &Path, to: &Path) {
+        // a dir rename moves everything recorded beneath it
+        if dirs.remove(from) {
+            dirs.insert(to.to_path_buf());
+
+            let moved: Vec<PathBuf> = self
+                .records
+                .keys()
+                .chain(dirs.iter())
+                .filter(|path| path.starts_with(from))
+                .cloned()
+                .collect();
+
+            moved.into_iter().for_each(|old| {
+                let Ok(rest) = old.strip_prefix(from) else {
+                    return;
+                };
+
+                let new = to.join(rest);
+
+                if let Some(record) = self.records.remove(&old) {
+                    self.records.insert(new, record);
+                } else if dirs.remove(&old) {
+                    dirs.insert(new);
+                }
+            });
+
+            return;
+        }
+
+        let record = self.records.remove(from).unwrap_or_else(|| FileRecord {
+            // a file which existed before this stream, and which was only renamed
+            opt_origin: Some(from.to_path_buf()),
+            ..Default::default()
+        });
+
+        self.records.insert(to.to_path_buf(), record);
+    }
+}
+
+struct Attributes<'a> {
+    inner: HashMap<u16, &'a [u8]>,
+}
+
+impl<'a> Attributes<'a> {
+    // attributes are type-length-value, except, from version 2 of the protocol,
+    // the data attribute has no length and runs to the end of the command
+    fn new(payload: &'a [u8], version: u32) -> Self {
+        let mut inner = HashMap::new();
+        let mut rest = payload;
+
+        while rest.len() >= 2 {
+            let attr_type = u16::from_le_bytes([rest[0], rest[1]]);
+            rest = &rest[2..];
+
+            if version >= 2 && attr_type == BTRFS_SEND_A_DATA {
+                inner.insert(attr_type, rest);
+                break;
+            }
+
+            if rest.len() < 2 {
+                break;
+            }
+
+            let attr_len = u16::from_le_bytes([rest[0], rest[1]]) as usize;
+            rest = &rest[2..];
+
+            if rest.len() < attr_len {
+                break;
+            }
+
+            inner.insert(attr_type, &rest[..attr_len]);
+            rest = &rest[attr_len..];
+        }
+
+        Self { inner }
+    }
+
+    fn get(&self, attr_type: u16) -> Option<&'a [u8]> {
+        self.inner.get(&attr_type).copied()
+    }
+
+    fn path(&self, attr_type: u16) -> Option<PathBuf> {
+        self.get(attr_type)
+            .map(|bytes| PathBuf::from(OsStr::from_bytes(bytes)))
+    }
+
+    fn u32(&self, attr_type: u16) -> Option<u32> {
+        self.get(attr_type)
+            .and_then(|bytes| bytes.try_into().ok())
+            .map(u32::from_le_bytes)
+    }
+
+    fn u64(&self, attr_type: u16) -> Option<u64> {
+        self.get(attr_type)
+            .and_then(|bytes| bytes.try_into().ok())
+            .map(u64::from_le_bytes)
+    }
+
+    // a timespec is a u64 of seconds followed by a u32 of nanoseconds
+    fn timespec(&self, attr_type: u16) -> Option<SystemTime> {
+        let bytes = self.get(attr_type)?;
+
+        if bytes.len() != 12 {
+            return None;
+        }
+
+        let secs = u64::from_le_bytes(bytes[..8].try_into().ok()?);
+        let nanos = u32::from_le_bytes(bytes[8..].try_into().ok()?);
+
+        Some(SystemTime::UNIX_EPOCH + Duration::new(secs, nanos))
+    }
+}
+
+fn read_u16(reader: &mut impl Read) -> std::io::Result<u16> {
+    let mut buf = [0u8; 2];
+    reader.read_exact(&mut buf)?;
+    Ok(u16::from_le_bytes(buf))
+}
+
+fn read_u32(reader: &mut impl Read) -> std::io::Result<u32> {
+    let mut buf = [0u8; 4];
+    reader.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
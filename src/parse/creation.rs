@@ -0,0 +1,187 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::library::results::{HttmError, HttmResult};
+use crate::parse::mounts::{FilesystemType, MapOfDatasets};
+use crate::parse::snap_labels::parse_gmt_token;
+use crate::parse::snaps::MapOfSnaps;
+use hashbrown::HashMap;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::process::Command as ExecProcess;
+use std::time::{Duration, SystemTime};
+use which::which;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MapOfCreationTimes {
+    inner: HashMap<PathBuf, SystemTime>,
+}
+
+impl From<HashMap<PathBuf, SystemTime>> for MapOfCreationTimes {
+    fn from(map: HashMap<PathBuf, SystemTime>) -> Self {
+        Self { inner: map }
+    }
+}
+
+impl Deref for MapOfCreationTimes {
+    type Target = HashMap<PathBuf, SystemTime>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl MapOfCreationTimes {
+    // precompute the creation time of each snapshot mount, keyed by snap mount,
+    // so we only ever need to exec the zfs command once per run
+    pub fn new(map_of_datasets: &MapOfDatasets, map_of_snaps: &MapOfSnaps) -> HttmResult<Self> {
+        let shadow_copy_times = Self::shadow_copy_times(map_of_datasets, map_of_snaps);
+
+        let zfs_datasets: Vec<(&PathBuf, &PathBuf)> = map_of_datasets
+            .iter()
+            .filter(|(_mount, dataset_info)| dataset_info.fs_type == FilesystemType::Zfs)
+            .map(|(mount, dataset_info)| (mount, &dataset_info.source))
+            .collect();
+
+        if zfs_datasets.is_empty() {
+            if !shadow_copy_times.is_empty() {
+                return Ok(shadow_copy_times.into());
+            }
+
+            return Err(HttmError::new(
+                "httm could not find any ZFS datasets or SMB shadow copies for which to determine snapshot creation times.",
+            )
+            .into());
+        }
+
+        let zfs_command = which("zfs").map_err(|_err| {
+            HttmError::new("'zfs' command not found. Make sure the command 'zfs' is in your path.")
+        })?;
+
+        // -H: tab separated, -p: parsable (seconds since the epoch), -d 1: only immediate snapshots
+        let mut process_args: Vec<String> = [
+            "get",
+            "-H",
+            "-p",
+            "-d",
+            "1",
+            "-t",
+            "snapshot",
+            "-o",
+            "name,value",
+            "creation",
+        ]
+        .into_iter()
+        .map(|arg| arg.to_owned())
+        .collect();
+
+        process_args.extend(
+            zfs_datasets
+                .iter()
+                .map(|(_mount, source)| source.to_string_lossy().to_string()),
+        );
+
+        let process_output = ExecProcess::new(zfs_command).args(process_args).output()?;
+        let stderr_string = std::str::from_utf8(&process_output.stderr)?.trim();
+
+        // stderr_string is a string not an error, so here we build an err or output
+        if !stderr_string.is_empty() {
+            let msg = "httm was unable to determine snapshot creation times. The 'zfs' command issued the following error: ".to_owned() + stderr_string;
+
+            return Err(HttmError::new(&msg).into());
+        }
+
+        let stdout_string = std::str::from_utf8(&process_output.stdout)?;
+
+        let snap_name_times: HashMap<&str, SystemTime> = stdout_string
+            .lines()
+            .filter_map(|line| line.split_once('\t'))
+            .filter_map(|(snap_name, value)| {
+                value.trim().parse::<u64>().ok().map(|secs| {
+                    (
+                        snap_name,
+                        SystemTime::UNIX_EPOCH + Duration::from_secs(secs),
+                    )
+                })
+            })
+            .collect();
+
+        let mut map_of_creation_times: HashMap<PathBuf, SystemTime> = zfs_datasets
+            .into_iter()
+            .filter_map(|(mount, source)| {
+                map_of_snaps
+                    .get(mount)
+                    .map(|snap_mounts| (source, snap_mounts))
+            })
+            .flat_map(|(source, snap_mounts)| {
+                snap_mounts.iter().filter_map(|snap_mount| {
+                    let snap_name = snap_mount.file_name()?.to_string_lossy();
+                    let full_name = format!("{}@{}", source.to_string_lossy(), snap_name);
+
+                    snap_name_times
+                        .get(full_name.as_str())
+                        .map(|time| (snap_mount.clone(), *time))
+                })
+            })
+            .collect();
+
+        map_of_creation_times.extend(shadow_copy_times);
+
+        Ok(map_of_creation_times.into())
+    }
+
+    // shadow copy creation times are parsed from their GMT tokens, and are cheap to determine,
+    // so, when there are SMB shadow copies, a map of only those creation times may be worth having
+    pub fn from_shadow_copies(
+        map_of_datasets: &MapOfDatasets,
+        map_of_snaps: &MapOfSnaps,
+    ) -> Option<Self> {
+        let shadow_copy_times = Self::shadow_copy_times(map_of_datasets, map_of_snaps);
+
+        if shadow_copy_times.is_empty() {
+            return None;
+        }
+
+        Some(shadow_copy_times.into())
+    }
+
+    fn shadow_copy_times(
+        map_of_datasets: &MapOfDatasets,
+        map_of_snaps: &MapOfSnaps,
+    ) -> HashMap<PathBuf, SystemTime> {
+        map_of_datasets
+            .iter()
+            .filter(|(_mount, dataset_info)| dataset_info.fs_type == FilesystemType::ShadowCopy)
+            .filter_map(|(mount, _dataset_info)| map_of_snaps.get(mount))
+            .flatten()
+            .filter_map(|snap_mount| {
+                let snap_name = snap_mount.file_name()?.to_string_lossy();
+
+                parse_gmt_token(&snap_name).map(|time| (snap_mount.clone(), time))
+            })
+            .collect()
+    }
+
+    // a snap version path is always a snap mount joined with a relative path,
+    // so the first ancestor which is a key is our snap mount
+    pub fn creation_time(&self, snap_path: &Path) -> Option<SystemTime> {
+        snap_path
+            .ancestors()
+            .find_map(|ancestor| self.inner.get(ancestor))
+            .copied()
+    }
+}
@@ -0,0 +1,286 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+use crate::library::cache_file::CacheFile;
+use crate::library::utility::httm_cache_dir;
+use crate::parse::mounts::{DatasetMetadata, FilesystemType, MountType};
+use hashbrown::{HashMap, HashSet};
+use std::collections::BTreeSet;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::process::Command as ExecProcess;
+use std::time::Duration;
+use which::which;
+
+// ZFS user properties, which are inherited by child datasets, so a pool's administrator
+// may configure httm for every user of a pool, or of a tree of datasets, at once
+const PROP_IGNORE: &str = "httm:ignore";
+const PROP_SNAP_FILTER: &str = "httm:snap-filter";
+// a native property, set upon received replicas, so that they are not modified between receives
+const PROP_READONLY: &str = "readonly";
+// a property set, or unset, is honoured by the next httm run after this long
+const CACHE_TTL: Duration = Duration::from_secs(60);
+const CACHE_FILE_NAME: &str = "dataset_props";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DatasetProps {
+    // "httm:ignore=true": the dataset is excluded, as if specified with EXCLUDE_DATASET
+    pub is_ignored: bool,
+    // "httm:snap-filter=daily,weekly": only snapshots of the classes given are searched, as if specified with SNAP_CLASS
+    pub opt_snap_filter: Option<Vec<String>>,
+    // "readonly=on": the dataset may not be modified, as is usual of a received replica
+    pub is_readonly: bool,
+}
+
+impl DatasetProps {
+    fn is_empty(&self) -> bool {
+        !self.is_ignored && self.opt_snap_filter.is_none() && !self.is_readonly
+    }
+
+    fn set(&mut self, property: &str, value: &str) {
+        match property {
+            PROP_IGNORE => {
+                self.is_ignored = matches!(
+                    value.trim().to_ascii_lowercase().as_str(),
+                    "true" | "on" | "yes" | "1"
+                )
+            }
+            PROP_SNAP_FILTER => {
+                let classes: Vec<String> = value
+                    .split_terminator(',')
+                    .map(|class| class.trim().to_owned())
+                    .filter(|class| !class.is_empty())
+                    .collect();
+
+                self.opt_snap_filter = (!classes.is_empty()).then_some(classes);
+            }
+            PROP_READONLY => self.is_readonly = value.trim() == "on",
+            _ => {}
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MapOfDatasetProps {
+    // key: mount, val: only those properties which are set
+    inner: HashMap<PathBuf, DatasetProps>,
+}
+
+impl Deref for MapOfDatasetProps {
+    type Target = HashMap<PathBuf, DatasetProps>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl MapOfDatasetProps {
+    // the properties are only a convenience, so, where they cannot be read, say, because the 'zfs' command
+    // is missing, or the pool is not accessible from within a container, httm simply proceeds without them
+    pub fn new(map_of_datasets: &HashMap<PathBuf, DatasetMetadata>) -> Option<Self> {
+        let mounts_by_name: HashMap<String, &PathBuf> = map_of_datasets
+            .iter()
+            .filter(|(_mount, dataset_info)| {
+                matches!(dataset_info.fs_type, FilesystemType::Zfs)
+                    && matches!(dataset_info.mount_type, MountType::Local)
+            })
+            .map(|(mount, dataset_info)| {
+                (dataset_info.source.to_string_lossy().into_owned(), mount)
+            })
+            .collect();
+
+        if mounts_by_name.is_empty() {
+            return None;
+        }
+
+        let mut dataset_names: Vec<&str> = mounts_by_name.keys().map(String::as_str).collect();
+        dataset_names.sort_unstable();
+
+        let zfs_output = Self::zfs_get(&dataset_names)?;
+
+        let mut inner: HashMap<PathBuf, DatasetProps> = HashMap::new();
+
+        std::str::from_utf8(&zfs_output)
+            .ok()?
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.split('\t');
+                let name = fields.next()?;
+                let property = fields.next()?;
+                let value = fields.next()?;
+
+                if value == "-" {
+                    return None;
+                }
+
+                let mount = mounts_by_name.get(name)?;
+
+                Some((*mount, property, value))
+            })
+            .for_each(|(mount, property, value)| {
+                inner.entry(mount.clone()).or_default().set(property, value)
+            });
+
+        inner.retain(|_mount, props| !props.is_empty());
+
+        if inner.is_empty() {
+            return None;
+        }
+
+        Some(Self { inner })
+    }
+
+    // httm is often run many times in a row, as by the preview of each version in the browse view,
+    // so the output of "zfs get" is cached, and reused, for the same datasets, for a short while
+    fn zfs_get(dataset_names: &[&str]) -> Option<Vec<u8>> {
+        let properties = format!("{PROP_IGNORE},{PROP_SNAP_FILTER},{PROP_READONLY}");
+
+        let key = blake3::hash(format!("{properties}\0{}", dataset_names.join("\0")).as_bytes())
+            .to_hex()
+            .to_string();
+
+        let opt_cache_path = httm_cache_dir().map(|cache_dir| cache_dir.join(CACHE_FILE_NAME));
+
+        if let Some(cached) = opt_cache_path
+            .as_deref()
+            .and_then(|cache_path| Self::read_cache(cache_path, &key))
+        {
+            return Some(cached);
+        }
+
+        let zfs_command = which("zfs").ok()?;
+
+        // -H: tab separated, and an unset property has the value "-"
+        let process_output = ExecProcess::new(zfs_command)
+            .args(["get", "-H", "-o", "name,property,value", &properties])
+            .args(dataset_names)
+            .output()
+            .ok()?;
+
+        if !process_output.status.success() {
+            return None;
+        }
+
+        if let Some(cache_path) = opt_cache_path {
+            let mut contents = format!("{key}\n").into_bytes();
+            contents.extend_from_slice(&process_output.stdout);
+
+            let _ = CacheFile::write(&cache_path, &contents);
+        }
+
+        Some(process_output.stdout)
+    }
+
+    // a cache is only used for the very same datasets, and only until it is older than CACHE_TTL
+    fn read_cache(cache_path: &Path, key: &str) -> Option<Vec<u8>> {
+        let age = cache_path
+            .metadata()
+            .ok()?
+            .modified()
+            .ok()?
+            .elapsed()
+            .ok()?;
+
+        if age >= CACHE_TTL {
+            return None;
+        }
+
+        let contents = CacheFile::read(cache_path)?;
+
+        let newline = contents.iter().position(|byte| *byte == b'\n')?;
+
+        if &contents[..newline] != key.as_bytes() {
+            return None;
+        }
+
+        Some(contents[newline + 1..].to_vec())
+    }
+
+    pub fn is_ignored(&self, mount: &Path) -> bool {
+        self.inner
+            .get(mount)
+            .map(|props| props.is_ignored)
+            .unwrap_or(false)
+    }
+
+    // a received replica shares the GUIDs of its snapshots with the dataset from which it was sent,
+    // so, where that dataset is also upon this machine, as when a backup pool is replicated from a
+    // local pool, the writable origin of a replica may be found among the datasets which share them
+    pub fn writable_origin(replica: &str) -> Option<String> {
+        let zfs_command = which("zfs").ok()?;
+
+        let process_output = ExecProcess::new(&zfs_command)
+            .args(["list", "-H", "-p", "-t", "snapshot", "-o", "name,guid"])
+            .output()
+            .ok()?;
+
+        if !process_output.status.success() {
+            return None;
+        }
+
+        let snaps: Vec<(&str, &str)> = std::str::from_utf8(&process_output.stdout)
+            .ok()?
+            .lines()
+            .filter_map(|line| line.split_once('\t'))
+            .filter_map(|(snap_name, guid)| {
+                snap_name
+                    .split_once('@')
+                    .map(|(dataset, _snap)| (dataset, guid))
+            })
+            .collect();
+
+        let replica_guids: HashSet<&str> = snaps
+            .iter()
+            .filter(|(dataset, _guid)| *dataset == replica)
+            .map(|(_dataset, guid)| *guid)
+            .collect();
+
+        let candidates: BTreeSet<&str> = snaps
+            .iter()
+            .filter(|(dataset, guid)| *dataset != replica && replica_guids.contains(guid))
+            .map(|(dataset, _guid)| *dataset)
+            .collect();
+
+        if candidates.is_empty() {
+            return None;
+        }
+
+        // other replicas of the same origin share the same GUIDs, so only a writable candidate will do
+        let process_output = ExecProcess::new(&zfs_command)
+            .args(["get", "-H", "-o", "name,value", PROP_READONLY])
+            .args(&candidates)
+            .output()
+            .ok()?;
+
+        if !process_output.status.success() {
+            return None;
+        }
+
+        std::str::from_utf8(&process_output.stdout)
+            .ok()?
+            .lines()
+            .filter_map(|line| line.split_once('\t'))
+            .find(|(_dataset, value)| value.trim() == "off")
+            .map(|(dataset, _value)| dataset.to_owned())
+    }
+
+    pub fn is_readonly(&self, mount: &Path) -> bool {
+        self.inner
+            .get(mount)
+            .map(|props| props.is_readonly)
+            .unwrap_or(false)
+    }
+}
@@ -0,0 +1,163 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::library::diagnostics::Diagnostic;
+use crate::library::results::{HttmError, HttmResult};
+use std::path::{Path, PathBuf};
+use std::process::Command as ExecProcess;
+use which::which;
+
+// an encrypted dataset whose key is not loaded cannot be mounted, and neither can its snapshots,
+// so any versions upon such a dataset are invisible to httm until the key is loaded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LockedDataset {
+    pub name: String,
+    pub mount: PathBuf,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LockedDatasets {
+    inner: Vec<LockedDataset>,
+}
+
+impl LockedDatasets {
+    pub fn new() -> HttmResult<Self> {
+        let zfs_command = which("zfs").map_err(|_err| {
+            HttmError::new("'zfs' command not found. Make sure the command 'zfs' is in your path.")
+        })?;
+
+        // -H: tab separated, keystatus is "-" for unencrypted datasets
+        let process_args = vec![
+            "list",
+            "-H",
+            "-t",
+            "filesystem",
+            "-o",
+            "name,mountpoint,keystatus",
+        ];
+
+        let process_output = ExecProcess::new(zfs_command).args(&process_args).output()?;
+        let stderr_string = std::str::from_utf8(&process_output.stderr)?.trim();
+
+        if !stderr_string.is_empty() {
+            let msg = "httm was unable to determine the key status of datasets. The 'zfs' command issued the following error: ".to_owned() + stderr_string;
+
+            return Err(HttmError::new(&msg).into());
+        }
+
+        let inner: Vec<LockedDataset> = std::str::from_utf8(&process_output.stdout)?
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.split('\t');
+                let name = fields.next()?;
+                let mountpoint = fields.next()?;
+                let keystatus = fields.next()?;
+
+                // "legacy" and "none" mountpoints are not paths we can match against
+                if keystatus.trim() != "unavailable" || !mountpoint.starts_with('/') {
+                    return None;
+                }
+
+                Some(LockedDataset {
+                    name: name.to_owned(),
+                    mount: PathBuf::from(mountpoint),
+                })
+            })
+            .collect();
+
+        Ok(Self { inner })
+    }
+
+    // a locked dataset is of interest when a path resides beneath its mount,
+    // or, for a directory, when the locked dataset's mount resides beneath that path
+    pub fn for_path<'a>(&'a self, path: &'a Path) -> impl Iterator<Item = &'a LockedDataset> {
+        self.inner
+            .iter()
+            .filter(move |locked| path.starts_with(&locked.mount) || locked.mount.starts_with(path))
+    }
+
+    pub fn load_keys(requested_paths: &[PathBuf]) -> HttmResult<()> {
+        let locked_datasets = Self::new()?;
+
+        let mut of_interest: Vec<&LockedDataset> = requested_paths
+            .iter()
+            .flat_map(|path| locked_datasets.for_path(path))
+            .collect();
+
+        of_interest.sort_by_key(|locked| &locked.name);
+        of_interest.dedup();
+
+        if of_interest.is_empty() {
+            return Ok(());
+        }
+
+        let zfs_command = which("zfs").map_err(|_err| {
+            HttmError::new("'zfs' command not found. Make sure the command 'zfs' is in your path.")
+        })?;
+
+        // the key may be a passphrase, so zfs is left to prompt the user on the terminal
+        of_interest.into_iter().for_each(|locked| {
+            Diagnostic::notice(
+                "loading_key",
+                format!(
+                    "Loading the key for the encrypted dataset {:?}, which is locked.",
+                    locked.name
+                ),
+            )
+            .with_dataset(Path::new(&locked.name))
+            .print();
+
+            let res = ExecProcess::new(&zfs_command)
+                .args(["load-key", &locked.name])
+                .status()
+                .and_then(|load_status| {
+                    if !load_status.success() {
+                        return Ok(load_status);
+                    }
+
+                    ExecProcess::new(&zfs_command)
+                        .args(["mount", &locked.name])
+                        .status()
+                });
+
+            match res {
+                Ok(status) if status.success() => {}
+                Ok(_status) => Diagnostic::warn(
+                    "load_key_failed",
+                    format!(
+                        "httm was unable to load the key for, or mount, the dataset {:?}. \
+                        Loading a key requires root, or delegated 'zfs allow' load-key and mount, privileges.",
+                        locked.name
+                    ),
+                )
+                .with_dataset(Path::new(&locked.name))
+                .print(),
+                Err(err) => Diagnostic::warn(
+                    "load_key_failed",
+                    format!(
+                        "httm was unable to load the key for the dataset {:?}: {}",
+                        locked.name, err
+                    ),
+                )
+                .with_dataset(Path::new(&locked.name))
+                .print(),
+            }
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,143 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::library::results::{HttmError, HttmResult};
+use crate::parse::mount_table::{MountTable, MountTableFormat};
+use proc_mounts::MountInfo;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MountBackend {
+    // the mount table at /proc/mounts (or /etc/mnttab)
+    Proc,
+    // the more complete, per-process, mount table at /proc/self/mountinfo
+    MountInfo,
+    // the C library's mount table API: getmntent(3) on Linux, getmntinfo(3) on Mac OS and FreeBSD
+    LibMount,
+    // parse the output of the 'mount' command
+    Cmd,
+}
+
+impl MountBackend {
+    pub fn from_mountinfo(path: &Path) -> HttmResult<Vec<MountInfo>> {
+        let mount_table = MountTable::from_file(path, MountTableFormat::MountInfo)?;
+
+        if mount_table.mount_infos.is_empty() {
+            let msg = format!("httm could not parse any mounts from: {path:?}");
+            return Err(HttmError::new(&msg).into());
+        }
+
+        Ok(mount_table.mount_infos)
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn from_libmount() -> HttmResult<Vec<MountInfo>> {
+        use std::ffi::CStr;
+
+        let mut mount_infos: Vec<MountInfo> = Vec::new();
+
+        // SAFETY: the mount table is opened, read, and closed only here, and each entry's
+        // strings are copied before the next call to getmntent, which may reuse them
+        unsafe {
+            let file = libc::setmntent(c"/proc/self/mounts".as_ptr(), c"r".as_ptr());
+
+            if file.is_null() {
+                return Err(HttmError::new(
+                    "httm could not open the mount table with setmntent(3).",
+                )
+                .into());
+            }
+
+            loop {
+                let entry = libc::getmntent(file);
+
+                if entry.is_null() {
+                    break;
+                }
+
+                let entry = &*entry;
+
+                let to_string =
+                    |ptr: *const libc::c_char| CStr::from_ptr(ptr).to_string_lossy().to_string();
+
+                mount_infos.push(MountInfo {
+                    source: PathBuf::from(to_string(entry.mnt_fsname)),
+                    dest: PathBuf::from(to_string(entry.mnt_dir)),
+                    fstype: to_string(entry.mnt_type),
+                    options: to_string(entry.mnt_opts)
+                        .split(',')
+                        .map(|opt| opt.to_owned())
+                        .collect(),
+                    dump: entry.mnt_freq,
+                    pass: entry.mnt_passno,
+                });
+            }
+
+            libc::endmntent(file);
+        }
+
+        Ok(mount_infos)
+    }
+
+    #[cfg(any(target_os = "macos", target_os = "freebsd"))]
+    pub fn from_libmount() -> HttmResult<Vec<MountInfo>> {
+        use std::ffi::CStr;
+
+        let mut mount_buf: *mut libc::statfs = std::ptr::null_mut();
+
+        // SAFETY: getmntinfo(3) owns the buffer it returns, which is valid until the next call,
+        // and all strings are copied out of the buffer before we return
+        let mount_infos: Vec<MountInfo> = unsafe {
+            let count = libc::getmntinfo(&mut mount_buf, libc::MNT_NOWAIT);
+
+            if count <= 0 || mount_buf.is_null() {
+                return Err(HttmError::new(
+                    "httm could not read the mount table with getmntinfo(3).",
+                )
+                .into());
+            }
+
+            std::slice::from_raw_parts(mount_buf, count as usize)
+                .iter()
+                .map(|stat| {
+                    let to_string = |chars: &[libc::c_char]| {
+                        CStr::from_ptr(chars.as_ptr()).to_string_lossy().to_string()
+                    };
+
+                    MountInfo {
+                        source: PathBuf::from(to_string(&stat.f_mntfromname)),
+                        dest: PathBuf::from(to_string(&stat.f_mntonname)),
+                        fstype: to_string(&stat.f_fstypename),
+                        options: Vec::new(),
+                        dump: 0,
+                        pass: 0,
+                    }
+                })
+                .collect()
+        };
+
+        Ok(mount_infos)
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "freebsd")))]
+    pub fn from_libmount() -> HttmResult<Vec<MountInfo>> {
+        Err(HttmError::new(
+            "The libmount mount backend is not available on this platform.  Try the cmd mount backend.",
+        )
+        .into())
+    }
+}
@@ -0,0 +1,574 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::library::results::{HttmError, HttmResult};
+use crate::library::utility::print_output_buf;
+use crate::parse::mounts::{
+    BaseFilesystemInfo, AFP_FSTYPE, BTRFS_FSTYPE, BTRFS_ROOT_SUBVOL, CIFS_FSTYPE,
+    FUSE_FSTYPE_LINUX, NFS_FSTYPE, NILFS2_FSTYPE, SMB_FSTYPE, ZFS_FSTYPE,
+};
+use crate::{GLOBAL_CONFIG, NILFS2_SNAPSHOT_ID_KEY};
+use proc_mounts::MountInfo;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+// no mount table has lines anywhere near this long, so a longer line is garbage, and is not parsed at all
+const MAX_LINE_LEN: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MountTableFormat {
+    // /proc/mounts, or /etc/mnttab: "source dest fstype options dump pass"
+    Proc,
+    // /proc/self/mountinfo: "36 35 98:0 /mnt1 /mnt2 rw,noatime master:1 - ext3 /dev/root rw,errors=continue"
+    MountInfo,
+    // the output of the 'mount' command, GNU: "source on dest type fstype (options)", or BSD: "source on dest (fstype, options)"
+    Cmd,
+}
+
+impl MountTableFormat {
+    fn name(&self) -> &'static str {
+        match self {
+            MountTableFormat::Proc => "proc",
+            MountTableFormat::MountInfo => "mountinfo",
+            MountTableFormat::Cmd => "mount command",
+        }
+    }
+
+    // a mount table submitted by a user may be of any format, so we guess by the first line which parses as any format
+    fn detect(text: &str) -> Option<Self> {
+        text.lines()
+            .filter(|line| !line.trim().is_empty())
+            .find_map(|line| {
+                [
+                    MountTableFormat::MountInfo,
+                    MountTableFormat::Cmd,
+                    MountTableFormat::Proc,
+                ]
+                .into_iter()
+                .find(|format| MountTable::parse_line(line, format).is_some())
+            })
+    }
+}
+
+// the mount table parsers never panic, and never fail as a whole, upon any input:
+// each line either parses, or is set aside as unparsed, so a single odd line never hides every other mount
+pub struct MountTable {
+    pub format: MountTableFormat,
+    pub mount_infos: Vec<MountInfo>,
+    pub unparsed: Vec<(usize, String)>,
+}
+
+impl MountTable {
+    pub fn new(text: &str, format: MountTableFormat) -> Self {
+        let (parsed, unparsed): (Vec<_>, Vec<_>) = text
+            .lines()
+            .enumerate()
+            .filter(|(_idx, line)| !line.trim().is_empty())
+            .map(|(idx, line)| (idx + 1, line, Self::parse_line(line, &format)))
+            .partition(|(_line_num, _line, opt_mount_info)| opt_mount_info.is_some());
+
+        Self {
+            format,
+            mount_infos: parsed
+                .into_iter()
+                .filter_map(|(_line_num, _line, opt_mount_info)| opt_mount_info)
+                .collect(),
+            unparsed: unparsed
+                .into_iter()
+                .map(|(line_num, line, _opt_mount_info)| (line_num, line.to_owned()))
+                .collect(),
+        }
+    }
+
+    pub fn from_file(path: &Path, format: MountTableFormat) -> HttmResult<Self> {
+        let contents = std::fs::read(path)?;
+
+        Ok(Self::new(&String::from_utf8_lossy(&contents), format))
+    }
+
+    fn parse_line(line: &str, format: &MountTableFormat) -> Option<MountInfo> {
+        if line.len() > MAX_LINE_LEN || line.contains('\0') {
+            return None;
+        }
+
+        let mount_info = match format {
+            MountTableFormat::Proc => Self::parse_proc_line(line),
+            MountTableFormat::MountInfo => Self::parse_mountinfo_line(line),
+            MountTableFormat::Cmd => Self::parse_cmd_line(line),
+        }?;
+
+        // whatever else may be odd about a line, a mount which is not an absolute path is no mount
+        if !mount_info.dest.is_absolute()
+            || mount_info.source.as_os_str().is_empty()
+            || mount_info.fstype.is_empty()
+        {
+            return None;
+        }
+
+        Some(mount_info)
+    }
+
+    fn parse_proc_line(line: &str) -> Option<MountInfo> {
+        let mut fields = line.split_whitespace();
+
+        let source = fields.next()?;
+        let dest = fields.next()?;
+        let fstype = fields.next()?;
+        let options = fields.next()?;
+
+        // /etc/mnttab has the time of the mount in place of the dump field, so neither is required to be small
+        let dump = fields.next().and_then(|value| value.parse::<i32>().ok());
+        let pass = fields.next().and_then(|value| value.parse::<i32>().ok());
+
+        Some(MountInfo {
+            source: PathBuf::from(Self::unescape(source)),
+            dest: PathBuf::from(Self::unescape(dest)),
+            fstype: Self::unescape(fstype),
+            options: options.split(',').map(Self::unescape).collect(),
+            dump: dump.unwrap_or_default(),
+            pass: pass.unwrap_or_default(),
+        })
+    }
+
+    // a mountinfo line looks like:
+    // 36 35 98:0 /mnt1 /mnt2 rw,noatime master:1 - ext3 /dev/root rw,errors=continue
+    // where the optional fields, like "master:1", are ended by a single "-"
+    fn parse_mountinfo_line(line: &str) -> Option<MountInfo> {
+        let (pre_separator, post_separator) = line.split_once(" - ")?;
+
+        let mut pre_fields = pre_separator.split(' ');
+
+        // the mount ID and parent ID are always numbers, which is how a mountinfo line is told from other formats
+        pre_fields.next()?.parse::<u64>().ok()?;
+        pre_fields.next()?.parse::<u64>().ok()?;
+
+        let dest = pre_fields.nth(2)?;
+        let mount_options = pre_fields.next()?;
+
+        let mut post_fields = post_separator.split(' ');
+        let fstype = post_fields.next()?;
+        let source = post_fields.next()?;
+        let super_options = post_fields.next().unwrap_or_default();
+
+        // some options, like a btrfs subvol, are only found in the super options
+        let options: Vec<String> = mount_options
+            .split(',')
+            .chain(super_options.split(','))
+            .filter(|opt| !opt.is_empty())
+            .map(Self::unescape)
+            .collect();
+
+        Some(MountInfo {
+            source: PathBuf::from(Self::unescape(source)),
+            dest: PathBuf::from(Self::unescape(dest)),
+            fstype: Self::unescape(fstype),
+            options,
+            dump: 0,
+            pass: 0,
+        })
+    }
+
+    // unlike the other formats, the mount command does not escape whitespace,
+    // so the source and the mount are whatever lies between the separators
+    fn parse_cmd_line(line: &str) -> Option<MountInfo> {
+        let (source, rest) = line.split_once(" on ")?;
+
+        let (dest, fstype, options) = match rest.split_once(" type ") {
+            // GNU Linux mount output
+            Some((dest, rest)) => {
+                let (fstype, options) = match rest.split_once(" (") {
+                    Some((fstype, options)) => (fstype, options),
+                    None => (rest, ""),
+                };
+
+                (dest, fstype.trim(), options)
+            }
+            // Busybox and BSD mount output, where the fstype is the first word within the parentheses
+            None => {
+                let (dest, rest) = rest.split_once(" (")?;
+
+                let (fstype, options) = match rest.split_once([',', ')']) {
+                    Some((fstype, options)) => (fstype, options),
+                    None => (rest, ""),
+                };
+
+                (dest, fstype.trim(), options)
+            }
+        };
+
+        let options: Vec<String> = options
+            .trim_end()
+            .trim_end_matches(')')
+            .split(',')
+            .map(str::trim)
+            .filter(|opt| !opt.is_empty())
+            .map(str::to_owned)
+            .collect();
+
+        Some(MountInfo {
+            source: PathBuf::from(source),
+            dest: PathBuf::from(dest),
+            fstype: fstype.to_owned(),
+            options,
+            dump: 0,
+            pass: 0,
+        })
+    }
+
+    // whitespace and backslashes are escaped as octal, like "\040" for a space
+    fn unescape(field: &str) -> String {
+        if !field.contains('\\') {
+            return field.to_owned();
+        }
+
+        let bytes = field.as_bytes();
+        let mut res: Vec<u8> = Vec::with_capacity(bytes.len());
+        let mut idx = 0;
+
+        while idx < bytes.len() {
+            let opt_octal = bytes
+                .get(idx + 1..idx + 4)
+                .filter(|_| bytes[idx] == b'\\')
+                .and_then(|digits| std::str::from_utf8(digits).ok())
+                .and_then(|digits| u8::from_str_radix(digits, 8).ok());
+
+            match opt_octal {
+                Some(byte) => {
+                    res.push(byte);
+                    idx += 4;
+                }
+                None => {
+                    res.push(bytes[idx]);
+                    idx += 1;
+                }
+            }
+        }
+
+        String::from_utf8_lossy(&res).to_string()
+    }
+
+    pub fn btrfs_subvol(mount_info: &MountInfo) -> Option<PathBuf> {
+        let keyed_options: BTreeMap<&str, &str> = mount_info
+            .options
+            .iter()
+            .filter(|line| line.contains('='))
+            .filter_map(|line| line.split_once('='))
+            .collect();
+
+        keyed_options
+            .get("subvol")
+            .map(|subvol| match keyed_options.get("subvolid") {
+                Some(id) if *id == "5" => BTRFS_ROOT_SUBVOL.clone(),
+                _ => PathBuf::from(subvol),
+            })
+    }
+
+    // what httm would make of a mount, judged only by the mount table, as the mount itself is upon another machine
+    fn verdict(mount_info: &MountInfo) -> String {
+        if mount_info
+            .options
+            .iter()
+            .any(|opt| opt.contains(NILFS2_SNAPSHOT_ID_KEY))
+        {
+            return "snapshot mount (nilfs2 checkpoint), not a dataset".to_owned();
+        }
+
+        // as with the mounts of this system, a snapshot mounted upon access is excluded, unless INCLUDE_SNAP_MOUNTS
+        if BaseFilesystemInfo::is_snap_mount_path(&mount_info.dest)
+            || (mount_info.fstype == ZFS_FSTYPE
+                && mount_info.source.to_string_lossy().contains('@'))
+        {
+            return "snapshot mount, not a dataset".to_owned();
+        }
+
+        match mount_info.fstype.as_str() {
+            ZFS_FSTYPE => "dataset (zfs)".to_owned(),
+            BTRFS_FSTYPE => match Self::btrfs_subvol(mount_info) {
+                Some(subvol) => format!("dataset (btrfs, subvol {:?})", subvol),
+                None => "dataset (btrfs)".to_owned(),
+            },
+            NILFS2_FSTYPE => "dataset (nilfs2)".to_owned(),
+            SMB_FSTYPE | CIFS_FSTYPE | AFP_FSTYPE | NFS_FSTYPE => {
+                "network share, a dataset only if its snapshots are found when probed".to_owned()
+            }
+            FUSE_FSTYPE_LINUX if mount_info.source == Path::new("restic") => {
+                "dataset (restic)".to_owned()
+            }
+            _ => "ignored".to_owned(),
+        }
+    }
+
+    // replay a mount table from another machine, so as to diagnose what httm makes of it, without access to that machine
+    pub fn exec(path: &Path) -> HttmResult<()> {
+        let contents = std::fs::read(path).map_err(|err| {
+            let msg = format!("httm could not read the mount table at {:?}: {err}", path);
+            HttmError::new(&msg).with_path(path)
+        })?;
+
+        let contents = String::from_utf8_lossy(&contents);
+
+        let Some(format) = MountTableFormat::detect(&contents) else {
+            let msg = format!("httm could not parse any mounts from: {:?}", path);
+            return Err(HttmError::new(&msg)
+                .with_code("mount_table_unparsable")
+                .with_path(path)
+                .into());
+        };
+
+        let mount_table = Self::new(&contents, format);
+
+        let output_buf = if GLOBAL_CONFIG.opt_json {
+            mount_table.to_json()?
+        } else {
+            mount_table.to_string()
+        };
+
+        print_output_buf(&output_buf)
+    }
+
+    fn to_json(&self) -> HttmResult<String> {
+        let mounts: Vec<Value> = self
+            .mount_infos
+            .iter()
+            .map(|mount_info| {
+                json!({
+                    "mount": mount_info.dest,
+                    "source": mount_info.source,
+                    "fstype": mount_info.fstype,
+                    "options": mount_info.options,
+                    "verdict": Self::verdict(mount_info),
+                })
+            })
+            .collect();
+
+        let unparsed: Vec<Value> = self
+            .unparsed
+            .iter()
+            .map(|(line_num, line)| json!({ "line": line_num, "text": line }))
+            .collect();
+
+        let value = json!({
+            "format": self.format.name(),
+            "mounts": mounts,
+            "unparsed": unparsed,
+        });
+
+        Ok(format!("{}\n", serde_json::to_string_pretty(&value)?))
+    }
+}
+
+impl std::fmt::Display for MountTable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let rows: Vec<[String; 4]> = self
+            .mount_infos
+            .iter()
+            .map(|mount_info| {
+                [
+                    mount_info.dest.to_string_lossy().to_string(),
+                    mount_info.source.to_string_lossy().to_string(),
+                    mount_info.fstype.clone(),
+                    Self::verdict(mount_info),
+                ]
+            })
+            .collect();
+
+        let headers = ["MOUNT", "SOURCE", "FSTYPE", "HTTM"].map(str::to_owned);
+
+        let widths: [usize; 4] = std::array::from_fn(|idx| {
+            rows.iter()
+                .chain(std::iter::once(&headers))
+                .map(|row| row[idx].chars().count())
+                .max()
+                .unwrap_or_default()
+        });
+
+        let mut buffer = format!(
+            "Parsed {} mount/s as a {} mount table. {} line/s could not be parsed.\n\n",
+            self.mount_infos.len(),
+            self.format.name(),
+            self.unparsed.len()
+        );
+
+        std::iter::once(&headers)
+            .chain(rows.iter())
+            .for_each(|row| {
+                let line: Vec<String> = row
+                    .iter()
+                    .zip(widths.iter())
+                    .map(|(field, width)| format!("{field:<width$}"))
+                    .collect();
+
+                buffer += line.join("  ").trim_end();
+                buffer += "\n";
+            });
+
+        if !self.unparsed.is_empty() {
+            buffer += "\n";
+
+            self.unparsed.iter().for_each(|(line_num, line)| {
+                buffer += &format!("Could not parse line {line_num}: {line:?}\n");
+            });
+        }
+
+        write!(f, "{buffer}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // each mount table of the corpus, its expected format, and what httm makes of it
+    const CORPUS: [(&str, &str, MountTableFormat, &str); 7] = [
+        (
+            "linux_proc_mounts",
+            include_str!("../../tests/mount_tables/linux_proc_mounts.txt"),
+            MountTableFormat::Proc,
+            include_str!("../../tests/mount_tables/linux_proc_mounts.golden"),
+        ),
+        (
+            "linux_mountinfo",
+            include_str!("../../tests/mount_tables/linux_mountinfo.txt"),
+            MountTableFormat::MountInfo,
+            include_str!("../../tests/mount_tables/linux_mountinfo.golden"),
+        ),
+        (
+            "linux_mount_cmd",
+            include_str!("../../tests/mount_tables/linux_mount_cmd.txt"),
+            MountTableFormat::Cmd,
+            include_str!("../../tests/mount_tables/linux_mount_cmd.golden"),
+        ),
+        (
+            "macos_mount_cmd",
+            include_str!("../../tests/mount_tables/macos_mount_cmd.txt"),
+            MountTableFormat::Cmd,
+            include_str!("../../tests/mount_tables/macos_mount_cmd.golden"),
+        ),
+        (
+            "freebsd_mount_cmd",
+            include_str!("../../tests/mount_tables/freebsd_mount_cmd.txt"),
+            MountTableFormat::Cmd,
+            include_str!("../../tests/mount_tables/freebsd_mount_cmd.golden"),
+        ),
+        (
+            "illumos_mnttab",
+            include_str!("../../tests/mount_tables/illumos_mnttab.txt"),
+            MountTableFormat::Proc,
+            include_str!("../../tests/mount_tables/illumos_mnttab.golden"),
+        ),
+        (
+            "garbage",
+            include_str!("../../tests/mount_tables/garbage.txt"),
+            MountTableFormat::Proc,
+            include_str!("../../tests/mount_tables/garbage.golden"),
+        ),
+    ];
+
+    #[test]
+    fn corpus_format_is_detected() {
+        CORPUS.iter().for_each(|(name, text, format, _golden)| {
+            assert_eq!(MountTableFormat::detect(text), Some(*format), "{name}");
+        });
+    }
+
+    #[test]
+    fn corpus_matches_golden() {
+        CORPUS.iter().for_each(|(name, text, format, golden)| {
+            assert_eq!(
+                MountTable::new(text, *format).to_string(),
+                *golden,
+                "{name}"
+            );
+        });
+    }
+
+    #[test]
+    fn corpus_as_json() {
+        CORPUS.iter().for_each(|(name, text, format, _golden)| {
+            let mount_table = MountTable::new(text, *format);
+            let value: Value = serde_json::from_str(&mount_table.to_json().unwrap()).unwrap();
+
+            assert_eq!(value["format"], format.name(), "{name}");
+            assert_eq!(
+                value["mounts"].as_array().map(Vec::len),
+                Some(mount_table.mount_infos.len()),
+                "{name}"
+            );
+            assert_eq!(
+                value["unparsed"].as_array().map(Vec::len),
+                Some(mount_table.unparsed.len()),
+                "{name}"
+            );
+        });
+    }
+
+    #[test]
+    fn nothing_parsable_is_not_detected() {
+        assert_eq!(MountTableFormat::detect(""), None);
+        assert_eq!(MountTableFormat::detect("\n  \n"), None);
+        assert_eq!(MountTableFormat::detect("# only\na comment"), None);
+    }
+
+    #[test]
+    fn odd_lines_are_set_aside() {
+        let long_line = format!("pool /{} zfs rw 0 0", "a".repeat(MAX_LINE_LEN));
+
+        let odd_lines = [
+            long_line.as_str(),
+            "pool /mnt\0 zfs rw 0 0",
+            "pool /mnt\\ zfs rw 0 0",
+            "pool /mnt\\04 zfs rw 0 0",
+            "pool /mnt\\777 zfs rw 0 0",
+            "pööl /mnt/ünïcode zfs rw 0 0",
+            " - - - - ",
+            "1 2 - ",
+            "on on",
+            " on  type  (",
+            "(,)",
+        ];
+
+        [
+            MountTableFormat::Proc,
+            MountTableFormat::MountInfo,
+            MountTableFormat::Cmd,
+        ]
+        .into_iter()
+        .for_each(|format| {
+            let mount_table = MountTable::new(&odd_lines.join("\n"), format);
+
+            assert_eq!(
+                mount_table.mount_infos.len() + mount_table.unparsed.len(),
+                odd_lines.len()
+            );
+
+            mount_table
+                .mount_infos
+                .iter()
+                .for_each(|mount_info| assert!(mount_info.dest.is_absolute()));
+        });
+    }
+
+    #[test]
+    fn octal_escapes() {
+        assert_eq!(MountTable::unescape("my\\040files"), "my files");
+        assert_eq!(MountTable::unescape("back\\134slash"), "back\\slash");
+        assert_eq!(MountTable::unescape("trailing\\"), "trailing\\");
+        assert_eq!(MountTable::unescape("short\\04"), "short\\04");
+        assert_eq!(MountTable::unescape("not\\999octal"), "not\\999octal");
+    }
+}
@@ -0,0 +1,99 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+use crate::parse::mount_table::{MountTable, MountTableFormat};
+use crate::parse::mounts::{BaseFilesystemInfo, PROC_SELF_MOUNTINFO};
+use hashbrown::HashMap;
+use std::fs::File;
+use std::io::{Read, Seek};
+use std::os::fd::AsRawFd;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+// a pool imported or exported mounts or unmounts each of its datasets in turn, so a change is only
+// answered once the mount table has been quiet for this long, and answered just once
+const SETTLE_INTERVAL: Duration = Duration::from_secs(2);
+
+// the kernel marks an open /proc/self/mountinfo with POLLPRI and POLLERR whenever the mount table changes,
+// so a long-running mode learns of a pool imported or exported without re-reading the mount table
+// upon every refresh.  The dataset and snapshot maps are built once, when the config is, and so
+// a change is answered by a reload, which rebuilds them, rather than by updating them in place.
+pub struct MountWatch {
+    file: File,
+    // key: mount, val: dataset/subvol, as of when the maps were built
+    dataset_sources: HashMap<PathBuf, PathBuf>,
+    // when the datasets were last seen to differ from those above, if they still do
+    opt_last_change: Option<Instant>,
+}
+
+impl MountWatch {
+    // None where there is no mountinfo to watch, as upon a system other than Linux
+    pub fn new() -> Option<Self> {
+        let mut file = File::open(PROC_SELF_MOUNTINFO.as_path()).ok()?;
+
+        let dataset_sources = Self::read(&mut file)?;
+
+        Some(Self {
+            file,
+            dataset_sources,
+            opt_last_change: None,
+        })
+    }
+
+    // true only once a dataset httm may search has been mounted, unmounted, or replaced,
+    // and the mount table has since settled, and not for any other change,
+    // like a tmpfs or a snapshot mounted upon access
+    pub fn datasets_changed(&mut self) -> bool {
+        if self.is_ready() {
+            // reading the table from the start is also what re-arms the notification
+            if let Some(dataset_sources) = Self::read(&mut self.file) {
+                self.opt_last_change = (dataset_sources != self.dataset_sources).then(Instant::now);
+            }
+
+            return false;
+        }
+
+        self.opt_last_change
+            .is_some_and(|last_change| last_change.elapsed() >= SETTLE_INTERVAL)
+    }
+
+    fn is_ready(&self) -> bool {
+        let mut poll_fd = libc::pollfd {
+            fd: self.file.as_raw_fd(),
+            events: libc::POLLPRI,
+            revents: 0,
+        };
+
+        // a zero timeout, as the caller already waits upon its own events
+        let num_ready = unsafe { libc::poll(&mut poll_fd, 1, 0) };
+
+        num_ready > 0 && poll_fd.revents & (libc::POLLPRI | libc::POLLERR) != 0
+    }
+
+    fn read(file: &mut File) -> Option<HashMap<PathBuf, PathBuf>> {
+        let mut contents = Vec::new();
+
+        file.rewind().ok()?;
+        file.read_to_end(&mut contents).ok()?;
+
+        let mount_table = MountTable::new(
+            &String::from_utf8_lossy(&contents),
+            MountTableFormat::MountInfo,
+        );
+
+        Some(BaseFilesystemInfo::dataset_sources(mount_table.mount_infos))
+    }
+}
@@ -15,18 +15,24 @@
 // For the full copyright and license information, please view the LICENSE file
 // that was distributed with this source code.
 
+use crate::library::privileged::PrivilegedHelper;
 use crate::library::results::{HttmError, HttmResult};
 use crate::library::utility::{find_common_path, fs_type_from_hidden_dir};
+use crate::parse::dataset_props::MapOfDatasetProps;
+use crate::parse::mount_backends::MountBackend;
+use crate::parse::mount_table::{MountTable, MountTableFormat};
+use crate::parse::network_probe::{ProbeVerdict, NETWORK_PROBE};
+use crate::parse::snap_labels::parse_gmt_token;
 use crate::parse::snaps::MapOfSnaps;
 use crate::{
-    NILFS2_SNAPSHOT_ID_KEY, ROOT_DIRECTORY, TM_DIR_LOCAL, TM_DIR_REMOTE, ZFS_HIDDEN_DIRECTORY,
+    BTRFS_SNAPPER_HIDDEN_DIRECTORY, BTRFS_SNAPPER_SUFFIX, NILFS2_SNAPSHOT_ID_KEY, ROOT_DIRECTORY,
+    TM_DIR_LOCAL, TM_DIR_REMOTE, ZFS_HIDDEN_DIRECTORY,
 };
 use hashbrown::{HashMap, HashSet};
 use once_cell::sync::Lazy;
-use proc_mounts::MountIter;
+use proc_mounts::MountInfo;
 use rayon::iter::Either;
 use rayon::prelude::*;
-use std::collections::BTreeMap;
 use std::ops::Deref;
 use std::path::Path;
 use std::path::PathBuf;
@@ -37,6 +43,7 @@ pub const ZFS_FSTYPE: &str = "zfs";
 pub const NILFS2_FSTYPE: &str = "nilfs2";
 pub const BTRFS_FSTYPE: &str = "btrfs";
 pub const SMB_FSTYPE: &str = "smbfs";
+pub const CIFS_FSTYPE: &str = "cifs";
 pub const NFS_FSTYPE: &str = "nfs";
 pub const AFP_FSTYPE: &str = "afpfs";
 pub const FUSE_FSTYPE_LINUX: &str = "fuse";
@@ -48,15 +55,83 @@ pub enum FilesystemType {
     Nilfs2,
     Apfs,
     Restic(Option<Vec<PathBuf>>),
+    ShadowCopy,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MountType {
+    Local,
+    Network,
+}
+
+impl MountType {
+    fn from_fstype(fstype: &str) -> Self {
+        match fstype {
+            SMB_FSTYPE | CIFS_FSTYPE | AFP_FSTYPE => MountType::Network,
+            // "nfs4" is also NFS
+            _ if fstype.starts_with(NFS_FSTYPE) => MountType::Network,
+            _ => MountType::Local,
+        }
+    }
+}
+
+// snapshot mounts are excluded from the datasets searched, unless the user intentionally mounted them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IncludeSnapMounts {
+    All,
+    Only(Vec<PathBuf>),
+}
+
+impl IncludeSnapMounts {
+    fn includes(&self, mount: &Path) -> bool {
+        match self {
+            IncludeSnapMounts::All => true,
+            IncludeSnapMounts::Only(mounts) => mounts.iter().any(|include| include == mount),
+        }
+    }
+}
+
+// datasets may be specified by mount point, or by name (the dataset's source), and either includes
+// the datasets beneath, so excluding "tank/media" also excludes "tank/media/movies"
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DatasetFilter {
+    pub exclude: Vec<String>,
+    pub opt_include_only: Option<Vec<String>>,
+}
+
+impl DatasetFilter {
+    fn is_filtered(&self, mount: &Path, dataset_info: &DatasetMetadata) -> bool {
+        let matches_any = |values: &[String]| {
+            values
+                .iter()
+                .any(|value| Self::matches(value, mount, dataset_info))
+        };
+
+        if matches_any(&self.exclude) {
+            return true;
+        }
+
+        match &self.opt_include_only {
+            Some(include_only) => !matches_any(include_only),
+            None => false,
+        }
+    }
+
+    fn matches(value: &str, mount: &Path, dataset_info: &DatasetMetadata) -> bool {
+        let value = Path::new(value);
+
+        mount.starts_with(value) || dataset_info.source.starts_with(value)
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct DatasetMetadata {
     pub source: PathBuf,
     pub fs_type: FilesystemType,
+    pub mount_type: MountType,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct FilterDirs {
     inner: HashSet<PathBuf>,
 }
@@ -83,7 +158,7 @@ impl MaxLen for FilterDirs {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct MapOfDatasets {
     inner: HashMap<PathBuf, DatasetMetadata>,
 }
@@ -110,6 +185,7 @@ pub static PROC_MOUNTS: Lazy<PathBuf> = Lazy::new(|| PathBuf::from("/proc/mounts
 pub static BTRFS_ROOT_SUBVOL: Lazy<PathBuf> = Lazy::new(|| PathBuf::from("<FS_TREE>"));
 pub static ROOT_PATH: Lazy<PathBuf> = Lazy::new(|| PathBuf::from(ROOT_DIRECTORY));
 static ETC_MNTTAB: Lazy<PathBuf> = Lazy::new(|| PathBuf::from("/etc/mnttab"));
+pub static PROC_SELF_MOUNTINFO: Lazy<PathBuf> = Lazy::new(|| PathBuf::from("/proc/self/mountinfo"));
 static RESTIC_SOURCE_PATH: Lazy<PathBuf> = Lazy::new(|| PathBuf::from("restic"));
 static TM_DIR_REMOTE_PATH: Lazy<PathBuf> = Lazy::new(|| PathBuf::from(TM_DIR_REMOTE));
 static TM_DIR_LOCAL_PATH: Lazy<PathBuf> = Lazy::new(|| PathBuf::from(TM_DIR_LOCAL));
@@ -118,25 +194,78 @@ pub struct BaseFilesystemInfo {
     pub map_of_datasets: MapOfDatasets,
     pub map_of_snaps: MapOfSnaps,
     pub filter_dirs: FilterDirs,
+    pub opt_map_of_dataset_props: Option<MapOfDatasetProps>,
 }
 
 impl BaseFilesystemInfo {
     // divide by the type of system we are on
     // Linux allows us the read proc mounts
-    pub fn new(opt_debug: bool, opt_alt_store: Option<&FilesystemType>) -> HttmResult<Self> {
-        let (mut raw_datasets, filter_dirs_set) = if PROC_MOUNTS.exists() {
-            Self::from_file(&PROC_MOUNTS)?
-        } else if ETC_MNTTAB.exists() {
-            Self::from_file(&ETC_MNTTAB)?
-        } else {
-            Self::from_mount_cmd()?
+    pub fn new(
+        opt_debug: bool,
+        opt_alt_store: Option<&FilesystemType>,
+        opt_privileged_helper: Option<&PrivilegedHelper>,
+        opt_mount_backend: Option<&MountBackend>,
+        opt_include_snap_mounts: Option<&IncludeSnapMounts>,
+        opt_dataset_filter: Option<&DatasetFilter>,
+    ) -> HttmResult<Self> {
+        let (mut raw_datasets, mut filter_dirs_set) = match opt_mount_backend {
+            Some(MountBackend::Proc) if PROC_MOUNTS.exists() => Self::from_file(&PROC_MOUNTS)?,
+            Some(MountBackend::Proc) if ETC_MNTTAB.exists() => Self::from_file(&ETC_MNTTAB)?,
+            Some(MountBackend::Proc) => {
+                return Err(HttmError::new(
+                    "Neither /proc/mounts nor /etc/mnttab exist upon this system.  Try another mount backend.",
+                )
+                .into())
+            }
+            Some(MountBackend::MountInfo) => {
+                Self::from_mount_infos(MountBackend::from_mountinfo(&PROC_SELF_MOUNTINFO)?)?
+            }
+            Some(MountBackend::LibMount) => {
+                Self::from_mount_infos(MountBackend::from_libmount()?)?
+            }
+            Some(MountBackend::Cmd) => Self::from_mount_cmd()?,
+            None if PROC_MOUNTS.exists() => Self::from_file(&PROC_MOUNTS)?,
+            None if ETC_MNTTAB.exists() => Self::from_file(&ETC_MNTTAB)?,
+            // prefer the C library's mount table to scraping the output of the mount command
+            None => match MountBackend::from_libmount() {
+                Ok(mount_infos) => Self::from_mount_infos(mount_infos)?,
+                Err(_err) => Self::from_mount_cmd()?,
+            },
         };
 
+        Self::exclude_snap_mounts(
+            &mut raw_datasets,
+            &mut filter_dirs_set,
+            opt_include_snap_mounts,
+        );
+
+        let opt_map_of_dataset_props = MapOfDatasetProps::new(&raw_datasets);
+
+        if let Some(map_of_dataset_props) = &opt_map_of_dataset_props {
+            Self::filter_datasets(
+                &mut raw_datasets,
+                &mut filter_dirs_set,
+                |mount, _dataset_info| map_of_dataset_props.is_ignored(mount),
+                "httm could not find any valid datasets which were not excluded by the \"httm:ignore\" ZFS property.",
+            )?;
+        }
+
+        if let Some(dataset_filter) = opt_dataset_filter {
+            Self::filter_datasets(
+                &mut raw_datasets,
+                &mut filter_dirs_set,
+                |mount, dataset_info| dataset_filter.is_filtered(mount, dataset_info),
+                "httm could not find any valid datasets which were not excluded by EXCLUDE_DATASET or INCLUDE_ONLY_DATASET.",
+            )?;
+        }
+
         if let Some(fs_type) = opt_alt_store {
             Self::from_blob_repo(&mut raw_datasets, fs_type)?;
         }
 
-        let map_of_snaps = MapOfSnaps::new(&raw_datasets, opt_debug)?;
+        NETWORK_PROBE.save();
+
+        let map_of_snaps = MapOfSnaps::new(&raw_datasets, opt_debug, opt_privileged_helper)?;
 
         let map_of_datasets = {
             MapOfDatasets {
@@ -154,24 +283,41 @@ impl BaseFilesystemInfo {
             map_of_datasets,
             map_of_snaps,
             filter_dirs,
+            opt_map_of_dataset_props,
         })
     }
 
+    // the source of each dataset httm may search, keyed by mount, but ignoring snapshot mounts,
+    // which come and go as snapshots are browsed, so a mount watch may compare one mount table to the next
+    pub fn dataset_sources(mount_infos: Vec<MountInfo>) -> HashMap<PathBuf, PathBuf> {
+        let Ok((mut raw_datasets, mut filter_dirs_set)) = Self::from_mount_infos(mount_infos)
+        else {
+            return HashMap::new();
+        };
+
+        Self::exclude_snap_mounts(&mut raw_datasets, &mut filter_dirs_set, None);
+
+        raw_datasets
+            .into_iter()
+            .map(|(mount, dataset_info)| (mount, dataset_info.source))
+            .collect()
+    }
+
     // parsing from proc mounts is both faster and necessary for certain btrfs features
     // for instance, allows us to read subvolumes mounts, like "/@" or "/@home"
     fn from_file(path: &Path) -> HttmResult<(HashMap<PathBuf, DatasetMetadata>, HashSet<PathBuf>)> {
-        let mount_iter = MountIter::new_from_file(path)?;
+        let mount_table = MountTable::from_file(path, MountTableFormat::Proc)?;
+
+        Self::from_mount_infos(mount_table.mount_infos)
+    }
 
+    // each mount backend produces the same mount info, so each is parsed the same way here
+    fn from_mount_infos(
+        mount_infos: Vec<MountInfo>,
+    ) -> HttmResult<(HashMap<PathBuf, DatasetMetadata>, HashSet<PathBuf>)> {
         let (map_of_datasets, filter_dirs): (HashMap<PathBuf, DatasetMetadata>, HashSet<PathBuf>) =
-            mount_iter
-                .par_bridge()
-                .flatten()
-                .filter(|mount_info| {
-                    !mount_info
-                        .dest
-                        .to_string_lossy()
-                        .contains(ZFS_HIDDEN_DIRECTORY)
-                })
+            mount_infos
+                .into_par_iter()
                 .filter(|mount_info| {
                     !mount_info
                         .options
@@ -188,47 +334,51 @@ impl BaseFilesystemInfo {
                         DatasetMetadata {
                             source: PathBuf::from(mount_info.source),
                             fs_type: FilesystemType::Zfs,
+                            mount_type: MountType::Local,
                         },
                     )),
-                    SMB_FSTYPE | AFP_FSTYPE | NFS_FSTYPE => {
-                        match fs_type_from_hidden_dir(&dest_path) {
-                            Some(FilesystemType::Zfs) => Either::Left((
+                    SMB_FSTYPE | CIFS_FSTYPE | AFP_FSTYPE | NFS_FSTYPE => {
+                        match NETWORK_PROBE.probe(
+                            Path::new(&mount_info.source),
+                            &mount_info.fstype,
+                            &dest_path,
+                        ) {
+                            ProbeVerdict::Zfs => Either::Left((
                                 dest_path,
                                 DatasetMetadata {
                                     source: PathBuf::from(mount_info.source),
                                     fs_type: FilesystemType::Zfs,
+                                    mount_type: MountType::Network,
                                 },
                             )),
-                            Some(FilesystemType::Btrfs(None)) => Either::Left((
+                            ProbeVerdict::Btrfs => Either::Left((
                                 dest_path,
                                 DatasetMetadata {
                                     source: PathBuf::from(mount_info.source),
                                     fs_type: FilesystemType::Btrfs(None),
+                                    mount_type: MountType::Network,
+                                },
+                            )),
+                            ProbeVerdict::ShadowCopy => Either::Left((
+                                dest_path,
+                                DatasetMetadata {
+                                    source: mount_info.source,
+                                    fs_type: FilesystemType::ShadowCopy,
+                                    mount_type: MountType::Network,
                                 },
                             )),
-                            _ => Either::Right(dest_path),
+                            ProbeVerdict::Unsupported => Either::Right(dest_path),
                         }
                     }
                     BTRFS_FSTYPE => {
-                        let keyed_options: BTreeMap<&str, &str> = mount_info
-                            .options
-                            .iter()
-                            .filter(|line| line.contains('='))
-                            .filter_map(|line| line.split_once('='))
-                            .collect();
-
-                        let opt_subvol = keyed_options.get("subvol").map(|subvol| {
-                            match keyed_options.get("subvolid") {
-                                Some(id) if *id == "5" => BTRFS_ROOT_SUBVOL.clone(),
-                                _ => PathBuf::from(subvol),
-                            }
-                        });
+                        let opt_subvol = MountTable::btrfs_subvol(&mount_info);
 
                         Either::Left((
                             dest_path,
                             DatasetMetadata {
                                 source: mount_info.source,
                                 fs_type: FilesystemType::Btrfs(opt_subvol),
+                                mount_type: MountType::Local,
                             },
                         ))
                     }
@@ -237,6 +387,7 @@ impl BaseFilesystemInfo {
                         DatasetMetadata {
                             source: PathBuf::from(mount_info.source),
                             fs_type: FilesystemType::Nilfs2,
+                            mount_type: MountType::Local,
                         },
                     )),
                     FUSE_FSTYPE_LINUX if mount_info.source == *RESTIC_SOURCE_PATH => {
@@ -245,6 +396,7 @@ impl BaseFilesystemInfo {
                             DatasetMetadata {
                                 source: mount_info.source,
                                 fs_type: FilesystemType::Restic(None),
+                                mount_type: MountType::Local,
                             },
                         ))
                     }
@@ -252,12 +404,137 @@ impl BaseFilesystemInfo {
                 });
 
         if map_of_datasets.is_empty() {
-            Err(HttmError::new("httm could not find any valid datasets on the system.").into())
+            Err(
+                HttmError::new("httm could not find any valid datasets on the system.")
+                    .with_code("no_datasets")
+                    .into(),
+            )
         } else {
             Ok((map_of_datasets, filter_dirs))
         }
     }
 
+    // filtered datasets become filter dirs, so neither their snapshots nor their contents are ever probed,
+    // except where a dataset which remains is mounted beneath, so it may still be reached by a recursive search
+    fn filter_datasets<F>(
+        map_of_datasets: &mut HashMap<PathBuf, DatasetMetadata>,
+        filter_dirs: &mut HashSet<PathBuf>,
+        is_filtered: F,
+        none_remain_msg: &str,
+    ) -> HttmResult<()>
+    where
+        F: Fn(&Path, &DatasetMetadata) -> bool,
+    {
+        let filtered: Vec<PathBuf> = map_of_datasets
+            .iter()
+            .filter(|(mount, dataset_info)| is_filtered(mount, dataset_info))
+            .map(|(mount, _dataset_info)| mount.clone())
+            .collect();
+
+        map_of_datasets.retain(|mount, _dataset_info| !filtered.contains(mount));
+
+        filtered
+            .into_iter()
+            .filter(|filtered_mount| {
+                !map_of_datasets
+                    .keys()
+                    .any(|mount| mount.starts_with(filtered_mount))
+            })
+            .for_each(|filtered_mount| {
+                filter_dirs.insert(filtered_mount);
+            });
+
+        if map_of_datasets.is_empty() {
+            return Err(HttmError::new(none_remain_msg)
+                .with_code("no_datasets")
+                .into());
+        }
+
+        Ok(())
+    }
+
+    // snapshot mounts are recognized by the components of their paths, never by a substring,
+    // so a directory named "backup.zfs" is never mistaken for a ".zfs" directory
+    fn exclude_snap_mounts(
+        map_of_datasets: &mut HashMap<PathBuf, DatasetMetadata>,
+        filter_dirs: &mut HashSet<PathBuf>,
+        opt_include_snap_mounts: Option<&IncludeSnapMounts>,
+    ) {
+        let is_included = |mount: &Path| {
+            opt_include_snap_mounts
+                .is_some_and(|include_snap_mounts| include_snap_mounts.includes(mount))
+        };
+
+        let snap_mounts: Vec<PathBuf> = map_of_datasets
+            .iter()
+            .filter(|(mount, dataset_info)| {
+                Self::is_snap_mount_path(mount) || Self::is_snap_dataset(dataset_info)
+            })
+            .map(|(mount, _dataset_info)| mount)
+            .chain(
+                filter_dirs
+                    .iter()
+                    .filter(|mount| Self::is_snap_mount_path(mount)),
+            )
+            .filter(|mount| !is_included(mount))
+            .cloned()
+            .collect();
+
+        if snap_mounts.is_empty() {
+            return;
+        }
+
+        // a mount beneath a snapshot mount, like a btrfs snapshot mounted within another snapshot,
+        // is also a snapshot mount
+        let is_excluded = |mount: &Path| {
+            !is_included(mount)
+                && snap_mounts
+                    .iter()
+                    .any(|snap_mount| mount.starts_with(snap_mount))
+        };
+
+        map_of_datasets.retain(|mount, _dataset_info| !is_excluded(mount));
+        filter_dirs.retain(|mount| !is_excluded(mount));
+    }
+
+    // a mount point within a ".zfs" directory, or within a btrfs Snapper ".snapshots" directory,
+    // but not the ".snapshots" directory itself, which btrfs subvolume snapshot paths are relative to
+    pub fn is_snap_mount_path(mount: &Path) -> bool {
+        let components: Vec<&std::ffi::OsStr> = mount
+            .components()
+            .map(|component| component.as_os_str())
+            .collect();
+
+        components.iter().enumerate().any(|(idx, component)| {
+            *component == ZFS_HIDDEN_DIRECTORY
+                || (*component == BTRFS_SNAPPER_HIDDEN_DIRECTORY && idx + 1 < components.len())
+        })
+    }
+
+    // a ZFS snapshot is named like "pool/data@snap", and a Snapper btrfs snapshot subvolume
+    // is named like "@/.snapshots/12/snapshot" or "@snapshots/12/snapshot"
+    fn is_snap_dataset(dataset_info: &DatasetMetadata) -> bool {
+        match &dataset_info.fs_type {
+            FilesystemType::Zfs if matches!(dataset_info.mount_type, MountType::Local) => {
+                dataset_info.source.to_string_lossy().contains('@')
+            }
+            FilesystemType::Btrfs(Some(subvol)) => {
+                if Self::is_snap_mount_path(subvol) {
+                    return true;
+                }
+
+                subvol.ends_with(BTRFS_SNAPPER_SUFFIX)
+                    && subvol
+                        .parent()
+                        .and_then(|parent| parent.file_name())
+                        .is_some_and(|number| {
+                            number.to_string_lossy().chars().all(|c| c.is_ascii_digit())
+                        })
+            }
+            _ => false,
+        }
+    }
+
     pub fn from_blob_repo(
         map_of_datasets: &mut HashMap<PathBuf, DatasetMetadata>,
         repo_type: &FilesystemType,
@@ -311,10 +588,12 @@ impl BaseFilesystemInfo {
             FilesystemType::Restic(_) => DatasetMetadata {
                 source: PathBuf::from(RESTIC_SOURCE_PATH.as_path()),
                 fs_type: FilesystemType::Restic(Some(repos)),
+                mount_type: MountType::Local,
             },
             FilesystemType::Apfs => DatasetMetadata {
                 source: PathBuf::from("timemachine"),
                 fs_type: FilesystemType::Apfs,
+                mount_type: MountType::Local,
             },
             _ => {
                 return Err(HttmError::new(
@@ -352,48 +631,58 @@ impl BaseFilesystemInfo {
 
         let stdout_string = std::str::from_utf8(&command_output.stdout)?;
 
+        let mount_table = MountTable::new(stdout_string, MountTableFormat::Cmd);
+
         // parse "mount" for filesystems and mountpoints
         let (mut map_of_datasets, filter_dirs): (
             HashMap<PathBuf, DatasetMetadata>,
             HashSet<PathBuf>,
-        ) = stdout_string
-            .par_lines()
-            // but exclude snapshot mounts.  we want the raw filesystem names.
-            .filter(|line| !line.contains(ZFS_HIDDEN_DIRECTORY))
-            .filter(|line| !line.contains(TM_DIR_REMOTE))
-            .filter(|line| !line.contains(TM_DIR_LOCAL))
-            // mount cmd includes and " on " between src and rest
-            .filter_map(|line| line.split_once(" on "))
-            // where to split, to just have the src and dest of mounts
-            .filter_map(|(filesystem, rest)| {
-                // GNU Linux mount output
-                if rest.contains("type") {
-                    let opt_mount = rest.split_once(" type");
-                    opt_mount.map(|mount| (filesystem, mount.0))
-                // Busybox and BSD mount output
-                } else if rest.contains(" (") {
-                    let opt_mount = rest.split_once(" (");
-                    opt_mount.map(|mount| (filesystem, mount.0))
-                } else {
-                    None
-                }
+        ) = mount_table
+            .mount_infos
+            .into_par_iter()
+            // but exclude Time Machine mounts.  we want the raw filesystem names.
+            .filter(|mount_info| {
+                ![&mount_info.source, &mount_info.dest].iter().any(|path| {
+                    let path = path.to_string_lossy();
+                    path.contains(TM_DIR_REMOTE) || path.contains(TM_DIR_LOCAL)
+                })
+            })
+            .map(|mount_info| {
+                let fstype = mount_info.fstype.as_str();
+
+                let source = mount_info.source;
+                let mount = mount_info.dest;
+                let mount_type = MountType::from_fstype(fstype);
+
+                // network mounts may be slow, or hung, so their verdicts are cached, and their probes time out
+                let verdict = match mount_type {
+                    MountType::Network => NETWORK_PROBE.probe(&source, fstype, &mount),
+                    MountType::Local => match fs_type_from_hidden_dir(&mount) {
+                        Some(FilesystemType::Zfs) => ProbeVerdict::Zfs,
+                        Some(FilesystemType::Btrfs(_)) => ProbeVerdict::Btrfs,
+                        _ => ProbeVerdict::Unsupported,
+                    },
+                };
+
+                (source, mount, mount_type, verdict)
             })
-            .map(|(filesystem, mount)| (PathBuf::from(filesystem), PathBuf::from(mount)))
             // sanity check: does the filesystem exist and have a ZFS hidden dir? if not, filter it out
             // and flip around, mount should key of key/value
-            .partition_map(|(source, mount)| match fs_type_from_hidden_dir(&mount) {
-                Some(FilesystemType::Zfs) => Either::Left((
+            .partition_map(|(source, mount, mount_type, verdict)| match verdict {
+                ProbeVerdict::Zfs => Either::Left((
                     mount,
                     DatasetMetadata {
                         source,
                         fs_type: FilesystemType::Zfs,
+                        mount_type,
                     },
                 )),
-                Some(FilesystemType::Btrfs(_)) => Either::Left((
+                ProbeVerdict::Btrfs => Either::Left((
                     mount,
                     DatasetMetadata {
                         source,
                         fs_type: FilesystemType::Btrfs(None),
+                        mount_type,
                     },
                 )),
                 _ if source == *RESTIC_SOURCE_PATH => Either::Left((
@@ -401,6 +690,15 @@ impl BaseFilesystemInfo {
                     DatasetMetadata {
                         source,
                         fs_type: FilesystemType::Restic(None),
+                        mount_type,
+                    },
+                )),
+                ProbeVerdict::ShadowCopy => Either::Left((
+                    mount,
+                    DatasetMetadata {
+                        source,
+                        fs_type: FilesystemType::ShadowCopy,
+                        mount_type,
                     },
                 )),
                 _ => Either::Right(mount),
@@ -413,6 +711,7 @@ impl BaseFilesystemInfo {
                     let metadata = DatasetMetadata {
                         source: PathBuf::from("timemachine"),
                         fs_type: FilesystemType::Apfs,
+                        mount_type: MountType::Local,
                     };
 
                     map_of_datasets.insert_unique_unchecked(ROOT_PATH.to_path_buf(), metadata);
@@ -421,12 +720,31 @@ impl BaseFilesystemInfo {
         }
 
         if map_of_datasets.is_empty() {
-            Err(HttmError::new("httm could not find any valid datasets on the system.").into())
+            Err(
+                HttmError::new("httm could not find any valid datasets on the system.")
+                    .with_code("no_datasets")
+                    .into(),
+            )
         } else {
             Ok((map_of_datasets, filter_dirs))
         }
     }
 
+    // SMB shares may expose their shadow copies as "@GMT-" token directories in the root of the share,
+    // so no alias is required to find their snapshots
+    pub fn has_shadow_copies(mount: &Path) -> bool {
+        std::fs::read_dir(mount)
+            .map(|mut read_dir| {
+                read_dir.any(|entry| {
+                    entry
+                        .ok()
+                        .and_then(|entry| parse_gmt_token(&entry.file_name().to_string_lossy()))
+                        .is_some()
+                })
+            })
+            .unwrap_or(false)
+    }
+
     // if we have some btrfs mounts, we check to see if there is a snap directory in common
     // so we can hide that common path from searches later
     pub fn common_snap_dir(&self) -> Option<PathBuf> {
@@ -0,0 +1,222 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::library::diagnostics::Diagnostic;
+use crate::library::utility::{fs_type_from_hidden_dir, httm_cache_dir};
+use crate::parse::mounts::{BaseFilesystemInfo, FilesystemType};
+use crossbeam_channel::bounded;
+use hashbrown::HashMap;
+use once_cell::sync::Lazy;
+use serde_json::Value;
+use std::fs::{create_dir_all, read_to_string, remove_file, rename, write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// a hung network mount must never hang httm, so each probe is given only so long
+pub const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+// verdicts are re-probed after a day, in case snapshots were enabled, or disabled, upon the server
+const CACHE_TTL: Duration = Duration::from_secs(60 * 60 * 24);
+const CACHE_FILE_NAME: &str = "network_fs_types.json";
+
+pub static NETWORK_PROBE: Lazy<NetworkProbe> = Lazy::new(NetworkProbe::load);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProbeVerdict {
+    Zfs,
+    Btrfs,
+    ShadowCopy,
+    Unsupported,
+}
+
+impl ProbeVerdict {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ProbeVerdict::Zfs => "zfs",
+            ProbeVerdict::Btrfs => "btrfs",
+            ProbeVerdict::ShadowCopy => "shadow_copy",
+            ProbeVerdict::Unsupported => "unsupported",
+        }
+    }
+
+    fn from_str(value: &str) -> Option<Self> {
+        match value {
+            "zfs" => Some(ProbeVerdict::Zfs),
+            "btrfs" => Some(ProbeVerdict::Btrfs),
+            "shadow_copy" => Some(ProbeVerdict::ShadowCopy),
+            "unsupported" => Some(ProbeVerdict::Unsupported),
+            _ => None,
+        }
+    }
+}
+
+// run a probe which may block upon a hung network mount, and give up on it after the timeout,
+// leaving the probe's thread to finish, or not, on its own
+pub fn with_timeout<T, F>(probe: F) -> Option<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    let (tx, rx) = bounded(1);
+
+    std::thread::spawn(move || {
+        let _ = tx.send(probe());
+    });
+
+    rx.recv_timeout(PROBE_TIMEOUT).ok()
+}
+
+// key: "<fstype>\t<mount source>", val: (verdict, time checked)
+pub struct NetworkProbe {
+    inner: Mutex<HashMap<String, (ProbeVerdict, SystemTime)>>,
+    is_modified: AtomicBool,
+}
+
+impl NetworkProbe {
+    // the cache is only an optimization, so a cache which cannot be read is simply ignored
+    fn load() -> Self {
+        let inner = Self::cache_path()
+            .and_then(|cache_path| read_to_string(cache_path).ok())
+            .and_then(|contents| serde_json::from_str::<Value>(&contents).ok())
+            .and_then(|value| value.as_object().cloned())
+            .map(|object| {
+                object
+                    .into_iter()
+                    .filter_map(|(key, entry)| {
+                        let verdict = entry
+                            .get("verdict")
+                            .and_then(Value::as_str)
+                            .and_then(ProbeVerdict::from_str)?;
+                        let checked = entry
+                            .get("checked")
+                            .and_then(Value::as_u64)
+                            .map(|secs| UNIX_EPOCH + Duration::from_secs(secs))?;
+
+                        Some((key, (verdict, checked)))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Self {
+            inner: Mutex::new(inner),
+            is_modified: AtomicBool::new(false),
+        }
+    }
+
+    pub fn probe(&self, source: &Path, fstype: &str, mount: &Path) -> ProbeVerdict {
+        let key = format!("{}\t{}", fstype, source.to_string_lossy());
+
+        if let Some(verdict) = self.cached(&key) {
+            return verdict;
+        }
+
+        let mount_clone = mount.to_path_buf();
+
+        let Some(verdict) = with_timeout(move || match fs_type_from_hidden_dir(&mount_clone) {
+            Some(FilesystemType::Zfs) => ProbeVerdict::Zfs,
+            Some(FilesystemType::Btrfs(_)) => ProbeVerdict::Btrfs,
+            _ if BaseFilesystemInfo::has_shadow_copies(&mount_clone) => ProbeVerdict::ShadowCopy,
+            _ => ProbeVerdict::Unsupported,
+        }) else {
+            // a timeout is not a verdict, so it is not cached
+            Diagnostic::warn(
+                "probe_timeout",
+                format!(
+                    "Network mount did not respond within {} seconds, and will not be searched: {:?}",
+                    PROBE_TIMEOUT.as_secs(),
+                    mount
+                ),
+            )
+            .with_path(mount)
+            .print();
+            return ProbeVerdict::Unsupported;
+        };
+
+        if let Ok(mut inner) = self.inner.lock() {
+            inner.insert(key, (verdict, SystemTime::now()));
+            self.is_modified.store(true, Ordering::Relaxed);
+        }
+
+        verdict
+    }
+
+    fn cached(&self, key: &str) -> Option<ProbeVerdict> {
+        let inner = self.inner.lock().ok()?;
+        let (verdict, checked) = inner.get(key)?;
+
+        match checked.elapsed() {
+            Ok(elapsed) if elapsed < CACHE_TTL => Some(*verdict),
+            _ => None,
+        }
+    }
+
+    pub fn save(&self) {
+        if !self.is_modified.load(Ordering::Relaxed) {
+            return;
+        }
+
+        let Some(cache_path) = Self::cache_path() else {
+            return;
+        };
+
+        let Ok(inner) = self.inner.lock() else {
+            return;
+        };
+
+        let object: serde_json::Map<String, Value> = inner
+            .iter()
+            .map(|(key, (verdict, checked))| {
+                let secs = checked
+                    .duration_since(UNIX_EPOCH)
+                    .map(|duration| duration.as_secs())
+                    .unwrap_or_default();
+
+                (
+                    key.clone(),
+                    serde_json::json!({ "verdict": verdict.as_str(), "checked": secs }),
+                )
+            })
+            .collect();
+
+        let Ok(contents) = serde_json::to_string_pretty(&Value::Object(object)) else {
+            return;
+        };
+
+        // write to a tmp file then rename, so a concurrent httm never reads a truncated cache
+        let tmp_path = cache_path.with_extension(format!("tmp.{}", std::process::id()));
+
+        let _ = cache_path
+            .parent()
+            .map(create_dir_all)
+            .transpose()
+            .and_then(|_| write(&tmp_path, contents))
+            .and_then(|_| rename(&tmp_path, &cache_path));
+    }
+
+    // a restart should re-probe every network mount, so the verdicts cached upon disk are discarded
+    pub fn invalidate() {
+        if let Some(cache_path) = Self::cache_path() {
+            let _ = remove_file(cache_path);
+        }
+    }
+
+    fn cache_path() -> Option<PathBuf> {
+        httm_cache_dir().map(|cache_dir| cache_dir.join(CACHE_FILE_NAME))
+    }
+}
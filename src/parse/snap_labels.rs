@@ -0,0 +1,291 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::parse::snaps::MapOfSnaps;
+use crate::BTRFS_SNAPPER_SUFFIX;
+use hashbrown::HashMap;
+use rayon::prelude::*;
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use time::format_description;
+use time::PrimitiveDateTime;
+
+static SNAP_LABEL_TIMESTAMP_FORMAT: &str = "[year]-[month]-[day] [hour]:[minute]:[second]";
+static GMT_TOKEN_PREFIX: &str = "@GMT-";
+static GMT_TOKEN_FORMAT: &str = "[year].[month].[day]-[hour].[minute].[second]";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapLabel {
+    pub scheme: &'static str,
+    pub opt_class: Option<String>,
+    pub timestamp: PrimitiveDateTime,
+}
+
+impl SnapLabel {
+    pub fn is_class(&self, class: &str) -> bool {
+        self.opt_class
+            .as_deref()
+            .map(|snap_class| snap_class.eq_ignore_ascii_case(class))
+            .unwrap_or(false)
+    }
+
+    fn display_timestamp(&self) -> String {
+        let parsed_format = format_description::parse(SNAP_LABEL_TIMESTAMP_FORMAT)
+            .expect("snap label timestamp format is invalid");
+
+        self.timestamp
+            .format(&parsed_format)
+            .expect("snap label timestamp format could not be applied to the date supplied")
+    }
+}
+
+impl Serialize for SnapLabel {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("SnapLabel", 3)?;
+
+        state.serialize_field("scheme", &self.scheme)?;
+        state.serialize_field("class", &self.opt_class)?;
+        state.serialize_field("timestamp", &self.display_timestamp())?;
+        state.end()
+    }
+}
+
+// each parser recognizes the snapshot names generated by a single naming scheme,
+// to add a new scheme, impl this trait and add the parser to SNAP_LABEL_PARSERS
+pub trait ParseSnapLabel: Sync {
+    fn parse(&self, snap_mount: &Path) -> Option<SnapLabel>;
+}
+
+static SNAP_LABEL_PARSERS: &[&dyn ParseSnapLabel] = &[
+    &Sanoid,
+    &Syncoid,
+    &ZfsAutoSnapshot,
+    &Zrepl,
+    &TimeMachine,
+    &Snapper,
+    &ShadowCopy,
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MapOfSnapLabels {
+    inner: HashMap<PathBuf, SnapLabel>,
+}
+
+impl From<HashMap<PathBuf, SnapLabel>> for MapOfSnapLabels {
+    fn from(map: HashMap<PathBuf, SnapLabel>) -> Self {
+        Self { inner: map }
+    }
+}
+
+impl Deref for MapOfSnapLabels {
+    type Target = HashMap<PathBuf, SnapLabel>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl MapOfSnapLabels {
+    // precompute a label for each snap mount whose name matches a known naming scheme,
+    // snap mounts which match no scheme are simply left unlabeled
+    pub fn new(map_of_snaps: &MapOfSnaps) -> Self {
+        let map_of_snap_labels: HashMap<PathBuf, SnapLabel> = map_of_snaps
+            .par_iter()
+            .flat_map(|(_mount, snap_mounts)| snap_mounts)
+            .filter_map(|snap_mount| {
+                Self::parse(snap_mount).map(|label| (snap_mount.clone(), label))
+            })
+            .collect();
+
+        map_of_snap_labels.into()
+    }
+
+    pub fn parse(snap_mount: &Path) -> Option<SnapLabel> {
+        SNAP_LABEL_PARSERS
+            .iter()
+            .find_map(|parser| parser.parse(snap_mount))
+    }
+
+    // a snap version path is always a snap mount joined with a relative path,
+    // so the first ancestor which is a key is our snap mount
+    pub fn snap_label(&self, snap_path: &Path) -> Option<&SnapLabel> {
+        snap_path
+            .ancestors()
+            .find_map(|ancestor| self.inner.get(ancestor))
+    }
+}
+
+fn parse_timestamp(value: &str, format: &str) -> Option<PrimitiveDateTime> {
+    let parsed_format = format_description::parse(format).ok()?;
+
+    PrimitiveDateTime::parse(value, &parsed_format).ok()
+}
+
+// SMB shares name each shadow copy by a GMT token, like "@GMT-2023.01.15-00.00.01",
+// which, unlike the modify times of files within the share, is always in UTC
+pub fn parse_gmt_token(snap_name: &str) -> Option<SystemTime> {
+    let date = snap_name.strip_prefix(GMT_TOKEN_PREFIX)?;
+
+    parse_timestamp(date, GMT_TOKEN_FORMAT).map(|timestamp| timestamp.assume_utc().into())
+}
+
+fn snap_name(snap_mount: &Path) -> Option<&str> {
+    snap_mount.file_name().and_then(|name| name.to_str())
+}
+
+// ex: "autosnap_2023-01-15_00:00:01_daily"
+struct Sanoid;
+
+impl ParseSnapLabel for Sanoid {
+    fn parse(&self, snap_mount: &Path) -> Option<SnapLabel> {
+        let rest = snap_name(snap_mount)?.strip_prefix("autosnap_")?;
+        let (date, class) = rest.rsplit_once('_')?;
+
+        Some(SnapLabel {
+            scheme: "sanoid",
+            opt_class: Some(class.to_owned()),
+            timestamp: parse_timestamp(date, "[year]-[month]-[day]_[hour]:[minute]:[second]")?,
+        })
+    }
+}
+
+// ex: "syncoid_hostname_2023-01-15:00:00:01" or "syncoid_hostname_2023-01-15:00:00:01-GMT00:00"
+struct Syncoid;
+
+impl ParseSnapLabel for Syncoid {
+    fn parse(&self, snap_mount: &Path) -> Option<SnapLabel> {
+        let rest = snap_name(snap_mount)?.strip_prefix("syncoid_")?;
+        let (_hostname, date) = rest.rsplit_once('_')?;
+        let date = date.split_once("-GMT").map_or(date, |(date, _offset)| date);
+
+        Some(SnapLabel {
+            scheme: "syncoid",
+            opt_class: None,
+            timestamp: parse_timestamp(date, "[year]-[month]-[day]:[hour]:[minute]:[second]")?,
+        })
+    }
+}
+
+// ex: "zfs-auto-snap_daily-2023-01-15-0000"
+struct ZfsAutoSnapshot;
+
+impl ParseSnapLabel for ZfsAutoSnapshot {
+    fn parse(&self, snap_mount: &Path) -> Option<SnapLabel> {
+        let rest = snap_name(snap_mount)?.strip_prefix("zfs-auto-snap_")?;
+        let (class, date) = rest.split_once('-')?;
+
+        Some(SnapLabel {
+            scheme: "zfs-auto-snapshot",
+            opt_class: Some(class.to_owned()),
+            timestamp: parse_timestamp(date, "[year]-[month]-[day]-[hour][minute]")?,
+        })
+    }
+}
+
+// ex: "zrepl_20230115_000001_000"
+struct Zrepl;
+
+impl ParseSnapLabel for Zrepl {
+    fn parse(&self, snap_mount: &Path) -> Option<SnapLabel> {
+        let rest = snap_name(snap_mount)?.strip_prefix("zrepl_")?;
+        let date = rest.get(..15)?;
+
+        Some(SnapLabel {
+            scheme: "zrepl",
+            opt_class: None,
+            timestamp: parse_timestamp(date, "[year][month][day]_[hour][minute][second]")?,
+        })
+    }
+}
+
+// ex: ".../2023-01-15-000001.backup/Data" or ".../2023-01-15-000001/Data"
+struct TimeMachine;
+
+impl ParseSnapLabel for TimeMachine {
+    fn parse(&self, snap_mount: &Path) -> Option<SnapLabel> {
+        if !snap_mount.ends_with("Data") {
+            return None;
+        }
+
+        let name = snap_name(snap_mount.parent()?)?;
+        let date = name.strip_suffix(".backup").unwrap_or(name);
+
+        Some(SnapLabel {
+            scheme: "timemachine",
+            opt_class: None,
+            timestamp: parse_timestamp(date, "[year]-[month]-[day]-[hour][minute][second]")?,
+        })
+    }
+}
+
+// ex: "/.snapshots/42/snapshot", with the class and date recorded in "/.snapshots/42/info.xml"
+struct Snapper;
+
+impl Snapper {
+    fn xml_value<'a>(contents: &'a str, tag: &str) -> Option<&'a str> {
+        let open = format!("<{tag}>");
+        let close = format!("</{tag}>");
+
+        let (_before, rest) = contents.split_once(&open)?;
+        let (value, _after) = rest.split_once(&close)?;
+
+        Some(value.trim())
+    }
+}
+
+impl ParseSnapLabel for Snapper {
+    fn parse(&self, snap_mount: &Path) -> Option<SnapLabel> {
+        if !snap_mount.ends_with(BTRFS_SNAPPER_SUFFIX) {
+            return None;
+        }
+
+        let info = snap_mount.parent()?.join("info.xml");
+        let contents = std::fs::read_to_string(info).ok()?;
+
+        let opt_class = Self::xml_value(&contents, "cleanup")
+            .filter(|class| !class.is_empty())
+            .map(|class| class.to_owned());
+        let date = Self::xml_value(&contents, "date")?;
+
+        Some(SnapLabel {
+            scheme: "snapper",
+            opt_class,
+            timestamp: parse_timestamp(date, SNAP_LABEL_TIMESTAMP_FORMAT)?,
+        })
+    }
+}
+
+// ex: "/mnt/share/@GMT-2023.01.15-00.00.01"
+struct ShadowCopy;
+
+impl ParseSnapLabel for ShadowCopy {
+    fn parse(&self, snap_mount: &Path) -> Option<SnapLabel> {
+        let date = snap_name(snap_mount)?.strip_prefix(GMT_TOKEN_PREFIX)?;
+
+        Some(SnapLabel {
+            scheme: "shadow_copy",
+            opt_class: None,
+            timestamp: parse_timestamp(date, GMT_TOKEN_FORMAT)?,
+        })
+    }
+}
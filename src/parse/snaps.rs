@@ -15,26 +15,32 @@
 // For the full copyright and license information, please view the LICENSE file
 // that was distributed with this source code.
 
+use crate::library::diagnostics::Diagnostic;
+use crate::library::privileged::PrivilegedHelper;
 use crate::library::results::{HttmError, HttmResult};
 use crate::library::utility::user_has_effective_root;
+use crate::parse::creation::MapOfCreationTimes;
+use crate::parse::dataset_props::MapOfDatasetProps;
 use crate::parse::mounts::BTRFS_ROOT_SUBVOL;
 use crate::parse::mounts::PROC_MOUNTS;
-use crate::parse::mounts::{DatasetMetadata, FilesystemType};
+use crate::parse::mounts::{DatasetMetadata, FilesystemType, MountType};
+use crate::parse::snap_labels::{parse_gmt_token, MapOfSnapLabels};
 use crate::{
     BTRFS_SNAPPER_HIDDEN_DIRECTORY, BTRFS_SNAPPER_SUFFIX, RESTIC_SNAPSHOT_DIRECTORY,
     ROOT_DIRECTORY, TM_DIR_LOCAL, TM_DIR_REMOTE, ZFS_SNAPSHOT_DIRECTORY,
 };
-use hashbrown::HashMap;
+use hashbrown::{HashMap, HashSet};
 use proc_mounts::MountIter;
 use rayon::prelude::*;
-use std::fs::read_dir;
+use std::fs::{read_dir, DirEntry};
 use std::ops::Deref;
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
 use std::process::Command as ExecProcess;
 use std::sync::Once;
+use std::time::SystemTime;
 use which::which;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct MapOfSnaps {
     inner: HashMap<PathBuf, Vec<PathBuf>>,
 }
@@ -54,17 +60,54 @@ impl Deref for MapOfSnaps {
 }
 
 impl MapOfSnaps {
+    // where a dataset has the "httm:snap-filter" property, only its snapshots of the classes given are searched
+    pub fn filter_by_props(&mut self, map_of_dataset_props: &MapOfDatasetProps) {
+        self.inner.iter_mut().for_each(|(mount, snap_mounts)| {
+            let Some(snap_filter) = map_of_dataset_props
+                .get(mount)
+                .and_then(|props| props.opt_snap_filter.as_ref())
+            else {
+                return;
+            };
+
+            snap_mounts.retain(|snap_mount| {
+                MapOfSnapLabels::parse(snap_mount)
+                    .is_some_and(|label| snap_filter.iter().any(|class| label.is_class(class)))
+            });
+        });
+    }
+
+    // a snapshot's time is its creation time, or the time parsed from its name, or, failing both,
+    // the modify time of its root directory
+    pub fn snap_time(
+        snap_mount: &Path,
+        opt_map_of_creation_times: Option<&MapOfCreationTimes>,
+    ) -> Option<SystemTime> {
+        opt_map_of_creation_times
+            .and_then(|map_of_creation_times| map_of_creation_times.creation_time(snap_mount))
+            .or_else(|| {
+                MapOfSnapLabels::parse(snap_mount).map(|label| label.timestamp.assume_utc().into())
+            })
+            .or_else(|| {
+                snap_mount
+                    .symlink_metadata()
+                    .and_then(|metadata| metadata.modified())
+                    .ok()
+            })
+    }
+
     // fans out precompute of snap mounts to the appropriate function based on fstype
     pub fn new(
         map_of_datasets: &HashMap<PathBuf, DatasetMetadata>,
         opt_debug: bool,
+        opt_privileged_helper: Option<&PrivilegedHelper>,
     ) -> HttmResult<Self> {
         let map_of_snaps: HashMap<PathBuf, Vec<PathBuf>> = map_of_datasets
             .par_iter()
             .map(|(mount, dataset_info)| {
                 let snap_mounts: Vec<PathBuf> = match &dataset_info.fs_type {
-                    FilesystemType::Zfs | FilesystemType::Nilfs2 | FilesystemType::Apfs | FilesystemType::Restic(_) | FilesystemType::Btrfs(None) => {
-                        Self::from_defined_mounts(mount, dataset_info)
+                    FilesystemType::Zfs | FilesystemType::Nilfs2 | FilesystemType::Apfs | FilesystemType::Restic(_) | FilesystemType::ShadowCopy | FilesystemType::Btrfs(None) => {
+                        Self::from_defined_mounts(mount, dataset_info, opt_privileged_helper)
                     }
                     // btrfs Some mounts are potential local mount
                     FilesystemType::Btrfs(Some(base_subvol)) => {
@@ -74,18 +117,21 @@ impl MapOfSnaps {
                             &base_subvol,
                             map_of_datasets,
                             opt_debug,
+                            opt_privileged_helper,
                         );
 
                         if res.is_empty() {
                             static NOTICE_FALLBACK: Once = Once::new();
 
                             NOTICE_FALLBACK.call_once(|| {
-                                eprintln!(
-                                    "NOTICE: Falling back to detection of btrfs snapshot mounts perhaps defined by Snapper.",
-                                );
+                                Diagnostic::notice(
+                                    "btrfs_snapper_fallback",
+                                    "Falling back to detection of btrfs snapshot mounts perhaps defined by Snapper.",
+                                )
+                                .print();
                             });
 
-                            res = Self::from_defined_mounts(mount, dataset_info);
+                            res = Self::from_defined_mounts(mount, dataset_info, opt_privileged_helper);
                         }
 
                         res
@@ -97,7 +143,11 @@ impl MapOfSnaps {
             .collect();
 
         if map_of_snaps.is_empty() {
-            Err(HttmError::new("httm could not find any valid snapshots on the system.").into())
+            Err(
+                HttmError::new("httm could not find any valid snapshots on the system.")
+                    .with_code("no_snapshots")
+                    .into(),
+            )
         } else {
             Ok(map_of_snaps.into())
         }
@@ -110,26 +160,57 @@ impl MapOfSnaps {
         base_subvol: &Path,
         map_of_datasets: &HashMap<PathBuf, DatasetMetadata>,
         opt_debug: bool,
+        opt_privileged_helper: Option<&PrivilegedHelper>,
     ) -> Vec<PathBuf> {
         const BTRFS_COMMAND_REQUIRES_ROOT: &str =
-            "btrfs mounts detected.  User must have super user permissions to determine the location of btrfs snapshots";
-
-        if let Err(_err) = user_has_effective_root(&BTRFS_COMMAND_REQUIRES_ROOT) {
-            static USER_HAS_ROOT_WARNING: Once = Once::new();
+            "btrfs mounts detected.  User must have super user permissions, or specify a privileged helper, to determine the location of btrfs snapshots";
+
+        let opt_delegate = match user_has_effective_root(BTRFS_COMMAND_REQUIRES_ROOT) {
+            Ok(_) => None,
+            Err(_err) if opt_privileged_helper.is_some() => opt_privileged_helper,
+            Err(_err) => {
+                static USER_HAS_ROOT_WARNING: Once = Once::new();
+
+                USER_HAS_ROOT_WARNING.call_once(|| {
+                    Diagnostic::warn("btrfs_requires_root", BTRFS_COMMAND_REQUIRES_ROOT).print();
+                });
+                return Vec::new();
+            }
+        };
 
-            USER_HAS_ROOT_WARNING.call_once(|| {
-                eprintln!("WARN: {}", BTRFS_COMMAND_REQUIRES_ROOT);
-            });
-            return Vec::new();
+        if let Some(privileged_helper) = opt_delegate {
+            let arg_path = base_mount.to_string_lossy();
+            let args = ["btrfs", "subvolume", "show", &arg_path];
+
+            return match privileged_helper.output(args) {
+                Ok(command_output) => Self::parse_btrfs_cmd_output(
+                    &command_output,
+                    base_mount,
+                    base_mount_metadata,
+                    base_subvol,
+                    map_of_datasets,
+                    opt_debug,
+                ),
+                Err(err) => {
+                    static PRIVILEGED_HELPER_WARNING: Once = Once::new();
+
+                    PRIVILEGED_HELPER_WARNING.call_once(|| {
+                        Diagnostic::warn("privileged_helper_failed", err.to_string()).print();
+                    });
+                    Vec::new()
+                }
+            };
         }
 
         let Ok(btrfs_command) = which("btrfs") else {
             static BTRFS_COMMAND_AVAILABLE_WARNING: Once = Once::new();
 
             BTRFS_COMMAND_AVAILABLE_WARNING.call_once(|| {
-                eprintln!(
-                    "WARN: 'btrfs' command not found. Make sure the command 'btrfs' is in your path.",
-                );
+                Diagnostic::warn(
+                    "command_not_found",
+                    "'btrfs' command not found. Make sure the command 'btrfs' is in your path.",
+                )
+                .print();
             });
 
             return Vec::new();
@@ -153,11 +234,33 @@ impl MapOfSnaps {
             static COULD_NOT_OBTAIN_BTRFS_COMMAND_OUTPUT: Once = Once::new();
 
             COULD_NOT_OBTAIN_BTRFS_COMMAND_OUTPUT.call_once(|| {
-                eprintln!("WARN: Could not obtain btrfs command output.",);
+                Diagnostic::warn(
+                    "btrfs_command_failed",
+                    "Could not obtain btrfs command output.",
+                )
+                .print();
             });
             return Vec::new();
         };
 
+        Self::parse_btrfs_cmd_output(
+            &command_output,
+            base_mount,
+            base_mount_metadata,
+            base_subvol,
+            map_of_datasets,
+            opt_debug,
+        )
+    }
+
+    fn parse_btrfs_cmd_output(
+        command_output: &str,
+        base_mount: &Path,
+        base_mount_metadata: &DatasetMetadata,
+        base_subvol: &Path,
+        map_of_datasets: &HashMap<PathBuf, DatasetMetadata>,
+        opt_debug: bool,
+    ) -> Vec<PathBuf> {
         match command_output
             .split_once("Snapshot(s):\n")
             .map(|(_first, last)| match last.rsplit_once("Quota group:") {
@@ -256,10 +359,16 @@ impl MapOfSnaps {
                 if snap_mount.exists() {
                     Some(snap_mount)
                 } else {
-                    eprintln!(
-                        "WARN: Snapshot mount requested does not exist or perhaps is not mounted: {:?}",
-                        snap_relative
-                    );
+                    Diagnostic::warn(
+                        "snap_mount_not_found",
+                        format!(
+                            "Snapshot mount requested does not exist or perhaps is not mounted: {:?}",
+                            snap_relative
+                        ),
+                    )
+                    .with_path(&snap_mount)
+                    .with_dataset(base_mount)
+                    .print();
                     None
                 }
             }
@@ -292,10 +401,16 @@ impl MapOfSnaps {
                 if snap_mount.exists() {
                     Some(snap_mount)
                 } else {
-                    eprintln!(
-                        "WARN: Snapshot mount requested does not exist or perhaps is not mounted: {:?}",
-                        snap_relative
-                    );
+                    Diagnostic::warn(
+                        "snap_mount_not_found",
+                        format!(
+                            "Snapshot mount requested does not exist or perhaps is not mounted: {:?}",
+                            snap_relative
+                        ),
+                    )
+                    .with_path(&snap_mount)
+                    .with_dataset(base_mount)
+                    .print();
                     None
                 }
             }
@@ -305,6 +420,7 @@ impl MapOfSnaps {
     fn from_defined_mounts(
         mount_point_path: &Path,
         dataset_metadata: &DatasetMetadata,
+        opt_privileged_helper: Option<&PrivilegedHelper>,
     ) -> Vec<PathBuf> {
         fn inner(
             mount_point_path: &Path,
@@ -315,7 +431,8 @@ impl MapOfSnaps {
                     read_dir(mount_point_path.join(BTRFS_SNAPPER_HIDDEN_DIRECTORY))?
                         .flatten()
                         .par_bridge()
-                        .map(|entry| entry.path().join(BTRFS_SNAPPER_SUFFIX))
+                        .filter_map(|entry| MapOfSnaps::entry_path(&entry))
+                        .map(|path| path.join(BTRFS_SNAPPER_SUFFIX))
                         .collect()
                 }
                 FilesystemType::Restic(None) => {
@@ -326,12 +443,18 @@ impl MapOfSnaps {
                     .flat_map(|repo| read_dir(repo.join(RESTIC_SNAPSHOT_DIRECTORY)))
                     .flatten_iter()
                     .flatten()
-                    .map(|dir_entry| dir_entry.path())
+                    .filter_map(|dir_entry| MapOfSnaps::entry_path(&dir_entry))
                     .collect(),
                 FilesystemType::Zfs => read_dir(mount_point_path.join(ZFS_SNAPSHOT_DIRECTORY))?
                     .flatten()
                     .par_bridge()
-                    .map(|entry| entry.path())
+                    .filter_map(|entry| MapOfSnaps::entry_path(&entry))
+                    .collect(),
+                FilesystemType::ShadowCopy => read_dir(mount_point_path)?
+                    .flatten()
+                    .par_bridge()
+                    .filter(|entry| parse_gmt_token(&entry.file_name().to_string_lossy()).is_some())
+                    .filter_map(|entry| MapOfSnaps::entry_path(&entry))
                     .collect(),
                 FilesystemType::Apfs => {
                     let mut res: Vec<PathBuf> = Vec::new();
@@ -379,12 +502,146 @@ impl MapOfSnaps {
                 }
             };
 
-            Ok(snaps)
+            Ok(MapOfSnaps::dedup(snaps))
         }
 
         match inner(mount_point_path, dataset_metadata) {
             Ok(res) => res,
-            Err(_err) => Vec::new(),
+            // snapper's hidden directory is, by default, only readable by root
+            Err(err) if err.kind() == std::io::ErrorKind::PermissionDenied => {
+                match (&dataset_metadata.fs_type, opt_privileged_helper) {
+                    (FilesystemType::Btrfs(_), Some(privileged_helper)) => privileged_helper
+                        .read_dir(&mount_point_path.join(BTRFS_SNAPPER_HIDDEN_DIRECTORY))
+                        .map(|entries| {
+                            entries
+                                .into_iter()
+                                .map(|entry| entry.join(BTRFS_SNAPPER_SUFFIX))
+                                .collect()
+                        })
+                        .unwrap_or_default(),
+                    _ => Self::unreadable_snap_dir(mount_point_path, dataset_metadata, &err),
+                }
+            }
+            Err(err) => Self::unreadable_snap_dir(mount_point_path, dataset_metadata, &err),
         }
     }
+
+    // a snapshot root may be a symlink to another snapshot, like restic's "latest", or a backup
+    // server's "latest -> 2024-05-01", and is then known by its target, so its versions are not
+    // counted twice, and the target's name and date are used.  the entry's file type comes from
+    // the directory itself, so no snapshot is mounted merely to check whether it is a symlink
+    fn entry_path(entry: &DirEntry) -> Option<PathBuf> {
+        const MAX_LINKS: usize = 8;
+
+        let mut path = entry.path();
+
+        if !entry
+            .file_type()
+            .is_ok_and(|file_type| file_type.is_symlink())
+        {
+            return Some(path);
+        }
+
+        // only the links themselves are resolved, and not the ancestors of the snapshot directory,
+        // so a snapshot remains within the same directory as its siblings
+        for _ in 0..MAX_LINKS {
+            let Ok(target) = std::fs::read_link(&path) else {
+                break;
+            };
+
+            path = path.parent()?.join(target);
+        }
+
+        if path
+            .components()
+            .any(|component| matches!(component, Component::ParentDir | Component::CurDir))
+        {
+            return path.canonicalize().ok();
+        }
+
+        // a dangling link is no snapshot at all
+        path.exists().then_some(path)
+    }
+
+    fn dedup(snaps: Vec<PathBuf>) -> Vec<PathBuf> {
+        let mut seen: HashSet<PathBuf> = HashSet::with_capacity(snaps.len());
+
+        snaps
+            .into_iter()
+            .filter(|snap| seen.insert(snap.clone()))
+            .collect()
+    }
+
+    // a ZFS dataset whose snapshot directory cannot be read would otherwise simply appear to have no snapshots,
+    // so, here, we ask the dataset's snapdir property why, and say so, once per dataset
+    fn unreadable_snap_dir(
+        mount_point_path: &Path,
+        dataset_metadata: &DatasetMetadata,
+        err: &std::io::Error,
+    ) -> Vec<PathBuf> {
+        if !matches!(dataset_metadata.fs_type, FilesystemType::Zfs) {
+            return Vec::new();
+        }
+
+        // the snapdir property of a dataset mounted over the network may only be queried upon the server
+        let opt_snapdir = match dataset_metadata.mount_type {
+            MountType::Local => Self::zfs_snapdir_property(&dataset_metadata.source),
+            MountType::Network => None,
+        };
+
+        let diagnostic = match opt_snapdir.as_deref() {
+            Some("disabled") => Diagnostic::warn(
+                "snapdir_disabled",
+                format!(
+                    "The ZFS snapdir property of dataset {:?} is \"disabled\", so its snapshots are not accessible, and will not be searched.  \
+                    Perhaps set the property to \"hidden\" (\"zfs set snapdir=hidden {}\").",
+                    dataset_metadata.source,
+                    dataset_metadata.source.to_string_lossy()
+                ),
+            ),
+            Some(snapdir) => Diagnostic::warn(
+                "snapdir_unavailable",
+                format!(
+                    "The ZFS snapdir property of dataset {:?} is \"{snapdir}\", but its snapshot directory could not be read, so its snapshots will not be searched.  \
+                    Perhaps the dataset is not mounted at its mountpoint, or snapshots may not be automounted here.  \
+                    \nDetails: {err}",
+                    dataset_metadata.source
+                ),
+            ),
+            None => Diagnostic::warn(
+                "snapdir_unavailable",
+                format!(
+                    "The snapshot directory of ZFS dataset {:?} could not be read, and its snapdir property could not be determined, so its snapshots will not be searched.  \
+                    \nDetails: {err}",
+                    dataset_metadata.source
+                ),
+            ),
+        };
+
+        diagnostic
+            .with_path(&mount_point_path.join(ZFS_SNAPSHOT_DIRECTORY))
+            .with_dataset(mount_point_path)
+            .print();
+
+        Vec::new()
+    }
+
+    fn zfs_snapdir_property(dataset: &Path) -> Option<String> {
+        let zfs_command = which("zfs").ok()?;
+
+        let process_output = ExecProcess::new(zfs_command)
+            .args(["get", "-H", "-o", "value", "snapdir"])
+            .arg(dataset)
+            .output()
+            .ok()?;
+
+        if !process_output.status.success() {
+            return None;
+        }
+
+        std::str::from_utf8(&process_output.stdout)
+            .ok()
+            .map(|stdout| stdout.trim().to_owned())
+            .filter(|snapdir| !snapdir.is_empty())
+    }
 }
@@ -0,0 +1,277 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+use crate::library::results::{HttmError, HttmResult};
+use crate::parse::mounts::{MapOfDatasets, MountType};
+use std::path::{Path, PathBuf};
+
+// a Windows path, as pasted from Explorer, or from documentation, as either a UNC path, like "\\server\share\dir\file",
+// or a drive letter path, like "Z:\dir\file"
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum WindowsPath {
+    Unc(Vec<String>),
+    Drive(char, Vec<String>),
+}
+
+impl WindowsPath {
+    fn new(input: &str) -> Option<Self> {
+        // the long form of a UNC path, "\\?\UNC\server\share", is the same path
+        let opt_unc = input
+            .strip_prefix(r"\\?\UNC\")
+            .or_else(|| input.strip_prefix(r"\\"));
+
+        if let Some(rest) = opt_unc {
+            return Some(WindowsPath::Unc(Self::components(rest)));
+        }
+
+        let rest = input.strip_prefix(r"\\?\").unwrap_or(input);
+        let mut chars = rest.chars();
+
+        match (chars.next(), chars.next(), chars.next()) {
+            (Some(letter), Some(':'), Some('\\' | '/')) if letter.is_ascii_alphabetic() => Some(
+                WindowsPath::Drive(letter.to_ascii_uppercase(), Self::components(&rest[3..])),
+            ),
+            _ => None,
+        }
+    }
+
+    fn path_components(&self) -> &[String] {
+        match self {
+            WindowsPath::Unc(components) | WindowsPath::Drive(_, components) => components,
+        }
+    }
+
+    fn components(input: &str) -> Vec<String> {
+        input
+            .split(['\\', '/'])
+            .filter(|component| !component.is_empty() && *component != ".")
+            .map(str::to_owned)
+            .collect()
+    }
+}
+
+// a share, as its components, "server", "share", and any subdirectory of the share which was mounted,
+// all lowercase, because Windows compares such names without regard to case
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct UncShare {
+    components: Vec<String>,
+}
+
+impl UncShare {
+    // mount tables record an SMB share as "//server/share", or, on Mac and BSD, as "//user@server/share",
+    // or "//domain;user@server/share", and some record it with backslashes, as in the "unc=" mount option
+    fn new(source: &str) -> Option<Self> {
+        let rest = source
+            .strip_prefix("//")
+            .or_else(|| source.strip_prefix(r"\\"))?;
+
+        let (server, share) = rest.split_once(['/', '\\'])?;
+
+        let server = match server.rsplit_once('@') {
+            Some((_user, server)) => server,
+            None => server,
+        };
+
+        let components: Vec<String> = std::iter::once(server.to_owned())
+            .chain(WindowsPath::components(share))
+            .map(|component| component.to_lowercase())
+            .collect();
+
+        // at least a server and a share
+        if components.len() < 2 {
+            return None;
+        }
+
+        Some(Self { components })
+    }
+
+    // the number of components of the UNC path matched by this share, if the share contains the path
+    fn matches(&self, unc_components: &[String]) -> Option<usize> {
+        if unc_components.len() < self.components.len() {
+            return None;
+        }
+
+        self.components
+            .iter()
+            .zip(unc_components.iter())
+            .all(|(share, unc)| share.as_str() == unc.to_lowercase())
+            .then_some(self.components.len())
+    }
+}
+
+// maps Windows paths onto the local mounts of the SMB shares they name, so that a path pasted from
+// Windows may be given to httm, as is, as an input path, or on stdin
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MapOfUncShares {
+    // (share, local mount)
+    shares: Vec<(UncShare, PathBuf)>,
+    // (drive letter, share or local directory), from HTTM_DRIVE_MAP
+    drives: Vec<(char, DriveTarget)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum DriveTarget {
+    Share(Vec<String>),
+    Local(PathBuf),
+}
+
+impl MapOfUncShares {
+    pub fn new(map_of_datasets: &MapOfDatasets) -> HttmResult<Self> {
+        let shares = map_of_datasets
+            .iter()
+            .filter(|(_mount, dataset_info)| matches!(dataset_info.mount_type, MountType::Network))
+            .filter_map(|(mount, dataset_info)| {
+                let source = dataset_info.source.to_string_lossy();
+
+                UncShare::new(&source).map(|share| (share, mount.clone()))
+            })
+            .collect();
+
+        let drives = match std::env::var("HTTM_DRIVE_MAP") {
+            Ok(value) => Self::drives(&value)?,
+            Err(_) => Vec::new(),
+        };
+
+        Ok(Self { shares, drives })
+    }
+
+    // HTTM_DRIVE_MAP is a comma separated list of "LETTER:=TARGET", where the target is either the
+    // UNC path of a share, or a local directory, like "Z:=\\nas\media,H:=/home/alice"
+    fn drives(value: &str) -> HttmResult<Vec<(char, DriveTarget)>> {
+        value
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(|item| {
+                let Some((letter, target)) = item.split_once(":=") else {
+                    let msg = format!(
+                        "HTTM_DRIVE_MAP entry \"{item}\" is not of the form \"LETTER:=TARGET\""
+                    );
+                    return Err(HttmError::new(&msg).into());
+                };
+
+                let mut chars = letter.trim().chars();
+
+                let letter = match (chars.next(), chars.next()) {
+                    (Some(letter), None) if letter.is_ascii_alphabetic() => {
+                        letter.to_ascii_uppercase()
+                    }
+                    _ => {
+                        let msg = format!("HTTM_DRIVE_MAP entry \"{item}\" does not name a drive letter");
+                        return Err(HttmError::new(&msg).into());
+                    }
+                };
+
+                let target = target.trim();
+
+                let target = match WindowsPath::new(target) {
+                    Some(WindowsPath::Unc(components)) if components.len() >= 2 => {
+                        DriveTarget::Share(components)
+                    }
+                    _ if target.starts_with('/') => DriveTarget::Local(PathBuf::from(target)),
+                    _ => {
+                        let msg = format!(
+                            "HTTM_DRIVE_MAP entry \"{item}\" must map to a UNC share, like \"\\\\server\\share\", or to an absolute local directory"
+                        );
+                        return Err(HttmError::new(&msg).into());
+                    }
+                };
+
+                Ok((letter, target))
+            })
+            .collect()
+    }
+
+    // returns the path unchanged, unless it is a Windows path, and errors if a Windows path names no local mount
+    pub fn resolve(&self, path: &Path) -> HttmResult<PathBuf> {
+        let Some(windows_path) = path.to_str().and_then(WindowsPath::new) else {
+            return Ok(path.to_path_buf());
+        };
+
+        // a relative POSIX path, like "c:/foo", may only look like a Windows path
+        if path.symlink_metadata().is_ok() {
+            return Ok(path.to_path_buf());
+        }
+
+        // a Windows path is joined, component by component, to a local mount, and so must not escape it
+        if windows_path
+            .path_components()
+            .iter()
+            .any(|component| component == "..")
+        {
+            let msg = format!(
+                "Windows path \"{}\" was given, but httm does not resolve Windows paths which contain a \"..\" component.",
+                path.display()
+            );
+            return Err(HttmError::new(&msg)
+                .with_code("parent_component")
+                .with_path(path)
+                .into());
+        }
+
+        let res = match &windows_path {
+            WindowsPath::Unc(components) => self.resolve_unc(components),
+            WindowsPath::Drive(letter, components) => {
+                match self.drives.iter().find(|(drive, _target)| drive == letter) {
+                    Some((_drive, DriveTarget::Local(local_dir))) => {
+                        Some(Self::join(local_dir, components))
+                    }
+                    Some((_drive, DriveTarget::Share(share))) => {
+                        let unc: Vec<String> =
+                            share.iter().chain(components.iter()).cloned().collect();
+
+                        self.resolve_unc(&unc)
+                    }
+                    None => {
+                        let msg = format!(
+                            "Windows drive letter path \"{}\" was given, but drive {letter}: is not mapped to a local directory, or to an SMB share. \
+                            Set HTTM_DRIVE_MAP, like \"{letter}:=\\\\server\\share\".",
+                            path.display()
+                        );
+                        return Err(HttmError::new(&msg)
+                            .with_code("unmapped_drive_letter")
+                            .into());
+                    }
+                }
+            }
+        };
+
+        res.ok_or_else(|| {
+            let msg = format!(
+                "Windows path \"{}\" was given, but httm could not find a local mount of its SMB share.",
+                path.display()
+            );
+            HttmError::new(&msg)
+                .with_code("unc_share_not_mounted")
+                .into()
+        })
+    }
+
+    fn resolve_unc(&self, components: &[String]) -> Option<PathBuf> {
+        // the share which matches the most components wins, in case a subdirectory of a share is also mounted
+        self.shares
+            .iter()
+            .filter_map(|(share, mount)| share.matches(components).map(|len| (len, mount)))
+            .max_by_key(|(len, _mount)| *len)
+            .map(|(len, mount)| Self::join(mount, &components[len..]))
+    }
+
+    fn join(base: &Path, components: &[String]) -> PathBuf {
+        components
+            .iter()
+            .fold(base.to_path_buf(), |acc, component| acc.join(component))
+    }
+}
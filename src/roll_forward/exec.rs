@@ -23,6 +23,7 @@ use crate::library::file_ops::Remove;
 use crate::library::results::{HttmError, HttmResult};
 use crate::library::snap_guard::{PrecautionarySnapType, SnapGuard};
 use crate::library::utility::is_metadata_same;
+use crate::library::utility::print_output_buf;
 use crate::library::utility::user_has_effective_root;
 use crate::roll_forward::preserve_hard_links::PreserveHardLinks;
 use crate::roll_forward::preserve_hard_links::SpawnPreserveLinks;
@@ -88,7 +89,7 @@ impl RollForward {
 
         match self.roll_forward() {
             Ok(_) => {
-                println!("httm roll forward completed successfully.");
+                print_output_buf("httm roll forward completed successfully.\n")?;
             }
             Err(err) => {
                 let msg = format!(
@@ -98,9 +99,8 @@ impl RollForward {
                 );
                 eprintln!("{}", msg);
 
-                snap_guard
-                    .rollback()
-                    .map(|_| println!("Rollback succeeded."))?;
+                snap_guard.rollback()?;
+                print_output_buf("Rollback succeeded.\n")?;
 
                 std::process::exit(1)
             }